@@ -1,4 +1,3 @@
-use std::collections::HashMap as StdHashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
@@ -14,24 +13,8 @@ use grpc_server::server::{ServerConfig, start_server as grpc_start_server};
 use tokio::runtime::Runtime;
 use tonic::transport::Channel;
 
-mod proto {
-    pub mod feast {
-        pub mod serving {
-            #![allow(dead_code)]
-            tonic::include_proto!("feast.serving");
-        }
-        pub mod types {
-            #![allow(dead_code)]
-            tonic::include_proto!("feast.types");
-        }
-    }
-}
-
-use proto::feast::serving::get_online_features_request::Kind;
-use proto::feast::serving::serving_service_client::ServingServiceClient;
-use proto::feast::serving::{FeatureList, GetOnlineFeaturesRequest};
-use proto::feast::types::value::Val;
-use proto::feast::types::{RepeatedValue, Value};
+use cli::bench::{RequestSpec, to_grpc_request};
+use cli::proto::feast::serving::serving_service_client::ServingServiceClient;
 
 fn workspace_path(relative: &str) -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -87,36 +70,6 @@ async fn connect_with_retry(endpoint: &str) -> ServingServiceClient<Channel> {
     );
 }
 
-fn build_request() -> GetOnlineFeaturesRequest {
-    let features = vec![
-        "driver_hourly_stats:conv_rate",
-        "driver_hourly_stats:acc_rate",
-        "driver_hourly_stats:avg_daily_trips",
-        "driver_hourly_stats_fresh:avg_daily_trips",
-    ]
-    .into_iter()
-    .map(str::to_string)
-    .collect();
-
-    let entity_values = vec![1005_i64, 1002, 1003]
-        .into_iter()
-        .map(|value| Value {
-            val: Some(Val::Int64Val(value)),
-        })
-        .collect();
-
-    let entities = StdHashMap::from([(
-        "driver_id".to_string(),
-        RepeatedValue { val: entity_values },
-    )]);
-    GetOnlineFeaturesRequest {
-        kind: Some(Kind::Features(FeatureList { val: features })),
-        entities,
-        full_feature_names: true,
-        request_context: StdHashMap::default(),
-    }
-}
-
 fn start_grpc_server(
     runtime: &Runtime,
     registry: Arc<dyn FeatureRegistryService>,
@@ -130,6 +83,7 @@ fn start_grpc_server(
         tls_enabled: false,
         tls_cert_path: None,
         tls_key_path: None,
+        ..Default::default()
     };
 
     runtime.spawn(async move { grpc_start_server(config, feature_store).await })
@@ -144,7 +98,7 @@ fn bench_grpc_server(c: &mut Criterion) {
 
     let endpoint = format!("http://127.0.0.1:{port}");
     let client = runtime.block_on(async { connect_with_retry(&endpoint).await });
-    let request = build_request();
+    let request = to_grpc_request(&RequestSpec::default().sample_request());
 
     c.bench_function("grpc_server_get_online_features", |b| {
         b.to_async(&runtime).iter(|| {
@@ -161,7 +115,7 @@ fn bench_grpc_server(c: &mut Criterion) {
     });
 
     server_task.abort();
-    match runtime.block_on(async { server_task.await }) {
+    match runtime.block_on(server_task) {
         Ok(_) => {}
         Err(err) => {
             eprintln!("gRPC server task failed: {:?}", err);
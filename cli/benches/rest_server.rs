@@ -3,8 +3,8 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
-use axum_server::Handle;
 use bytes::Bytes;
+use cli::bench::RequestSpec;
 use criterion::{Criterion, criterion_group, criterion_main};
 use feast_server_core::feature_store::FeatureStore;
 use feast_server_core::onlinestore::OnlineStore;
@@ -12,6 +12,7 @@ use feast_server_core::onlinestore::sqlite_onlinestore::{ConnectionOptions, Sqli
 use feast_server_core::registry::FeatureRegistryService;
 use feast_server_core::registry::file_registry::FileFeatureRegistry;
 use reqwest::Client;
+use rest_server::server::ShutdownHandle;
 use tokio::runtime::Runtime;
 
 fn workspace_path(relative: &str) -> PathBuf {
@@ -53,10 +54,10 @@ fn find_free_port() -> u16 {
 async fn wait_for_server(client: &Client, url: &str) {
     const MAX_ATTEMPTS: usize = 50;
     for _ in 0..MAX_ATTEMPTS {
-        if let Ok(response) = client.get(url).send().await {
-            if response.status().is_success() {
-                return;
-            }
+        if let Ok(response) = client.get(url).send().await
+            && response.status().is_success()
+        {
+            return;
         }
         tokio::time::sleep(Duration::from_millis(100)).await;
     }
@@ -68,8 +69,8 @@ fn start_rest_server(
     registry: Arc<dyn FeatureRegistryService>,
     online_store: Arc<dyn OnlineStore>,
     port: u16,
-) -> (Handle, tokio::task::JoinHandle<Result<()>>) {
-    let handle = Handle::new();
+) -> (ShutdownHandle, tokio::task::JoinHandle<Result<()>>) {
+    let handle = ShutdownHandle::new();
     let server_handle = handle.clone();
     let feature_store = FeatureStore::new(registry, online_store);
     let server_config = rest_server::server::ServerConfig {
@@ -78,6 +79,7 @@ fn start_rest_server(
         tls_enabled: false,
         tls_cert_path: None,
         tls_key_path: None,
+        ..Default::default()
     };
 
     let join = runtime.spawn(async move {
@@ -98,9 +100,8 @@ fn bench_rest_server(c: &mut Criterion) {
     let health_url = format!("http://127.0.0.1:{port}/health");
     runtime.block_on(wait_for_server(&client, &health_url));
 
-    let request_path = workspace_path("cli/benches/body.json");
-    let request_body =
-        std::fs::read_to_string(&request_path).expect("failed to read request payload");
+    let request_body = serde_json::to_vec(&RequestSpec::default().sample_request())
+        .expect("failed to serialize request payload");
     let request_body = Bytes::from(request_body);
     let request_url = format!("http://127.0.0.1:{port}/get-online-features");
 
@@ -130,7 +131,7 @@ fn bench_rest_server(c: &mut Criterion) {
 
     shutdown_handle.shutdown();
     runtime
-        .block_on(async { server_task.await })
+        .block_on(server_task)
         .expect("server task panicked")
         .expect("server returned error");
 }
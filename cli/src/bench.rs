@@ -0,0 +1,317 @@
+//! Synthetic GetOnlineFeatures load against a running feature server.
+
+use crate::proto::feast::serving::get_online_features_request::Kind;
+use crate::proto::feast::serving::serving_service_client::ServingServiceClient;
+use crate::proto::feast::serving::{
+    FeatureList, GetOnlineFeaturesRequest as GrpcGetOnlineFeaturesRequest,
+};
+use crate::proto::feast::types::value::Val;
+use crate::proto::feast::types::{RepeatedValue, Value};
+use anyhow::{Result, anyhow};
+use feast_server_core::model::{EntityIdValue, GetOnlineFeaturesRequest};
+use std::collections::HashMap as StdHashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// Features of the test repository read by the benches when none are given.
+pub const DEFAULT_FEATURES: [&str; 4] = [
+    "driver_hourly_stats:conv_rate",
+    "driver_hourly_stats:acc_rate",
+    "driver_hourly_stats:avg_daily_trips",
+    "driver_hourly_stats_fresh:avg_daily_trips",
+];
+
+/// Shape of generated requests. Entity ids are drawn from `entity_ids`.
+#[derive(Debug, Clone)]
+pub struct RequestSpec {
+    pub features: Vec<String>,
+    pub feature_service: Option<String>,
+    pub entity_name: String,
+    pub entity_ids: RangeInclusive<i64>,
+    pub entities_per_request: usize,
+    pub full_feature_names: bool,
+}
+
+impl Default for RequestSpec {
+    fn default() -> Self {
+        Self {
+            features: DEFAULT_FEATURES.iter().map(|f| f.to_string()).collect(),
+            feature_service: None,
+            entity_name: "driver_id".to_string(),
+            entity_ids: 1001..=1005,
+            entities_per_request: 3,
+            full_feature_names: true,
+        }
+    }
+}
+
+impl RequestSpec {
+    /// Request for the given entity ids.
+    pub fn request(&self, entity_ids: Vec<i64>) -> GetOnlineFeaturesRequest {
+        let (feature_service, features) = match &self.feature_service {
+            Some(name) => (Some(name.clone()), None),
+            None => (None, Some(self.features.clone())),
+        };
+        GetOnlineFeaturesRequest {
+            entities: [(
+                self.entity_name.clone(),
+                entity_ids.into_iter().map(EntityIdValue::Int).collect(),
+            )]
+            .into_iter()
+            .collect(),
+            feature_service,
+            features,
+            full_feature_names: Some(self.full_feature_names),
+            ..Default::default()
+        }
+    }
+
+    /// Deterministic request for the first ids of the range, cycling through the range when
+    /// more entities are requested than it holds.
+    pub fn sample_request(&self) -> GetOnlineFeaturesRequest {
+        let ids = self
+            .entity_ids
+            .clone()
+            .cycle()
+            .take(self.entities_per_request);
+        self.request(ids.collect())
+    }
+}
+
+/// Convert a request to the gRPC serving API.
+pub fn to_grpc_request(request: &GetOnlineFeaturesRequest) -> GrpcGetOnlineFeaturesRequest {
+    let kind = match (&request.feature_service, &request.features) {
+        (Some(name), _) => Some(Kind::FeatureService(name.clone())),
+        (None, Some(features)) => Some(Kind::Features(FeatureList {
+            val: features.clone(),
+        })),
+        (None, None) => None,
+    };
+    let entities = request
+        .entities
+        .iter()
+        .map(|(name, values)| {
+            let val = values
+                .iter()
+                .map(|value| Value {
+                    val: Some(match value {
+                        EntityIdValue::Int(i) => Val::Int64Val(*i),
+                        EntityIdValue::String(s) => Val::StringVal(s.clone()),
+                    }),
+                })
+                .collect();
+            (name.clone(), RepeatedValue { val })
+        })
+        .collect();
+    GrpcGetOnlineFeaturesRequest {
+        kind,
+        entities,
+        full_feature_names: request.full_feature_names.unwrap_or(false),
+        request_context: StdHashMap::default(),
+    }
+}
+
+/// Pseudo random entity id source, xorshift64 seeded per worker.
+struct EntitySampler(u64);
+
+impl EntitySampler {
+    fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next_ids(&mut self, spec: &RequestSpec) -> Vec<i64> {
+        let start = *spec.entity_ids.start();
+        let span = spec.entity_ids.end().abs_diff(start) + 1;
+        (0..spec.entities_per_request)
+            .map(|_| {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                start.wrapping_add((self.0 % span) as i64)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchTarget {
+    Http,
+    Grpc,
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Base URL of the server, e.g. `http://127.0.0.1:6566`.
+    pub url: String,
+    pub target: BenchTarget,
+    pub spec: RequestSpec,
+    /// Number of requests in flight at any time.
+    pub concurrency: usize,
+    pub duration: Duration,
+}
+
+/// Latencies of successful requests and the error count of a run.
+#[derive(Debug, Default)]
+pub struct BenchReport {
+    latencies: Vec<Duration>,
+    errors: u64,
+    elapsed: Duration,
+}
+
+impl BenchReport {
+    fn merge(&mut self, other: BenchReport) {
+        self.latencies.extend(other.latencies);
+        self.errors += other.errors;
+    }
+
+    /// Latency below which `percentile` percent of successful requests completed.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile / 100.0 * (self.latencies.len() - 1) as f64).round() as usize;
+        self.latencies[rank.min(self.latencies.len() - 1)]
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            0.0
+        } else {
+            self.latencies.len() as f64 / seconds
+        }
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "requests: {} ok, {} failed in {:.2?}",
+            self.latencies.len(),
+            self.errors,
+            self.elapsed
+        )?;
+        writeln!(f, "throughput: {:.1} req/s", self.requests_per_second())?;
+        write!(
+            f,
+            "latency: p50 {:.2?}, p90 {:.2?}, p99 {:.2?}, p99.9 {:.2?}, max {:.2?}",
+            self.percentile(50.0),
+            self.percentile(90.0),
+            self.percentile(99.0),
+            self.percentile(99.9),
+            self.latencies.last().copied().unwrap_or_default()
+        )
+    }
+}
+
+/// Send requests from `options.concurrency` workers until `options.duration` elapsed.
+pub async fn run(options: BenchOptions) -> Result<BenchReport> {
+    if options.concurrency == 0 || options.spec.entities_per_request == 0 {
+        return Err(anyhow!(
+            "Concurrency and entities per request must be greater than 0"
+        ));
+    }
+    let url = options.url.trim_end_matches('/').to_string();
+    let grpc_client = match options.target {
+        BenchTarget::Grpc => Some(ServingServiceClient::connect(url.clone()).await?),
+        BenchTarget::Http => None,
+    };
+    let http_client = reqwest::Client::new();
+    let request_url = format!("{}/get-online-features", url);
+
+    let start = Instant::now();
+    let deadline = start + options.duration;
+    let mut workers = JoinSet::new();
+    for worker in 0..options.concurrency {
+        let spec = options.spec.clone();
+        let http_client = http_client.clone();
+        let request_url = request_url.clone();
+        let mut grpc_client = grpc_client.clone();
+        workers.spawn(async move {
+            let mut sampler = EntitySampler::new(worker as u64 + 1);
+            let mut report = BenchReport::default();
+            while Instant::now() < deadline {
+                let request = spec.request(sampler.next_ids(&spec));
+                let sent = Instant::now();
+                let result = match grpc_client.as_mut() {
+                    Some(client) => client
+                        .get_online_features(to_grpc_request(&request))
+                        .await
+                        .map(|_| ())
+                        .map_err(anyhow::Error::from),
+                    None => send_http(&http_client, &request_url, &request).await,
+                };
+                match result {
+                    Ok(()) => report.latencies.push(sent.elapsed()),
+                    Err(err) => {
+                        if report.errors == 0 {
+                            tracing::warn!("Request failed: {:#}", err);
+                        }
+                        report.errors += 1;
+                    }
+                }
+            }
+            report
+        });
+    }
+
+    let mut report = BenchReport::default();
+    while let Some(worker_report) = workers.join_next().await {
+        report.merge(worker_report?);
+    }
+    report.elapsed = start.elapsed();
+    report.latencies.sort_unstable();
+    Ok(report)
+}
+
+async fn send_http(
+    client: &reqwest::Client,
+    url: &str,
+    request: &GetOnlineFeaturesRequest,
+) -> Result<()> {
+    client
+        .post(url)
+        .json(request)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampled_ids_stay_in_range() {
+        let spec = RequestSpec {
+            entity_ids: 10..=12,
+            entities_per_request: 100,
+            ..Default::default()
+        };
+        let ids = EntitySampler::new(1).next_ids(&spec);
+        assert_eq!(ids.len(), 100);
+        assert!(ids.iter().all(|id| spec.entity_ids.contains(id)));
+        assert_eq!(
+            spec.sample_request().entities["driver_id"][..4],
+            [10, 11, 12, 10].map(EntityIdValue::Int)
+        );
+    }
+
+    #[test]
+    fn report_percentiles() {
+        let report = BenchReport {
+            latencies: (1..=100).map(Duration::from_millis).collect(),
+            errors: 0,
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(report.percentile(50.0), Duration::from_millis(51));
+        assert_eq!(report.percentile(99.0), Duration::from_millis(99));
+        assert_eq!(report.requests_per_second(), 50.0);
+    }
+}
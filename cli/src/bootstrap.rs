@@ -0,0 +1,290 @@
+//! Wiring of `feast serve` from a parsed `feature_store.yaml`, for applications embedding the
+//! servers and for tests which need the exact setup of the command line.
+//!
+//! ```no_run
+//! # async fn example(config: feast_server_core::config::RepoConfig) -> anyhow::Result<()> {
+//! use cli::bootstrap::{ServeOverrides, bootstrap};
+//! use std::sync::Arc;
+//!
+//! let overrides = ServeOverrides {
+//!     cwd: Some("/path/to/feature_repo".into()),
+//!     ..Default::default()
+//! };
+//! let (feature_store, servers) = bootstrap(config, overrides).await?;
+//! grpc_server::server::start_server(servers.grpc, Arc::new(feature_store)).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{Result, anyhow};
+use feast_server_core::bundle::open_bundle;
+use feast_server_core::config::{Provider, RepoConfig};
+use feast_server_core::feature_store::{EffectiveConfig, FeatureStore, ResponseRecording};
+use feast_server_core::onlinestore::OnlineStore;
+use feast_server_core::onlinestore::in_memory::InMemoryOnlineStore;
+use feast_server_core::preflight::{RequiredFile, run_preflight_checks};
+use feast_server_core::registry::FeatureRegistryService;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Options of `feast serve` taking precedence over `feature_store.yaml`. The defaults are
+/// those of the command line.
+#[derive(Clone)]
+pub struct ServeOverrides {
+    /// Directory relative paths of the config are resolved against, the current directory
+    /// when unset.
+    pub cwd: Option<PathBuf>,
+    /// Path of `feature_store.yaml`, reported by `/admin/config`.
+    pub config_path: Option<PathBuf>,
+    /// Arguments the server was started with, reported by `/admin/config`.
+    pub command_line: Vec<String>,
+    /// Hosts listened on, the first one being the main host.
+    pub hosts: Vec<String>,
+    pub port: u16,
+    pub dual_stack: bool,
+    pub backlog: Option<u32>,
+    pub unix_socket_path: Option<String>,
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    /// Build the feature store without checking TLS files, registry and online store first.
+    pub skip_checks: bool,
+    /// Serve the registry and online data of a bundle written by `export-bundle`.
+    pub bundle: Option<PathBuf>,
+    pub response_recording: Option<ResponseRecording>,
+    /// Use this registry instead of the one described by the config.
+    pub registry: Option<Arc<dyn FeatureRegistryService>>,
+    /// Use this online store instead of the one described by the config.
+    pub online_store: Option<Arc<dyn OnlineStore>>,
+    pub grpc_web: bool,
+    pub json_transcoding: bool,
+}
+
+impl Default for ServeOverrides {
+    fn default() -> Self {
+        Self {
+            cwd: None,
+            config_path: None,
+            command_line: Vec::new(),
+            hosts: vec!["127.0.0.1".to_string()],
+            port: 6566,
+            dual_stack: false,
+            backlog: None,
+            unix_socket_path: None,
+            tls_cert_path: None,
+            tls_key_path: None,
+            skip_checks: false,
+            bundle: None,
+            response_recording: None,
+            registry: None,
+            online_store: None,
+            grpc_web: false,
+            json_transcoding: false,
+        }
+    }
+}
+
+/// Configs of the HTTP and gRPC servers; either one is started with the feature store.
+pub struct Servers {
+    pub http: rest_server::server::ServerConfig,
+    pub grpc: grpc_server::server::ServerConfig,
+}
+
+/// Resolve the secrets of `repo_config`, build its feature store the way `feast serve` does
+/// and the configs of the servers serving it.
+pub async fn bootstrap(
+    repo_config: RepoConfig,
+    overrides: ServeOverrides,
+) -> Result<(FeatureStore, Servers)> {
+    let ServeOverrides {
+        cwd,
+        config_path,
+        command_line,
+        hosts,
+        port,
+        dual_stack,
+        backlog,
+        unix_socket_path,
+        tls_cert_path,
+        tls_key_path,
+        skip_checks,
+        bundle,
+        response_recording,
+        registry,
+        online_store,
+        grpc_web,
+        json_transcoding,
+    } = overrides;
+    if tls_key_path.is_some() != tls_cert_path.is_some() {
+        return Err(anyhow!(
+            "Both --key and --cert must be provided to enable TLS"
+        ));
+    }
+    if let Some(Provider::Unknown(other)) = &repo_config.provider {
+        tracing::warn!(
+            "Unknown provider '{}', using the {:?} provider for the registry and {:?} for the online store",
+            other,
+            repo_config.registry_provider(),
+            repo_config.online_store_provider()
+        );
+    }
+    let cwd = match cwd {
+        Some(cwd) => cwd,
+        None => std::env::current_dir()?,
+    };
+    let mut effective_config = EffectiveConfig::new(&repo_config).with_command_line(command_line);
+    if let Some(config_path) = config_path {
+        effective_config = effective_config.with_config_path(config_path.display().to_string());
+    }
+    let repo_config = feast_server_core::secrets::resolve_secrets(repo_config).await?;
+
+    let replay = matches!(response_recording, Some(ResponseRecording::Replay(_)));
+    let feature_store = if let Some(bundle) = bundle {
+        let bundle = open_bundle(&bundle).await?;
+        tracing::info!(
+            "Serving feature services [{}] of project {} from bundle created at {}",
+            bundle.feature_services.join(", "),
+            bundle.project,
+            bundle.created_at
+        );
+        FeatureStore::builder()
+            .with_repo_config(repo_config.clone())
+            .with_registry(registry.unwrap_or(bundle.registry))
+            .with_online_store(online_store.unwrap_or(bundle.online_store))
+            .build()
+            .await?
+    } else if skip_checks || replay || registry.is_some() || online_store.is_some() {
+        let mut builder = FeatureStore::builder()
+            .with_repo_config(repo_config.clone())
+            .with_cwd(&cwd);
+        if let Some(registry) = registry {
+            builder = builder.with_registry(registry);
+        }
+        match online_store {
+            Some(online_store) => builder = builder.with_online_store(online_store),
+            // Replayed responses are never read from the online store
+            None if replay => {
+                builder = builder.with_online_store(Arc::new(InMemoryOnlineStore::new()))
+            }
+            None => {}
+        }
+        builder.build().await?
+    } else {
+        let tls_files = [
+            tls_key_path
+                .as_ref()
+                .map(|key| RequiredFile::new("TLS private key", key)),
+            tls_cert_path
+                .as_ref()
+                .map(|cert| RequiredFile::new("TLS certificate", cert)),
+        ];
+        let admin_tls = repo_config
+            .runtime
+            .admin_listener
+            .as_ref()
+            .and_then(|admin| admin.tls.as_ref());
+        let admin_tls_files = admin_tls.into_iter().flat_map(|tls| {
+            [
+                Some(RequiredFile::new("admin TLS private key", &tls.key_path)),
+                Some(RequiredFile::new("admin TLS certificate", &tls.cert_path)),
+                tls.client_ca_path
+                    .as_ref()
+                    .map(|ca| RequiredFile::new("admin client CA", ca)),
+            ]
+        });
+        let tls_files = tls_files
+            .into_iter()
+            .chain(admin_tls_files)
+            .flatten()
+            .collect::<Vec<_>>();
+        run_preflight_checks(&repo_config, &cwd, &tls_files).await?
+    };
+    let feature_store = match response_recording {
+        Some(recording) => feature_store.with_response_recording(recording),
+        None => feature_store,
+    };
+    let feature_store = feature_store.with_effective_config(effective_config);
+
+    let tls_enabled = tls_key_path.is_some() && tls_cert_path.is_some();
+    let backlog = backlog.or(repo_config.runtime.backlog).unwrap_or(1024);
+    let dual_stack = dual_stack || repo_config.runtime.dual_stack;
+    let (host, additional_hosts) = match hosts.split_first() {
+        Some((host, additional_hosts)) => (host.clone(), additional_hosts.to_vec()),
+        None => ("127.0.0.1".to_string(), Vec::new()),
+    };
+    let servers = Servers {
+        http: rest_server::server::ServerConfig {
+            host: host.clone(),
+            additional_hosts: additional_hosts.clone(),
+            dual_stack,
+            port,
+            tls_enabled,
+            tls_cert_path: tls_cert_path.clone(),
+            tls_key_path: tls_key_path.clone(),
+            backlog,
+            unix_socket_path: unix_socket_path.clone(),
+            tuning: repo_config.runtime.http.clone(),
+            load_shedding: repo_config.runtime.load_shedding.clone(),
+            nan_policy: repo_config.nan_policy,
+            admin_listener: repo_config.runtime.admin_listener.clone(),
+        },
+        grpc: grpc_server::server::ServerConfig {
+            host,
+            additional_hosts,
+            dual_stack,
+            port,
+            tls_enabled,
+            tls_cert_path,
+            tls_key_path,
+            backlog,
+            unix_socket_path,
+            tuning: repo_config.runtime.grpc.clone(),
+            load_shedding: repo_config.runtime.load_shedding.clone(),
+            grpc_web,
+            json_transcoding,
+        },
+    };
+    Ok((feature_store, servers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feast_server_core::registry::FileFeatureRegistry;
+
+    #[tokio::test]
+    async fn bootstrap_with_prebuilt_components() -> Result<()> {
+        let test_data =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../feast-server-core/test_data");
+        let repo_config = RepoConfig::from_yaml_str(
+            "project: golden_hornet\nregistry: registry.pb\nonline_store:\n    type: sqlite\n    \
+             path: online_store.db\nruntime:\n    backlog: 64\n",
+        )?;
+        let registry = Arc::new(FileFeatureRegistry::from_path(
+            &test_data.join("registry.pb"),
+        )?);
+        let overrides = ServeOverrides {
+            cwd: Some(test_data),
+            hosts: vec!["0.0.0.0".to_string(), "::".to_string()],
+            registry: Some(registry),
+            online_store: Some(Arc::new(InMemoryOnlineStore::new())),
+            ..Default::default()
+        };
+
+        let (feature_store, servers) = bootstrap(repo_config, overrides).await?;
+        assert!(feature_store.effective_config().is_some());
+        assert_eq!(servers.http.host, "0.0.0.0");
+        assert_eq!(servers.grpc.additional_hosts, ["::"]);
+        assert_eq!(servers.grpc.backlog, 64);
+        assert!(!servers.http.tls_enabled);
+
+        let half_tls = ServeOverrides {
+            tls_key_path: Some("server.key".to_string()),
+            ..Default::default()
+        };
+        let repo_config = RepoConfig::from_yaml_str(
+            "project: p\nregistry: r.pb\nonline_store:\n    type: sqlite\n    path: o.db\n",
+        )?;
+        assert!(bootstrap(repo_config, half_tls).await.is_err());
+        Ok(())
+    }
+}
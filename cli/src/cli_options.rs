@@ -17,6 +17,12 @@ impl Display for ServeType {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum SchemaFormat {
+    JsonSchema,
+    Proto,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum LogLevel {
     Debug,
@@ -42,9 +48,13 @@ impl From<LogLevel> for tracing::Level {
 pub enum CliCommand {
     /// Start a feature server locally on a given port
     Serve {
-        /// Specify a host for the server
+        /// Specify a host for the server. Can be repeated to listen on several addresses
         #[arg(short = 'h', long = "host", default_value = "127.0.0.1")]
-        host: String,
+        hosts: Vec<String>,
+        /// Also listen on the IPv6 counterpart of IPv4 wildcard and loopback hosts, and the
+        /// other way around. Overrides `runtime.dual_stack` from feature_store.yaml
+        #[arg(long = "dual-stack", default_value_t = false)]
+        dual_stack: bool,
         /// Specify a port for the server
         #[arg(short = 'p', long = "port", default_value_t = 6566)]
         port: u16,
@@ -63,6 +73,130 @@ pub enum CliCommand {
         /// Enable the Metrics Server
         #[arg(short = 'm', long = "metrics", default_value_t = false)]
         metrics_enabled: bool,
+        /// Maximum number of pending connections in the listen queue.
+        /// Overrides `runtime.backlog` from feature_store.yaml, defaults to 1024
+        #[arg(long = "backlog", default_value = None)]
+        backlog: Option<u32>,
+        /// Serve on a Unix domain socket at this path instead of host and port.
+        /// Sockets passed by systemd socket activation (LISTEN_FDS) take precedence
+        #[arg(short = 'u', long = "unix-socket", default_value = None)]
+        unix_socket: Option<String>,
+        /// Skip the startup checks of TLS files, registry and online store. Failures then
+        /// surface on the first requests instead of stopping the server before it binds
+        #[arg(long = "skip-checks", default_value_t = false)]
+        skip_checks: bool,
+        /// Serve the registry and online data of a bundle written by `export-bundle` instead
+        /// of the ones in feature_store.yaml, which is then optional
+        #[arg(long = "bundle", default_value = None)]
+        bundle: Option<String>,
+        /// Write the response to every online feature request to a file of this directory,
+        /// named after a hash of the request
+        #[arg(long = "record", default_value = None, conflicts_with = "replay")]
+        record: Option<String>,
+        /// Answer online feature requests with the responses recorded by `--record` in this
+        /// directory, without connecting to the online store
+        #[arg(long = "replay", default_value = None, conflicts_with = "bundle")]
+        replay: Option<String>,
+        /// Also serve grpc-web requests on the gRPC port, over HTTP/1.1 as well
+        #[arg(long = "grpc-web", default_value_t = false)]
+        grpc_web: bool,
+        /// Also answer JSON GetOnlineFeatures requests on the gRPC port, with the request and
+        /// response bodies of the HTTP server
+        #[arg(long = "json-transcoding", default_value_t = false)]
+        json_transcoding: bool,
+    },
+    /// Write feature rows of a parquet or CSV file to the online store of a feature view
+    Load {
+        /// Parquet file, or CSV file with a `.csv` extension, with a column per join key and
+        /// feature of the view and an event timestamp column
+        path: String,
+        /// Feature view the rows are written to
+        #[arg(short = 'v', long = "feature-view")]
+        feature_view: String,
+        /// Column holding the event timestamps of the rows
+        #[arg(long = "timestamp-field", default_value = "event_timestamp")]
+        timestamp_field: String,
+        /// Column holding the creation timestamps of the rows
+        #[arg(long = "created-timestamp-column", default_value = None)]
+        created_timestamp_column: Option<String>,
+    },
+    /// Write the registry and SQLite online data of feature services to a single bundle file
+    /// which can be served with `serve --bundle`
+    ExportBundle {
+        /// Bundle file to write, replaced if it exists
+        #[arg(short = 'o', long = "output")]
+        output: String,
+        /// Feature service to include in the bundle, can be repeated
+        #[arg(short = 's', long = "feature-service", required = true)]
+        feature_services: Vec<String>,
+    },
+    /// Print the JSON Schema or `.proto` message of the response columns of a feature service,
+    /// for generating typed response structs
+    ExportSchema {
+        /// Feature service to describe
+        #[arg(short = 's', long = "feature-service")]
+        feature_service: String,
+        /// Schema format: 'json-schema' or 'proto'
+        #[arg(
+            value_enum,
+            short = 'f',
+            long = "format",
+            default_value = "json-schema"
+        )]
+        format: SchemaFormat,
+        /// Name feature columns `<view>__<feature>`, as responses to requests with
+        /// `full_feature_names`
+        #[arg(long = "full-feature-names", default_value_t = false)]
+        full_feature_names: bool,
+        /// File to write the schema to instead of standard output
+        #[arg(short = 'o', long = "output", default_value = None)]
+        output: Option<String>,
+    },
+    /// Send synthetic GetOnlineFeatures load to a running feature server and report latency
+    /// percentiles and throughput
+    Bench {
+        /// Base URL of the server
+        #[arg(long = "url", default_value = "http://127.0.0.1:6566")]
+        url: String,
+        /// Server type: 'http' or 'grpc'
+        #[arg(value_enum, short = 't', long = "type", default_value = "http")]
+        r#type: ServeType,
+        /// Feature to request as `view:feature`, can be repeated. Defaults to features of the
+        /// driver_hourly_stats example views
+        #[arg(long = "feature")]
+        features: Vec<String>,
+        /// Request a feature service instead of features
+        #[arg(long = "feature-service", default_value = None, conflicts_with = "features")]
+        feature_service: Option<String>,
+        /// Join key of the requested entities
+        #[arg(long = "entity", default_value = "driver_id")]
+        entity: String,
+        /// Smallest integer entity id to request
+        #[arg(long = "min-entity-id", default_value_t = 1001)]
+        min_entity_id: i64,
+        /// Largest integer entity id to request
+        #[arg(long = "max-entity-id", default_value_t = 1005)]
+        max_entity_id: i64,
+        /// Number of entities in every request
+        #[arg(short = 'e', long = "entities-per-request", default_value_t = 3)]
+        entities_per_request: usize,
+        /// Number of requests in flight
+        #[arg(long = "concurrency", default_value_t = 8)]
+        concurrency: usize,
+        /// Duration of the run in seconds
+        #[arg(short = 'd', long = "duration", default_value_t = 10)]
+        duration: u64,
+    },
+    /// Print the completion script of a shell to stdout
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Write man pages of the CLI and of every subcommand to a directory
+    Manpages {
+        /// Directory the pages are written to, created if missing
+        dir: String,
     },
 }
 
@@ -88,6 +222,17 @@ pub struct CliOptions {
     /// Can also be set via the FEAST_FS_YAML_FILE_PATH environment variable
     #[arg(short='f', long="feature-store-yaml", default_value = None)]
     pub feature_store_yaml: Option<String>,
+    /// Fail on feature_store.yaml keys not used by the server instead of logging warnings
+    #[arg(long = "strict-config", default_value_t = false)]
+    pub strict_config: bool,
+    /// Number of tokio worker threads. Overrides `runtime.worker_threads` from
+    /// feature_store.yaml, defaults to the number of CPU cores
+    #[arg(long = "worker-threads", default_value = None)]
+    pub worker_threads: Option<usize>,
+    /// Maximum number of tokio blocking threads. Overrides `runtime.max_blocking_threads`
+    /// from feature_store.yaml
+    #[arg(long = "max-blocking-threads", default_value = None)]
+    pub max_blocking_threads: Option<usize>,
     #[command(subcommand)]
     pub command: CliCommand,
 }
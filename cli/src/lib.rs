@@ -0,0 +1,18 @@
+//! Library part of the CLI shared with the criterion benches and with applications
+//! embedding the servers.
+
+pub mod bench;
+pub mod bootstrap;
+
+pub mod proto {
+    pub mod feast {
+        pub mod serving {
+            #![allow(dead_code)]
+            tonic::include_proto!("feast.serving");
+        }
+        pub mod types {
+            #![allow(dead_code, clippy::enum_variant_names)]
+            tonic::include_proto!("feast.types");
+        }
+    }
+}
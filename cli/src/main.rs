@@ -1,10 +1,18 @@
 use crate::cli_options::{CliCommand, CliOptions};
 use anyhow::{Result, anyhow};
-use clap::Parser;
-use feast_server_core::config::{Provider, RepoConfig};
+use clap::{CommandFactory, Parser};
+use cli::bench::{BenchOptions, BenchTarget, RequestSpec};
+use cli::bootstrap::{ServeOverrides, bootstrap};
+use feast_server_core::bundle::{bundle_repo_config, export_bundle};
+use feast_server_core::config::RepoConfig;
+use feast_server_core::feature_store::ResponseRecording;
+use feast_server_core::load::read_feature_file;
+use feast_server_core::registry::schema::ResponseSchema;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::runtime::Runtime;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
@@ -15,17 +23,31 @@ const FEATURE_REPO_DIR_ENV_VAR_NAME: &str = "FEATURE_REPO_DIR_ENV_VAR";
 const FEAST_FS_YAML_FILE_PATH_ENV_VAR: &str = "FEAST_FS_YAML_FILE_PATH";
 const DEFAULT_FEATURE_STORE_FILE_NAME: &str = "feature_store.yaml";
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
+    // SAFETY: no other thread runs yet.
+    unsafe { server_listener::take_systemd_env() };
     let cli_opts = CliOptions::parse();
     let CliOptions {
         chdir,
         help: _,
         log_level,
         feature_store_yaml,
+        strict_config,
+        worker_threads,
+        max_blocking_threads,
         command,
     } = cli_opts;
 
+    // Packaging helpers write to stdout or files and need neither logging nor a repository
+    match &command {
+        CliCommand::Completions { shell } => {
+            write_completions(*shell, &mut std::io::stdout());
+            return Ok(());
+        }
+        CliCommand::Manpages { dir } => return write_manpages(Path::new(dir)),
+        _ => {}
+    }
+
     tracing_subscriber::registry()
         .with(
             EnvFilter::builder()
@@ -35,85 +57,275 @@ async fn main() -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // The load generator talks to a running server and needs no feature repository
+    if let CliCommand::Bench { .. } = command {
+        let runtime = build_runtime(worker_threads, max_blocking_threads)?;
+        return runtime.block_on(run_bench(command));
+    }
+
     let cwd =
         if let Some(path) = chdir.or_else(|| std::env::var(FEATURE_REPO_DIR_ENV_VAR_NAME).ok()) {
             PathBuf::from(path)
         } else {
             std::env::current_dir()?
         };
-    let cwd_str = cwd
-        .to_str()
-        .ok_or_else(|| anyhow!("Feature repository path contains invalid UTF-8"))?;
 
     let feature_store_yaml = feature_store_yaml
         .or(std::env::var(FEAST_FS_YAML_FILE_PATH_ENV_VAR).ok())
         .unwrap_or(DEFAULT_FEATURE_STORE_FILE_NAME.to_string());
     let config_path = cwd.join(&feature_store_yaml);
-    let yaml_str = fs::read_to_string(&config_path)?;
-    let repo_config = RepoConfig::from_yaml_str(&yaml_str)?;
+    let repo_config = match &command {
+        // Edge nodes may be shipped a bundle alone
+        CliCommand::Serve {
+            bundle: Some(bundle),
+            ..
+        } if !config_path.exists() => {
+            tracing::info!(
+                "feast-server-rust {} using bundle {} without {}",
+                env!("CARGO_PKG_VERSION"),
+                bundle,
+                config_path.display()
+            );
+            bundle_repo_config(Path::new(bundle))
+        }
+        _ => {
+            let yaml_str = fs::read_to_string(&config_path)?;
+            let repo_config = if strict_config {
+                RepoConfig::from_yaml_str_strict(&yaml_str)?
+            } else {
+                RepoConfig::from_yaml_str(&yaml_str)?
+            };
+            tracing::info!(
+                "feast-server-rust {} using {}",
+                env!("CARGO_PKG_VERSION"),
+                config_path.display()
+            );
+            repo_config
+        }
+    };
+    tracing::info!(
+        "Effective configuration: {}",
+        repo_config.redacted_summary()
+    );
+
+    let runtime = build_runtime(
+        worker_threads.or(repo_config.runtime.worker_threads),
+        max_blocking_threads.or(repo_config.runtime.max_blocking_threads),
+    )?;
+    let config_path = config_path.exists().then_some(config_path);
+    runtime.block_on(run_command(
+        command,
+        repo_config,
+        &cwd,
+        config_path.as_deref(),
+    ))
+}
+
+fn write_completions(shell: clap_complete::Shell, out: &mut dyn std::io::Write) {
+    let mut command = CliOptions::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+}
+
+/// Write `feast-server-rust.1` and a `feast-server-rust-<subcommand>.1` page per subcommand.
+fn write_manpages(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    clap_mangen::generate_to(CliOptions::command(), dir)?;
+    Ok(())
+}
+
+fn build_runtime(
+    worker_threads: Option<usize>,
+    max_blocking_threads: Option<usize>,
+) -> Result<Runtime> {
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+    builder.enable_all();
+    if let Some(threads) = worker_threads {
+        if threads == 0 {
+            return Err(anyhow!("Number of worker threads must be greater than 0"));
+        }
+        builder.worker_threads(threads);
+    }
+    if let Some(threads) = max_blocking_threads {
+        if threads == 0 {
+            return Err(anyhow!("Number of blocking threads must be greater than 0"));
+        }
+        builder.max_blocking_threads(threads);
+    }
+    tracing::debug!(
+        ?worker_threads,
+        ?max_blocking_threads,
+        "Building tokio runtime"
+    );
+    Ok(builder.build()?)
+}
+
+async fn run_bench(command: CliCommand) -> Result<()> {
+    let CliCommand::Bench {
+        url,
+        r#type,
+        features,
+        feature_service,
+        entity,
+        min_entity_id,
+        max_entity_id,
+        entities_per_request,
+        concurrency,
+        duration,
+    } = command
+    else {
+        return Err(anyhow!("Not a bench command"));
+    };
+    if min_entity_id > max_entity_id {
+        return Err(anyhow!(
+            "--min-entity-id must not be greater than --max-entity-id"
+        ));
+    }
+    let mut spec = RequestSpec {
+        feature_service,
+        entity_name: entity,
+        entity_ids: min_entity_id..=max_entity_id,
+        entities_per_request,
+        ..Default::default()
+    };
+    if !features.is_empty() {
+        spec.features = features;
+    }
+    let target = match r#type {
+        cli_options::ServeType::Http => BenchTarget::Http,
+        cli_options::ServeType::Grpc => BenchTarget::Grpc,
+    };
+    tracing::info!(
+        "Sending requests with {} entities to {} for {}s from {} workers",
+        entities_per_request,
+        url,
+        duration,
+        concurrency
+    );
+    let report = cli::bench::run(BenchOptions {
+        url,
+        target,
+        spec,
+        concurrency,
+        duration: Duration::from_secs(duration),
+    })
+    .await?;
+    println!("{}", report);
+    Ok(())
+}
 
+/// Time given to requests in flight to be answered once a shutdown is requested.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Wait for SIGTERM or Ctrl+C.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(err) => {
+                tracing::warn!("Cannot listen for SIGTERM: {}", err);
+                std::future::pending::<()>().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = terminate => tracing::info!("Received SIGTERM, shutting down..."),
+        _ = tokio::signal::ctrl_c() => tracing::info!("Received Ctrl+C, shutting down..."),
+    }
+}
+
+async fn run_command(
+    command: CliCommand,
+    repo_config: RepoConfig,
+    cwd: &Path,
+    config_path: Option<&Path>,
+) -> Result<()> {
     match command {
         CliCommand::Serve {
-            host,
+            hosts,
             port,
             r#type,
             key,
             cert,
             metrics_enabled,
+            dual_stack,
+            backlog,
+            unix_socket,
+            skip_checks,
+            bundle,
+            record,
+            replay,
+            grpc_web,
+            json_transcoding,
         } => {
-            if key.is_some() && cert.is_none() || key.is_none() && cert.is_some() {
-                return Err(anyhow!(
-                    "Both --key and --cert must be provided to enable TLS"
-                ));
+            match &unix_socket {
+                Some(path) => tracing::info!("Start serving on {} using {}", path, r#type),
+                None => tracing::info!(
+                    "Start serving on {}:{} using {}",
+                    hosts.join(", "),
+                    port,
+                    r#type
+                ),
             }
-            if let Some(Provider::Unknown(other)) = repo_config.provider {
-                return Err(anyhow!(
-                    "Unsupported provider: {}, available providers: [local, aws, gcp]",
-                    other
-                ));
+            if let Some(replay) = &replay {
+                tracing::info!("Replaying responses recorded in {}", replay);
             }
-            tracing::info!("Start serving on {}:{} using {}", host, port, r#type);
-            let tls_enabled = key.is_some() && cert.is_some();
-            let registry = feast_server_core::registry::get_registry(
-                repo_config.registry.clone(),
-                repo_config.provider.clone(),
-                repo_config.project.clone(),
-                Some(cwd_str),
-            )
-            .await?;
-            let online_store = feast_server_core::onlinestore::get_online_store(
-                &repo_config.online_store,
-                &repo_config.project,
-                Some(cwd_str),
-            )
-            .await?;
-            let feature_store =
-                feast_server_core::feature_store::FeatureStore::new(registry, online_store);
-            match r#type {
+            if let Some(record) = &record {
+                tracing::info!("Recording responses to {}", record);
+            }
+            let response_recording = match (record, replay) {
+                (Some(dir), _) => Some(ResponseRecording::Record(dir.into())),
+                (_, Some(dir)) => Some(ResponseRecording::Replay(dir.into())),
+                (None, None) => None,
+            };
+            let overrides = ServeOverrides {
+                cwd: Some(cwd.to_path_buf()),
+                config_path: config_path.map(Path::to_path_buf),
+                command_line: std::env::args().collect(),
+                hosts,
+                port,
+                dual_stack,
+                backlog,
+                unix_socket_path: unix_socket,
+                tls_cert_path: cert,
+                tls_key_path: key,
+                skip_checks,
+                bundle: bundle.map(PathBuf::from),
+                response_recording,
+                grpc_web,
+                json_transcoding,
+                ..Default::default()
+            };
+            let (feature_store, servers) = bootstrap(repo_config, overrides).await?;
+            let feature_store = Arc::new(feature_store);
+            // The store is shut down on every exit of the server before its result is returned
+            let served = match r#type {
                 cli_options::ServeType::Http => {
-                    let server_config = rest_server::server::ServerConfig {
-                        host,
-                        port,
-                        tls_enabled,
-                        tls_cert_path: cert,
-                        tls_key_path: key,
-                    };
-                    let handler = axum_server::Handle::new();
-                    let mut sigterm =
-                        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
-                    tokio::select! {
-                    res = rest_server::server::start_server(server_config, feature_store, metrics_enabled, handler.clone()) => {
-                    res?
+                    if grpc_web || json_transcoding {
+                        tracing::warn!(
+                            "grpc-web and JSON transcoding only apply to gRPC; ignoring flags for HTTP"
+                        );
                     }
-                    _ = sigterm.recv() => {
-                        tracing::info!("Received SIGTERM, shutting down...");
-                        handler.graceful_shutdown(Some(Duration::from_secs(5)));
-                        }
-                    _ = tokio::signal::ctrl_c() => {
-                            tracing::info!("Received Ctrl+C, shutting down...");
-                            handler.graceful_shutdown(Some(Duration::from_secs(5)));
+                    let server_config = servers.http;
+                    let handler = rest_server::server::ShutdownHandle::new();
+                    let server = rest_server::server::start_server(
+                        server_config,
+                        Arc::clone(&feature_store),
+                        metrics_enabled,
+                        handler.clone(),
+                    );
+                    tokio::pin!(server);
+                    tokio::select! {
+                        res = &mut server => res,
+                        _ = shutdown_signal() => {
+                            handler.graceful_shutdown(Some(SHUTDOWN_GRACE_PERIOD));
+                            server.await
                         }
-
                     }
                 }
                 cli_options::ServeType::Grpc => {
@@ -122,44 +334,162 @@ async fn main() -> Result<()> {
                             "Metrics server is only available for HTTP; ignoring flag for gRPC"
                         );
                     }
-                    let server_config = grpc_server::server::ServerConfig {
-                        host,
-                        port,
-                        tls_enabled,
-                        tls_cert_path: cert,
-                        tls_key_path: key,
-                    };
-                    #[cfg(unix)]
-                    {
-                        let mut sigterm = tokio::signal::unix::signal(
-                            tokio::signal::unix::SignalKind::terminate(),
-                        )?;
-                        tokio::select! {
-                            res = grpc_server::server::start_server(server_config, feature_store) => {
-                                res?
-                            }
-                            _ = sigterm.recv() => {
-                                tracing::info!("Received SIGTERM, shutting down...");
-                            }
-                            _ = tokio::signal::ctrl_c() => {
-                                tracing::info!("Received Ctrl+C, shutting down...");
-                            }
-                        }
+                    if servers.http.admin_listener.is_some() {
+                        tracing::warn!(
+                            "runtime.admin_listener only applies to HTTP; ignoring it for gRPC"
+                        );
                     }
-                    #[cfg(not(unix))]
-                    {
-                        tokio::select! {
-                            res = grpc_server::server::start_server(server_config, feature_store) => {
-                                res?
-                            }
-                            _ = tokio::signal::ctrl_c() => {
-                                tracing::info!("Received Ctrl+C, shutting down...");
+                    let server_config = servers.grpc;
+                    let (drain, drain_requested) = tokio::sync::oneshot::channel::<()>();
+                    let server = grpc_server::server::start_server_with_shutdown(
+                        server_config,
+                        Arc::clone(&feature_store),
+                        async {
+                            let _ = drain_requested.await;
+                        },
+                    );
+                    tokio::pin!(server);
+                    tokio::select! {
+                        res = &mut server => res,
+                        _ = shutdown_signal() => {
+                            let _ = drain.send(());
+                            match tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, server).await {
+                                Ok(res) => res,
+                                Err(_) => {
+                                    tracing::warn!(
+                                        "gRPC requests still in flight after {:?}, closing them",
+                                        SHUTDOWN_GRACE_PERIOD
+                                    );
+                                    Ok(())
+                                }
                             }
                         }
                     }
                 }
+            };
+            if let Err(err) = feature_store.shutdown().await {
+                tracing::warn!("Feature store did not shut down cleanly: {:#}", err);
             }
+            served?;
+        }
+        CliCommand::Load {
+            path,
+            feature_view,
+            timestamp_field,
+            created_timestamp_column,
+        } => {
+            let repo_config = feast_server_core::secrets::resolve_secrets(repo_config).await?;
+            let feature_store = feast_server_core::feature_store::FeatureStore::builder()
+                .with_repo_config(repo_config)
+                .with_cwd(cwd)
+                .build()
+                .await?;
+            // CSV cells are read as the registry types of their columns
+            let view = feature_store.feature_view(&feature_view).await?;
+            let columns = tokio::task::spawn_blocking(move || {
+                read_feature_file(Path::new(&path), |column| view.column_type(column))
+            })
+            .await??;
+            let count = feature_store
+                .write_to_online_store(
+                    &feature_view,
+                    &columns,
+                    &timestamp_field,
+                    created_timestamp_column.as_deref(),
+                )
+                .await?;
+            tracing::info!("Wrote {} rows to feature view {}", count, feature_view);
+            feature_store.shutdown().await?;
+        }
+        CliCommand::ExportSchema {
+            feature_service,
+            format,
+            full_feature_names,
+            output,
+        } => {
+            let repo_config = feast_server_core::secrets::resolve_secrets(repo_config).await?;
+            let cwd = cwd
+                .to_str()
+                .ok_or_else(|| anyhow!("Feature repository path contains invalid UTF-8"))?;
+            let registry = feast_server_core::registry::get_registry(
+                repo_config.registry,
+                repo_config.project,
+                Some(cwd),
+            )
+            .await?;
+            let schema = ResponseSchema::new(
+                &registry.metadata().await?,
+                &feature_service,
+                full_feature_names,
+            )?;
+            let schema = match format {
+                cli_options::SchemaFormat::JsonSchema => {
+                    serde_json::to_string_pretty(&schema.to_json_schema())? + "\n"
+                }
+                cli_options::SchemaFormat::Proto => schema.to_proto(),
+            };
+            match output {
+                Some(output) => tokio::fs::write(&output, schema).await?,
+                None => print!("{}", schema),
+            }
+        }
+        CliCommand::ExportBundle {
+            output,
+            feature_services,
+        } => {
+            let repo_config = feast_server_core::secrets::resolve_secrets(repo_config).await?;
+            let cwd = cwd
+                .to_str()
+                .ok_or_else(|| anyhow!("Feature repository path contains invalid UTF-8"))?;
+            let summary = export_bundle(
+                &repo_config,
+                Some(cwd),
+                &feature_services,
+                Path::new(&output),
+            )
+            .await?;
+            if !summary.missing_tables.is_empty() {
+                tracing::warn!(
+                    "Feature views without online data: {}",
+                    summary.missing_tables.join(", ")
+                );
+            }
+            tracing::info!(
+                "Wrote {} rows of feature views [{}] to bundle {}",
+                summary.rows,
+                summary.feature_views.join(", "),
+                output
+            );
+        }
+        CliCommand::Bench { .. } => run_bench(command).await?,
+        CliCommand::Completions { .. } | CliCommand::Manpages { .. } => {
+            return Err(anyhow!(
+                "Packaging commands do not run against a repository"
+            ));
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packaging_outputs() -> Result<()> {
+        CliOptions::command().debug_assert();
+
+        let mut completions = Vec::new();
+        write_completions(clap_complete::Shell::Bash, &mut completions);
+        let completions = String::from_utf8(completions)?;
+        assert!(completions.contains("feast-server-rust"));
+        assert!(completions.contains("export-bundle"));
+
+        let dir = tempfile::tempdir()?;
+        write_manpages(dir.path())?;
+        let page = fs::read_to_string(dir.path().join("feast-server-rust-serve.1"))?;
+        assert!(page.contains("Unix domain socket"));
+        assert!(dir.path().join("feast-server-rust.1").exists());
+        Ok(())
+    }
+}
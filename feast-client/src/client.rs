@@ -0,0 +1,409 @@
+use crate::proto::feast::serving::serving_service_client::ServingServiceClient;
+use crate::proto::feast::serving::{
+    FeatureList, GetOnlineFeaturesRequest, get_online_features_request,
+};
+use crate::proto::feast::types::{RepeatedValue, Value};
+use crate::response::OnlineFeatures;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::time::Duration;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Request, Status};
+
+/// Response metadata key carrying each warning of a partial response.
+const WARNING_METADATA_KEY: &str = "x-feast-warning";
+
+/// Retries of requests failing with a transient status: `UNAVAILABLE`, which the server
+/// also answers when it sheds load, `RESOURCE_EXHAUSTED` and `ABORTED`. The delay before
+/// each retry doubles from `initial_backoff` up to `max_backoff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts including the first one; 1 disables retries.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    fn is_retryable(status: &Status) -> bool {
+        matches!(
+            status.code(),
+            Code::Unavailable | Code::ResourceExhausted | Code::Aborted
+        )
+    }
+
+    /// Delay before the retry following the failed `attempt`, counted from 1.
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+/// Entity columns of a request, each holding one value per entity row.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Entities(HashMap<String, RepeatedValue>);
+
+impl Entities {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the entity column `name`, e.g. `.with("driver_id", [1001_i64, 1002])`.
+    pub fn with<V: Into<Value>>(
+        mut self,
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let val = values.into_iter().map(Into::into).collect();
+        self.0.insert(name.into(), RepeatedValue { val });
+        self
+    }
+}
+
+/// Client of the feature server's `ServingService`. Cloning is cheap and clones share the
+/// underlying connection.
+#[derive(Debug, Clone)]
+pub struct FeastClient {
+    client: ServingServiceClient<Channel>,
+    retry_policy: RetryPolicy,
+    timeout: Option<Duration>,
+    full_feature_names: bool,
+}
+
+impl FeastClient {
+    /// Connect to the server at `endpoint`, e.g. `http://127.0.0.1:6567`.
+    pub async fn connect(endpoint: impl Into<String>) -> Result<Self> {
+        let endpoint = endpoint.into();
+        let channel = Endpoint::from_shared(endpoint.clone())
+            .map_err(|err| anyhow!("Invalid endpoint {}: {}", endpoint, err))?
+            .connect()
+            .await
+            .map_err(|err| anyhow!("Failed to connect to {}: {}", endpoint, err))?;
+        Ok(Self::new(channel))
+    }
+
+    /// Client over an existing channel, e.g. one configured with TLS.
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            client: ServingServiceClient::new(channel),
+            retry_policy: RetryPolicy::default(),
+            timeout: None,
+            full_feature_names: false,
+        }
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Accept responses up to `limit` bytes instead of the 4 MiB tonic accepts by default,
+    /// e.g. for requests with many entity rows.
+    pub fn with_max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.client = self.client.max_decoding_message_size(limit);
+        self
+    }
+
+    /// Send requests up to `limit` bytes; larger requests fail without being sent.
+    pub fn with_max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.client = self.client.max_encoding_message_size(limit);
+        self
+    }
+
+    /// Fail attempts taking longer than `timeout` with `DEADLINE_EXCEEDED`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Return features as `feature_view__feature` instead of their bare names.
+    pub fn with_full_feature_names(mut self, full_feature_names: bool) -> Self {
+        self.full_feature_names = full_feature_names;
+        self
+    }
+
+    /// Features of `feature_service` for the `entities` rows.
+    pub async fn get_online_features(
+        &self,
+        feature_service: &str,
+        entities: Entities,
+    ) -> Result<OnlineFeatures> {
+        let kind = get_online_features_request::Kind::FeatureService(feature_service.to_string());
+        self.request(kind, entities).await
+    }
+
+    /// Features referenced as `feature_view:feature` for the `entities` rows.
+    pub async fn get_online_features_by_refs(
+        &self,
+        features: impl IntoIterator<Item = impl Into<String>>,
+        entities: Entities,
+    ) -> Result<OnlineFeatures> {
+        let kind = get_online_features_request::Kind::Features(FeatureList {
+            val: features.into_iter().map(Into::into).collect(),
+        });
+        self.request(kind, entities).await
+    }
+
+    async fn request(
+        &self,
+        kind: get_online_features_request::Kind,
+        entities: Entities,
+    ) -> Result<OnlineFeatures> {
+        let request = GetOnlineFeaturesRequest {
+            kind: Some(kind),
+            entities: entities.0,
+            full_feature_names: self.full_feature_names,
+            request_context: HashMap::new(),
+        };
+        let mut attempt = 1;
+        loop {
+            match self.attempt(request.clone()).await {
+                Ok(features) => return Ok(features),
+                Err(status)
+                    if attempt < self.retry_policy.max_attempts
+                        && RetryPolicy::is_retryable(&status) =>
+                {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    tracing::debug!(
+                        "Retrying online features request in {:?} after attempt {} failed: {}",
+                        backoff,
+                        attempt,
+                        status
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                // Returned as is, so callers can downcast to `tonic::Status` for the code
+                Err(status) => return Err(status.into()),
+            }
+        }
+    }
+
+    async fn attempt(&self, request: GetOnlineFeaturesRequest) -> Result<OnlineFeatures, Status> {
+        let mut client = self.client.clone();
+        let mut request = Request::new(request);
+        let response = match self.timeout {
+            Some(timeout) => {
+                request.set_timeout(timeout);
+                tokio::time::timeout(timeout, client.get_online_features(request))
+                    .await
+                    .map_err(|_| Status::deadline_exceeded("online features request timed out"))??
+            }
+            None => client.get_online_features(request).await?,
+        };
+        let warnings = response
+            .metadata()
+            .get_all(WARNING_METADATA_KEY)
+            .iter()
+            .filter_map(|value| value.to_str().ok().map(str::to_string))
+            .collect();
+        Ok(OnlineFeatures::new(response.into_inner(), warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feast_server_core::feature_store::FeatureStore;
+    use feast_server_core::onlinestore::sqlite_onlinestore::{
+        ConnectionOptions, SqliteOnlineStore,
+    };
+    use feast_server_core::registry::FileFeatureRegistry;
+    use grpc_server::proto::feast::serving::serving_service_server::{
+        ServingService, ServingServiceServer,
+    };
+    use grpc_server::proto::feast::serving::{
+        GetFeastServingInfoRequest, GetFeastServingInfoResponse,
+        GetOnlineFeaturesRequest as ServerRequest, GetOnlineFeaturesResponse as ServerResponse,
+    };
+    use grpc_server::server::FeastGrpcService;
+    use std::net::SocketAddr;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::Response;
+    use tonic::transport::Server;
+
+    async fn serve<S: ServingService>(service: S) -> Result<SocketAddr> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(
+            Server::builder()
+                .add_service(ServingServiceServer::new(service))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        Ok(addr)
+    }
+
+    async fn feature_server() -> Result<SocketAddr> {
+        let test_data =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../feast-server-core/test_data");
+        let registry = FileFeatureRegistry::from_path(&test_data.join("registry.pb"))?;
+        let online_store = SqliteOnlineStore::from_options(
+            test_data.join("online_store.db").to_str().unwrap(),
+            "golden_hornet".to_string(),
+            ConnectionOptions::default(),
+        )
+        .await?;
+        let feature_store = FeatureStore::new(Arc::new(registry), Arc::new(online_store));
+        serve(FeastGrpcService::new(feature_store)).await
+    }
+
+    #[tokio::test]
+    async fn reads_typed_features_of_feature_service() -> Result<()> {
+        let addr = feature_server().await?;
+        let client = FeastClient::connect(format!("http://{}", addr)).await?;
+
+        let features = client
+            .get_online_features(
+                "driver_activity_v4",
+                Entities::new().with("driver_id", [1005_i64, 9999]),
+            )
+            .await?;
+        assert_eq!(features.len(), 2);
+        assert_eq!(
+            features.column::<i64>("driver_id")?,
+            [Some(1005), Some(9999)]
+        );
+        assert!(features.get::<f64>("conv_rate", 0)?.is_some());
+        assert_eq!(features.get::<f64>("conv_rate", 1)?, None);
+        assert_eq!(
+            features.status("conv_rate", 1),
+            Some(crate::FieldStatus::NotFound)
+        );
+
+        let features = client
+            .clone()
+            .with_full_feature_names(true)
+            .get_online_features_by_refs(
+                ["driver_hourly_stats:conv_rate"],
+                Entities::new().with("driver_id", [1005_i64]),
+            )
+            .await?;
+        assert_eq!(
+            features.feature_names(),
+            ["driver_id", "driver_hourly_stats__conv_rate"]
+        );
+        assert!(
+            features
+                .get::<f32>("driver_hourly_stats:conv_rate", 0)?
+                .is_some()
+        );
+
+        let err = client
+            .get_online_features("missing_service", Entities::new())
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<Status>().is_some(), "{:#}", err);
+        Ok(())
+    }
+
+    /// Service failing with `code` on the first `failures` requests.
+    struct FlakyService {
+        code: Code,
+        failures: u32,
+        calls: Arc<AtomicU32>,
+    }
+
+    #[tonic::async_trait]
+    impl ServingService for FlakyService {
+        async fn get_feast_serving_info(
+            &self,
+            _request: tonic::Request<GetFeastServingInfoRequest>,
+        ) -> Result<Response<GetFeastServingInfoResponse>, Status> {
+            Err(Status::unimplemented("not used"))
+        }
+
+        async fn get_online_features(
+            &self,
+            _request: tonic::Request<ServerRequest>,
+        ) -> Result<Response<ServerResponse>, Status> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(Status::new(self.code, "failed"));
+            }
+            Ok(Response::new(ServerResponse::default()))
+        }
+    }
+
+    async fn flaky_client(code: Code, failures: u32) -> Result<(FeastClient, Arc<AtomicU32>)> {
+        let calls = Arc::new(AtomicU32::new(0));
+        let addr = serve(FlakyService {
+            code,
+            failures,
+            calls: calls.clone(),
+        })
+        .await?;
+        let client = FeastClient::connect(format!("http://{}", addr))
+            .await?
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 3,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(5),
+            });
+        Ok((client, calls))
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures() -> Result<()> {
+        let (client, calls) = flaky_client(Code::Unavailable, 2).await?;
+        client
+            .get_online_features("driver_activity_v4", Entities::new())
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let (client, calls) = flaky_client(Code::Unavailable, 3).await?;
+        let err = client
+            .get_online_features("driver_activity_v4", Entities::new())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Status>().unwrap().code(),
+            Code::Unavailable
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let (client, calls) = flaky_client(Code::InvalidArgument, 1).await?;
+        let err = client
+            .get_online_features("driver_activity_v4", Entities::new())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Status>().unwrap().code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_millis(150),
+        };
+        let backoffs = (1..=4)
+            .map(|attempt| policy.backoff(attempt))
+            .collect::<Vec<_>>();
+        assert_eq!(backoffs, [50, 100, 150, 150].map(Duration::from_millis));
+    }
+}
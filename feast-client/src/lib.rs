@@ -0,0 +1,38 @@
+//! Client of the feature server's gRPC serving API, for Rust services reading online
+//! features without handling the serving protos themselves.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use feast_client::{Entities, FeastClient};
+//!
+//! let client = FeastClient::connect("http://127.0.0.1:6567").await?;
+//! let features = client
+//!     .get_online_features(
+//!         "driver_activity_v4",
+//!         Entities::new().with("driver_id", [1001_i64, 1002]),
+//!     )
+//!     .await?;
+//! let conv_rates: Vec<Option<f32>> = features.column("conv_rate")?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod client;
+pub mod response;
+pub mod value;
+
+pub use client::{Entities, FeastClient, RetryPolicy};
+pub use proto::feast::serving::FieldStatus;
+pub use response::OnlineFeatures;
+pub use value::FromValue;
+
+pub mod proto {
+    pub mod feast {
+        pub mod serving {
+            tonic::include_proto!("feast.serving");
+        }
+        pub mod types {
+            tonic::include_proto!("feast.types");
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::proto::feast::serving::get_online_features_response::FeatureVector;
+use crate::proto::feast::serving::{
+    FeatureList, FieldStatus, GetOnlineFeaturesResponse, GetOnlineFeaturesResponseMetadata,
+};
+use crate::proto::feast::types::Value;
+use crate::value::{FromValue, extract};
+use anyhow::{Result, anyhow};
+
+/// Online features of a request, one column per feature and one row per entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnlineFeatures {
+    feature_names: Vec<String>,
+    results: Vec<FeatureVector>,
+    warnings: Vec<String>,
+}
+
+impl OnlineFeatures {
+    pub(crate) fn new(response: GetOnlineFeaturesResponse, warnings: Vec<String>) -> Self {
+        let feature_names = response
+            .metadata
+            .and_then(|metadata| metadata.feature_names)
+            .map(|names| names.val)
+            .unwrap_or_default();
+        Self {
+            feature_names,
+            results: response.results,
+            warnings,
+        }
+    }
+
+    /// Names of the columns, entity columns included when the server returns them.
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+
+    /// Number of entity rows.
+    pub fn len(&self) -> usize {
+        self.results
+            .iter()
+            .map(|result| result.values.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Warnings of a partial response, e.g. feature views the server failed to read.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Value of `feature` for the entity `row`, `None` when either does not exist.
+    pub fn value(&self, feature: &str, row: usize) -> Option<&Value> {
+        self.feature(feature)?.values.get(row)
+    }
+
+    /// Status of `feature` for the entity `row`, `None` when the server left statuses out.
+    pub fn status(&self, feature: &str, row: usize) -> Option<FieldStatus> {
+        let status = *self.feature(feature)?.statuses.get(row)?;
+        FieldStatus::try_from(status).ok()
+    }
+
+    /// Value of `feature` for the entity `row` as `T`, `None` when the value is missing or
+    /// null. Fails for unknown features and values of another type.
+    pub fn get<T: FromValue>(&self, feature: &str, row: usize) -> Result<Option<T>> {
+        match self.existing_feature(feature)?.values.get(row) {
+            Some(value) => {
+                extract(value).map_err(|err| anyhow!("Feature {} of row {}: {}", feature, row, err))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Values of `feature` for all entity rows as `T`.
+    pub fn column<T: FromValue>(&self, feature: &str) -> Result<Vec<Option<T>>> {
+        self.existing_feature(feature)?
+            .values
+            .iter()
+            .enumerate()
+            .map(|(row, value)| {
+                extract(value).map_err(|err| anyhow!("Feature {} of row {}: {}", feature, row, err))
+            })
+            .collect()
+    }
+
+    pub fn into_response(self) -> GetOnlineFeaturesResponse {
+        GetOnlineFeaturesResponse {
+            metadata: Some(GetOnlineFeaturesResponseMetadata {
+                feature_names: Some(FeatureList {
+                    val: self.feature_names,
+                }),
+            }),
+            results: self.results,
+            status: true,
+        }
+    }
+
+    fn existing_feature(&self, feature: &str) -> Result<&FeatureVector> {
+        self.feature(feature).ok_or_else(|| {
+            anyhow!(
+                "Feature {} is not in the response; returned features: [{}]",
+                feature,
+                self.feature_names.join(", ")
+            )
+        })
+    }
+
+    /// Column of `feature`, which is looked up by its returned name as well as by its
+    /// `feature_view:feature` reference.
+    fn feature(&self, feature: &str) -> Option<&FeatureVector> {
+        let position = |name: &str| self.feature_names.iter().position(|n| n == name);
+        let index = position(feature).or_else(|| {
+            let (view, name) = feature.split_once(':')?;
+            position(&format!("{}__{}", view, name)).or_else(|| position(name))
+        })?;
+        self.results.get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn online_features() -> OnlineFeatures {
+        let column = |values: Vec<Value>, statuses: Vec<FieldStatus>| FeatureVector {
+            values,
+            statuses: statuses.into_iter().map(|status| status as i32).collect(),
+            event_timestamps: vec![],
+        };
+        OnlineFeatures::new(
+            GetOnlineFeaturesResponse {
+                metadata: Some(GetOnlineFeaturesResponseMetadata {
+                    feature_names: Some(FeatureList {
+                        val: vec![
+                            "driver_id".to_string(),
+                            "driver_hourly_stats__conv_rate".to_string(),
+                        ],
+                    }),
+                }),
+                results: vec![
+                    column(
+                        vec![Value::from(1001_i64), Value::from(1002_i64)],
+                        vec![FieldStatus::Present, FieldStatus::Present],
+                    ),
+                    column(
+                        vec![Value::from(0.5_f32), Value::default()],
+                        vec![FieldStatus::Present, FieldStatus::NotFound],
+                    ),
+                ],
+                status: true,
+            },
+            vec![],
+        )
+    }
+
+    #[test]
+    fn reads_values_by_returned_name_and_reference() -> Result<()> {
+        let features = online_features();
+        assert_eq!(features.len(), 2);
+        assert_eq!(
+            features.column::<i64>("driver_id")?,
+            [Some(1001), Some(1002)]
+        );
+        assert_eq!(
+            features.column::<f64>("driver_hourly_stats:conv_rate")?,
+            [Some(0.5), None]
+        );
+        assert_eq!(
+            features.status("driver_hourly_stats__conv_rate", 1),
+            Some(FieldStatus::NotFound)
+        );
+        assert_eq!(
+            features.get::<f32>("driver_hourly_stats:conv_rate", 5)?,
+            None
+        );
+
+        let err = features.get::<f32>("acc_rate", 0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Feature acc_rate is not in the response; returned features: \
+             [driver_id, driver_hourly_stats__conv_rate]"
+        );
+        let err = features.column::<String>("driver_id").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Feature driver_id of row 0: Cannot read INT64 value as alloc::string::String"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+//! Conversions between Rust types and feature values.
+
+use crate::proto::feast::types::value::Val;
+use crate::proto::feast::types::{
+    BoolList, BytesList, DoubleList, FloatList, Int32List, Int64List, StringList, Value,
+};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+
+/// Rust type a feature value can be read as. Integer and floating point values are widened,
+/// so an `INT32` feature reads as `i64` and a `FLOAT` feature as `f64`.
+pub trait FromValue: Sized {
+    /// Convert a value which is not null, `None` when it holds another type.
+    fn from_val(val: &Val) -> Option<Self>;
+}
+
+/// Read `value` as `T`, `None` for missing and null values.
+pub(crate) fn extract<T: FromValue>(value: &Value) -> Result<Option<T>> {
+    match &value.val {
+        None | Some(Val::NullVal(_)) => Ok(None),
+        Some(val) => T::from_val(val).map(Some).ok_or_else(|| {
+            anyhow!(
+                "Cannot read {} value as {}",
+                val_type_name(val),
+                std::any::type_name::<T>()
+            )
+        }),
+    }
+}
+
+fn val_type_name(val: &Val) -> &'static str {
+    match val {
+        Val::BytesVal(_) => "BYTES",
+        Val::StringVal(_) => "STRING",
+        Val::Int32Val(_) => "INT32",
+        Val::Int64Val(_) => "INT64",
+        Val::DoubleVal(_) => "DOUBLE",
+        Val::FloatVal(_) => "FLOAT",
+        Val::BoolVal(_) => "BOOL",
+        Val::UnixTimestampVal(_) => "UNIX_TIMESTAMP",
+        Val::BytesListVal(_) => "BYTES_LIST",
+        Val::StringListVal(_) => "STRING_LIST",
+        Val::Int32ListVal(_) => "INT32_LIST",
+        Val::Int64ListVal(_) => "INT64_LIST",
+        Val::DoubleListVal(_) => "DOUBLE_LIST",
+        Val::FloatListVal(_) => "FLOAT_LIST",
+        Val::BoolListVal(_) => "BOOL_LIST",
+        Val::UnixTimestampListVal(_) => "UNIX_TIMESTAMP_LIST",
+        Val::NullVal(_) => "NULL",
+    }
+}
+
+fn timestamp(seconds: i64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(seconds, 0)
+}
+
+macro_rules! from_value {
+    ($ty:ty, |$val:ident| $body:expr) => {
+        impl FromValue for $ty {
+            fn from_val($val: &Val) -> Option<Self> {
+                $body
+            }
+        }
+    };
+}
+
+from_value!(i32, |val| match val {
+    Val::Int32Val(v) => Some(*v),
+    _ => None,
+});
+from_value!(i64, |val| match val {
+    Val::Int32Val(v) => Some(i64::from(*v)),
+    Val::Int64Val(v) | Val::UnixTimestampVal(v) => Some(*v),
+    _ => None,
+});
+from_value!(f32, |val| match val {
+    Val::FloatVal(v) => Some(*v),
+    _ => None,
+});
+from_value!(f64, |val| match val {
+    Val::FloatVal(v) => Some(f64::from(*v)),
+    Val::DoubleVal(v) => Some(*v),
+    _ => None,
+});
+from_value!(bool, |val| match val {
+    Val::BoolVal(v) => Some(*v),
+    _ => None,
+});
+from_value!(String, |val| match val {
+    Val::StringVal(v) => Some(v.clone()),
+    _ => None,
+});
+from_value!(Vec<u8>, |val| match val {
+    Val::BytesVal(v) => Some(v.clone()),
+    _ => None,
+});
+from_value!(DateTime<Utc>, |val| match val {
+    Val::UnixTimestampVal(v) => timestamp(*v),
+    _ => None,
+});
+from_value!(Vec<Vec<u8>>, |val| match val {
+    Val::BytesListVal(v) => Some(v.val.clone()),
+    _ => None,
+});
+from_value!(Vec<String>, |val| match val {
+    Val::StringListVal(v) => Some(v.val.clone()),
+    _ => None,
+});
+from_value!(Vec<i32>, |val| match val {
+    Val::Int32ListVal(v) => Some(v.val.clone()),
+    _ => None,
+});
+from_value!(Vec<i64>, |val| match val {
+    Val::Int32ListVal(v) => Some(v.val.iter().copied().map(i64::from).collect()),
+    Val::Int64ListVal(v) | Val::UnixTimestampListVal(v) => Some(v.val.clone()),
+    _ => None,
+});
+from_value!(Vec<f32>, |val| match val {
+    Val::FloatListVal(v) => Some(v.val.clone()),
+    _ => None,
+});
+from_value!(Vec<f64>, |val| match val {
+    Val::FloatListVal(v) => Some(v.val.iter().copied().map(f64::from).collect()),
+    Val::DoubleListVal(v) => Some(v.val.clone()),
+    _ => None,
+});
+from_value!(Vec<bool>, |val| match val {
+    Val::BoolListVal(v) => Some(v.val.clone()),
+    _ => None,
+});
+from_value!(Vec<DateTime<Utc>>, |val| match val {
+    Val::UnixTimestampListVal(v) => v.val.iter().map(|&seconds| timestamp(seconds)).collect(),
+    _ => None,
+});
+
+macro_rules! into_value {
+    ($ty:ty, |$v:ident| $val:expr) => {
+        impl From<$ty> for Value {
+            fn from($v: $ty) -> Self {
+                Value { val: Some($val) }
+            }
+        }
+    };
+}
+
+into_value!(i32, |v| Val::Int32Val(v));
+into_value!(i64, |v| Val::Int64Val(v));
+into_value!(f32, |v| Val::FloatVal(v));
+into_value!(f64, |v| Val::DoubleVal(v));
+into_value!(bool, |v| Val::BoolVal(v));
+into_value!(String, |v| Val::StringVal(v));
+into_value!(&str, |v| Val::StringVal(v.to_string()));
+into_value!(Vec<u8>, |v| Val::BytesVal(v));
+into_value!(DateTime<Utc>, |v| Val::UnixTimestampVal(v.timestamp()));
+into_value!(Vec<String>, |v| Val::StringListVal(StringList { val: v }));
+into_value!(Vec<i32>, |v| Val::Int32ListVal(Int32List { val: v }));
+into_value!(Vec<i64>, |v| Val::Int64ListVal(Int64List { val: v }));
+into_value!(Vec<f32>, |v| Val::FloatListVal(FloatList { val: v }));
+into_value!(Vec<f64>, |v| Val::DoubleListVal(DoubleList { val: v }));
+into_value!(Vec<bool>, |v| Val::BoolListVal(BoolList { val: v }));
+into_value!(Vec<Vec<u8>>, |v| Val::BytesListVal(BytesList { val: v }));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::feast::types::Null;
+
+    #[test]
+    fn extracts_typed_values() -> Result<()> {
+        assert_eq!(extract::<i64>(&Value::from(7_i32))?, Some(7));
+        assert_eq!(extract::<f64>(&Value::from(0.5_f32))?, Some(0.5));
+        assert_eq!(extract::<String>(&Value::from("a"))?, Some("a".to_string()));
+        assert_eq!(
+            extract::<Vec<i64>>(&Value::from(vec![1_i32, 2]))?,
+            Some(vec![1, 2])
+        );
+        let ts = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        assert_eq!(extract::<DateTime<Utc>>(&Value::from(ts))?, Some(ts));
+
+        assert_eq!(extract::<f32>(&Value::default())?, None);
+        let null = Value {
+            val: Some(Val::NullVal(Null::Null as i32)),
+        };
+        assert_eq!(extract::<f32>(&null)?, None);
+
+        let err = extract::<i32>(&Value::from(1.5_f64)).unwrap_err();
+        assert_eq!(err.to_string(), "Cannot read DOUBLE value as i32");
+        Ok(())
+    }
+}
@@ -1,11 +1,10 @@
 #![allow(dead_code)]
 
-use rustc_hash::FxHashMap as HashMap;
 use std::sync::{Arc, OnceLock};
 
 use anyhow::Result;
 use feast_server_core::feature_store::FeatureStore;
-use feast_server_core::model::{EntityIdValue, GetOnlineFeaturesRequest};
+use feast_server_core::model::{EntityIdValue, GetOnlineFeaturesRequest, OrderedMap};
 use feast_server_core::onlinestore::OnlineStore;
 use feast_server_core::onlinestore::sqlite_onlinestore::{ConnectionOptions, SqliteOnlineStore};
 use feast_server_core::registry::FeatureRegistryService;
@@ -64,7 +63,7 @@ pub async fn feature_store() -> Result<Arc<FeatureStore>> {
 }
 
 pub fn sample_request() -> GetOnlineFeaturesRequest {
-    let entities = HashMap::from_iter([(
+    let entities = OrderedMap::from_iter([(
         "driver_id".to_string(),
         vec![
             EntityIdValue::Int(1005),
@@ -81,5 +80,6 @@ pub fn sample_request() -> GetOnlineFeaturesRequest {
         ]
         .into(),
         full_feature_names: Some(false),
+        ..Default::default()
     }
 }
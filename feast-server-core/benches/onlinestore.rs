@@ -10,6 +10,7 @@ use common::online_store;
 use feast_server_core::feast::types::value::Val;
 use feast_server_core::feast::types::{EntityKey, Value};
 use feast_server_core::model::{Feature, HashEntityKey};
+use feast_server_core::onlinestore::feature_keys::{FeatureKeyCache, hash_feature_key};
 
 fn build_entity_keys() -> Vec<EntityKey> {
     [1005_i64, 1002, 2003]
@@ -29,7 +30,7 @@ fn bench_onlinestore(c: &mut Criterion) {
         .block_on(online_store())
         .expect("failed to create sqlite online store");
     let entity_keys = build_entity_keys();
-    let feature_names = vec!["conv_rate", "acc_rate"];
+    let feature_names = ["conv_rate", "acc_rate"];
 
     let arg: HashMap<HashEntityKey, Vec<Feature>> = entity_keys
         .into_iter()
@@ -59,5 +60,35 @@ fn bench_onlinestore(c: &mut Criterion) {
     });
 }
 
-criterion_group!(onlinestore_benches, bench_onlinestore);
+/// Hash fields of a Redis read of 100 features for 10 entity rows, which hashes every
+/// feature once per row.
+fn bench_feature_keys(c: &mut Criterion) {
+    let features: Vec<Feature> = (0..100)
+        .map(|i| Feature::from_names("driver_hourly_stats", &format!("feature_{}", i)))
+        .collect();
+    let rows = 10;
+
+    c.bench_function("redis_feature_keys_100_features_uncached", |b| {
+        b.iter(|| {
+            for _ in 0..rows {
+                for feature in &features {
+                    criterion::black_box(hash_feature_key(feature).expect("hash failed"));
+                }
+            }
+        });
+    });
+
+    let cache = FeatureKeyCache::new();
+    c.bench_function("redis_feature_keys_100_features_cached", |b| {
+        b.iter(|| {
+            for _ in 0..rows {
+                for feature in &features {
+                    criterion::black_box(cache.get(feature).expect("hash failed"));
+                }
+            }
+        });
+    });
+}
+
+criterion_group!(onlinestore_benches, bench_onlinestore, bench_feature_keys);
 criterion_main!(onlinestore_benches);
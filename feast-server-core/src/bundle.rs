@@ -0,0 +1,429 @@
+//! Self-contained snapshots of a project for edge deployments. A bundle is a SQLite database
+//! holding the registry proto of the selected feature services next to copies of their
+//! SQLite online tables, so a node can serve it without reaching the registry or the online
+//! store it was exported from.
+
+use crate::config::{OnlineStoreConfig, RegistryConfig, RepoConfig};
+use crate::feast::core::Registry;
+use crate::feast::core::on_demand_source::Source;
+use crate::onlinestore::OnlineStore;
+use crate::onlinestore::sqlite_onlinestore::{
+    ConnectionOptions, FEATURE_VIEW_PLACEHOLDER, SqliteOnlineStore,
+};
+use crate::registry::{FeatureRegistryService, FileFeatureRegistry, compat, load_registry_proto};
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use prost::Message;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Connection, Row, SqliteConnection};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Table of a bundle holding its single metadata row.
+const BUNDLE_TABLE: &str = "feast_bundle";
+
+/// Outcome of [`export_bundle`].
+#[derive(Debug)]
+pub struct ExportSummary {
+    /// Feature views whose online tables were copied into the bundle.
+    pub feature_views: Vec<String>,
+    /// Views of the selected feature services without an online table to copy.
+    pub missing_tables: Vec<String>,
+    pub rows: u64,
+}
+
+/// Registry and online store read from a bundle file.
+pub struct Bundle {
+    pub project: String,
+    pub feature_services: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub registry: Arc<dyn FeatureRegistryService>,
+    pub online_store: Arc<dyn OnlineStore>,
+}
+
+/// Write the registry objects and SQLite online data serving `feature_services` to a bundle at
+/// `output`, replacing any existing file. The registry must be a file registry and the online
+/// store a SQLite store.
+pub async fn export_bundle(
+    repo_config: &RepoConfig,
+    cwd: Option<&str>,
+    feature_services: &[String],
+    output: &Path,
+) -> Result<ExportSummary> {
+    if feature_services.is_empty() {
+        return Err(anyhow!("At least one feature service must be exported"));
+    }
+    let OnlineStoreConfig::Sqlite { path } = &repo_config.online_store else {
+        return Err(anyhow!(
+            "Bundles can only be exported from a SQLite online store"
+        ));
+    };
+    if path.contains(FEATURE_VIEW_PLACEHOLDER) {
+        return Err(anyhow!(
+            "Bundles cannot be exported from a SQLite online store sharded by feature view"
+        ));
+    }
+    let online_store_path = cwd
+        .map(|prefix| format!("{}/{}", prefix, path))
+        .unwrap_or_else(|| path.to_string());
+    // Attaching a missing database would create it and export an empty bundle
+    if !Path::new(&online_store_path).is_file() {
+        return Err(anyhow!("Online store {} does not exist", online_store_path));
+    }
+
+    let mut registry = load_registry_proto(&repo_config.registry, cwd).await?;
+    let feature_views = retain_feature_services(&mut registry, feature_services)?;
+
+    // Build the bundle next to the output and move it in place once complete, so a reader
+    // never sees a partial bundle
+    let mut partial_path = output.as_os_str().to_owned();
+    partial_path.push(".partial");
+    let partial_path = PathBuf::from(partial_path);
+    if partial_path.exists() {
+        std::fs::remove_file(&partial_path)?;
+    }
+    let summary = write_bundle(
+        &partial_path,
+        &online_store_path,
+        &repo_config.project,
+        &registry,
+        feature_services,
+        feature_views,
+    )
+    .await;
+    match summary {
+        Ok(summary) => {
+            std::fs::rename(&partial_path, output).with_context(|| {
+                format!("Failed to move bundle in place at {}", output.display())
+            })?;
+            Ok(summary)
+        }
+        Err(err) => {
+            let _ = std::fs::remove_file(&partial_path);
+            Err(err)
+        }
+    }
+}
+
+/// Open a bundle written by [`export_bundle`].
+pub async fn open_bundle(path: &Path) -> Result<Bundle> {
+    let location = path.display().to_string();
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let mut connection = SqliteConnection::connect_with(&options)
+        .await
+        .with_context(|| format!("Failed to open bundle {}", location))?;
+    let row = sqlx::query(&format!(
+        "SELECT project, registry, feature_services, created_at FROM {}",
+        BUNDLE_TABLE
+    ))
+    .fetch_one(&mut connection)
+    .await
+    .with_context(|| format!("{} is not a feature bundle", location))?;
+    connection.close().await?;
+
+    let project: String = row.try_get("project")?;
+    let registry: Vec<u8> = row.try_get("registry")?;
+    let feature_services: String = row.try_get("feature_services")?;
+    let created_at: DateTime<Utc> = row.try_get("created_at")?;
+    let registry = FileFeatureRegistry::from_proto(compat::decode_registry(&registry, &location)?)?;
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| anyhow!("Bundle path contains invalid UTF-8"))?;
+    let online_store =
+        SqliteOnlineStore::from_options(path_str, project.clone(), ConnectionOptions::default())
+            .await?;
+    Ok(Bundle {
+        project,
+        feature_services: feature_services.split(',').map(str::to_string).collect(),
+        created_at,
+        registry: Arc::new(registry),
+        online_store: Arc::new(online_store),
+    })
+}
+
+/// Config serving a bundle deployed without a `feature_store.yaml`. The registry and online
+/// store settings only describe the bundle, which is opened with [`open_bundle`].
+pub fn bundle_repo_config(path: &Path) -> RepoConfig {
+    let path = path.display().to_string();
+    RepoConfig {
+        registry: RegistryConfig {
+            path: path.clone(),
+            ..Default::default()
+        },
+        online_store: OnlineStoreConfig::Sqlite { path },
+        ..Default::default()
+    }
+}
+
+/// Drop feature services other than `feature_services` from the registry together with the
+/// views only they use, returning the feature views with online tables.
+fn retain_feature_services(
+    registry: &mut Registry,
+    feature_services: &[String],
+) -> Result<BTreeSet<String>> {
+    let service_name = |service: &crate::feast::core::FeatureService| {
+        service
+            .spec
+            .as_ref()
+            .map(|spec| spec.name.clone())
+            .unwrap_or_default()
+    };
+    let known = registry
+        .feature_services
+        .iter()
+        .map(service_name)
+        .collect::<BTreeSet<_>>();
+    if let Some(missing) = feature_services.iter().find(|name| !known.contains(*name)) {
+        return Err(anyhow!("Feature service {} not found in registry", missing));
+    }
+    registry
+        .feature_services
+        .retain(|service| feature_services.contains(&service_name(service)));
+
+    let mut views = registry
+        .feature_services
+        .iter()
+        .filter_map(|service| service.spec.as_ref())
+        .flat_map(|spec| spec.features.iter())
+        .map(|projection| projection.feature_view_name.clone())
+        .collect::<BTreeSet<_>>();
+    registry.on_demand_feature_views.retain(|view| {
+        view.spec
+            .as_ref()
+            .is_some_and(|spec| views.contains(&spec.name))
+    });
+    for source in registry
+        .on_demand_feature_views
+        .iter()
+        .filter_map(|view| view.spec.as_ref())
+        .flat_map(|spec| spec.sources.values())
+    {
+        match &source.source {
+            Some(Source::FeatureView(view)) => {
+                views.extend(view.spec.as_ref().map(|spec| spec.name.clone()));
+            }
+            Some(Source::FeatureViewProjection(projection)) => {
+                views.insert(projection.feature_view_name.clone());
+            }
+            Some(Source::RequestDataSource(_)) | None => {}
+        }
+    }
+    registry.feature_views.retain(|view| {
+        view.spec
+            .as_ref()
+            .is_some_and(|spec| views.contains(&spec.name))
+    });
+    registry.stream_feature_views.retain(|view| {
+        view.spec
+            .as_ref()
+            .is_some_and(|spec| views.contains(&spec.name))
+    });
+
+    let feature_views = registry
+        .feature_views
+        .iter()
+        .filter_map(|view| view.spec.as_ref().map(|spec| spec.name.clone()))
+        .chain(
+            registry
+                .stream_feature_views
+                .iter()
+                .filter_map(|view| view.spec.as_ref().map(|spec| spec.name.clone())),
+        )
+        .collect();
+    Ok(feature_views)
+}
+
+async fn write_bundle(
+    path: &Path,
+    online_store_path: &str,
+    project: &str,
+    registry: &Registry,
+    feature_services: &[String],
+    feature_views: BTreeSet<String>,
+) -> Result<ExportSummary> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true);
+    let mut connection = SqliteConnection::connect_with(&options).await?;
+    sqlx::query("ATTACH DATABASE ? AS source")
+        .bind(online_store_path)
+        .execute(&mut connection)
+        .await
+        .with_context(|| format!("Failed to open online store {}", online_store_path))?;
+
+    let mut summary = ExportSummary {
+        feature_views: Vec::new(),
+        missing_tables: Vec::new(),
+        rows: 0,
+    };
+    let mut transaction = connection.begin().await?;
+    sqlx::query(&format!(
+        "CREATE TABLE {} (project TEXT NOT NULL, registry BLOB NOT NULL, \
+         feature_services TEXT NOT NULL, created_at timestamp NOT NULL)",
+        BUNDLE_TABLE
+    ))
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT INTO {} (project, registry, feature_services, created_at) VALUES (?, ?, ?, ?)",
+        BUNDLE_TABLE
+    ))
+    .bind(project)
+    .bind(registry.encode_to_vec())
+    .bind(feature_services.join(","))
+    .bind(Utc::now())
+    .execute(&mut *transaction)
+    .await?;
+
+    for view in feature_views {
+        let table_name = format!("{}_{}", project, view);
+        // Recreate the table with the schema and indexes it has in the online store
+        let statements: Vec<String> = sqlx::query_scalar(
+            "SELECT sql FROM source.sqlite_master WHERE tbl_name = ? AND sql IS NOT NULL \
+             ORDER BY type DESC",
+        )
+        .bind(&table_name)
+        .fetch_all(&mut *transaction)
+        .await?;
+        if statements.is_empty() {
+            tracing::warn!("Online store has no table {} to export", table_name);
+            summary.missing_tables.push(view);
+            continue;
+        }
+        for statement in statements {
+            sqlx::query(&statement).execute(&mut *transaction).await?;
+        }
+        let copied = sqlx::query(&format!(
+            "INSERT INTO main.\"{0}\" SELECT * FROM source.\"{0}\"",
+            table_name
+        ))
+        .execute(&mut *transaction)
+        .await?;
+        summary.rows += copied.rows_affected();
+        summary.feature_views.push(view);
+    }
+    transaction.commit().await?;
+    sqlx::query("DETACH DATABASE source")
+        .execute(&mut connection)
+        .await?;
+    connection.close().await?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature_store::FeatureStore;
+    use crate::model::{EntityIdValue, GetOnlineFeaturesRequest};
+
+    fn repo_config() -> Result<RepoConfig> {
+        RepoConfig::from_yaml_str(
+            r#"
+project: golden_hornet
+registry: registry.pb
+online_store:
+    type: sqlite
+    path: online_store.db
+"#,
+        )
+    }
+
+    fn test_data() -> String {
+        format!("{}/test_data", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn request() -> GetOnlineFeaturesRequest {
+        GetOnlineFeaturesRequest {
+            feature_service: Some("driver_activity_v4".to_string()),
+            entities: [("driver_id".to_string(), vec![EntityIdValue::Int(1005)])]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn serve_exported_bundle() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("bundle.db");
+        let summary = export_bundle(
+            &repo_config()?,
+            Some(&test_data()),
+            &["driver_activity_v4".to_string()],
+            &output,
+        )
+        .await?;
+        assert!(summary.rows > 0);
+        assert!(summary.missing_tables.is_empty(), "{:?}", summary);
+
+        let bundle = open_bundle(&output).await?;
+        assert_eq!(bundle.project, "golden_hornet");
+        assert_eq!(bundle.feature_services, vec!["driver_activity_v4"]);
+        let mut bundled_views = bundle
+            .registry
+            .feature_views()
+            .await?
+            .iter()
+            .map(|view| crate::intern::rodeo_ref().resolve(&view.name).to_string())
+            .collect::<Vec<_>>();
+        bundled_views.sort();
+        assert_eq!(bundled_views, summary.feature_views);
+
+        let bundled = FeatureStore::new(bundle.registry, bundle.online_store)
+            .get_online_features(request())
+            .await?;
+        let source = FeatureStore::builder()
+            .with_repo_config(repo_config()?)
+            .with_cwd(test_data())
+            .build()
+            .await?
+            .get_online_features(request())
+            .await?;
+        assert_eq!(bundled, source);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unknown_feature_service_is_rejected() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("bundle.db");
+        let err = export_bundle(
+            &repo_config()?,
+            Some(&test_data()),
+            &["missing_service".to_string()],
+            &output,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Feature service missing_service not found in registry"
+        );
+        assert!(!output.exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn missing_online_store_is_rejected() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("bundle.db");
+        let cwd = dir.path().to_str().unwrap();
+        let err = export_bundle(
+            &repo_config()?,
+            Some(cwd),
+            &["driver_activity_v4".to_string()],
+            &output,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Online store {}/online_store.db does not exist", cwd)
+        );
+        assert!(!output.exists());
+        assert!(!dir.path().join("online_store.db").exists());
+        Ok(())
+    }
+}
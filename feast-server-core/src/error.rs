@@ -9,6 +9,74 @@ pub enum FeastCoreError {
         feature_view_name: String,
         service_name: Option<String>,
     },
+    PushSourceNotFound {
+        name: String,
+    },
+    /// A requested name is not in the registry and the server no longer records new names
+    /// received in requests.
+    UnknownName {
+        name: String,
+    },
+    /// A requested entity value cannot be used for its entity column.
+    InvalidEntityValue {
+        entity: String,
+        value: String,
+        value_type: String,
+    },
+    /// Requested entities have different numbers of values.
+    EntityCountMismatch {
+        entity: String,
+        count: usize,
+        other_entity: String,
+        other_count: usize,
+    },
+    /// A request set an entity both by a configured alias and by the join key it stands for.
+    EntityAliasConflict {
+        alias: String,
+        join_key: String,
+    },
+    /// Several requested features map to the same response column name.
+    FeatureNameCollision {
+        feature_names: Vec<String>,
+        full_feature_names: bool,
+    },
+    /// A request pinned a registry version the server no longer keeps.
+    RegistryVersionNotFound {
+        version: u64,
+        current: u64,
+    },
+    /// A feature query cannot be parsed.
+    InvalidQuery {
+        message: String,
+    },
+    /// A request selected a project the server is not configured to serve.
+    ProjectNotAllowed {
+        project: String,
+    },
+    /// A request listed features and named a feature service, which select features in
+    /// different ways.
+    FeaturesWithFeatureService {
+        feature_service: String,
+    },
+    /// A replaying server has no recorded response to a request.
+    RecordedResponseNotFound {
+        file_name: String,
+    },
+    /// The estimated memory of a response exceeds the configured response budget.
+    ResponseTooLarge {
+        entity_rows: usize,
+        columns: usize,
+        estimated_bytes: u64,
+        max_bytes: u64,
+    },
+    /// Pushed data does not fit the feature views of its push source.
+    InvalidPushData {
+        message: String,
+    },
+    /// Probes of the online store failed repeatedly, so reads fail without trying it.
+    OnlineStoreUnavailable {
+        consecutive_failures: u32,
+    },
 }
 
 impl FeastCoreError {
@@ -33,10 +101,137 @@ impl FeastCoreError {
         }
     }
 
+    pub fn push_source_not_found(name: impl Into<String>) -> Self {
+        Self::PushSourceNotFound { name: name.into() }
+    }
+
+    pub fn unknown_name(name: impl Into<String>) -> Self {
+        Self::UnknownName { name: name.into() }
+    }
+
+    pub fn feature_name_collision(feature_names: Vec<String>, full_feature_names: bool) -> Self {
+        Self::FeatureNameCollision {
+            feature_names,
+            full_feature_names,
+        }
+    }
+
+    pub fn invalid_entity_value(
+        entity: impl Into<String>,
+        value: impl Into<String>,
+        value_type: impl Into<String>,
+    ) -> Self {
+        Self::InvalidEntityValue {
+            entity: entity.into(),
+            value: value.into(),
+            value_type: value_type.into(),
+        }
+    }
+
+    pub fn registry_version_not_found(version: u64, current: u64) -> Self {
+        Self::RegistryVersionNotFound { version, current }
+    }
+
+    pub fn invalid_query(message: impl Into<String>) -> Self {
+        Self::InvalidQuery {
+            message: message.into(),
+        }
+    }
+
+    pub fn project_not_allowed(project: impl Into<String>) -> Self {
+        Self::ProjectNotAllowed {
+            project: project.into(),
+        }
+    }
+
+    pub fn features_with_feature_service(feature_service: impl Into<String>) -> Self {
+        Self::FeaturesWithFeatureService {
+            feature_service: feature_service.into(),
+        }
+    }
+
+    pub fn recorded_response_not_found(file_name: impl Into<String>) -> Self {
+        Self::RecordedResponseNotFound {
+            file_name: file_name.into(),
+        }
+    }
+
+    pub fn entity_count_mismatch(
+        entity: impl Into<String>,
+        count: usize,
+        other_entity: impl Into<String>,
+        other_count: usize,
+    ) -> Self {
+        Self::EntityCountMismatch {
+            entity: entity.into(),
+            count,
+            other_entity: other_entity.into(),
+            other_count,
+        }
+    }
+
+    pub fn entity_alias_conflict(alias: impl Into<String>, join_key: impl Into<String>) -> Self {
+        Self::EntityAliasConflict {
+            alias: alias.into(),
+            join_key: join_key.into(),
+        }
+    }
+
+    pub fn response_too_large(
+        entity_rows: usize,
+        columns: usize,
+        estimated_bytes: u64,
+        max_bytes: u64,
+    ) -> Self {
+        Self::ResponseTooLarge {
+            entity_rows,
+            columns,
+            estimated_bytes,
+            max_bytes,
+        }
+    }
+
+    pub fn invalid_push_data(message: impl Into<String>) -> Self {
+        Self::InvalidPushData {
+            message: message.into(),
+        }
+    }
+
+    pub fn online_store_unavailable(consecutive_failures: u32) -> Self {
+        Self::OnlineStoreUnavailable {
+            consecutive_failures,
+        }
+    }
+
+    /// The error is temporary and the request can be retried later.
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, Self::OnlineStoreUnavailable { .. })
+    }
+
+    pub fn is_invalid_request(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidEntityValue { .. }
+                | Self::EntityCountMismatch { .. }
+                | Self::EntityAliasConflict { .. }
+                | Self::FeatureNameCollision { .. }
+                | Self::InvalidQuery { .. }
+                | Self::ProjectNotAllowed { .. }
+                | Self::FeaturesWithFeatureService { .. }
+                | Self::ResponseTooLarge { .. }
+                | Self::InvalidPushData { .. }
+        )
+    }
+
     pub fn is_not_found(&self) -> bool {
         matches!(
             self,
-            Self::FeatureServiceNotFound { .. } | Self::FeatureViewNotFound { .. }
+            Self::FeatureServiceNotFound { .. }
+                | Self::FeatureViewNotFound { .. }
+                | Self::PushSourceNotFound { .. }
+                | Self::UnknownName { .. }
+                | Self::RegistryVersionNotFound { .. }
+                | Self::RecordedResponseNotFound { .. }
         )
     }
 }
@@ -61,6 +256,99 @@ impl Display for FeastCoreError {
                     write!(f, "Feature view '{}' not found", feature_view_name)
                 }
             }
+            Self::PushSourceNotFound { name } => {
+                write!(f, "Push source '{}' not found", name)
+            }
+            Self::UnknownName { name } => {
+                write!(f, "'{}' not found in the registry", name)
+            }
+            Self::InvalidEntityValue {
+                entity,
+                value,
+                value_type,
+            } => write!(
+                f,
+                "Value {} of entity '{}' does not match its type {}",
+                value, entity, value_type
+            ),
+            Self::EntityCountMismatch {
+                entity,
+                count,
+                other_entity,
+                other_count,
+            } => write!(
+                f,
+                "Entity '{}' has {} values but entity '{}' has {}; every entity needs a value \
+                 per entity row",
+                entity, count, other_entity, other_count
+            ),
+            Self::EntityAliasConflict { alias, join_key } => write!(
+                f,
+                "Entity '{}' is an alias of '{}', which the request also sets",
+                alias, join_key
+            ),
+            Self::FeatureNameCollision {
+                feature_names,
+                full_feature_names,
+            } => {
+                writeln!(
+                    f,
+                    "Duplicate features named {} found.",
+                    feature_names.join(", ")
+                )?;
+                if *full_feature_names {
+                    write!(
+                        f,
+                        "To resolve this collision, please ensure that the feature views or \
+                         their own features have different names."
+                    )
+                } else {
+                    write!(
+                        f,
+                        "To resolve this collision, either use the full feature name by \
+                         setting 'full_feature_names=True', or ensure that the features in \
+                         question have different names."
+                    )
+                }
+            }
+            Self::RegistryVersionNotFound { version, current } => write!(
+                f,
+                "Registry version {} is no longer available, the current version is {}",
+                version, current
+            ),
+            Self::InvalidQuery { message } => write!(f, "Invalid query: {}", message),
+            Self::ProjectNotAllowed { project } => {
+                write!(f, "Project {} is not served by this server", project)
+            }
+            Self::FeaturesWithFeatureService { feature_service } => write!(
+                f,
+                "Request sets both features and feature service {}; only one of them can be set",
+                feature_service
+            ),
+            Self::RecordedResponseNotFound { file_name } => write!(
+                f,
+                "No response was recorded for this request (expected in {})",
+                file_name
+            ),
+            Self::ResponseTooLarge {
+                entity_rows,
+                columns,
+                estimated_bytes,
+                max_bytes,
+            } => write!(
+                f,
+                "Response of {} entity rows and {} columns is estimated at {} bytes, above the \
+                 response budget of {} bytes",
+                entity_rows, columns, estimated_bytes, max_bytes
+            ),
+            Self::InvalidPushData { message } => write!(f, "Invalid push data: {}", message),
+            Self::OnlineStoreUnavailable {
+                consecutive_failures,
+            } => write!(
+                f,
+                "Online store is unavailable after {} failed probes",
+                consecutive_failures
+            ),
         }
     }
 }
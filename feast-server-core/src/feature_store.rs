@@ -1,6 +1,30 @@
 //! Feature Store module. Contains main logic for feature retrieval and management.
 
+mod builder;
+mod entity_aliases;
+mod feature_matrix;
+mod feature_names;
 mod feature_store_impl;
+#[cfg(test)]
+mod golden_tests;
+mod key_format_check;
+mod read_stats;
+mod request_collapsing;
+mod response_budget;
 mod response_builder;
+mod response_cache;
+mod response_recording;
+mod server_metadata;
+mod slow_requests;
+mod table_check;
 
+pub use builder::FeatureStoreBuilder;
+pub use feature_matrix::{ColumnValues, FeatureColumn, FeatureMatrix};
+pub(crate) use feature_names::full_feature_name;
 pub use feature_store_impl::FeatureStore;
+pub use key_format_check::KeyFormatCheck;
+pub use read_stats::{FeatureViewReadStats, ReadStatsSnapshot};
+pub use response_cache::ResponseCacheStats;
+pub use response_recording::ResponseRecording;
+pub use server_metadata::{BuildInfo, EffectiveConfig, ServerMetadata};
+pub use slow_requests::{RequestDiagnostics, RequestShape, SlowRequest, StoreCallTiming};
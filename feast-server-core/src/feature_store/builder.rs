@@ -0,0 +1,286 @@
+use super::{EffectiveConfig, FeatureStore};
+use crate::config::{CircuitBreakerConfig, OnlineStoreConfig, RepoConfig};
+use crate::onlinestore::circuit_breaker::CircuitBreakerOnlineStore;
+use crate::onlinestore::in_memory::InMemoryOnlineStore;
+use crate::onlinestore::{OnlineStore, get_online_store};
+use crate::registry::{FeatureRegistryService, get_registry};
+use anyhow::{Result, anyhow};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Builder of a [`FeatureStore`] from a parsed `feature_store.yaml`:
+///
+/// ```no_run
+/// # async fn example(config: feast_server_core::config::RepoConfig) -> anyhow::Result<()> {
+/// use feast_server_core::feature_store::FeatureStore;
+///
+/// let store = FeatureStore::builder()
+///     .with_repo_config(config)
+///     .with_cwd("/path/to/feature_repo")
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Secret references in the config are not resolved; pass the config through
+/// [`crate::secrets::resolve_secrets`] first when it contains any.
+#[derive(Default)]
+pub struct FeatureStoreBuilder {
+    repo_config: Option<RepoConfig>,
+    cwd: Option<PathBuf>,
+    registry: Option<Arc<dyn FeatureRegistryService>>,
+    online_store: Option<Arc<dyn OnlineStore>>,
+}
+
+impl FeatureStoreBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_repo_config(mut self, repo_config: RepoConfig) -> Self {
+        self.repo_config = Some(repo_config);
+        self
+    }
+
+    /// Directory relative registry and SQLite paths of the config are resolved against,
+    /// normally the feature repository.
+    pub fn with_cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    /// Use `registry` instead of the registry described by the config.
+    pub fn with_registry(mut self, registry: Arc<dyn FeatureRegistryService>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Use `online_store` instead of the online store described by the config.
+    pub fn with_online_store(mut self, online_store: Arc<dyn OnlineStore>) -> Self {
+        self.online_store = Some(online_store);
+        self
+    }
+
+    /// Connect to the registry and online store and apply the serving options of the config.
+    pub async fn build(self) -> Result<FeatureStore> {
+        let cwd = self
+            .cwd
+            .as_deref()
+            .map(|cwd| {
+                cwd.to_str()
+                    .ok_or_else(|| anyhow!("Feature repository path contains invalid UTF-8"))
+            })
+            .transpose()?;
+        let config = self.repo_config.as_ref();
+        let missing_config = |component: &str| {
+            anyhow!(
+                "A repo config or an explicit {} is required to build a feature store",
+                component
+            )
+        };
+
+        let registry = match self.registry {
+            Some(registry) => registry,
+            None => {
+                let config = config.ok_or_else(|| missing_config("registry"))?;
+                get_registry(config.registry.clone(), config.project.clone(), cwd).await?
+            }
+        };
+        let online_store = match self.online_store {
+            Some(online_store) => online_store,
+            None => {
+                let config = config.ok_or_else(|| missing_config("online store"))?;
+                match &config.online_store {
+                    // CSV fixture cells are read as the registry types of their columns
+                    OnlineStoreConfig::InMemory { fixtures } => Arc::new(
+                        InMemoryOnlineStore::from_registry_fixtures(fixtures, cwd, &*registry)
+                            .await?,
+                    ),
+                    online_store => get_online_store(online_store, &config.project, cwd).await?,
+                }
+            }
+        };
+
+        // Reads only fall back to the secondary store while the circuit is open
+        let circuit_breaker = config.and_then(|config| match &config.circuit_breaker {
+            Some(circuit_breaker) => Some(circuit_breaker.clone()),
+            None => config
+                .secondary_online_store
+                .as_ref()
+                .map(|_| CircuitBreakerConfig::default()),
+        });
+        let online_store = match circuit_breaker {
+            Some(circuit_breaker) => Arc::new(CircuitBreakerOnlineStore::new(
+                online_store,
+                circuit_breaker,
+            )),
+            None => online_store,
+        };
+
+        let mut feature_store = FeatureStore::new(registry, online_store);
+        if let Some(config) = config {
+            feature_store = feature_store.with_project(&config.project);
+            if let Some(secondary) = &config.secondary_online_store {
+                let secondary = get_online_store(secondary, &config.project, cwd).await?;
+                feature_store = feature_store.with_secondary_online_store(secondary);
+            }
+            for project in &config.allowed_projects {
+                if project == &config.project {
+                    continue;
+                }
+                // Stores key their values by project, so every project gets its own store
+                let online_store = get_online_store(&config.online_store, project, cwd).await?;
+                feature_store = feature_store.with_project_online_store(project, online_store);
+            }
+            feature_store = feature_store
+                .with_entity_key_coercion(config.entity_key_coercion)
+                .with_entity_count_mismatch(config.entity_count_mismatch)
+                .with_partial_results(config.partial_results);
+            if let Some(cache_config) = &config.response_cache {
+                feature_store = feature_store.with_response_cache(cache_config);
+            }
+            feature_store = feature_store
+                .with_readiness(&config.readiness)
+                .with_ttl_overrides(&config.projection_ttl_seconds)
+                .with_tombstone_views(&config.tombstone_feature_views)
+                .with_lenient_decoding_views(&config.lenient_decoding_feature_views)
+                .with_feature_defaults(&config.load_feature_defaults(cwd)?)
+                .with_entity_aliases(&config.load_entity_aliases(cwd)?)
+                .with_debug_responses(config.debug_responses)
+                .with_effective_config(EffectiveConfig::new(config));
+            if let Some(slow_requests) = &config.slow_requests {
+                feature_store = feature_store.with_slow_requests(slow_requests);
+            }
+            if let Some(budget) = &config.response_budget {
+                feature_store = feature_store.with_response_budget(budget);
+            }
+            crate::intern::set_request_string_limit(config.max_interned_request_strings);
+            crate::payload_sizes::set_report(&config.runtime.payload_size_report);
+        }
+        crate::intern::record_metrics();
+        if let Err(err) = feature_store.check_online_tables().await {
+            tracing::warn!("Failed to check online tables: {:#}", err);
+        }
+        if let Err(err) = feature_store.check_feature_defaults().await {
+            tracing::warn!("Failed to check feature defaults: {:#}", err);
+        }
+        if let Some(config) = config {
+            let version = &config.entity_key_serialization_version;
+            if let Err(err) = feature_store.check_entity_key_format(version).await {
+                tracing::warn!(
+                    "Failed to check the format of stored entity keys: {:#}",
+                    err
+                );
+            }
+        }
+        Ok(feature_store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{EntityIdValue, FeatureStatus, GetOnlineFeaturesRequest};
+    use crate::registry::FileFeatureRegistry;
+    use std::path::Path;
+
+    fn test_data() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data")
+    }
+
+    fn request() -> GetOnlineFeaturesRequest {
+        GetOnlineFeaturesRequest {
+            entities: [("driver_id".to_string(), vec![EntityIdValue::Int(1001)])]
+                .into_iter()
+                .collect(),
+            features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn build_from_repo_config() -> Result<()> {
+        let config = RepoConfig::from_yaml_str(
+            r#"
+project: golden_hornet
+registry: registry.pb
+online_store:
+    type: sqlite
+    path: online_store.db
+"#,
+        )?;
+        let store = FeatureStore::builder()
+            .with_repo_config(config)
+            .with_cwd(test_data())
+            .build()
+            .await?;
+
+        let response = store.get_online_features(request()).await?;
+        assert_eq!(response.metadata.feature_names, ["driver_id", "conv_rate"]);
+        assert_ne!(response.results[1].statuses[0], FeatureStatus::NotFound);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn requests_select_allowed_projects() -> Result<()> {
+        let config = RepoConfig::from_yaml_str(
+            r#"
+project: golden_hornet
+registry: registry.pb
+online_store:
+    type: sqlite
+    path: online_store.db
+allowed_projects: [golden_hornet_eu]
+"#,
+        )?;
+        let store = FeatureStore::builder()
+            .with_repo_config(config)
+            .with_cwd(test_data())
+            .build()
+            .await?;
+
+        let selecting = |project: &str| GetOnlineFeaturesRequest {
+            project: Some(project.to_string()),
+            ..request()
+        };
+        let response = store
+            .get_online_features(selecting("golden_hornet"))
+            .await?;
+        assert_ne!(response.results[1].statuses[0], FeatureStatus::NotFound);
+        // The online store holds no tables of the other project
+        let response = store
+            .get_online_features(selecting("golden_hornet_eu"))
+            .await?;
+        assert_eq!(response.results[1].statuses, [FeatureStatus::NotFound]);
+        let err = store
+            .get_online_features(selecting("other"))
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<crate::error::FeastCoreError>().unwrap();
+        assert!(err.is_invalid_request());
+        assert_eq!(
+            err.to_string(),
+            "Project other is not served by this server"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn build_requires_config_or_components() -> Result<()> {
+        let registry = Arc::new(FileFeatureRegistry::from_path(
+            &test_data().join("registry.pb"),
+        )?);
+        let err = FeatureStore::builder()
+            .with_registry(registry)
+            .build()
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "A repo config or an explicit online store is required to build a feature store"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+//! Entity names of client API contracts translated to the join keys of the registry, e.g.
+//! `user_id` to `customer_id`, configured with `entity_aliases`.
+
+use crate::error::FeastCoreError;
+use crate::model::{EntityIdValue, OrderedMap};
+use rustc_hash::FxHashMap as HashMap;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default)]
+pub(crate) struct EntityAliases {
+    /// Join key of each alias.
+    join_keys: HashMap<String, String>,
+}
+
+impl EntityAliases {
+    pub(crate) fn new(aliases: &BTreeMap<String, String>) -> Self {
+        Self {
+            join_keys: aliases
+                .iter()
+                .map(|(alias, join_key)| (alias.clone(), join_key.clone()))
+                .collect(),
+        }
+    }
+
+    /// Rename the aliased entities of a request to their join keys, keeping their order.
+    /// Returns the renamed entities as (join key, alias) pairs, so that their response
+    /// columns can be given the requested names back.
+    pub(crate) fn translate(
+        &self,
+        entities: &mut OrderedMap<String, Vec<EntityIdValue>>,
+    ) -> Result<Vec<(String, String)>, FeastCoreError> {
+        if !entities
+            .keys()
+            .any(|name| self.join_keys.contains_key(name))
+        {
+            return Ok(Vec::new());
+        }
+        let mut renamed = Vec::new();
+        let mut translated =
+            OrderedMap::with_capacity_and_hasher(entities.len(), Default::default());
+        for (name, values) in entities.drain(..) {
+            let name = match self.join_keys.get(&name) {
+                Some(join_key) => {
+                    renamed.push((join_key.clone(), name));
+                    join_key.clone()
+                }
+                None => name,
+            };
+            if translated.contains_key(&name) {
+                let alias = renamed
+                    .iter()
+                    .find(|(join_key, _)| *join_key == name)
+                    .map(|(_, alias)| alias.clone())
+                    .unwrap_or_default();
+                return Err(FeastCoreError::entity_alias_conflict(alias, name));
+            }
+            translated.insert(name, values);
+        }
+        *entities = translated;
+        Ok(renamed)
+    }
+
+    /// Give the entity columns among `column_names` their requested names back.
+    pub(crate) fn restore(column_names: &mut [String], renamed: &[(String, String)]) {
+        for name in column_names {
+            if let Some((_, alias)) = renamed.iter().find(|(join_key, _)| join_key == name) {
+                *name = alias.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_are_translated_and_restored() {
+        let aliases = EntityAliases::new(&BTreeMap::from([(
+            "user_id".to_string(),
+            "customer_id".to_string(),
+        )]));
+        let mut entities = OrderedMap::from_iter([
+            ("user_id".to_string(), vec![EntityIdValue::Int(1)]),
+            ("driver_id".to_string(), vec![EntityIdValue::Int(1001)]),
+        ]);
+        let renamed = aliases.translate(&mut entities).unwrap();
+        assert_eq!(
+            entities.keys().collect::<Vec<_>>(),
+            ["customer_id", "driver_id"]
+        );
+        assert_eq!(
+            renamed,
+            [("customer_id".to_string(), "user_id".to_string())]
+        );
+
+        let mut columns = vec!["customer_id".to_string(), "driver_id".to_string()];
+        EntityAliases::restore(&mut columns, &renamed);
+        assert_eq!(columns, ["user_id", "driver_id"]);
+
+        let mut conflicting = OrderedMap::from_iter([
+            ("customer_id".to_string(), vec![EntityIdValue::Int(1)]),
+            ("user_id".to_string(), vec![EntityIdValue::Int(2)]),
+        ]);
+        let err = aliases.translate(&mut conflicting).unwrap_err();
+        assert!(err.is_invalid_request());
+    }
+}
@@ -0,0 +1,390 @@
+//! Typed columnar view of online feature responses, for code embedding the feature store
+//! rather than serving it.
+
+use crate::feast::types::value::Val;
+use crate::feast::types::value_type::Enum as ValueType;
+use crate::feast::types::{
+    BoolList, BytesList, DoubleList, FloatList, Int32List, Int64List, StringList, Value,
+};
+use crate::model::{
+    FeatureResults, FeatureStatus, GetOnlineFeatureResponse, GetOnlineFeatureResponseMetadata,
+    ValueWrapper,
+};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+
+fn timestamp(seconds: i64) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp(seconds, 0)
+        .ok_or_else(|| anyhow!("Timestamp {} is out of range", seconds))
+}
+
+macro_rules! column_values {
+    ($($variant:ident($ty:ty) <=> $proto:ident: |$from:ident| $from_expr:expr, |$to:ident| $to_expr:expr;)*) => {
+        /// Values of one column. Rows without a value are `None`.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum ColumnValues {
+            /// Column whose rows all lack a value, so that its type is unknown.
+            Null(usize),
+            $($variant(Vec<Option<$ty>>),)*
+        }
+
+        impl ColumnValues {
+            pub fn len(&self) -> usize {
+                match self {
+                    Self::Null(len) => *len,
+                    $(Self::$variant(values) => values.len(),)*
+                }
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            fn type_name(&self) -> &'static str {
+                match self {
+                    Self::Null(_) => "Null",
+                    $(Self::$variant(_) => stringify!($variant),)*
+                }
+            }
+
+            /// Empty column of the declared type `value_type`, or `None` for types without
+            /// column, e.g. sets and maps.
+            fn of_type(value_type: ValueType) -> Option<Self> {
+                match value_type {
+                    $(ValueType::$variant => Some(Self::$variant(Vec::new())),)*
+                    _ => None,
+                }
+            }
+
+            /// Empty column of the type of `val`, with `nulls` leading rows without value.
+            fn typed_like(val: &Val, nulls: usize) -> Self {
+                match val {
+                    $(Val::$proto(_) => Self::$variant(vec![None; nulls]),)*
+                    Val::NullVal(_) => Self::Null(nulls),
+                }
+            }
+
+            fn push(&mut self, val: Option<Val>) -> Result<()> {
+                let val = val.filter(|val| !matches!(val, Val::NullVal(_)));
+                if let (Self::Null(nulls), Some(val)) = (&*self, &val) {
+                    *self = Self::typed_like(val, *nulls);
+                }
+                match (self, val) {
+                    (Self::Null(len), None) => *len += 1,
+                    $((Self::$variant(values), None) => values.push(None),)*
+                    $((Self::$variant(values), Some(Val::$proto($from))) => {
+                        values.push(Some($from_expr))
+                    })*
+                    (column, Some(val)) => {
+                        return Err(anyhow!(
+                            "{} value {:?} in a column of {} values",
+                            ColumnValues::typed_like(&val, 0).type_name(),
+                            val,
+                            column.type_name()
+                        ));
+                    }
+                }
+                Ok(())
+            }
+
+            fn into_proto(self) -> Vec<Value> {
+                let value = |val: Option<Val>| Value {
+                    val: Some(val.unwrap_or(Val::NullVal(0))),
+                };
+                match self {
+                    Self::Null(len) => vec![value(None); len],
+                    $(Self::$variant(values) => values
+                        .into_iter()
+                        .map(|v| value(v.map(|$to| Val::$proto($to_expr))))
+                        .collect(),)*
+                }
+            }
+        }
+    };
+}
+
+column_values! {
+    Bytes(Vec<u8>) <=> BytesVal: |v| v, |v| v;
+    String(String) <=> StringVal: |v| v, |v| v;
+    Int32(i32) <=> Int32Val: |v| v, |v| v;
+    Int64(i64) <=> Int64Val: |v| v, |v| v;
+    Double(f64) <=> DoubleVal: |v| v, |v| v;
+    Float(f32) <=> FloatVal: |v| v, |v| v;
+    Bool(bool) <=> BoolVal: |v| v, |v| v;
+    UnixTimestamp(DateTime<Utc>) <=> UnixTimestampVal: |v| timestamp(v)?, |v| v.timestamp();
+    BytesList(Vec<Vec<u8>>) <=> BytesListVal: |v| v.val, |v| BytesList { val: v };
+    StringList(Vec<String>) <=> StringListVal: |v| v.val, |v| StringList { val: v };
+    Int32List(Vec<i32>) <=> Int32ListVal: |v| v.val, |v| Int32List { val: v };
+    Int64List(Vec<i64>) <=> Int64ListVal: |v| v.val, |v| Int64List { val: v };
+    DoubleList(Vec<f64>) <=> DoubleListVal: |v| v.val, |v| DoubleList { val: v };
+    FloatList(Vec<f32>) <=> FloatListVal: |v| v.val, |v| FloatList { val: v };
+    BoolList(Vec<bool>) <=> BoolListVal: |v| v.val, |v| BoolList { val: v };
+    UnixTimestampList(Vec<DateTime<Utc>>) <=> UnixTimestampListVal:
+        |v| v.val.into_iter().map(timestamp).collect::<Result<_>>()?,
+        |v| Int64List { val: v.iter().map(DateTime::timestamp).collect() };
+}
+
+/// One requested feature or entity of a response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureColumn {
+    /// Name of the column as in the response metadata.
+    pub name: String,
+    pub values: ColumnValues,
+    /// Empty when the request left out statuses.
+    pub statuses: Vec<FeatureStatus>,
+    /// Empty when the request left out timestamps.
+    pub event_timestamps: Vec<DateTime<Utc>>,
+    /// Empty unless the request asked for created timestamps.
+    pub created_timestamps: Vec<Option<DateTime<Utc>>>,
+}
+
+/// Online features as typed columns, in the order of the response metadata.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeatureMatrix {
+    pub columns: Vec<FeatureColumn>,
+    pub warnings: Vec<String>,
+}
+
+impl FeatureMatrix {
+    pub fn column(&self, name: &str) -> Option<&FeatureColumn> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.columns.first().map_or(0, |column| column.values.len())
+    }
+
+    /// Columns of `response` of the types `type_of` declares by column name. Columns of
+    /// undeclared types take the type of their first value, and stay [`ColumnValues::Null`]
+    /// without values.
+    pub(crate) fn with_types(
+        response: GetOnlineFeatureResponse,
+        type_of: impl Fn(&str) -> Option<ValueType>,
+    ) -> Result<Self> {
+        let columns = response
+            .metadata
+            .feature_names
+            .into_iter()
+            .zip(response.results)
+            .map(|(name, result)| {
+                let mut values = type_of(&name)
+                    .and_then(ColumnValues::of_type)
+                    .unwrap_or(ColumnValues::Null(0));
+                for value in result.values {
+                    values
+                        .push(value.0.val)
+                        .map_err(|err| anyhow!("Feature {}: {}", name, err))?;
+                }
+                Ok(FeatureColumn {
+                    name,
+                    values,
+                    statuses: result.statuses,
+                    event_timestamps: result.event_timestamps,
+                    created_timestamps: result.created_timestamps,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            columns,
+            warnings: response.warnings,
+        })
+    }
+}
+
+/// Columns typed by their values, for responses not resolved against a registry.
+impl TryFrom<GetOnlineFeatureResponse> for FeatureMatrix {
+    type Error = anyhow::Error;
+
+    fn try_from(response: GetOnlineFeatureResponse) -> Result<Self> {
+        Self::with_types(response, |_| None)
+    }
+}
+
+/// Response holding the values of `matrix`, which the JSON, msgpack and protobuf encoders
+/// of the servers take.
+impl From<FeatureMatrix> for GetOnlineFeatureResponse {
+    fn from(matrix: FeatureMatrix) -> Self {
+        let mut feature_names = Vec::with_capacity(matrix.columns.len());
+        let mut results = Vec::with_capacity(matrix.columns.len());
+        for column in matrix.columns {
+            feature_names.push(column.name);
+            results.push(FeatureResults {
+                values: column
+                    .values
+                    .into_proto()
+                    .into_iter()
+                    .map(ValueWrapper)
+                    .collect(),
+                statuses: column.statuses,
+                event_timestamps: column.event_timestamps,
+                created_timestamps: column.created_timestamps,
+            });
+        }
+        Self {
+            metadata: GetOnlineFeatureResponseMetadata { feature_names },
+            results,
+            warnings: matrix.warnings,
+            feature_service_version: None,
+            registry_version: None,
+            debug: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(val: Option<Val>) -> ValueWrapper {
+        ValueWrapper(Value { val })
+    }
+
+    fn response(columns: Vec<(&str, Vec<Option<Val>>)>) -> GetOnlineFeatureResponse {
+        let (names, results): (Vec<_>, Vec<_>) = columns
+            .into_iter()
+            .map(|(name, values)| {
+                let statuses = values
+                    .iter()
+                    .map(|val| match val {
+                        None | Some(Val::NullVal(_)) => FeatureStatus::NotFound,
+                        Some(_) => FeatureStatus::Present,
+                    })
+                    .collect();
+                let result = FeatureResults {
+                    values: values.into_iter().map(value).collect(),
+                    statuses,
+                    event_timestamps: Vec::new(),
+                    created_timestamps: Vec::new(),
+                };
+                (name.to_string(), result)
+            })
+            .unzip();
+        GetOnlineFeatureResponse {
+            metadata: GetOnlineFeatureResponseMetadata {
+                feature_names: names,
+            },
+            results,
+            warnings: vec!["partial".to_string()],
+            feature_service_version: None,
+            registry_version: None,
+            debug: None,
+        }
+    }
+
+    #[test]
+    fn typed_columns_round_trip() -> Result<()> {
+        let response = response(vec![
+            (
+                "driver_id",
+                vec![Some(Val::Int64Val(1001)), Some(Val::Int64Val(1002))],
+            ),
+            (
+                "conv_rate",
+                vec![Some(Val::FloatVal(0.5)), Some(Val::NullVal(0))],
+            ),
+            ("unknown", vec![None, Some(Val::NullVal(0))]),
+            (
+                "trips",
+                vec![None, Some(Val::Int32ListVal(Int32List { val: vec![1, 2] }))],
+            ),
+        ]);
+
+        let matrix = FeatureMatrix::try_from(response.clone())?;
+        assert_eq!(matrix.num_rows(), 2);
+        assert_eq!(
+            matrix.column("driver_id").unwrap().values,
+            ColumnValues::Int64(vec![Some(1001), Some(1002)])
+        );
+        assert_eq!(
+            matrix.column("conv_rate").unwrap().values,
+            ColumnValues::Float(vec![Some(0.5), None])
+        );
+        assert_eq!(
+            matrix.column("unknown").unwrap().values,
+            ColumnValues::Null(2)
+        );
+        assert_eq!(
+            matrix.column("trips").unwrap().values,
+            ColumnValues::Int32List(vec![None, Some(vec![1, 2])])
+        );
+        assert_eq!(
+            matrix.column("trips").unwrap().statuses,
+            [FeatureStatus::NotFound, FeatureStatus::Present]
+        );
+        assert_eq!(matrix.warnings, ["partial"]);
+
+        // Rows without value come back as explicit nulls
+        let round_trip = GetOnlineFeatureResponse::from(matrix);
+        assert_eq!(round_trip.metadata, response.metadata);
+        assert_eq!(round_trip.results[0], response.results[0]);
+        assert_eq!(
+            round_trip.results[2].values,
+            [value(Some(Val::NullVal(0))), value(Some(Val::NullVal(0)))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mixed_column_types_are_rejected() {
+        let response = response(vec![(
+            "conv_rate",
+            vec![Some(Val::FloatVal(0.5)), Some(Val::StringVal("a".into()))],
+        )]);
+        let err = FeatureMatrix::try_from(response).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Feature conv_rate: String value StringVal(\"a\") in a column of Float values"
+        );
+    }
+
+    #[tokio::test]
+    async fn store_columns_have_registry_types() -> Result<()> {
+        use crate::config::RepoConfig;
+        use crate::feature_store::FeatureStore;
+        use crate::model::{EntityIdValue, GetOnlineFeaturesRequest};
+
+        let config = RepoConfig::from_yaml_str(
+            "project: golden_hornet\nregistry: registry.pb\nonline_store:\n    type: sqlite\n    path: online_store.db\n",
+        )?;
+        let store = FeatureStore::builder()
+            .with_repo_config(config)
+            .with_cwd(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data"))
+            .build()
+            .await?;
+        let request = |driver_ids: Vec<i64>| GetOnlineFeaturesRequest {
+            entities: [(
+                "driver_id".to_string(),
+                driver_ids.into_iter().map(EntityIdValue::Int).collect(),
+            )]
+            .into_iter()
+            .collect(),
+            features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+            full_feature_names: Some(true),
+            ..Default::default()
+        };
+
+        let matrix = store.get_online_features_typed(request(vec![1001])).await?;
+        assert_eq!(
+            matrix.column("driver_id").unwrap().values,
+            ColumnValues::Int64(vec![Some(1001)])
+        );
+        assert!(matches!(
+            matrix
+                .column("driver_hourly_stats__conv_rate")
+                .unwrap()
+                .values,
+            ColumnValues::Float(_)
+        ));
+
+        // Without any stored value the column still has the declared type
+        let missing = store.get_online_features_typed(request(vec![-1])).await?;
+        assert_eq!(
+            missing
+                .column("driver_hourly_stats__conv_rate")
+                .unwrap()
+                .values,
+            ColumnValues::Float(vec![None])
+        );
+        Ok(())
+    }
+}
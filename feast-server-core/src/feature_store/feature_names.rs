@@ -0,0 +1,195 @@
+//! Column names of features in online responses.
+//!
+//! Follows the Feast reference implementation: with `full_feature_names` every feature,
+//! found, missing or entity-less, is named `{view}__{feature}`, where `{view}` is the alias
+//! the feature service projection gives the view or the view name otherwise. Without it the
+//! bare feature name is used.
+
+use crate::error::FeastCoreError;
+use crate::intern;
+use crate::model::{Feature, FeatureView, OrderedMap};
+use lasso::Spur;
+use rustc_hash::FxHashMap as HashMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Separator between view and feature names in full feature names.
+const FULL_NAME_SEPARATOR: &str = "__";
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FeatureNaming {
+    full_feature_names: bool,
+}
+
+impl FeatureNaming {
+    pub(crate) fn new(full_feature_names: bool) -> Self {
+        Self { full_feature_names }
+    }
+
+    /// Response column name of `feature`. `view` is the view the feature was resolved
+    /// from, carrying the projection alias; the feature's view name is used without it.
+    pub(crate) fn column_name(&self, feature: &Feature, view: Option<&FeatureView>) -> Spur {
+        if !self.full_feature_names {
+            return feature.feature_name;
+        }
+        let rodeo = intern::rodeo_ref();
+        let view_name = view
+            .map(FeatureView::name_to_use)
+            .unwrap_or(feature.feature_view_name);
+        rodeo.get_or_intern(full_feature_name(
+            rodeo.resolve(&view_name),
+            rodeo.resolve(&feature.feature_name),
+        ))
+    }
+
+    /// Fail with [`FeastCoreError::FeatureNameCollision`] when several requested features
+    /// would be returned under the same column name, as Feast does instead of letting one
+    /// column shadow another.
+    pub(crate) fn check_collisions(
+        &self,
+        feature_to_view: &OrderedMap<Feature, Arc<FeatureView>>,
+    ) -> Result<(), FeastCoreError> {
+        let mut seen: HashMap<Spur, &Feature> = HashMap::default();
+        let mut collisions = BTreeSet::new();
+        for (feature, view) in feature_to_view {
+            let name = self.column_name(feature, Some(view));
+            if let Some(other) = seen.insert(name, feature)
+                && other != feature
+            {
+                collisions.insert(intern::rodeo_ref().resolve(&name).to_string());
+            }
+        }
+        if collisions.is_empty() {
+            Ok(())
+        } else {
+            Err(FeastCoreError::feature_name_collision(
+                collisions.into_iter().collect(),
+                self.full_feature_names,
+            ))
+        }
+    }
+}
+
+pub(crate) fn full_feature_name(view_name: &str, feature_name: &str) -> String {
+    format!("{}{}{}", view_name, FULL_NAME_SEPARATOR, feature_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intern::rodeo;
+    use crate::model::DUMMY_ENTITY_NAME;
+
+    fn view(name: &str, alias: Option<&str>, entity: &str) -> FeatureView {
+        FeatureView {
+            name: rodeo().get_or_intern(name),
+            name_alias: alias.map(|alias| rodeo().get_or_intern(alias)),
+            entity_names: vec![rodeo().get_or_intern(entity)],
+            ..Default::default()
+        }
+    }
+
+    fn column_name(full: bool, feature: &Feature, view: Option<&FeatureView>) -> &'static str {
+        intern::rodeo_ref().resolve(&FeatureNaming::new(full).column_name(feature, view))
+    }
+
+    /// Expected names taken from `OnlineResponse` of the Feast Python SDK for the same
+    /// requests.
+    #[test]
+    fn names_match_reference_implementation() {
+        let plain = view("driver_hourly_stats", None, "driver");
+        let aliased = view("driver_hourly_stats_fresh", Some("driver_alias"), "driver");
+        let entity_less = view("global_stats", None, DUMMY_ENTITY_NAME);
+        let aliased_entity_less = view("global_stats", Some("totals"), DUMMY_ENTITY_NAME);
+        let cases = [
+            (&plain, "conv_rate", true, "driver_hourly_stats__conv_rate"),
+            (&plain, "conv_rate", false, "conv_rate"),
+            (&aliased, "acc_rate", true, "driver_alias__acc_rate"),
+            (&aliased, "acc_rate", false, "acc_rate"),
+            (
+                &entity_less,
+                "active_drivers",
+                true,
+                "global_stats__active_drivers",
+            ),
+            (&entity_less, "active_drivers", false, "active_drivers"),
+            (
+                &aliased_entity_less,
+                "active_drivers",
+                true,
+                "totals__active_drivers",
+            ),
+            (
+                &aliased_entity_less,
+                "active_drivers",
+                false,
+                "active_drivers",
+            ),
+        ];
+        for (view, feature_name, full, expected) in cases {
+            let feature = Feature::new(view.name, rodeo().get_or_intern(feature_name));
+            assert_eq!(
+                column_name(full, &feature, Some(view)),
+                expected,
+                "{:?} with full_feature_names={}",
+                feature,
+                full
+            );
+        }
+    }
+
+    fn requested(
+        views: &[&FeatureView],
+        feature_name: &str,
+    ) -> OrderedMap<Feature, Arc<FeatureView>> {
+        views
+            .iter()
+            .map(|view| {
+                let feature = Feature::new(view.name, rodeo().get_or_intern(feature_name));
+                (feature, Arc::new((*view).clone()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detect_colliding_feature_names() {
+        let hourly = view("driver_hourly_stats", None, "driver");
+        let fresh = view("driver_hourly_stats_fresh", None, "driver");
+        let features = requested(&[&hourly, &fresh], "conv_rate");
+
+        assert_eq!(
+            FeatureNaming::new(false).check_collisions(&features),
+            Err(FeastCoreError::feature_name_collision(
+                vec!["conv_rate".to_string()],
+                false
+            ))
+        );
+        assert_eq!(FeatureNaming::new(true).check_collisions(&features), Ok(()));
+    }
+
+    #[test]
+    fn detect_colliding_aliases() {
+        let hourly = view("driver_hourly_stats", Some("drivers"), "driver");
+        let fresh = view("driver_hourly_stats_fresh", Some("drivers"), "driver");
+        let features = requested(&[&hourly, &fresh], "conv_rate");
+
+        let err = FeatureNaming::new(true)
+            .check_collisions(&features)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            FeastCoreError::feature_name_collision(vec!["drivers__conv_rate".to_string()], true)
+        );
+        assert!(err.is_invalid_request());
+    }
+
+    #[test]
+    fn unknown_view_falls_back_to_feature_view_name() {
+        let feature = Feature::from_names("missing_view", "some__feature");
+        assert_eq!(
+            column_name(true, &feature, None),
+            "missing_view__some__feature"
+        );
+        assert_eq!(column_name(false, &feature, None), "some__feature");
+    }
+}
@@ -1,25 +1,96 @@
+use super::FeatureStoreBuilder;
+use super::entity_aliases::EntityAliases;
+use super::feature_matrix::FeatureMatrix;
+use super::feature_names::FeatureNaming;
+use super::key_format_check::{KeyFormatCheck, check_key_format};
+use super::read_stats::{ReadStats, ReadStatsSnapshot};
+use super::request_collapsing::RequestCollapser;
+use super::response_budget::ResponseBudget;
+use super::response_cache::ResponseCache;
+use super::response_recording::{self, ResponseRecording};
+use super::server_metadata::{BuildInfo, EffectiveConfig, ServerMetadata};
+use super::slow_requests::{RequestShape, RequestTimings, SlowRequest, SlowRequestLog, StoreCall};
+use super::table_check::TableCheck;
+use crate::config::{
+    EntityCountMismatch, EntityKeyCoercion, EntityKeySerializationVersion, ReadinessConfig,
+    ResponseBudgetConfig, ResponseCacheConfig, SlowRequestsConfig,
+};
+use crate::error::FeastCoreError;
 use crate::feast::types::value::Val;
 use crate::feast::types::{EntityKey, Value, value_type};
 use crate::intern;
 use crate::model;
 use crate::model::{
-    DUMMY_ENTITY_ID, DUMMY_ENTITY_VAL, EntityIdValue, Feature, FeatureType, FeatureView,
-    GetOnlineFeatureResponse, GetOnlineFeaturesRequest, HashEntityKey, RequestedFeatures,
+    DUMMY_ENTITY_ID, DUMMY_ENTITY_VAL, DeleteEntityKeysRequest, DeleteEntityKeysResponse,
+    DeletedEntityKeys, EntityIdValue, Feature, FeatureFreshnessRequest, FeatureFreshnessResponse,
+    FeatureType, FeatureView, GetOnlineFeatureResponse, GetOnlineFeaturesRequest, HashEntityKey,
+    OrderedMap, PushMode, PushRequest, RequestedFeatures,
+};
+use crate::onlinestore::sqlite_onlinestore::SqliteReadError;
+use crate::onlinestore::{
+    OnlineStore, OnlineStoreRow, OnlineWriteRow, VectorSearchResult, legacy_value,
 };
-use crate::onlinestore::OnlineStore;
-use crate::registry::FeatureRegistryService;
-use anyhow::{Result, anyhow};
+use crate::proto_utils::json_to_proto_value;
+use crate::registry::schema::ResponseSchema;
+use crate::registry::{FeatureRegistryService, RegistryRefreshFailure};
+use crate::util::parse_json_timestamp;
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
 use lasso::Spur;
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use std::collections::BTreeMap;
 use std::collections::hash_map::Entry;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tokio::task::JoinSet;
 use tracing;
 
 pub struct FeatureStore {
     registry: Arc<dyn FeatureRegistryService>,
     online_store: Arc<dyn OnlineStore>,
+    /// Store read instead of `online_store` while it is unavailable.
+    secondary_online_store: Option<Arc<dyn OnlineStore>>,
+    read_stats: ReadStats,
+    response_cache: Option<ResponseCache>,
+    entity_key_coercion: EntityKeyCoercion,
+    entity_count_mismatch: EntityCountMismatch,
+    partial_results: bool,
+    table_check: Arc<TableCheck>,
+    slow_requests: Option<SlowRequestLog>,
+    response_budget: Option<ResponseBudget>,
+    /// TTL of feature views, by the name they have in the projection, overridden per
+    /// feature service.
+    ttl_overrides: HashMap<String, HashMap<Spur, chrono::Duration>>,
+    tombstone_views: HashSet<Spur>,
+    lenient_decoding_views: HashSet<Spur>,
+    entity_aliases: EntityAliases,
+    /// Values answered for features not found, converted to the feature type per request.
+    feature_defaults: HashMap<Feature, serde_json::Value>,
+    debug_responses: bool,
+    /// Configured project, which requests not selecting one are served from.
+    project: Option<String>,
+    /// Online stores of the projects requests may select, by project name.
+    project_stores: HashMap<String, Arc<dyn OnlineStore>>,
+    response_recording: Option<ResponseRecording>,
+    effective_config: Option<EffectiveConfig>,
+    collapser: RequestCollapser,
+    /// Registry generation the online stores were last told about.
+    store_generation: AtomicU64,
 }
 
+/// Number of `get_online_features` calls, by project.
+pub const ONLINE_REQUESTS_METRIC: &str = "online_feature_requests_total";
+
+/// Number of reads answered by the secondary online store while the primary was
+/// unavailable.
+pub const SECONDARY_READS_METRIC: &str = "feast_secondary_online_store_reads_total";
+
+/// Warning of responses read from the secondary online store, whose values may be older
+/// than those of the primary.
+pub const SECONDARY_STORE_WARNING: &str =
+    "Served from the secondary online store while the primary is unavailable";
+
 impl FeatureStore {
     pub fn new(
         registry: Arc<dyn FeatureRegistryService>,
@@ -28,146 +99,1541 @@ impl FeatureStore {
         Self {
             registry,
             online_store,
+            secondary_online_store: None,
+            read_stats: ReadStats::default(),
+            response_cache: None,
+            entity_key_coercion: EntityKeyCoercion::default(),
+            entity_count_mismatch: EntityCountMismatch::default(),
+            partial_results: false,
+            table_check: Arc::new(TableCheck::new(Vec::new())),
+            slow_requests: None,
+            response_budget: None,
+            ttl_overrides: HashMap::default(),
+            tombstone_views: HashSet::default(),
+            lenient_decoding_views: HashSet::default(),
+            entity_aliases: EntityAliases::default(),
+            feature_defaults: HashMap::default(),
+            debug_responses: false,
+            project: None,
+            project_stores: HashMap::default(),
+            response_recording: None,
+            effective_config: None,
+            collapser: RequestCollapser::default(),
+            store_generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Builder creating the registry and online store from a repo config.
+    pub fn builder() -> FeatureStoreBuilder {
+        FeatureStoreBuilder::new()
+    }
+
+    /// Policy matching requested entity values to the types of entity columns.
+    pub fn with_entity_key_coercion(mut self, coercion: EntityKeyCoercion) -> Self {
+        self.entity_key_coercion = coercion;
+        self
+    }
+
+    /// Handling of requests whose entities have different numbers of values.
+    pub fn with_entity_count_mismatch(mut self, policy: EntityCountMismatch) -> Self {
+        self.entity_count_mismatch = policy;
+        self
+    }
+
+    /// Reject or chunk requests whose response is estimated above the budget of `config`.
+    pub fn with_response_budget(mut self, config: &ResponseBudgetConfig) -> Self {
+        self.response_budget = Some(ResponseBudget::new(config));
+        self
+    }
+
+    /// Read every feature view separately and answer with the views which could be read
+    /// when others fail, instead of failing the whole request.
+    pub fn with_partial_results(mut self, partial_results: bool) -> Self {
+        self.partial_results = partial_results;
+        self
+    }
+
+    /// Cache responses of the feature services listed in `config`.
+    pub fn with_response_cache(mut self, config: &ResponseCacheConfig) -> Self {
+        self.response_cache = Some(ResponseCache::new(config));
+        self
+    }
+
+    /// Log `get_online_features` calls slower than the threshold of `config` and keep the
+    /// most recent of them.
+    pub fn with_slow_requests(mut self, config: &SlowRequestsConfig) -> Self {
+        self.slow_requests = Some(SlowRequestLog::new(config));
+        self
+    }
+
+    /// Report the store as not ready while feature views of the required feature services
+    /// of `config` have no online table.
+    pub fn with_readiness(mut self, config: &ReadinessConfig) -> Self {
+        self.table_check = Arc::new(TableCheck::new(config.required_feature_services.clone()));
+        self
+    }
+
+    /// Compute the statuses of features requested through a feature service with the TTL in
+    /// seconds given to their view in `overrides`, keyed by feature service and by view name
+    /// or projection alias. These take precedence over the TTL tags of the registry.
+    pub fn with_ttl_overrides(
+        mut self,
+        overrides: &BTreeMap<String, BTreeMap<String, u64>>,
+    ) -> Self {
+        let rodeo = intern::rodeo_ref();
+        self.ttl_overrides = overrides
+            .iter()
+            .map(|(service, views)| {
+                let views = views
+                    .iter()
+                    .map(|(view, seconds)| {
+                        (
+                            rodeo.get_or_intern(view),
+                            chrono::Duration::seconds(*seconds as i64),
+                        )
+                    })
+                    .collect();
+                (service.clone(), views)
+            })
+            .collect();
+        self
+    }
+
+    /// Treat empty values stored for the feature views `views` as deleted, answering them
+    /// with the `NOT_FOUND` status instead of `NULL_VALUE`.
+    pub fn with_tombstone_views(mut self, views: &[String]) -> Self {
+        let rodeo = intern::rodeo_ref();
+        self.tombstone_views = views.iter().map(|view| rodeo.get_or_intern(view)).collect();
+        self
+    }
+
+    /// Read stored values of the feature views `views` which are not encoded `Value`s, as
+    /// written by old materializations, as raw string or bytes values instead of answering
+    /// them with the `INVALID` status.
+    pub fn with_lenient_decoding_views(mut self, views: &[String]) -> Self {
+        let rodeo = intern::rodeo_ref();
+        self.lenient_decoding_views = views.iter().map(|view| rodeo.get_or_intern(view)).collect();
+        self
+    }
+
+    /// Accept the entity names of `aliases` in requests for the join keys they map to. The
+    /// entity columns of responses keep the requested names.
+    pub fn with_entity_aliases(mut self, aliases: &BTreeMap<String, String>) -> Self {
+        self.entity_aliases = EntityAliases::new(aliases);
+        self
+    }
+
+    /// Answer values the online store has none of with `defaults`, by feature view and
+    /// feature, with the `DEFAULT_VALUE` status instead of `NOT_FOUND`.
+    pub fn with_feature_defaults(
+        mut self,
+        defaults: &BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    ) -> Self {
+        self.feature_defaults = defaults
+            .iter()
+            .flat_map(|(view, features)| {
+                features.iter().map(move |(feature, value)| {
+                    (Feature::from_names(view, feature), value.clone())
+                })
+            })
+            .collect();
+        self
+    }
+
+    /// Answer requests asking for `debug` with their timings. Requests asking for them are
+    /// answered without timings otherwise.
+    pub fn with_debug_responses(mut self, debug_responses: bool) -> Self {
+        self.debug_responses = debug_responses;
+        self
+    }
+
+    /// Record the responses to online feature requests to files of a directory, or answer
+    /// requests with the responses recorded there without reading the online store.
+    pub fn with_response_recording(mut self, recording: ResponseRecording) -> Self {
+        self.response_recording = Some(recording);
+        self
+    }
+
+    /// Configuration reported by [`Self::effective_config`].
+    pub fn with_effective_config(mut self, effective_config: EffectiveConfig) -> Self {
+        self.effective_config = Some(effective_config);
+        self
+    }
+
+    /// Name of the project served by default, labelling the metrics of requests which do
+    /// not select a project. Requests may select it explicitly.
+    pub fn with_project(mut self, project: impl Into<String>) -> Self {
+        let project = project.into();
+        self.project_stores
+            .insert(project.clone(), Arc::clone(&self.online_store));
+        self.project = Some(project);
+        self
+    }
+
+    /// Read requests of the configured project from `online_store` while the main store is
+    /// unavailable, i.e. while its circuit breaker is open.
+    pub fn with_secondary_online_store(mut self, online_store: Arc<dyn OnlineStore>) -> Self {
+        self.secondary_online_store = Some(online_store);
+        self
+    }
+
+    /// Let requests select `project`, reading its values from `online_store`. Feature
+    /// definitions still come from the registry of the store, so the project must define
+    /// the same feature views.
+    pub fn with_project_online_store(
+        mut self,
+        project: impl Into<String>,
+        online_store: Arc<dyn OnlineStore>,
+    ) -> Self {
+        self.project_stores.insert(project.into(), online_store);
+        self
+    }
+
+    /// Online store of the project selected by a request, failing unless it is allowed.
+    fn project_online_store(&self, project: Option<&str>) -> Result<&Arc<dyn OnlineStore>> {
+        match project {
+            None => Ok(&self.online_store),
+            Some(project) => self
+                .project_stores
+                .get(project)
+                .ok_or_else(|| FeastCoreError::project_not_allowed(project).into()),
+        }
+    }
+
+    /// Per feature view read statistics collected since start or the last reset.
+    pub fn read_stats(&self) -> ReadStatsSnapshot {
+        let mut snapshot = self.read_stats.snapshot();
+        snapshot.response_cache = self.response_cache.as_ref().map(ResponseCache::stats);
+        snapshot
+    }
+
+    /// Check that the feature store can serve requests. The registry is loaded when the
+    /// store is built, so only the online store and the tables of the required feature
+    /// services are checked.
+    pub async fn health_check(&self) -> Result<()> {
+        self.online_store.health_check().await?;
+        self.check_required_tables().await
+    }
+
+    /// Stop the registry refreshes and close the connections of the online stores, once the
+    /// server stopped answering requests. Every store is closed even when some fail, and
+    /// the first failure is returned.
+    pub async fn shutdown(&self) -> Result<()> {
+        self.registry.shutdown().await;
+        let mut result = Ok(());
+        for store in self.online_stores() {
+            if let Err(err) = store.shutdown().await {
+                tracing::warn!("Failed to shut down online store: {:#}", err);
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+        result
+    }
+
+    /// Every online store once, whichever projects read from it.
+    fn online_stores(&self) -> Vec<&Arc<dyn OnlineStore>> {
+        let mut stores: Vec<&Arc<dyn OnlineStore>> = vec![&self.online_store];
+        stores.extend(&self.secondary_online_store);
+        for store in self.project_stores.values() {
+            if !stores.iter().any(|listed| Arc::ptr_eq(listed, store)) {
+                stores.push(store);
+            }
+        }
+        stores
+    }
+
+    /// Tell the online stores when the registry was reloaded since the last read.
+    fn notify_registry_reload(&self) {
+        let generation = self.registry.generation();
+        if self.store_generation.swap(generation, Ordering::Relaxed) != generation {
+            for store in self.online_stores() {
+                store.registry_reloaded();
+            }
+        }
+    }
+
+    /// Check that the feature views of the required feature services have online tables,
+    /// without querying the online store unless the registry was reloaded.
+    pub async fn check_required_tables(&self) -> Result<()> {
+        self.check_online_tables().await?;
+        self.table_check.check_readiness(&*self.registry).await
+    }
+
+    /// Look for feature views of the registry without an online table, logging them and
+    /// exporting their number as a metric. Views are only checked again after the registry
+    /// is reloaded.
+    pub async fn check_online_tables(&self) -> Result<Vec<String>> {
+        self.table_check
+            .check(&*self.registry, &*self.online_store)
+            .await?;
+        Ok(self.table_check.missing_tables())
+    }
+
+    /// Log the feature defaults which name no feature of the registry or cannot be converted
+    /// to the type of their feature. Such defaults are not answered.
+    pub async fn check_feature_defaults(&self) -> Result<Vec<String>> {
+        if self.feature_defaults.is_empty() {
+            return Ok(Vec::new());
+        }
+        let views = self.registry.feature_views().await?;
+        let mut invalid = Vec::new();
+        for (feature, default) in &self.feature_defaults {
+            let field = views
+                .iter()
+                .find(|view| view.name == feature.feature_view_name)
+                .and_then(|view| {
+                    view.features
+                        .iter()
+                        .find(|field| field.name == feature.feature_name)
+                });
+            let problem = match field {
+                None => "is not a feature of the registry".to_string(),
+                Some(field) => match json_to_proto_value(default, field.value_type) {
+                    Ok(_) => continue,
+                    Err(err) => format!("is invalid: {:#}", err),
+                },
+            };
+            tracing::warn!("Default of feature {} {}", feature.full_name(), problem);
+            invalid.push(feature.full_name());
+        }
+        invalid.sort();
+        Ok(invalid)
+    }
+
+    /// Sample entity keys of the online store and log an error when they, or the configured
+    /// `version`, are not in the V3 format the server reads. Reads of keys of another
+    /// version otherwise just return empty features.
+    pub async fn check_entity_key_format(
+        &self,
+        version: &EntityKeySerializationVersion,
+    ) -> Result<KeyFormatCheck> {
+        check_key_format(&*self.registry, &*self.online_store, version).await
+    }
+
+    /// Configuration the server was started with, unset for stores built without a config.
+    pub fn effective_config(&self) -> Option<&EffectiveConfig> {
+        self.effective_config.as_ref()
+    }
+
+    /// Entities, feature views and feature services of the current registry, with the build
+    /// of the server.
+    pub async fn metadata(&self) -> Result<ServerMetadata> {
+        let registry_generation = self.registry.generation();
+        Ok(ServerMetadata {
+            build: BuildInfo::current(),
+            registry_generation,
+            registry: self.registry.metadata().await?,
+            registry_refresh_failure: self.registry.refresh_failure(),
+        })
+    }
+
+    /// Response columns of `feature_service` with their types, for client code generation.
+    pub async fn response_schema(
+        &self,
+        feature_service: &str,
+        full_feature_names: bool,
+    ) -> Result<ResponseSchema> {
+        ResponseSchema::new(
+            &self.registry.metadata().await?,
+            feature_service,
+            full_feature_names,
+        )
+    }
+
+    /// Failure of the background registry refreshes since the last successful one. The
+    /// registry loaded last keeps being served meanwhile.
+    pub fn registry_refresh_failure(&self) -> Option<RegistryRefreshFailure> {
+        self.registry.refresh_failure()
+    }
+
+    /// Most recent requests slower than the slow request threshold, oldest first. Empty
+    /// unless slow request logging is enabled.
+    pub fn slow_requests(&self) -> Vec<SlowRequest> {
+        self.slow_requests
+            .as_ref()
+            .map(SlowRequestLog::entries)
+            .unwrap_or_default()
+    }
+
+    pub fn reset_read_stats(&self) {
+        self.read_stats.reset();
+        if let Some(cache) = &self.response_cache {
+            cache.reset_stats();
+        }
+    }
+
+    /// Online features as typed columns instead of proto values. Columns have the types the
+    /// registry declares, so that columns without values are typed as well.
+    pub async fn get_online_features_typed(
+        &self,
+        request: GetOnlineFeaturesRequest,
+    ) -> Result<FeatureMatrix> {
+        let requested_features = RequestedFeatures::try_from(&request)?;
+        let naming = FeatureNaming::new(request.full_feature_names.unwrap_or(false));
+        let response = self.get_online_features(request).await?;
+        // Types of the registry generation which answered the request
+        let feature_to_view = match response.registry_version {
+            Some(version) => {
+                self.registry
+                    .request_to_view_keys_at(requested_features, version)
+                    .await?
+            }
+            None => {
+                self.registry
+                    .request_to_view_keys(requested_features)
+                    .await?
+            }
+        };
+        let rodeo = intern::rodeo_ref();
+        let mut types: HashMap<&str, value_type::Enum> = HashMap::default();
+        for (feature, view) in &feature_to_view {
+            for column in &view.entity_columns {
+                types
+                    .entry(rodeo.resolve(&column.name))
+                    .or_insert(column.value_type);
+            }
+            if let Some(field) = view
+                .features
+                .iter()
+                .find(|field| field.name == feature.feature_name)
+            {
+                let name = naming.column_name(feature, Some(view));
+                types.insert(rodeo.resolve(&name), field.value_type);
+            }
         }
+        FeatureMatrix::with_types(response, |name| types.get(name).copied())
     }
 
     pub async fn get_online_features(
+        &self,
+        mut request: GetOnlineFeaturesRequest,
+    ) -> Result<GetOnlineFeatureResponse> {
+        self.table_check
+            .check_in_background(&self.registry, &self.online_store);
+        let project = request.project.as_deref().or(self.project.as_deref());
+        metrics::counter!(
+            ONLINE_REQUESTS_METRIC,
+            "project" => project.unwrap_or_default().to_string()
+        )
+        .increment(1);
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let shape = self
+            .slow_requests
+            .as_ref()
+            .map(|_| RequestShape::from(&request));
+        let renamed_entities = self.entity_aliases.translate(&mut request.entities)?;
+        let entity_count = request.entities.len();
+        let entity_rows = request.entities.values().map(Vec::len).max().unwrap_or(0);
+        let fields = request.response_fields();
+        let debug = self.debug_responses && request.debug.unwrap_or(false);
+        let mut timings = RequestTimings::default();
+        let mut response = self.recorded_online_features(request, &mut timings).await?;
+        if !renamed_entities.is_empty() {
+            let entity_count = entity_count.min(response.metadata.feature_names.len());
+            EntityAliases::restore(
+                &mut response.metadata.feature_names[..entity_count],
+                &renamed_entities,
+            );
+        }
+        response.retain_fields(entity_count, fields);
+        if debug {
+            response.debug = Some(timings.diagnostics(
+                started.elapsed(),
+                entity_rows,
+                response.registry_version,
+            ));
+        }
+        if let (Some(slow_requests), Some(shape)) = (&self.slow_requests, shape) {
+            slow_requests.record(started_at, started.elapsed(), timings, shape);
+        }
+        Ok(response)
+    }
+
+    /// Like [`Self::get_online_features`], but requests identical to one in flight wait for
+    /// its response instead of reading the online store again, protecting it from bursts
+    /// of synchronized client retries.
+    pub async fn get_online_features_collapsed(
+        &self,
+        request: GetOnlineFeaturesRequest,
+    ) -> Result<GetOnlineFeatureResponse> {
+        let key = serde_json::to_string(&request)?;
+        self.collapser
+            .run(key, || self.get_online_features(request))
+            .await
+    }
+
+    /// Complete response to `request`, recorded or replayed when the store was built with a
+    /// [`ResponseRecording`].
+    async fn recorded_online_features(
+        &self,
+        request: GetOnlineFeaturesRequest,
+        timings: &mut RequestTimings,
+    ) -> Result<GetOnlineFeatureResponse> {
+        match &self.response_recording {
+            None => self.cached_online_features(request, timings).await,
+            Some(ResponseRecording::Replay(dir)) => response_recording::replay(dir, &request).await,
+            Some(ResponseRecording::Record(dir)) => {
+                let recorded_request = request.clone();
+                let response = self.cached_online_features(request, timings).await?;
+                if let Err(err) =
+                    response_recording::record(dir, &recorded_request, &response).await
+                {
+                    tracing::warn!("Failed to record response: {:#}", err);
+                }
+                Ok(response)
+            }
+        }
+    }
+
+    /// Complete response to `request`, taken from the response cache when possible.
+    async fn cached_online_features(
+        &self,
+        request: GetOnlineFeaturesRequest,
+        timings: &mut RequestTimings,
+    ) -> Result<GetOnlineFeatureResponse> {
+        let Some((cache, key)) = self
+            .response_cache
+            .as_ref()
+            .and_then(|cache| cache.key(&request).map(|key| (cache, key)))
+        else {
+            return self.fetch_online_features(request, timings).await;
+        };
+        let generation = self.registry.generation();
+        if request
+            .registry_version
+            .is_some_and(|version| version != generation)
+        {
+            // Only responses of the current registry are cached
+            return self.fetch_online_features(request, timings).await;
+        }
+        if let Some(response) = cache.get(&key, generation) {
+            timings.cached = true;
+            return Ok(response);
+        }
+        let clears = cache.clears();
+        let response = self.fetch_online_features(request, timings).await?;
+        if response.warnings.is_empty() {
+            // Under the generation which answered, in case the registry was refreshed since
+            let generation = response.registry_version.unwrap_or(generation);
+            cache.insert(key, generation, clears, &response);
+        }
+        Ok(response)
+    }
+
+    async fn fetch_online_features(
         &self,
         request: GetOnlineFeaturesRequest,
+        timings: &mut RequestTimings,
     ) -> Result<GetOnlineFeatureResponse> {
-        let requested_features: RequestedFeatures = RequestedFeatures::from(&request);
+        let requested_features = RequestedFeatures::try_from(&request)?;
+        let online_store = self.project_online_store(request.project.as_deref())?;
+        self.notify_registry_reload();
 
         let GetOnlineFeaturesRequest {
             entities,
             feature_service,
             features,
             full_feature_names,
+            registry_version,
+            ..
         } = request;
         let rodeo = intern::rodeo_ref();
-        let entities: HashMap<Spur, Vec<EntityIdValue>> = entities
+        let mut entities: OrderedMap<Spur, Vec<EntityIdValue>> = entities
             .into_iter()
-            .map(|(e, v)| (rodeo.get_or_intern(&e), v))
-            .collect();
-        let feature_to_view: HashMap<Feature, Arc<FeatureView>> = self
-            .registry
-            .request_to_view_keys(requested_features)
-            .await?;
+            .map(|(e, v)| Ok((intern::request_key(&e)?, v)))
+            .collect::<Result<_>>()?;
+        let registry_started = Instant::now();
+        let (mut feature_to_view, registry_version) = match registry_version {
+            Some(version) => {
+                let views = self
+                    .registry
+                    .request_to_view_keys_at(requested_features, version)
+                    .await?;
+                (views, version)
+            }
+            None => {
+                self.registry
+                    .request_to_view_keys_versioned(requested_features)
+                    .await?
+            }
+        };
+        timings.registry = registry_started.elapsed();
+        if let Some(overrides) = feature_service
+            .as_ref()
+            .and_then(|service| self.ttl_overrides.get(service))
+        {
+            override_ttls(&mut feature_to_view, overrides);
+        }
+        let feature_service_version = feature_service
+            .is_some()
+            .then(|| projection_version(&feature_to_view));
+        let full_feature_names = full_feature_names.unwrap_or(false);
+        FeatureNaming::new(full_feature_names).check_collisions(&feature_to_view)?;
 
         let lookup_mapping =
             build_lookup_key_mapping(&feature_to_view, entities.keys().collect::<Vec<_>>());
-        // feature view name to feature view
+        coerce_entity_values(
+            &mut entities,
+            &feature_to_view,
+            &lookup_mapping,
+            self.entity_key_coercion,
+        )?;
+        align_entity_counts(&mut entities, self.entity_count_mismatch)?;
+        let chunk_rows = match &self.response_budget {
+            Some(budget) => {
+                let entity_rows = entities.values().map(Vec::len).max().unwrap_or(0);
+                budget.chunk_rows(entity_rows, entities.len() + feature_to_view.len())?
+            }
+            None => None,
+        };
+        let mut response = match chunk_rows {
+            Some(chunk_rows) => {
+                let mut response = GetOnlineFeatureResponse::default();
+                for entities in split_entity_rows(entities, chunk_rows) {
+                    let chunk = self
+                        .read_entity_rows(
+                            online_store,
+                            entities,
+                            &feature_to_view,
+                            lookup_mapping.clone(),
+                            full_feature_names,
+                            timings,
+                        )
+                        .await?;
+                    response.append(chunk);
+                }
+                response
+            }
+            None => {
+                self.read_entity_rows(
+                    online_store,
+                    entities,
+                    &feature_to_view,
+                    lookup_mapping,
+                    full_feature_names,
+                    timings,
+                )
+                .await?
+            }
+        };
+        response.feature_service_version = feature_service_version;
+        response.registry_version = Some(registry_version);
+        Ok(response)
+    }
+
+    /// Read the features of `entities` and build their response.
+    async fn read_entity_rows(
+        &self,
+        online_store: &Arc<dyn OnlineStore>,
+        entities: OrderedMap<Spur, Vec<EntityIdValue>>,
+        feature_to_view: &OrderedMap<Feature, Arc<FeatureView>>,
+        lookup_mapping: HashMap<EntityColumnRef, Spur>,
+        full_feature_names: bool,
+        timings: &mut RequestTimings,
+    ) -> Result<GetOnlineFeatureResponse> {
+        // projection name, the alias of the view if any, to feature view
         let view_name_to_view: HashMap<Spur, Arc<FeatureView>> = feature_to_view
             .values()
-            .map(|view| (view.name, view.clone()))
+            .map(|view| (view.name_to_use(), view.clone()))
             .collect();
-
         let features_with_keys: Vec<FeatureWithKeys> =
-            feature_views_to_keys(&feature_to_view, &entities, &lookup_mapping)?;
+            feature_views_to_keys(feature_to_view, &entities, &lookup_mapping)?;
 
-        let mut features: HashMap<HashEntityKey, Vec<Feature>> = HashMap::default();
-
-        for feature in features_with_keys.iter() {
-            for entity_key in feature.entity_keys.iter() {
-                features
-                    .entry(HashEntityKey(entity_key.clone()))
-                    .or_default()
-                    .push(feature.feature.clone());
+        let features = store_features(&features_with_keys, &view_name_to_view);
+        let secondary = self
+            .secondary_online_store
+            .as_ref()
+            .filter(|_| Arc::ptr_eq(online_store, &self.online_store));
+        let read = self
+            .read_feature_values(online_store, features, &view_name_to_view, timings)
+            .await;
+        let (mut feature_rows, failed_views, from_secondary) = match (read, secondary) {
+            (Err(err), Some(secondary)) if is_store_unavailable(&err) => {
+                metrics::counter!(SECONDARY_READS_METRIC).increment(1);
+                // Built again rather than cloned up front, as reads rarely fall back
+                let features = store_features(&features_with_keys, &view_name_to_view);
+                let (rows, failed_views) = self
+                    .read_feature_values(secondary, features, &view_name_to_view, timings)
+                    .await?;
+                (rows, failed_views, true)
+            }
+            (read, _) => {
+                let (rows, failed_views) = read?;
+                (rows, failed_views, false)
+            }
+        };
+        if !self.lenient_decoding_views.is_empty() {
+            for row in feature_rows.iter_mut() {
+                if !self.lenient_decoding_views.contains(&row.feature_view_name) {
+                    continue;
+                }
+                if let Some(bytes) = row.undecoded.take() {
+                    let value_type = view_name_to_view
+                        .values()
+                        .find(|view| view.name == row.feature_view_name)
+                        .and_then(|view| {
+                            view.features
+                                .iter()
+                                .find(|field| field.name == row.feature_name)
+                        })
+                        .map(|field| field.value_type);
+                    row.value = legacy_value(row.feature_view_name, value_type, bytes);
+                    row.invalid = false;
+                }
             }
         }
+        if !self.tombstone_views.is_empty() {
+            // Values of these views are only deleted by writing an empty value over them
+            feature_rows.retain(|row| {
+                row.invalid
+                    || row.value.val.is_some()
+                    || !self.tombstone_views.contains(&row.feature_view_name)
+            });
+        }
+        let feature_rows = rows_of_projections(feature_rows, feature_to_view);
+        self.read_stats.record(&features_with_keys, &feature_rows);
 
-        let feature_rows = self.online_store.get_feature_values(features).await?;
-
-        let feature_set = features_with_keys
-            .iter()
-            .map(|f| f.feature.clone())
-            .collect();
+        let requested_features = feature_to_view.keys().cloned().collect();
+        let entity_count = entities.len();
 
-        GetOnlineFeatureResponse::try_from(
+        let build_started = Instant::now();
+        let mut response = GetOnlineFeatureResponse::try_from(
             entities,
             feature_rows,
             view_name_to_view,
             lookup_mapping,
-            feature_set,
-            full_feature_names.unwrap_or(false),
-        )
+            requested_features,
+            full_feature_names,
+        )?;
+        if !failed_views.is_empty() {
+            // Views failed by the name they are stored under
+            let stored_features: Vec<Feature> = feature_to_view
+                .iter()
+                .map(|(feature, view)| Feature::new(view.name, feature.feature_name))
+                .collect();
+            response.mark_failed_views(entity_count, stored_features.iter(), failed_views);
+        }
+        if let Some(defaults) = self.defaults_of(feature_to_view) {
+            response.fill_defaults(entity_count, &defaults);
+        }
+        if from_secondary {
+            response.warnings.push(SECONDARY_STORE_WARNING.to_string());
+        }
+        timings.build += build_started.elapsed();
+        Ok(response)
     }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct FeatureWithKeys {
-    pub feature: Feature,
-    pub feature_type: FeatureType,
-    pub entity_keys: Arc<Vec<Arc<EntityKey>>>,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub(crate) struct EntityColumnRef {
-    pub view_name: Spur,
-    pub column_name: Spur,
-}
 
-impl EntityColumnRef {
-    pub(crate) fn new(view_name: Spur, column_name: Spur) -> Self {
-        Self {
-            view_name,
-            column_name,
+    /// Defaults of the requested features in request order, converted to their type.
+    /// `None` when none of them has a default.
+    fn defaults_of(
+        &self,
+        feature_to_view: &OrderedMap<Feature, Arc<FeatureView>>,
+    ) -> Option<Vec<Option<Value>>> {
+        if self.feature_defaults.is_empty() {
+            return None;
         }
+        let defaults: Vec<Option<Value>> = feature_to_view
+            .iter()
+            .map(|(feature, view)| {
+                let default = self
+                    .feature_defaults
+                    .get(&Feature::new(view.name, feature.feature_name))?;
+                let field = view
+                    .features
+                    .iter()
+                    .find(|field| field.name == feature.feature_name)?;
+                json_to_proto_value(default, field.value_type).ok()
+            })
+            .collect();
+        defaults.iter().any(Option::is_some).then_some(defaults)
     }
-}
-
-static ENTITY_LESS_FEATURE_KEY: std::sync::LazyLock<Arc<Vec<Arc<EntityKey>>>> =
-    std::sync::LazyLock::new(|| {
-        Arc::new(vec![Arc::new(EntityKey {
-            join_keys: vec![DUMMY_ENTITY_ID.to_string()],
-            entity_values: vec![Value {
-                val: Some(Val::StringVal(DUMMY_ENTITY_VAL.to_string())),
-            }],
-        })])
-    });
 
-struct LookupKey {
-    origin_col_name: Spur,
-    lookup: Spur,
-    value_type: value_type::Enum,
-}
+    /// Rows of `features` from `online_store`, per feature view in partial results mode.
+    async fn read_feature_values(
+        &self,
+        online_store: &Arc<dyn OnlineStore>,
+        features: HashMap<HashEntityKey, Vec<Feature>>,
+        view_name_to_view: &HashMap<Spur, Arc<FeatureView>>,
+        timings: &mut RequestTimings,
+    ) -> Result<(Vec<OnlineStoreRow>, Vec<(Spur, anyhow::Error)>)> {
+        if self.partial_results {
+            return Self::get_feature_values_per_view(online_store, features, &mut timings.store)
+                .await;
+        }
+        let store_started = Instant::now();
+        let keys = features.len();
+        let rows = online_store.get_feature_values(features).await?;
+        timings.store.push(StoreCall {
+            feature_views: view_name_to_view.keys().copied().collect(),
+            keys,
+            duration: store_started.elapsed(),
+        });
+        Ok((rows, Vec::new()))
+    }
 
-fn build_lookup_key_mapping(
-    feature_to_view: &HashMap<Feature, Arc<FeatureView>>,
-    entities_from_request: Vec<&Spur>,
-) -> HashMap<EntityColumnRef, Spur> {
-    let mut mapping = HashMap::with_capacity_and_hasher(feature_to_view.len(), Default::default());
-    let rodeo = intern::rodeo_ref();
+    /// Query the online store once per feature view, so that a failing view leaves the rows
+    /// of the others. Views whose query failed are returned with their errors. An unavailable
+    /// sqlite database or store fails every view alike, so it fails the whole read instead. The
+    /// duration of every query is added to `store_timings`.
+    async fn get_feature_values_per_view(
+        online_store: &Arc<dyn OnlineStore>,
+        features: HashMap<HashEntityKey, Vec<Feature>>,
+        store_timings: &mut Vec<StoreCall>,
+    ) -> Result<(Vec<OnlineStoreRow>, Vec<(Spur, anyhow::Error)>)> {
+        let mut view_features: HashMap<Spur, HashMap<HashEntityKey, Vec<Feature>>> =
+            HashMap::default();
+        for (entity_key, features) in features {
+            for feature in features {
+                view_features
+                    .entry(feature.feature_view_name)
+                    .or_default()
+                    .entry(entity_key.clone())
+                    .or_default()
+                    .push(feature);
+            }
+        }
 
-    for (feature, view) in feature_to_view {
-        if view.is_entity_less() {
-            continue;
+        let mut join_set = JoinSet::new();
+        for (view_name, features) in view_features {
+            let online_store = Arc::clone(online_store);
+            join_set.spawn(async move {
+                let started = Instant::now();
+                let keys = features.len();
+                let result = online_store.get_feature_values(features).await;
+                let call = StoreCall {
+                    feature_views: vec![view_name],
+                    keys,
+                    duration: started.elapsed(),
+                };
+                (view_name, result, call)
+            });
         }
-        for col in &view.entity_columns {
-            let lookup_name = if let Some(join_key_map) = &view.join_key_map {
-                join_key_map
-                    .get(&col.name)
-                    .filter(|col_name| entities_from_request.contains(col_name))
-                    .cloned()
-                    .unwrap_or(col.name)
-            } else {
-                col.name
-            };
-            let key = EntityColumnRef::new(view.name, col.name);
-            mapping.insert(key, lookup_name);
+        let mut rows = Vec::new();
+        let mut failed_views = Vec::new();
+        while let Some(joined) = join_set.join_next().await {
+            let (view_name, result, call) = joined?;
+            store_timings.push(call);
+            match result {
+                Ok(view_rows) => rows.extend(view_rows),
+                Err(err) if is_database_unavailable(&err) || is_store_unavailable(&err) => {
+                    join_set.abort_all();
+                    return Err(err);
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to read feature view {}: {:#}",
+                        intern::rodeo_ref().resolve(&view_name),
+                        err
+                    );
+                    failed_views.push((view_name, err));
+                }
+            }
         }
+        Ok((rows, failed_views))
     }
-    mapping
-}
 
-/// Extract entity keys for each feature view from requested entity keys.
-/// Returns a mapping from requested features to shared entity key vectors.
+    /// Latest event timestamp stored for each requested entity row of a feature view,
+    /// taken over all of its features.
+    pub async fn get_feature_freshness(
+        &self,
+        request: FeatureFreshnessRequest,
+    ) -> Result<FeatureFreshnessResponse> {
+        let FeatureFreshnessRequest {
+            feature_view,
+            entities,
+        } = request;
+        let view = self
+            .registry
+            .feature_view(intern::request_key(&feature_view)?)
+            .await?;
+        let entity_keys = if view.is_entity_less() {
+            ENTITY_LESS_FEATURE_KEY.clone()
+        } else {
+            Arc::new(freshness_entity_keys(
+                &view,
+                &entities,
+                self.entity_key_coercion,
+            )?)
+        };
+        let view_features: Vec<Feature> = view
+            .features
+            .iter()
+            .map(|field| Feature::new(view.name, field.name))
+            .collect();
+        let features: HashMap<HashEntityKey, Vec<Feature>> = entity_keys
+            .iter()
+            .map(|key| (HashEntityKey(key.clone()), view_features.clone()))
+            .collect();
+
+        let rows = self.online_store.get_feature_values(features).await?;
+        let mut latest: HashMap<HashEntityKey, DateTime<Utc>> = HashMap::default();
+        for row in rows {
+            latest
+                .entry(row.entity_key)
+                .and_modify(|ts| *ts = (*ts).max(row.event_ts))
+                .or_insert(row.event_ts);
+        }
+        let event_timestamps = entity_keys
+            .iter()
+            .map(|key| latest.get(&HashEntityKey(key.clone())).copied())
+            .collect();
+        Ok(FeatureFreshnessResponse {
+            feature_view,
+            event_timestamps,
+        })
+    }
+
+    /// Up to `top_k` stored rows of a vector feature closest to `query`, best match first,
+    /// for online stores with vector indexes.
+    pub async fn search_similar(
+        &self,
+        feature_view_name: &str,
+        feature_name: &str,
+        query: Vec<f32>,
+        top_k: usize,
+    ) -> Result<Vec<VectorSearchResult>> {
+        let view = self.feature_view(feature_view_name).await?;
+        let feature = view
+            .features
+            .iter()
+            .find(|field| intern::rodeo_ref().resolve(&field.name) == feature_name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Feature view {} has no feature {}",
+                    feature_view_name,
+                    feature_name
+                )
+            })?;
+        self.online_store
+            .search_similar(view.name, feature.name, query, top_k)
+            .await
+    }
+
+    /// Purge every stored feature of the requested entity keys from the online store, e.g.
+    /// for data deletion requests. Views whose join keys are not all given in the request
+    /// are left untouched. Every purge is logged under the `audit` target.
+    pub async fn delete_entity_keys(
+        &self,
+        request: DeleteEntityKeysRequest,
+    ) -> Result<DeleteEntityKeysResponse> {
+        let rodeo = intern::rodeo_ref();
+        let mut views: Vec<Arc<FeatureView>> = self
+            .registry
+            .feature_views()
+            .await?
+            .into_iter()
+            .filter(|view| {
+                !view.is_entity_less()
+                    && !view.entity_columns.is_empty()
+                    && view
+                        .entity_columns
+                        .iter()
+                        .all(|col| request.entities.contains_key(rodeo.resolve(&col.name)))
+            })
+            .collect();
+        views.sort_by_key(|view| rodeo.resolve(&view.name));
+        if views.is_empty() {
+            tracing::warn!(
+                "No feature view is keyed by the join keys {:?} of the delete request",
+                request.entities.keys().collect::<Vec<_>>()
+            );
+        }
+
+        let mut deleted = Vec::with_capacity(views.len());
+        for view in views {
+            let entity_keys =
+                freshness_entity_keys(&view, &request.entities, self.entity_key_coercion)?;
+            let requested = entity_keys.len();
+            let feature_names = view.features.iter().map(|field| field.name).collect();
+            let entity_keys = entity_keys.iter().map(|key| (**key).clone()).collect();
+            let feature_view = rodeo.resolve(&view.name);
+            let entity_key_count = self
+                .online_store
+                .delete_entity_keys(view.name, feature_names, entity_keys)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to delete entity keys of feature view {}",
+                        feature_view
+                    )
+                })?;
+            self.clear_response_cache();
+            let view_entities: Vec<_> = view
+                .entity_columns
+                .iter()
+                .map(|col| {
+                    let name = rodeo.resolve(&col.name);
+                    (name, &request.entities[name])
+                })
+                .collect();
+            tracing::info!(
+                target: "audit",
+                "Deleted {} of {} entity keys {:?} from feature view {}",
+                entity_key_count,
+                requested,
+                view_entities,
+                feature_view
+            );
+            deleted.push(DeletedEntityKeys {
+                feature_view: feature_view.to_string(),
+                entity_keys: entity_key_count,
+            });
+        }
+        Ok(DeleteEntityKeysResponse { deleted })
+    }
+
+    /// Write pushed data into the online store for every feature view fed by the push source.
+    pub async fn push(&self, request: PushRequest) -> Result<()> {
+        let PushRequest {
+            push_source_name,
+            df,
+            to,
+            ..
+        } = request;
+        if to != PushMode::Online {
+            return Err(FeastCoreError::invalid_push_data(
+                "only pushing to the online store is supported",
+            )
+            .into());
+        }
+        let invalid = |err: anyhow::Error| FeastCoreError::invalid_push_data(format!("{:#}", err));
+        let num_rows = column_row_count(&df).map_err(invalid)?;
+        let rodeo = intern::rodeo_ref();
+        let views = self
+            .registry
+            .push_source_views(intern::request_key(&push_source_name)?)
+            .await?;
+        for view in views {
+            let push_source = view.push_source.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "Feature view {} has no push source",
+                    rodeo.resolve(&view.name)
+                )
+            })?;
+            let rows = rows_for_view(
+                &view,
+                &df,
+                num_rows,
+                &push_source.timestamp_field,
+                push_source.created_timestamp_column.as_deref(),
+            )
+            .map_err(invalid)?;
+            self.online_store
+                .online_write_batch(view.name, rows)
+                .await?;
+            self.clear_response_cache();
+        }
+        Ok(())
+    }
+
+    /// Drop the cached responses once values of the online store changed, so that they are
+    /// not answered until they expire.
+    fn clear_response_cache(&self) {
+        if let Some(cache) = &self.response_cache {
+            cache.clear();
+        }
+    }
+
+    /// Feature view `feature_view_name` of the registry, e.g. for the types of the columns
+    /// of data written to it.
+    pub async fn feature_view(&self, feature_view_name: &str) -> Result<Arc<FeatureView>> {
+        self.registry
+            .feature_view(intern::request_key(feature_view_name)?)
+            .await
+    }
+
+    /// Write columnar rows to the online store of a feature view, e.g. rows materialized
+    /// from an offline file. `df` holds the join keys and features of the view and the
+    /// `timestamp_field` column; `created_timestamp_column` is optional. Returns the number
+    /// of written rows.
+    pub async fn write_to_online_store(
+        &self,
+        feature_view_name: &str,
+        df: &HashMap<String, Vec<serde_json::Value>>,
+        timestamp_field: &str,
+        created_timestamp_column: Option<&str>,
+    ) -> Result<usize> {
+        let num_rows = column_row_count(df)?;
+        let view = self.feature_view(feature_view_name).await?;
+        let rows = rows_for_view(
+            &view,
+            df,
+            num_rows,
+            timestamp_field,
+            created_timestamp_column,
+        )?;
+        self.online_store
+            .online_write_batch(view.name, rows)
+            .await?;
+        self.clear_response_cache();
+        Ok(num_rows)
+    }
+}
+
+/// Number of rows of columnar data, failing when the columns differ in length.
+fn column_row_count(df: &HashMap<String, Vec<serde_json::Value>>) -> Result<usize> {
+    let num_rows = df.values().next().map(Vec::len).unwrap_or(0);
+    if let Some((column, _)) = df.iter().find(|(_, values)| values.len() != num_rows) {
+        return Err(anyhow!(
+            "Column {} has a different number of rows than other columns",
+            column
+        ));
+    }
+    Ok(num_rows)
+}
+
+/// Build entity keys of a feature view from columnar entity values keyed by join key.
+fn freshness_entity_keys(
+    view: &FeatureView,
+    entities: &HashMap<String, Vec<EntityIdValue>>,
+    coercion: EntityKeyCoercion,
+) -> Result<Vec<Arc<EntityKey>>> {
+    let rodeo = intern::rodeo_ref();
+    let columns = view
+        .entity_columns
+        .iter()
+        .map(|col| {
+            let name = rodeo.resolve(&col.name);
+            entities
+                .get(name)
+                .map(|values| (col, name, values))
+                .ok_or_else(|| anyhow!("Missing entity key: {}", name))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let num_rows = columns
+        .first()
+        .map(|(_, _, values)| values.len())
+        .unwrap_or(0);
+    if columns
+        .iter()
+        .any(|(_, _, values)| values.len() != num_rows)
+    {
+        return Err(anyhow!(
+            "All entity columns must have the same number of values"
+        ));
+    }
+    (0..num_rows)
+        .map(|i| {
+            let entity_values = columns
+                .iter()
+                .map(|(col, name, values)| {
+                    coerce_entity_value(name, &values[i], col.value_type, coercion)?
+                        .to_proto_value(col.value_type)
+                })
+                .collect::<Result<Vec<Value>>>()?;
+            Ok(Arc::new(EntityKey {
+                join_keys: columns
+                    .iter()
+                    .map(|(_, name, _)| name.to_string())
+                    .collect(),
+                entity_values,
+            }))
+        })
+        .collect()
+}
+
+/// Build online store rows for a feature view from columnar data, e.g. a push payload.
+/// Only features present in the data are written.
+fn rows_for_view(
+    view: &FeatureView,
+    df: &HashMap<String, Vec<serde_json::Value>>,
+    num_rows: usize,
+    timestamp_field: &str,
+    created_timestamp_column: Option<&str>,
+) -> Result<Vec<OnlineWriteRow>> {
+    let rodeo = intern::rodeo_ref();
+    let view_name = rodeo.resolve(&view.name);
+    let column = |name: &str| -> Result<&Vec<serde_json::Value>> {
+        df.get(name).ok_or_else(|| {
+            anyhow!(
+                "Missing column {} in data written to feature view {}",
+                name,
+                view_name
+            )
+        })
+    };
+
+    let entity_columns = view
+        .entity_columns
+        .iter()
+        .map(|col| Ok((col, column(rodeo.resolve(&col.name))?)))
+        .collect::<Result<Vec<_>>>()?;
+    let feature_columns: Vec<_> = view
+        .features
+        .iter()
+        .filter_map(|field| df.get(rodeo.resolve(&field.name)).map(|c| (field, c)))
+        .collect();
+    if feature_columns.is_empty() {
+        return Err(anyhow!(
+            "No features of feature view {} found in written data",
+            view_name
+        ));
+    }
+    let event_ts_column = column(timestamp_field)?;
+    let created_ts_column = created_timestamp_column.and_then(|name| df.get(name));
+
+    let mut rows = Vec::with_capacity(num_rows);
+    for i in 0..num_rows {
+        let mut join_keys = Vec::with_capacity(entity_columns.len());
+        let mut entity_values = Vec::with_capacity(entity_columns.len());
+        for (col, values) in &entity_columns {
+            join_keys.push(rodeo.resolve(&col.name).to_string());
+            entity_values.push(json_to_proto_value(&values[i], col.value_type)?);
+        }
+        let values = feature_columns
+            .iter()
+            .map(|(field, values)| {
+                json_to_proto_value(&values[i], field.value_type)
+                    .map(|value| (field.name, value))
+                    .with_context(|| {
+                        format!("Invalid value for feature {}", rodeo.resolve(&field.name))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let created_ts = created_ts_column
+            .map(|values| &values[i])
+            .filter(|value| !value.is_null())
+            .map(parse_json_timestamp)
+            .transpose()?;
+        rows.push(OnlineWriteRow {
+            entity_key: EntityKey {
+                join_keys,
+                entity_values,
+            },
+            values,
+            event_ts: parse_json_timestamp(&event_ts_column[i])?,
+            created_ts,
+        });
+    }
+    Ok(rows)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureWithKeys {
+    pub feature: Feature,
+    pub feature_type: FeatureType,
+    pub entity_keys: Arc<Vec<Arc<EntityKey>>>,
+}
+
+/// Entity column of a view under the name it is projected as, its alias if it has one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct EntityColumnRef {
+    pub view_name: Spur,
+    pub column_name: Spur,
+}
+
+impl EntityColumnRef {
+    pub(crate) fn new(view_name: Spur, column_name: Spur) -> Self {
+        Self {
+            view_name,
+            column_name,
+        }
+    }
+}
+
+static ENTITY_LESS_FEATURE_KEY: std::sync::LazyLock<Arc<Vec<Arc<EntityKey>>>> =
+    std::sync::LazyLock::new(|| {
+        Arc::new(vec![Arc::new(EntityKey {
+            join_keys: vec![DUMMY_ENTITY_ID.to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::StringVal(DUMMY_ENTITY_VAL.to_string())),
+            }],
+        })])
+    });
+
+struct LookupKey {
+    origin_col_name: Spur,
+    lookup: Spur,
+    value_type: value_type::Enum,
+}
+
+/// Give the views named in `overrides` by their name in the projection their overridden TTL.
+/// Features of the same view keep sharing one copy of it.
+fn override_ttls(
+    feature_to_view: &mut OrderedMap<Feature, Arc<FeatureView>>,
+    overrides: &HashMap<Spur, chrono::Duration>,
+) {
+    let mut overridden: HashMap<Spur, Arc<FeatureView>> = HashMap::default();
+    for view in feature_to_view.values_mut() {
+        let name = view.name_to_use();
+        if let Some(ttl) = overrides.get(&name) {
+            *view = overridden
+                .entry(name)
+                .or_insert_with(|| {
+                    Arc::new(FeatureView {
+                        ttl: *ttl,
+                        ..FeatureView::clone(view)
+                    })
+                })
+                .clone();
+        }
+    }
+}
+
+/// Hex digest of the resolved projections: names, aliases, feature and entity types, TTLs
+/// and join key maps, in request order. Names are hashed rather than interned keys, so that
+/// every server reports the same version for the same definition.
+fn projection_version(feature_to_view: &OrderedMap<Feature, Arc<FeatureView>>) -> String {
+    let rodeo = intern::rodeo_ref();
+    let mut definition = String::new();
+    for (feature, view) in feature_to_view {
+        let value_type = view
+            .features
+            .iter()
+            .find(|field| field.name == feature.feature_name)
+            .map_or("", |field| field.value_type.as_str_name());
+        let entity_columns: Vec<_> = view
+            .entity_columns
+            .iter()
+            .map(|field| {
+                format!(
+                    "{}:{}",
+                    rodeo.resolve(&field.name),
+                    field.value_type.as_str_name()
+                )
+            })
+            .collect();
+        let mut join_key_map: Vec<_> = view
+            .join_key_map
+            .iter()
+            .flatten()
+            .map(|(from, to)| format!("{}={}", rodeo.resolve(from), rodeo.resolve(to)))
+            .collect();
+        join_key_map.sort();
+        definition.push_str(&format!(
+            "{}|{}|{}:{}|{}|{}|{}\n",
+            rodeo.resolve(&view.name),
+            view.name_alias.map_or("", |alias| rodeo.resolve(&alias)),
+            rodeo.resolve(&feature.feature_name),
+            value_type,
+            view.ttl.num_seconds(),
+            entity_columns.join(","),
+            join_key_map.join(",")
+        ));
+    }
+    let hash = murmur3::murmur3_x64_128(&mut std::io::Cursor::new(definition), 0)
+        .expect("reading from memory cannot fail");
+    format!("{:032x}", hash)
+}
+
+/// Map the entity columns of every requested view to the request column holding their
+/// values. A column renamed by the `join_key_map` of a feature service projection is read
+/// from its new name; the original name is accepted only when the request lacks the new one,
+/// and missing columns keep the name the request is expected to provide.
+fn build_lookup_key_mapping(
+    feature_to_view: &OrderedMap<Feature, Arc<FeatureView>>,
+    entities_from_request: Vec<&Spur>,
+) -> HashMap<EntityColumnRef, Spur> {
+    let mut mapping = HashMap::with_capacity_and_hasher(feature_to_view.len(), Default::default());
+
+    for view in feature_to_view.values() {
+        if view.is_entity_less() {
+            continue;
+        }
+        for col in &view.entity_columns {
+            let mapped_name = view
+                .join_key_map
+                .as_ref()
+                .and_then(|join_key_map| join_key_map.get(&col.name))
+                .copied();
+            let lookup_name = match mapped_name {
+                Some(mapped) if entities_from_request.contains(&&mapped) => mapped,
+                Some(_) if entities_from_request.contains(&&col.name) => col.name,
+                Some(mapped) => mapped,
+                None => col.name,
+            };
+            let key = EntityColumnRef::new(view.name_to_use(), col.name);
+            mapping.insert(key, lookup_name);
+        }
+    }
+    mapping
+}
+
+fn coerce_entity_value(
+    entity: &str,
+    value: &EntityIdValue,
+    value_type: value_type::Enum,
+    coercion: EntityKeyCoercion,
+) -> Result<EntityIdValue, FeastCoreError> {
+    value.coerce(value_type, coercion).ok_or_else(|| {
+        FeastCoreError::invalid_entity_value(
+            entity,
+            serde_json::to_string(value).unwrap_or_default(),
+            value_type.as_str_name(),
+        )
+    })
+}
+
+/// Convert requested entity values in place to the types of the entity columns they are
+/// looked up for, so rows read back from the store match the request. A request column
+/// shared by several views is converted to the type of the first view declaring it.
+fn coerce_entity_values(
+    entities: &mut OrderedMap<Spur, Vec<EntityIdValue>>,
+    feature_to_view: &OrderedMap<Feature, Arc<FeatureView>>,
+    lookup_mapping: &HashMap<EntityColumnRef, Spur>,
+    coercion: EntityKeyCoercion,
+) -> Result<(), FeastCoreError> {
+    let mut column_types: HashMap<Spur, value_type::Enum> = HashMap::default();
+    for view in feature_to_view.values() {
+        for col in &view.entity_columns {
+            let col_ref = EntityColumnRef::new(view.name_to_use(), col.name);
+            if let Some(lookup) = lookup_mapping.get(&col_ref) {
+                column_types.entry(*lookup).or_insert(col.value_type);
+            }
+        }
+    }
+    let rodeo = intern::rodeo_ref();
+    for (name, value_type) in column_types {
+        let Some(values) = entities.get_mut(&name) else {
+            continue;
+        };
+        for value in values.iter_mut() {
+            *value = coerce_entity_value(rodeo.resolve(&name), value, value_type, coercion)?;
+        }
+    }
+    Ok(())
+}
+
+/// Give every requested entity the same number of values as `policy` says, before the
+/// entity keys of the feature views are built from them.
+fn align_entity_counts(
+    entities: &mut OrderedMap<Spur, Vec<EntityIdValue>>,
+    policy: EntityCountMismatch,
+) -> Result<(), FeastCoreError> {
+    let counts = || entities.iter().map(|(name, values)| (*name, values.len()));
+    let (Some(shortest), Some(longest)) = (
+        counts().min_by_key(|(_, count)| *count),
+        counts().max_by_key(|(_, count)| *count),
+    ) else {
+        return Ok(());
+    };
+    if shortest.1 == longest.1 {
+        return Ok(());
+    }
+    let mismatch = |(entity, count): (Spur, usize), (other, other_count): (Spur, usize)| {
+        let rodeo = intern::rodeo_ref();
+        FeastCoreError::entity_count_mismatch(
+            rodeo.resolve(&entity),
+            count,
+            rodeo.resolve(&other),
+            other_count,
+        )
+    };
+    match policy {
+        EntityCountMismatch::Error => return Err(mismatch(shortest, longest)),
+        EntityCountMismatch::ZipToShortest => {
+            for values in entities.values_mut() {
+                values.truncate(shortest.1);
+            }
+        }
+        EntityCountMismatch::Broadcast => {
+            if let Some(uneven) = counts().find(|(_, count)| *count != 1 && *count != longest.1) {
+                return Err(mismatch(uneven, longest));
+            }
+            for values in entities.values_mut() {
+                if values.len() == 1 {
+                    values.resize(longest.1, values[0].clone());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Split aligned entity values into requests of at most `chunk_rows` entity rows.
+fn split_entity_rows(
+    entities: OrderedMap<Spur, Vec<EntityIdValue>>,
+    chunk_rows: usize,
+) -> Vec<OrderedMap<Spur, Vec<EntityIdValue>>> {
+    let entity_rows = entities.values().map(Vec::len).max().unwrap_or(0);
+    let mut chunks: Vec<OrderedMap<Spur, Vec<EntityIdValue>>> =
+        vec![OrderedMap::default(); entity_rows.div_ceil(chunk_rows)];
+    for (entity, values) in entities {
+        for (chunk, values) in chunks.iter_mut().zip(values.chunks(chunk_rows)) {
+            chunk.insert(entity, values.to_vec());
+        }
+    }
+    chunks
+}
+
+/// Features to read from the online store per entity key. Features are read by the name
+/// of their view, once for every projection of the view.
+fn store_features(
+    features_with_keys: &[FeatureWithKeys],
+    view_name_to_view: &HashMap<Spur, Arc<FeatureView>>,
+) -> HashMap<HashEntityKey, Vec<Feature>> {
+    let mut features: HashMap<HashEntityKey, Vec<Feature>> = HashMap::default();
+    for feature in features_with_keys {
+        let view = &view_name_to_view[&feature.feature.feature_view_name];
+        let stored = Feature::new(view.name, feature.feature.feature_name);
+        let aliased = view.name_alias.is_some();
+        for entity_key in feature.entity_keys.iter() {
+            let key_features = features
+                .entry(HashEntityKey(entity_key.clone()))
+                .or_default();
+            if !aliased || !key_features.contains(&stored) {
+                key_features.push(stored.clone());
+            }
+        }
+    }
+    features
+}
+
+/// Rows read from the store named after the projections of their view requesting their
+/// feature, so that a view projected under several aliases answers each of them. Rows of
+/// views without an alias are kept as they are.
+fn rows_of_projections(
+    rows: Vec<OnlineStoreRow>,
+    feature_to_view: &OrderedMap<Feature, Arc<FeatureView>>,
+) -> Vec<OnlineStoreRow> {
+    let mut projections: HashMap<Spur, Vec<Spur>> = HashMap::default();
+    for view in feature_to_view.values() {
+        if view.name_alias.is_some() {
+            projections.entry(view.name).or_default();
+        }
+    }
+    if projections.is_empty() {
+        return rows;
+    }
+    for view in feature_to_view.values() {
+        if let Some(names) = projections.get_mut(&view.name)
+            && !names.contains(&view.name_to_use())
+        {
+            names.push(view.name_to_use());
+        }
+    }
+    let mut projected = Vec::with_capacity(rows.len());
+    for row in rows {
+        let Some(names) = projections.get(&row.feature_view_name) else {
+            projected.push(row);
+            continue;
+        };
+        for &name in names {
+            if feature_to_view.contains_key(&Feature::new(name, row.feature_name)) {
+                projected.push(OnlineStoreRow {
+                    feature_view_name: name,
+                    ..row.clone()
+                });
+            }
+        }
+    }
+    projected
+}
+
+/// Extract entity keys for each feature view from requested entity keys.
+/// Returns a mapping from requested features to shared entity key vectors.
 fn feature_views_to_keys(
-    feature_to_view: &HashMap<Feature, Arc<FeatureView>>,
-    requested_entity_keys: &HashMap<Spur, Vec<EntityIdValue>>,
+    feature_to_view: &OrderedMap<Feature, Arc<FeatureView>>,
+    requested_entity_keys: &OrderedMap<Spur, Vec<EntityIdValue>>,
     lookup_mapping: &HashMap<EntityColumnRef, Spur>,
 ) -> Result<Vec<FeatureWithKeys>> {
     let mut result = vec![];
-    let mut key_cache: HashMap<Vec<Spur>, Arc<Vec<Arc<EntityKey>>>> = HashMap::default();
+    // Keyed by the request column of every entity column, as views joined on the same
+    // columns may read them from different request columns through their join key maps
+    let mut key_cache: HashMap<Vec<(Spur, Spur)>, Arc<Vec<Arc<EntityKey>>>> = HashMap::default();
     let rodeo = intern::rodeo_ref();
     for (feature, view) in feature_to_view {
         if view.is_entity_less() {
@@ -181,7 +1647,7 @@ fn feature_views_to_keys(
                 .entity_columns
                 .iter()
                 .map(|col| {
-                    let entity_col_ref = EntityColumnRef::new(view.name, col.name);
+                    let entity_col_ref = EntityColumnRef::new(view.name_to_use(), col.name);
                     lookup_mapping
                         .get(&entity_col_ref)
                         .map(|lookup| LookupKey {
@@ -216,8 +1682,8 @@ fn feature_views_to_keys(
 
             let cache_key = lookup_keys
                 .iter()
-                .map(|lookup_key| lookup_key.origin_col_name)
-                .collect::<Vec<Spur>>();
+                .map(|lookup_key| (lookup_key.origin_col_name, lookup_key.lookup))
+                .collect::<Vec<(Spur, Spur)>>();
             let entity_keys = match key_cache.entry(cache_key) {
                 Entry::Occupied(entry) => Arc::clone(entry.get()),
                 Entry::Vacant(entry) => {
@@ -231,6 +1697,18 @@ fn feature_views_to_keys(
                         .iter()
                         .map(|lookup_key| &requested_entity_keys[&lookup_key.lookup])
                         .collect();
+                    if let Some((lookup_key, _)) = lookup_keys
+                        .iter()
+                        .zip(lookup_values_vec.iter())
+                        .find(|(_, values)| values.len() != num_entities)
+                    {
+                        return Err(anyhow!(
+                            "Entity keys {} and {} of feature view {} have different numbers of values",
+                            rodeo.resolve(&first_lookup_key),
+                            rodeo.resolve(&lookup_key.lookup),
+                            rodeo.resolve(&view.name)
+                        ));
+                    }
 
                     let mut entity_keys_vec = Vec::with_capacity(num_entities);
                     for i in 0..num_entities {
@@ -266,12 +1744,23 @@ fn feature_views_to_keys(
     Ok(result)
 }
 
+fn is_database_unavailable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<SqliteReadError>()
+        .is_some_and(SqliteReadError::is_database_unavailable)
+}
+
+fn is_store_unavailable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<FeastCoreError>()
+        .is_some_and(FeastCoreError::is_unavailable)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::BudgetExceeded;
     use crate::feast::types::{value, value_type};
     use crate::intern::rodeo;
-    use crate::model::{EntityIdValue, Field, GetOnlineFeaturesRequest};
+    use crate::model::{EntityIdValue, FeatureStatus, Field, GetOnlineFeaturesRequest};
     use chrono::Duration;
     use rustc_hash::FxHashMap as HashMap;
     use std::sync::Arc;
@@ -367,11 +1856,11 @@ mod tests {
         };
         let feature_1 = Feature::from_names("feature_view1", "col1");
         let feature_2 = Feature::from_names("feature_view2", "col2");
-        let features = HashMap::from_iter([
+        let features = OrderedMap::from_iter([
             (feature_1.clone(), Arc::new(feature_view_1)),
             (feature_2.clone(), Arc::new(feature_view_2)),
         ]);
-        let requested_entity_keys = HashMap::from_iter([
+        let requested_entity_keys = OrderedMap::from_iter([
             (
                 rodeo().get_or_intern("entity_col_1"),
                 vec![
@@ -392,19 +1881,14 @@ mod tests {
         let lookup_mapping =
             build_lookup_key_mapping(&features, requested_entity_keys.keys().collect::<Vec<_>>());
         let mut result = feature_views_to_keys(&features, &requested_entity_keys, &lookup_mapping)?;
-        result.sort_by_key(|f| {
-            (
-                f.feature.feature_view_name.clone(),
-                f.feature.feature_name.clone(),
-            )
-        });
+        result.sort_by_key(|f| (f.feature.feature_view_name, f.feature.feature_name));
         assert_eq!(result.len(), 2);
         let feature_1 = Feature::from_names("feature_view1", "col1");
         let feature_2 = Feature::from_names("feature_view2", "col2");
 
-        let entity_values_1 = build_entity_keys(&vec!["entity_col_1"], &[12, 14, 16]);
+        let entity_values_1 = build_entity_keys(&["entity_col_1"], &[12, 14, 16]);
         let entity_values_2 = build_entity_keys(
-            &vec!["entity_col_1", "entity_col_2"],
+            &["entity_col_1", "entity_col_2"],
             &[(12, 22), (14, 24), (16, 26)],
         );
 
@@ -421,16 +1905,79 @@ mod tests {
             },
         ];
 
-        expected.sort_by_key(|f| {
-            (
-                f.feature.feature_view_name.clone(),
-                f.feature.feature_name.clone(),
-            )
-        });
+        expected.sort_by_key(|f| (f.feature.feature_view_name, f.feature.feature_name));
         assert_eq!(result, expected);
         Ok(())
     }
 
+    #[test]
+    fn feature_views_to_keys_rejects_uneven_composite_keys() {
+        let feature_view = get_features_views().remove(1);
+        let features = OrderedMap::from_iter([(
+            Feature::from_names("feature_view2", "col2"),
+            Arc::new(feature_view),
+        )]);
+        let requested_entity_keys = OrderedMap::from_iter([
+            (
+                rodeo().get_or_intern("entity_col_1"),
+                vec![EntityIdValue::Int(12), EntityIdValue::Int(14)],
+            ),
+            (
+                rodeo().get_or_intern("entity_col_2"),
+                vec![EntityIdValue::Int(22)],
+            ),
+        ]);
+        let lookup_mapping =
+            build_lookup_key_mapping(&features, requested_entity_keys.keys().collect::<Vec<_>>());
+        let err =
+            feature_views_to_keys(&features, &requested_entity_keys, &lookup_mapping).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Entity keys entity_col_1 and entity_col_2 of feature view feature_view2 have different numbers of values"
+        );
+    }
+
+    #[test]
+    fn entity_counts_are_aligned_by_policy() {
+        let ints = |values: &[i64]| values.iter().map(|v| EntityIdValue::Int(*v)).collect();
+        let entities = || -> OrderedMap<Spur, Vec<EntityIdValue>> {
+            OrderedMap::from_iter([
+                (rodeo().get_or_intern("truck_id"), ints(&[7])),
+                (rodeo().get_or_intern("driver_id"), ints(&[1, 2, 3])),
+            ])
+        };
+        let counts = |entities: &OrderedMap<Spur, Vec<EntityIdValue>>| {
+            entities
+                .values()
+                .map(|values| values.len())
+                .collect::<Vec<_>>()
+        };
+
+        let err = align_entity_counts(&mut entities(), EntityCountMismatch::Error).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Entity 'truck_id' has 1 values but entity 'driver_id' has 3; every entity needs a value per entity row"
+        );
+        assert!(err.is_invalid_request());
+
+        let mut zipped = entities();
+        align_entity_counts(&mut zipped, EntityCountMismatch::ZipToShortest).unwrap();
+        assert_eq!(counts(&zipped), [1, 1]);
+        assert_eq!(zipped[1], ints(&[1]));
+
+        let mut broadcast = entities();
+        align_entity_counts(&mut broadcast, EntityCountMismatch::Broadcast).unwrap();
+        assert_eq!(broadcast[0], ints(&[7, 7, 7]));
+
+        let mut uneven = entities();
+        uneven.insert(rodeo().get_or_intern("customer_id"), ints(&[4, 5]));
+        let err = align_entity_counts(&mut uneven, EntityCountMismatch::Broadcast).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Entity 'customer_id' has 2 values but entity 'driver_id' has 3; every entity needs a value per entity row"
+        );
+    }
+
     #[test]
     fn feature_views_to_keys_mapping_test() -> Result<()> {
         let mut feature_view_1 = {
@@ -442,8 +1989,8 @@ mod tests {
             rodeo().get_or_intern("alias_1"),
         )]));
         let feature_1 = Feature::from_names("feature_view1", "col1");
-        let features = HashMap::from_iter([(feature_1.clone(), Arc::from(feature_view_1))]);
-        let requested_entity_keys = HashMap::from_iter([(
+        let features = OrderedMap::from_iter([(feature_1.clone(), Arc::from(feature_view_1))]);
+        let requested_entity_keys = OrderedMap::from_iter([(
             rodeo().get_or_intern("alias_1"),
             vec![
                 EntityIdValue::Int(12),
@@ -457,7 +2004,7 @@ mod tests {
         assert_eq!(result.len(), 1);
         let feature_1 = Feature::from_names("feature_view1", "col1");
 
-        let entity_values_1 = build_entity_keys(&vec!["entity_col_1"], &[12, 14, 16]);
+        let entity_values_1 = build_entity_keys(&["entity_col_1"], &[12, 14, 16]);
 
         let expected = vec![FeatureWithKeys {
             feature: feature_1,
@@ -468,36 +2015,128 @@ mod tests {
         Ok(())
     }
 
-    use crate::feast::types::Value;
-    use crate::feature_store::feature_store_impl::FeatureStore;
-    use crate::onlinestore::sqlite_onlinestore::{ConnectionOptions, SqliteOnlineStore};
-    use crate::registry::file_registry::FileFeatureRegistry;
-    use crate::util::EntityKeyWrapper;
-    use anyhow::Result;
-
-    async fn get_feature_store() -> Result<FeatureStore> {
+    #[test]
+    fn feature_views_to_keys_separates_join_key_maps() -> Result<()> {
+        let mut mapped_view = get_features_views().remove(0);
+        mapped_view.join_key_map = Some(HashMap::from_iter([(
+            rodeo().get_or_intern("entity_col_1"),
+            rodeo().get_or_intern("alias_1"),
+        )]));
+        let mut unmapped_view = get_features_views().remove(0);
+        unmapped_view.name = rodeo().get_or_intern("feature_view3");
+        let mapped_feature = Feature::from_names("feature_view1", "col1");
+        let unmapped_feature = Feature::from_names("feature_view3", "col1");
+        let features = OrderedMap::from_iter([
+            (mapped_feature.clone(), Arc::new(mapped_view)),
+            (unmapped_feature.clone(), Arc::new(unmapped_view)),
+        ]);
+        let requested_entity_keys = OrderedMap::from_iter([
+            (
+                rodeo().get_or_intern("alias_1"),
+                vec![EntityIdValue::Int(12), EntityIdValue::Int(14)],
+            ),
+            (
+                rodeo().get_or_intern("entity_col_1"),
+                vec![EntityIdValue::Int(22), EntityIdValue::Int(24)],
+            ),
+        ]);
+        let lookup_mapping =
+            build_lookup_key_mapping(&features, requested_entity_keys.keys().collect::<Vec<_>>());
+        let result = feature_views_to_keys(&features, &requested_entity_keys, &lookup_mapping)?;
+
+        let expected = vec![
+            FeatureWithKeys {
+                feature: mapped_feature,
+                feature_type: FeatureType::Plain,
+                entity_keys: Arc::new(build_entity_keys(&["entity_col_1"], &[12, 14])),
+            },
+            FeatureWithKeys {
+                feature: unmapped_feature,
+                feature_type: FeatureType::Plain,
+                entity_keys: Arc::new(build_entity_keys(&["entity_col_1"], &[22, 24])),
+            },
+        ];
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn lookup_mapping_follows_join_key_map() {
+        let mut view = get_features_views()[1].clone();
+        view.join_key_map = Some(HashMap::from_iter([
+            (
+                rodeo().get_or_intern("entity_col_1"),
+                rodeo().get_or_intern("origin_id"),
+            ),
+            (
+                rodeo().get_or_intern("entity_col_2"),
+                rodeo().get_or_intern("destination_id"),
+            ),
+        ]));
+        let view_name = view.name;
+        let features =
+            OrderedMap::from_iter([(Feature::from_names("feature_view2", "col2"), Arc::new(view))]);
+        let lookup = |requested: &[&str]| {
+            let requested: Vec<Spur> = requested
+                .iter()
+                .map(|name| rodeo().get_or_intern(name))
+                .collect();
+            let mapping = build_lookup_key_mapping(&features, requested.iter().collect());
+            ["entity_col_1", "entity_col_2"].map(|col| {
+                let col_ref = EntityColumnRef::new(view_name, rodeo().get_or_intern(col));
+                rodeo().resolve(&mapping[&col_ref]).to_string()
+            })
+        };
+
+        assert_eq!(
+            lookup(&["origin_id", "destination_id"]),
+            ["origin_id", "destination_id"]
+        );
+        // Unmapped names are still accepted when the mapped one is absent.
+        assert_eq!(
+            lookup(&["entity_col_1", "destination_id"]),
+            ["entity_col_1", "destination_id"]
+        );
+        // Missing columns are reported under the name the request should use.
+        assert_eq!(lookup(&[]), ["origin_id", "destination_id"]);
+    }
+
+    use crate::feast::types::Value;
+    use crate::feature_store::feature_store_impl::FeatureStore;
+    use crate::onlinestore::sqlite_onlinestore::{ConnectionOptions, SqliteOnlineStore};
+    use crate::registry::file_registry::FileFeatureRegistry;
+    use crate::test_util::temp_online_store;
+    use crate::util::EntityKeyWrapper;
+    use anyhow::Result;
+
+    async fn get_feature_store() -> Result<FeatureStore> {
+        let project_dir = env!("CARGO_MANIFEST_DIR");
+        let sqlite_path = format!("{}/test_data/online_store.db", project_dir);
+        get_feature_store_with_db(&sqlite_path).await
+    }
+
+    async fn get_feature_store_with_db(sqlite_path: &str) -> Result<FeatureStore> {
         let project_dir = env!("CARGO_MANIFEST_DIR");
         let registry_file = format!("{}/test_data/registry.pb", project_dir);
         let registry_file_path = std::path::PathBuf::from(&registry_file);
         let feature_registry = FileFeatureRegistry::from_path(&registry_file_path)?;
-        let sqlite_path = format!("{}/test_data/online_store.db", project_dir);
         let sqlite_store = SqliteOnlineStore::from_options(
-            &sqlite_path,
+            sqlite_path,
             "golden_hornet".to_string(),
             ConnectionOptions::default(),
         )
         .await?;
-        Ok(FeatureStore {
-            registry: Arc::new(feature_registry),
-            online_store: Arc::new(sqlite_store),
-        })
+        Ok(FeatureStore::new(
+            Arc::new(feature_registry),
+            Arc::new(sqlite_store),
+        ))
     }
 
     #[tokio::test]
     async fn get_features() -> Result<()> {
         let store = get_feature_store().await?;
 
-        let entities = HashMap::from_iter([(
+        let entities = OrderedMap::from_iter([(
             "driver_id".to_string(),
             vec![
                 EntityIdValue::Int(1005),
@@ -513,30 +2152,262 @@ mod tests {
                 "driver_hourly_stats:acc_rate".to_string(),
             ]),
             full_feature_names: Some(false),
+            ..Default::default()
         };
         let result = store.get_online_features(request).await?;
         assert_eq!(result.metadata.feature_names.len(), 3);
         assert_eq!(result.results.len(), 3);
         for (i, feature) in result.metadata.feature_names.iter().enumerate() {
-            match feature.as_ref() {
-                "driver_id" => {
-                    let vec_res: Vec<Option<Val>> = result.results[i]
-                        .values
-                        .iter()
-                        .map(|v| v.clone().0.val)
-                        .collect();
-                    assert_eq!(
-                        vec_res,
-                        vec![
-                            Some(Val::Int64Val(1005)),
-                            Some(Val::Int64Val(1002)),
-                            Some(Val::Int64Val(2003))
-                        ]
-                    );
-                }
-                _ => {}
+            if feature == "driver_id" {
+                let vec_res: Vec<Option<Val>> = result.results[i]
+                    .values
+                    .iter()
+                    .map(|v| v.clone().0.val)
+                    .collect();
+                assert_eq!(
+                    vec_res,
+                    vec![
+                        Some(Val::Int64Val(1005)),
+                        Some(Val::Int64Val(1002)),
+                        Some(Val::Int64Val(2003))
+                    ]
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn response_budget_rejects_or_chunks() -> Result<()> {
+        let request = || GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![
+                    EntityIdValue::Int(1005),
+                    EntityIdValue::Int(1002),
+                    EntityIdValue::Int(2003),
+                ],
+            )]),
+            features: Some(vec![
+                "driver_hourly_stats_fresh:conv_rate".to_string(),
+                "driver_hourly_stats:acc_rate".to_string(),
+            ]),
+            ..Default::default()
+        };
+        let expected = get_feature_store()
+            .await?
+            .get_online_features(request())
+            .await?;
+
+        // 3 entity rows of 3 columns are estimated at 90 bytes
+        let mut config = ResponseBudgetConfig {
+            max_bytes: 60,
+            avg_value_bytes: 10,
+            on_exceed: BudgetExceeded::Reject,
+        };
+        let store = get_feature_store().await?.with_response_budget(&config);
+        let err = store.get_online_features(request()).await.unwrap_err();
+        assert!(
+            err.downcast_ref::<FeastCoreError>()
+                .is_some_and(|e| e.is_invalid_request())
+        );
+
+        config.on_exceed = BudgetExceeded::Chunk;
+        let store = get_feature_store().await?.with_response_budget(&config);
+        let chunked = store.get_online_features(request()).await?;
+        assert_eq!(chunked.metadata, expected.metadata);
+        assert_eq!(chunked.results, expected.results);
+        Ok(())
+    }
+
+    /// Online store failing every read of one feature view.
+    struct FailingViewStore {
+        inner: Arc<dyn OnlineStore>,
+        failing_view: Spur,
+    }
+
+    #[async_trait::async_trait]
+    impl OnlineStore for FailingViewStore {
+        async fn get_feature_values(
+            &self,
+            features: HashMap<HashEntityKey, Vec<Feature>>,
+        ) -> Result<Vec<OnlineStoreRow>> {
+            let reads_failing_view = features
+                .values()
+                .flatten()
+                .any(|feature| feature.feature_view_name == self.failing_view);
+            if reads_failing_view {
+                return Err(anyhow!("no such table"));
             }
+            self.inner.get_feature_values(features).await
+        }
+    }
+
+    #[tokio::test]
+    async fn partial_results_mark_failed_views() -> Result<()> {
+        let store = get_feature_store().await?;
+        let failing_store = Arc::new(FailingViewStore {
+            inner: Arc::clone(&store.online_store),
+            failing_view: rodeo().get_or_intern("driver_hourly_stats_fresh"),
+        });
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1005), EntityIdValue::Int(1002)],
+            )]),
+            features: Some(vec![
+                "driver_hourly_stats_fresh:conv_rate".to_string(),
+                "driver_hourly_stats:acc_rate".to_string(),
+            ]),
+            ..Default::default()
+        };
+
+        let strict_store = FeatureStore::new(Arc::clone(&store.registry), failing_store.clone());
+        let err = strict_store
+            .get_online_features(request.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no such table");
+
+        let partial_store = FeatureStore::new(Arc::clone(&store.registry), failing_store)
+            .with_partial_results(true);
+        let response = partial_store.get_online_features(request).await?;
+        assert_eq!(
+            response.metadata.feature_names,
+            ["driver_id", "conv_rate", "acc_rate"]
+        );
+        assert_eq!(
+            response.results[1].statuses,
+            [FeatureStatus::Invalid, FeatureStatus::Invalid]
+        );
+        assert!(
+            !response.results[2]
+                .statuses
+                .contains(&FeatureStatus::Invalid)
+        );
+        assert_ne!(response.results[2].statuses[0], FeatureStatus::NotFound);
+        assert_eq!(
+            response.warnings,
+            ["Failed to read feature view driver_hourly_stats_fresh: no such table"]
+        );
+        Ok(())
+    }
+
+    /// Online store whose circuit breaker is open.
+    struct UnavailableStore;
+
+    #[async_trait::async_trait]
+    impl OnlineStore for UnavailableStore {
+        async fn get_feature_values(
+            &self,
+            _features: HashMap<HashEntityKey, Vec<Feature>>,
+        ) -> Result<Vec<OnlineStoreRow>> {
+            Err(FeastCoreError::online_store_unavailable(3).into())
+        }
+    }
+
+    #[tokio::test]
+    async fn unavailable_store_falls_back_to_secondary() -> Result<()> {
+        let store = get_feature_store().await?;
+        let expected = store.get_online_features(driver_stats_request()).await?;
+
+        for partial_results in [false, true] {
+            let fallback_store =
+                FeatureStore::new(Arc::clone(&store.registry), Arc::new(UnavailableStore))
+                    .with_secondary_online_store(Arc::clone(&store.online_store))
+                    .with_partial_results(partial_results);
+            let response = fallback_store
+                .get_online_features(driver_stats_request())
+                .await?;
+            assert_eq!(response.results, expected.results);
+            assert_eq!(response.warnings, [SECONDARY_STORE_WARNING]);
         }
+
+        let without_secondary =
+            FeatureStore::new(Arc::clone(&store.registry), Arc::new(UnavailableStore));
+        let err = without_secondary
+            .get_online_features(driver_stats_request())
+            .await
+            .unwrap_err();
+        assert!(is_store_unavailable(&err));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn partial_results_fail_on_unavailable_database() -> Result<()> {
+        let store = get_feature_store().await?.with_partial_results(true);
+        store.online_store.shutdown().await?;
+        let err = store
+            .get_online_features(driver_stats_request())
+            .await
+            .unwrap_err();
+        assert!(is_database_unavailable(&err));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_stats_per_view() -> Result<()> {
+        let store = get_feature_store().await?;
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1005), EntityIdValue::Int(-1)],
+            )]),
+            features: Some(vec!["driver_hourly_stats:acc_rate".to_string()]),
+            ..Default::default()
+        };
+        store.get_online_features(request).await?;
+
+        let stats = store.read_stats();
+        let view_stats = &stats.feature_views["driver_hourly_stats"];
+        assert_eq!(view_stats.requests, 1);
+        assert_eq!(view_stats.values_requested, 2);
+        assert_eq!(view_stats.hits, 1);
+        assert_eq!(view_stats.misses, 1);
+        assert!(view_stats.avg_value_size_bytes > 0.0);
+
+        store.reset_read_stats();
+        assert!(store.read_stats().feature_views.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cache_feature_service_responses() -> Result<()> {
+        let config = ResponseCacheConfig {
+            max_entries: 10,
+            feature_services: [("driver_activity_alias".to_string(), 60_000)].into(),
+        };
+        let store = get_feature_store().await?.with_response_cache(&config);
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1005)],
+            )]),
+            feature_service: Some("driver_activity_alias".to_string()),
+            ..Default::default()
+        };
+        let first = store.get_online_features(request.clone()).await?;
+        let second = store.get_online_features(request.clone()).await?;
+        assert_eq!(first, second);
+
+        let stats = store.read_stats();
+        let cache_stats = &stats.response_cache.as_ref().unwrap()["driver_activity_alias"];
+        assert_eq!((cache_stats.hits, cache_stats.misses), (1, 1));
+        // the cached response did not reach the online store
+        assert!(!stats.feature_views.is_empty());
+        assert!(stats.feature_views.values().all(|view| view.requests == 1));
+
+        let features_request = GetOnlineFeaturesRequest {
+            feature_service: None,
+            features: Some(vec!["driver_hourly_stats:acc_rate".to_string()]),
+            ..request
+        };
+        store.get_online_features(features_request.clone()).await?;
+        store.get_online_features(features_request).await?;
+        assert_eq!(
+            store.read_stats().feature_views["driver_hourly_stats"].requests,
+            2
+        );
         Ok(())
     }
 
@@ -544,7 +2415,7 @@ mod tests {
     async fn get_features_alias() -> Result<()> {
         let store = get_feature_store().await?;
 
-        let entities = HashMap::from_iter([
+        let entities = OrderedMap::from_iter([
             (
                 "truck_id".to_string(),
                 vec![EntityIdValue::Int(1002), EntityIdValue::Int(2003)],
@@ -559,6 +2430,7 @@ mod tests {
             feature_service: Some("driver_activity_alias".to_string()),
             features: None,
             full_feature_names: Some(false),
+            ..Default::default()
         };
 
         let result = store.get_online_features(request).await?;
@@ -568,18 +2440,1175 @@ mod tests {
             "Feature names: {:?}",
             result.metadata.feature_names
         );
-        let mut feature_names = result.metadata.feature_names.clone();
-        feature_names.sort();
+        // Entities in request order, then features in service projection order.
         assert_eq!(
-            feature_names,
-            vec![
-                "acc_rate".to_string(),
-                "avg_daily_trips".to_string(),
-                "conv_rate".to_string(),
+            result.metadata.feature_names,
+            [
+                "truck_id",
+                "driver_id",
+                "conv_rate",
+                "acc_rate",
+                "avg_daily_trips"
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn ttl_overrides_of_feature_service() -> Result<()> {
+        let overrides = BTreeMap::from([(
+            "driver_activity_alias".to_string(),
+            BTreeMap::from([("driver_alias".to_string(), 100 * 365 * 86_400)]),
+        )]);
+        let store = get_feature_store().await?.with_ttl_overrides(&overrides);
+        let request = |feature_service: Option<&str>, features: Option<Vec<String>>| {
+            GetOnlineFeaturesRequest {
+                entities: OrderedMap::from_iter([
+                    ("truck_id".to_string(), vec![EntityIdValue::Int(1002)]),
+                    ("driver_id".to_string(), vec![EntityIdValue::Int(1003)]),
+                ]),
+                feature_service: feature_service.map(str::to_string),
+                features,
+                ..Default::default()
+            }
+        };
+
+        let result = store
+            .get_online_features(request(Some("driver_activity_alias"), None))
+            .await?;
+        for column in &result.results[2..] {
+            assert_eq!(column.statuses, [FeatureStatus::Present]);
+        }
+
+        // Features requested by reference keep the TTL of their view
+        let features = vec!["driver_hourly_stats:conv_rate".to_string()];
+        let result = store
+            .get_online_features(request(None, Some(features)))
+            .await?;
+        assert_eq!(result.results[2].statuses, [FeatureStatus::OutsideMaxAge]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn entity_aliases_are_requested_and_answered() -> Result<()> {
+        let aliases = BTreeMap::from([("user_id".to_string(), "driver_id".to_string())]);
+        let store = get_feature_store().await?.with_entity_aliases(&aliases);
+        let request = |entity: &str| GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(entity.to_string(), vec![EntityIdValue::Int(1001)])]),
+            features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let aliased = store.get_online_features(request("user_id")).await?;
+        let direct = store.get_online_features(request("driver_id")).await?;
+        assert_eq!(aliased.metadata.feature_names, ["user_id", "conv_rate"]);
+        assert_eq!(aliased.results, direct.results);
+        assert_ne!(aliased.results[1].statuses, [FeatureStatus::NotFound]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn defaults_answer_features_not_found() -> Result<()> {
+        let defaults = BTreeMap::from([(
+            "driver_hourly_stats".to_string(),
+            BTreeMap::from([
+                ("conv_rate".to_string(), serde_json::json!(0.5)),
+                ("avg_daily_trips".to_string(), serde_json::json!("many")),
+                ("unknown".to_string(), serde_json::json!(1)),
+            ]),
+        )]);
+        let store = get_feature_store().await?.with_feature_defaults(&defaults);
+        assert_eq!(
+            store.check_feature_defaults().await?,
+            [
+                "driver_hourly_stats__avg_daily_trips",
+                "driver_hourly_stats__unknown"
+            ]
+        );
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1001), EntityIdValue::Int(999_999)],
+            )]),
+            features: Some(vec![
+                "driver_hourly_stats:conv_rate".to_string(),
+                "driver_hourly_stats:avg_daily_trips".to_string(),
+            ]),
+            ..Default::default()
+        };
+        let result = store.get_online_features(request).await?;
+        let conv_rate = &result.results[1];
+        assert_ne!(conv_rate.statuses[0], FeatureStatus::DefaultValue);
+        assert_eq!(conv_rate.statuses[1], FeatureStatus::DefaultValue);
+        assert_eq!(
+            conv_rate.values[1].0.val,
+            Some(crate::feast::types::value::Val::FloatVal(0.5))
+        );
+        // Defaults which cannot be converted to the feature type are not answered
+        assert_eq!(result.results[2].statuses[1], FeatureStatus::NotFound);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn feature_service_version_follows_its_definition() -> Result<()> {
+        let request = |feature_service: Option<&str>, features: Option<Vec<String>>| {
+            GetOnlineFeaturesRequest {
+                entities: OrderedMap::from_iter([
+                    ("truck_id".to_string(), vec![EntityIdValue::Int(1002)]),
+                    ("driver_id".to_string(), vec![EntityIdValue::Int(1003)]),
+                ]),
+                feature_service: feature_service.map(str::to_string),
+                features,
+                ..Default::default()
+            }
+        };
+        let version = |response: GetOnlineFeatureResponse| response.feature_service_version;
+        let store = get_feature_store().await?;
+        let alias = version(
+            store
+                .get_online_features(request(Some("driver_activity_alias"), None))
+                .await?,
+        )
+        .unwrap();
+        assert_eq!(alias.len(), 32);
+        let again = version(
+            get_feature_store()
+                .await?
+                .get_online_features(request(Some("driver_activity_alias"), None))
+                .await?,
+        );
+        assert_eq!(again.as_deref(), Some(alias.as_str()));
+        let v4 = version(
+            store
+                .get_online_features(request(Some("driver_activity_v4"), None))
+                .await?,
+        );
+        assert_ne!(v4.as_deref(), Some(alias.as_str()));
+
+        // Overriding a TTL of the service changes its definition
+        let overrides = BTreeMap::from([(
+            "driver_activity_alias".to_string(),
+            BTreeMap::from([("driver_alias".to_string(), 60)]),
+        )]);
+        let overridden = version(
+            get_feature_store()
+                .await?
+                .with_ttl_overrides(&overrides)
+                .get_online_features(request(Some("driver_activity_alias"), None))
+                .await?,
+        );
+        assert_ne!(overridden.as_deref(), Some(alias.as_str()));
+
+        let features = vec!["driver_hourly_stats:conv_rate".to_string()];
+        let by_reference = store
+            .get_online_features(request(None, Some(features)))
+            .await?;
+        assert_eq!(version(by_reference), None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn debug_timings_need_to_be_enabled() -> Result<()> {
+        let request = || GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1001), EntityIdValue::Int(1002)],
+            )]),
+            features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+            debug: Some(true),
+            ..Default::default()
+        };
+        let disabled = get_feature_store().await?;
+        assert_eq!(disabled.get_online_features(request()).await?.debug, None);
+
+        let store = get_feature_store().await?.with_debug_responses(true);
+        let diagnostics = store.get_online_features(request()).await?.debug.unwrap();
+        assert!(!diagnostics.cached);
+        assert_eq!(diagnostics.entity_rows, 2);
+        assert_eq!(diagnostics.registry_generation, Some(0));
+        assert_eq!(diagnostics.store_calls.len(), 1);
+        assert_eq!(
+            diagnostics.store_calls[0].feature_views,
+            ["driver_hourly_stats"]
+        );
+        assert_eq!(diagnostics.store_calls[0].keys, 2);
+        assert!(diagnostics.duration_ms >= diagnostics.store_calls[0].duration_ms);
+
+        let json = serde_json::to_value(store.get_online_features(request()).await?)?;
+        assert!(json["debug"]["registry_ms"].is_number());
+        assert_eq!(json["debug"]["registry_generation"], 0);
+        let quiet = GetOnlineFeaturesRequest {
+            debug: None,
+            ..request()
+        };
+        let json = serde_json::to_value(store.get_online_features(quiet).await?)?;
+        assert!(json.get("debug").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn columns_follow_request_order() -> Result<()> {
+        let store = get_feature_store().await?;
+        let request = |features: &[&str]| GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
                 "driver_id".to_string(),
-                "truck_id".to_string()
+                vec![EntityIdValue::Int(1001), EntityIdValue::Int(1002)],
+            )]),
+            features: Some(features.iter().map(|f| f.to_string()).collect()),
+            full_feature_names: Some(true),
+            ..Default::default()
+        };
+        let features = [
+            "driver_hourly_stats:avg_daily_trips",
+            "driver_hourly_stats_fresh:conv_rate",
+            "driver_hourly_stats:conv_rate",
+        ];
+
+        let result = store.get_online_features(request(&features)).await?;
+        assert_eq!(
+            result.metadata.feature_names,
+            [
+                "driver_id",
+                "driver_hourly_stats__avg_daily_trips",
+                "driver_hourly_stats_fresh__conv_rate",
+                "driver_hourly_stats__conv_rate",
+            ]
+        );
+        for _ in 0..5 {
+            let repeated = store.get_online_features(request(&features)).await?;
+            assert_eq!(
+                repeated.metadata.feature_names,
+                result.metadata.feature_names
+            );
+        }
+
+        let reversed: Vec<&str> = features.iter().rev().copied().collect();
+        let result = store.get_online_features(request(&reversed)).await?;
+        assert_eq!(
+            result.metadata.feature_names[1..],
+            [
+                "driver_hourly_stats__conv_rate",
+                "driver_hourly_stats_fresh__conv_rate",
+                "driver_hourly_stats__avg_daily_trips",
             ]
         );
         Ok(())
     }
+
+    #[tokio::test]
+    async fn reject_colliding_feature_names() -> Result<()> {
+        let store = get_feature_store().await?;
+        let request = |full_feature_names| GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1001)],
+            )]),
+            features: Some(vec![
+                "driver_hourly_stats:conv_rate".to_string(),
+                "driver_hourly_stats_fresh:conv_rate".to_string(),
+            ]),
+            full_feature_names: Some(full_feature_names),
+            ..Default::default()
+        };
+
+        let err = store.get_online_features(request(false)).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<crate::error::FeastCoreError>(),
+            Some(&crate::error::FeastCoreError::feature_name_collision(
+                vec!["conv_rate".to_string()],
+                false
+            ))
+        );
+        let result = store.get_online_features(request(true)).await?;
+        assert_eq!(result.metadata.feature_names.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_features_alias_by_mapped_key_only() -> Result<()> {
+        let store = get_feature_store().await?;
+        let request = |entity: &str| GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(entity.to_string(), vec![EntityIdValue::Int(1002)])]),
+            feature_service: Some("driver_activity_alias".to_string()),
+            ..Default::default()
+        };
+
+        let aliased = store.get_online_features(request("truck_id")).await?;
+        let direct = store
+            .get_online_features(GetOnlineFeaturesRequest {
+                features: Some(vec!["driver_hourly_stats_fresh:conv_rate".to_string()]),
+                feature_service: None,
+                ..request("driver_id")
+            })
+            .await?;
+        let column = |response: &GetOnlineFeatureResponse, name: &str| {
+            let idx = response
+                .metadata
+                .feature_names
+                .iter()
+                .position(|feature_name| feature_name == name)
+                .unwrap();
+            response.results[idx].values.clone()
+        };
+        assert_eq!(column(&aliased, "truck_id"), column(&direct, "driver_id"));
+        assert_eq!(column(&aliased, "conv_rate"), column(&direct, "conv_rate"));
+        Ok(())
+    }
+
+    /// Full feature names of a feature service mapping its join key, as the response
+    /// schema describes them.
+    #[tokio::test]
+    async fn full_feature_names_of_aliased_join_keys() -> Result<()> {
+        let store = get_feature_store().await?;
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "truck_id".to_string(),
+                vec![EntityIdValue::Int(1002)],
+            )]),
+            feature_service: Some("driver_activity_alias".to_string()),
+            full_feature_names: Some(true),
+            ..Default::default()
+        };
+        let response = store.get_online_features(request).await?;
+        // Entity columns keep the mapped join key, features take the projection alias
+        assert_eq!(
+            response.metadata.feature_names,
+            [
+                "truck_id",
+                "driver_alias__conv_rate",
+                "driver_alias__acc_rate",
+                "driver_alias__avg_daily_trips",
+            ]
+        );
+        let schema = store.response_schema("driver_activity_alias", true).await?;
+        let schema_names: Vec<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(response.metadata.feature_names, schema_names);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn view_projected_twice_answers_both_projections() -> Result<()> {
+        use crate::feast::core::{
+            FeatureService as FeatureServiceProto, FeatureServiceMeta, FeatureServiceSpec,
+            FeatureViewProjection,
+        };
+
+        let project_dir = env!("CARGO_MANIFEST_DIR");
+        let mut registry = crate::registry::file_registry::read_registry_proto(
+            std::path::Path::new(&format!("{}/test_data/registry.pb", project_dir)),
+        )?;
+        let conv_rate = registry
+            .feature_views
+            .iter()
+            .filter_map(|view| view.spec.as_ref())
+            .find(|spec| spec.name == "driver_hourly_stats")
+            .and_then(|spec| spec.features.iter().find(|field| field.name == "conv_rate"))
+            .cloned()
+            .expect("driver_hourly_stats has a conv_rate feature");
+        let projection = |alias: &str, join_key: &str| FeatureViewProjection {
+            feature_view_name: "driver_hourly_stats".to_string(),
+            feature_view_name_alias: alias.to_string(),
+            feature_columns: vec![conv_rate.clone()],
+            join_key_map: [("driver_id".to_string(), join_key.to_string())].into(),
+            ..Default::default()
+        };
+        registry.feature_services.push(FeatureServiceProto {
+            spec: Some(FeatureServiceSpec {
+                name: "driver_pairs".to_string(),
+                features: vec![
+                    projection("origin", "origin_driver"),
+                    projection("destination", "destination_driver"),
+                ],
+                ..Default::default()
+            }),
+            meta: Some(FeatureServiceMeta::default()),
+        });
+        let sqlite_store = SqliteOnlineStore::from_options(
+            &format!("{}/test_data/online_store.db", project_dir),
+            "golden_hornet".to_string(),
+            ConnectionOptions::default(),
+        )
+        .await?;
+        let store = FeatureStore::new(
+            Arc::new(FileFeatureRegistry::from_proto(registry)?),
+            Arc::new(sqlite_store),
+        );
+
+        let ids = |ids: &[i64]| -> Vec<EntityIdValue> {
+            ids.iter().map(|id| EntityIdValue::Int(*id)).collect()
+        };
+        let response = store
+            .get_online_features(GetOnlineFeaturesRequest {
+                entities: OrderedMap::from_iter([
+                    ("origin_driver".to_string(), ids(&[1005, 1002])),
+                    ("destination_driver".to_string(), ids(&[1002, 2003])),
+                ]),
+                feature_service: Some("driver_pairs".to_string()),
+                full_feature_names: Some(true),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(
+            response.metadata.feature_names,
+            [
+                "origin_driver",
+                "destination_driver",
+                "origin__conv_rate",
+                "destination__conv_rate",
+            ]
+        );
+        let conv_rate_request = |driver_ids: &[i64]| GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([("driver_id".to_string(), ids(driver_ids))]),
+            features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let origin = store
+            .get_online_features(conv_rate_request(&[1005, 1002]))
+            .await?;
+        let destination = store
+            .get_online_features(conv_rate_request(&[1002, 2003]))
+            .await?;
+        assert_eq!(response.results[2], origin.results[1]);
+        assert_eq!(response.results[3], destination.results[1]);
+        assert_ne!(response.results[2].values, response.results[3].values);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn coerce_entity_values_to_column_type() -> Result<()> {
+        let request = |ids: Vec<EntityIdValue>| GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([("driver_id".to_string(), ids)]),
+            features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let string_ids = || {
+            vec![
+                EntityIdValue::String("1001".to_string()),
+                EntityIdValue::String(" 1002".to_string()),
+            ]
+        };
+
+        let store = get_feature_store().await?;
+        let coerced = store.get_online_features(request(string_ids())).await?;
+        let typed = store
+            .get_online_features(request(vec![
+                EntityIdValue::Int(1001),
+                EntityIdValue::Int(1002),
+            ]))
+            .await?;
+        assert_eq!(coerced.results, typed.results);
+        assert!(coerced.results.iter().all(|result| {
+            result
+                .statuses
+                .iter()
+                .all(|s| *s != FeatureStatus::NotFound)
+        }));
+
+        let invalid = store
+            .get_online_features(request(vec![EntityIdValue::String("abc".to_string())]))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            invalid.downcast_ref::<FeastCoreError>(),
+            Some(&FeastCoreError::invalid_entity_value(
+                "driver_id",
+                "\"abc\"",
+                "INT64"
+            ))
+        );
+
+        let strict = get_feature_store()
+            .await?
+            .with_entity_key_coercion(EntityKeyCoercion::Strict);
+        assert!(
+            strict
+                .get_online_features(request(string_ids()))
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn push_then_get_features() -> Result<()> {
+        let online_store_db = temp_online_store()?;
+        let sqlite_path = online_store_db.path();
+        let store = get_feature_store_with_db(sqlite_path.to_str().unwrap()).await?;
+
+        let df = HashMap::from_iter([
+            ("driver_id".to_string(), vec![serde_json::json!(9001)]),
+            ("conv_rate".to_string(), vec![serde_json::json!(0.25)]),
+            ("acc_rate".to_string(), vec![serde_json::json!(0.5)]),
+            (
+                "event_timestamp".to_string(),
+                vec![serde_json::json!(chrono::Utc::now().to_rfc3339())],
+            ),
+        ]);
+        store
+            .push(PushRequest {
+                push_source_name: "driver_stats_push_source".to_string(),
+                df,
+                ..Default::default()
+            })
+            .await?;
+
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(9001)],
+            )]),
+            features: Some(vec!["driver_hourly_stats_fresh:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let result = store.get_online_features(request).await?;
+        let conv_rate_idx = result
+            .metadata
+            .feature_names
+            .iter()
+            .position(|name| name == "conv_rate")
+            .unwrap();
+        assert_eq!(
+            result.results[conv_rate_idx].values[0].0.val,
+            Some(Val::FloatVal(0.25))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn push_rejects_invalid_data() -> Result<()> {
+        let store = get_feature_store().await?;
+        let df = HashMap::from_iter([
+            ("driver_id".to_string(), vec![serde_json::json!(9001)]),
+            ("conv_rate".to_string(), vec![serde_json::json!("fast")]),
+            (
+                "event_timestamp".to_string(),
+                vec![serde_json::json!("2024-05-01T10:00:00.250Z")],
+            ),
+        ]);
+        let err = store
+            .push(PushRequest {
+                push_source_name: "driver_stats_push_source".to_string(),
+                df,
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<FeastCoreError>().unwrap();
+        assert!(err.is_invalid_request());
+        assert!(err.to_string().contains("conv_rate"), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn undecodable_values_are_invalid() -> Result<()> {
+        use sqlx::Connection;
+
+        let online_store_db = temp_online_store()?;
+        let sqlite_path = online_store_db.path();
+        let mut connection =
+            sqlx::SqliteConnection::connect(&format!("sqlite://{}", sqlite_path.display())).await?;
+        sqlx::query(
+            "UPDATE golden_hornet_driver_hourly_stats SET value = X'0aff' \
+             WHERE feature_name = 'conv_rate'",
+        )
+        .execute(&mut connection)
+        .await?;
+        connection.close().await?;
+        let store = get_feature_store_with_db(sqlite_path.to_str().unwrap()).await?;
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1002), EntityIdValue::Int(-1)],
+            )]),
+            features: Some(vec![
+                "driver_hourly_stats:conv_rate".to_string(),
+                "driver_hourly_stats:acc_rate".to_string(),
+            ]),
+            ..Default::default()
+        };
+        let result = store.get_online_features(request).await;
+        let result = result?;
+        assert_eq!(
+            result.results[1].statuses,
+            [FeatureStatus::Invalid, FeatureStatus::NotFound]
+        );
+        assert_eq!(result.results[1].values[0].0.val, None);
+        assert_ne!(result.results[2].statuses[0], FeatureStatus::Invalid);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn legacy_values_of_lenient_views() -> Result<()> {
+        use sqlx::Connection;
+
+        let online_store_db = temp_online_store()?;
+        let sqlite_path = online_store_db.path();
+        let mut connection =
+            sqlx::SqliteConnection::connect(&format!("sqlite://{}", sqlite_path.display())).await?;
+        // Raw UTF-8 bytes are no valid protobuf message
+        sqlx::query(
+            "UPDATE golden_hornet_driver_hourly_stats SET value = CAST('{[' AS BLOB) \
+             WHERE feature_name = 'conv_rate'",
+        )
+        .execute(&mut connection)
+        .await?;
+        connection.close().await?;
+        let store = get_feature_store_with_db(sqlite_path.to_str().unwrap()).await?;
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1002)],
+            )]),
+            features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let strict = store.get_online_features(request.clone()).await;
+        let lenient = store
+            .with_lenient_decoding_views(&["driver_hourly_stats".to_string()])
+            .get_online_features(request)
+            .await;
+        assert_eq!(strict?.results[1].statuses, [FeatureStatus::Invalid]);
+        let lenient = lenient?;
+        assert_ne!(lenient.results[1].statuses[0], FeatureStatus::Invalid);
+        assert_eq!(
+            lenient.results[1].values[0].0.val,
+            Some(Val::StringVal("{[".to_string()))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tombstones_are_not_found() -> Result<()> {
+        let online_store_db = temp_online_store()?;
+        let sqlite_path = online_store_db.path();
+        let store = get_feature_store_with_db(sqlite_path.to_str().unwrap()).await?;
+        let view = rodeo().get_or_intern("driver_hourly_stats_fresh");
+        store
+            .online_store
+            .online_write_batch(
+                view,
+                vec![OnlineWriteRow {
+                    entity_key: EntityKey {
+                        join_keys: vec!["driver_id".to_string()],
+                        entity_values: vec![Value {
+                            val: Some(Val::Int64Val(1002)),
+                        }],
+                    },
+                    values: vec![(rodeo().get_or_intern("conv_rate"), Value { val: None })],
+                    event_ts: chrono::Utc::now(),
+                    created_ts: None,
+                }],
+            )
+            .await?;
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1002)],
+            )]),
+            features: Some(vec!["driver_hourly_stats_fresh:conv_rate".to_string()]),
+            ..Default::default()
+        };
+
+        let result = store.get_online_features(request.clone()).await?;
+        assert_eq!(result.results[1].statuses, [FeatureStatus::NullValue]);
+
+        let store = store.with_tombstone_views(&["driver_hourly_stats_fresh".to_string()]);
+        let result = store.get_online_features(request).await?;
+        assert_eq!(result.results[1].statuses, [FeatureStatus::NotFound]);
+        assert_eq!(
+            result.results[1].event_timestamps,
+            [DateTime::<Utc>::UNIX_EPOCH]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_file_rows_then_get_features() -> Result<()> {
+        let online_store_db = temp_online_store()?;
+        let sqlite_path = online_store_db.path();
+        let store = get_feature_store_with_db(sqlite_path.to_str().unwrap()).await?;
+
+        let csv = format!(
+            "driver_id,conv_rate,acc_rate,ts\n9101,0.75,0.5,{}\n9102,0.25,,{}\n",
+            chrono::Utc::now().to_rfc3339(),
+            chrono::Utc::now().to_rfc3339()
+        );
+        let columns = crate::load::read_csv(csv.as_bytes(), |_| None)?;
+        let missing_ts = store
+            .write_to_online_store(
+                "driver_hourly_stats_fresh",
+                &columns,
+                "event_timestamp",
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            missing_ts.to_string(),
+            "Missing column event_timestamp in data written to feature view driver_hourly_stats_fresh"
+        );
+        let count = store
+            .write_to_online_store("driver_hourly_stats_fresh", &columns, "ts", None)
+            .await?;
+        assert_eq!(count, 2);
+
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(9101), EntityIdValue::Int(9102)],
+            )]),
+            features: Some(vec!["driver_hourly_stats_fresh:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let result = store.get_online_features(request).await?;
+        assert_eq!(
+            result.results[1]
+                .values
+                .iter()
+                .map(|value| value.0.val.clone())
+                .collect::<Vec<_>>(),
+            [Some(Val::FloatVal(0.75)), Some(Val::FloatVal(0.25))]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_entity_keys_purges_views_of_join_keys() -> Result<()> {
+        let online_store_db = temp_online_store()?;
+        let sqlite_path = online_store_db.path();
+        let store = get_feature_store_with_db(sqlite_path.to_str().unwrap())
+            .await?
+            .with_response_cache(&ResponseCacheConfig {
+                max_entries: 10,
+                feature_services: [("driver_activity_alias".to_string(), 60_000)].into(),
+            });
+        let cached_request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1005)],
+            )]),
+            feature_service: Some("driver_activity_alias".to_string()),
+            ..Default::default()
+        };
+        let cached = store.get_online_features(cached_request.clone()).await?;
+        assert!(
+            cached.results[1..]
+                .iter()
+                .any(|column| column.values[0].0.val.is_some())
+        );
+        let csv = format!(
+            "driver_id,conv_rate,ts\n9201,0.75,{}\n",
+            chrono::Utc::now().to_rfc3339()
+        );
+        store
+            .write_to_online_store(
+                "driver_hourly_stats_fresh",
+                &crate::load::read_csv(csv.as_bytes(), |_| None)?,
+                "ts",
+                None,
+            )
+            .await?;
+
+        let response = store
+            .delete_entity_keys(DeleteEntityKeysRequest {
+                entities: HashMap::from_iter([(
+                    "driver_id".to_string(),
+                    vec![EntityIdValue::Int(9201), EntityIdValue::Int(9202)],
+                )]),
+            })
+            .await?;
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(9201)],
+            )]),
+            features: Some(vec!["driver_hourly_stats_fresh:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let result = store.get_online_features(request).await?;
+
+        assert!(response.deleted.contains(&DeletedEntityKeys {
+            feature_view: "driver_hourly_stats_fresh".to_string(),
+            entity_keys: 1,
+        }));
+        assert_eq!(result.results[1].values[0].0.val, None);
+
+        // Responses cached before the purge are not answered anymore
+        store
+            .delete_entity_keys(DeleteEntityKeysRequest {
+                entities: HashMap::from_iter([(
+                    "driver_id".to_string(),
+                    vec![EntityIdValue::Int(1005)],
+                )]),
+            })
+            .await?;
+        let purged = store.get_online_features(cached_request).await?;
+        assert!(
+            purged.results[1..]
+                .iter()
+                .all(|column| column.values[0].0.val.is_none())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn response_fields_are_trimmed() -> Result<()> {
+        let store = get_feature_store().await?;
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1005), EntityIdValue::Int(1002)],
+            )]),
+            features: Some(vec!["driver_hourly_stats:acc_rate".to_string()]),
+            include_entity_values: Some(false),
+            include_statuses: Some(false),
+            include_timestamps: Some(false),
+            ..Default::default()
+        };
+        let result = store.get_online_features(request).await?;
+        assert_eq!(result.metadata.feature_names, ["acc_rate"]);
+        assert_eq!(result.results[0].values.len(), 2);
+        assert!(result.results[0].statuses.is_empty());
+        assert!(result.results[0].event_timestamps.is_empty());
+        let json = serde_json::to_value(&result)?;
+        assert!(json["results"][0].get("statuses").is_none());
+        assert!(json["results"][0].get("event_timestamps").is_none());
+        assert!(json["results"][0].get("created_timestamps").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn created_timestamps_on_request() -> Result<()> {
+        let store = get_feature_store().await?;
+        let request = GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1005), EntityIdValue::Int(-1)],
+            )]),
+            features: Some(vec!["driver_hourly_stats:acc_rate".to_string()]),
+            include_created_timestamps: Some(true),
+            ..Default::default()
+        };
+        let result = store.get_online_features(request).await?;
+        let acc_rate = &result.results[1];
+        assert!(
+            acc_rate.created_timestamps[0]
+                .is_some_and(|created| created >= acc_rate.event_timestamps[0])
+        );
+        assert_eq!(acc_rate.created_timestamps[1], None);
+        assert_eq!(result.results[0].created_timestamps, [None, None]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_feature_freshness() -> Result<()> {
+        let store = get_feature_store().await?;
+        let request = FeatureFreshnessRequest {
+            feature_view: "driver_hourly_stats".to_string(),
+            entities: HashMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1005), EntityIdValue::Int(-1)],
+            )]),
+        };
+        let result = store.get_feature_freshness(request).await?;
+        assert_eq!(result.event_timestamps.len(), 2);
+        assert!(result.event_timestamps[0].is_some());
+        assert!(result.event_timestamps[1].is_none());
+
+        let missing_entity = FeatureFreshnessRequest {
+            feature_view: "driver_hourly_stats".to_string(),
+            ..Default::default()
+        };
+        assert!(store.get_feature_freshness(missing_entity).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn search_similar_checks_feature_and_store_support() -> Result<()> {
+        let store = get_feature_store().await?;
+        let unknown = store
+            .search_similar("driver_hourly_stats", "missing", vec![0.5], 3)
+            .await
+            .unwrap_err();
+        assert!(unknown.to_string().contains("has no feature missing"));
+        let unsupported = store
+            .search_similar("driver_hourly_stats", "conv_rate", vec![0.5], 3)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            unsupported.to_string(),
+            "Online store does not support vector search"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn push_unknown_source() -> Result<()> {
+        let store = get_feature_store().await?;
+        let result = store
+            .push(PushRequest {
+                push_source_name: "missing_source".to_string(),
+                ..Default::default()
+            })
+            .await;
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    /// Registry replaced on demand, like a cached registry refreshed in the background.
+    struct SwappingRegistry {
+        current: arc_swap::ArcSwap<FileFeatureRegistry>,
+        generation: std::sync::atomic::AtomicU64,
+    }
+
+    impl SwappingRegistry {
+        fn new(registry: FileFeatureRegistry) -> Self {
+            Self {
+                current: arc_swap::ArcSwap::from_pointee(registry),
+                generation: Default::default(),
+            }
+        }
+
+        fn swap(&self, registry: FileFeatureRegistry) {
+            self.current.store(Arc::new(registry));
+            self.generation
+                .fetch_add(1, std::sync::atomic::Ordering::Release);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FeatureRegistryService for SwappingRegistry {
+        async fn request_to_view_keys(
+            &self,
+            request: RequestedFeatures,
+        ) -> Result<OrderedMap<Feature, Arc<FeatureView>>> {
+            self.current.load().request_to_view_keys(request).await
+        }
+
+        async fn feature_view(&self, feature_view_name: Spur) -> Result<Arc<FeatureView>> {
+            self.current.load().feature_view(feature_view_name).await
+        }
+
+        async fn feature_views(&self) -> Result<Vec<Arc<FeatureView>>> {
+            self.current.load().feature_views().await
+        }
+
+        async fn push_source_views(&self, push_source_name: Spur) -> Result<Vec<Arc<FeatureView>>> {
+            self.current
+                .load()
+                .push_source_views(push_source_name)
+                .await
+        }
+
+        fn generation(&self) -> u64 {
+            self.generation.load(std::sync::atomic::Ordering::Acquire)
+        }
+    }
+
+    /// Test registry, leaving out the feature view `removed_view` when given.
+    fn test_registry(removed_view: Option<&str>) -> Result<FileFeatureRegistry> {
+        let registry_path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/registry.pb");
+        let mut proto = crate::registry::file_registry::read_registry_proto(&registry_path)?;
+        proto.feature_views.retain(|view| {
+            view.spec
+                .as_ref()
+                .is_none_or(|spec| Some(spec.name.as_str()) != removed_view)
+        });
+        FileFeatureRegistry::from_proto(proto)
+    }
+
+    /// Online store whose reads happen while the registry is refreshed without
+    /// `driver_hourly_stats`.
+    struct RefreshingStore {
+        inner: Arc<dyn OnlineStore>,
+        registry: Arc<SwappingRegistry>,
+    }
+
+    #[async_trait::async_trait]
+    impl OnlineStore for RefreshingStore {
+        async fn get_feature_values(
+            &self,
+            features: HashMap<HashEntityKey, Vec<Feature>>,
+        ) -> Result<Vec<OnlineStoreRow>> {
+            self.registry
+                .swap(test_registry(Some("driver_hourly_stats"))?);
+            self.inner.get_feature_values(features).await
+        }
+    }
+
+    /// Online store counting the registry reloads it is told about.
+    struct ReloadCountingStore {
+        inner: Arc<dyn OnlineStore>,
+        reloads: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl OnlineStore for ReloadCountingStore {
+        async fn get_feature_values(
+            &self,
+            features: HashMap<HashEntityKey, Vec<Feature>>,
+        ) -> Result<Vec<OnlineStoreRow>> {
+            self.inner.get_feature_values(features).await
+        }
+
+        fn registry_reloaded(&self) {
+            self.reloads.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn online_stores_are_told_of_registry_reloads() -> Result<()> {
+        let registry = Arc::new(SwappingRegistry::new(test_registry(None)?));
+        let online_store = Arc::new(ReloadCountingStore {
+            inner: Arc::clone(&get_feature_store().await?.online_store),
+            reloads: Default::default(),
+        });
+        let store = FeatureStore::new(registry.clone(), online_store.clone());
+
+        store.get_online_features(driver_stats_request()).await?;
+        assert_eq!(online_store.reloads.load(Ordering::SeqCst), 0);
+        registry.swap(test_registry(None)?);
+        store.get_online_features(driver_stats_request()).await?;
+        store.get_online_features(driver_stats_request()).await?;
+        assert_eq!(online_store.reloads.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn replay_recorded_responses() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let recording = get_feature_store()
+            .await?
+            .with_response_recording(ResponseRecording::Record(dir.path().to_path_buf()));
+        let recorded = recording
+            .get_online_features(driver_stats_request())
+            .await?;
+
+        let registry = FileFeatureRegistry::from_path(&std::path::PathBuf::from(format!(
+            "{}/test_data/registry.pb",
+            env!("CARGO_MANIFEST_DIR")
+        )))?;
+        let replaying = FeatureStore::new(
+            Arc::new(registry),
+            Arc::new(crate::onlinestore::in_memory::InMemoryOnlineStore::new()),
+        )
+        .with_response_recording(ResponseRecording::Replay(dir.path().to_path_buf()));
+        let replayed = replaying
+            .get_online_features(driver_stats_request())
+            .await?;
+        assert_eq!(
+            serde_json::to_string(&replayed)?,
+            serde_json::to_string(&recorded)?
+        );
+
+        let unrecorded = GetOnlineFeaturesRequest {
+            full_feature_names: Some(true),
+            ..driver_stats_request()
+        };
+        let err = replaying.get_online_features(unrecorded).await.unwrap_err();
+        assert!(is_not_found(&err), "{:#}", err);
+        Ok(())
+    }
+
+    fn is_not_found(err: &anyhow::Error) -> bool {
+        err.downcast_ref::<FeastCoreError>()
+            .is_some_and(FeastCoreError::is_not_found)
+    }
+
+    fn driver_stats_request() -> GetOnlineFeaturesRequest {
+        GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1005)],
+            )]),
+            features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn feature_view_removed_by_registry_refresh() -> Result<()> {
+        let base = get_feature_store().await?;
+        let registry = Arc::new(SwappingRegistry::new(test_registry(None)?));
+        let store = FeatureStore::new(
+            registry.clone(),
+            Arc::new(RefreshingStore {
+                inner: Arc::clone(&base.online_store),
+                registry: registry.clone(),
+            }),
+        );
+
+        // A request in flight keeps the views it was resolved to
+        let response = store.get_online_features(driver_stats_request()).await?;
+        assert_ne!(response.results[1].statuses[0], FeatureStatus::NotFound);
+
+        let err = store
+            .get_online_features(driver_stats_request())
+            .await
+            .err()
+            .unwrap();
+        assert!(is_not_found(&err), "{:#}", err);
+        let service_request = GetOnlineFeaturesRequest {
+            feature_service: Some("driver_activity_v4".to_string()),
+            features: None,
+            ..driver_stats_request()
+        };
+        let err = store
+            .get_online_features(service_request)
+            .await
+            .err()
+            .unwrap();
+        assert!(is_not_found(&err), "{:#}", err);
+        let err = store
+            .get_feature_freshness(FeatureFreshnessRequest {
+                feature_view: "driver_hourly_stats".to_string(),
+                entities: HashMap::from_iter([(
+                    "driver_id".to_string(),
+                    vec![EntityIdValue::Int(1005)],
+                )]),
+            })
+            .await
+            .err()
+            .unwrap();
+        assert!(is_not_found(&err), "{:#}", err);
+        Ok(())
+    }
+
+    /// Online store answering with rows of a view the request was not resolved to.
+    struct StaleViewStore {
+        inner: Arc<dyn OnlineStore>,
+    }
+
+    #[async_trait::async_trait]
+    impl OnlineStore for StaleViewStore {
+        async fn get_feature_values(
+            &self,
+            features: HashMap<HashEntityKey, Vec<Feature>>,
+        ) -> Result<Vec<OnlineStoreRow>> {
+            let mut rows = self.inner.get_feature_values(features).await?;
+            for row in &mut rows {
+                row.feature_view_name = rodeo().get_or_intern("removed_view");
+            }
+            Ok(rows)
+        }
+    }
+
+    #[tokio::test]
+    async fn rows_of_unresolved_views_are_not_found() -> Result<()> {
+        let base = get_feature_store().await?;
+        let store = FeatureStore::new(
+            Arc::clone(&base.registry),
+            Arc::new(StaleViewStore {
+                inner: Arc::clone(&base.online_store),
+            }),
+        );
+        let err = store
+            .get_online_features(driver_stats_request())
+            .await
+            .err()
+            .unwrap();
+        assert!(is_not_found(&err), "{:#}", err);
+        assert_eq!(err.to_string(), "Feature view 'removed_view' not found");
+        Ok(())
+    }
 }
@@ -0,0 +1,223 @@
+//! Compatibility suite replaying request/response pairs of the Feast Python feature server.
+//!
+//! Every `test_data/golden/*.json` file holds a `request` to `/get-online-features` and the
+//! `response` of the Python server for the test repository, as JSON printed by it; pairs
+//! recorded from a Python server on the same repository can be added as they are. Responses
+//! are compared semantically: columns must come in the order of the Python server, values are
+//! matched by feature name and floats compare with float32 precision, as the Python server prints float32 features as doubles.
+//!
+//! The test repository is extended with the entity-less view `global_stats`, whose single
+//! row holds `active_drivers = 5` written at 2025-09-12T14:00:00Z without a TTL.
+
+use super::FeatureStore;
+use crate::feast::core::{
+    FeatureSpecV2, FeatureView as FeatureViewProto, FeatureViewSpec, Registry,
+};
+use crate::feast::types::value::Val;
+use crate::feast::types::{EntityKey, Value, value_type};
+use crate::intern;
+use crate::model::{
+    DUMMY_ENTITY_ID, DUMMY_ENTITY_NAME, DUMMY_ENTITY_VAL, GetOnlineFeaturesRequest,
+};
+use crate::onlinestore::sqlite_onlinestore::{ConnectionOptions, SqliteOnlineStore};
+use crate::onlinestore::{OnlineStore, OnlineWriteRow};
+use crate::registry::FileFeatureRegistry;
+use crate::test_util::temp_online_store;
+use anyhow::{Context, Result, anyhow};
+use chrono::{TimeZone, Utc};
+use prost::Message;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const ENTITY_LESS_VIEW: &str = "global_stats";
+/// Relative tolerance of float comparisons, slightly above the float32 epsilon.
+const FLOAT_TOLERANCE: f64 = 1e-6;
+
+#[derive(Deserialize)]
+struct GoldenCase {
+    request: GetOnlineFeaturesRequest,
+    response: JsonValue,
+}
+
+fn test_data(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("test_data")
+        .join(relative)
+}
+
+fn entity_less_view() -> FeatureViewProto {
+    FeatureViewProto {
+        spec: Some(FeatureViewSpec {
+            name: ENTITY_LESS_VIEW.to_string(),
+            entities: vec![DUMMY_ENTITY_NAME.to_string()],
+            features: vec![FeatureSpecV2 {
+                name: "active_drivers".to_string(),
+                value_type: value_type::Enum::Int64 as i32,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+async fn golden_feature_store(sqlite_path: &Path) -> Result<FeatureStore> {
+    let mut registry = Registry::decode(std::fs::read(test_data("registry.pb"))?.as_slice())?;
+    registry.feature_views.push(entity_less_view());
+    let registry = FileFeatureRegistry::from_proto(registry)?;
+
+    let online_store = SqliteOnlineStore::from_options(
+        sqlite_path.to_str().unwrap(),
+        "golden_hornet".to_string(),
+        ConnectionOptions::default(),
+    )
+    .await?;
+    let rodeo = intern::rodeo_ref();
+    let row = OnlineWriteRow {
+        entity_key: EntityKey {
+            join_keys: vec![DUMMY_ENTITY_ID.to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::StringVal(DUMMY_ENTITY_VAL.to_string())),
+            }],
+        },
+        values: vec![(
+            rodeo.get_or_intern("active_drivers"),
+            Value {
+                val: Some(Val::Int64Val(5)),
+            },
+        )],
+        event_ts: Utc.with_ymd_and_hms(2025, 9, 12, 14, 0, 0).unwrap(),
+        created_ts: None,
+    };
+    online_store
+        .online_write_batch(rodeo.get_or_intern(ENTITY_LESS_VIEW), vec![row])
+        .await?;
+    Ok(FeatureStore::new(
+        Arc::new(registry),
+        Arc::new(online_store),
+    ))
+}
+
+/// Key the result columns of a response by feature name, so that a difference is reported
+/// against the column it is in. Column order is compared separately.
+fn columns_by_name(response: &JsonValue) -> Option<JsonValue> {
+    let names = response.pointer("/metadata/feature_names")?.as_array()?;
+    let results = response.get("results")?.as_array()?;
+    if names.len() != results.len() {
+        return None;
+    }
+    let columns = names
+        .iter()
+        .zip(results)
+        .map(|(name, column)| Some((name.as_str()?.to_string(), column.clone())))
+        .collect::<Option<serde_json::Map<_, _>>>()?;
+    Some(JsonValue::Object(columns))
+}
+
+/// Describe the first difference between `expected` and `actual` below `path`.
+fn first_difference(path: &str, expected: &JsonValue, actual: &JsonValue) -> Option<String> {
+    match (expected, actual) {
+        (JsonValue::Object(expected), JsonValue::Object(actual)) => {
+            if let Some(key) = expected.keys().find(|key| !actual.contains_key(*key)) {
+                return Some(format!("{}.{} is missing", path, key));
+            }
+            if let Some(key) = actual.keys().find(|key| !expected.contains_key(*key)) {
+                return Some(format!("{}.{} is unexpected", path, key));
+            }
+            expected.iter().find_map(|(key, value)| {
+                first_difference(&format!("{}.{}", path, key), value, &actual[key])
+            })
+        }
+        (JsonValue::Array(expected), JsonValue::Array(actual)) => {
+            if expected.len() != actual.len() {
+                return Some(format!(
+                    "{} has {} items instead of {}",
+                    path,
+                    actual.len(),
+                    expected.len()
+                ));
+            }
+            expected
+                .iter()
+                .zip(actual)
+                .enumerate()
+                .find_map(|(i, (expected, actual))| {
+                    first_difference(&format!("{}[{}]", path, i), expected, actual)
+                })
+        }
+        (JsonValue::Number(expected), JsonValue::Number(actual))
+            if expected.is_f64() || actual.is_f64() =>
+        {
+            let (expected_f, actual_f) = (expected.as_f64()?, actual.as_f64()?);
+            let tolerance = FLOAT_TOLERANCE * expected_f.abs().max(actual_f.abs());
+            ((expected_f - actual_f).abs() > tolerance)
+                .then(|| format!("{} is {} instead of {}", path, actual, expected))
+        }
+        _ => {
+            (expected != actual).then(|| format!("{} is {} instead of {}", path, actual, expected))
+        }
+    }
+}
+
+#[tokio::test]
+async fn golden_responses() -> Result<()> {
+    let online_store_db = temp_online_store()?;
+    let store = golden_feature_store(online_store_db.path()).await?;
+
+    let mut fixtures: Vec<PathBuf> = std::fs::read_dir(test_data("golden"))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    fixtures.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no golden fixtures found");
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let name = fixture.file_name().unwrap().to_string_lossy();
+        let case: GoldenCase = serde_json::from_str(&std::fs::read_to_string(fixture)?)
+            .with_context(|| format!("Invalid golden fixture {}", name))?;
+        let response = store
+            .get_online_features(case.request)
+            .await
+            .with_context(|| format!("Request of {} failed", name))?;
+        let actual = serde_json::to_value(&response)?;
+        let expected_names = case.response.pointer("/metadata/feature_names");
+        let actual_names = actual.pointer("/metadata/feature_names");
+        if expected_names != actual_names {
+            failures.push(format!(
+                "{}: columns are {} instead of {}",
+                name,
+                actual_names.unwrap_or(&JsonValue::Null),
+                expected_names.unwrap_or(&JsonValue::Null)
+            ));
+            continue;
+        }
+        let expected = columns_by_name(&case.response)
+            .ok_or_else(|| anyhow!("Golden fixture {} has a malformed response", name))?;
+        let actual = columns_by_name(&actual).unwrap_or(actual);
+        if let Some(difference) = first_difference("response", &expected, &actual) {
+            failures.push(format!("{}: {}", name, difference));
+        }
+    }
+    if !failures.is_empty() {
+        return Err(anyhow!(
+            "Responses differ from golden files:\n{}",
+            failures.join("\n")
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn compare_floats_with_float32_precision() {
+    let expected = serde_json::json!({"values": [0.16094867885112762, 3, null]});
+    let same = serde_json::json!({"values": [0.16094868, 3, null]});
+    let different = serde_json::json!({"values": [0.1609, 3, null]});
+    assert_eq!(first_difference("response", &expected, &same), None);
+    assert_eq!(
+        first_difference("response", &expected, &different),
+        Some("response.values[0] is 0.1609 instead of 0.16094867885112762".to_string())
+    );
+}
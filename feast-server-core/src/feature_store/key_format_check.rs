@@ -0,0 +1,102 @@
+use crate::config::EntityKeySerializationVersion;
+use crate::key_serialization::detect_key_version;
+use crate::onlinestore::OnlineStore;
+use crate::registry::FeatureRegistryService;
+use anyhow::Result;
+
+/// Number of stored entity keys sampled by [`check_key_format`].
+pub(crate) const SAMPLED_KEYS: usize = 20;
+
+/// Outcome of sampling the entity keys of the online store.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyFormatCheck {
+    /// Number of keys sampled from the online store.
+    pub sampled: usize,
+    /// Sampled keys which do not read as keys of the version the server reads.
+    pub mismatched: usize,
+    /// Version the mismatched keys look written with, when recognised.
+    pub detected_version: Option<EntityKeySerializationVersion>,
+}
+
+/// Sample keys of the feature views of the registry and compare their format with the V3
+/// format the server reads. Keys of another version are never found, so reads of them
+/// return empty features; both a mismatch and a configured version other than 3 are logged
+/// as errors.
+pub(crate) async fn check_key_format(
+    registry: &dyn FeatureRegistryService,
+    online_store: &dyn OnlineStore,
+    configured: &EntityKeySerializationVersion,
+) -> Result<KeyFormatCheck> {
+    if *configured != EntityKeySerializationVersion::V3 {
+        tracing::error!(
+            "entity_key_serialization_version {} is configured, but the server only reads \
+             version 3 keys; features of keys written with version {} are not found",
+            u64::from(configured.clone()),
+            u64::from(configured.clone()),
+        );
+    }
+    let views = registry
+        .feature_views()
+        .await?
+        .iter()
+        .map(|view| view.name)
+        .collect::<Vec<_>>();
+    let keys = online_store.sample_entity_keys(views, SAMPLED_KEYS).await?;
+    let mut check = KeyFormatCheck {
+        sampled: keys.len(),
+        ..Default::default()
+    };
+    for key in &keys {
+        match detect_key_version(key) {
+            Some(EntityKeySerializationVersion::V3) => {}
+            detected => {
+                check.mismatched += 1;
+                check.detected_version = check.detected_version.or(detected);
+            }
+        }
+    }
+    if check.mismatched > 0 {
+        let detected = match &check.detected_version {
+            Some(version) => format!(
+                "they look written with version {}",
+                u64::from(version.clone())
+            ),
+            None => "their version is not recognised".to_string(),
+        };
+        tracing::error!(
+            "{} of {} sampled entity keys of the online store are not version 3 keys ({}); \
+             reads of these keys return empty features until the data is materialized again \
+             with entity_key_serialization_version 3",
+            check.mismatched,
+            check.sampled,
+            detected
+        );
+    }
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::onlinestore::sqlite_onlinestore::{ConnectionOptions, SqliteOnlineStore};
+    use crate::registry::FileFeatureRegistry;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn stored_keys_of_test_data_are_v3() -> Result<()> {
+        let test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_data");
+        let registry = FileFeatureRegistry::from_path(&test_data.join("registry.pb"))?;
+        let online_store = SqliteOnlineStore::from_options(
+            test_data.join("online_store.db").to_str().unwrap(),
+            "golden_hornet".to_string(),
+            ConnectionOptions::default(),
+        )
+        .await?;
+        let check =
+            check_key_format(&registry, &online_store, &EntityKeySerializationVersion::V3).await?;
+        assert!(check.sampled > 0);
+        assert_eq!(check.mismatched, 0);
+        assert_eq!(check.detected_version, None);
+        Ok(())
+    }
+}
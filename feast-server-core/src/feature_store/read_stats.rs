@@ -0,0 +1,125 @@
+use crate::intern;
+use crate::onlinestore::OnlineStoreRow;
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use prost::Message;
+use rustc_hash::FxHashMap as HashMap;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use super::feature_store_impl::FeatureWithKeys;
+use super::response_cache::ResponseCacheStats;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct ViewCounters {
+    requests: u64,
+    values_requested: u64,
+    hits: u64,
+    value_bytes: u64,
+}
+
+/// Read statistics of a single feature view since the last reset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeatureViewReadStats {
+    /// Number of requests which asked for at least one feature of the view.
+    pub requests: u64,
+    /// Number of requested (entity, feature) values.
+    pub values_requested: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_ratio: f64,
+    /// Average encoded size of values found in the online store.
+    pub avg_value_size_bytes: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReadStatsSnapshot {
+    pub since: DateTime<Utc>,
+    pub feature_views: BTreeMap<String, FeatureViewReadStats>,
+    /// Response cache counters per feature service, present when the cache is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_cache: Option<BTreeMap<String, ResponseCacheStats>>,
+}
+
+/// Per feature view read counters collected by the feature store.
+/// Counters are aggregated per request and merged under a single lock.
+#[derive(Debug)]
+pub(crate) struct ReadStats {
+    inner: Mutex<(DateTime<Utc>, HashMap<Spur, ViewCounters>)>,
+}
+
+impl Default for ReadStats {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new((Utc::now(), HashMap::default())),
+        }
+    }
+}
+
+impl ReadStats {
+    pub(crate) fn record(&self, requested: &[FeatureWithKeys], rows: &[OnlineStoreRow]) {
+        let mut per_request: HashMap<Spur, ViewCounters> = HashMap::default();
+        for feature in requested {
+            let counters = per_request
+                .entry(feature.feature.feature_view_name)
+                .or_default();
+            counters.requests = 1;
+            counters.values_requested += feature.entity_keys.len() as u64;
+        }
+        for row in rows {
+            if let Some(counters) = per_request.get_mut(&row.feature_view_name) {
+                counters.hits += 1;
+                counters.value_bytes += row.value.encoded_len() as u64;
+            }
+        }
+
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        for (view, counters) in per_request {
+            let total = guard.1.entry(view).or_default();
+            total.requests += counters.requests;
+            total.values_requested += counters.values_requested;
+            total.hits += counters.hits;
+            total.value_bytes += counters.value_bytes;
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> ReadStatsSnapshot {
+        let rodeo = intern::rodeo_ref();
+        let guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let feature_views = guard
+            .1
+            .iter()
+            .map(|(view, c)| {
+                let misses = c.values_requested.saturating_sub(c.hits);
+                let stats = FeatureViewReadStats {
+                    requests: c.requests,
+                    values_requested: c.values_requested,
+                    hits: c.hits,
+                    misses,
+                    hit_ratio: ratio(c.hits, c.values_requested),
+                    avg_value_size_bytes: ratio(c.value_bytes, c.hits),
+                };
+                (rodeo.resolve(view).to_string(), stats)
+            })
+            .collect();
+        ReadStatsSnapshot {
+            since: guard.0,
+            feature_views,
+            response_cache: None,
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = (Utc::now(), HashMap::default());
+    }
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
@@ -0,0 +1,156 @@
+use crate::error::FeastCoreError;
+use crate::model::GetOnlineFeatureResponse;
+use anyhow::{Result, anyhow};
+use rustc_hash::FxHashMap as HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// Number of requests answered with the response of an identical request in flight.
+pub(crate) const COLLAPSED_REQUESTS_METRIC: &str = "feast_collapsed_requests_total";
+
+type SharedResponse = Arc<OnceCell<Result<GetOnlineFeatureResponse, Arc<anyhow::Error>>>>;
+
+/// Requests in flight by their serialized form. Identical requests arriving meanwhile wait
+/// for the first one and share its response, so a burst of retries reads the online store
+/// once. Should the first request be cancelled, a waiting one fetches the response instead.
+#[derive(Default)]
+pub(crate) struct RequestCollapser {
+    in_flight: Mutex<HashMap<String, SharedResponse>>,
+}
+
+impl RequestCollapser {
+    /// Response of `fetch`, or of the identical request with the same `key` in flight.
+    pub(crate) async fn run<F, Fut>(
+        &self,
+        key: String,
+        fetch: F,
+    ) -> Result<GetOnlineFeatureResponse>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<GetOnlineFeatureResponse>>,
+    {
+        let shared = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            match in_flight.get(&key) {
+                Some(shared) => {
+                    metrics::counter!(COLLAPSED_REQUESTS_METRIC).increment(1);
+                    Arc::clone(shared)
+                }
+                None => {
+                    let shared = SharedResponse::default();
+                    in_flight.insert(key.clone(), Arc::clone(&shared));
+                    shared
+                }
+            }
+        };
+        let guard = InFlightGuard {
+            collapser: self,
+            key,
+            shared,
+        };
+        let result = guard
+            .shared
+            .get_or_init(|| async { fetch().await.map_err(Arc::new) })
+            .await;
+        match result {
+            Ok(response) => Ok(response.clone()),
+            Err(err) => Err(match err.downcast_ref::<FeastCoreError>() {
+                Some(feast_error) => feast_error.clone().into(),
+                None => anyhow!("{:#}", err),
+            }),
+        }
+    }
+}
+
+/// Removes a request from the requests in flight once it is answered, or once its last
+/// waiter is cancelled.
+struct InFlightGuard<'a> {
+    collapser: &'a RequestCollapser,
+    key: String,
+    shared: SharedResponse,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self
+            .collapser
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let Some(current) = in_flight.get(&self.key) else {
+            return;
+        };
+        // References left: the map and this guard
+        let abandoned = Arc::strong_count(&self.shared) <= 2;
+        if Arc::ptr_eq(current, &self.shared) && (self.shared.initialized() || abandoned) {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    async fn slow_fetch(fetches: &AtomicUsize) -> Result<GetOnlineFeatureResponse> {
+        fetches.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(GetOnlineFeatureResponse {
+            warnings: vec!["shared".to_string()],
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn identical_requests_share_one_fetch() -> Result<()> {
+        let collapser = RequestCollapser::default();
+        let fetches = AtomicUsize::new(0);
+        let run = |key: &str| collapser.run(key.to_string(), || slow_fetch(&fetches));
+        let (first, second, other) = tokio::join!(run("a"), run("a"), run("b"));
+        assert_eq!(first?, second?);
+        other?;
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert!(collapser.in_flight.lock().unwrap().is_empty());
+
+        // Answered requests are fetched again
+        run("a").await?;
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn errors_are_shared() {
+        let collapser = RequestCollapser::default();
+        let fetch = || async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err(FeastCoreError::feature_service_not_found("missing").into())
+        };
+        let (first, second) = tokio::join!(
+            collapser.run("a".to_string(), fetch),
+            collapser.run("a".to_string(), fetch)
+        );
+        for result in [first, second] {
+            let err = result.unwrap_err();
+            assert!(
+                err.downcast_ref::<FeastCoreError>()
+                    .is_some_and(FeastCoreError::is_not_found)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_requests_are_removed() {
+        let collapser = RequestCollapser::default();
+        let fetches = AtomicUsize::new(0);
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(5),
+            collapser.run("a".to_string(), || slow_fetch(&fetches)),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        assert!(collapser.in_flight.lock().unwrap().is_empty());
+    }
+}
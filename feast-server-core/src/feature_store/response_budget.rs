@@ -0,0 +1,133 @@
+//! Up front estimate of the memory of feature responses, checked against the configured
+//! `response_budget` before the online store is read.
+
+use crate::config::{BudgetExceeded, ResponseBudgetConfig};
+use crate::error::FeastCoreError;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Resident set size of the server process, sampled while a response budget is configured.
+pub(crate) const RESIDENT_MEMORY_METRIC: &str = "feast_process_resident_memory_bytes";
+/// Interval between samples of the resident set size.
+const RESIDENT_MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+pub(crate) struct ResponseBudget {
+    config: ResponseBudgetConfig,
+    /// Task sampling the resident set size, when created within a runtime.
+    sampler: Option<JoinHandle<()>>,
+}
+
+impl ResponseBudget {
+    pub(crate) fn new(config: &ResponseBudgetConfig) -> Self {
+        let sampler = tokio::runtime::Handle::try_current()
+            .ok()
+            .map(|runtime| runtime.spawn(sample_resident_memory()));
+        Self {
+            config: config.clone(),
+            sampler,
+        }
+    }
+
+    pub(crate) fn estimate(&self, entity_rows: usize, columns: usize) -> u64 {
+        (entity_rows as u64)
+            .saturating_mul(columns as u64)
+            .saturating_mul(self.config.avg_value_bytes)
+    }
+
+    /// Entity rows to read from the online store at a time, `None` when the whole response
+    /// fits in the budget. Fails when it does not and oversized requests are rejected.
+    /// Chunks bound the rows read at a time only: the response joining them is not bounded.
+    pub(crate) fn chunk_rows(
+        &self,
+        entity_rows: usize,
+        columns: usize,
+    ) -> Result<Option<usize>, FeastCoreError> {
+        let estimated_bytes = self.estimate(entity_rows, columns);
+        if estimated_bytes <= self.config.max_bytes {
+            return Ok(None);
+        }
+        match self.config.on_exceed {
+            BudgetExceeded::Reject => Err(FeastCoreError::response_too_large(
+                entity_rows,
+                columns,
+                estimated_bytes,
+                self.config.max_bytes,
+            )),
+            BudgetExceeded::Chunk => {
+                let row_bytes = self.estimate(1, columns).max(1);
+                let rows = (self.config.max_bytes / row_bytes).max(1);
+                Ok(Some(usize::try_from(rows).unwrap_or(usize::MAX)))
+            }
+        }
+    }
+}
+
+impl Drop for ResponseBudget {
+    fn drop(&mut self) {
+        if let Some(sampler) = &self.sampler {
+            sampler.abort();
+        }
+    }
+}
+
+/// Export the resident set size of the process periodically. Only Linux exposes it, in
+/// `/proc/self/status`, so the task ends on other systems.
+async fn sample_resident_memory() {
+    let mut interval = tokio::time::interval(RESIDENT_MEMORY_SAMPLE_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let Some(bytes) = tokio::task::spawn_blocking(resident_memory)
+            .await
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+        metrics::gauge!(RESIDENT_MEMORY_METRIC).set(bytes as f64);
+    }
+}
+
+fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kb = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_responses_are_rejected_or_chunked() {
+        let mut config = ResponseBudgetConfig {
+            max_bytes: 10_000,
+            avg_value_bytes: 10,
+            on_exceed: BudgetExceeded::Reject,
+        };
+        let budget = ResponseBudget::new(&config);
+        assert_eq!(budget.estimate(100, 10), 10_000);
+        assert_eq!(budget.chunk_rows(100, 10).ok(), Some(None));
+        let err = budget.chunk_rows(101, 10).unwrap_err();
+        assert!(err.is_invalid_request());
+
+        config.on_exceed = BudgetExceeded::Chunk;
+        let budget = ResponseBudget::new(&config);
+        assert_eq!(budget.chunk_rows(101, 10).ok(), Some(Some(100)));
+        // A row above the budget on its own is still read, one at a time
+        assert_eq!(budget.chunk_rows(2, 2_000).ok(), Some(Some(1)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn resident_memory_is_read() {
+        assert!(resident_memory().is_some_and(|bytes| bytes > 0));
+    }
+}
@@ -1,22 +1,22 @@
+use crate::error::FeastCoreError;
 use crate::feast::types::value::Val;
 use crate::feast::types::{EntityKey, Value};
+use crate::feature_store::feature_names::FeatureNaming;
 use crate::feature_store::feature_store_impl::{EntityColumnRef, FeatureWithKeys};
 use crate::intern;
 use crate::model::FeatureStatus::Present;
 use crate::model::{
     DUMMY_ENTITY_ID, EntityIdValue, Feature, FeatureResults, FeatureStatus, FeatureType,
-    FeatureView, GetOnlineFeatureResponse, ValueWrapper,
+    FeatureView, GetOnlineFeatureResponse, OrderedMap, ResponseFields, ValueWrapper,
 };
 use crate::onlinestore::OnlineStoreRow;
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Duration, SubsecRound, Utc};
 use lasso::Spur;
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use smallvec::{SmallVec, smallvec};
 use std::sync::Arc;
 
-static DUMMY_ENTITY_ID_SPUR: std::sync::LazyLock<Spur> =
-    std::sync::LazyLock::new(|| intern::rodeo_ref().get_or_intern(DUMMY_ENTITY_ID));
-
 #[derive(Debug, Clone)]
 struct ResponseFeatureRow(Feature, Value, FeatureStatus, DateTime<Utc>);
 
@@ -46,20 +46,17 @@ impl<'a> From<&'a Feature> for FeatureRef<'a> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct RequestEntityIdKey {
-    pub name: Spur,
-    pub value: EntityIdValue,
-}
-
 fn get_feature_status(
     value: &Value,
     feature_view: Option<Arc<FeatureView>>,
     event_ts: &DateTime<Utc>,
 ) -> FeatureStatus {
-    if value.val.is_none() {
+    if matches!(value.val, None | Some(Val::NullVal(_))) {
         FeatureStatus::NullValue
-    } else if let Some(feature_view) = feature_view {
+    } else if let Some(feature_view) = feature_view
+        && feature_view.ttl > Duration::zero()
+    {
+        // A zero TTL means values never expire
         if let Some(expiration_time) = event_ts.checked_add_signed(feature_view.ttl) {
             if Utc::now() > expiration_time {
                 FeatureStatus::OutsideMaxAge
@@ -83,14 +80,71 @@ fn val_to_entity_id_value(value: &Val) -> Result<EntityIdValue> {
     }
 }
 
-#[derive(Clone, Copy)]
-struct EntityPosition {
-    entity_idx: usize,
-    value_idx: usize,
+type RequestColumns = SmallVec<[Spur; 2]>;
+type RequestValues = SmallVec<[EntityIdValue; 2]>;
+
+/// Positions of request rows by the values of the request entity columns looked up for a
+/// feature view, so that stored rows are matched by their single or composite entity key.
+/// Every position of a key is kept, as a request may ask for the same entity several times.
+#[derive(Default)]
+struct RequestRowIndex {
+    by_columns: HashMap<RequestColumns, HashMap<RequestValues, SmallVec<[usize; 1]>>>,
+}
+
+impl RequestRowIndex {
+    fn add_columns(
+        &mut self,
+        mut columns: RequestColumns,
+        entities: &[(Spur, Vec<EntityIdValue>)],
+        entity_name_to_index: &HashMap<Spur, usize>,
+    ) {
+        columns.sort_unstable();
+        columns.dedup();
+        if columns.is_empty() || self.by_columns.contains_key(&columns) {
+            return;
+        }
+        let Some(column_values) = columns
+            .iter()
+            .map(|name| {
+                entity_name_to_index
+                    .get(name)
+                    .map(|&idx| entities[idx].1.as_slice())
+            })
+            .collect::<Option<SmallVec<[&[EntityIdValue]; 2]>>>()
+        else {
+            return;
+        };
+        let row_count = column_values
+            .iter()
+            .map(|values| values.len())
+            .min()
+            .unwrap_or(0);
+        let mut positions: HashMap<RequestValues, SmallVec<[usize; 1]>> =
+            HashMap::with_capacity_and_hasher(row_count, Default::default());
+        for value_idx in 0..row_count {
+            let key = column_values
+                .iter()
+                .map(|values| values[value_idx].clone())
+                .collect();
+            positions.entry(key).or_default().push(value_idx);
+        }
+        self.by_columns.insert(columns, positions);
+    }
+
+    /// Request rows holding `key`, a list of request column names and values sorted by name.
+    fn value_indices(&self, key: &[(Spur, EntityIdValue)]) -> &[usize] {
+        let columns: RequestColumns = key.iter().map(|(name, _)| *name).collect();
+        let values: RequestValues = key.iter().map(|(_, value)| value.clone()).collect();
+        self.by_columns
+            .get(&columns)
+            .and_then(|positions| positions.get(&values))
+            .map(SmallVec::as_slice)
+            .unwrap_or_default()
+    }
 }
 
 struct GetOnlineFeatureResponseBuilder {
-    full_feature_names: bool,
+    naming: FeatureNaming,
     num_values: usize,
     features: Vec<Spur>,
     results: Vec<FeatureResults>,
@@ -100,7 +154,7 @@ struct GetOnlineFeatureResponseBuilder {
 impl GetOnlineFeatureResponseBuilder {
     fn new(full_feature_names: bool, num_values: usize, capacity: usize) -> Self {
         Self {
-            full_feature_names,
+            naming: FeatureNaming::new(full_feature_names),
             num_values,
             features: Vec::with_capacity(capacity),
             results: Vec::with_capacity(capacity),
@@ -115,6 +169,7 @@ impl GetOnlineFeatureResponseBuilder {
             values: Vec::with_capacity(capacity),
             statuses: Vec::with_capacity(capacity),
             event_timestamps: Vec::with_capacity(capacity),
+            created_timestamps: Vec::with_capacity(capacity),
         });
         idx
     }
@@ -133,6 +188,7 @@ impl GetOnlineFeatureResponseBuilder {
         self.results[entity_idx]
             .event_timestamps
             .push(DateTime::<Utc>::UNIX_EPOCH.round_subsecs(0));
+        self.results[entity_idx].created_timestamps.push(None);
     }
 
     fn push_empty_values(&mut self, value_count: usize) {
@@ -140,19 +196,20 @@ impl GetOnlineFeatureResponseBuilder {
             values: vec![ValueWrapper(Value { val: None }); value_count],
             statuses: vec![FeatureStatus::NotFound; value_count],
             event_timestamps: vec![DateTime::<Utc>::UNIX_EPOCH; value_count],
+            created_timestamps: vec![None; value_count],
         });
     }
 
     fn ensure_feature_slot(
         &mut self,
         feature: &Feature,
+        view: Option<&FeatureView>,
         value_count: usize,
-        is_entity_less: bool,
     ) -> usize {
         if let Some(&idx) = self.feature_to_idx.get(feature) {
             return idx;
         }
-        let feature_name = self.format_feature_name(feature, is_entity_less);
+        let feature_name = self.naming.column_name(feature, view);
         let idx = self.features.len();
         self.features.push(feature_name);
         self.push_empty_values(value_count);
@@ -167,6 +224,7 @@ impl GetOnlineFeatureResponseBuilder {
         value: Value,
         status: FeatureStatus,
         event_ts: DateTime<Utc>,
+        created_ts: Option<DateTime<Utc>>,
     ) {
         if let Some(slot) = self.results.get_mut(feature_idx)
             && value_idx < slot.values.len()
@@ -174,40 +232,25 @@ impl GetOnlineFeatureResponseBuilder {
             slot.values[value_idx] = ValueWrapper(value);
             slot.statuses[value_idx] = status;
             slot.event_timestamps[value_idx] = event_ts;
+            slot.created_timestamps[value_idx] = created_ts;
         }
     }
 
-    fn add_entity_less_feature(
+    /// Set every value of an entity-less feature column to the single stored value.
+    fn set_entity_less_value(
         &mut self,
-        feature: Feature,
+        feature_idx: usize,
         value: Value,
         status: FeatureStatus,
         event_ts: DateTime<Utc>,
+        created_ts: Option<DateTime<Utc>>,
     ) {
-        self.features.push(feature.feature_name);
-        self.results.push(FeatureResults {
-            values: vec![ValueWrapper(value); self.num_values],
-            statuses: vec![status; self.num_values],
-            event_timestamps: vec![event_ts; self.num_values],
-        });
-    }
-
-    fn add_missing_feature(&mut self, feature: Feature, value_count: usize, is_entity_less: bool) {
-        let feature_name = self.format_feature_name(&feature, is_entity_less);
-        self.features.push(feature_name);
-        self.push_empty_values(value_count);
-    }
-
-    fn format_feature_name(&self, feature: &Feature, is_entity_less: bool) -> Spur {
-        let rodeo = intern::rodeo_ref();
-        if self.full_feature_names && !is_entity_less {
-            rodeo.get_or_intern(format!(
-                "{}__{}",
-                rodeo.resolve(&feature.feature_view_name),
-                rodeo.resolve(&feature.feature_name)
-            ))
-        } else {
-            feature.feature_name
+        if let Some(slot) = self.results.get_mut(feature_idx) {
+            let value_count = slot.values.len();
+            slot.values = vec![ValueWrapper(value); value_count];
+            slot.statuses = vec![status; value_count];
+            slot.event_timestamps = vec![event_ts; value_count];
+            slot.created_timestamps = vec![created_ts; value_count];
         }
     }
 
@@ -222,26 +265,31 @@ impl GetOnlineFeatureResponseBuilder {
                     .collect(),
             },
             results: self.results,
+            warnings: Vec::new(),
+            feature_service_version: None,
+            registry_version: None,
+            debug: None,
         }
     }
 }
 
 impl GetOnlineFeatureResponse {
     /// Build GetOnlineFeatureResponse from entity keys of request data,
-    /// online store rows and feature view to ttl mapping.
+    /// online store rows and feature view to ttl mapping. Entity columns come first in
+    /// request order, followed by feature columns in the order of `features`.
     ///
     /// Parameters:
     /// `entity_keys` - passed by user entity key for requested features
     /// `rows` - data return by onlinestore
     /// `feature_views` - mapping feature_view name to its declaration
-    /// `typed_features` - list of requested features with types
+    /// `features` - requested features, in response column order
     /// `full_feature_names` - use full feature names in result object
     pub(crate) fn try_from(
-        entity_keys: HashMap<Spur, Vec<EntityIdValue>>,
+        entity_keys: OrderedMap<Spur, Vec<EntityIdValue>>,
         rows: Vec<OnlineStoreRow>,
         feature_views: HashMap<Spur, Arc<FeatureView>>,
         lookup_mapping: HashMap<EntityColumnRef, Spur>,
-        mut feature_set: HashSet<Feature>,
+        features: Vec<Feature>,
         full_feature_names: bool,
     ) -> Result<Self> {
         let rodeo = intern::rodeo_ref();
@@ -260,28 +308,30 @@ impl GetOnlineFeatureResponse {
             entity_name_to_index.insert(*name, idx);
         }
 
-        let mut positions: Vec<EntityPosition> = Vec::new();
-        let mut key_index: HashMap<RequestEntityIdKey, usize> = HashMap::default();
-        for (entity_idx, (entity_name, values)) in ordered_entities.iter().enumerate() {
-            for (value_idx, key) in values.iter().enumerate() {
-                let request_key = RequestEntityIdKey {
-                    name: *entity_name,
-                    value: key.clone(),
-                };
-                let slot = positions.len();
-                positions.push(EntityPosition {
-                    entity_idx,
-                    value_idx,
-                });
-                key_index.insert(request_key, slot);
+        let mut row_index = RequestRowIndex::default();
+        for (name, _) in &ordered_entities {
+            row_index.add_columns(smallvec![*name], &ordered_entities, &entity_name_to_index);
+        }
+        for view in feature_views.values() {
+            if view.is_entity_less() {
+                continue;
             }
+            let columns = view
+                .entity_columns
+                .iter()
+                .filter_map(|col| {
+                    lookup_mapping.get(&EntityColumnRef::new(view.name_to_use(), col.name))
+                })
+                .copied()
+                .collect();
+            row_index.add_columns(columns, &ordered_entities, &entity_name_to_index);
         }
 
         let mut entity_lengths: Vec<usize> = Vec::with_capacity(entity_count);
         let mut response_builder = GetOnlineFeatureResponseBuilder::new(
             full_feature_names,
             max_value_count,
-            entity_count + feature_set.len(),
+            entity_count + features.len(),
         );
         for (entity_name, values) in ordered_entities.into_iter() {
             let expected_len = values.len();
@@ -292,6 +342,22 @@ impl GetOnlineFeatureResponse {
             entity_lengths.push(expected_len);
         }
 
+        for feature in &features {
+            let view = feature_views
+                .get(&feature.feature_view_name)
+                .map(Arc::as_ref);
+            let value_count = view
+                .filter(|view| !view.is_entity_less())
+                .and_then(|view| view.entity_columns.first().map(|col| (view, col)))
+                .and_then(|(view, column)| {
+                    lookup_mapping.get(&EntityColumnRef::new(view.name_to_use(), column.name))
+                })
+                .and_then(|request_key| entity_name_to_index.get(request_key))
+                .and_then(|&entity_idx| entity_lengths.get(entity_idx).copied())
+                .unwrap_or(max_value_count);
+            response_builder.ensure_feature_slot(feature, view, value_count);
+        }
+
         for row in rows {
             let OnlineStoreRow {
                 feature_view_name,
@@ -299,86 +365,187 @@ impl GetOnlineFeatureResponse {
                 feature_name,
                 value,
                 event_ts,
-                created_ts: _,
+                created_ts,
+                invalid,
+                undecoded: _,
             } = row;
 
-            if entity_key.0.join_keys.len() != 1 || entity_key.0.entity_values.len() != 1 {
+            let join_keys = &entity_key.0.join_keys;
+            let entity_values = &entity_key.0.entity_values;
+            if join_keys.is_empty() || join_keys.len() != entity_values.len() {
                 return Err(anyhow!(
-                    "Invalid entity key with multiple join keys or entity values"
+                    "Invalid entity key with {} join keys and {} entity values",
+                    join_keys.len(),
+                    entity_values.len()
                 ));
             }
 
-            let entity_key_name = &entity_key.0.join_keys[0];
-            let entity_col_ref =
-                EntityColumnRef::new(feature_view_name, rodeo.get_or_intern(entity_key_name));
-            let lookup_key = lookup_mapping
-                .get(&entity_col_ref)
-                .expect("programming error: lookup_mapping should contain all entity columns");
-            let entity_id_value = entity_key.0.entity_values[0]
-                .val
-                .as_ref()
-                .map(val_to_entity_id_value)
-                .transpose()?
-                .ok_or(anyhow!("Empty entity id value"))?;
-            let request_key = RequestEntityIdKey {
-                name: *lookup_key,
-                value: entity_id_value.clone(),
+            // Rows are only expected for the views the request was resolved to. Others belong
+            // to views the registry no longer has, e.g. after a refresh during the request.
+            let Some(view) = feature_views.get(&feature_view_name) else {
+                return Err(FeastCoreError::feature_view_not_found(
+                    rodeo.resolve(&feature_view_name),
+                )
+                .into());
             };
 
-            let feature = Feature::new(entity_col_ref.view_name, feature_name);
-            let status = get_feature_status(
-                &value,
-                feature_views.get(&entity_col_ref.view_name).cloned(),
-                &event_ts,
-            );
-
-            if let Some(&slot) = key_index.get(&request_key) {
-                let position = positions[slot];
-                let value_count = entity_lengths
-                    .get(position.entity_idx)
-                    .copied()
-                    .unwrap_or(0);
-                let feature_idx =
-                    response_builder.ensure_feature_slot(&feature, value_count, false);
-                response_builder.set_feature_value(
+            let status = if invalid {
+                FeatureStatus::Invalid
+            } else {
+                get_feature_status(&value, Some(view.clone()), &event_ts)
+            };
+            if join_keys.len() == 1 && join_keys[0] == DUMMY_ENTITY_ID {
+                let feature = Feature::new(feature_view_name, feature_name);
+                let feature_idx = response_builder.ensure_feature_slot(
+                    &feature,
+                    Some(view.as_ref()),
+                    max_value_count,
+                );
+                response_builder.set_entity_less_value(
                     feature_idx,
-                    position.value_idx,
                     value,
                     status,
                     event_ts,
+                    created_ts,
                 );
-                feature_set.remove(&feature);
-            } else if *lookup_key == *DUMMY_ENTITY_ID_SPUR {
-                feature_set.remove(&feature);
-                response_builder.add_entity_less_feature(feature, value, status, event_ts);
-            } else {
+                continue;
+            }
+
+            let mut request_key: SmallVec<[(Spur, EntityIdValue); 2]> =
+                SmallVec::with_capacity(join_keys.len());
+            for (join_key, entity_value) in join_keys.iter().zip(entity_values) {
+                let entity_col_ref =
+                    EntityColumnRef::new(feature_view_name, rodeo.get_or_intern(join_key));
+                let lookup_key = lookup_mapping.get(&entity_col_ref).ok_or_else(|| {
+                    anyhow!(
+                        "Online store returned join key {} which is not an entity column of feature view {}",
+                        join_key,
+                        rodeo.resolve(&feature_view_name)
+                    )
+                })?;
+                let entity_id_value = entity_value
+                    .val
+                    .as_ref()
+                    .map(val_to_entity_id_value)
+                    .transpose()?
+                    .ok_or(anyhow!("Empty entity id value"))?;
+                request_key.push((*lookup_key, entity_id_value));
+            }
+            // Stores may return the join keys of a composite key in any order
+            request_key.sort_unstable_by_key(|(name, _)| *name);
+
+            let value_indices = row_index.value_indices(&request_key);
+            if value_indices.is_empty() {
                 // Row does not correspond to requested entity keys; ignore it.
+                continue;
+            }
+            let feature = Feature::new(feature_view_name, feature_name);
+            let value_count = entity_name_to_index
+                .get(&request_key[0].0)
+                .and_then(|&entity_idx| entity_lengths.get(entity_idx).copied())
+                .unwrap_or(0);
+            let feature_idx =
+                response_builder.ensure_feature_slot(&feature, Some(view.as_ref()), value_count);
+            for &value_idx in value_indices {
+                response_builder.set_feature_value(
+                    feature_idx,
+                    value_idx,
+                    value.clone(),
+                    status.clone(),
+                    event_ts,
+                    created_ts,
+                );
             }
         }
 
-        for feature in feature_set.into_iter() {
-            if let Some(view_arc) = feature_views.get(&feature.feature_view_name) {
-                let view = view_arc.as_ref();
-                if view.is_entity_less() {
-                    response_builder.add_missing_feature(feature, max_value_count, true);
-                    continue;
-                }
+        Ok(response_builder.build())
+    }
+
+    /// Drop the parts of the response a client left out: the `entity_count` leading entity
+    /// columns, statuses, event timestamps or created timestamps.
+    pub(crate) fn retain_fields(&mut self, entity_count: usize, fields: ResponseFields) {
+        if !fields.entity_values {
+            let entity_count = entity_count.min(self.results.len());
+            self.results.drain(..entity_count);
+            self.metadata.feature_names.drain(..entity_count);
+        }
+        for column in &mut self.results {
+            if !fields.statuses {
+                column.statuses = Vec::new();
+            }
+            if !fields.timestamps {
+                column.event_timestamps = Vec::new();
+            }
+            if !fields.created_timestamps {
+                column.created_timestamps = Vec::new();
+            }
+        }
+    }
 
-                if let Some(column) = view.entity_columns.first() {
-                    let entity_col_ref = EntityColumnRef::new(view.name, column.name);
-                    if let Some(request_key) = lookup_mapping.get(&entity_col_ref)
-                        && let Some(&entity_idx) = entity_name_to_index.get(request_key)
-                    {
-                        let len = entity_lengths.get(entity_idx).copied().unwrap_or(0);
-                        response_builder.add_missing_feature(feature, len, false);
-                        continue;
-                    }
+    /// Mark the columns of features from views which could not be read as invalid and list
+    /// the failures in the warnings. Feature columns follow the `entity_count` entity columns
+    /// in the order of `features`, as laid out by [`Self::try_from`].
+    pub(crate) fn mark_failed_views<'a>(
+        &mut self,
+        entity_count: usize,
+        features: impl Iterator<Item = &'a Feature>,
+        failed_views: Vec<(Spur, anyhow::Error)>,
+    ) {
+        let rodeo = intern::rodeo_ref();
+        let failed_names: HashSet<Spur> = failed_views.iter().map(|(name, _)| *name).collect();
+        for (idx, feature) in features.enumerate() {
+            if !failed_names.contains(&feature.feature_view_name) {
+                continue;
+            }
+            if let Some(column) = self.results.get_mut(entity_count + idx) {
+                column.statuses.fill(FeatureStatus::Invalid);
+            }
+        }
+        for (view_name, err) in failed_views {
+            self.warnings.push(format!(
+                "Failed to read feature view {}: {:#}",
+                rodeo.resolve(&view_name),
+                err
+            ));
+        }
+    }
+
+    /// Answer the values not found of feature columns having a default with the default,
+    /// with status `DEFAULT_VALUE`. Feature columns follow the `entity_count` entity columns
+    /// in the order of `defaults`.
+    pub(crate) fn fill_defaults(&mut self, entity_count: usize, defaults: &[Option<Value>]) {
+        for (column, default) in self.results.iter_mut().skip(entity_count).zip(defaults) {
+            let Some(default) = default else {
+                continue;
+            };
+            for (value, status) in column.values.iter_mut().zip(column.statuses.iter_mut()) {
+                if *status == FeatureStatus::NotFound {
+                    *value = ValueWrapper(default.clone());
+                    *status = FeatureStatus::DefaultValue;
                 }
             }
-            response_builder.add_missing_feature(feature, max_value_count, false);
         }
+    }
 
-        Ok(response_builder.build())
+    /// Add the entity rows of `other`, a response to more entity rows of the same request,
+    /// after the rows of this one.
+    pub(crate) fn append(&mut self, other: Self) {
+        if self.results.is_empty() {
+            self.metadata = other.metadata;
+            self.results = other.results;
+        } else {
+            for (column, other) in self.results.iter_mut().zip(other.results) {
+                column.values.extend(other.values);
+                column.statuses.extend(other.statuses);
+                column.event_timestamps.extend(other.event_timestamps);
+                column.created_timestamps.extend(other.created_timestamps);
+            }
+        }
+        for warning in other.warnings {
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
     }
 }
 
@@ -397,7 +564,7 @@ mod tests {
 
     #[test]
     fn try_from_builds_response_with_missing_values() -> Result<()> {
-        let mut entity_keys = HashMap::default();
+        let mut entity_keys = OrderedMap::default();
         entity_keys.insert(
             rodeo().get_or_intern("driver_id"),
             vec![EntityIdValue::Int(1001), EntityIdValue::Int(1002)],
@@ -420,22 +587,23 @@ mod tests {
             value: feature_value.clone(),
             event_ts,
             created_ts: None,
+            invalid: false,
+            undecoded: None,
         };
 
-        let mut feature_view = FeatureView::default();
-        feature_view.name = rodeo().get_or_intern("driver_hourly_stats");
-        feature_view.ttl = Duration::seconds(3600);
-        feature_view.entity_names = vec![rodeo().get_or_intern("driver_id")];
+        let feature_view = FeatureView {
+            name: rodeo().get_or_intern("driver_hourly_stats"),
+            ttl: Duration::seconds(3600),
+            entity_names: vec![rodeo().get_or_intern("driver_id")],
+            ..Default::default()
+        };
 
         let mut feature_views: HashMap<Spur, Arc<FeatureView>> = HashMap::default();
         let feature = Arc::from(feature_view);
 
         feature_views.insert(feature.name, feature);
 
-        let features: HashSet<Feature> =
-            vec![Feature::from_names("driver_hourly_stats", "acc_rate")]
-                .into_iter()
-                .collect();
+        let features = vec![Feature::from_names("driver_hourly_stats", "acc_rate")];
 
         let lookup_mapping: HashMap<EntityColumnRef, Spur> = vec![(
             EntityColumnRef::new(
@@ -465,6 +633,7 @@ mod tests {
             ],
             statuses: vec![Present, Present],
             event_timestamps: vec![DateTime::<Utc>::UNIX_EPOCH, DateTime::<Utc>::UNIX_EPOCH],
+            created_timestamps: vec![None, None],
         });
 
         expected.results.push(FeatureResults {
@@ -474,9 +643,152 @@ mod tests {
             ],
             statuses: vec![Present, FeatureStatus::NotFound],
             event_timestamps: vec![event_ts, DateTime::<Utc>::UNIX_EPOCH.round_subsecs(0)],
+            created_timestamps: vec![None, None],
         });
 
         assert_eq!(response, expected);
         Ok(())
     }
+
+    #[test]
+    fn try_from_matches_composite_entity_keys() -> Result<()> {
+        let driver_id = rodeo().get_or_intern("driver_id");
+        let customer_id = rodeo().get_or_intern("customer_id");
+        let view_name = rodeo().get_or_intern("driver_customer_stats");
+        let mut entity_keys = OrderedMap::default();
+        entity_keys.insert(
+            driver_id,
+            vec![
+                EntityIdValue::Int(1001),
+                EntityIdValue::Int(1001),
+                EntityIdValue::Int(1002),
+                EntityIdValue::Int(1001),
+            ],
+        );
+        entity_keys.insert(
+            customer_id,
+            vec![
+                EntityIdValue::String("a".to_string()),
+                EntityIdValue::String("b".to_string()),
+                EntityIdValue::String("a".to_string()),
+                EntityIdValue::String("a".to_string()),
+            ],
+        );
+
+        // Join keys come back sorted by name, not in the order of the view's entity columns
+        let event_ts = Utc::now().round_subsecs(0);
+        let feature_value = Value {
+            val: Some(Val::DoubleVal(0.5)),
+        };
+        let row = OnlineStoreRow {
+            feature_view_name: view_name,
+            entity_key: HashEntityKey(Arc::new(EntityKey {
+                join_keys: vec!["customer_id".to_string(), "driver_id".to_string()],
+                entity_values: vec![
+                    Value {
+                        val: Some(Val::StringVal("a".to_string())),
+                    },
+                    Value {
+                        val: Some(Val::Int64Val(1001)),
+                    },
+                ],
+            })),
+            feature_name: rodeo().get_or_intern("trips"),
+            value: feature_value.clone(),
+            event_ts,
+            created_ts: None,
+            invalid: false,
+            undecoded: None,
+        };
+
+        let feature_view = Arc::new(FeatureView {
+            name: view_name,
+            entity_names: vec![driver_id, customer_id],
+            entity_columns: vec![
+                crate::model::Field::new("driver_id", crate::feast::types::value_type::Enum::Int64),
+                crate::model::Field::new(
+                    "customer_id",
+                    crate::feast::types::value_type::Enum::String,
+                ),
+            ],
+            ..Default::default()
+        });
+        let feature_views: HashMap<Spur, Arc<FeatureView>> =
+            [(view_name, feature_view)].into_iter().collect();
+        let lookup_mapping: HashMap<EntityColumnRef, Spur> = [
+            (EntityColumnRef::new(view_name, driver_id), driver_id),
+            (EntityColumnRef::new(view_name, customer_id), customer_id),
+        ]
+        .into_iter()
+        .collect();
+
+        let response = GetOnlineFeatureResponse::try_from(
+            entity_keys,
+            vec![row],
+            feature_views,
+            lookup_mapping,
+            vec![Feature::from_names("driver_customer_stats", "trips")],
+            false,
+        )?;
+
+        assert_eq!(
+            response.metadata.feature_names,
+            ["driver_id", "customer_id", "trips"]
+        );
+        let trips = &response.results[2];
+        assert_eq!(
+            trips.statuses,
+            [
+                Present,
+                FeatureStatus::NotFound,
+                FeatureStatus::NotFound,
+                Present
+            ]
+        );
+        assert_eq!(trips.values[0], ValueWrapper(feature_value.clone()));
+        assert_eq!(trips.values[3], ValueWrapper(feature_value));
+        assert_eq!(trips.event_timestamps[3], event_ts);
+        Ok(())
+    }
+
+    #[test]
+    fn null_values_have_null_value_status() {
+        let now = Utc::now();
+        let status = |val| get_feature_status(&Value { val }, None, &now);
+        assert_eq!(status(None), FeatureStatus::NullValue);
+        assert_eq!(status(Some(Val::NullVal(0))), FeatureStatus::NullValue);
+        assert_eq!(status(Some(Val::DoubleVal(f64::NAN))), Present);
+    }
+
+    #[test]
+    fn try_from_rejects_malformed_entity_keys() {
+        let row = OnlineStoreRow {
+            feature_view_name: rodeo().get_or_intern("driver_hourly_stats"),
+            entity_key: HashEntityKey(Arc::new(EntityKey {
+                join_keys: vec!["driver_id".to_string(), "customer_id".to_string()],
+                entity_values: vec![Value {
+                    val: Some(Val::Int64Val(1001)),
+                }],
+            })),
+            feature_name: rodeo().get_or_intern("acc_rate"),
+            value: Value::default(),
+            event_ts: Utc::now(),
+            created_ts: None,
+            invalid: false,
+            undecoded: None,
+        };
+        let err = GetOnlineFeatureResponse::try_from(
+            OrderedMap::default(),
+            vec![row],
+            HashMap::default(),
+            HashMap::default(),
+            vec![],
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid entity key with 2 join keys and 1 entity values"
+        );
+    }
 }
@@ -0,0 +1,216 @@
+use crate::config::ResponseCacheConfig;
+use crate::model::{EntityIdValue, GetOnlineFeatureResponse, GetOnlineFeaturesRequest};
+use rustc_hash::FxHashMap as HashMap;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Request identity used as the cache key. Entities keep the order of the request, which
+/// is the order of the entity columns of the cached response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    feature_service: String,
+    project: Option<String>,
+    full_feature_names: bool,
+    entities: Vec<(String, Vec<EntityIdValue>)>,
+}
+
+struct CachedResponse {
+    expires_at: Instant,
+    response: GetOnlineFeatureResponse,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct CacheCounters {
+    hits: u64,
+    misses: u64,
+}
+
+/// Response cache hit counters of a single feature service.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_ratio: f64,
+}
+
+struct CacheState {
+    /// Registry generation the cached responses were computed with.
+    generation: u64,
+    /// Number of times the cached responses were dropped as stored values changed.
+    clears: u64,
+    entries: HashMap<CacheKey, CachedResponse>,
+    counters: HashMap<String, CacheCounters>,
+}
+
+/// Per feature service cache of complete online feature responses.
+pub(crate) struct ResponseCache {
+    ttls: HashMap<String, Duration>,
+    max_entries: usize,
+    state: Mutex<CacheState>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(config: &ResponseCacheConfig) -> Self {
+        Self {
+            ttls: config
+                .feature_services
+                .iter()
+                .map(|(name, ttl_ms)| (name.clone(), Duration::from_millis(*ttl_ms)))
+                .collect(),
+            max_entries: config.max_entries,
+            state: Mutex::new(CacheState {
+                generation: 0,
+                clears: 0,
+                entries: HashMap::default(),
+                counters: HashMap::default(),
+            }),
+        }
+    }
+
+    /// Cache key of `request`, or `None` when responses to it are not cached.
+    pub(crate) fn key(&self, request: &GetOnlineFeaturesRequest) -> Option<CacheKey> {
+        let feature_service = request.feature_service.as_ref()?;
+        if !self.ttls.contains_key(feature_service) {
+            return None;
+        }
+        let entities = request
+            .entities
+            .iter()
+            .map(|(name, values)| (name.clone(), values.clone()))
+            .collect();
+        Some(CacheKey {
+            feature_service: feature_service.clone(),
+            project: request.project.clone(),
+            full_feature_names: request.full_feature_names.unwrap_or(false),
+            entities,
+        })
+    }
+
+    /// Cached response for `key`. Every cached response is dropped when the registry
+    /// `generation` differs from the one the responses were computed with.
+    pub(crate) fn get(&self, key: &CacheKey, generation: u64) -> Option<GetOnlineFeatureResponse> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.generation != generation {
+            state.entries.clear();
+            state.generation = generation;
+        }
+        let now = Instant::now();
+        let response = match state.entries.get(key) {
+            Some(cached) if cached.expires_at > now => Some(cached.response.clone()),
+            Some(_) => {
+                state.entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counters = state
+            .counters
+            .entry(key.feature_service.clone())
+            .or_default();
+        match response {
+            Some(_) => counters.hits += 1,
+            None => counters.misses += 1,
+        }
+        response
+    }
+
+    /// Store `response` unless the cache is full of unexpired entries, or the registry was
+    /// reloaded or the cache cleared while the response was computed. `clears` is the value
+    /// of [`Self::clears`] before the response was computed.
+    pub(crate) fn insert(
+        &self,
+        key: CacheKey,
+        generation: u64,
+        clears: u64,
+        response: &GetOnlineFeatureResponse,
+    ) {
+        let Some(ttl) = self.ttls.get(&key.feature_service) else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.generation != generation || state.clears != clears {
+            return;
+        }
+        let now = Instant::now();
+        if state.entries.len() >= self.max_entries {
+            state.entries.retain(|_, cached| cached.expires_at > now);
+            if state.entries.len() >= self.max_entries {
+                return;
+            }
+        }
+        state.entries.insert(
+            key,
+            CachedResponse {
+                expires_at: now + *ttl,
+                response: response.clone(),
+            },
+        );
+    }
+
+    /// Drop every cached response, e.g. after values of the online store were written or
+    /// deleted.
+    pub(crate) fn clear(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.entries.clear();
+        state.clears += 1;
+    }
+
+    /// Number of times the cache was cleared, to tell responses computed before a clear.
+    pub(crate) fn clears(&self) -> u64 {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).clears
+    }
+
+    pub(crate) fn stats(&self) -> BTreeMap<String, ResponseCacheStats> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .counters
+            .iter()
+            .map(|(name, c)| {
+                let lookups = c.hits + c.misses;
+                let hit_ratio = if lookups == 0 {
+                    0.0
+                } else {
+                    c.hits as f64 / lookups as f64
+                };
+                let stats = ResponseCacheStats {
+                    hits: c.hits,
+                    misses: c.misses,
+                    hit_ratio,
+                };
+                (name.clone(), stats)
+            })
+            .collect()
+    }
+
+    pub(crate) fn reset_stats(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.counters.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::OrderedMap;
+
+    #[test]
+    fn keys_keep_the_entity_order_of_requests() {
+        let cache = ResponseCache::new(&ResponseCacheConfig {
+            max_entries: 10,
+            feature_services: [("driver_activity".to_string(), 60_000)].into(),
+        });
+        let request = |names: [&str; 2]| GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter(
+                names.map(|name| (name.to_string(), vec![EntityIdValue::Int(1)])),
+            ),
+            feature_service: Some("driver_activity".to_string()),
+            ..Default::default()
+        };
+        let key = cache.key(&request(["driver_id", "customer_id"]));
+        assert!(key.is_some());
+        assert_eq!(key, cache.key(&request(["driver_id", "customer_id"])));
+        assert_ne!(key, cache.key(&request(["customer_id", "driver_id"])));
+    }
+}
@@ -0,0 +1,258 @@
+use crate::error::FeastCoreError;
+use crate::feast::types::Value;
+use crate::model::{
+    EntityIdValue, FeatureResults, FeatureStatus, GetOnlineFeatureResponse,
+    GetOnlineFeatureResponseMetadata, GetOnlineFeaturesRequest, ValueWrapper,
+};
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Recording of complete online feature responses to files of a directory, one per request,
+/// or serving of the responses recorded there instead of reading the online store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResponseRecording {
+    /// Write every response to the directory, replacing the one of an identical request.
+    Record(PathBuf),
+    /// Answer requests with the responses recorded in the directory.
+    Replay(PathBuf),
+}
+
+/// Parts of a request which select the response. The `include_*` flags are left out, as
+/// complete responses are recorded and trimmed like any other.
+#[derive(Serialize)]
+struct RecordingKey<'a> {
+    entities: Vec<(&'a String, &'a Vec<EntityIdValue>)>,
+    feature_service: &'a Option<String>,
+    features: &'a Option<Vec<String>>,
+    full_feature_names: bool,
+    project: &'a Option<String>,
+}
+
+/// Contents of a recording file. The request is kept for readers of the file only.
+#[derive(Serialize, Deserialize)]
+struct RecordedResponse<Q> {
+    request: Q,
+    feature_names: Vec<String>,
+    results: Vec<RecordedResults>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+    feature_service_version: Option<String>,
+    registry_version: Option<u64>,
+}
+
+/// Column of a recorded response. Values are base64 encoded `Value` protos, which keep the
+/// value types JSON numbers and arrays lose.
+#[derive(Serialize, Deserialize)]
+struct RecordedResults {
+    values: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    statuses: Vec<FeatureStatus>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    event_timestamps: Vec<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    created_timestamps: Vec<Option<DateTime<Utc>>>,
+}
+
+impl<Q> RecordedResponse<Q> {
+    fn new(request: Q, response: &GetOnlineFeatureResponse) -> Self {
+        Self {
+            request,
+            feature_names: response.metadata.feature_names.clone(),
+            results: response
+                .results
+                .iter()
+                .map(|results| RecordedResults {
+                    values: results
+                        .values
+                        .iter()
+                        .map(|value| BASE64.encode(value.0.encode_to_vec()))
+                        .collect(),
+                    statuses: results.statuses.clone(),
+                    event_timestamps: results.event_timestamps.clone(),
+                    created_timestamps: results.created_timestamps.clone(),
+                })
+                .collect(),
+            warnings: response.warnings.clone(),
+            feature_service_version: response.feature_service_version.clone(),
+            registry_version: response.registry_version,
+        }
+    }
+
+    fn into_response(self) -> Result<GetOnlineFeatureResponse> {
+        let results = self
+            .results
+            .into_iter()
+            .map(|results| {
+                let values = results
+                    .values
+                    .iter()
+                    .map(|value| {
+                        Ok(ValueWrapper(Value::decode(
+                            BASE64.decode(value)?.as_slice(),
+                        )?))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(FeatureResults {
+                    values,
+                    statuses: results.statuses,
+                    event_timestamps: results.event_timestamps,
+                    created_timestamps: results.created_timestamps,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(GetOnlineFeatureResponse {
+            metadata: GetOnlineFeatureResponseMetadata {
+                feature_names: self.feature_names,
+            },
+            results,
+            warnings: self.warnings,
+            feature_service_version: self.feature_service_version,
+            registry_version: self.registry_version,
+            debug: None,
+        })
+    }
+}
+
+/// Name of the file recording the response to `request`: a hash of the features, entities
+/// and naming it asks for.
+pub(crate) fn recording_file_name(request: &GetOnlineFeaturesRequest) -> Result<String> {
+    let key = RecordingKey {
+        entities: request.entities.iter().collect(),
+        feature_service: &request.feature_service,
+        features: &request.features,
+        full_feature_names: request.full_feature_names.unwrap_or(false),
+        project: &request.project,
+    };
+    let key = serde_json::to_vec(&key)?;
+    let hash = murmur3::murmur3_x64_128(&mut std::io::Cursor::new(key), 0)?;
+    Ok(format!("{:032x}.json", hash))
+}
+
+/// Write `response` to the recording file of `request` in `dir`.
+pub(crate) async fn record(
+    dir: &Path,
+    request: &GetOnlineFeaturesRequest,
+    response: &GetOnlineFeatureResponse,
+) -> Result<()> {
+    static TEMP_FILES: AtomicU64 = AtomicU64::new(0);
+
+    let file_name = recording_file_name(request)?;
+    let path = dir.join(&file_name);
+    let contents = serde_json::to_vec_pretty(&RecordedResponse::new(request, response))?;
+    tokio::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("Cannot create recording directory {}", dir.display()))?;
+    // Written aside and renamed, so that replays never read a partly written file
+    let temp_path = dir.join(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        std::process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    if let Err(err) = tokio::fs::write(&temp_path, contents).await {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        return Err(err)
+            .with_context(|| format!("Cannot write recorded response {}", temp_path.display()));
+    }
+    tokio::fs::rename(&temp_path, &path)
+        .await
+        .with_context(|| format!("Cannot write recorded response {}", path.display()))
+}
+
+/// Response recorded for `request` in `dir`, failing with
+/// [`FeastCoreError::RecordedResponseNotFound`] when none was.
+pub(crate) async fn replay(
+    dir: &Path,
+    request: &GetOnlineFeaturesRequest,
+) -> Result<GetOnlineFeatureResponse> {
+    let file_name = recording_file_name(request)?;
+    let path = dir.join(&file_name);
+    let contents = match tokio::fs::read(&path).await {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(FeastCoreError::recorded_response_not_found(file_name).into());
+        }
+        Err(err) => {
+            return Err(err).with_context(|| format!("Cannot read {}", path.display()));
+        }
+    };
+    serde_json::from_slice::<RecordedResponse<serde::de::IgnoredAny>>(&contents)
+        .map_err(anyhow::Error::from)
+        .and_then(RecordedResponse::into_response)
+        .with_context(|| format!("Invalid recorded response {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feast::types::Int64List;
+    use crate::feast::types::value::Val;
+    use crate::model::OrderedMap;
+
+    fn request(ids: Vec<i64>) -> GetOnlineFeaturesRequest {
+        GetOnlineFeaturesRequest {
+            entities: OrderedMap::from_iter([(
+                "driver_id".to_string(),
+                ids.into_iter().map(EntityIdValue::Int).collect(),
+            )]),
+            features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn file_names_ignore_response_fields() -> Result<()> {
+        let name = recording_file_name(&request(vec![1001]))?;
+        let trimmed = GetOnlineFeaturesRequest {
+            include_statuses: Some(false),
+            debug: Some(true),
+            ..request(vec![1001])
+        };
+        assert_eq!(recording_file_name(&trimmed)?, name);
+        assert_ne!(recording_file_name(&request(vec![1002]))?, name);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn replayed_responses_keep_value_types_and_versions() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let dir = temp_dir.path();
+        let values = vec![
+            Value {
+                val: Some(Val::FloatVal(0.5)),
+            },
+            Value {
+                val: Some(Val::Int32Val(7)),
+            },
+            Value {
+                val: Some(Val::Int64ListVal(Int64List { val: vec![1, 2] })),
+            },
+        ];
+        let response = GetOnlineFeatureResponse {
+            metadata: GetOnlineFeatureResponseMetadata {
+                feature_names: vec!["conv_rate".to_string()],
+            },
+            results: vec![FeatureResults {
+                values: values.into_iter().map(ValueWrapper).collect(),
+                statuses: vec![FeatureStatus::Present; 3],
+                event_timestamps: vec![],
+                created_timestamps: vec![],
+            }],
+            feature_service_version: Some("v2".to_string()),
+            registry_version: Some(3),
+            ..Default::default()
+        };
+
+        record(dir, &request(vec![1001]), &response).await?;
+        assert_eq!(replay(dir, &request(vec![1001])).await?, response);
+        let files = std::fs::read_dir(dir)?.count();
+        assert_eq!(files, 1, "no temporary file is left behind");
+        Ok(())
+    }
+}
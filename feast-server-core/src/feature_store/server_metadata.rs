@@ -0,0 +1,116 @@
+//! Description of the served registry and of the server build, for feature catalogs.
+
+use crate::config::RepoConfig;
+use crate::registry::{RegistryMetadata, RegistryRefreshFailure};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    /// `release` or `debug`.
+    pub profile: &'static str,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        Self {
+            name: "feast-server-rust",
+            version: env!("CARGO_PKG_VERSION"),
+            profile: if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerMetadata {
+    pub build: BuildInfo,
+    /// Number of registry reloads, changing whenever the registry below may have changed.
+    pub registry_generation: u64,
+    pub registry: RegistryMetadata,
+    /// Failure of the background registry refreshes since the last successful one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry_refresh_failure: Option<RegistryRefreshFailure>,
+}
+
+/// Configuration the server runs with, to tell which config a running server was started
+/// with. Passwords, API keys and credentials in connection strings are redacted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectiveConfig {
+    pub build: BuildInfo,
+    /// `feature_store.yaml` the config was read from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_path: Option<String>,
+    /// Command line of the server process.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub command_line: Vec<String>,
+    /// Path or connection string the registry is loaded from.
+    pub registry_source: JsonValue,
+    /// Seconds between registry refreshes, unset when the registry is never refreshed.
+    pub registry_cache_ttl_seconds: Option<u64>,
+    pub online_store: JsonValue,
+    pub repo_config: JsonValue,
+}
+
+impl EffectiveConfig {
+    pub fn new(config: &RepoConfig) -> Self {
+        let repo_config = config.redacted();
+        Self {
+            build: BuildInfo::current(),
+            config_path: None,
+            command_line: Vec::new(),
+            registry_source: repo_config["registry"]["path"].clone(),
+            registry_cache_ttl_seconds: config.registry.cache_ttl_seconds,
+            online_store: repo_config["online_store"].clone(),
+            repo_config,
+        }
+    }
+
+    pub fn with_config_path(mut self, config_path: impl Into<String>) -> Self {
+        self.config_path = Some(config_path.into());
+        self
+    }
+
+    pub fn with_command_line(mut self, command_line: Vec<String>) -> Self {
+        self.command_line = command_line;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_config_is_redacted() -> anyhow::Result<()> {
+        let config = RepoConfig::from_yaml_str(
+            r#"
+project: test
+registry:
+  path: postgresql://feast:secret@db:5432/feast
+  registry_type: sql
+  cache_ttl_seconds: 60
+provider: local
+online_store:
+  type: redis
+  connection_string: "redis:6379,password=hunter2"
+"#,
+        )?;
+        let effective = EffectiveConfig::new(&config).with_config_path("/repo/feature_store.yaml");
+        let json = serde_json::to_string(&effective)?;
+        assert!(!json.contains("secret"), "{}", json);
+        assert!(!json.contains("hunter2"), "{}", json);
+        assert_eq!(
+            effective.registry_source,
+            JsonValue::String("postgresql://feast:***@db:5432/feast".to_string())
+        );
+        assert_eq!(effective.registry_cache_ttl_seconds, Some(60));
+        assert_eq!(effective.online_store["type"], "redis");
+        Ok(())
+    }
+}
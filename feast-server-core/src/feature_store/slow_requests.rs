@@ -0,0 +1,252 @@
+use crate::config::SlowRequestsConfig;
+use crate::intern;
+use crate::model::GetOnlineFeaturesRequest;
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Time spent in the steps of a single `get_online_features` call.
+#[derive(Debug, Default)]
+pub(crate) struct RequestTimings {
+    pub(crate) cached: bool,
+    pub(crate) registry: Duration,
+    /// Online store calls with the feature views each of them read.
+    pub(crate) store: Vec<StoreCall>,
+    pub(crate) build: Duration,
+}
+
+/// One online store call of a request.
+#[derive(Debug)]
+pub(crate) struct StoreCall {
+    pub(crate) feature_views: Vec<Spur>,
+    /// Number of entity keys read.
+    pub(crate) keys: usize,
+    pub(crate) duration: Duration,
+}
+
+impl RequestTimings {
+    fn store_calls(&self) -> Vec<StoreCallTiming> {
+        let rodeo = intern::rodeo_ref();
+        self.store
+            .iter()
+            .map(|call| StoreCallTiming {
+                feature_views: call
+                    .feature_views
+                    .iter()
+                    .map(|view| rodeo.resolve(view).to_string())
+                    .collect(),
+                keys: call.keys,
+                duration_ms: millis(call.duration),
+            })
+            .collect()
+    }
+
+    /// Diagnostics of a request which took `elapsed` and asked for `entity_rows` rows,
+    /// resolved against registry generation `registry_generation`.
+    pub(crate) fn diagnostics(
+        &self,
+        elapsed: Duration,
+        entity_rows: usize,
+        registry_generation: Option<u64>,
+    ) -> RequestDiagnostics {
+        RequestDiagnostics {
+            duration_ms: millis(elapsed),
+            cached: self.cached,
+            registry_ms: millis(self.registry),
+            store_calls: self.store_calls(),
+            build_ms: millis(self.build),
+            entity_rows,
+            registry_generation,
+        }
+    }
+}
+
+/// Request without its entity values, which may identify users.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequestShape {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_service: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Number of values per entity column.
+    pub entities: BTreeMap<String, usize>,
+}
+
+impl From<&GetOnlineFeaturesRequest> for RequestShape {
+    fn from(request: &GetOnlineFeaturesRequest) -> Self {
+        Self {
+            feature_service: request.feature_service.clone(),
+            features: request.features.clone().unwrap_or_default(),
+            entities: request
+                .entities
+                .iter()
+                .map(|(name, values)| (name.clone(), values.len()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StoreCallTiming {
+    pub feature_views: Vec<String>,
+    /// Number of entity keys read.
+    pub keys: usize,
+    pub duration_ms: f64,
+}
+
+/// Timings of a request returned in its response when the request asks for them with
+/// `debug`, so that slow requests can be diagnosed without access to the server logs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequestDiagnostics {
+    pub duration_ms: f64,
+    /// The response was taken from the response cache.
+    pub cached: bool,
+    pub registry_ms: f64,
+    pub store_calls: Vec<StoreCallTiming>,
+    pub build_ms: f64,
+    /// Number of requested entity rows.
+    pub entity_rows: usize,
+    /// Generation of the registry the features were resolved against, which increases
+    /// with every registry reload.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry_generation: Option<u64>,
+}
+
+/// Request which took longer than the slow request threshold.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlowRequest {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: f64,
+    /// The response was taken from the response cache.
+    pub cached: bool,
+    pub registry_ms: f64,
+    pub store_calls: Vec<StoreCallTiming>,
+    pub build_ms: f64,
+    pub request: RequestShape,
+}
+
+/// Most recent slow requests, oldest first.
+#[derive(Debug)]
+pub(crate) struct SlowRequestLog {
+    threshold: Duration,
+    max_entries: usize,
+    entries: Mutex<VecDeque<SlowRequest>>,
+}
+
+impl SlowRequestLog {
+    pub(crate) fn new(config: &SlowRequestsConfig) -> Self {
+        Self {
+            threshold: Duration::from_millis(config.threshold_ms),
+            max_entries: config.max_entries,
+            entries: Mutex::new(VecDeque::with_capacity(config.max_entries)),
+        }
+    }
+
+    /// Log and keep the request when `elapsed` reaches the threshold.
+    pub(crate) fn record(
+        &self,
+        started_at: DateTime<Utc>,
+        elapsed: Duration,
+        timings: RequestTimings,
+        request: RequestShape,
+    ) {
+        if elapsed < self.threshold {
+            return;
+        }
+        let slow_request = SlowRequest {
+            started_at,
+            duration_ms: millis(elapsed),
+            cached: timings.cached,
+            registry_ms: millis(timings.registry),
+            store_calls: timings.store_calls(),
+            build_ms: millis(timings.build),
+            request,
+        };
+        tracing::warn!(
+            "Slow request took {:.1}ms: {}",
+            slow_request.duration_ms,
+            serde_json::to_string(&slow_request).unwrap_or_default()
+        );
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() == self.max_entries {
+            entries.pop_front();
+        }
+        entries.push_back(slow_request);
+    }
+
+    pub(crate) fn entries(&self) -> Vec<SlowRequest> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().cloned().collect()
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::EntityIdValue;
+
+    fn request_shape(feature_service: &str) -> RequestShape {
+        RequestShape::from(&GetOnlineFeaturesRequest {
+            feature_service: Some(feature_service.to_string()),
+            entities: [(
+                "driver_id".to_string(),
+                vec![EntityIdValue::Int(1001), EntityIdValue::Int(1002)],
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn keeps_last_slow_requests() {
+        let log = SlowRequestLog::new(&SlowRequestsConfig {
+            threshold_ms: 100,
+            max_entries: 2,
+        });
+        let timings = || RequestTimings {
+            store: vec![StoreCall {
+                feature_views: vec![intern::rodeo_ref().get_or_intern("driver_hourly_stats")],
+                keys: 2,
+                duration: Duration::from_millis(90),
+            }],
+            ..Default::default()
+        };
+        for (service, elapsed_ms) in [
+            ("fast", 50),
+            ("first", 150),
+            ("second", 200),
+            ("third", 100),
+        ] {
+            log.record(
+                Utc::now(),
+                Duration::from_millis(elapsed_ms),
+                timings(),
+                request_shape(service),
+            );
+        }
+
+        let entries = log.entries();
+        let services = entries
+            .iter()
+            .map(|entry| entry.request.feature_service.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(services, ["second", "third"]);
+        assert_eq!(entries[1].request.entities["driver_id"], 2);
+        assert_eq!(
+            entries[1].store_calls,
+            [StoreCallTiming {
+                feature_views: vec!["driver_hourly_stats".to_string()],
+                keys: 2,
+                duration_ms: 90.0
+            }]
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use crate::intern;
+use crate::model::{GetOnlineFeaturesRequest, RequestedFeatures};
+use crate::onlinestore::OnlineStore;
+use crate::registry::FeatureRegistryService;
+use anyhow::{Result, anyhow};
+use lasso::Spur;
+use rustc_hash::FxHashSet as HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Number of feature views of the registry without an online table.
+pub(crate) const MISSING_TABLES_METRIC: &str = "feast_missing_online_tables";
+
+/// Registry generation value before the first check.
+const NOT_CHECKED: u64 = u64::MAX;
+
+/// Detection of feature views whose online table does not exist, which otherwise read as
+/// missing values. Views are checked again whenever the registry is reloaded.
+#[derive(Debug)]
+pub(crate) struct TableCheck {
+    required_feature_services: Vec<String>,
+    checked_generation: AtomicU64,
+    missing: Mutex<HashSet<Spur>>,
+}
+
+impl TableCheck {
+    pub(crate) fn new(required_feature_services: Vec<String>) -> Self {
+        Self {
+            required_feature_services,
+            checked_generation: AtomicU64::new(NOT_CHECKED),
+            missing: Mutex::new(HashSet::default()),
+        }
+    }
+
+    /// Check the views of the current registry generation unless it was already checked.
+    pub(crate) async fn check(
+        &self,
+        registry: &dyn FeatureRegistryService,
+        online_store: &dyn OnlineStore,
+    ) -> Result<()> {
+        let generation = registry.generation();
+        if self.checked_generation.swap(generation, Ordering::AcqRel) == generation {
+            return Ok(());
+        }
+        let result = self.check_tables(registry, online_store).await;
+        if result.is_err() {
+            self.checked_generation
+                .store(NOT_CHECKED, Ordering::Release);
+        }
+        result
+    }
+
+    /// Run [`Self::check`] on a background task when the registry was reloaded since the
+    /// last check, without delaying the caller.
+    pub(crate) fn check_in_background(
+        self: &Arc<Self>,
+        registry: &Arc<dyn FeatureRegistryService>,
+        online_store: &Arc<dyn OnlineStore>,
+    ) {
+        if self.checked_generation.load(Ordering::Acquire) == registry.generation() {
+            return;
+        }
+        let table_check = Arc::clone(self);
+        let registry = Arc::clone(registry);
+        let online_store = Arc::clone(online_store);
+        tokio::spawn(async move {
+            if let Err(err) = table_check.check(&*registry, &*online_store).await {
+                tracing::warn!("Failed to check online tables: {:#}", err);
+            }
+        });
+    }
+
+    /// Names of the feature views found without an online table by the last check.
+    pub(crate) fn missing_tables(&self) -> Vec<String> {
+        let rodeo = intern::rodeo_ref();
+        let mut missing = self
+            .missing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|view| rodeo.resolve(view).to_string())
+            .collect::<Vec<_>>();
+        missing.sort();
+        missing
+    }
+
+    /// Fail when a view of a required feature service has no online table.
+    pub(crate) async fn check_readiness(
+        &self,
+        registry: &dyn FeatureRegistryService,
+    ) -> Result<()> {
+        if self.required_feature_services.is_empty() {
+            return Ok(());
+        }
+        let missing = self
+            .missing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let rodeo = intern::rodeo_ref();
+        for feature_service in &self.required_feature_services {
+            let request = GetOnlineFeaturesRequest {
+                feature_service: Some(feature_service.clone()),
+                ..Default::default()
+            };
+            let views = registry
+                .request_to_view_keys(RequestedFeatures::try_from(&request)?)
+                .await?;
+            let mut missing_views = views
+                .values()
+                .map(|view| view.name)
+                .filter(|view| missing.contains(view))
+                .map(|view| rodeo.resolve(&view))
+                .collect::<Vec<_>>();
+            if !missing_views.is_empty() {
+                missing_views.sort();
+                missing_views.dedup();
+                return Err(anyhow!(
+                    "Online tables of feature views [{}] of feature service {} are missing",
+                    missing_views.join(", "),
+                    feature_service
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    async fn check_tables(
+        &self,
+        registry: &dyn FeatureRegistryService,
+        online_store: &dyn OnlineStore,
+    ) -> Result<()> {
+        let views = registry
+            .feature_views()
+            .await?
+            .iter()
+            .map(|view| view.name)
+            .collect::<Vec<_>>();
+        let missing = online_store.missing_tables(views).await?;
+        let rodeo = intern::rodeo_ref();
+        for view in &missing {
+            tracing::warn!(
+                "Online table of feature view {} does not exist, its features read as missing",
+                rodeo.resolve(view)
+            );
+        }
+        metrics::gauge!(MISSING_TABLES_METRIC).set(missing.len() as f64);
+        *self.missing.lock().unwrap_or_else(|e| e.into_inner()) = missing.into_iter().collect();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::RepoConfig;
+    use crate::feature_store::FeatureStore;
+    use anyhow::Result;
+    use std::path::Path;
+
+    async fn feature_store(repo_dir: &Path) -> Result<FeatureStore> {
+        let config = RepoConfig::from_yaml_str(
+            r#"
+project: golden_hornet
+registry: registry.pb
+online_store:
+    type: sqlite
+    path: online_store.db
+readiness:
+    required_feature_services: [driver_activity_v4]
+"#,
+        )?;
+        FeatureStore::builder()
+            .with_repo_config(config)
+            .with_cwd(repo_dir)
+            .build()
+            .await
+    }
+
+    #[tokio::test]
+    async fn missing_tables_fail_readiness_of_required_services() -> Result<()> {
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data");
+        let temp_dir = tempfile::tempdir()?;
+        let repo_dir = temp_dir.path();
+        for file in ["registry.pb", "online_store.db"] {
+            std::fs::copy(test_data.join(file), repo_dir.join(file))?;
+        }
+
+        let store = feature_store(repo_dir).await?;
+        store.health_check().await?;
+        let missing_before = store.check_online_tables().await?;
+        assert!(!missing_before.contains(&"driver_hourly_stats".to_string()));
+
+        let pool =
+            sqlx::SqlitePool::connect(repo_dir.join("online_store.db").to_str().unwrap()).await?;
+        sqlx::query("DROP TABLE golden_hornet_driver_hourly_stats")
+            .execute(&pool)
+            .await?;
+        // Tables are checked again only once the registry is reloaded
+        assert_eq!(store.check_online_tables().await?, missing_before);
+
+        let store = feature_store(repo_dir).await?;
+        let missing = store.check_online_tables().await?;
+        let readiness = store.health_check().await;
+        assert!(missing.contains(&"driver_hourly_stats".to_string()));
+        assert_eq!(
+            readiness.err().unwrap().to_string(),
+            "Online tables of feature views [driver_hourly_stats] of feature service \
+             driver_activity_v4 are missing"
+        );
+        Ok(())
+    }
+}
@@ -1,8 +1,33 @@
-use lasso::ThreadedRodeo;
+//! Process wide string interner.
+//!
+//! Registry names are interned when a registry is loaded. Reloads intern the same names
+//! again, so the interner only grows with names added to the registry. Interned keys are
+//! kept by registries, caches and in-flight requests across reloads, which rules out
+//! rebuilding the interner; instead strings received in requests go through
+//! [`request_key`], which only interns a bounded number of strings the registry does not
+//! know.
+
+use crate::error::FeastCoreError;
+use anyhow::Result;
+use lasso::{Spur, ThreadedRodeo};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, OnceLock};
 
 static GLOBAL_RODEO: OnceLock<Arc<ThreadedRodeo>> = OnceLock::new();
 
+/// Number of strings interned by [`request_key`].
+static REQUEST_STRINGS: AtomicUsize = AtomicUsize::new(0);
+static REQUEST_STRING_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_REQUEST_STRING_LIMIT);
+
+pub const DEFAULT_REQUEST_STRING_LIMIT: usize = 100_000;
+
+/// Number of interned strings.
+pub const INTERNED_STRINGS_METRIC: &str = "feast_interned_strings";
+/// Number of strings interned from requests rather than from the registry.
+pub const INTERNED_REQUEST_STRINGS_METRIC: &str = "feast_interned_request_strings";
+/// Bytes allocated by the interner for string contents.
+pub const INTERNER_MEMORY_METRIC: &str = "feast_interner_memory_bytes";
+
 /// Returns a clone of the global `ThreadedRodeo`, initialising it on first use.
 pub fn rodeo() -> Arc<ThreadedRodeo> {
     GLOBAL_RODEO
@@ -16,3 +41,74 @@ pub fn rodeo_ref() -> &'static ThreadedRodeo {
         .get_or_init(|| Arc::new(ThreadedRodeo::default()))
         .as_ref()
 }
+
+/// Upper bound on strings interned by [`request_key`].
+pub fn set_request_string_limit(limit: usize) {
+    REQUEST_STRING_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// Key of a name received in a request. Names already interned, e.g. every name of the
+/// registry, are looked up without allocating. Other names cannot refer to anything in the
+/// registry but are interned so that responses and errors can carry them, until the
+/// request string limit is reached; past it they are rejected as unknown.
+pub fn request_key(name: &str) -> Result<Spur> {
+    let limit = REQUEST_STRING_LIMIT.load(Ordering::Relaxed);
+    let (key, interned) = bounded_key(rodeo_ref(), &REQUEST_STRINGS, limit, name)?;
+    if interned {
+        record_metrics();
+    }
+    Ok(key)
+}
+
+/// Key of `name`, interning it only while `count` is below `limit`. Returns whether the
+/// name was interned.
+fn bounded_key(
+    rodeo: &ThreadedRodeo,
+    count: &AtomicUsize,
+    limit: usize,
+    name: &str,
+) -> Result<(Spur, bool)> {
+    if let Some(key) = rodeo.get(name) {
+        return Ok((key, false));
+    }
+    if count.fetch_add(1, Ordering::Relaxed) >= limit {
+        count.fetch_sub(1, Ordering::Relaxed);
+        return Err(FeastCoreError::unknown_name(name).into());
+    }
+    Ok((rodeo.get_or_intern(name), true))
+}
+
+/// Export the size of the interner as metrics.
+pub fn record_metrics() {
+    let rodeo = rodeo_ref();
+    metrics::gauge!(INTERNED_STRINGS_METRIC).set(rodeo.len() as f64);
+    metrics::gauge!(INTERNED_REQUEST_STRINGS_METRIC)
+        .set(REQUEST_STRINGS.load(Ordering::Relaxed) as f64);
+    metrics::gauge!(INTERNER_MEMORY_METRIC).set(rodeo.current_memory_usage() as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_names_past_the_limit_are_unknown() {
+        let rodeo = ThreadedRodeo::default();
+        let count = AtomicUsize::new(0);
+        let known = rodeo.get_or_intern("driver_hourly_stats");
+        let key = |name| bounded_key(&rodeo, &count, 1, name);
+
+        assert_eq!(key("driver_hourly_stats").unwrap(), (known, false));
+        let (first, interned) = key("first_unknown").unwrap();
+        assert!(interned);
+        assert_eq!(rodeo.resolve(&first), "first_unknown");
+        assert_eq!(key("first_unknown").unwrap(), (first, false));
+        let err = key("second_unknown").unwrap_err();
+        assert!(
+            err.downcast_ref::<FeastCoreError>()
+                .is_some_and(FeastCoreError::is_not_found)
+        );
+        assert!(rodeo.get("second_unknown").is_none());
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+    }
+}
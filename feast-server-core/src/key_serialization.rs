@@ -26,14 +26,14 @@ fn serialize_value(value: &Value) -> Result<Vec<u8>> {
         Val::StringVal(v) => {
             let mut bytes = vec![];
             bytes.extend((Enum::String as u32).to_le_bytes());
-            bytes.extend((v.len() as u32).to_le_bytes());
+            bytes.extend(serialized_len(v.len())?.to_le_bytes());
             bytes.extend(v.as_bytes());
             Ok(bytes)
         }
         Val::BytesVal(v) => {
             let mut bytes = vec![];
             bytes.extend((Enum::Bytes as u32).to_le_bytes());
-            bytes.extend((v.len() as u32).to_le_bytes());
+            bytes.extend(serialized_len(v.len())?.to_le_bytes());
             bytes.extend(v);
             Ok(bytes)
         }
@@ -41,50 +41,76 @@ fn serialize_value(value: &Value) -> Result<Vec<u8>> {
     }
 }
 
-fn deserialize_val(bytes: &[u8], mut idx: usize) -> Result<(Val, usize)> {
-    let value_type_int: i32 = i32::from_le_bytes(bytes[idx..idx + 4].try_into()?);
+/// Length prefix of a serialized key part, which cannot exceed `u32::MAX`.
+fn serialized_len(len: usize) -> Result<u32> {
+    u32::try_from(len).map_err(|_| anyhow!("Entity key part of {} bytes is too long", len))
+}
+
+/// Reads of a serialized entity key which fail with the offset and the part being read
+/// when the key is too short, instead of indexing past its end.
+struct KeyReader<'a> {
+    bytes: &'a [u8],
+    idx: usize,
+}
+
+impl<'a> KeyReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, idx: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.idx
+    }
+
+    fn read_bytes(&mut self, len: usize, part: &dyn std::fmt::Display) -> Result<&'a [u8]> {
+        let read = self
+            .idx
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.idx..end))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Entity key of {} bytes is truncated: {} needs {} bytes at offset {}",
+                    self.bytes.len(),
+                    part,
+                    len,
+                    self.idx
+                )
+            })?;
+        self.idx += len;
+        Ok(read)
+    }
+
+    fn read_u32(&mut self, part: &dyn std::fmt::Display) -> Result<u32> {
+        let bytes = self.read_bytes(4, part)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+fn deserialize_val(reader: &mut KeyReader, i: u32) -> Result<Val> {
+    let value_type_int = reader.read_u32(&format_args!("type of value {}", i))? as i32;
     let value_type = Enum::try_from(value_type_int).with_context(|| {
         format!(
             "Failed to convert i32 value {} to value type",
             value_type_int
         )
     })?;
-    idx += 4;
+    let size = reader.read_u32(&format_args!("size of value {}", i))? as usize;
+    let value = reader.read_bytes(size, &format_args!("value {}", i))?;
     match value_type {
         Enum::Int32 => {
-            let size: u32 = u32::from_le_bytes(bytes[idx..idx + 4].try_into()?);
-            if size != 4 {
-                return Err(anyhow!("Incorrect size of serialized int 32"));
-            }
-            idx += 4;
-            let val_int = i32::from_le_bytes(bytes[idx..idx + 4].try_into()?);
-            idx += 4;
-            Ok((Val::Int32Val(val_int), idx))
+            let value: [u8; 4] = value
+                .try_into()
+                .map_err(|_| anyhow!("Incorrect size of serialized int 32"))?;
+            Ok(Val::Int32Val(i32::from_le_bytes(value)))
         }
         Enum::Int64 => {
-            let size: u32 = u32::from_le_bytes(bytes[idx..idx + 4].try_into()?);
-            if size != 8 {
-                return Err(anyhow!("Incorrect size of serialized int 64"));
-            }
-            idx += 4;
-            let val_int = i64::from_le_bytes(bytes[idx..idx + 8].try_into()?);
-            idx += 8;
-            Ok((Val::Int64Val(val_int), idx))
-        }
-        Enum::Bytes => {
-            let size: u32 = u32::from_le_bytes(bytes[idx..idx + 4].try_into()?);
-            idx += 4;
-            let val_bytes = bytes[idx..idx + size as usize].to_vec();
-            idx += size as usize;
-            Ok((Val::BytesVal(val_bytes), idx))
-        }
-        Enum::String => {
-            let size: u32 = u32::from_le_bytes(bytes[idx..idx + 4].try_into()?);
-            idx += 4;
-            let val_str = String::from_utf8(bytes[idx..idx + size as usize].into())?;
-            idx += size as usize;
-            Ok((Val::StringVal(val_str), idx))
+            let value: [u8; 8] = value
+                .try_into()
+                .map_err(|_| anyhow!("Incorrect size of serialized int 64"))?;
+            Ok(Val::Int64Val(i64::from_le_bytes(value)))
         }
+        Enum::Bytes => Ok(Val::BytesVal(value.to_vec())),
+        Enum::String => Ok(Val::StringVal(String::from_utf8(value.to_vec())?)),
         other => Err(anyhow!(
             "Unsupported serialized type {}",
             other.as_str_name()
@@ -92,6 +118,70 @@ fn deserialize_val(bytes: &[u8], mut idx: usize) -> Result<(Val, usize)> {
     }
 }
 
+/// Number of bytes of the first `chars` UTF-8 characters of `bytes`, from the lengths their
+/// leading bytes announce. Invalid sequences are left to the UTF-8 validation of the caller.
+fn utf8_len(bytes: &[u8], chars: usize) -> usize {
+    let mut len = 0;
+    for _ in 0..chars {
+        len += match bytes.get(len) {
+            Some(byte) if byte & 0xE0 == 0xC0 => 2,
+            Some(byte) if byte & 0xF0 == 0xE0 => 3,
+            Some(byte) if byte & 0xF8 == 0xF0 => 4,
+            _ => 1,
+        };
+    }
+    len
+}
+
+/// Serialization version `bytes` of a stored entity key look written with: V3 when they
+/// hold exactly the key count, the length prefixed join keys and the values of a V3 key,
+/// V2 when they start with a join key tag directly followed by its name, as keys of V2 and
+/// earlier have neither a key count nor key lengths. `None` when they match neither.
+pub(crate) fn detect_key_version(bytes: &[u8]) -> Option<EntityKeySerializationVersion> {
+    let read_u32 = |at: usize| {
+        bytes
+            .get(at..at.checked_add(4)?)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let is_v3 = || {
+        let key_count = read_u32(0)?;
+        if key_count == 0 || key_count as usize > bytes.len() {
+            return None;
+        }
+        let mut idx = 4;
+        for _ in 0..key_count {
+            if read_u32(idx)? != Enum::String as u32 {
+                return None;
+            }
+            let chars = read_u32(idx + 4)? as usize;
+            idx += 8;
+            let name = bytes.get(idx..)?;
+            if chars > name.len() {
+                return None;
+            }
+            let name_len = utf8_len(name, chars);
+            std::str::from_utf8(name.get(..name_len)?).ok()?;
+            idx += name_len;
+        }
+        for _ in 0..key_count {
+            let value_len = read_u32(idx + 4)? as usize;
+            idx = idx.checked_add(8 + value_len)?;
+        }
+        (idx == bytes.len()).then_some(())
+    };
+    if is_v3().is_some() {
+        return Some(EntityKeySerializationVersion::V3);
+    }
+    match (read_u32(0), bytes.get(4)) {
+        (Some(tag), Some(first))
+            if tag == Enum::String as u32 && (first.is_ascii_alphabetic() || *first == b'_') =>
+        {
+            Some(EntityKeySerializationVersion::V2)
+        }
+        _ => None,
+    }
+}
+
 /// Serialize entity key to bytes.
 /// Support only V3 version of serializer.
 pub fn serialize_key(
@@ -101,6 +191,13 @@ pub fn serialize_key(
     if serializer_version != EntityKeySerializationVersion::V3 {
         return Err(anyhow!("Unsupported version of key serializer"));
     }
+    if entity_key.join_keys.len() != entity_key.entity_values.len() {
+        return Err(anyhow!(
+            "Entity key has {} join keys but {} values",
+            entity_key.join_keys.len(),
+            entity_key.entity_values.len()
+        ));
+    }
     let key_map: HashMap<&str, &Value> = entity_key
         .join_keys
         .iter()
@@ -110,10 +207,12 @@ pub fn serialize_key(
     let mut sorted_keys: Vec<&str> = key_map.keys().cloned().collect();
     sorted_keys.sort();
     let mut bytes: Vec<u8> = Vec::with_capacity(30);
-    bytes.extend((sorted_keys.len() as u32).to_le_bytes());
+    bytes.extend(serialized_len(sorted_keys.len())?.to_le_bytes());
     for key in &sorted_keys {
         bytes.extend((Enum::String as u32).to_le_bytes());
-        bytes.extend((key.len() as u32).to_le_bytes());
+        // The Python SDK writes `len(k)`, the number of characters rather than of bytes,
+        // which only differ for non-ASCII join keys
+        bytes.extend(serialized_len(key.chars().count())?.to_le_bytes());
         bytes.extend(key.bytes());
     }
     for key in &sorted_keys {
@@ -137,31 +236,47 @@ pub fn deserialize_key(
     if serializer_version != EntityKeySerializationVersion::V3 {
         return Err(anyhow!("Unsupported version of key serializer"));
     }
-    let key_len: u32 = u32::from_le_bytes(bytes[0..4].try_into()?);
-    let mut join_keys: Vec<String> = Vec::with_capacity(key_len as usize);
-    let mut entity_values: Vec<Value> = Vec::with_capacity(key_len as usize);
-    let mut idx: usize = 4;
-    for i in 0..key_len {
-        let string_type = u32::from_le_bytes(bytes[idx..idx + 4].try_into()?);
+    let mut reader = KeyReader::new(&bytes);
+    let key_count = reader.read_u32(&"key count")?;
+    // Every join key takes at least 8 bytes, so a larger count is corrupt rather than a
+    // capacity to allocate
+    if key_count as usize > reader.remaining() / 8 {
+        return Err(anyhow!(
+            "Incorrect format of key: {} join keys do not fit in {} bytes",
+            key_count,
+            bytes.len()
+        ));
+    }
+    let mut join_keys: Vec<String> = Vec::with_capacity(key_count as usize);
+    let mut entity_values: Vec<Value> = Vec::with_capacity(key_count as usize);
+    for i in 0..key_count {
+        let string_type = reader.read_u32(&format_args!("type of key {}", i))?;
         if string_type != Enum::String as u32 {
             return Err(anyhow!("Incorrect format of key: incorrect key type"));
         }
-        idx += 4;
-        let key_len = u32::from_le_bytes(
-            bytes[idx..idx + 4]
-                .try_into()
-                .with_context(|| format!("Cannot deserialize key len for key number {}", i))?,
-        );
-        idx += 4;
-        let key_name = String::from_utf8(bytes[idx..idx + key_len as usize].into())
-            .with_context(|| format!("Cannot deserialize key name for key number {}", i))?
-            .to_string();
+        let chars = reader
+            .read_u32(&format_args!("length of key {}", i))
+            .with_context(|| format!("Cannot deserialize key len for key number {}", i))?;
+        let remaining = &bytes[reader.idx..];
+        if chars as usize > remaining.len() {
+            return Err(anyhow!(
+                "Entity key of {} bytes is truncated: key {} of {} characters at offset {}",
+                bytes.len(),
+                i,
+                chars,
+                reader.idx
+            ));
+        }
+        let name = reader.read_bytes(
+            utf8_len(remaining, chars as usize),
+            &format_args!("name of key {}", i),
+        )?;
+        let key_name = String::from_utf8(name.to_vec())
+            .with_context(|| format!("Cannot deserialize key name for key number {}", i))?;
         join_keys.push(key_name);
-        idx += key_len as usize;
     }
-    for i in 0..key_len {
-        let (val, new_val_start) = deserialize_val(&bytes, idx)?;
-        idx = new_val_start;
+    for i in 0..key_count {
+        let val = deserialize_val(&mut reader, i)?;
         entity_values.push(Value { val: Some(val) });
     }
     Ok(EntityKey {
@@ -213,4 +328,280 @@ mod tests {
         assert_eq!(entity_key, deserialized_key);
         Ok(())
     }
+
+    fn vector_value(value: &serde_json::Value) -> Value {
+        let (kind, raw) = value.as_object().unwrap().iter().next().unwrap();
+        let val = match kind.as_str() {
+            "int32_val" => Val::Int32Val(raw.as_i64().unwrap() as i32),
+            "int64_val" => Val::Int64Val(raw.as_i64().unwrap()),
+            "string_val" => Val::StringVal(raw.as_str().unwrap().to_string()),
+            "bytes_val" => Val::BytesVal(hex::decode(raw.as_str().unwrap()).unwrap()),
+            other => panic!("unexpected value kind {}", other),
+        };
+        Value { val: Some(val) }
+    }
+
+    /// Keys are deserialized sorted by join key, as they are serialized.
+    fn sorted(entity_key: &EntityKey) -> EntityKey {
+        let mut pairs: Vec<_> = entity_key
+            .join_keys
+            .iter()
+            .cloned()
+            .zip(entity_key.entity_values.iter().cloned())
+            .collect();
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        let (join_keys, entity_values) = pairs.into_iter().unzip();
+        EntityKey {
+            join_keys,
+            entity_values,
+        }
+    }
+
+    /// Vectors of `test_data/entity_key_vectors.json`, as the Python SDK serializes them; see
+    /// `test_data/generate_entity_key_vectors.py`.
+    #[test]
+    fn python_sdk_vectors() -> Result<()> {
+        let path = format!(
+            "{}/test_data/entity_key_vectors.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let vectors: Vec<serde_json::Value> =
+            serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        for vector in vectors {
+            let description = vector["description"].as_str().unwrap();
+            let entity_key = EntityKey {
+                join_keys: vector["join_keys"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|key| key.as_str().unwrap().to_string())
+                    .collect(),
+                entity_values: vector["values"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(vector_value)
+                    .collect(),
+            };
+            let expected = hex::decode(vector["serialized"].as_str().unwrap())?;
+            let serialized = serialize_key(&entity_key, EntityKeySerializationVersion::V3)?;
+            assert_eq!(
+                hex::encode(&serialized),
+                hex::encode(&expected),
+                "{}",
+                description
+            );
+            assert_eq!(
+                deserialize_key(expected, EntityKeySerializationVersion::V3)?,
+                sorted(&entity_key),
+                "{}",
+                description
+            );
+        }
+        Ok(())
+    }
+
+    /// Round trips of generated keys, with a fixed seed so that failures reproduce.
+    #[test]
+    fn generated_keys_round_trip() -> Result<()> {
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let alphabet: Vec<char> = "abz_09ßé東京🚀".chars().collect();
+        for _ in 0..1000 {
+            let key_count = 1 + next() % 4;
+            let mut join_keys: Vec<String> = Vec::new();
+            while (join_keys.len() as u64) < key_count {
+                let key: String = (0..1 + next() % 12)
+                    .map(|_| alphabet[(next() % alphabet.len() as u64) as usize])
+                    .collect();
+                if !join_keys.contains(&key) {
+                    join_keys.push(key);
+                }
+            }
+            let entity_values = join_keys
+                .iter()
+                .map(|_| {
+                    let val = match next() % 4 {
+                        0 => Val::Int32Val(next() as i32),
+                        1 => Val::Int64Val(next() as i64),
+                        2 => Val::StringVal(
+                            (0..next() % 8)
+                                .map(|_| alphabet[(next() % alphabet.len() as u64) as usize])
+                                .collect(),
+                        ),
+                        _ => Val::BytesVal((0..next() % 8).map(|_| next() as u8).collect()),
+                    };
+                    Value { val: Some(val) }
+                })
+                .collect();
+            let entity_key = EntityKey {
+                join_keys,
+                entity_values,
+            };
+            let serialized = serialize_key(&entity_key, EntityKeySerializationVersion::V3)?;
+            let deserialized = deserialize_key(serialized, EntityKeySerializationVersion::V3)?;
+            assert_eq!(deserialized, sorted(&entity_key));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_keys_are_rejected() -> Result<()> {
+        let entity_key = EntityKey {
+            join_keys: vec!["driver_id".to_string(), "città".to_string()],
+            entity_values: vec![
+                Value {
+                    val: Some(Val::Int64Val(1005)),
+                },
+                Value {
+                    val: Some(Val::StringVal("abc".to_string())),
+                },
+            ],
+        };
+        let serialized = serialize_key(&entity_key, EntityKeySerializationVersion::V3)?;
+        for len in 0..serialized.len() {
+            let truncated = serialized[..len].to_vec();
+            assert!(
+                deserialize_key(truncated, EntityKeySerializationVersion::V3).is_err(),
+                "key truncated to {} bytes was deserialized",
+                len
+            );
+        }
+        let error_at = |serialized: &[u8], len: usize| {
+            deserialize_key(
+                serialized[..len].to_vec(),
+                EntityKeySerializationVersion::V3,
+            )
+            .unwrap_err()
+            .to_string()
+        };
+        assert_eq!(
+            error_at(&serialized, 30),
+            "Entity key of 30 bytes is truncated: key 1 of 9 characters at offset 26"
+        );
+        // "città" has 5 characters but 6 bytes
+        let single = serialize_key(
+            &EntityKey {
+                join_keys: vec!["città".to_string()],
+                entity_values: vec![Value {
+                    val: Some(Val::Int32Val(7)),
+                }],
+            },
+            EntityKeySerializationVersion::V3,
+        )?;
+        assert_eq!(
+            error_at(&single, 17),
+            "Entity key of 17 bytes is truncated: name of key 0 needs 6 bytes at offset 12"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_lengths_are_rejected() {
+        let deserialize = |bytes: Vec<u8>| {
+            deserialize_key(bytes, EntityKeySerializationVersion::V3)
+                .unwrap_err()
+                .to_string()
+        };
+        let mut key = Vec::new();
+        key.extend(u32::MAX.to_le_bytes());
+        key.extend((Enum::String as u32).to_le_bytes());
+        assert_eq!(
+            deserialize(key),
+            "Incorrect format of key: 4294967295 join keys do not fit in 8 bytes"
+        );
+
+        let mut key = Vec::new();
+        key.extend(1u32.to_le_bytes());
+        key.extend((Enum::String as u32).to_le_bytes());
+        key.extend(1u32.to_le_bytes());
+        key.extend(b"a");
+        key.extend((Enum::Bytes as u32).to_le_bytes());
+        key.extend(u32::MAX.to_le_bytes());
+        key.extend(b"ab");
+        assert_eq!(
+            deserialize(key),
+            "Entity key of 23 bytes is truncated: value 0 needs 4294967295 bytes at offset 21"
+        );
+
+        let mut key = Vec::new();
+        key.extend(1u32.to_le_bytes());
+        key.extend((Enum::String as u32).to_le_bytes());
+        key.extend(1u32.to_le_bytes());
+        key.extend(b"a");
+        key.extend((Enum::Int64 as u32).to_le_bytes());
+        key.extend(4u32.to_le_bytes());
+        key.extend(7i32.to_le_bytes());
+        assert_eq!(deserialize(key), "Incorrect size of serialized int 64");
+    }
+
+    #[test]
+    fn keys_without_a_value_per_join_key_are_rejected() {
+        let entity_key = EntityKey {
+            join_keys: vec!["driver_id".to_string(), "customer_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(1005)),
+            }],
+        };
+        let err = serialize_key(&entity_key, EntityKeySerializationVersion::V3).unwrap_err();
+        assert_eq!(err.to_string(), "Entity key has 2 join keys but 1 values");
+    }
+
+    #[test]
+    fn only_v3_keys_are_supported() {
+        let entity_key = EntityKey {
+            join_keys: vec!["driver_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(1005)),
+            }],
+        };
+        for version in [
+            EntityKeySerializationVersion::V1,
+            EntityKeySerializationVersion::V2,
+        ] {
+            assert!(serialize_key(&entity_key, version.clone()).is_err());
+            assert!(deserialize_key(vec![0; 4], version).is_err());
+        }
+    }
+
+    #[test]
+    fn detects_key_versions() -> Result<()> {
+        let entity_key = EntityKey {
+            join_keys: vec!["driver_id".to_string(), "customer_id".to_string()],
+            entity_values: vec![
+                Value {
+                    val: Some(Val::Int64Val(1005)),
+                },
+                Value {
+                    val: Some(Val::StringVal("abc".to_string())),
+                },
+            ],
+        };
+        let v3 = serialize_key(&entity_key, EntityKeySerializationVersion::V3)?;
+        assert_eq!(
+            detect_key_version(&v3),
+            Some(EntityKeySerializationVersion::V3)
+        );
+        assert_eq!(detect_key_version(&v3[..v3.len() - 1]), None);
+
+        // V2: the tag and name of every join key, then the values
+        let mut v2 = Vec::new();
+        v2.extend((Enum::String as u32).to_le_bytes());
+        v2.extend(b"driver_id");
+        v2.extend((Enum::Int64 as u32).to_le_bytes());
+        v2.extend(8u32.to_le_bytes());
+        v2.extend(1005i64.to_le_bytes());
+        assert_eq!(
+            detect_key_version(&v2),
+            Some(EntityKeySerializationVersion::V2)
+        );
+        assert_eq!(detect_key_version(&[]), None);
+        assert_eq!(detect_key_version(&[0xFF; 12]), None);
+        Ok(())
+    }
 }
@@ -1,16 +1,25 @@
 #![allow(unused)]
 
+pub mod bundle;
 pub mod config;
 pub mod error;
 pub mod feature_store;
 pub mod intern;
 mod key_serialization;
+pub mod load;
+pub mod load_shedding;
 pub mod model;
 mod util;
 
 pub mod onlinestore;
+pub mod payload_sizes;
+pub mod preflight;
 mod proto_utils;
+pub mod query;
 pub mod registry;
+pub mod secrets;
+#[cfg(test)]
+mod test_util;
 
 pub mod feast {
     pub mod types {
@@ -0,0 +1,159 @@
+//! Reading of feature rows from offline parquet and CSV files, to materialize small data
+//! sets into the online store with [`FeatureStore::write_to_online_store`] without the
+//! Python SDK.
+//!
+//! [`FeatureStore::write_to_online_store`]: crate::feature_store::FeatureStore::write_to_online_store
+
+use crate::feast::types::value_type::Enum as ValueTypeEnum;
+use crate::util::csv_cell_to_json;
+use anyhow::{Context, Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use parquet::file::reader::SerializedFileReader;
+use parquet::record::Field;
+use rustc_hash::FxHashMap as HashMap;
+use serde_json::Value as JsonValue;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Columns of a file by name, each holding one value per row.
+pub type FeatureColumns = HashMap<String, Vec<JsonValue>>;
+
+/// Read the rows of a parquet file, or of a CSV file when the extension is `.csv`. CSV cells
+/// are converted to the type `column_type` gives their column.
+pub fn read_feature_file(
+    path: &Path,
+    column_type: impl Fn(&str) -> Option<ValueTypeEnum>,
+) -> Result<FeatureColumns> {
+    let file = File::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+    let is_csv = path.extension().is_some_and(|ext| ext == "csv");
+    let columns = if is_csv {
+        read_csv(file, column_type)
+    } else {
+        read_parquet(file)
+    };
+    columns.with_context(|| format!("Cannot read feature rows from {}", path.display()))
+}
+
+/// Read CSV rows with a header line. Cells are read as the type `column_type` gives their
+/// column, e.g. the registry type of the feature or join key, and inferred as for in-memory
+/// store fixtures for other columns such as timestamps.
+pub fn read_csv(
+    reader: impl Read,
+    column_type: impl Fn(&str) -> Option<ValueTypeEnum>,
+) -> Result<FeatureColumns> {
+    let mut csv_reader = csv::Reader::from_reader(reader);
+    let headers = csv_reader.headers()?.clone();
+    let mut columns: Vec<Vec<JsonValue>> = vec![Vec::new(); headers.len()];
+    for record in csv_reader.records() {
+        let record = record?;
+        for ((column, header), cell) in columns.iter_mut().zip(&headers).zip(record.iter()) {
+            column.push(csv_cell_to_json(cell, column_type(header)));
+        }
+    }
+    Ok(headers.iter().map(str::to_string).zip(columns).collect())
+}
+
+/// Read parquet rows of every row group. Timestamps become epoch seconds and binary values
+/// base64 strings, the representations accepted for pushed data.
+pub fn read_parquet(file: File) -> Result<FeatureColumns> {
+    let reader = SerializedFileReader::new(file)?;
+    let mut columns = FeatureColumns::default();
+    for (row_idx, row) in reader.into_iter().enumerate() {
+        for (name, field) in row?.get_column_iter() {
+            let column = columns.entry(name.clone()).or_default();
+            // Columns missing from earlier rows are padded with nulls
+            column.resize(row_idx, JsonValue::Null);
+            column.push(
+                parquet_field_to_json(field)
+                    .with_context(|| format!("Invalid value in column {}", name))?,
+            );
+        }
+    }
+    Ok(columns)
+}
+
+fn parquet_field_to_json(field: &Field) -> Result<JsonValue> {
+    let json = match field {
+        Field::Null => JsonValue::Null,
+        Field::Bool(v) => JsonValue::from(*v),
+        Field::Byte(v) => JsonValue::from(*v),
+        Field::Short(v) => JsonValue::from(*v),
+        Field::Int(v) => JsonValue::from(*v),
+        Field::Long(v) => JsonValue::from(*v),
+        Field::UByte(v) => JsonValue::from(*v),
+        Field::UShort(v) => JsonValue::from(*v),
+        Field::UInt(v) => JsonValue::from(*v),
+        Field::ULong(v) => JsonValue::from(*v),
+        Field::Float16(v) => JsonValue::from(v.to_f64()),
+        Field::Float(v) => JsonValue::from(*v as f64),
+        Field::Double(v) => JsonValue::from(*v),
+        Field::Str(v) => JsonValue::from(v.as_str()),
+        Field::Bytes(v) => JsonValue::from(BASE64.encode(v.data())),
+        Field::Date(days) => JsonValue::from(*days as i64 * 86_400),
+        Field::TimestampMillis(millis) => JsonValue::from(*millis as f64 / 1e3),
+        Field::TimestampMicros(micros) => JsonValue::from(*micros as f64 / 1e6),
+        Field::ListInternal(list) => JsonValue::Array(
+            list.elements()
+                .iter()
+                .map(parquet_field_to_json)
+                .collect::<Result<_>>()?,
+        ),
+        other => return Err(anyhow!("Unsupported parquet value {}", other)),
+    };
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn read_csv_columns() -> Result<()> {
+        let csv = "driver_id,conv_rate,event_timestamp\n\
+                   1001,0.5,2024-01-01T00:00:00Z\n\
+                   1002,,2024-01-02T00:00:00Z\n";
+        let columns = read_csv(csv.as_bytes(), |_| None)?;
+        assert_eq!(columns["driver_id"], [json!(1001), json!(1002)]);
+        assert_eq!(columns["conv_rate"], [json!(0.5), JsonValue::Null]);
+        assert_eq!(
+            columns["event_timestamp"],
+            [json!("2024-01-01T00:00:00Z"), json!("2024-01-02T00:00:00Z")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn read_csv_cells_as_column_types() -> Result<()> {
+        let csv = "zip,active,conv_rate\n007,true,1\n";
+        let columns = read_csv(csv.as_bytes(), |column| match column {
+            "zip" => Some(ValueTypeEnum::String),
+            "active" => Some(ValueTypeEnum::Bool),
+            "conv_rate" => Some(ValueTypeEnum::Float),
+            _ => None,
+        })?;
+        assert_eq!(columns["zip"], [json!("007")]);
+        assert_eq!(columns["active"], [json!(true)]);
+        assert_eq!(columns["conv_rate"], [json!(1.0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn convert_parquet_fields() -> Result<()> {
+        assert_eq!(
+            parquet_field_to_json(&Field::TimestampMicros(1_700_000_000_500_000))?,
+            json!(1_700_000_000.5)
+        );
+        assert_eq!(
+            parquet_field_to_json(&Field::Bytes(vec![1u8, 2, 3].into()))?,
+            json!("AQID")
+        );
+        assert_eq!(
+            parquet_field_to_json(&Field::Str("a".to_string()))?,
+            json!("a")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+//! Load shedding: requests arriving while too many requests are in flight, or while recent
+//! requests are slow, are rejected immediately instead of queueing on the runtime.
+
+use crate::config::LoadSheddingConfig;
+use crate::onlinestore::hedged::LatencyWindow;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Counter of shed requests, labelled with the endpoint.
+pub const SHED_REQUESTS_METRIC: &str = "feast_shed_requests_total";
+
+/// Admission control shared by all endpoints of a server.
+pub struct LoadShedder {
+    config: LoadSheddingConfig,
+    started: Instant,
+    in_flight: AtomicUsize,
+    latencies: Mutex<LatencyWindow>,
+    p99_micros: AtomicU64,
+    /// Milliseconds since `started` at which `p99_micros` was computed.
+    p99_computed_at_ms: AtomicU64,
+}
+
+/// Admitted request. Dropping it records the request latency.
+pub struct InFlightRequest<'a> {
+    shedder: &'a LoadShedder,
+    start: Instant,
+}
+
+impl Drop for InFlightRequest<'_> {
+    fn drop(&mut self) {
+        self.shedder.in_flight.fetch_sub(1, Ordering::AcqRel);
+        self.shedder.record_latency(self.start.elapsed());
+    }
+}
+
+impl LoadShedder {
+    pub fn new(config: LoadSheddingConfig) -> Self {
+        Self {
+            config,
+            started: Instant::now(),
+            in_flight: AtomicUsize::new(0),
+            latencies: Mutex::new(LatencyWindow::new()),
+            p99_micros: AtomicU64::new(0),
+            p99_computed_at_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Admit a request to `endpoint`, or count it as shed and return `None` when the server
+    /// is over a threshold.
+    pub fn try_acquire(&self, endpoint: &str) -> Option<InFlightRequest<'_>> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let over_in_flight = self
+            .config
+            .max_in_flight
+            .is_some_and(|max_in_flight| in_flight >= max_in_flight);
+        if over_in_flight || self.over_latency() {
+            self.in_flight.fetch_sub(1, Ordering::AcqRel);
+            tracing::debug!(
+                "Shedding {} request with {} requests in flight",
+                endpoint,
+                in_flight
+            );
+            metrics::counter!(SHED_REQUESTS_METRIC, "endpoint" => endpoint.to_string())
+                .increment(1);
+            return None;
+        }
+        Some(InFlightRequest {
+            shedder: self,
+            start: Instant::now(),
+        })
+    }
+
+    /// Number of requests currently admitted.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    fn over_latency(&self) -> bool {
+        let Some(max_latency_ms) = self.config.max_p99_latency_ms else {
+            return false;
+        };
+        let computed_at = Duration::from_millis(self.p99_computed_at_ms.load(Ordering::Acquire));
+        let age = self.started.elapsed().saturating_sub(computed_at);
+        // An outdated percentile is ignored, otherwise shedding every request would keep it
+        // from ever being recomputed
+        age < Duration::from_secs(self.config.latency_window_secs)
+            && self.p99_micros.load(Ordering::Acquire) > max_latency_ms * 1000
+    }
+
+    fn record_latency(&self, latency: Duration) {
+        if self.config.max_p99_latency_ms.is_none() {
+            return;
+        }
+        let recomputed = self
+            .latencies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record(latency.as_micros() as u64, 99.0);
+        if let Some(micros) = recomputed {
+            self.p99_micros.store(micros, Ordering::Release);
+            self.p99_computed_at_ms
+                .store(self.started.elapsed().as_millis() as u64, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sheds_requests_over_in_flight_limit() {
+        let shedder = LoadShedder::new(LoadSheddingConfig {
+            max_in_flight: Some(2),
+            ..Default::default()
+        });
+        let first = shedder.try_acquire("test");
+        let second = shedder.try_acquire("test");
+        assert!(first.is_some() && second.is_some());
+        assert!(shedder.try_acquire("test").is_none());
+        assert_eq!(shedder.in_flight(), 2);
+
+        drop(first);
+        assert!(shedder.try_acquire("test").is_some());
+        assert_eq!(shedder.in_flight(), 1);
+    }
+
+    #[test]
+    fn sheds_requests_while_latency_percentile_is_high() {
+        let shedder = LoadShedder::new(LoadSheddingConfig {
+            max_p99_latency_ms: Some(50),
+            latency_window_secs: 60,
+            ..Default::default()
+        });
+        for _ in 0..128 {
+            shedder.record_latency(Duration::from_millis(100));
+        }
+        assert!(shedder.try_acquire("test").is_none());
+
+        let stale = LoadShedder::new(LoadSheddingConfig {
+            max_p99_latency_ms: Some(50),
+            latency_window_secs: 0,
+            ..Default::default()
+        });
+        for _ in 0..128 {
+            stale.record_latency(Duration::from_millis(100));
+        }
+        assert!(stale.try_acquire("test").is_some());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::config::EntityKeyCoercion;
+use crate::error::FeastCoreError;
+use crate::feast::core::DataSource as DataSourceProto;
 use crate::feast::core::Entity as EntityProto;
 use crate::feast::core::FeatureService as FeatureServiceProto;
 use crate::feast::core::FeatureSpecV2 as FeatureSpecV2Proto;
@@ -5,9 +8,17 @@ use crate::feast::core::FeatureView as FeatureViewProto;
 use crate::feast::core::FeatureViewProjection as FeatureViewProjectionProto;
 use crate::feast::core::OnDemandFeatureView as OnDemandFeatureViewProto;
 use crate::feast::core::Registry as RegistryProto;
+use crate::feast::core::StreamFeatureView as StreamFeatureViewProto;
+use crate::feast::core::data_source::SourceType;
+use crate::feast::serving::FeatureList as FeatureListProto;
+use crate::feast::serving::FieldStatus as FieldStatusProto;
+use crate::feast::serving::GetOnlineFeaturesResponse as GetOnlineFeaturesResponseProto;
+use crate::feast::serving::GetOnlineFeaturesResponseMetadata as GetOnlineFeaturesResponseMetadataProto;
+use crate::feast::serving::get_online_features_response::FeatureVector as FeatureVectorProto;
 use crate::feast::types::value::Val;
 use crate::feast::types::value_type::Enum as ValueTypeEnum;
 use crate::feast::types::{EntityKey, Value, value_type};
+use crate::feature_store::RequestDiagnostics;
 use crate::intern::rodeo;
 use crate::util::prost_duration_to_duration;
 use crate::util::prost_timestamp_to_datetime;
@@ -24,6 +35,9 @@ use std::fmt::Formatter;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
+/// Map iterating in insertion order, for request items whose order is kept in responses.
+pub type OrderedMap<K, V> = indexmap::IndexMap<K, V, rustc_hash::FxBuildHasher>;
+
 pub(crate) const DUMMY_ENTITY_ID: &str = "__dummy_id";
 pub(crate) const DUMMY_ENTITY_NAME: &str = "__dummy";
 pub(crate) const DUMMY_ENTITY_VAL: &str = "";
@@ -37,6 +51,36 @@ pub enum EntityIdValue {
 }
 
 impl EntityIdValue {
+    /// Value converted to the representation of an entity column of `value_type`, or `None`
+    /// when `coercion` does not allow it. Only integer and string columns are coerced; other
+    /// types are returned unchanged and checked by [`Self::to_proto_value`].
+    pub fn coerce(
+        &self,
+        value_type: value_type::Enum,
+        coercion: EntityKeyCoercion,
+    ) -> Option<EntityIdValue> {
+        let lenient = coercion == EntityKeyCoercion::Lenient;
+        match (self, value_type) {
+            (EntityIdValue::Int(i), value_type::Enum::Int32) => {
+                i32::try_from(*i).ok().map(|_| self.clone())
+            }
+            (EntityIdValue::String(s), value_type::Enum::Int32) if lenient => s
+                .trim()
+                .parse::<i32>()
+                .ok()
+                .map(|i| EntityIdValue::Int(i.into())),
+            (EntityIdValue::String(s), value_type::Enum::Int64) if lenient => {
+                s.trim().parse::<i64>().ok().map(EntityIdValue::Int)
+            }
+            (EntityIdValue::Int(i), value_type::Enum::String) if lenient => {
+                Some(EntityIdValue::String(i.to_string()))
+            }
+            (EntityIdValue::String(_), value_type::Enum::Int32 | value_type::Enum::Int64)
+            | (EntityIdValue::Int(_), value_type::Enum::String) => None,
+            _ => Some(self.clone()),
+        }
+    }
+
     pub fn to_proto_value(&self, output_type: value_type::Enum) -> Result<Value> {
         match self {
             EntityIdValue::String(s) => Ok(Value {
@@ -44,7 +88,9 @@ impl EntityIdValue {
             }),
             EntityIdValue::Int(i) => match output_type {
                 value_type::Enum::Int32 => Ok(Value {
-                    val: Some(Val::Int32Val(*i as i32)),
+                    val: Some(Val::Int32Val(i32::try_from(*i).map_err(|_| {
+                        anyhow!("Entity value {} is out of range for type INT32", i)
+                    })?)),
                 }),
                 value_type::Enum::Int64 => Ok(Value {
                     val: Some(Val::Int64Val(*i)),
@@ -58,12 +104,193 @@ impl EntityIdValue {
     }
 }
 
+/// Request of online features. JSON requests give entities either column-wise, as
+/// `"entities": {"driver_id": [1001, 1002]}`, or row-wise, as
+/// `"entity_rows": [{"driver_id": 1001}, {"driver_id": 1002}]`; rows are converted to
+/// columns when the request is parsed.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "GetOnlineFeaturesRequestBody")]
 pub struct GetOnlineFeaturesRequest {
-    pub entities: HashMap<String, Vec<EntityIdValue>>,
+    pub entities: OrderedMap<String, Vec<EntityIdValue>>,
     pub feature_service: Option<String>,
     pub features: Option<Vec<String>>,
     pub full_feature_names: Option<bool>,
+    /// Echo the requested entity columns in the response, `true` by default.
+    pub include_entity_values: Option<bool>,
+    /// Return a status per value, `true` by default.
+    pub include_statuses: Option<bool>,
+    /// Return an event timestamp per value, `true` by default.
+    pub include_timestamps: Option<bool>,
+    /// Return the time each value was written to the online store, `false` by default.
+    pub include_created_timestamps: Option<bool>,
+    /// Resolve the requested features against this registry version of the server, as
+    /// returned with earlier responses, instead of the latest one.
+    pub registry_version: Option<u64>,
+    /// Return the timings of the request with the response, when the server allows it.
+    pub debug: Option<bool>,
+    /// Read the online store of this project instead of the configured one. The project
+    /// must be allowed by the server config.
+    pub project: Option<String>,
+}
+
+impl GetOnlineFeaturesRequest {
+    /// Response parts the request asks for.
+    pub fn response_fields(&self) -> ResponseFields {
+        ResponseFields {
+            entity_values: self.include_entity_values.unwrap_or(true),
+            statuses: self.include_statuses.unwrap_or(true),
+            timestamps: self.include_timestamps.unwrap_or(true),
+            created_timestamps: self.include_created_timestamps.unwrap_or(false),
+        }
+    }
+}
+
+/// Parts of an online features response which clients may leave out to reduce its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseFields {
+    pub entity_values: bool,
+    pub statuses: bool,
+    pub timestamps: bool,
+    pub created_timestamps: bool,
+}
+
+/// JSON shape of [`GetOnlineFeaturesRequest`] accepting both entity layouts.
+#[derive(Deserialize)]
+struct GetOnlineFeaturesRequestBody {
+    entities: Option<OrderedMap<String, Vec<EntityIdValue>>>,
+    entity_rows: Option<Vec<OrderedMap<String, EntityIdValue>>>,
+    feature_service: Option<String>,
+    features: Option<Vec<String>>,
+    full_feature_names: Option<bool>,
+    include_entity_values: Option<bool>,
+    include_statuses: Option<bool>,
+    include_timestamps: Option<bool>,
+    include_created_timestamps: Option<bool>,
+    registry_version: Option<u64>,
+    debug: Option<bool>,
+    project: Option<String>,
+}
+
+impl TryFrom<GetOnlineFeaturesRequestBody> for GetOnlineFeaturesRequest {
+    type Error = Error;
+
+    fn try_from(body: GetOnlineFeaturesRequestBody) -> Result<Self> {
+        let entities = match (body.entities, body.entity_rows) {
+            (Some(entities), None) => entities,
+            (None, Some(rows)) => entity_rows_to_columns(rows)?,
+            (Some(_), Some(_)) => {
+                return Err(anyhow!(
+                    "Only one of entities and entity_rows can be set in a request"
+                ));
+            }
+            (None, None) => return Err(anyhow!("missing field `entities`")),
+        };
+        Ok(Self {
+            entities,
+            feature_service: body.feature_service,
+            features: body.features,
+            full_feature_names: body.full_feature_names,
+            include_entity_values: body.include_entity_values,
+            include_statuses: body.include_statuses,
+            include_timestamps: body.include_timestamps,
+            include_created_timestamps: body.include_created_timestamps,
+            registry_version: body.registry_version,
+            debug: body.debug,
+            project: body.project,
+        })
+    }
+}
+
+/// Columns of entity rows, in the key order of the first row. Every row must have the
+/// same keys.
+fn entity_rows_to_columns(
+    rows: Vec<OrderedMap<String, EntityIdValue>>,
+) -> Result<OrderedMap<String, Vec<EntityIdValue>>> {
+    let mut columns: OrderedMap<String, Vec<EntityIdValue>> = OrderedMap::default();
+    if let Some(first) = rows.first() {
+        for name in first.keys() {
+            columns.insert(name.clone(), Vec::with_capacity(rows.len()));
+        }
+    }
+    for (idx, row) in rows.into_iter().enumerate() {
+        if row.len() != columns.len() || row.keys().any(|name| !columns.contains_key(name)) {
+            return Err(anyhow!(
+                "Entity row {} has keys [{}] instead of [{}]",
+                idx,
+                row.keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                columns
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        for (name, value) in row {
+            columns[&name].push(value);
+        }
+    }
+    Ok(columns)
+}
+
+/// Request for the latest event timestamps of a feature view. `entities` uses the same
+/// columnar layout as [`GetOnlineFeaturesRequest`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeatureFreshnessRequest {
+    pub feature_view: String,
+    #[serde(default)]
+    pub entities: HashMap<String, Vec<EntityIdValue>>,
+}
+
+/// Latest event timestamp over all features of a view, one entry per requested entity row.
+/// `None` means no values are stored for the entity.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureFreshnessResponse {
+    pub feature_view: String,
+    pub event_timestamps: Vec<Option<DateTime<Utc>>>,
+}
+
+/// Entity keys whose stored features are purged from the online store, given column-wise by
+/// join key. Every feature view keyed by a subset of the join keys is purged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeleteEntityKeysRequest {
+    pub entities: HashMap<String, Vec<EntityIdValue>>,
+}
+
+/// Number of entity keys which had stored values, per purged feature view.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeleteEntityKeysResponse {
+    pub deleted: Vec<DeletedEntityKeys>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeletedEntityKeys {
+    pub feature_view: String,
+    pub entity_keys: u64,
+}
+
+/// Destination of pushed data, as in the Feast `/push` API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushMode {
+    #[default]
+    Online,
+    Offline,
+    OnlineAndOffline,
+}
+
+/// Request of the Feast `/push` API. `df` holds the pushed dataframe in columnar form,
+/// mapping column names to their values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PushRequest {
+    pub push_source_name: String,
+    pub df: HashMap<String, Vec<serde_json::Value>>,
+    #[serde(default)]
+    pub to: PushMode,
+    #[serde(default)]
+    pub allow_registry_cache: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -79,6 +306,8 @@ pub enum FeatureStatus {
     NullValue,
     NotFound,
     OutsideMaxAge,
+    /// No value was found and the configured default of the feature was answered.
+    DefaultValue,
 }
 
 #[derive(PartialEq, Clone)]
@@ -102,12 +331,16 @@ impl Serialize for ValueWrapper {
     where
         S: Serializer,
     {
+        // Null values and floats JSON has no number for are written as `null`, as the Python
+        // feature server does; `NanPolicy::Error` rejects such responses before encoding.
         match &self.0.val {
-            None => serializer.serialize_none(),
+            None | Some(Val::NullVal(_)) => serializer.serialize_none(),
             Some(v) => match v {
                 Val::Int32Val(i) => serializer.serialize_i32(*i),
                 Val::Int64Val(i) => serializer.serialize_i64(*i),
+                Val::FloatVal(f) if !f.is_finite() => serializer.serialize_none(),
                 Val::FloatVal(f) => serializer.serialize_f32(*f),
+                Val::DoubleVal(d) if !d.is_finite() => serializer.serialize_none(),
                 Val::DoubleVal(d) => serializer.serialize_f64(*d),
                 Val::StringVal(s) => serializer.serialize_str(s),
                 Val::BytesVal(b) => serializer.serialize_bytes(b),
@@ -122,23 +355,168 @@ impl Serialize for ValueWrapper {
     }
 }
 
+/// Values of JSON responses. JSON keeps no value types, so integers read as
+/// `INT64`, other numbers as `DOUBLE` and arrays of bytes as `BYTES`.
+impl<'de> Deserialize<'de> for ValueWrapper {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let val = match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Null => None,
+            serde_json::Value::Bool(b) => Some(Val::BoolVal(b)),
+            serde_json::Value::Number(n) => Some(match n.as_i64() {
+                Some(i) => Val::Int64Val(i),
+                None => Val::DoubleVal(n.as_f64().unwrap_or(f64::NAN)),
+            }),
+            serde_json::Value::String(s) => Some(Val::StringVal(s)),
+            serde_json::Value::Array(items) => Some(Val::BytesVal(
+                items
+                    .iter()
+                    .map(|item| item.as_u64().and_then(|b| u8::try_from(b).ok()))
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or_else(|| {
+                        <D::Error as serde::de::Error>::custom("unsupported feature value: array")
+                    })?,
+            )),
+            serde_json::Value::Object(_) => {
+                return Err(<D::Error as serde::de::Error>::custom(
+                    "unsupported feature value: object",
+                ));
+            }
+        };
+        Ok(Self(Value { val }))
+    }
+}
+
+impl ValueWrapper {
+    /// NaN or infinite float value, which JSON cannot represent.
+    pub fn is_non_finite(&self) -> bool {
+        match self.0.val {
+            Some(Val::FloatVal(f)) => !f.is_finite(),
+            Some(Val::DoubleVal(d)) => !d.is_finite(),
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Debug for ValueWrapper {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         self.0.fmt(f)
     }
 }
 
-#[derive(Debug, Default, PartialEq, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct FeatureResults {
     pub values: Vec<ValueWrapper>,
+    /// Empty when the request left out statuses.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub statuses: Vec<FeatureStatus>,
+    /// Empty when the request left out timestamps.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub event_timestamps: Vec<DateTime<Utc>>,
+    /// Time each value was written to the online store, `None` when the store does not
+    /// keep it. Empty unless the request asked for created timestamps.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub created_timestamps: Vec<Option<DateTime<Utc>>>,
 }
 
-#[derive(Debug, Default, PartialEq, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct GetOnlineFeatureResponse {
     pub metadata: GetOnlineFeatureResponseMetadata,
     pub results: Vec<FeatureResults>,
+    /// Problems which did not fail the request, e.g. feature views which could not be read
+    /// in partial results mode.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Hash of the projections of the requested feature service, which changes whenever
+    /// the definition of the service does. Sent as transport metadata rather than in the
+    /// body.
+    #[serde(skip)]
+    pub feature_service_version: Option<String>,
+    /// Registry version the features were resolved against, which later requests can pin.
+    /// Sent as transport metadata rather than in the body.
+    #[serde(skip)]
+    pub registry_version: Option<u64>,
+    /// Timings of the request, when it asked for them with `debug`.
+    #[serde(skip_serializing_if = "Option::is_none", skip_deserializing)]
+    pub debug: Option<RequestDiagnostics>,
+}
+
+impl GetOnlineFeatureResponse {
+    /// Fail when a feature value is NaN or infinite, for [`NanPolicy::Error`].
+    ///
+    /// [`NanPolicy::Error`]: crate::config::NanPolicy::Error
+    pub fn check_finite(&self) -> Result<()> {
+        for (feature_idx, result) in self.results.iter().enumerate() {
+            if let Some(row) = result.values.iter().position(ValueWrapper::is_non_finite) {
+                let feature_name = self
+                    .metadata
+                    .feature_names
+                    .get(feature_idx)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                return Err(anyhow!(
+                    "Feature {} has a NaN or infinite value in row {}, which JSON cannot represent",
+                    feature_name,
+                    row
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<FeatureStatus> for FieldStatusProto {
+    fn from(status: FeatureStatus) -> Self {
+        match status {
+            FeatureStatus::Invalid => FieldStatusProto::Invalid,
+            FeatureStatus::Present => FieldStatusProto::Present,
+            FeatureStatus::NullValue => FieldStatusProto::NullValue,
+            FeatureStatus::NotFound => FieldStatusProto::NotFound,
+            FeatureStatus::OutsideMaxAge => FieldStatusProto::OutsideMaxAge,
+            FeatureStatus::DefaultValue => FieldStatusProto::DefaultValue,
+        }
+    }
+}
+
+/// Feast serving proto of the response, as returned by the Feast gRPC feature server.
+impl From<GetOnlineFeatureResponse> for GetOnlineFeaturesResponseProto {
+    fn from(response: GetOnlineFeatureResponse) -> Self {
+        let results = response
+            .results
+            .into_iter()
+            .map(|result| FeatureVectorProto {
+                values: result
+                    .values
+                    .into_iter()
+                    .map(|ValueWrapper(value)| value)
+                    .collect(),
+                statuses: result
+                    .statuses
+                    .into_iter()
+                    .map(|status| FieldStatusProto::from(status) as i32)
+                    .collect(),
+                event_timestamps: result
+                    .event_timestamps
+                    .into_iter()
+                    .map(|ts| prost_types::Timestamp {
+                        seconds: ts.timestamp(),
+                        nanos: ts.timestamp_subsec_nanos() as i32,
+                    })
+                    .collect(),
+            })
+            .collect();
+        GetOnlineFeaturesResponseProto {
+            metadata: Some(GetOnlineFeaturesResponseMetadataProto {
+                feature_names: Some(FeatureListProto {
+                    val: response.metadata.feature_names,
+                }),
+            }),
+            results,
+            status: true,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -190,6 +568,8 @@ pub struct FeatureProjection {
     pub feature_view_name_alias: Option<Spur>,
     pub features: Vec<Field>,
     pub join_key_map: HashMap<Spur, Spur>,
+    /// TTL replacing the one of the view when computing statuses of the projection.
+    pub ttl: Option<Duration>,
 }
 
 #[derive(Debug, Clone)]
@@ -197,25 +577,38 @@ pub struct ResolvedFeatureProjection {
     pub feature_view: Arc<FeatureView>,
 }
 
+/// Push source feeding a feature view, with the columns of pushed data holding timestamps.
+#[derive(Debug, Clone)]
+pub struct PushSource {
+    pub name: Spur,
+    pub timestamp_field: String,
+    pub created_timestamp_column: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct FeatureView {
     pub name: Spur,
+    /// Alias given to the view by the feature service projection it was resolved from.
+    pub name_alias: Option<Spur>,
     pub features: Arc<Vec<Field>>,
     pub ttl: Duration,
     pub entity_names: Vec<Spur>,
     pub entity_columns: Vec<Field>,
     pub join_key_map: Option<HashMap<Spur, Spur>>,
+    pub push_source: Option<PushSource>,
 }
 
 impl Default for FeatureView {
     fn default() -> Self {
         Self {
             name: crate::intern::rodeo_ref().get_or_intern(""),
+            name_alias: None,
             features: Arc::new(Vec::new()),
             ttl: Duration::zero(),
             entity_names: Vec::new(),
             entity_columns: Vec::new(),
             join_key_map: None,
+            push_source: None,
         }
     }
 }
@@ -231,11 +624,13 @@ impl FeatureView {
     ) -> Self {
         Self {
             name: crate::intern::rodeo_ref().get_or_intern(name.as_ref()),
+            name_alias: None,
             features: Arc::new(features),
             ttl,
             entity_names,
             entity_columns,
             join_key_map,
+            push_source: None,
         }
     }
 }
@@ -295,8 +690,12 @@ impl FeatureRegistry {
             for projection in &feature_service.projections {
                 if let Some(view) = self.feature_views.get(&projection.feature_view_name) {
                     let mut resolved_feature_view = view.clone();
+                    resolved_feature_view.name_alias = projection.feature_view_name_alias;
                     resolved_feature_view.join_key_map = Some(projection.join_key_map.clone());
                     resolved_feature_view.features = Arc::new(projection.features.clone());
+                    if let Some(ttl) = projection.ttl {
+                        resolved_feature_view.ttl = ttl;
+                    }
                     let feature_view = Arc::new(resolved_feature_view);
                     resolved_projections.push(ResolvedFeatureProjection {
                         feature_view: feature_view.clone(),
@@ -314,7 +713,9 @@ impl FeatureRegistry {
 
 #[derive(Debug, Clone)]
 pub enum RequestedFeatures {
-    FeatureNames(Vec<Spur>),
+    /// `<view>:<feature>` references, kept as received so that they are only interned
+    /// once split.
+    FeatureNames(Vec<String>),
     FeatureService(Spur),
 }
 
@@ -460,10 +861,9 @@ impl Feature {
 
     pub fn full_name(&self) -> String {
         let rodeo = crate::intern::rodeo_ref();
-        format!(
-            "{}__{}",
+        crate::feature_store::full_feature_name(
             rodeo.resolve(&self.feature_view_name),
-            rodeo.resolve(&self.feature_name)
+            rodeo.resolve(&self.feature_name),
         )
     }
 }
@@ -492,18 +892,17 @@ impl TryFrom<&str> for Feature {
     type Error = Error;
 
     fn try_from(s: &str) -> Result<Self> {
-        let rodeo = crate::intern::rodeo_ref();
         if s.is_empty() {
             return Err(anyhow!("Empty feature string"));
         }
         if let Some(idx) = s.find(':') {
             let (fv_name, f_name) = s.split_at(idx);
             Ok(Self::new(
-                rodeo.get_or_intern(fv_name),
-                rodeo.get_or_intern(&f_name[1..]),
+                crate::intern::request_key(fv_name)?,
+                crate::intern::request_key(&f_name[1..])?,
             ))
         } else {
-            Ok(Self::entity_feature(rodeo.get_or_intern(s)))
+            Ok(Self::entity_feature(crate::intern::request_key(s)?))
         }
     }
 }
@@ -518,21 +917,30 @@ impl TryFrom<&Spur> for Feature {
     }
 }
 
-impl From<&GetOnlineFeaturesRequest> for RequestedFeatures {
-    fn from(get_online_feature_request: &GetOnlineFeaturesRequest) -> Self {
-        let rodeo = crate::intern::rodeo_ref();
-        if let Some(feature_service) = &get_online_feature_request.feature_service {
-            RequestedFeatures::FeatureService(rodeo.get_or_intern(feature_service))
-        } else if let Some(features) = &get_online_feature_request.features {
-            RequestedFeatures::FeatureNames(
-                features
-                    .iter()
-                    .map(|feature| rodeo.get_or_intern(feature))
-                    .collect(),
-            )
-        } else {
-            RequestedFeatures::FeatureNames(Vec::new())
-        }
+impl TryFrom<&GetOnlineFeaturesRequest> for RequestedFeatures {
+    type Error = Error;
+
+    /// Requests name a feature service or list features, as in the Python SDK. An empty
+    /// feature list next to a feature service is accepted.
+    fn try_from(get_online_feature_request: &GetOnlineFeaturesRequest) -> Result<Self> {
+        Ok(
+            if let Some(feature_service) = &get_online_feature_request.feature_service {
+                if get_online_feature_request
+                    .features
+                    .as_ref()
+                    .is_some_and(|features| !features.is_empty())
+                {
+                    return Err(
+                        FeastCoreError::features_with_feature_service(feature_service).into(),
+                    );
+                }
+                RequestedFeatures::FeatureService(crate::intern::request_key(feature_service)?)
+            } else if let Some(features) = &get_online_feature_request.features {
+                RequestedFeatures::FeatureNames(features.clone())
+            } else {
+                RequestedFeatures::FeatureNames(Vec::new())
+            },
+        )
     }
 }
 
@@ -587,20 +995,35 @@ impl TryFrom<FeatureViewProjectionProto> for FeatureProjection {
             .collect();
         Ok(FeatureProjection {
             feature_view_name: rodeo.get_or_intern(projection_proto.feature_view_name),
-            feature_view_name_alias: Some(
-                rodeo.get_or_intern(projection_proto.feature_view_name_alias),
-            ),
+            feature_view_name_alias: (!projection_proto.feature_view_name_alias.is_empty())
+                .then(|| rodeo.get_or_intern(projection_proto.feature_view_name_alias)),
             features: features?,
             join_key_map: projection_proto
                 .join_key_map
                 .into_iter()
                 .map(|(k, v)| (rodeo.get_or_intern(k), rodeo.get_or_intern(v)))
                 .collect(),
+            ttl: None,
         })
     }
 }
 
 impl FeatureView {
+    /// Name of the view in full feature names: the projection alias if any.
+    pub fn name_to_use(&self) -> Spur {
+        self.name_alias.unwrap_or(self.name)
+    }
+
+    /// Registry type of an entity or feature column of the view.
+    pub fn column_type(&self, column: &str) -> Option<ValueTypeEnum> {
+        let name = crate::intern::rodeo_ref().get(column)?;
+        self.entity_columns
+            .iter()
+            .chain(self.features.iter())
+            .find(|field| field.name == name)
+            .map(|field| field.value_type)
+    }
+
     pub fn is_entity_less(&self) -> bool {
         let rodeo = crate::intern::rodeo_ref();
         self.entity_names.len() == 1
@@ -611,33 +1034,88 @@ impl FeatureView {
 impl TryFrom<FeatureViewProto> for FeatureView {
     type Error = Error;
     fn try_from(feature_view_proto: FeatureViewProto) -> Result<Self> {
-        let rodeo = crate::intern::rodeo_ref();
         let spec = feature_view_proto
             .spec
             .ok_or(anyhow!("Missing feature view value"))?;
-        let features: Result<Vec<Field>> = spec.features.into_iter().map(Field::try_from).collect();
+        FeatureView::from_spec_parts(
+            spec.name,
+            spec.features,
+            spec.ttl,
+            spec.entities,
+            spec.entity_columns,
+            spec.stream_source,
+        )
+    }
+}
+
+impl FeatureView {
+    /// Build a view from the spec fields shared by regular and stream feature views.
+    fn from_spec_parts(
+        name: String,
+        features: Vec<FeatureSpecV2Proto>,
+        ttl: Option<prost_types::Duration>,
+        entities: Vec<String>,
+        entity_columns: Vec<FeatureSpecV2Proto>,
+        stream_source: Option<DataSourceProto>,
+    ) -> Result<Self> {
+        let rodeo = crate::intern::rodeo_ref();
+        let features: Result<Vec<Field>> = features.into_iter().map(Field::try_from).collect();
         Ok(FeatureView {
-            name: rodeo.get_or_intern(spec.name),
+            name: rodeo.get_or_intern(name),
+            name_alias: None,
             features: Arc::from(features?),
-            ttl: spec
-                .ttl
+            ttl: ttl
                 .as_ref()
                 .map(prost_duration_to_duration)
                 .unwrap_or_else(Duration::zero),
-            entity_names: spec
-                .entities
+            entity_names: entities
                 .into_iter()
                 .map(|entity| rodeo.get_or_intern(entity))
                 .collect(),
-            entity_columns: spec
-                .entity_columns
+            entity_columns: entity_columns
                 .into_iter()
-                .map(|col| Field {
-                    name: rodeo.get_or_intern(col.name),
-                    value_type: ValueTypeEnum::try_from(col.value_type).unwrap(),
-                })
-                .collect(),
+                .map(Field::try_from)
+                .collect::<Result<Vec<_>>>()?,
             join_key_map: None,
+            push_source: stream_source.and_then(PushSource::from_data_source),
+        })
+    }
+}
+
+/// Stream feature view read from the registry. Stream views are materialized to the online
+/// store like regular views, so they are served as plain [`FeatureView`]s.
+#[derive(Debug, Clone)]
+pub(crate) struct StreamFeatureView(pub(crate) FeatureView);
+
+impl TryFrom<StreamFeatureViewProto> for StreamFeatureView {
+    type Error = Error;
+    fn try_from(stream_feature_view_proto: StreamFeatureViewProto) -> Result<Self> {
+        let spec = stream_feature_view_proto
+            .spec
+            .ok_or(anyhow!("Missing stream feature view specs"))?;
+        FeatureView::from_spec_parts(
+            spec.name,
+            spec.features,
+            spec.ttl,
+            spec.entities,
+            spec.entity_columns,
+            spec.stream_source,
+        )
+        .map(StreamFeatureView)
+    }
+}
+
+impl PushSource {
+    fn from_data_source(data_source: DataSourceProto) -> Option<Self> {
+        if data_source.r#type != SourceType::PushSource as i32 {
+            return None;
+        }
+        let rodeo = crate::intern::rodeo_ref();
+        Some(PushSource {
+            name: rodeo.get_or_intern(data_source.name),
+            timestamp_field: data_source.timestamp_field,
+            created_timestamp_column: Some(data_source.created_timestamp_column)
+                .filter(|column| !column.is_empty()),
         })
     }
 }
@@ -656,6 +1134,10 @@ impl TryFrom<OnDemandFeatureViewProto> for OnDemandFeatureView {
     }
 }
 
+/// Prefix of feature service tags overriding the TTL of a projected view, named by its name
+/// or alias, as in `ttl:driver_hourly_stats: "3600"` with the TTL in seconds.
+pub const TTL_TAG_PREFIX: &str = "ttl:";
+
 impl TryFrom<FeatureServiceProto> for FeatureService {
     type Error = Error;
     fn try_from(feature_service_proto: FeatureServiceProto) -> Result<Self> {
@@ -666,11 +1148,35 @@ impl TryFrom<FeatureServiceProto> for FeatureService {
         let metadata = feature_service_proto
             .meta
             .ok_or(anyhow!("Missing feature service metadata"))?;
-        let projections: Result<Vec<FeatureProjection>> = spec
+        let mut projections = spec
             .features
             .into_iter()
             .map(FeatureProjection::try_from)
-            .collect();
+            .collect::<Result<Vec<_>>>()?;
+        for (key, value) in &spec.tags {
+            let Some(view) = key.strip_prefix(TTL_TAG_PREFIX) else {
+                continue;
+            };
+            let seconds: u64 = value.parse().with_context(|| {
+                format!("Invalid TTL tag {} of feature service {}", key, spec.name)
+            })?;
+            let projection = projections
+                .iter_mut()
+                .find(|projection| {
+                    rodeo.resolve(&projection.feature_view_name) == view
+                        || projection
+                            .feature_view_name_alias
+                            .is_some_and(|alias| rodeo.resolve(&alias) == view)
+                })
+                .ok_or_else(|| {
+                    anyhow!(
+                        "TTL tag {} of feature service {} names no projected feature view",
+                        key,
+                        spec.name
+                    )
+                })?;
+            projection.ttl = Some(Duration::seconds(seconds as i64));
+        }
         Ok(FeatureService {
             name: rodeo.get_or_intern(spec.name),
             project: spec.project,
@@ -680,7 +1186,7 @@ impl TryFrom<FeatureServiceProto> for FeatureService {
             last_updated_timestamp: metadata
                 .last_updated_timestamp
                 .map(|ts| prost_timestamp_to_datetime(&ts)),
-            projections: projections?,
+            projections,
             resolved_projections: Vec::new(),
             missing_feature_views: Vec::new(),
             logging_config: None,
@@ -690,45 +1196,65 @@ impl TryFrom<FeatureServiceProto> for FeatureService {
 
 impl TryFrom<RegistryProto> for FeatureRegistry {
     type Error = Error;
+    /// Maps are sized from the object counts of the proto up front, as collecting results
+    /// into them cannot tell the number of objects.
     fn try_from(registry_proto: RegistryProto) -> Result<Self> {
-        let rodeo = crate::intern::rodeo_ref();
-        let entities: Result<HashMap<Spur, Entity>> = registry_proto
-            .entities
-            .into_iter()
-            .map(|e| {
-                let entity = Entity::try_from(e)?;
-                Ok((entity.name, entity))
-            })
-            .collect();
-        let feature_views: Result<HashMap<Spur, FeatureView>> = registry_proto
-            .feature_views
-            .into_iter()
-            .map(|fv| {
-                let feature_view = FeatureView::try_from(fv)?;
-                Ok((feature_view.name, feature_view))
-            })
-            .collect();
-        let ondemand_feature_views: Result<HashMap<Spur, OnDemandFeatureView>> = registry_proto
-            .on_demand_feature_views
-            .into_iter()
-            .map(|odfv| {
-                let on_demand_feature_view = OnDemandFeatureView::try_from(odfv)?;
-                Ok((on_demand_feature_view.name, on_demand_feature_view))
-            })
-            .collect();
-        let feature_services: Result<HashMap<Spur, FeatureService>> = registry_proto
-            .feature_services
-            .into_iter()
-            .map(|fs| {
-                let feature_service = FeatureService::try_from(fs)?;
-                Ok((feature_service.name, feature_service))
-            })
-            .collect();
+        let mut entities: HashMap<Spur, Entity> =
+            HashMap::with_capacity_and_hasher(registry_proto.entities.len(), Default::default());
+        for e in registry_proto.entities {
+            let entity = Entity::try_from(e)?;
+            entities.insert(entity.name, entity);
+        }
+        let mut feature_views: HashMap<Spur, FeatureView> = HashMap::with_capacity_and_hasher(
+            registry_proto.feature_views.len() + registry_proto.stream_feature_views.len(),
+            Default::default(),
+        );
+        for fv in registry_proto.feature_views {
+            let name = fv.spec.as_ref().map(|spec| spec.name.clone());
+            let feature_view = FeatureView::try_from(fv).with_context(|| {
+                format!("Failed to load feature view '{}'", name.unwrap_or_default())
+            })?;
+            feature_views.insert(feature_view.name, feature_view);
+        }
+        for sfv in registry_proto.stream_feature_views {
+            let name = sfv.spec.as_ref().map(|spec| spec.name.clone());
+            let StreamFeatureView(feature_view) =
+                StreamFeatureView::try_from(sfv).with_context(|| {
+                    format!(
+                        "Failed to load stream feature view '{}'",
+                        name.unwrap_or_default()
+                    )
+                })?;
+            feature_views.insert(feature_view.name, feature_view);
+        }
+        let mut on_demand_feature_views: HashMap<Spur, OnDemandFeatureView> =
+            HashMap::with_capacity_and_hasher(
+                registry_proto.on_demand_feature_views.len(),
+                Default::default(),
+            );
+        for odfv in registry_proto.on_demand_feature_views {
+            let on_demand_feature_view = OnDemandFeatureView::try_from(odfv)?;
+            on_demand_feature_views.insert(on_demand_feature_view.name, on_demand_feature_view);
+        }
+        let mut feature_services: HashMap<Spur, FeatureService> = HashMap::with_capacity_and_hasher(
+            registry_proto.feature_services.len(),
+            Default::default(),
+        );
+        for fs in registry_proto.feature_services {
+            let name = fs.spec.as_ref().map(|spec| spec.name.clone());
+            let feature_service = FeatureService::try_from(fs).with_context(|| {
+                format!(
+                    "Failed to load feature service '{}'",
+                    name.unwrap_or_default()
+                )
+            })?;
+            feature_services.insert(feature_service.name, feature_service);
+        }
         let mut registry = FeatureRegistry {
-            entities: entities?,
-            feature_views: feature_views?,
-            on_demand_feature_views: ondemand_feature_views?,
-            feature_services: feature_services?,
+            entities,
+            feature_views,
+            on_demand_feature_views,
+            feature_services,
         };
         registry.resolve_feature_services();
         Ok(registry)
@@ -752,3 +1278,148 @@ try_from_vec_u8!(Entity, EntityProto);
 try_from_vec_u8!(FeatureService, FeatureServiceProto);
 try_from_vec_u8!(OnDemandFeatureView, OnDemandFeatureViewProto);
 try_from_vec_u8!(FeatureView, FeatureViewProto);
+try_from_vec_u8!(StreamFeatureView, StreamFeatureViewProto);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_entity_rows() -> Result<()> {
+        let columnar: GetOnlineFeaturesRequest = serde_json::from_str(
+            r#"{"entities": {"driver_id": [1001, 1002], "city": ["sf", "nyc"]},
+                "features": ["driver_hourly_stats:conv_rate"]}"#,
+        )?;
+        let rows: GetOnlineFeaturesRequest = serde_json::from_str(
+            r#"{"entity_rows": [{"driver_id": 1001, "city": "sf"}, {"city": "nyc", "driver_id": 1002}],
+                "features": ["driver_hourly_stats:conv_rate"]}"#,
+        )?;
+        assert_eq!(rows.entities, columnar.entities);
+        assert_eq!(
+            rows.entities.keys().collect::<Vec<_>>(),
+            ["driver_id", "city"]
+        );
+        assert_eq!(rows.features, columnar.features);
+
+        let serialized = serde_json::to_value(&rows)?;
+        assert_eq!(
+            serialized["entities"],
+            serde_json::json!({"driver_id": [1001, 1002], "city": ["sf", "nyc"]})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reject_invalid_entity_rows() {
+        let parse = |json: &str| {
+            serde_json::from_str::<GetOnlineFeaturesRequest>(json)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            parse(r#"{"entity_rows": [{"driver_id": 1001}, {"driver_id": 1002, "city": "sf"}]}"#),
+            "Entity row 1 has keys [driver_id, city] instead of [driver_id]"
+        );
+        assert_eq!(
+            parse(r#"{"entities": {"driver_id": [1001]}, "entity_rows": [{"driver_id": 1001}]}"#),
+            "Only one of entities and entity_rows can be set in a request"
+        );
+        assert_eq!(parse(r#"{"features": []}"#), "missing field `entities`");
+    }
+
+    #[test]
+    fn out_of_range_int32_entities_are_rejected() {
+        let value = EntityIdValue::Int(i64::from(i32::MAX) + 1);
+        assert!(value.to_proto_value(value_type::Enum::Int32).is_err());
+        assert_eq!(
+            EntityIdValue::Int(-7)
+                .to_proto_value(value_type::Enum::Int32)
+                .unwrap()
+                .val,
+            Some(Val::Int32Val(-7))
+        );
+    }
+
+    fn float_results() -> GetOnlineFeatureResponse {
+        let float = |val| ValueWrapper(Value { val: Some(val) });
+        GetOnlineFeatureResponse {
+            metadata: GetOnlineFeatureResponseMetadata {
+                feature_names: vec!["conv_rate".to_string(), "avg_daily_trips".to_string()],
+            },
+            results: vec![
+                FeatureResults {
+                    values: vec![
+                        float(Val::DoubleVal(0.5)),
+                        float(Val::DoubleVal(f64::NAN)),
+                        float(Val::FloatVal(f32::INFINITY)),
+                    ],
+                    ..Default::default()
+                },
+                FeatureResults {
+                    values: vec![
+                        ValueWrapper(Value { val: None }),
+                        float(Val::NullVal(0)),
+                        float(Val::Int64Val(3)),
+                    ],
+                    ..Default::default()
+                },
+            ],
+            warnings: vec![],
+            feature_service_version: None,
+            registry_version: None,
+            debug: None,
+        }
+    }
+
+    /// Values are written as the Python feature server writes them.
+    #[test]
+    fn serialize_null_and_nan_values_as_null() -> Result<()> {
+        let response = float_results();
+        let json = serde_json::to_value(&response.results)?;
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"values": [0.5, null, null]},
+                {"values": [null, null, 3]}
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_finite_reports_first_non_finite_value() {
+        let mut response = float_results();
+        let err = response.check_finite().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Feature conv_rate has a NaN or infinite value in row 1, which JSON cannot represent"
+        );
+        response.results[0].values.truncate(1);
+        assert!(response.check_finite().is_ok());
+    }
+
+    #[test]
+    fn features_and_feature_service_are_exclusive() -> Result<()> {
+        let request: GetOnlineFeaturesRequest = serde_json::from_str(
+            r#"{"feature_service": "driver_activity", "features": ["driver_hourly_stats:conv_rate"],
+                "entities": {"driver_id": [1001]}}"#,
+        )?;
+        let err = RequestedFeatures::try_from(&request).unwrap_err();
+        let err = err.downcast_ref::<FeastCoreError>().unwrap();
+        assert!(err.is_invalid_request());
+        assert_eq!(
+            err.to_string(),
+            "Request sets both features and feature service driver_activity; only one of them can be set"
+        );
+
+        let request = GetOnlineFeaturesRequest {
+            features: Some(Vec::new()),
+            ..request
+        };
+        assert!(matches!(
+            RequestedFeatures::try_from(&request)?,
+            RequestedFeatures::FeatureService(_)
+        ));
+        Ok(())
+    }
+}
@@ -1,10 +1,22 @@
 //! Online store interface and implementations for different backends.
 //! Contains logic for retrieving feature values from online stores.
 
+pub mod circuit_breaker;
+mod clickhouse;
+#[cfg(feature = "aws")]
+mod dynamodb;
+pub mod factory;
+pub mod feature_keys;
+pub mod hedged;
+pub mod in_memory;
+mod qdrant;
+#[cfg(feature = "redis")]
 mod redis;
 pub mod sqlite_onlinestore;
 
 use crate::config::OnlineStoreConfig;
+use crate::feast::types::value::Val;
+use crate::feast::types::value_type::Enum as ValueTypeEnum;
 use crate::feast::types::{EntityKey, Value};
 use crate::model::{Feature, HashEntityKey};
 use crate::onlinestore::sqlite_onlinestore::{ConnectionOptions, SqliteOnlineStore};
@@ -16,7 +28,9 @@ use rustc_hash::FxHashMap as HashMap;
 use std::sync::Arc;
 use tracing::debug;
 
-#[derive(Debug)]
+pub use factory::{OnlineStoreFactory, register_online_store};
+
+#[derive(Debug, Clone)]
 pub struct OnlineStoreRow {
     pub feature_view_name: Spur,
     pub entity_key: HashEntityKey,
@@ -24,6 +38,94 @@ pub struct OnlineStoreRow {
     pub value: Value,
     pub event_ts: DateTime<Utc>,
     pub created_ts: Option<DateTime<Utc>>,
+    /// The stored value could not be decoded. `value` is empty and the feature is answered
+    /// with the `INVALID` status.
+    pub invalid: bool,
+    /// Stored bytes of a value which could not be decoded, kept for views decoded leniently.
+    pub undecoded: Option<Vec<u8>>,
+}
+
+/// Number of stored values which could not be decoded, by store and feature view.
+pub const DECODE_FAILURES_METRIC: &str = "feast_decode_failures_total";
+
+/// Count a stored value which could not be decoded and log its key at DEBUG, so that the
+/// row can be answered as invalid instead of failing the request.
+pub(crate) fn record_decode_failure(
+    store: &'static str,
+    feature_view_name: Spur,
+    feature_name: &str,
+    entity_key: &EntityKey,
+    err: &dyn std::fmt::Display,
+) {
+    let feature_view = crate::intern::rodeo_ref().resolve(&feature_view_name);
+    metrics::counter!(
+        DECODE_FAILURES_METRIC,
+        "store" => store,
+        "feature_view" => feature_view.to_string()
+    )
+    .increment(1);
+    debug!(
+        "Failed to decode value of feature {}:{} for entity key {:?} in the {} store: {}",
+        feature_view, feature_name, entity_key, store, err
+    );
+}
+
+/// Number of stored entity keys which could not be deserialized, by store and feature view.
+pub const CORRUPT_ENTITY_KEYS_METRIC: &str = "feast_corrupt_entity_keys_total";
+
+/// Count a stored entity key which could not be deserialized. The read it belongs to fails
+/// with the error, as the row cannot be matched to a requested entity.
+pub(crate) fn record_corrupt_key(store: &'static str, feature_view_name: Spur) {
+    metrics::counter!(
+        CORRUPT_ENTITY_KEYS_METRIC,
+        "store" => store,
+        "feature_view" => crate::intern::rodeo_ref().resolve(&feature_view_name).to_string()
+    )
+    .increment(1);
+}
+
+/// Number of stored values which were not protobuf encoded and were read as raw string or
+/// bytes values, by feature view and kind of value.
+pub const LEGACY_VALUE_FALLBACKS_METRIC: &str = "feast_legacy_value_fallbacks_total";
+
+/// Read stored bytes which are not an encoded `Value`, as written by old materializations,
+/// as a string value when they are valid UTF-8 and the feature is not a bytes feature, and
+/// as a bytes value otherwise.
+pub(crate) fn legacy_value(
+    feature_view_name: Spur,
+    value_type: Option<ValueTypeEnum>,
+    bytes: Vec<u8>,
+) -> Value {
+    let (val, kind) = match value_type {
+        Some(ValueTypeEnum::Bytes) => (Val::BytesVal(bytes), "bytes"),
+        _ => match String::from_utf8(bytes) {
+            Ok(string) => (Val::StringVal(string), "string"),
+            Err(err) => (Val::BytesVal(err.into_bytes()), "bytes"),
+        },
+    };
+    metrics::counter!(
+        LEGACY_VALUE_FALLBACKS_METRIC,
+        "feature_view" => crate::intern::rodeo_ref().resolve(&feature_view_name).to_string(),
+        "kind" => kind
+    )
+    .increment(1);
+    Value { val: Some(val) }
+}
+
+/// Row returned by a nearest neighbour search together with its similarity score.
+#[derive(Debug)]
+pub struct VectorSearchResult {
+    pub row: OnlineStoreRow,
+    pub score: f32,
+}
+
+/// Feature values of a single entity key to be written to an online store.
+#[derive(Debug, Clone)]
+pub struct OnlineWriteRow {
+    pub entity_key: EntityKey,
+    pub values: Vec<(Spur, Value)>,
+    pub event_ts: DateTime<Utc>,
+    pub created_ts: Option<DateTime<Utc>>,
 }
 
 #[async_trait]
@@ -32,6 +134,73 @@ pub trait OnlineStore: Send + Sync + 'static {
         &self,
         features: HashMap<HashEntityKey, Vec<Feature>>,
     ) -> Result<Vec<OnlineStoreRow>>;
+
+    /// Write feature rows of a feature view, replacing previously stored values.
+    /// Stores are read-only unless they override this method.
+    async fn online_write_batch(
+        &self,
+        feature_view_name: Spur,
+        rows: Vec<OnlineWriteRow>,
+    ) -> Result<()> {
+        Err(anyhow!("Online store does not support writes"))
+    }
+
+    /// Delete the stored values of `feature_names` of a feature view for the given entity
+    /// keys and return the number of entity keys which had values. Stores are read-only
+    /// unless they override this method.
+    async fn delete_entity_keys(
+        &self,
+        feature_view_name: Spur,
+        feature_names: Vec<Spur>,
+        entity_keys: Vec<EntityKey>,
+    ) -> Result<u64> {
+        Err(anyhow!("Online store does not support deletes"))
+    }
+
+    /// Return up to `top_k` rows of `feature_name` in a feature view whose vectors are
+    /// closest to `query`, best match first. Stores without vector indexes do not support
+    /// searches unless they override this method.
+    async fn search_similar(
+        &self,
+        feature_view_name: Spur,
+        feature_name: Spur,
+        query: Vec<f32>,
+        top_k: usize,
+    ) -> Result<Vec<VectorSearchResult>> {
+        Err(anyhow!("Online store does not support vector search"))
+    }
+
+    /// Check that the backend of the store is reachable, for readiness probes. Stores
+    /// without a backend to check are always healthy.
+    async fn health_check(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Feature views among `feature_view_names` whose table does not exist in the backend.
+    /// Reads of such views find no values. Stores without per view tables report none.
+    async fn missing_tables(&self, feature_view_names: Vec<Spur>) -> Result<Vec<Spur>> {
+        Ok(Vec::new())
+    }
+
+    /// Up to `limit` serialized entity keys stored for `feature_view_names`, to check the
+    /// format of stored keys. Stores which cannot list their keys return none.
+    async fn sample_entity_keys(
+        &self,
+        feature_view_names: Vec<Spur>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>> {
+        Ok(Vec::new())
+    }
+
+    /// Drop data derived from feature definitions, called once reads resolve features
+    /// against a reloaded registry. Stores without such data have nothing to drop.
+    fn registry_reloaded(&self) {}
+
+    /// Close the connections of the store before the server exits. Reads after a shutdown
+    /// fail. Stores without connections to close have nothing to do.
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub async fn get_online_store(
@@ -53,10 +222,47 @@ pub async fn get_online_store(
             .await
             .map(|sqlite| Arc::new(sqlite) as Arc<dyn OnlineStore>)
         }
+        #[cfg(feature = "redis")]
         conf @ OnlineStoreConfig::Redis { .. } => {
             debug!("Create Redis online store");
             redis::from_config(project.to_string(), conf.clone()).await
         }
-        other => Err(anyhow!("Unsupported online store type: {:?}", other)),
+        #[cfg(not(feature = "redis"))]
+        OnlineStoreConfig::Redis { .. } => Err(crate::util::feature_disabled(
+            "Redis online stores",
+            "redis",
+        )),
+        conf @ OnlineStoreConfig::Clickhouse { .. } => {
+            debug!("Create ClickHouse online store");
+            clickhouse::from_config(conf, project)
+                .map(|store| Arc::new(store) as Arc<dyn OnlineStore>)
+        }
+        #[cfg(feature = "aws")]
+        conf @ OnlineStoreConfig::DynamoDB { .. } => {
+            debug!("Create DynamoDB online store");
+            dynamodb::from_config(conf, project)
+                .await
+                .map(|store| Arc::new(store) as Arc<dyn OnlineStore>)
+        }
+        #[cfg(not(feature = "aws"))]
+        OnlineStoreConfig::DynamoDB { .. } => Err(crate::util::feature_disabled(
+            "DynamoDB online stores",
+            "aws",
+        )),
+        OnlineStoreConfig::InMemory { fixtures } => {
+            debug!("Create in-memory online store");
+            in_memory::InMemoryOnlineStore::from_fixtures(fixtures, cwd)
+                .map(|store| Arc::new(store) as Arc<dyn OnlineStore>)
+        }
+        conf @ OnlineStoreConfig::Qdrant { .. } => {
+            debug!("Create Qdrant online store");
+            qdrant::from_config(conf.clone())
+                .await
+                .map(|store| Arc::new(store) as Arc<dyn OnlineStore>)
+        }
+        OnlineStoreConfig::Custom { module, options } => {
+            debug!("Create custom online store {}", module);
+            factory::create_custom_online_store(module, options, project, cwd).await
+        }
     }
 }
@@ -0,0 +1,245 @@
+//! Circuit breaker in front of an online store: a background task probes the store with its
+//! health check, and while probes keep failing reads fail at once instead of each waiting
+//! for the store to time out.
+
+use crate::config::CircuitBreakerConfig;
+use crate::error::FeastCoreError;
+use crate::feast::types::EntityKey;
+use crate::model::{Feature, HashEntityKey};
+use crate::onlinestore::{OnlineStore, OnlineStoreRow, OnlineWriteRow, VectorSearchResult};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use lasso::Spur;
+use rustc_hash::FxHashMap as HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 1 while the circuit is open and reads fail fast, 0 otherwise.
+pub const CIRCUIT_OPEN_METRIC: &str = "feast_online_store_circuit_open";
+
+/// Online store failing reads with [`FeastCoreError::OnlineStoreUnavailable`] once
+/// `failure_threshold` consecutive probes of `inner` failed, until a probe succeeds again.
+pub struct CircuitBreakerOnlineStore {
+    inner: Arc<dyn OnlineStore>,
+    failure_threshold: u32,
+    consecutive_failures: Arc<AtomicU32>,
+    probe_task: JoinHandle<()>,
+}
+
+impl CircuitBreakerOnlineStore {
+    /// Wrap `inner` and start probing it. Must be called within a Tokio runtime.
+    pub fn new(inner: Arc<dyn OnlineStore>, config: CircuitBreakerConfig) -> Self {
+        let consecutive_failures = Arc::new(AtomicU32::new(0));
+        metrics::gauge!(CIRCUIT_OPEN_METRIC).set(0.0);
+        let probe_task = tokio::spawn(probe(
+            Arc::clone(&inner),
+            config.clone(),
+            Arc::clone(&consecutive_failures),
+        ));
+        Self {
+            inner,
+            failure_threshold: config.failure_threshold,
+            consecutive_failures,
+            probe_task,
+        }
+    }
+
+    /// Reads fail fast while the circuit is open.
+    pub fn is_open(&self) -> bool {
+        self.consecutive_failures.load(Ordering::Relaxed) >= self.failure_threshold
+    }
+
+    fn check_closed(&self) -> Result<()> {
+        let failures = self.consecutive_failures.load(Ordering::Relaxed);
+        if failures >= self.failure_threshold {
+            return Err(FeastCoreError::online_store_unavailable(failures).into());
+        }
+        Ok(())
+    }
+}
+
+async fn probe(
+    store: Arc<dyn OnlineStore>,
+    config: CircuitBreakerConfig,
+    consecutive_failures: Arc<AtomicU32>,
+) {
+    let timeout = Duration::from_millis(config.probe_timeout_ms);
+    let mut interval = tokio::time::interval(Duration::from_millis(config.probe_interval_ms));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let result = match tokio::time::timeout(timeout, store.health_check()).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!("probe timed out after {:?}", timeout)),
+        };
+        match result {
+            Ok(()) => {
+                let failures = consecutive_failures.swap(0, Ordering::Relaxed);
+                if failures >= config.failure_threshold {
+                    tracing::info!("Online store probe succeeded, closing the circuit");
+                    metrics::gauge!(CIRCUIT_OPEN_METRIC).set(0.0);
+                }
+            }
+            Err(err) => {
+                let failures = consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                if failures == config.failure_threshold {
+                    tracing::error!(
+                        "Online store probe failed {} times, failing reads until it recovers: {:#}",
+                        failures,
+                        err
+                    );
+                    metrics::gauge!(CIRCUIT_OPEN_METRIC).set(1.0);
+                } else {
+                    tracing::warn!("Online store probe failed: {:#}", err);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl OnlineStore for CircuitBreakerOnlineStore {
+    async fn get_feature_values(
+        &self,
+        features: HashMap<HashEntityKey, Vec<Feature>>,
+    ) -> Result<Vec<OnlineStoreRow>> {
+        self.check_closed()?;
+        self.inner.get_feature_values(features).await
+    }
+
+    async fn online_write_batch(
+        &self,
+        feature_view_name: Spur,
+        rows: Vec<OnlineWriteRow>,
+    ) -> Result<()> {
+        self.check_closed()?;
+        self.inner.online_write_batch(feature_view_name, rows).await
+    }
+
+    async fn delete_entity_keys(
+        &self,
+        feature_view_name: Spur,
+        feature_names: Vec<Spur>,
+        entity_keys: Vec<EntityKey>,
+    ) -> Result<u64> {
+        self.check_closed()?;
+        self.inner
+            .delete_entity_keys(feature_view_name, feature_names, entity_keys)
+            .await
+    }
+
+    async fn search_similar(
+        &self,
+        feature_view_name: Spur,
+        feature_name: Spur,
+        query: Vec<f32>,
+        top_k: usize,
+    ) -> Result<Vec<VectorSearchResult>> {
+        self.check_closed()?;
+        self.inner
+            .search_similar(feature_view_name, feature_name, query, top_k)
+            .await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+
+    async fn missing_tables(&self, feature_view_names: Vec<Spur>) -> Result<Vec<Spur>> {
+        self.inner.missing_tables(feature_view_names).await
+    }
+
+    async fn sample_entity_keys(
+        &self,
+        feature_view_names: Vec<Spur>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>> {
+        self.inner
+            .sample_entity_keys(feature_view_names, limit)
+            .await
+    }
+
+    fn registry_reloaded(&self) {
+        self.inner.registry_reloaded();
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        self.probe_task.abort();
+        self.inner.shutdown().await
+    }
+}
+
+impl Drop for CircuitBreakerOnlineStore {
+    fn drop(&mut self) {
+        self.probe_task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    struct FlakyStore {
+        healthy: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl OnlineStore for FlakyStore {
+        async fn get_feature_values(
+            &self,
+            _features: HashMap<HashEntityKey, Vec<Feature>>,
+        ) -> Result<Vec<OnlineStoreRow>> {
+            Ok(Vec::new())
+        }
+
+        async fn health_check(&self) -> Result<()> {
+            if self.healthy.load(Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err(anyhow!("Connection refused"))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn opens_on_failed_probes_and_recovers() -> Result<()> {
+        let healthy = Arc::new(AtomicBool::new(false));
+        let store = CircuitBreakerOnlineStore::new(
+            Arc::new(FlakyStore {
+                healthy: Arc::clone(&healthy),
+            }),
+            CircuitBreakerConfig {
+                probe_interval_ms: 5,
+                probe_timeout_ms: 100,
+                failure_threshold: 2,
+            },
+        );
+        for _ in 0..100 {
+            if store.is_open() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let err = store
+            .get_feature_values(HashMap::default())
+            .await
+            .unwrap_err();
+        assert!(
+            err.downcast_ref::<FeastCoreError>()
+                .is_some_and(FeastCoreError::is_unavailable)
+        );
+
+        healthy.store(true, Ordering::SeqCst);
+        for _ in 0..100 {
+            if !store.is_open() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert!(store.get_feature_values(HashMap::default()).await.is_ok());
+        store.shutdown().await
+    }
+}
@@ -0,0 +1,329 @@
+//! Experimental online store reading the latest feature values from ClickHouse tables over
+//! the HTTP interface, for teams serving features from an OLAP database.
+//!
+//! Tables follow the layout of the Feast SQLite online store: one row per (entity key,
+//! feature) and write, with `entity_key` (serialized key), `feature_name`, `value`
+//! (`Value` proto), `event_ts` and `created_ts` columns. Rows are not replaced on write, so
+//! the latest value per entity key and feature is picked with `argMax` at read time.
+
+use crate::config::{EntityKeySerializationVersion, OnlineStoreConfig};
+use crate::feast::types::Value;
+use crate::intern;
+use crate::key_serialization::serialize_key;
+use crate::model::{Feature, HashEntityKey};
+use crate::onlinestore::{OnlineStore, OnlineStoreRow, record_decode_failure};
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use prost::Message;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+const USER_HEADER: &str = "X-ClickHouse-User";
+const KEY_HEADER: &str = "X-ClickHouse-Key";
+
+/// Latest value query of feature views without their own query. `{table}`,
+/// `{entity_keys}` and `{feature_names}` are replaced with the table name and the SQL
+/// lists of requested keys and features.
+pub const DEFAULT_LATEST_VALUE_QUERY: &str = "SELECT hex(entity_key) AS entity_key, \
+     feature_name, hex(argMax(value, event_ts)) AS value, \
+     toUnixTimestamp(max(event_ts)) AS event_ts, \
+     toUnixTimestamp(argMax(created_ts, event_ts)) AS created_ts \
+     FROM {table} WHERE entity_key IN ({entity_keys}) AND feature_name IN ({feature_names}) \
+     GROUP BY entity_key, feature_name";
+
+/// Row of a latest value query, with hex encoded key and value and timestamps in seconds.
+#[derive(Debug, Deserialize)]
+struct LatestValueRow {
+    entity_key: String,
+    feature_name: String,
+    value: String,
+    event_ts: i64,
+    #[serde(default)]
+    created_ts: Option<i64>,
+}
+
+pub(crate) struct ClickhouseOnlineStore {
+    client: reqwest::Client,
+    url: String,
+    database: String,
+    user: Option<String>,
+    password: Option<String>,
+    project: String,
+    table: String,
+    queries: BTreeMap<String, String>,
+}
+
+impl ClickhouseOnlineStore {
+    fn table_name(&self, feature_view_name: &str) -> String {
+        self.table
+            .replace("{project}", &self.project)
+            .replace("{feature_view}", feature_view_name)
+    }
+
+    /// Run `query` and return its output in the `JSONEachRow` format.
+    async fn query(&self, query: String) -> Result<String> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .query(&[
+                ("database", self.database.as_str()),
+                ("default_format", "JSONEachRow"),
+            ])
+            .body(query);
+        if let Some(user) = &self.user {
+            request = request.header(USER_HEADER, user);
+        }
+        if let Some(password) = &self.password {
+            request = request.header(KEY_HEADER, password);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Cannot reach ClickHouse at {}", self.url))?;
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(anyhow!(
+                "ClickHouse query failed with status {}: {}",
+                status,
+                text.trim()
+            ));
+        }
+        Ok(text)
+    }
+
+    async fn read_view(
+        &self,
+        view_name: Spur,
+        keys: &HashMap<String, HashEntityKey>,
+        features: &HashSet<Spur>,
+    ) -> Result<Vec<OnlineStoreRow>> {
+        let rodeo = intern::rodeo_ref();
+        let view = rodeo.resolve(&view_name);
+        let template = self
+            .queries
+            .get(view)
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_LATEST_VALUE_QUERY);
+        let feature_names = features.iter().map(|feature| rodeo.resolve(feature));
+        let query = latest_value_query(
+            template,
+            &self.table_name(view),
+            keys.keys().map(String::as_str),
+            feature_names,
+        );
+        let output = self
+            .query(query)
+            .await
+            .with_context(|| format!("Failed to read feature view {} from ClickHouse", view))?;
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let row: LatestValueRow = serde_json::from_str(line)
+                    .with_context(|| format!("Unexpected ClickHouse row of {}", view))?;
+                let Some(entity_key) = keys.get(&row.entity_key.to_ascii_lowercase()) else {
+                    return Err(anyhow!(
+                        "ClickHouse returned a key of {} not requested",
+                        view
+                    ));
+                };
+                to_online_row(view_name, entity_key.clone(), row)
+            })
+            .collect()
+    }
+}
+
+/// Quote `value` as a ClickHouse string literal.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn latest_value_query<'a>(
+    template: &str,
+    table: &str,
+    hex_keys: impl Iterator<Item = &'a str>,
+    feature_names: impl Iterator<Item = &'a str>,
+) -> String {
+    let entity_keys = hex_keys
+        .map(|key| format!("unhex({})", quote(key)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let feature_names = feature_names.map(quote).collect::<Vec<_>>().join(", ");
+    template
+        .replace("{table}", table)
+        .replace("{entity_keys}", &entity_keys)
+        .replace("{feature_names}", &feature_names)
+}
+
+fn to_online_row(
+    view_name: Spur,
+    entity_key: HashEntityKey,
+    row: LatestValueRow,
+) -> Result<OnlineStoreRow> {
+    let timestamp = |seconds: i64| {
+        DateTime::<Utc>::from_timestamp(seconds, 0)
+            .ok_or_else(|| anyhow!("Invalid ClickHouse timestamp {}", seconds))
+    };
+    let bytes = hex::decode(&row.value).context("ClickHouse value is not hex encoded")?;
+    let (value, undecoded) = match Value::decode(bytes.as_slice()) {
+        Ok(value) => (value, None),
+        Err(err) => {
+            record_decode_failure(
+                "clickhouse",
+                view_name,
+                &row.feature_name,
+                &entity_key.0,
+                &err,
+            );
+            (Value::default(), Some(bytes))
+        }
+    };
+    Ok(OnlineStoreRow {
+        feature_view_name: view_name,
+        entity_key,
+        feature_name: intern::rodeo_ref().get_or_intern(&row.feature_name),
+        value,
+        event_ts: timestamp(row.event_ts)?,
+        created_ts: row.created_ts.map(timestamp).transpose()?,
+        invalid: undecoded.is_some(),
+        undecoded,
+    })
+}
+
+#[async_trait]
+impl OnlineStore for ClickhouseOnlineStore {
+    async fn get_feature_values(
+        &self,
+        features: HashMap<HashEntityKey, Vec<Feature>>,
+    ) -> Result<Vec<OnlineStoreRow>> {
+        let mut view_to_keys: HashMap<Spur, HashMap<String, HashEntityKey>> = HashMap::default();
+        let mut view_features: HashMap<Spur, HashSet<Spur>> = HashMap::default();
+        for (entity_key, feature_list) in features {
+            let hex_key = hex::encode(serialize_key(
+                &entity_key.0,
+                EntityKeySerializationVersion::V3,
+            )?);
+            for feature in feature_list {
+                view_features
+                    .entry(feature.feature_view_name)
+                    .or_default()
+                    .insert(feature.feature_name);
+                view_to_keys
+                    .entry(feature.feature_view_name)
+                    .or_default()
+                    .insert(hex_key.clone(), entity_key.clone());
+            }
+        }
+
+        let mut rows = Vec::new();
+        for (view_name, keys) in view_to_keys {
+            let features = view_features.remove(&view_name).unwrap_or_default();
+            rows.extend(self.read_view(view_name, &keys, &features).await?);
+        }
+        Ok(rows)
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.query("SELECT 1".to_string()).await?;
+        Ok(())
+    }
+
+    async fn missing_tables(&self, feature_view_names: Vec<Spur>) -> Result<Vec<Spur>> {
+        #[derive(Deserialize)]
+        struct Table {
+            name: String,
+        }
+        let output = self
+            .query(format!(
+                "SELECT name FROM system.tables WHERE database = {}",
+                quote(&self.database)
+            ))
+            .await?;
+        let tables = output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str::<Table>(line).map(|table| table.name))
+            .collect::<Result<HashSet<_>, _>>()?;
+        let rodeo = intern::rodeo_ref();
+        Ok(feature_view_names
+            .into_iter()
+            .filter(|view| !tables.contains(&self.table_name(rodeo.resolve(view))))
+            .collect())
+    }
+}
+
+pub fn from_config(config: &OnlineStoreConfig, project: &str) -> Result<ClickhouseOnlineStore> {
+    match config {
+        OnlineStoreConfig::Clickhouse {
+            url,
+            database,
+            user,
+            password,
+            table,
+            queries,
+        } => Ok(ClickhouseOnlineStore {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            database: database.clone(),
+            user: user.clone(),
+            password: password.clone(),
+            project: project.to_string(),
+            table: table.clone(),
+            queries: queries.clone(),
+        }),
+        _ => Err(anyhow!("Invalid config for ClickhouseOnlineStore")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feast::types::EntityKey;
+    use crate::feast::types::value::Val;
+    use std::sync::Arc;
+
+    #[test]
+    fn latest_value_query_lists_keys_and_features() {
+        let query = latest_value_query(
+            "SELECT * FROM {table} WHERE entity_key IN ({entity_keys}) AND feature_name IN \
+             ({feature_names})",
+            "golden_hornet_driver_hourly_stats",
+            ["0a0b", "0c0d"].into_iter(),
+            ["conv_rate", "it's"].into_iter(),
+        );
+        assert_eq!(
+            query,
+            "SELECT * FROM golden_hornet_driver_hourly_stats WHERE entity_key IN \
+             (unhex('0a0b'), unhex('0c0d')) AND feature_name IN ('conv_rate', 'it\\'s')"
+        );
+    }
+
+    #[test]
+    fn converts_latest_value_row() -> Result<()> {
+        let entity_key = HashEntityKey(Arc::new(EntityKey {
+            join_keys: vec!["driver_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(1001)),
+            }],
+        }));
+        let value = Value {
+            val: Some(Val::DoubleVal(0.5)),
+        };
+        let row: LatestValueRow = serde_json::from_str(&format!(
+            r#"{{"entity_key":"00","feature_name":"conv_rate","value":"{}","event_ts":1714557600,"created_ts":1714557605}}"#,
+            hex::encode(value.encode_to_vec())
+        ))?;
+        let view = intern::rodeo_ref().get_or_intern("driver_hourly_stats");
+        let row = to_online_row(view, entity_key.clone(), row)?;
+        assert_eq!(row.value, value);
+        assert_eq!(row.entity_key, entity_key);
+        assert_eq!(row.event_ts.timestamp(), 1_714_557_600);
+        assert_eq!(row.created_ts.map(|ts| ts.timestamp()), Some(1_714_557_605));
+        assert!(!row.invalid);
+        Ok(())
+    }
+}
@@ -0,0 +1,650 @@
+//! Online store reading the tables written by the Feast DynamoDB online store, built with
+//! the `aws` feature.
+//!
+//! Every feature view has a table holding an item per entity key: `entity_id` is the MD5
+//! hex digest of the serialized entity key, `event_ts` the event time as Python formats it
+//! and `values` a map of feature names to encoded `Value`s.
+//!
+//! Keys are read with BatchGetItem requests of at most 100 keys. Requests run concurrently
+//! up to a limit which halves whenever DynamoDB throttles and grows back by one with every
+//! request served in full. Throttled requests and unprocessed keys are retried with
+//! jittered exponential backoff.
+
+use crate::config::{EntityKeySerializationVersion, OnlineStoreConfig};
+use crate::feast::types::Value;
+use crate::intern;
+use crate::key_serialization::serialize_key;
+use crate::model::{Feature, HashEntityKey};
+use crate::onlinestore::{OnlineStore, OnlineStoreRow, record_decode_failure};
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use aws_sdk_dynamodb::config::retry::RetryConfig;
+use aws_sdk_dynamodb::error::{DisplayErrorContext, SdkError};
+use aws_sdk_dynamodb::operation::batch_get_item::BatchGetItemError;
+use aws_sdk_dynamodb::types::{AttributeValue, KeysAndAttributes};
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use md5::{Digest, Md5};
+use prost::Message;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// Number of BatchGetItem requests DynamoDB throttled, by table.
+pub const DYNAMODB_THROTTLED_REQUESTS_METRIC: &str = "feast_dynamodb_throttled_requests_total";
+
+/// Number of keys DynamoDB returned unprocessed, by table. They are read again.
+pub const DYNAMODB_UNPROCESSED_KEYS_METRIC: &str = "feast_dynamodb_unprocessed_keys_total";
+
+/// Current limit on concurrent BatchGetItem requests of a read.
+pub const DYNAMODB_READ_CONCURRENCY_METRIC: &str = "feast_dynamodb_read_concurrency";
+
+/// Delay before the first retry; each further retry doubles it up to [`MAX_RETRY_DELAY`].
+const BASE_RETRY_DELAY: Duration = Duration::from_millis(25);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Item of a feature view table.
+#[derive(Debug, Clone, PartialEq)]
+struct Item {
+    entity_id: String,
+    event_ts: String,
+    values: Vec<(String, Vec<u8>)>,
+}
+
+/// Answer of a BatchGetItem request.
+#[derive(Debug)]
+enum BatchGetOutcome {
+    /// Items found, and the keys DynamoDB left unprocessed, e.g. beyond the capacity of
+    /// the table.
+    Read {
+        items: Vec<Item>,
+        unprocessed: Vec<String>,
+    },
+    /// The request was throttled as a whole.
+    Throttled,
+}
+
+/// Sends BatchGetItem requests, so that batching and retries are tested without DynamoDB.
+#[async_trait]
+trait BatchGetter: Send + Sync + 'static {
+    async fn batch_get(
+        &self,
+        table: &str,
+        entity_ids: &[String],
+        consistent_read: bool,
+    ) -> Result<BatchGetOutcome>;
+}
+
+struct SdkBatchGetter(aws_sdk_dynamodb::Client);
+
+#[async_trait]
+impl BatchGetter for SdkBatchGetter {
+    async fn batch_get(
+        &self,
+        table: &str,
+        entity_ids: &[String],
+        consistent_read: bool,
+    ) -> Result<BatchGetOutcome> {
+        let keys = entity_ids
+            .iter()
+            .fold(KeysAndAttributes::builder(), |builder, entity_id| {
+                builder.keys(std::collections::HashMap::from([(
+                    "entity_id".to_string(),
+                    AttributeValue::S(entity_id.clone()),
+                )]))
+            })
+            .consistent_read(consistent_read)
+            .build()?;
+        let output = match self
+            .0
+            .batch_get_item()
+            .request_items(table, keys)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(err) if is_throttling(&err) => return Ok(BatchGetOutcome::Throttled),
+            Err(err) => {
+                return Err(anyhow!(
+                    "BatchGetItem on {} failed: {}",
+                    table,
+                    DisplayErrorContext(err)
+                ));
+            }
+        };
+        let items = output
+            .responses()
+            .and_then(|responses| responses.get(table))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|item| {
+                item_from_attributes(item).with_context(|| format!("Invalid item of {}", table))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let unprocessed = output
+            .unprocessed_keys()
+            .and_then(|unprocessed| unprocessed.get(table))
+            .map(|keys| keys.keys())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|key| key.get("entity_id")?.as_s().ok().cloned())
+            .collect();
+        Ok(BatchGetOutcome::Read { items, unprocessed })
+    }
+}
+
+fn is_throttling(err: &SdkError<BatchGetItemError>) -> bool {
+    err.as_service_error().is_some_and(|err| {
+        err.is_provisioned_throughput_exceeded_exception()
+            || err.is_request_limit_exceeded()
+            || err.is_throttling_exception()
+    })
+}
+
+fn item_from_attributes(
+    attributes: &std::collections::HashMap<String, AttributeValue>,
+) -> Result<Item> {
+    let string = |name: &str| {
+        attributes
+            .get(name)
+            .and_then(|value| value.as_s().ok())
+            .cloned()
+            .ok_or_else(|| anyhow!("Missing string attribute {}", name))
+    };
+    let values = match attributes.get("values") {
+        Some(AttributeValue::M(values)) => values
+            .iter()
+            .map(|(name, value)| match value {
+                AttributeValue::B(blob) => Ok((name.clone(), blob.as_ref().to_vec())),
+                _ => Err(anyhow!("Value of feature {} is not binary", name)),
+            })
+            .collect::<Result<Vec<_>>>()?,
+        _ => return Err(anyhow!("Missing map attribute values")),
+    };
+    Ok(Item {
+        entity_id: string("entity_id")?,
+        event_ts: string("event_ts")?,
+        values,
+    })
+}
+
+/// Limit on concurrent BatchGetItem requests, halved when DynamoDB throttles and raised by
+/// one with every request served in full, up to `max`.
+struct AdaptiveConcurrency {
+    limit: AtomicUsize,
+    max: usize,
+}
+
+impl AdaptiveConcurrency {
+    fn new(max: usize) -> Self {
+        Self {
+            limit: AtomicUsize::new(max),
+            max,
+        }
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.load(Ordering::Acquire)
+    }
+
+    fn throttled(&self) {
+        self.update(|limit| (limit / 2).max(1));
+    }
+
+    fn served(&self) {
+        self.update(|limit| (limit + 1).min(self.max));
+    }
+
+    fn update(&self, f: impl Fn(usize) -> usize) {
+        let _ = self
+            .limit
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |limit| Some(f(limit)));
+        metrics::gauge!(DYNAMODB_READ_CONCURRENCY_METRIC).set(self.limit() as f64);
+    }
+}
+
+/// Delay before retry `attempt`, drawn uniformly up to the exponential backoff so that
+/// requests throttled together do not retry together.
+fn retry_delay(base: Duration, attempt: u32) -> Duration {
+    let backoff = base
+        .saturating_mul(1 << attempt.min(16))
+        .min(MAX_RETRY_DELAY);
+    backoff.mul_f64(fastrand::f64())
+}
+
+pub(crate) struct DynamoDbOnlineStore {
+    getter: Arc<dyn BatchGetter>,
+    project: String,
+    table_name_template: String,
+    batch_size: usize,
+    consistent_reads: bool,
+    max_retries: u32,
+    retry_base_delay: Duration,
+    concurrency: Arc<AdaptiveConcurrency>,
+}
+
+/// Requested keys of a feature view with its table.
+struct ViewRead {
+    view_name: Spur,
+    table: String,
+    keys: HashMap<String, HashEntityKey>,
+    features: HashSet<Spur>,
+}
+
+impl DynamoDbOnlineStore {
+    fn table_name(&self, feature_view_name: &str) -> String {
+        self.table_name_template
+            .replace("{project}", &self.project)
+            .replace("{table_name}", feature_view_name)
+    }
+
+    /// Read `entity_ids` of `table`, retrying throttled requests and unprocessed keys.
+    async fn read_batch(
+        getter: Arc<dyn BatchGetter>,
+        concurrency: Arc<AdaptiveConcurrency>,
+        table: Arc<str>,
+        mut entity_ids: Vec<String>,
+        consistent_read: bool,
+        max_retries: u32,
+        retry_base_delay: Duration,
+    ) -> Result<Vec<Item>> {
+        let mut items = Vec::new();
+        let mut attempt = 0;
+        loop {
+            match getter
+                .batch_get(&table, &entity_ids, consistent_read)
+                .await?
+            {
+                BatchGetOutcome::Read {
+                    items: read,
+                    unprocessed,
+                } => {
+                    items.extend(read);
+                    if unprocessed.is_empty() {
+                        concurrency.served();
+                        return Ok(items);
+                    }
+                    metrics::counter!(
+                        DYNAMODB_UNPROCESSED_KEYS_METRIC,
+                        "table" => table.to_string()
+                    )
+                    .increment(unprocessed.len() as u64);
+                    entity_ids = unprocessed;
+                }
+                BatchGetOutcome::Throttled => {
+                    metrics::counter!(
+                        DYNAMODB_THROTTLED_REQUESTS_METRIC,
+                        "table" => table.to_string()
+                    )
+                    .increment(1);
+                }
+            }
+            concurrency.throttled();
+            if attempt == max_retries {
+                return Err(anyhow!(
+                    "DynamoDB throttled reads of {} keys of {} after {} retries",
+                    entity_ids.len(),
+                    table,
+                    max_retries
+                ));
+            }
+            attempt += 1;
+            tokio::time::sleep(retry_delay(retry_base_delay, attempt)).await;
+        }
+    }
+
+    /// Run the batches of every view, at most as many at once as the concurrency limit.
+    async fn read_views(&self, views: &[ViewRead]) -> Result<Vec<(usize, Item)>> {
+        let mut batches = views.iter().enumerate().flat_map(|(view, read)| {
+            let table: Arc<str> = Arc::from(read.table.as_str());
+            let ids: Vec<String> = read.keys.keys().cloned().collect();
+            ids.chunks(self.batch_size)
+                .map(|chunk| (view, Arc::clone(&table), chunk.to_vec()))
+                .collect::<Vec<_>>()
+        });
+        let mut join_set = JoinSet::new();
+        let mut items = Vec::new();
+        loop {
+            while join_set.len() < self.concurrency.limit() {
+                let Some((view, table, ids)) = batches.next() else {
+                    break;
+                };
+                let read = Self::read_batch(
+                    Arc::clone(&self.getter),
+                    Arc::clone(&self.concurrency),
+                    table,
+                    ids,
+                    self.consistent_reads,
+                    self.max_retries,
+                    self.retry_base_delay,
+                );
+                join_set.spawn(async move { read.await.map(|items| (view, items)) });
+            }
+            let Some(joined) = join_set.join_next().await else {
+                return Ok(items);
+            };
+            let (view, read) = joined??;
+            items.extend(read.into_iter().map(|item| (view, item)));
+        }
+    }
+}
+
+/// Event time as Python formats timezone aware datetimes, e.g.
+/// `2024-05-01 10:00:00.250000+00:00`.
+fn parse_event_ts(event_ts: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_str(event_ts, "%Y-%m-%d %H:%M:%S%.f%:z")
+        .or_else(|_| DateTime::parse_from_rfc3339(event_ts))
+        .map(|ts| ts.with_timezone(&Utc))
+        .map_err(|_| anyhow!("Invalid DynamoDB event_ts {}", event_ts))
+}
+
+fn to_online_rows(read: &ViewRead, item: Item) -> Result<Vec<OnlineStoreRow>> {
+    let Some(entity_key) = read.keys.get(&item.entity_id) else {
+        return Err(anyhow!(
+            "DynamoDB returned a key of {} not requested",
+            read.table
+        ));
+    };
+    let event_ts = parse_event_ts(&item.event_ts)?;
+    let rodeo = intern::rodeo_ref();
+    Ok(item
+        .values
+        .into_iter()
+        .filter_map(|(feature_name, bytes)| {
+            let feature = rodeo.get(&feature_name)?;
+            if !read.features.contains(&feature) {
+                return None;
+            }
+            let (value, undecoded) = match Value::decode(bytes.as_slice()) {
+                Ok(value) => (value, None),
+                Err(err) => {
+                    record_decode_failure(
+                        "dynamodb",
+                        read.view_name,
+                        &feature_name,
+                        &entity_key.0,
+                        &err,
+                    );
+                    (Value::default(), Some(bytes))
+                }
+            };
+            Some(OnlineStoreRow {
+                feature_view_name: read.view_name,
+                entity_key: entity_key.clone(),
+                feature_name: feature,
+                value,
+                event_ts,
+                created_ts: None,
+                invalid: undecoded.is_some(),
+                undecoded,
+            })
+        })
+        .collect())
+}
+
+/// `entity_id` of an entity key, as computed by Feast.
+fn entity_id(entity_key: &HashEntityKey) -> Result<String> {
+    let serialized = serialize_key(&entity_key.0, EntityKeySerializationVersion::V3)?;
+    Ok(hex::encode(Md5::digest(serialized)))
+}
+
+#[async_trait]
+impl OnlineStore for DynamoDbOnlineStore {
+    async fn get_feature_values(
+        &self,
+        features: HashMap<HashEntityKey, Vec<Feature>>,
+    ) -> Result<Vec<OnlineStoreRow>> {
+        let rodeo = intern::rodeo_ref();
+        let mut views: HashMap<Spur, ViewRead> = HashMap::default();
+        for (entity_key, feature_list) in features {
+            let entity_id = entity_id(&entity_key)?;
+            for feature in feature_list {
+                let read = views
+                    .entry(feature.feature_view_name)
+                    .or_insert_with(|| ViewRead {
+                        view_name: feature.feature_view_name,
+                        table: self.table_name(rodeo.resolve(&feature.feature_view_name)),
+                        keys: HashMap::default(),
+                        features: HashSet::default(),
+                    });
+                read.features.insert(feature.feature_name);
+                read.keys.insert(entity_id.clone(), entity_key.clone());
+            }
+        }
+        let views: Vec<ViewRead> = views.into_values().collect();
+        let mut rows = Vec::new();
+        for (view, item) in self.read_views(&views).await? {
+            rows.extend(to_online_rows(&views[view], item)?);
+        }
+        Ok(rows)
+    }
+}
+
+pub async fn from_config(config: &OnlineStoreConfig, project: &str) -> Result<DynamoDbOnlineStore> {
+    let OnlineStoreConfig::DynamoDB {
+        region,
+        endpoint_url,
+        table_name_template,
+        batch_size,
+        max_read_concurrency,
+        consistent_reads,
+        max_retries,
+    } = config
+    else {
+        return Err(anyhow!("Invalid config for DynamoDbOnlineStore"));
+    };
+    let mut loader = aws_config::from_env();
+    if let Some(region) = region {
+        loader = loader.region(aws_config::Region::new(region.clone()));
+    }
+    if let Some(endpoint_url) = endpoint_url {
+        loader = loader.endpoint_url(endpoint_url);
+    }
+    // Throttled requests are retried by the store, which counts them and lowers its
+    // concurrency
+    let client_config = aws_sdk_dynamodb::config::Builder::from(&loader.load().await)
+        .retry_config(RetryConfig::disabled())
+        .build();
+    Ok(DynamoDbOnlineStore {
+        getter: Arc::new(SdkBatchGetter(aws_sdk_dynamodb::Client::from_conf(
+            client_config,
+        ))),
+        project: project.to_string(),
+        table_name_template: table_name_template.clone(),
+        batch_size: *batch_size,
+        consistent_reads: *consistent_reads,
+        max_retries: *max_retries,
+        retry_base_delay: BASE_RETRY_DELAY,
+        concurrency: Arc::new(AdaptiveConcurrency::new(*max_read_concurrency)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feast::types::EntityKey;
+    use crate::feast::types::value::Val;
+    use std::sync::Mutex;
+
+    /// Table of items answering the first `throttle` requests as throttled and leaving the
+    /// keys beyond `max_processed` of every request unprocessed.
+    #[derive(Default)]
+    struct FakeTable {
+        items: HashMap<String, Item>,
+        throttle: AtomicUsize,
+        max_processed: usize,
+        requests: Mutex<Vec<usize>>,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl BatchGetter for FakeTable {
+        async fn batch_get(
+            &self,
+            _table: &str,
+            entity_ids: &[String],
+            _consistent_read: bool,
+        ) -> Result<BatchGetOutcome> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            self.requests.lock().unwrap().push(entity_ids.len());
+            if self
+                .throttle
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Ok(BatchGetOutcome::Throttled);
+            }
+            let processed = entity_ids.len().min(self.max_processed);
+            Ok(BatchGetOutcome::Read {
+                items: entity_ids[..processed]
+                    .iter()
+                    .filter_map(|id| self.items.get(id).cloned())
+                    .collect(),
+                unprocessed: entity_ids[processed..].to_vec(),
+            })
+        }
+    }
+
+    fn driver_key(id: i64) -> HashEntityKey {
+        HashEntityKey(Arc::new(EntityKey {
+            join_keys: vec!["driver_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(id)),
+            }],
+        }))
+    }
+
+    fn store(
+        table: FakeTable,
+        max_read_concurrency: usize,
+    ) -> (DynamoDbOnlineStore, Arc<FakeTable>) {
+        let table = Arc::new(table);
+        let store = DynamoDbOnlineStore {
+            getter: Arc::clone(&table) as Arc<dyn BatchGetter>,
+            project: "golden_hornet".to_string(),
+            table_name_template: "{project}.{table_name}".to_string(),
+            batch_size: 100,
+            consistent_reads: false,
+            max_retries: 3,
+            retry_base_delay: Duration::ZERO,
+            concurrency: Arc::new(AdaptiveConcurrency::new(max_read_concurrency)),
+        };
+        (store, table)
+    }
+
+    fn table_of(ids: impl Iterator<Item = i64>) -> Result<FakeTable> {
+        let value = Value {
+            val: Some(Val::DoubleVal(0.5)),
+        };
+        let items = ids
+            .map(|id| {
+                let entity_id = entity_id(&driver_key(id))?;
+                let item = Item {
+                    entity_id: entity_id.clone(),
+                    event_ts: "2024-05-01 10:00:00.250000+00:00".to_string(),
+                    values: vec![("conv_rate".to_string(), value.encode_to_vec())],
+                };
+                Ok((entity_id, item))
+            })
+            .collect::<Result<_>>()?;
+        Ok(FakeTable {
+            items,
+            max_processed: usize::MAX,
+            ..Default::default()
+        })
+    }
+
+    fn request(ids: impl Iterator<Item = i64>) -> HashMap<HashEntityKey, Vec<Feature>> {
+        ids.map(|id| {
+            (
+                driver_key(id),
+                vec![Feature::from_names("driver_hourly_stats", "conv_rate")],
+            )
+        })
+        .collect()
+    }
+
+    #[test]
+    fn entity_id_is_md5_of_serialized_key() -> Result<()> {
+        // Feast hashes the V3 serialization of the key, whatever the configured version
+        let serialized = serialize_key(&driver_key(1001).0, EntityKeySerializationVersion::V3)?;
+        assert_eq!(
+            entity_id(&driver_key(1001))?,
+            hex::encode(Md5::digest(serialized))
+        );
+        assert_eq!(
+            parse_event_ts("2024-05-01 10:00:00.250000+00:00")?,
+            DateTime::parse_from_rfc3339("2024-05-01T10:00:00.25Z")?
+        );
+        assert_eq!(
+            parse_event_ts("2024-05-01 10:00:00+00:00")?.timestamp(),
+            1_714_557_600
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reads_in_batches_of_at_most_100_keys() -> Result<()> {
+        let (store, table) = store(table_of(0..250)?, 4);
+        let rows = store.get_feature_values(request(0..250)).await?;
+        assert_eq!(rows.len(), 250);
+        assert!(
+            rows.iter()
+                .all(|row| row.value.val == Some(Val::DoubleVal(0.5)))
+        );
+        let mut requests = table.requests.lock().unwrap().clone();
+        requests.sort();
+        assert_eq!(requests, [50, 100, 100]);
+        assert!(table.max_in_flight.load(Ordering::SeqCst) <= 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn retries_unprocessed_keys_and_throttled_requests() -> Result<()> {
+        let mut table = table_of(0..10)?;
+        table.throttle = AtomicUsize::new(1);
+        table.max_processed = 4;
+        let (store, table) = store(table, 8);
+        let rows = store.get_feature_values(request(0..10)).await?;
+        assert_eq!(rows.len(), 10);
+        // Throttled, then 4 of 10, 4 of 6 and the last 2 keys
+        assert_eq!(*table.requests.lock().unwrap(), [10, 10, 6, 2]);
+        // Halved on every throttled or partial request, raised after the last one
+        assert_eq!(store.concurrency.limit(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fails_after_max_retries() -> Result<()> {
+        let mut table = table_of(0..3)?;
+        table.throttle = AtomicUsize::new(usize::MAX);
+        let (store, table) = store(table, 1);
+        let err = store.get_feature_values(request(0..3)).await.unwrap_err();
+        assert!(err.to_string().contains("after 3 retries"), "{}", err);
+        assert_eq!(table.requests.lock().unwrap().len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn concurrency_halves_and_recovers() {
+        let concurrency = AdaptiveConcurrency::new(8);
+        concurrency.throttled();
+        concurrency.throttled();
+        assert_eq!(concurrency.limit(), 2);
+        for _ in 0..10 {
+            concurrency.served();
+        }
+        assert_eq!(concurrency.limit(), 8);
+        for _ in 0..10 {
+            concurrency.throttled();
+        }
+        assert_eq!(concurrency.limit(), 1);
+    }
+}
@@ -0,0 +1,130 @@
+//! Registry of online store implementations living outside this crate. A store registered
+//! under a module name is selected with `type: custom` and `module: <name>` in the
+//! `online_store` section of `feature_store.yaml`.
+
+use crate::onlinestore::OnlineStore;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Creates a custom online store from the `online_store` settings other than `type` and
+/// `module`.
+#[async_trait]
+pub trait OnlineStoreFactory: Send + Sync + 'static {
+    async fn create(
+        &self,
+        options: &BTreeMap<String, JsonValue>,
+        project: &str,
+        cwd: Option<&str>,
+    ) -> Result<Arc<dyn OnlineStore>>;
+}
+
+type Factories = RwLock<BTreeMap<String, Arc<dyn OnlineStoreFactory>>>;
+
+static FACTORIES: OnceLock<Factories> = OnceLock::new();
+
+fn factories() -> &'static Factories {
+    FACTORIES.get_or_init(Default::default)
+}
+
+/// Make an online store available as `module` to `type: custom` configs. Stores must be
+/// registered before the feature store is built; a later registration under the same name
+/// replaces the earlier one.
+pub fn register_online_store(module: impl Into<String>, factory: impl OnlineStoreFactory) {
+    factories()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(module.into(), Arc::new(factory));
+}
+
+pub(crate) async fn create_custom_online_store(
+    module: &str,
+    options: &BTreeMap<String, JsonValue>,
+    project: &str,
+    cwd: Option<&str>,
+) -> Result<Arc<dyn OnlineStore>> {
+    let factory = {
+        let factories = factories().read().unwrap_or_else(|e| e.into_inner());
+        match factories.get(module) {
+            Some(factory) => Arc::clone(factory),
+            None => {
+                return Err(anyhow!(
+                    "No online store registered for module {}; registered modules: [{}]",
+                    module,
+                    factories.keys().cloned().collect::<Vec<_>>().join(", ")
+                ));
+            }
+        }
+    };
+    factory.create(options, project, cwd).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OnlineStoreConfig, RepoConfig};
+    use crate::onlinestore::get_online_store;
+    use crate::onlinestore::in_memory::InMemoryOnlineStore;
+
+    struct InMemoryFactory;
+
+    #[async_trait]
+    impl OnlineStoreFactory for InMemoryFactory {
+        async fn create(
+            &self,
+            options: &BTreeMap<String, JsonValue>,
+            project: &str,
+            _cwd: Option<&str>,
+        ) -> Result<Arc<dyn OnlineStore>> {
+            assert_eq!(options["capacity"], JsonValue::from(10));
+            assert_eq!(project, "custom_project");
+            Ok(Arc::new(InMemoryOnlineStore::new()))
+        }
+    }
+
+    fn repo_config(module: &str) -> Result<RepoConfig> {
+        RepoConfig::from_yaml_str_strict(&format!(
+            r#"
+project: custom_project
+registry: registry.pb
+online_store:
+    type: custom
+    module: {}
+    capacity: 10
+"#,
+            module
+        ))
+    }
+
+    #[tokio::test]
+    async fn registered_store_is_created_from_config() -> Result<()> {
+        register_online_store("test_in_memory", InMemoryFactory);
+        let config = repo_config("test_in_memory")?;
+        let OnlineStoreConfig::Custom { module, options } = &config.online_store else {
+            panic!("Expected custom online store config");
+        };
+        assert_eq!(module, "test_in_memory");
+        assert_eq!(options.len(), 1);
+
+        let store = get_online_store(&config.online_store, &config.project, None).await?;
+        store.health_check().await
+    }
+
+    #[tokio::test]
+    async fn unregistered_module_fails() -> Result<()> {
+        let config = repo_config("missing_store")?;
+        let err = get_online_store(&config.online_store, &config.project, None)
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string()
+                .starts_with("No online store registered for module missing_store"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Hash fields the Redis online store of Feast keeps feature values under: the little
+//! endian murmur3 hash of `<feature view>:<feature>`. A request hashes the same features
+//! for each of its entity rows, so the hashes are cached per feature.
+
+use crate::intern;
+use crate::model::Feature;
+use anyhow::Result;
+use lasso::Spur;
+use rustc_hash::FxHashMap as HashMap;
+use smallvec::SmallVec;
+use std::sync::RwLock;
+
+/// Hash field of `feature`, computed without the cache.
+pub fn hash_feature_key(feature: &Feature) -> Result<[u8; 4]> {
+    let rodeo = intern::rodeo_ref();
+    let feature_view_name = rodeo.resolve(&feature.feature_view_name);
+    let feature_name = rodeo.resolve(&feature.feature_name);
+    let mut key_bytes: SmallVec<[u8; 64]> =
+        SmallVec::with_capacity(feature_view_name.len() + 1 + feature_name.len());
+    key_bytes.extend_from_slice(feature_view_name.as_bytes());
+    key_bytes.push(b':');
+    key_bytes.extend_from_slice(feature_name.as_bytes());
+    let mut reader = std::io::Cursor::new(&key_bytes[..]);
+    let hashed_key = murmur3::murmur3_32(&mut reader, 0)?;
+    Ok(hashed_key.to_le_bytes())
+}
+
+/// Hash fields by feature view and feature. Holds the features read since the last
+/// [`Self::clear`], which happens when the registry is reloaded.
+#[derive(Default)]
+pub struct FeatureKeyCache {
+    keys: RwLock<HashMap<(Spur, Spur), [u8; 4]>>,
+}
+
+impl FeatureKeyCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hash field of `feature`, hashed on its first use.
+    pub fn get(&self, feature: &Feature) -> Result<[u8; 4]> {
+        let id = (feature.feature_view_name, feature.feature_name);
+        if let Some(key) = self.keys.read().unwrap_or_else(|e| e.into_inner()).get(&id) {
+            return Ok(*key);
+        }
+        let key = hash_feature_key(feature)?;
+        self.keys
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, key);
+        Ok(key)
+    }
+
+    pub fn clear(&self) {
+        self.keys.write().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_keys_match_feast() -> Result<()> {
+        let cache = FeatureKeyCache::new();
+        let feature = Feature::from_names("driver_hourly_stats", "conv_rate");
+        let expected = hash_feature_key(&feature)?;
+        assert_eq!(
+            expected,
+            murmur3::murmur3_32(&mut &b"driver_hourly_stats:conv_rate"[..], 0)?.to_le_bytes()
+        );
+
+        assert_eq!(cache.get(&feature)?, expected);
+        assert_eq!(cache.get(&feature)?, expected);
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+        Ok(())
+    }
+}
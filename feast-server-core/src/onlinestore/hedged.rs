@@ -0,0 +1,291 @@
+//! Hedged reads: a read which has not completed within a latency percentile of recent
+//! reads is sent again to an alternate store and the first successful response is used.
+
+use crate::config::HedgingConfig;
+use crate::feast::types::EntityKey;
+use crate::model::{Feature, HashEntityKey};
+use crate::onlinestore::{OnlineStore, OnlineStoreRow, OnlineWriteRow, VectorSearchResult};
+use anyhow::Result;
+use async_trait::async_trait;
+use lasso::Spur;
+use rustc_hash::FxHashMap as HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Number of recent primary read latencies the hedging delay is computed from.
+const LATENCY_WINDOW: usize = 1024;
+/// The delay is recomputed after this many new samples, and first once the window holds
+/// this many samples.
+const RECOMPUTE_INTERVAL: usize = 128;
+
+/// Sliding window of latencies in microseconds.
+pub(crate) struct LatencyWindow {
+    samples: Vec<u64>,
+    next: usize,
+    since_recompute: usize,
+}
+
+impl LatencyWindow {
+    pub(crate) fn new() -> Self {
+        Self {
+            samples: Vec::with_capacity(LATENCY_WINDOW),
+            next: 0,
+            since_recompute: 0,
+        }
+    }
+
+    /// Record a sample, returning the new percentile value when it is due for recomputation.
+    pub(crate) fn record(&mut self, micros: u64, percentile: f64) -> Option<u64> {
+        if self.samples.len() < LATENCY_WINDOW {
+            self.samples.push(micros);
+        } else {
+            self.samples[self.next] = micros;
+        }
+        self.next = (self.next + 1) % LATENCY_WINDOW;
+        self.since_recompute += 1;
+        if self.since_recompute < RECOMPUTE_INTERVAL {
+            return None;
+        }
+        self.since_recompute = 0;
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        let rank = ((percentile / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+        Some(sorted[rank.min(sorted.len() - 1)])
+    }
+}
+
+/// Online store sending reads to `primary` and hedging slow reads to `alternate`.
+/// Writes only go to `primary`.
+pub struct HedgedOnlineStore {
+    primary: Arc<dyn OnlineStore>,
+    alternate: Arc<dyn OnlineStore>,
+    config: HedgingConfig,
+    delay_micros: AtomicU64,
+    latencies: Mutex<LatencyWindow>,
+    hedged_reads: AtomicU64,
+}
+
+impl HedgedOnlineStore {
+    pub fn new(
+        primary: Arc<dyn OnlineStore>,
+        alternate: Arc<dyn OnlineStore>,
+        config: HedgingConfig,
+    ) -> Self {
+        let initial_delay = config
+            .initial_delay_ms
+            .clamp(config.min_delay_ms, config.max_delay_ms);
+        Self {
+            primary,
+            alternate,
+            delay_micros: AtomicU64::new(initial_delay * 1000),
+            config,
+            latencies: Mutex::new(LatencyWindow::new()),
+            hedged_reads: AtomicU64::new(0),
+        }
+    }
+
+    /// Current delay after which reads are hedged.
+    pub fn hedge_delay(&self) -> Duration {
+        Duration::from_micros(self.delay_micros.load(Ordering::Relaxed))
+    }
+
+    /// Number of reads sent to the alternate store since creation.
+    pub fn hedged_reads(&self) -> u64 {
+        self.hedged_reads.load(Ordering::Relaxed)
+    }
+
+    fn record_latency(&self, latency: Duration) {
+        let recomputed = self
+            .latencies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record(latency.as_micros() as u64, self.config.percentile);
+        if let Some(micros) = recomputed {
+            let delay = micros.clamp(
+                self.config.min_delay_ms * 1000,
+                self.config.max_delay_ms * 1000,
+            );
+            self.delay_micros.store(delay, Ordering::Relaxed);
+        }
+    }
+}
+
+#[async_trait]
+impl OnlineStore for HedgedOnlineStore {
+    async fn get_feature_values(
+        &self,
+        features: HashMap<HashEntityKey, Vec<Feature>>,
+    ) -> Result<Vec<OnlineStoreRow>> {
+        let start = Instant::now();
+        let delay = self.hedge_delay();
+        let hedge_features = features.clone();
+        let mut primary = self.primary.get_feature_values(features);
+        tokio::select! {
+            result = &mut primary => {
+                self.record_latency(start.elapsed());
+                return result;
+            }
+            _ = tokio::time::sleep(delay) => {}
+        }
+
+        self.hedged_reads.fetch_add(1, Ordering::Relaxed);
+        debug!("Hedging online store read after {:?}", delay);
+        let mut alternate = self.alternate.get_feature_values(hedge_features);
+        tokio::select! {
+            result = &mut primary => {
+                self.record_latency(start.elapsed());
+                match result {
+                    Ok(rows) => Ok(rows),
+                    Err(err) => {
+                        debug!("Primary read failed, waiting for hedged read: {:#}", err);
+                        alternate.await.map_err(|_| err)
+                    }
+                }
+            }
+            result = &mut alternate => {
+                // The primary read is abandoned, its latency is at least the elapsed time
+                self.record_latency(start.elapsed());
+                match result {
+                    Ok(rows) => Ok(rows),
+                    Err(_) => primary.await,
+                }
+            }
+        }
+    }
+
+    async fn online_write_batch(
+        &self,
+        feature_view_name: Spur,
+        rows: Vec<OnlineWriteRow>,
+    ) -> Result<()> {
+        self.primary
+            .online_write_batch(feature_view_name, rows)
+            .await
+    }
+
+    async fn delete_entity_keys(
+        &self,
+        feature_view_name: Spur,
+        feature_names: Vec<Spur>,
+        entity_keys: Vec<EntityKey>,
+    ) -> Result<u64> {
+        self.primary
+            .delete_entity_keys(feature_view_name, feature_names, entity_keys)
+            .await
+    }
+
+    async fn search_similar(
+        &self,
+        feature_view_name: Spur,
+        feature_name: Spur,
+        query: Vec<f32>,
+        top_k: usize,
+    ) -> Result<Vec<VectorSearchResult>> {
+        self.primary
+            .search_similar(feature_view_name, feature_name, query, top_k)
+            .await
+    }
+
+    /// Readiness follows the primary store, which serves every read that is not hedged.
+    async fn health_check(&self) -> Result<()> {
+        self.primary.health_check().await
+    }
+
+    async fn missing_tables(&self, feature_view_names: Vec<Spur>) -> Result<Vec<Spur>> {
+        self.primary.missing_tables(feature_view_names).await
+    }
+
+    async fn sample_entity_keys(
+        &self,
+        feature_view_names: Vec<Spur>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>> {
+        self.primary
+            .sample_entity_keys(feature_view_names, limit)
+            .await
+    }
+
+    fn registry_reloaded(&self) {
+        self.primary.registry_reloaded();
+        self.alternate.registry_reloaded();
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        let primary = self.primary.shutdown().await;
+        self.alternate.shutdown().await?;
+        primary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    struct DelayedStore {
+        delay: Duration,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl OnlineStore for DelayedStore {
+        async fn get_feature_values(
+            &self,
+            _features: HashMap<HashEntityKey, Vec<Feature>>,
+        ) -> Result<Vec<OnlineStoreRow>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            Ok(Vec::new())
+        }
+    }
+
+    fn hedged_store(primary_delay_ms: u64, alternate_calls: Arc<AtomicUsize>) -> HedgedOnlineStore {
+        let primary = DelayedStore {
+            delay: Duration::from_millis(primary_delay_ms),
+            calls: Arc::new(AtomicUsize::new(0)),
+        };
+        let alternate = DelayedStore {
+            delay: Duration::from_millis(1),
+            calls: alternate_calls,
+        };
+        let config = HedgingConfig {
+            initial_delay_ms: 20,
+            ..Default::default()
+        };
+        HedgedOnlineStore::new(Arc::new(primary), Arc::new(alternate), config)
+    }
+
+    #[tokio::test]
+    async fn fast_reads_are_not_hedged() -> Result<()> {
+        let alternate_calls = Arc::new(AtomicUsize::new(0));
+        let store = hedged_store(1, alternate_calls.clone());
+        store.get_feature_values(HashMap::default()).await?;
+        assert_eq!(alternate_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(store.hedged_reads(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn slow_reads_are_hedged() -> Result<()> {
+        let alternate_calls = Arc::new(AtomicUsize::new(0));
+        let store = hedged_store(5_000, alternate_calls.clone());
+        let start = Instant::now();
+        store.get_feature_values(HashMap::default()).await?;
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(alternate_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(store.hedged_reads(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn delay_follows_latency_percentile() {
+        let mut window = LatencyWindow::new();
+        let mut percentile = None;
+        for micros in 1..=RECOMPUTE_INTERVAL as u64 {
+            percentile = window.record(micros * 100, 99.0);
+        }
+        assert_eq!(percentile, Some(12_700));
+    }
+}
@@ -0,0 +1,539 @@
+//! Online store keeping feature rows in process memory, for tests and demos.
+//!
+//! Rows are loaded from fixtures or written through pushes. Fixture values carry no type
+//! information: integers are stored as `Int64`, other numbers as `Double`, and strings,
+//! booleans and lists of those as their matching types. Integer entity values match
+//! entity columns of both `Int32` and `Int64` type.
+
+use crate::config::{EntityKeySerializationVersion, InMemoryFixture};
+use crate::feast::types::value::Val;
+use crate::feast::types::{EntityKey, Value, value_type};
+use crate::intern;
+use crate::key_serialization::serialize_key;
+use crate::model::{Feature, HashEntityKey};
+use crate::onlinestore::{OnlineStore, OnlineStoreRow, OnlineWriteRow};
+use crate::proto_utils::json_to_proto_value;
+use crate::registry::FeatureRegistryService;
+use crate::util::{csv_cell_to_json, parse_json_timestamp};
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use rustc_hash::FxHashMap as HashMap;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::io::Read;
+use std::path::Path;
+use std::sync::RwLock;
+
+const EVENT_TIMESTAMP_COLUMN: &str = "event_timestamp";
+const CREATED_TIMESTAMP_COLUMN: &str = "created_timestamp";
+
+struct StoredValue {
+    value: Value,
+    event_ts: DateTime<Utc>,
+    created_ts: Option<DateTime<Utc>>,
+}
+
+/// Feature view name to serialized entity key to the stored features of the entity.
+type Tables = HashMap<Spur, HashMap<Vec<u8>, HashMap<Spur, StoredValue>>>;
+
+/// Row of a JSON fixture. A fixture holds an array of rows.
+#[derive(Debug, Deserialize)]
+struct JsonFixtureRow {
+    feature_view: String,
+    entities: serde_json::Map<String, JsonValue>,
+    features: serde_json::Map<String, JsonValue>,
+    event_timestamp: Option<JsonValue>,
+    created_timestamp: Option<JsonValue>,
+}
+
+#[derive(Default)]
+pub struct InMemoryOnlineStore {
+    tables: RwLock<Tables>,
+}
+
+impl InMemoryOnlineStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store with the rows of `fixtures`, whose paths are resolved against `cwd`. The types
+    /// of CSV cells are inferred.
+    pub fn from_fixtures(fixtures: &[InMemoryFixture], cwd: Option<&str>) -> Result<Self> {
+        Self::load_fixtures(fixtures, cwd, |_, _| None)
+    }
+
+    /// Store with the rows of `fixtures`, converting CSV cells to the types of the columns
+    /// of their feature view in `registry`.
+    pub async fn from_registry_fixtures(
+        fixtures: &[InMemoryFixture],
+        cwd: Option<&str>,
+        registry: &dyn FeatureRegistryService,
+    ) -> Result<Self> {
+        let mut views = HashMap::default();
+        for name in fixtures.iter().filter_map(|f| f.feature_view.as_deref()) {
+            let view = registry.feature_view(intern::request_key(name)?).await?;
+            views.insert(name, view);
+        }
+        Self::load_fixtures(fixtures, cwd, |view, column| {
+            views.get(view)?.column_type(column)
+        })
+    }
+
+    fn load_fixtures(
+        fixtures: &[InMemoryFixture],
+        cwd: Option<&str>,
+        column_type: impl Fn(&str, &str) -> Option<value_type::Enum>,
+    ) -> Result<Self> {
+        let store = Self::new();
+        for fixture in fixtures {
+            let path = Path::new(cwd.unwrap_or("")).join(&fixture.path);
+            let file = std::fs::File::open(&path)
+                .with_context(|| format!("Cannot open fixture {}", path.display()))?;
+            let is_csv = path.extension().is_some_and(|ext| ext == "csv");
+            let loaded = if is_csv {
+                let feature_view = fixture.feature_view.as_deref().ok_or_else(|| {
+                    anyhow!("CSV fixture {} requires a feature_view", path.display())
+                })?;
+                store.load_csv(feature_view, &fixture.join_keys, file, |column| {
+                    column_type(feature_view, column)
+                })
+            } else {
+                store.load_json(file)
+            };
+            let count = loaded.with_context(|| format!("Invalid fixture {}", path.display()))?;
+            tracing::info!("Loaded {} rows from fixture {}", count, path.display());
+        }
+        Ok(store)
+    }
+
+    /// Load a JSON array of rows shaped as
+    /// `{"feature_view": .., "entities": {..}, "features": {..}, "event_timestamp": ..}`.
+    /// Returns the number of loaded rows.
+    pub fn load_json(&self, reader: impl Read) -> Result<usize> {
+        let rows: Vec<JsonFixtureRow> = serde_json::from_reader(reader)?;
+        let count = rows.len();
+        let rodeo = intern::rodeo_ref();
+        let mut tables = self.tables.write().unwrap_or_else(|e| e.into_inner());
+        for row in rows {
+            let write_row = fixture_row(
+                row.entities.iter().map(|(k, v)| (k.as_str(), v)),
+                row.features.iter().map(|(k, v)| (k.as_str(), v)),
+                row.event_timestamp.as_ref(),
+                row.created_timestamp.as_ref(),
+                |_| None,
+            )?;
+            insert(
+                &mut tables,
+                rodeo.get_or_intern(&row.feature_view),
+                write_row,
+            )?;
+        }
+        Ok(count)
+    }
+
+    /// Load CSV rows of `feature_view`. The header names the `join_keys` columns, optional
+    /// `event_timestamp` and `created_timestamp` columns and the feature columns; empty cells
+    /// are stored as missing values. Cells are converted to the type `column_type` gives
+    /// their column, or inferred. Returns the number of loaded rows.
+    pub fn load_csv(
+        &self,
+        feature_view: &str,
+        join_keys: &[String],
+        reader: impl Read,
+        column_type: impl Fn(&str) -> Option<value_type::Enum>,
+    ) -> Result<usize> {
+        let mut csv_reader = csv::Reader::from_reader(reader);
+        let headers = csv_reader.headers()?.clone();
+        if let Some(missing) = join_keys
+            .iter()
+            .find(|key| !headers.iter().any(|h| h == key.as_str()))
+        {
+            return Err(anyhow!("Missing join key column {}", missing));
+        }
+        let feature_view = intern::rodeo_ref().get_or_intern(feature_view);
+        let mut tables = self.tables.write().unwrap_or_else(|e| e.into_inner());
+        let mut count = 0;
+        for record in csv_reader.records() {
+            let cells: Vec<(&str, JsonValue)> = headers
+                .iter()
+                .zip(record?.iter())
+                .map(|(header, cell)| (header, csv_cell_to_json(cell, column_type(header))))
+                .collect();
+            let column = |name: &str| {
+                cells
+                    .iter()
+                    .find(|(header, _)| *header == name)
+                    .map(|(_, value)| value)
+                    .filter(|value| !value.is_null())
+            };
+            let is_feature = |name: &str| {
+                name != EVENT_TIMESTAMP_COLUMN
+                    && name != CREATED_TIMESTAMP_COLUMN
+                    && !join_keys.iter().any(|key| key == name)
+            };
+            let write_row = fixture_row(
+                cells
+                    .iter()
+                    .filter(|(name, _)| join_keys.iter().any(|key| key == name))
+                    .map(|(name, value)| (*name, value)),
+                cells
+                    .iter()
+                    .filter(|(name, _)| is_feature(name))
+                    .map(|(name, value)| (*name, value)),
+                column(EVENT_TIMESTAMP_COLUMN),
+                column(CREATED_TIMESTAMP_COLUMN),
+                &column_type,
+            )?;
+            insert(&mut tables, feature_view, write_row)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// Integer entity values are stored as `Int64`, so keys built for `Int32` and `Int64`
+/// entity columns find the same rows.
+fn normalize_entity_key(entity_key: &EntityKey) -> EntityKey {
+    let entity_values = entity_key
+        .entity_values
+        .iter()
+        .map(|value| match value.val {
+            Some(Val::Int32Val(i)) => Value {
+                val: Some(Val::Int64Val(i.into())),
+            },
+            _ => value.clone(),
+        })
+        .collect();
+    EntityKey {
+        join_keys: entity_key.join_keys.clone(),
+        entity_values,
+    }
+}
+
+fn storage_key(entity_key: &EntityKey) -> Result<Vec<u8>> {
+    serialize_key(
+        &normalize_entity_key(entity_key),
+        EntityKeySerializationVersion::V3,
+    )
+}
+
+fn insert(tables: &mut Tables, feature_view: Spur, row: OnlineWriteRow) -> Result<()> {
+    let key = storage_key(&row.entity_key)?;
+    let features = tables
+        .entry(feature_view)
+        .or_default()
+        .entry(key)
+        .or_default();
+    for (feature_name, value) in row.values {
+        features.insert(
+            feature_name,
+            StoredValue {
+                value,
+                event_ts: row.event_ts,
+                created_ts: row.created_ts,
+            },
+        );
+    }
+    Ok(())
+}
+
+fn fixture_row<'a>(
+    entities: impl Iterator<Item = (&'a str, &'a JsonValue)>,
+    features: impl Iterator<Item = (&'a str, &'a JsonValue)>,
+    event_timestamp: Option<&JsonValue>,
+    created_timestamp: Option<&JsonValue>,
+    column_type: impl Fn(&str) -> Option<value_type::Enum>,
+) -> Result<OnlineWriteRow> {
+    let mut join_keys = Vec::new();
+    let mut entity_values = Vec::new();
+    for (name, value) in entities {
+        join_keys.push(name.to_string());
+        entity_values.push(fixture_value(value, column_type(name))?);
+    }
+    if join_keys.is_empty() {
+        return Err(anyhow!("Fixture row without entity values"));
+    }
+    let rodeo = intern::rodeo_ref();
+    let values = features
+        .map(|(name, value)| {
+            let value = fixture_value(value, column_type(name))?;
+            Ok((rodeo.get_or_intern(name), value))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(OnlineWriteRow {
+        entity_key: EntityKey {
+            join_keys,
+            entity_values,
+        },
+        values,
+        event_ts: event_timestamp
+            .map(parse_json_timestamp)
+            .transpose()?
+            .unwrap_or_else(Utc::now),
+        created_ts: created_timestamp.map(parse_json_timestamp).transpose()?,
+    })
+}
+
+fn infer_value_type(json: &JsonValue) -> Option<value_type::Enum> {
+    use value_type::Enum;
+    match json {
+        JsonValue::Bool(_) => Some(Enum::Bool),
+        JsonValue::Number(n) if n.is_i64() => Some(Enum::Int64),
+        JsonValue::Number(_) => Some(Enum::Double),
+        JsonValue::String(_) => Some(Enum::String),
+        JsonValue::Array(items) => match items.first().map(infer_value_type)? {
+            Some(Enum::Bool) => Some(Enum::BoolList),
+            Some(Enum::Int64) if items.iter().all(|item| item.is_i64()) => Some(Enum::Int64List),
+            Some(Enum::Int64 | Enum::Double) => Some(Enum::DoubleList),
+            Some(Enum::String) => Some(Enum::StringList),
+            _ => None,
+        },
+        JsonValue::Null | JsonValue::Object(_) => None,
+    }
+}
+
+/// Value of a fixture cell of a column of `value_type`, or of the type inferred from the
+/// JSON value when the column type is not known.
+fn fixture_value(json: &JsonValue, value_type: Option<value_type::Enum>) -> Result<Value> {
+    if json.is_null() {
+        return Ok(Value { val: None });
+    }
+    let value_type = match value_type {
+        Some(value_type) => value_type,
+        None => {
+            infer_value_type(json).ok_or_else(|| anyhow!("Unsupported fixture value {}", json))?
+        }
+    };
+    json_to_proto_value(json, value_type)
+}
+
+#[async_trait]
+impl OnlineStore for InMemoryOnlineStore {
+    async fn get_feature_values(
+        &self,
+        features: HashMap<HashEntityKey, Vec<Feature>>,
+    ) -> Result<Vec<OnlineStoreRow>> {
+        let tables = self.tables.read().unwrap_or_else(|e| e.into_inner());
+        let mut rows = Vec::new();
+        for (entity_key, requested) in features {
+            let key = storage_key(&entity_key.0)?;
+            for feature in requested {
+                let Some(stored) = tables
+                    .get(&feature.feature_view_name)
+                    .and_then(|table| table.get(&key))
+                    .and_then(|features| features.get(&feature.feature_name))
+                else {
+                    continue;
+                };
+                rows.push(OnlineStoreRow {
+                    feature_view_name: feature.feature_view_name,
+                    entity_key: entity_key.clone(),
+                    feature_name: feature.feature_name,
+                    value: stored.value.clone(),
+                    event_ts: stored.event_ts,
+                    created_ts: stored.created_ts,
+                    invalid: false,
+                    undecoded: None,
+                });
+            }
+        }
+        Ok(rows)
+    }
+
+    async fn online_write_batch(
+        &self,
+        feature_view_name: Spur,
+        rows: Vec<OnlineWriteRow>,
+    ) -> Result<()> {
+        let mut tables = self.tables.write().unwrap_or_else(|e| e.into_inner());
+        for row in rows {
+            insert(&mut tables, feature_view_name, row)?;
+        }
+        Ok(())
+    }
+
+    async fn delete_entity_keys(
+        &self,
+        feature_view_name: Spur,
+        feature_names: Vec<Spur>,
+        entity_keys: Vec<EntityKey>,
+    ) -> Result<u64> {
+        let mut tables = self.tables.write().unwrap_or_else(|e| e.into_inner());
+        let Some(table) = tables.get_mut(&feature_view_name) else {
+            return Ok(0);
+        };
+        let mut deleted = 0;
+        for entity_key in entity_keys {
+            let key = storage_key(&entity_key)?;
+            let Some(features) = table.get_mut(&key) else {
+                continue;
+            };
+            let stored_count = features.len();
+            features.retain(|name, _| !feature_names.contains(name));
+            if features.len() < stored_count {
+                deleted += 1;
+            }
+            if features.is_empty() {
+                table.remove(&key);
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn driver_key(value: Val) -> HashEntityKey {
+        HashEntityKey(Arc::new(EntityKey {
+            join_keys: vec!["driver_id".to_string()],
+            entity_values: vec![Value { val: Some(value) }],
+        }))
+    }
+
+    async fn read(
+        store: &InMemoryOnlineStore,
+        key: HashEntityKey,
+        features: &[&str],
+    ) -> Result<Vec<OnlineStoreRow>> {
+        let features = features
+            .iter()
+            .map(|feature| {
+                let (view, name) = feature.split_once(':').unwrap();
+                Feature::from_names(view, name)
+            })
+            .collect();
+        store
+            .get_feature_values([(key, features)].into_iter().collect())
+            .await
+    }
+
+    #[tokio::test]
+    async fn load_json_fixture() -> Result<()> {
+        let store = InMemoryOnlineStore::new();
+        let fixture = r#"[
+            {
+                "feature_view": "driver_hourly_stats",
+                "entities": {"driver_id": 1001},
+                "features": {"conv_rate": 0.5, "avg_daily_trips": 12, "tags": ["a", "b"]},
+                "event_timestamp": "2025-09-12T14:00:00Z"
+            }
+        ]"#;
+        assert_eq!(store.load_json(fixture.as_bytes())?, 1);
+
+        let mut rows = read(
+            &store,
+            driver_key(Val::Int32Val(1001)),
+            &[
+                "driver_hourly_stats:conv_rate",
+                "driver_hourly_stats:avg_daily_trips",
+                "driver_hourly_stats:tags",
+                "driver_hourly_stats:acc_rate",
+            ],
+        )
+        .await?;
+        rows.sort_by_key(|row| intern::rodeo_ref().resolve(&row.feature_name));
+        let values: Vec<_> = rows.iter().map(|row| row.value.val.clone()).collect();
+        assert_eq!(
+            values,
+            [
+                Some(Val::Int64Val(12)),
+                Some(Val::DoubleVal(0.5)),
+                Some(Val::StringListVal(crate::feast::types::StringList {
+                    val: vec!["a".to_string(), "b".to_string()]
+                })),
+            ]
+        );
+        assert_eq!(rows[0].event_ts.to_rfc3339(), "2025-09-12T14:00:00+00:00");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn load_csv_fixture() -> Result<()> {
+        let store = InMemoryOnlineStore::new();
+        let fixture = "driver_id,conv_rate,event_timestamp,active\n\
+                       1001,0.25,2025-09-12 14:00:00,true\n\
+                       1002,,2025-09-12 15:00:00,false\n";
+        let loaded = store.load_csv(
+            "driver_hourly_stats",
+            &["driver_id".to_string()],
+            fixture.as_bytes(),
+            |_| None,
+        )?;
+        assert_eq!(loaded, 2);
+
+        let rows = read(
+            &store,
+            driver_key(Val::Int64Val(1002)),
+            &[
+                "driver_hourly_stats:conv_rate",
+                "driver_hourly_stats:active",
+            ],
+        )
+        .await?;
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().any(|row| row.value.val.is_none()));
+        assert!(
+            rows.iter()
+                .any(|row| row.value.val == Some(Val::BoolVal(false)))
+        );
+
+        let missing_key = store.load_csv(
+            "driver_hourly_stats",
+            &["truck_id".to_string()],
+            fixture.as_bytes(),
+            |_| None,
+        );
+        assert!(missing_key.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn load_csv_cells_with_column_types() -> Result<()> {
+        use value_type::Enum;
+        let store = InMemoryOnlineStore::new();
+        let fixture = "customer_id,code,active,score\n007,042,true,1\n";
+        store.load_csv(
+            "customer_profile",
+            &["customer_id".to_string()],
+            fixture.as_bytes(),
+            |column| match column {
+                "customer_id" | "code" => Some(Enum::String),
+                "active" => Some(Enum::Bool),
+                "score" => Some(Enum::Float),
+                _ => None,
+            },
+        )?;
+
+        let key = HashEntityKey(Arc::new(EntityKey {
+            join_keys: vec!["customer_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::StringVal("007".to_string())),
+            }],
+        }));
+        let rows = read(
+            &store,
+            key,
+            &[
+                "customer_profile:code",
+                "customer_profile:active",
+                "customer_profile:score",
+            ],
+        )
+        .await?;
+        let value_of = |name: &str| {
+            let name = intern::rodeo_ref().get_or_intern(name);
+            rows.iter()
+                .find(|row| row.feature_name == name)
+                .and_then(|row| row.value.val.clone())
+        };
+        assert_eq!(value_of("code"), Some(Val::StringVal("042".to_string())));
+        assert_eq!(value_of("active"), Some(Val::BoolVal(true)));
+        assert_eq!(value_of("score"), Some(Val::FloatVal(1.0)));
+        Ok(())
+    }
+}
@@ -0,0 +1,469 @@
+//! Qdrant backed online store.
+//!
+//! Points follow the layout written by the Feast Python Qdrant online store: one point per
+//! (entity key, feature) pair carrying `entity_key` (hex encoded serialized key), `feature_name`,
+//! `feature_value` (base64 encoded `Value` proto), `timestamp` and `created_ts` in the payload.
+//! Besides key lookups the store answers approximate nearest neighbour searches through
+//! [`OnlineStore::search_similar`].
+
+use crate::config::{DistanceMetric, EntityKeySerializationVersion, OnlineStoreConfig};
+use crate::feast::types::Value;
+use crate::intern;
+use crate::key_serialization::{deserialize_key, serialize_key};
+use crate::model::{Feature, HashEntityKey};
+use crate::onlinestore::{OnlineStore, OnlineStoreRow, VectorSearchResult, record_corrupt_key};
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use prost::Message;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use serde::Deserialize;
+use serde_json::{Value as JsonValue, json};
+use std::sync::Arc;
+
+const SCROLL_PAGE_SIZE: usize = 256;
+const API_KEY_HEADER: &str = "api-key";
+
+#[derive(Debug, Deserialize)]
+struct QdrantResponse<T> {
+    result: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScrollResult {
+    points: Vec<QdrantPoint>,
+    next_page_offset: Option<JsonValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantPoint {
+    #[serde(default)]
+    score: Option<f32>,
+    #[serde(default)]
+    payload: HashMap<String, JsonValue>,
+}
+
+pub(crate) struct QdrantOnlineStore {
+    client: reqwest::Client,
+    url: String,
+    collection: Option<String>,
+    api_key: Option<String>,
+    vector_name: Option<String>,
+}
+
+impl QdrantOnlineStore {
+    fn collection_name(&self, feature_view_name: Spur) -> String {
+        match &self.collection {
+            Some(collection) => collection.clone(),
+            None => intern::rodeo_ref().resolve(&feature_view_name).to_string(),
+        }
+    }
+
+    async fn post<T>(&self, path: &str, body: JsonValue) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut request = self
+            .client
+            .post(format!("{}{}", self.url, path))
+            .json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Qdrant request to {} failed", path))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Qdrant request to {} failed with status {}: {}",
+                path,
+                status,
+                text
+            ));
+        }
+        let parsed: QdrantResponse<T> = response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse Qdrant response from {}", path))?;
+        Ok(parsed.result)
+    }
+
+    async fn check_collection(
+        &self,
+        collection: &str,
+        distance_metric: DistanceMetric,
+    ) -> Result<()> {
+        let mut request = self
+            .client
+            .get(format!("{}/collections/{}", self.url, collection));
+        if let Some(api_key) = &self.api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Cannot reach Qdrant at {}", self.url))?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Qdrant collection '{}' is not available: status {}",
+                collection,
+                response.status()
+            ));
+        }
+        let info: QdrantResponse<JsonValue> = response.json().await?;
+        let vectors = &info.result["config"]["params"]["vectors"];
+        let vector_params = match &self.vector_name {
+            Some(name) => &vectors[name],
+            None => vectors,
+        };
+        if let Some(distance) = vector_params["distance"].as_str()
+            && distance != distance_metric.as_qdrant_str()
+        {
+            return Err(anyhow!(
+                "Qdrant collection '{}' uses distance {}, but {} is configured",
+                collection,
+                distance,
+                distance_metric.as_qdrant_str()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Body of a search for the points of `feature_name` closest to `query`.
+    fn search_body(&self, feature_name: &str, query: Vec<f32>, top_k: usize) -> JsonValue {
+        let vector = match &self.vector_name {
+            Some(name) => json!({"name": name, "vector": query}),
+            None => json!(query),
+        };
+        json!({
+            "vector": vector,
+            "limit": top_k,
+            "with_payload": true,
+            "filter": {
+                "must": [
+                    {"key": "feature_name", "match": {"value": feature_name}},
+                ]
+            },
+        })
+    }
+
+    async fn scroll_view(
+        &self,
+        feature_view_name: Spur,
+        entity_keys: &HashMap<String, HashEntityKey>,
+        features: &HashSet<Spur>,
+    ) -> Result<Vec<OnlineStoreRow>> {
+        let rodeo = intern::rodeo_ref();
+        let collection = self.collection_name(feature_view_name);
+        let feature_names: Vec<&str> = features.iter().map(|f| rodeo.resolve(f)).collect();
+        let key_filter: Vec<&str> = entity_keys.keys().map(String::as_str).collect();
+        let path = format!("/collections/{}/points/scroll", collection);
+
+        let mut rows = Vec::new();
+        let mut offset: Option<JsonValue> = None;
+        loop {
+            let mut body = json!({
+                "filter": {
+                    "must": [
+                        {"key": "entity_key", "match": {"any": key_filter}},
+                        {"key": "feature_name", "match": {"any": feature_names}},
+                    ]
+                },
+                "limit": SCROLL_PAGE_SIZE,
+                "with_payload": true,
+                "with_vector": false,
+            });
+            if let Some(offset) = offset.take() {
+                body["offset"] = offset;
+            }
+            let page: ScrollResult = self.post(&path, body).await?;
+            for point in page.points {
+                let hex_key = payload_str(&point.payload, "entity_key")?;
+                let entity_key = entity_keys.get(hex_key).cloned().ok_or_else(|| {
+                    anyhow!(
+                        "Qdrant returned unexpected entity key {} for collection {}",
+                        hex_key,
+                        collection
+                    )
+                })?;
+                rows.push(point_to_row(feature_view_name, entity_key, &point.payload)?);
+            }
+            match page.next_page_offset {
+                Some(next) if !next.is_null() => offset = Some(next),
+                _ => break,
+            }
+        }
+        Ok(rows)
+    }
+}
+
+fn payload_str<'a>(payload: &'a HashMap<String, JsonValue>, key: &str) -> Result<&'a str> {
+    payload
+        .get(key)
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| anyhow!("Qdrant point payload is missing '{}'", key))
+}
+
+fn payload_timestamp(payload: &HashMap<String, JsonValue>, key: &str) -> Option<DateTime<Utc>> {
+    match payload.get(key)? {
+        JsonValue::String(s) => DateTime::parse_from_rfc3339(s)
+            .map(|ts| ts.with_timezone(&Utc))
+            .or_else(|_| {
+                chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+                    .map(|ts| ts.and_utc())
+            })
+            .ok(),
+        JsonValue::Number(n) => n
+            .as_f64()
+            .and_then(|secs| DateTime::<Utc>::from_timestamp_micros((secs * 1_000_000.0) as i64)),
+        _ => None,
+    }
+}
+
+fn point_to_row(
+    feature_view_name: Spur,
+    entity_key: HashEntityKey,
+    payload: &HashMap<String, JsonValue>,
+) -> Result<OnlineStoreRow> {
+    let rodeo = intern::rodeo_ref();
+    let feature_name = payload_str(payload, "feature_name")?;
+    let encoded_value = payload_str(payload, "feature_value")?;
+    let value_bytes = BASE64.decode(encoded_value).with_context(|| {
+        format!(
+            "Failed to decode base64 value for feature {}:{}",
+            rodeo.resolve(&feature_view_name),
+            feature_name
+        )
+    })?;
+    let value = Value::decode(value_bytes.as_slice()).with_context(|| {
+        format!(
+            "Failed to decode value for feature {}:{}",
+            rodeo.resolve(&feature_view_name),
+            feature_name
+        )
+    })?;
+    Ok(OnlineStoreRow {
+        feature_view_name,
+        entity_key,
+        feature_name: rodeo.get_or_intern(feature_name),
+        value,
+        event_ts: payload_timestamp(payload, "timestamp").unwrap_or(DateTime::<Utc>::UNIX_EPOCH),
+        created_ts: payload_timestamp(payload, "created_ts"),
+        invalid: false,
+        undecoded: None,
+    })
+}
+
+fn point_to_search_result(
+    feature_view_name: Spur,
+    point: &QdrantPoint,
+) -> Result<VectorSearchResult> {
+    let key_bytes = hex::decode(payload_str(&point.payload, "entity_key")?)?;
+    let entity_key = deserialize_key(key_bytes, EntityKeySerializationVersion::V3)
+        .inspect_err(|_| record_corrupt_key("qdrant", feature_view_name))?;
+    let row = point_to_row(
+        feature_view_name,
+        HashEntityKey(Arc::new(entity_key)),
+        &point.payload,
+    )?;
+    Ok(VectorSearchResult {
+        row,
+        score: point.score.unwrap_or_default(),
+    })
+}
+
+#[async_trait]
+impl OnlineStore for QdrantOnlineStore {
+    async fn get_feature_values(
+        &self,
+        features: HashMap<HashEntityKey, Vec<Feature>>,
+    ) -> Result<Vec<OnlineStoreRow>> {
+        let mut view_to_keys: HashMap<Spur, HashMap<String, HashEntityKey>> = HashMap::default();
+        let mut view_features: HashMap<Spur, HashSet<Spur>> = HashMap::default();
+        for (entity_key, feature_list) in features {
+            let hex_key = hex::encode(serialize_key(
+                &entity_key.0,
+                EntityKeySerializationVersion::V3,
+            )?);
+            for feature in feature_list {
+                view_features
+                    .entry(feature.feature_view_name)
+                    .or_default()
+                    .insert(feature.feature_name);
+                view_to_keys
+                    .entry(feature.feature_view_name)
+                    .or_default()
+                    .insert(hex_key.clone(), entity_key.clone());
+            }
+        }
+
+        let mut rows = Vec::new();
+        for (view_name, keys) in view_to_keys {
+            let features = view_features.remove(&view_name).unwrap_or_default();
+            rows.extend(self.scroll_view(view_name, &keys, &features).await?);
+        }
+        Ok(rows)
+    }
+
+    async fn search_similar(
+        &self,
+        feature_view_name: Spur,
+        feature_name: Spur,
+        query: Vec<f32>,
+        top_k: usize,
+    ) -> Result<Vec<VectorSearchResult>> {
+        let collection = self.collection_name(feature_view_name);
+        let body = self.search_body(intern::rodeo_ref().resolve(&feature_name), query, top_k);
+        let points: Vec<QdrantPoint> = self
+            .post(&format!("/collections/{}/points/search", collection), body)
+            .await?;
+        points
+            .iter()
+            .map(|point| point_to_search_result(feature_view_name, point))
+            .collect()
+    }
+}
+
+pub async fn new(
+    url: String,
+    collection: Option<String>,
+    distance_metric: DistanceMetric,
+    api_key: Option<String>,
+    vector_name: Option<String>,
+) -> Result<QdrantOnlineStore> {
+    let store = QdrantOnlineStore {
+        client: reqwest::Client::new(),
+        url: url.trim_end_matches('/').to_string(),
+        collection,
+        api_key,
+        vector_name,
+    };
+    if let Some(collection) = store.collection.clone() {
+        store.check_collection(&collection, distance_metric).await?;
+    }
+    Ok(store)
+}
+
+pub async fn from_config(config: OnlineStoreConfig) -> Result<QdrantOnlineStore> {
+    match config {
+        OnlineStoreConfig::Qdrant {
+            url,
+            collection,
+            distance_metric,
+            api_key,
+            vector_name,
+        } => new(url, collection, distance_metric, api_key, vector_name).await,
+        _ => Err(anyhow!("Invalid config for QdrantOnlineStore")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feast::types::EntityKey;
+    use crate::feast::types::value::Val;
+
+    fn payload(value: JsonValue) -> HashMap<String, JsonValue> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn converts_point_payload_to_row() -> Result<()> {
+        let entity_key = HashEntityKey(Arc::new(EntityKey {
+            join_keys: vec!["item_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(7)),
+            }],
+        }));
+        let value = Value {
+            val: Some(Val::StringVal("red shoes".to_string())),
+        };
+        let point_payload = payload(json!({
+            "entity_key": hex::encode(serialize_key(&entity_key.0, EntityKeySerializationVersion::V3)?),
+            "feature_name": "title",
+            "feature_value": BASE64.encode(value.encode_to_vec()),
+            "timestamp": "2024-05-01T10:00:00",
+            "created_ts": "2024-05-01T10:00:05+00:00",
+        }));
+        let view = intern::rodeo_ref().get_or_intern("items");
+        let row = point_to_row(view, entity_key.clone(), &point_payload)?;
+        assert_eq!(row.value, value);
+        assert_eq!(row.entity_key, entity_key);
+        assert_eq!(row.event_ts.timestamp(), 1_714_557_600);
+        assert_eq!(row.created_ts.map(|ts| ts.timestamp()), Some(1_714_557_605));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_payload_field_is_an_error() {
+        let view = intern::rodeo_ref().get_or_intern("items");
+        let entity_key = HashEntityKey(Arc::new(EntityKey::default()));
+        let result = point_to_row(view, entity_key, &payload(json!({"feature_name": "title"})));
+        assert!(result.is_err());
+    }
+
+    fn store(vector_name: Option<&str>) -> QdrantOnlineStore {
+        QdrantOnlineStore {
+            client: reqwest::Client::new(),
+            url: "http://localhost:6333".to_string(),
+            collection: None,
+            api_key: None,
+            vector_name: vector_name.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn search_body_filters_feature_and_names_vector() {
+        let body = store(Some("embedding")).search_body("title", vec![0.5, 1.0], 3);
+        assert_eq!(
+            body,
+            json!({
+                "vector": {"name": "embedding", "vector": [0.5, 1.0]},
+                "limit": 3,
+                "with_payload": true,
+                "filter": {
+                    "must": [
+                        {"key": "feature_name", "match": {"value": "title"}},
+                    ]
+                },
+            })
+        );
+        let body = store(None).search_body("title", vec![0.5, 1.0], 3);
+        assert_eq!(body["vector"], json!([0.5, 1.0]));
+    }
+
+    #[test]
+    fn converts_scored_point_to_search_result() -> Result<()> {
+        let entity_key = EntityKey {
+            join_keys: vec!["item_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(7)),
+            }],
+        };
+        let value = Value {
+            val: Some(Val::StringVal("red shoes".to_string())),
+        };
+        let point: QdrantPoint = serde_json::from_value(json!({
+            "id": 1,
+            "score": 0.875,
+            "payload": {
+                "entity_key": hex::encode(serialize_key(&entity_key, EntityKeySerializationVersion::V3)?),
+                "feature_name": "title",
+                "feature_value": BASE64.encode(value.encode_to_vec()),
+                "timestamp": "2024-05-01T10:00:00",
+            },
+        }))?;
+        let view = intern::rodeo_ref().get_or_intern("items");
+        let result = point_to_search_result(view, &point)?;
+        assert_eq!(result.score, 0.875);
+        assert_eq!(*result.row.entity_key.0, entity_key);
+        assert_eq!(result.row.value, value);
+        Ok(())
+    }
+}
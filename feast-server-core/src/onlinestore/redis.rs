@@ -1,15 +1,20 @@
-use crate::config::{OnlineStoreConfig, RedisType};
-use crate::feast::types::Value as FeastValue;
+use crate::config::{OnlineStoreConfig, RedisConnectionConfig, RedisType};
+use crate::feast::types::{EntityKey, Value as FeastValue};
 use crate::intern;
 use crate::model::{Feature, HashEntityKey};
-use crate::onlinestore::{OnlineStore, OnlineStoreRow};
+use crate::onlinestore::feature_keys::FeatureKeyCache;
+use crate::onlinestore::hedged::HedgedOnlineStore;
+use crate::onlinestore::{OnlineStore, OnlineStoreRow, record_decode_failure};
 use anyhow::{Context, Result, anyhow};
+use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use lasso::Spur;
 use prost::Message;
 use prost_types::Timestamp;
-use redis::aio::{ConnectionLike, ConnectionManager, MultiplexedConnection};
+use redis::aio::{
+    ConnectionLike, ConnectionManager, ConnectionManagerConfig, MultiplexedConnection,
+};
 use redis::cluster::{ClusterClient, ClusterClientBuilder};
 use redis::cluster_async::ClusterConnection;
 use redis::sentinel::SentinelServerType::Master;
@@ -17,28 +22,19 @@ use redis::sentinel::{
     Sentinel, SentinelClient, SentinelClientBuilder, SentinelNodeConnectionInfo, SentinelServerType,
 };
 use redis::{
-    AsyncCommands, Client, ClientTlsConfig, Commands, ConnectionAddr, ConnectionInfo,
-    FromRedisValue, IntoConnectionInfo, RedisConnectionInfo, RedisResult, TlsCertificates, TlsMode,
+    AsyncCommands, Client, ClientTlsConfig, Commands, ConnectionAddr, ConnectionInfo, ErrorKind,
+    FromRedisValue, IntoConnectionInfo, Pipeline, RedisConnectionInfo, RedisError, RedisFuture,
+    RedisResult, TlsCertificates, TlsMode,
 };
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use rustls::crypto::CryptoProvider;
-use smallvec::SmallVec;
+use std::future::Future;
 use std::hash::Hash;
+use std::path::PathBuf;
 use std::sync::Arc;
-
-fn feature_redis_key(feature: &Feature) -> Result<Vec<u8>> {
-    let rodeo = intern::rodeo_ref();
-    let feature_view_name = rodeo.resolve(&feature.feature_view_name);
-    let feature_name = rodeo.resolve(&feature.feature_name);
-    let mut key_bytes: SmallVec<[u8; 64]> =
-        SmallVec::with_capacity(feature_view_name.len() + 1 + feature_name.len());
-    key_bytes.extend_from_slice(feature_view_name.as_bytes());
-    key_bytes.push(b':');
-    key_bytes.extend_from_slice(feature_name.as_bytes());
-    let mut reader = std::io::Cursor::new(&key_bytes[..]);
-    let hashed_key = murmur3::murmur3_32(&mut reader, 0)?;
-    Ok(Vec::from(hashed_key.to_le_bytes()))
-}
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
 
 fn parse_redis_connection_string(connection_string: &str) -> Result<RedisConnectionOption> {
     let mut result = RedisConnectionOption::default();
@@ -77,36 +73,422 @@ fn parse_redis_connection_string(connection_string: &str) -> Result<RedisConnect
 trait RedisStore {
     fn get_connection(&self) -> impl ConnectionLike + Send + Sync;
     fn get_project(&self) -> &str;
+
+    /// Hash fields of the features read by this store, dropped when the registry is
+    /// reloaded.
+    fn feature_keys(&self) -> &FeatureKeyCache;
+
+    /// Reload the slot assignment of a cluster after a node redirected a command. Returns
+    /// whether the command should be sent again.
+    fn refresh_topology(&self) -> impl Future<Output = bool> + Send {
+        async { false }
+    }
+
+    /// Stop the keepalive and reconnection task of the connection, for connections which
+    /// have one.
+    fn close(&self) {}
+}
+
+/// Number of reloads of the Redis cluster slot assignment after MOVED or ASK redirects.
+pub(crate) const TOPOLOGY_REFRESHES_METRIC: &str = "feast_redis_topology_refreshes_total";
+
+/// Number of connections reopened to load changed TLS certificates.
+pub(crate) const TLS_RELOADS_METRIC: &str = "feast_redis_tls_reloads_total";
+
+fn is_redirect(err: &RedisError) -> bool {
+    matches!(err.kind(), ErrorKind::Moved | ErrorKind::Ask)
+}
+
+/// Run `pipeline`, sending it once more after a cluster node redirected it, which happens
+/// while the cluster is resharded.
+async fn query_pipeline<S, T>(store: &S, pipeline: &Pipeline) -> RedisResult<T>
+where
+    S: RedisStore + Sync,
+    T: FromRedisValue,
+{
+    let mut connection = store.get_connection();
+    match pipeline.query_async(&mut connection).await {
+        Err(err) if is_redirect(&err) && store.refresh_topology().await => {
+            let mut connection = store.get_connection();
+            pipeline.query_async(&mut connection).await
+        }
+        result => result,
+    }
+}
+
+/// Opens a new connection replacing the current one of a [`ManagedConnection`].
+type Connector<C> = Box<dyn Fn() -> RedisFuture<'static, C> + Send + Sync>;
+
+/// Connection shared by the requests of a store. A background task pings it every
+/// keepalive interval, so that the connection notices when it was dropped while idle and
+/// reconnects before a request needs it, and replaces it once it reaches its maximum age.
+/// The task stops when the connection is dropped.
+struct ManagedConnection<C> {
+    current: Arc<ArcSwap<C>>,
+    connect: Option<Arc<Connector<C>>>,
+    /// Time of the last replacement by [`Self::reconnect`].
+    reconnected: tokio::sync::Mutex<Option<Instant>>,
+    upkeep: Option<JoinHandle<()>>,
+    /// Task reopening the connection when its certificates change.
+    reload: Option<JoinHandle<()>>,
+}
+
+impl<C> ManagedConnection<C>
+where
+    C: ConnectionLike + Clone + Send + Sync + 'static,
+{
+    /// Connection kept up as `config` says. Unless `certificate_files` is empty, it is
+    /// reopened on SIGHUP and when one of the files changes.
+    fn new(
+        connection: C,
+        connect: Connector<C>,
+        config: &RedisConnectionConfig,
+        certificate_files: Vec<PathBuf>,
+    ) -> Self {
+        let keepalive = (config.keepalive_interval_secs > 0)
+            .then(|| Duration::from_secs(config.keepalive_interval_secs));
+        let max_age = config.max_connection_age_secs.map(Duration::from_secs);
+        let connection = Self::with_periods(connection, connect, keepalive, max_age);
+        if certificate_files.is_empty() {
+            return connection;
+        }
+        let poll_interval = config.tls_reload_interval_secs.map(Duration::from_secs);
+        connection.with_certificate_reload(certificate_files, poll_interval)
+    }
+
+    fn with_periods(
+        connection: C,
+        connect: Connector<C>,
+        keepalive: Option<Duration>,
+        max_age: Option<Duration>,
+    ) -> Self {
+        let current = Arc::new(ArcSwap::from_pointee(connection));
+        let connect = Arc::new(connect);
+        let upkeep = keepalive.into_iter().chain(max_age).min().map(|period| {
+            tokio::spawn(upkeep_connection(
+                current.clone(),
+                connect.clone(),
+                keepalive.is_some(),
+                max_age,
+                period,
+            ))
+        });
+        Self {
+            current,
+            connect: Some(connect),
+            reconnected: tokio::sync::Mutex::new(None),
+            upkeep,
+            reload: None,
+        }
+    }
+
+    /// Reopen the connection on SIGHUP and, when `poll_interval` is set, once one of
+    /// `files` changed, so that rotated certificates are used without a restart.
+    fn with_certificate_reload(
+        mut self,
+        files: Vec<PathBuf>,
+        poll_interval: Option<Duration>,
+    ) -> Self {
+        if let Some(connect) = &self.connect {
+            self.reload = Some(tokio::spawn(reload_certificates(
+                self.current.clone(),
+                connect.clone(),
+                files,
+                poll_interval,
+            )));
+        }
+        self
+    }
+
+    /// Connection without upkeep.
+    fn fixed(connection: C) -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(connection)),
+            connect: None,
+            reconnected: tokio::sync::Mutex::new(None),
+            upkeep: None,
+            reload: None,
+        }
+    }
+
+    fn get(&self) -> C {
+        C::clone(&self.current.load())
+    }
+
+    /// Stop the upkeep and reload tasks, which hold a clone of the connection. The
+    /// connection is closed once the store and the commands in flight drop theirs.
+    fn close(&self) {
+        for task in self.upkeep.iter().chain(&self.reload) {
+            task.abort();
+        }
+    }
+
+    /// Replace the connection by a new one, unless it was replaced less than
+    /// `min_interval` ago. Concurrent callers wait for a single replacement. Returns
+    /// whether a new connection was opened.
+    async fn reconnect(&self, min_interval: Duration) -> RedisResult<bool> {
+        let Some(connect) = &self.connect else {
+            return Ok(false);
+        };
+        let mut reconnected = self.reconnected.lock().await;
+        if reconnected.is_some_and(|at| at.elapsed() < min_interval) {
+            return Ok(false);
+        }
+        let connection = connect().await?;
+        self.current.store(Arc::new(connection));
+        *reconnected = Some(Instant::now());
+        Ok(true)
+    }
+}
+
+impl<C> Drop for ManagedConnection<C> {
+    fn drop(&mut self) {
+        for task in self.upkeep.iter().chain(&self.reload) {
+            task.abort();
+        }
+    }
+}
+
+async fn upkeep_connection<C>(
+    current: Arc<ArcSwap<C>>,
+    connect: Arc<Connector<C>>,
+    keepalive: bool,
+    max_age: Option<Duration>,
+    period: Duration,
+) where
+    C: ConnectionLike + Clone + Send + Sync + 'static,
+{
+    let mut opened = Instant::now();
+    let mut ticks = tokio::time::interval_at(Instant::now() + period, period);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        if let Some(max_age) = max_age
+            && opened.elapsed() >= max_age
+        {
+            match connect().await {
+                Ok(connection) => {
+                    current.store(Arc::new(connection));
+                    opened = Instant::now();
+                    tracing::debug!("Replaced Redis connection older than {:?}", max_age);
+                    continue;
+                }
+                Err(err) => tracing::warn!("Cannot replace Redis connection: {}", err),
+            }
+        }
+        if keepalive {
+            let mut connection = C::clone(&current.load());
+            if let Err(err) = redis::cmd("PING").query_async::<()>(&mut connection).await {
+                tracing::warn!("Redis keepalive PING failed: {}", err);
+            }
+        }
+    }
+}
+
+async fn reload_certificates<C>(
+    current: Arc<ArcSwap<C>>,
+    connect: Arc<Connector<C>>,
+    files: Vec<PathBuf>,
+    poll_interval: Option<Duration>,
+) where
+    C: ConnectionLike + Clone + Send + Sync + 'static,
+{
+    let mut hangup = HangupSignal::new();
+    let mut versions = file_versions(&files);
+    loop {
+        let reason = tokio::select! {
+            _ = hangup.recv() => "SIGHUP",
+            _ = sleep_or_wait(poll_interval) => {
+                let current_versions = file_versions(&files);
+                if current_versions == versions {
+                    continue;
+                }
+                versions = current_versions;
+                "certificate files changed"
+            }
+        };
+        match connect().await {
+            Ok(connection) => {
+                current.store(Arc::new(connection));
+                metrics::counter!(TLS_RELOADS_METRIC).increment(1);
+                tracing::info!(
+                    "Reopened Redis connection with reloaded certificates ({})",
+                    reason
+                );
+            }
+            // The current connection stays in use until a reload succeeds
+            Err(err) => tracing::error!(
+                "Cannot reload Redis certificates ({}): {:#}",
+                reason,
+                with_tls_hint(err.into())
+            ),
+        }
+    }
+}
+
+/// Length and modification time of each file, `None` for files which cannot be read.
+fn file_versions(files: &[PathBuf]) -> Vec<Option<(u64, std::time::SystemTime)>> {
+    files
+        .iter()
+        .map(|file| {
+            let metadata = std::fs::metadata(file).ok()?;
+            Some((metadata.len(), metadata.modified().ok()?))
+        })
+        .collect()
+}
+
+async fn sleep_or_wait(period: Option<Duration>) {
+    match period {
+        Some(period) => tokio::time::sleep(period).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// SIGHUP of the process, never received on other platforms.
+struct HangupSignal {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl HangupSignal {
+    fn new() -> Self {
+        #[cfg(unix)]
+        {
+            let signal =
+                tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+            if signal.is_none() {
+                tracing::warn!(
+                    "Cannot listen for SIGHUP, Redis certificates are not reloaded on it"
+                );
+            }
+            Self { signal }
+        }
+        #[cfg(not(unix))]
+        Self {}
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            if signal.recv().await.is_some() {
+                return;
+            }
+            self.signal = None;
+        }
+        std::future::pending().await
+    }
+}
+
+/// Client configuration errors, e.g. unreadable certificate files, as errors of a connection
+/// attempt.
+fn invalid_client_config(err: anyhow::Error) -> RedisError {
+    RedisError::from((
+        ErrorKind::InvalidClientConfig,
+        "Invalid Redis client configuration",
+        format!("{:#}", err),
+    ))
+}
+
+/// Explanation of the TLS handshake failure reported in `message`, naming the options which
+/// fix it.
+fn tls_error_hint(message: &str) -> Option<&'static str> {
+    let message = message.to_ascii_lowercase().replace(['_', ' '], "");
+    let hint = if message.contains("unknownissuer") {
+        "the server certificate is not signed by a trusted CA, set ssl_ca_certs to the CA certificate of the server or ssl_cert_reqs=none to skip verification"
+    } else if message.contains("notvalidforname") {
+        "the server certificate is not issued for the host of the connection string, connect by a name of the certificate or set ssl_cert_reqs=none to skip verification"
+    } else if message.contains("expired") || message.contains("notvalidyet") {
+        "the server certificate is expired or not valid yet, check the certificate of the server and the clock of this host"
+    } else if message.contains("certificaterequired") {
+        "the server requires a client certificate, set ssl_certfile and ssl_keyfile"
+    } else if message.contains("badcertificate") || message.contains("unknownca") {
+        "the server rejected the client certificate of ssl_certfile"
+    } else if message.contains("corruptmessage") || message.contains("invalidcontenttype") {
+        "the server did not answer with TLS, check that the port serves TLS or remove ssl=true"
+    } else {
+        return None;
+    };
+    Some(hint)
+}
+
+/// `err` with an explanation added when it is a TLS handshake failure.
+fn with_tls_hint(err: anyhow::Error) -> anyhow::Error {
+    match tls_error_hint(&format!("{:#}", err)) {
+        Some(hint) => err.context(format!("TLS handshake failed: {}", hint)),
+        None => err,
+    }
 }
 
 pub(crate) struct RedisSingleNodeOnlineStore {
     project: String,
-    connection_manager: ConnectionManager,
+    connection_manager: ManagedConnection<ConnectionManager>,
+    feature_keys: FeatureKeyCache,
 }
 
 impl RedisStore for RedisSingleNodeOnlineStore {
     fn get_connection(&self) -> impl ConnectionLike + Send + Sync {
-        self.connection_manager.clone()
+        self.connection_manager.get()
     }
 
     fn get_project(&self) -> &str {
         &self.project
     }
+
+    fn feature_keys(&self) -> &FeatureKeyCache {
+        &self.feature_keys
+    }
+
+    fn close(&self) {
+        self.connection_manager.close();
+    }
 }
 
 pub(crate) struct RedisClusterOnlineStore {
     project: String,
-    cluster_connection: ClusterConnection,
+    cluster_connection: ManagedConnection<ClusterConnection>,
+    /// Minimum time between two reloads of the slot assignment.
+    topology_refresh_interval: Duration,
+    feature_keys: FeatureKeyCache,
 }
 
 impl RedisStore for RedisClusterOnlineStore {
     fn get_connection(&self) -> impl ConnectionLike + Send + Sync {
-        self.cluster_connection.clone()
+        self.cluster_connection.get()
     }
 
     fn get_project(&self) -> &str {
         &self.project
     }
+
+    fn feature_keys(&self) -> &FeatureKeyCache {
+        &self.feature_keys
+    }
+
+    fn close(&self) {
+        self.cluster_connection.close();
+    }
+
+    /// A new cluster connection reads the slot assignment from the nodes. Redirects within
+    /// the refresh interval are retried on the connection of the last refresh.
+    async fn refresh_topology(&self) -> bool {
+        match self
+            .cluster_connection
+            .reconnect(self.topology_refresh_interval)
+            .await
+        {
+            Ok(refreshed) => {
+                if refreshed {
+                    metrics::counter!(TOPOLOGY_REFRESHES_METRIC).increment(1);
+                    tracing::info!("Reloaded Redis cluster topology after a redirect");
+                }
+                true
+            }
+            Err(err) => {
+                tracing::warn!("Cannot reload Redis cluster topology: {}", err);
+                false
+            }
+        }
+    }
 }
 
 const SENTINEL_MASTER_SERVICE_DEFAULT_NAME: &str = "mymaster";
@@ -117,6 +499,7 @@ struct RedisSentinelOnlineStore {
     project: String,
     _client: SentinelClient,
     connection_pool: MultiplexedConnection,
+    feature_keys: FeatureKeyCache,
 }
 
 // TODO: Implement reconnection logic for Sentinel connections
@@ -128,6 +511,10 @@ impl RedisStore for RedisSentinelOnlineStore {
     fn get_project(&self) -> &str {
         &self.project
     }
+
+    fn feature_keys(&self) -> &FeatureKeyCache {
+        &self.feature_keys
+    }
 }
 
 struct SentinelConnectionOption {
@@ -151,7 +538,9 @@ impl TryFrom<SentinelConnectionOption> for SentinelClient {
         )?;
         if redis_options.common_options.ssl == Some(true) {
             let certificates: TlsCertificates = (&redis_options.common_options).try_into()?;
-            builder = builder.set_client_to_redis_certificates(certificates.clone());
+            builder = builder
+                .set_client_to_redis_tls_mode(redis_options.common_options.ssl_cert_reqs.tls_mode())
+                .set_client_to_redis_certificates(certificates.clone());
             builder = builder.set_client_to_sentinel_certificates(certificates);
         }
         if let Some(username) = redis_options.common_options.username.take() {
@@ -188,6 +577,40 @@ struct CommonConnectionOptions {
     ssl_certfile: Option<String>,
     ssl_keyfile: Option<String>,
     ssl_ca_certs: Option<String>,
+    ssl_cert_reqs: SslCertReqs,
+}
+
+impl CommonConnectionOptions {
+    /// Certificate and key files read when a TLS connection is opened.
+    fn certificate_files(&self) -> Vec<PathBuf> {
+        [&self.ssl_certfile, &self.ssl_keyfile, &self.ssl_ca_certs]
+            .into_iter()
+            .flatten()
+            .map(PathBuf::from)
+            .collect()
+    }
+}
+
+/// Verification of the server certificate of TLS connections, the `ssl_cert_reqs` option of
+/// redis-py.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SslCertReqs {
+    /// Accept any server certificate.
+    None,
+    /// Verify the server certificate. Servers always present one, so like Python's `ssl`
+    /// module for clients this is the same as `Required`.
+    Optional,
+    #[default]
+    Required,
+}
+
+impl SslCertReqs {
+    fn tls_mode(self) -> TlsMode {
+        match self {
+            SslCertReqs::None => TlsMode::Insecure,
+            SslCertReqs::Optional | SslCertReqs::Required => TlsMode::Secure,
+        }
+    }
 }
 
 impl TryFrom<&CommonConnectionOptions> for TlsCertificates {
@@ -269,7 +692,7 @@ impl IntoConnectionInfo for SingleNodeConnectionOption {
             ConnectionAddr::TcpTls {
                 host: self.host,
                 port: self.port,
-                insecure: false,
+                insecure: self.common_options.ssl_cert_reqs == SslCertReqs::None,
                 tls_params: None,
             }
         } else {
@@ -314,28 +737,82 @@ impl TryFrom<RedisConnectionOption> for ClusterClient {
     type Error = anyhow::Error;
 
     fn try_from(value: RedisConnectionOption) -> Result<Self> {
-        let hosts: Vec<RedisClusterHost> = value.clone().into();
-        let mut builder = ClusterClientBuilder::new(hosts);
-        let RedisConnectionOption {
-            hosts: _,
-            common_options,
-        } = value;
-        if common_options.ssl == Some(true) {
-            CryptoProvider::install_default(rustls::crypto::ring::default_provider())
-                .map_err(|_| anyhow!("Cannot initialize TLS provider"))?;
-            let certificates = TlsCertificates::try_from(&common_options)?;
-            builder = builder.certs(certificates);
-        }
-        if let Some(username) = common_options.username {
-            builder = builder.username(username);
-        }
-        if let Some(password) = common_options.password {
-            builder = builder.password(password);
-        }
-        Ok(builder.build()?)
+        build_cluster_client(value, false, &RedisConnectionConfig::default())
     }
 }
 
+/// Build a cluster client, optionally routing reads to replicas.
+fn build_cluster_client(
+    value: RedisConnectionOption,
+    read_from_replicas: bool,
+    connection_config: &RedisConnectionConfig,
+) -> Result<ClusterClient> {
+    let hosts: Vec<RedisClusterHost> = value.clone().into();
+    let mut builder = ClusterClientBuilder::new(hosts);
+    if read_from_replicas {
+        builder = builder.read_from_replicas();
+    }
+    if let Some(retries) = connection_config.reconnect_retries {
+        builder = builder.retries(retries);
+    }
+    if let Some(max_delay) = connection_config.reconnect_max_delay_ms {
+        builder = builder.max_retry_wait(max_delay);
+    }
+    if let Some(timeout) = connection_config.connection_timeout_ms {
+        builder = builder.connection_timeout(Duration::from_millis(timeout));
+    }
+    if let Some(timeout) = connection_config.response_timeout_ms {
+        builder = builder.response_timeout(Duration::from_millis(timeout));
+    }
+    let RedisConnectionOption {
+        hosts: _,
+        common_options,
+    } = value;
+    if common_options.ssl == Some(true) {
+        install_crypto_provider()?;
+        let certificates = TlsCertificates::try_from(&common_options)?;
+        builder = builder
+            .tls(common_options.ssl_cert_reqs.tls_mode())
+            .certs(certificates);
+    }
+    if let Some(username) = common_options.username {
+        builder = builder.username(username);
+    }
+    if let Some(password) = common_options.password {
+        builder = builder.password(password);
+    }
+    Ok(builder.build()?)
+}
+
+fn connection_manager_config(connection_config: &RedisConnectionConfig) -> ConnectionManagerConfig {
+    let mut config = ConnectionManagerConfig::new();
+    if let Some(retries) = connection_config.reconnect_retries {
+        config = config.set_number_of_retries(retries as usize);
+    }
+    if let Some(max_delay) = connection_config.reconnect_max_delay_ms {
+        config = config.set_max_delay(max_delay);
+    }
+    if let Some(timeout) = connection_config.connection_timeout_ms {
+        config = config.set_connection_timeout(Duration::from_millis(timeout));
+    }
+    if let Some(timeout) = connection_config.response_timeout_ms {
+        config = config.set_response_timeout(Duration::from_millis(timeout));
+    }
+    config
+}
+
+/// Install the process wide TLS provider unless it was installed before, e.g. by a
+/// previously created store.
+fn install_crypto_provider() -> Result<()> {
+    if CryptoProvider::get_default().is_none()
+        && CryptoProvider::install_default(rustls::crypto::ring::default_provider()).is_err()
+        && CryptoProvider::get_default().is_none()
+    {
+        return Err(anyhow!("Cannot initialize TLS provider"));
+    }
+    Ok(())
+}
+
 #[inline]
 fn parse_common_options(
     result: &mut CommonConnectionOptions,
@@ -364,6 +841,20 @@ fn parse_common_options(
         "ssl_certfile" => result.ssl_certfile = Some(value.to_string()),
         "ssl_keyfile" => result.ssl_keyfile = Some(value.to_string()),
         "ssl_ca_certs" => result.ssl_ca_certs = Some(value.to_string()),
+        "ssl_cert_reqs" => {
+            result.ssl_cert_reqs = match value.to_ascii_lowercase().as_str() {
+                "none" => SslCertReqs::None,
+                "optional" => SslCertReqs::Optional,
+                "required" => SslCertReqs::Required,
+                _ => {
+                    return Err(anyhow!(
+                        "Invalid ssl_cert_reqs value at index {}: {}, supported values are 'none', 'optional', 'required'",
+                        i,
+                        value
+                    ));
+                }
+            };
+        }
         other => {
             return Err(anyhow!(
                 "Invalid connection option at index {}: {}",
@@ -375,11 +866,32 @@ fn parse_common_options(
     Ok(())
 }
 
+/// Client of a single node, reading the certificate files of TLS connections.
+fn single_node_client(option: &SingleNodeConnectionOption) -> Result<Client> {
+    if option.common_options.ssl == Some(true) {
+        install_crypto_provider()?;
+        let certificates = TlsCertificates::try_from(&option.common_options)?;
+        Ok(Client::build_with_tls(option.clone(), certificates)?)
+    } else {
+        Ok(Client::open(option.clone())?)
+    }
+}
+
+/// Certificate files watched for changes, none for plain connections.
+fn tls_certificate_files(options: &CommonConnectionOptions) -> Vec<PathBuf> {
+    if options.ssl == Some(true) {
+        options.certificate_files()
+    } else {
+        Vec::new()
+    }
+}
+
 async fn check_redis_connection(client: &Client) -> Result<()> {
     let mut conn = client
         .get_multiplexed_async_connection()
         .await
-        .with_context(|| anyhow!("Cannot establish redis connection"))?;
+        .with_context(|| anyhow!("Cannot establish redis connection"))
+        .map_err(with_tls_hint)?;
     let ping_response: String = redis::cmd("PING").query_async(&mut conn).await?;
     if ping_response.to_uppercase() != "PONG" {
         return Err(anyhow!(
@@ -390,44 +902,82 @@ async fn check_redis_connection(client: &Client) -> Result<()> {
     Ok(())
 }
 
+/// Connect to Redis. Cluster connections created with `read_from_replicas` send reads
+/// to replica nodes.
 pub async fn new(
     project: String,
     redis_type: RedisType,
     connection_string: String,
     sentinel_master: Option<String>,
+    read_from_replicas: bool,
+    connection_config: &RedisConnectionConfig,
 ) -> Result<Arc<dyn OnlineStore>> {
     let connection_option = parse_redis_connection_string(&connection_string)?;
     match redis_type {
         RedisType::SingleNode => {
-            let client = if connection_option.common_options.ssl == Some(true) {
-                CryptoProvider::install_default(rustls::crypto::ring::default_provider())
-                    .map_err(|_| anyhow!("Cannot initialize TLS provider"))?;
-                let certificates = TlsCertificates::try_from(&connection_option.common_options)?;
-                let single_node_option =
-                    SingleNodeConnectionOption::try_from(connection_option.clone())?;
-                Client::build_with_tls(single_node_option, certificates)?
-            } else {
-                let single_node_option = SingleNodeConnectionOption::try_from(connection_option)?;
-                Client::open(single_node_option)?
-            };
+            let certificate_files = tls_certificate_files(&connection_option.common_options);
+            let single_node_option = SingleNodeConnectionOption::try_from(connection_option)?;
+            let client = single_node_client(&single_node_option)?;
 
             check_redis_connection(&client).await?;
-            let connection_pool = ConnectionManager::new(client).await?;
+            let manager_config = connection_manager_config(connection_config);
+            let connection_pool =
+                ConnectionManager::new_with_config(client, manager_config.clone()).await?;
+            let connect: Connector<ConnectionManager> = Box::new(move || {
+                // A new client reads the certificate files again
+                let client = single_node_client(&single_node_option);
+                let manager_config = manager_config.clone();
+                Box::pin(async move {
+                    let client = client.map_err(invalid_client_config)?;
+                    ConnectionManager::new_with_config(client, manager_config).await
+                })
+            });
             Ok(Arc::new(RedisSingleNodeOnlineStore {
                 project,
-                connection_manager: connection_pool,
+                connection_manager: ManagedConnection::new(
+                    connection_pool,
+                    connect,
+                    connection_config,
+                    certificate_files,
+                ),
+                feature_keys: FeatureKeyCache::new(),
             }))
         }
         RedisType::RedisCluster => {
-            let cluster_client = ClusterClient::try_from(connection_option)?;
-            let mut connection_pool = cluster_client
+            let certificate_files = tls_certificate_files(&connection_option.common_options);
+            let cluster_client = build_cluster_client(
+                connection_option.clone(),
+                read_from_replicas,
+                connection_config,
+            )?;
+            let connection_pool = cluster_client
                 .get_async_connection()
                 .await
-                .with_context(|| anyhow!("Cannot establish redis cluster connection"))?;
+                .with_context(|| anyhow!("Cannot establish redis cluster connection"))
+                .map_err(with_tls_hint)?;
+            let config = connection_config.clone();
+            let connect: Connector<ClusterConnection> = Box::new(move || {
+                // A new client reads the certificate files again
+                let cluster_client =
+                    build_cluster_client(connection_option.clone(), read_from_replicas, &config);
+                Box::pin(async move {
+                    let cluster_client = cluster_client.map_err(invalid_client_config)?;
+                    cluster_client.get_async_connection().await
+                })
+            });
 
             Ok(Arc::new(RedisClusterOnlineStore {
                 project,
-                cluster_connection: connection_pool,
+                cluster_connection: ManagedConnection::new(
+                    connection_pool,
+                    connect,
+                    connection_config,
+                    certificate_files,
+                ),
+                topology_refresh_interval: Duration::from_millis(
+                    connection_config.topology_refresh_interval_ms,
+                ),
+                feature_keys: FeatureKeyCache::new(),
             }))
         }
         RedisType::Sentinel => {
@@ -436,7 +986,11 @@ pub async fn new(
                 redis_options: connection_option,
             };
             let mut sentinel_client = SentinelClient::try_from(sentinel_options)?;
-            let sentinel_connection = sentinel_client.get_async_connection().await?;
+            let sentinel_connection = sentinel_client
+                .get_async_connection()
+                .await
+                .with_context(|| anyhow!("Cannot establish redis sentinel connection"))
+                .map_err(with_tls_hint)?;
             {
                 let mut conn_for_ping = sentinel_connection.clone();
                 let ping_response: String = conn_for_ping.ping().await?;
@@ -451,6 +1005,7 @@ pub async fn new(
                 project,
                 _client: sentinel_client,
                 connection_pool: sentinel_connection,
+                feature_keys: FeatureKeyCache::new(),
             }))
         }
     }
@@ -464,7 +1019,34 @@ pub async fn from_config(
             redis_type,
             connection_string,
             sentinel_master,
-        } => new(project, redis_type, connection_string, sentinel_master).await,
+            hedging,
+            connection,
+        } => {
+            let store = new(
+                project.clone(),
+                redis_type.clone(),
+                connection_string.clone(),
+                sentinel_master.clone(),
+                false,
+                &connection,
+            )
+            .await?;
+            let Some(hedging) = hedging else {
+                return Ok(store);
+            };
+            // Hedged reads go to replicas of a cluster, other deployments get a second
+            // connection which is not blocked behind the slow request
+            let alternate = new(
+                project,
+                redis_type,
+                connection_string,
+                sentinel_master,
+                true,
+                &connection,
+            )
+            .await?;
+            Ok(Arc::new(HedgedOnlineStore::new(store, alternate, hedging)))
+        }
         _ => Err(anyhow!("Invalid config for RedisOnlineStore")),
     }
 }
@@ -475,12 +1057,36 @@ enum RedisRequest<'a> {
         entity_key: &'a HashEntityKey,
         feature_name: Spur,
     },
+    /// Event timestamp of the view.
     TimestampRow {
         entity_key: &'a HashEntityKey,
         feature_view_name: Spur,
     },
 }
 
+/// Hash field prefix of the event timestamp of a feature view, as written by Feast. Feast
+/// keeps no created timestamp in Redis, so rows of Redis stores have none.
+const EVENT_TS_PREFIX: &[u8] = b"_ts:";
+
+fn decode_timestamp(
+    value: Option<Vec<u8>>,
+    feature_view_name: Spur,
+) -> Result<Option<DateTime<Utc>>> {
+    let Some(bytes) = value else {
+        return Ok(None);
+    };
+    let timestamp_proto = Timestamp::decode(bytes.as_slice()).with_context(|| {
+        format!(
+            "Failed to decode timestamp for feature view {}",
+            intern::rodeo_ref().resolve(&feature_view_name)
+        )
+    })?;
+    Ok(DateTime::<Utc>::from_timestamp(
+        timestamp_proto.seconds,
+        timestamp_proto.nanos.max(0) as u32,
+    ))
+}
+
 /// Implement OnlineStore for single-node and cluster Redis online stores
 #[async_trait]
 impl<T> OnlineStore for T
@@ -491,7 +1097,9 @@ where
         &self,
         features: HashMap<HashEntityKey, Vec<Feature>>,
     ) -> Result<Vec<OnlineStoreRow>> {
-        let mut entities: Vec<RedisRequest> = vec![];
+        // Requests of every HMGET of the pipeline, in the order of its fields
+        let mut commands: Vec<(&HashEntityKey, Vec<RedisRequest>)> =
+            Vec::with_capacity(features.len());
 
         let mut pipeline = redis::pipe();
 
@@ -500,6 +1108,7 @@ where
         for (key, feature_vec) in features.iter() {
             let mut seen_views: HashSet<Spur> = HashSet::default();
             let mut feature_keys: Vec<Vec<u8>> = vec![];
+            let mut requests: Vec<RedisRequest> = vec![];
             let mut hset_entity_key = crate::key_serialization::serialize_key(
                 &key.0,
                 crate::config::EntityKeySerializationVersion::V3,
@@ -511,14 +1120,14 @@ where
                 if !seen_views.contains(&view_name) {
                     seen_views.insert(view_name);
                     let view_name_str = rodeo.resolve(&view_name);
-feature_keys.push([b"_ts:", view_name_str.as_bytes()].concat());
-                    entities.push(RedisRequest::TimestampRow {
+                    feature_keys.push([EVENT_TS_PREFIX, view_name_str.as_bytes()].concat());
+                    requests.push(RedisRequest::TimestampRow {
                         entity_key: key,
                         feature_view_name: view_name,
                     });
                 }
-                feature_keys.push(feature_redis_key(feature)?);
-                entities.push(RedisRequest::FeatureRow {
+                feature_keys.push(self.feature_keys().get(feature)?.to_vec());
+                requests.push(RedisRequest::FeatureRow {
                     feature_view_name: view_name,
                     entity_key: key,
                     feature_name,
@@ -526,94 +1135,191 @@ feature_keys.push([b"_ts:", view_name_str.as_bytes()].concat());
             }
 
             pipeline.cmd("HMGET").arg(hset_entity_key).arg(feature_keys);
+            commands.push((key, requests));
         }
 
-        let mut connection = self.get_connection();
-
-        let results: Vec<Vec<Option<Vec<u8>>>> = pipeline.query_async(&mut connection).await?;
-        let result_count: usize = results.iter().map(|v| v.len()).sum();
-        if result_count != entities.len() {
+        let results: Vec<Vec<Option<Vec<u8>>>> = query_pipeline(self, &pipeline).await?;
+        if results.len() != commands.len() {
             return Err(anyhow!(
-                "Mismatched number of results: expected {}, got {}",
-                entities.len(),
-                result_count
+                "Mismatched number of HMGET results: expected {}, got {}",
+                commands.len(),
+                results.len()
             ));
         }
         let mut result_rows: Vec<OnlineStoreRow> = vec![];
         let mut timestamp_map: HashMap<(Spur, &HashEntityKey), Option<DateTime<Utc>>> =
             HashMap::default();
-        for (request, value) in entities.into_iter().zip(results.into_iter().flatten()) {
-            match request {
-                RedisRequest::FeatureRow {
-                    feature_view_name,
-                    entity_key,
-                    feature_name,
-                } => {
-                    let ts = timestamp_map
-                        .get(&(feature_view_name, entity_key))
-                        .cloned()
-                        .flatten()
-                        .unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
-                    let decoded_value = match value {
-                        Some(bytes) => FeastValue::decode(bytes.as_slice()).with_context(|| {
-                            format!(
-                                "Failed to decode value for feature {}:{} from bytes: {:?}",
-                                rodeo.resolve(&feature_view_name),
-                                rodeo.resolve(&feature_name),
-                                bytes
-                            )
-                        })?,
-                        None => FeastValue::default(),
-                    };
-                    result_rows.push(OnlineStoreRow {
+        // Results are matched per command, so that a short reply of one command cannot
+        // shift the values of the following ones
+        for ((entity_key, requests), values) in commands.into_iter().zip(results) {
+            if values.len() != requests.len() {
+                return Err(anyhow!(
+                    "HMGET of entity key {:?} returned {} fields, expected {}",
+                    entity_key.0,
+                    values.len(),
+                    requests.len()
+                ));
+            }
+            for (request, value) in requests.into_iter().zip(values) {
+                match request {
+                    RedisRequest::FeatureRow {
                         feature_view_name,
-                        entity_key: entity_key.clone(),
+                        entity_key,
                         feature_name,
-                        value: decoded_value,
-                        event_ts: ts,
-                        created_ts: None,
-                    });
-                }
-                RedisRequest::TimestampRow {
-                    entity_key,
-                    feature_view_name,
-                } => {
-                    let ts = match value {
-                        Some(bytes) => {
-                            let timestamp_proto = Timestamp::decode(bytes.as_slice())
-                                .with_context(|| {
-                                    format!(
-                                        "Failed to decode timestamp for feature view {}",
-                                        rodeo.resolve(&feature_view_name)
-                                    )
-                                })?;
-                            DateTime::<Utc>::from_timestamp(
-                                timestamp_proto.seconds,
-                                timestamp_proto.nanos.max(0) as u32,
-                            )
-                        }
-                        None => None,
-                    };
-                    timestamp_map.insert((feature_view_name, entity_key), ts);
+                    } => {
+                        let ts = timestamp_map
+                            .get(&(feature_view_name, entity_key))
+                            .cloned()
+                            .flatten()
+                            .unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+                        let decoded = match value {
+                            Some(bytes) => FeastValue::decode(bytes.as_slice()).map_err(|err| {
+                                record_decode_failure(
+                                    "redis",
+                                    feature_view_name,
+                                    rodeo.resolve(&feature_name),
+                                    &entity_key.0,
+                                    &format_args!("{} (bytes {:?})", err, bytes),
+                                );
+                                bytes
+                            }),
+                            None => Ok(FeastValue::default()),
+                        };
+                        let (value, undecoded) = match decoded {
+                            Ok(value) => (value, None),
+                            Err(bytes) => (FeastValue::default(), Some(bytes)),
+                        };
+                        result_rows.push(OnlineStoreRow {
+                            feature_view_name,
+                            entity_key: entity_key.clone(),
+                            feature_name,
+                            invalid: undecoded.is_some(),
+                            undecoded,
+                            value,
+                            event_ts: ts,
+                            created_ts: None,
+                        });
+                    }
+                    RedisRequest::TimestampRow {
+                        entity_key,
+                        feature_view_name,
+                    } => {
+                        let ts = decode_timestamp(value, feature_view_name)?;
+                        timestamp_map.insert((feature_view_name, entity_key), ts);
+                    }
                 }
             }
         }
 
         Ok(result_rows)
     }
+
+    /// Remove the timestamp and feature fields of the view from the hash of every entity
+    /// key. Hashes without fields left are removed by Redis.
+    async fn delete_entity_keys(
+        &self,
+        feature_view_name: Spur,
+        feature_names: Vec<Spur>,
+        entity_keys: Vec<EntityKey>,
+    ) -> Result<u64> {
+        if entity_keys.is_empty() {
+            return Ok(0);
+        }
+        let project_name = self.get_project();
+        let view_name = intern::rodeo_ref().resolve(&feature_view_name);
+        let mut fields = vec![[EVENT_TS_PREFIX, view_name.as_bytes()].concat()];
+        for feature_name in feature_names {
+            let feature = Feature::new(feature_view_name, feature_name);
+            fields.push(self.feature_keys().get(&feature)?.to_vec());
+        }
+        let mut pipeline = redis::pipe();
+        for entity_key in &entity_keys {
+            let mut hset_entity_key = crate::key_serialization::serialize_key(
+                entity_key,
+                crate::config::EntityKeySerializationVersion::V3,
+            )?;
+            hset_entity_key.extend_from_slice(project_name.as_bytes());
+            pipeline.cmd("HDEL").arg(hset_entity_key).arg(&fields);
+        }
+        let removed: Vec<u64> = query_pipeline(self, &pipeline).await?;
+        Ok(removed.iter().filter(|count| **count > 0).count() as u64)
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        let mut connection = self.get_connection();
+        let ping_response: String = redis::cmd("PING").query_async(&mut connection).await?;
+        if ping_response.to_uppercase() != "PONG" {
+            return Err(anyhow!("Unexpected PING response: {}", ping_response));
+        }
+        Ok(())
+    }
+
+    /// Keys of the first `SCAN` batch matching the project. Views share the hashes of their
+    /// entities, so keys are not told apart by view.
+    async fn sample_entity_keys(
+        &self,
+        _feature_view_names: Vec<Spur>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>> {
+        let project = self.get_project();
+        let pattern = format!("*{}", escape_glob(project));
+        let mut connection = self.get_connection();
+        let (_, keys): (u64, Vec<Vec<u8>>) = redis::cmd("SCAN")
+            .arg(0)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(limit.max(100))
+            .query_async(&mut connection)
+            .await?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| key.strip_suffix(project.as_bytes()).map(<[u8]>::to_vec))
+            .take(limit)
+            .collect())
+    }
+
+    fn registry_reloaded(&self) {
+        self.feature_keys().clear();
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        self.close();
+        Ok(())
+    }
+}
+
+/// `value` with the glob characters of `SCAN` patterns escaped.
+fn escape_glob(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
 #[cfg(test)]
 mod tests {
-    use super::new;
+    use super::{
+        Connector, ManagedConnection, RedisStore, SslCertReqs, new, parse_redis_connection_string,
+        query_pipeline, tls_error_hint,
+    };
+    use crate::config::RedisConnectionConfig;
     use crate::feast::types::value::Val;
     use crate::feast::types::{EntityKey, Value};
     use crate::model::{Feature, HashEntityKey};
     use crate::onlinestore::OnlineStore;
+    use crate::onlinestore::feature_keys::{FeatureKeyCache, hash_feature_key};
     use anyhow::Result;
-    use redis::aio::ConnectionManager;
+    use redis::RedisFuture;
+    use redis::aio::{ConnectionLike, ConnectionManager};
     use rustc_hash::FxHashMap as HashMap;
     use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
 
     impl super::RedisSingleNodeOnlineStore {
         async fn new_from_manager(
@@ -622,7 +1328,8 @@ mod tests {
         ) -> Result<Self> {
             Ok(Self {
                 project,
-                connection_manager: connection_pool,
+                connection_manager: super::ManagedConnection::fixed(connection_pool),
+                feature_keys: FeatureKeyCache::new(),
             })
         }
     }
@@ -652,6 +1359,72 @@ mod tests {
         println!("result: {:?}", result);
         Ok(())
     }
+    #[tokio::test]
+    #[ignore]
+    async fn composite_key_test() -> Result<()> {
+        use prost::Message;
+
+        let project = "careful_tomcat";
+        let client = redis::Client::open("redis://127.0.0.1:7000/")?;
+        let mut con = client.get_connection_manager().await?;
+        let feature = Feature::from_names("driver_customer_stats", "trips");
+        let stored_key = EntityKey {
+            join_keys: vec!["customer_id".to_string(), "driver_id".to_string()],
+            entity_values: vec![
+                Value {
+                    val: Some(Val::StringVal("a".to_string())),
+                },
+                Value {
+                    val: Some(Val::Int64Val(1001)),
+                },
+            ],
+        };
+        let mut hset_key = crate::key_serialization::serialize_key(
+            &stored_key,
+            crate::config::EntityKeySerializationVersion::V3,
+        )?;
+        hset_key.extend_from_slice(project.as_bytes());
+        let value = Value {
+            val: Some(Val::Int64Val(7)),
+        };
+        let event_ts = prost_types::Timestamp {
+            seconds: 1_700_000_000,
+            nanos: 0,
+        };
+        redis::cmd("HSET")
+            .arg(&hset_key)
+            .arg(hash_feature_key(&feature)?.as_slice())
+            .arg(value.encode_to_vec())
+            .arg(b"_ts:driver_customer_stats".as_slice())
+            .arg(event_ts.encode_to_vec())
+            .query_async::<()>(&mut con)
+            .await?;
+
+        // Requested in the order of the view's entity columns, not sorted by name
+        let requested_key = HashEntityKey(Arc::new(EntityKey {
+            join_keys: vec!["driver_id".to_string(), "customer_id".to_string()],
+            entity_values: vec![
+                Value {
+                    val: Some(Val::Int64Val(1001)),
+                },
+                Value {
+                    val: Some(Val::StringVal("a".to_string())),
+                },
+            ],
+        }));
+        let redis_store =
+            super::RedisSingleNodeOnlineStore::new_from_manager(project.to_string(), con).await?;
+        let rows = redis_store
+            .get_feature_values(HashMap::from_iter([(requested_key.clone(), vec![feature])]))
+            .await?;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].entity_key, requested_key);
+        assert_eq!(rows[0].value, value);
+        assert_eq!(rows[0].event_ts.timestamp(), event_ts.seconds);
+        assert_eq!(rows[0].created_ts, None);
+        Ok(())
+    }
+
     #[tokio::test]
     #[ignore]
     async fn tls_connection_test() -> Result<()> {
@@ -667,6 +1440,8 @@ mod tests {
                 project_dir, project_dir, project_dir
             ),
             None,
+            false,
+            &RedisConnectionConfig::default(),
         )
         .await?;
         Ok(())
@@ -680,8 +1455,361 @@ mod tests {
             super::RedisType::Sentinel,
             "127.0.0.1:26379".to_string(),
             Some("mymaster".to_string()),
+            false,
+            &RedisConnectionConfig::default(),
         )
         .await?;
         Ok(())
     }
+
+    /// Connection counting the commands sent over it.
+    #[derive(Clone)]
+    struct CountingConnection {
+        id: usize,
+        commands: Arc<AtomicUsize>,
+    }
+
+    impl ConnectionLike for CountingConnection {
+        fn req_packed_command<'a>(
+            &'a mut self,
+            _cmd: &'a redis::Cmd,
+        ) -> RedisFuture<'a, redis::Value> {
+            self.commands.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(redis::Value::SimpleString("PONG".to_string())) })
+        }
+
+        fn req_packed_commands<'a>(
+            &'a mut self,
+            _cmd: &'a redis::Pipeline,
+            _offset: usize,
+            _count: usize,
+        ) -> RedisFuture<'a, Vec<redis::Value>> {
+            Box::pin(async { Ok(vec![]) })
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+    }
+
+    #[tokio::test]
+    async fn managed_connection_pings_and_recycles() {
+        let pings = Arc::new(AtomicUsize::new(0));
+        let opened = Arc::new(AtomicUsize::new(0));
+        let connect: Connector<CountingConnection> = {
+            let (pings, opened) = (pings.clone(), opened.clone());
+            Box::new(move || {
+                let id = opened.fetch_add(1, Ordering::SeqCst) + 1;
+                let commands = pings.clone();
+                Box::pin(async move { Ok(CountingConnection { id, commands }) })
+            })
+        };
+        let initial = CountingConnection {
+            id: 0,
+            commands: pings.clone(),
+        };
+        let connection = ManagedConnection::with_periods(
+            initial,
+            connect,
+            Some(Duration::from_millis(10)),
+            Some(Duration::from_millis(45)),
+        );
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(pings.load(Ordering::SeqCst) >= 3);
+        assert!(opened.load(Ordering::SeqCst) >= 1);
+        assert_eq!(connection.get().id, opened.load(Ordering::SeqCst));
+
+        drop(connection);
+        let pings_after_drop = pings.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(pings.load(Ordering::SeqCst), pings_after_drop);
+    }
+
+    #[tokio::test]
+    async fn managed_connection_reopens_on_certificate_change() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let certificate = dir.path().join("ca.crt");
+        std::fs::write(&certificate, "first")?;
+        let opened = Arc::new(AtomicUsize::new(0));
+        let connect: Connector<CountingConnection> = {
+            let opened = opened.clone();
+            Box::new(move || {
+                let id = opened.fetch_add(1, Ordering::SeqCst) + 1;
+                let commands = Arc::new(AtomicUsize::new(0));
+                Box::pin(async move { Ok(CountingConnection { id, commands }) })
+            })
+        };
+        let initial = CountingConnection {
+            id: 0,
+            commands: Arc::new(AtomicUsize::new(0)),
+        };
+        let connection = ManagedConnection::with_periods(initial, connect, None, None)
+            .with_certificate_reload(vec![certificate.clone()], Some(Duration::from_millis(10)));
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(opened.load(Ordering::SeqCst), 0);
+
+        std::fs::write(&certificate, "rotated")?;
+        for _ in 0..100 {
+            if opened.load(Ordering::SeqCst) > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+        assert_eq!(connection.get().id, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn ssl_cert_reqs_option() -> Result<()> {
+        let options = parse_redis_connection_string(
+            "localhost:6379,ssl=true,ssl_ca_certs=ca.crt,ssl_cert_reqs=NONE",
+        )?;
+        assert_eq!(options.common_options.ssl_cert_reqs, SslCertReqs::None);
+        assert_eq!(
+            options.common_options.certificate_files(),
+            [std::path::PathBuf::from("ca.crt")]
+        );
+        let options = parse_redis_connection_string("localhost:6379,ssl=true")?;
+        assert_eq!(options.common_options.ssl_cert_reqs, SslCertReqs::Required);
+
+        let err = parse_redis_connection_string("localhost:6379,ssl_cert_reqs=maybe").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid ssl_cert_reqs value at index 1: maybe, supported values are 'none', 'optional', 'required'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tls_handshake_errors_are_explained() {
+        let hint = tls_error_hint("invalid peer certificate: UnknownIssuer").unwrap();
+        assert!(hint.contains("ssl_ca_certs"));
+        let hint =
+            tls_error_hint("invalid peer certificate: certificate not valid for name \"redis\"")
+                .unwrap();
+        assert!(hint.contains("ssl_cert_reqs=none"));
+        let hint = tls_error_hint("received fatal alert: CertificateRequired").unwrap();
+        assert!(hint.contains("ssl_certfile"));
+        assert_eq!(tls_error_hint("Connection refused (os error 111)"), None);
+    }
+
+    /// Connection of a cluster node which redirects pipelines while `moved` is set.
+    #[derive(Clone)]
+    struct ReshardedConnection {
+        moved: bool,
+    }
+
+    impl ConnectionLike for ReshardedConnection {
+        fn req_packed_command<'a>(
+            &'a mut self,
+            _cmd: &'a redis::Cmd,
+        ) -> RedisFuture<'a, redis::Value> {
+            Box::pin(async { Ok(redis::Value::Okay) })
+        }
+
+        fn req_packed_commands<'a>(
+            &'a mut self,
+            _cmd: &'a redis::Pipeline,
+            _offset: usize,
+            _count: usize,
+        ) -> RedisFuture<'a, Vec<redis::Value>> {
+            let moved = self.moved;
+            Box::pin(async move {
+                if moved {
+                    Err(redis::RedisError::from((
+                        redis::ErrorKind::Moved,
+                        "An error was signalled by the server",
+                        "3999 127.0.0.1:7001".to_string(),
+                    )))
+                } else {
+                    Ok(vec![redis::Value::Int(1)])
+                }
+            })
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+    }
+
+    struct ReshardedStore {
+        connection: ManagedConnection<ReshardedConnection>,
+        feature_keys: FeatureKeyCache,
+    }
+
+    impl RedisStore for ReshardedStore {
+        fn get_connection(&self) -> impl ConnectionLike + Send + Sync {
+            self.connection.get()
+        }
+
+        fn get_project(&self) -> &str {
+            "project"
+        }
+
+        fn feature_keys(&self) -> &FeatureKeyCache {
+            &self.feature_keys
+        }
+
+        async fn refresh_topology(&self) -> bool {
+            self.connection
+                .reconnect(Duration::from_secs(60))
+                .await
+                .is_ok()
+        }
+    }
+
+    #[tokio::test]
+    async fn redirected_pipelines_are_sent_again_after_topology_refresh() -> Result<()> {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let connect: Connector<ReshardedConnection> = {
+            let opened = opened.clone();
+            Box::new(move || {
+                opened.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { Ok(ReshardedConnection { moved: false }) })
+            })
+        };
+        let store = ReshardedStore {
+            connection: ManagedConnection::with_periods(
+                ReshardedConnection { moved: true },
+                connect,
+                None,
+                None,
+            ),
+            feature_keys: FeatureKeyCache::new(),
+        };
+        let mut pipeline = redis::pipe();
+        pipeline.cmd("HDEL").arg("key").arg("field");
+
+        let removed: Vec<u64> = query_pipeline(&store, &pipeline).await?;
+        assert_eq!(removed, [1]);
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+
+        // Later redirects within the refresh interval reuse the refreshed connection
+        store
+            .connection
+            .current
+            .store(Arc::new(ReshardedConnection { moved: true }));
+        let err = query_pipeline::<_, Vec<u64>>(&store, &pipeline)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), redis::ErrorKind::Moved);
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    /// Connection answering every pipeline with `replies`.
+    #[derive(Clone)]
+    struct FixedReplyConnection {
+        replies: Vec<redis::Value>,
+    }
+
+    impl ConnectionLike for FixedReplyConnection {
+        fn req_packed_command<'a>(
+            &'a mut self,
+            _cmd: &'a redis::Cmd,
+        ) -> RedisFuture<'a, redis::Value> {
+            Box::pin(async { Ok(redis::Value::Okay) })
+        }
+
+        fn req_packed_commands<'a>(
+            &'a mut self,
+            _cmd: &'a redis::Pipeline,
+            _offset: usize,
+            _count: usize,
+        ) -> RedisFuture<'a, Vec<redis::Value>> {
+            let replies = self.replies.clone();
+            Box::pin(async move { Ok(replies) })
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+    }
+
+    /// Store reading through a [`FixedReplyConnection`].
+    struct FixedReplyStore {
+        connection: FixedReplyConnection,
+        feature_keys: FeatureKeyCache,
+    }
+
+    impl FixedReplyStore {
+        fn new(replies: Vec<redis::Value>) -> Self {
+            Self {
+                connection: FixedReplyConnection { replies },
+                feature_keys: FeatureKeyCache::new(),
+            }
+        }
+    }
+
+    impl RedisStore for FixedReplyStore {
+        fn get_connection(&self) -> impl ConnectionLike + Send + Sync {
+            self.connection.clone()
+        }
+
+        fn get_project(&self) -> &str {
+            "project"
+        }
+
+        fn feature_keys(&self) -> &FeatureKeyCache {
+            &self.feature_keys
+        }
+    }
+
+    #[tokio::test]
+    async fn short_hmget_replies_are_rejected() -> Result<()> {
+        let features = |driver_id: i64| {
+            let entity_key = HashEntityKey(Arc::new(EntityKey {
+                join_keys: vec!["driver_id".to_string()],
+                entity_values: vec![Value {
+                    val: Some(Val::Int64Val(driver_id)),
+                }],
+            }));
+            (
+                entity_key,
+                vec![Feature::from_names("driver_hourly_stats", "conv_rate")],
+            )
+        };
+        let request: HashMap<_, _> = [features(1001), features(1002)].into_iter().collect();
+        // Timestamp and feature of every key, but one field moved to the other reply: the
+        // total count matches while each reply is misaligned
+        let reply = |fields: usize| redis::Value::Array(vec![redis::Value::Nil; fields]);
+        let store = FixedReplyStore::new(vec![reply(1), reply(3)]);
+        let err = store.get_feature_values(request.clone()).await.unwrap_err();
+        assert!(err.to_string().contains("fields, expected 2"), "{}", err);
+
+        let store = FixedReplyStore::new(vec![reply(2), reply(2)]);
+        let rows = store.get_feature_values(request).await?;
+        assert_eq!(rows.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn registry_reload_clears_feature_keys_of_the_store() -> Result<()> {
+        let entity_key = HashEntityKey(Arc::new(EntityKey {
+            join_keys: vec!["driver_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(1001)),
+            }],
+        }));
+        let request: HashMap<_, _> = [(
+            entity_key,
+            vec![Feature::from_names("driver_hourly_stats", "conv_rate")],
+        )]
+        .into_iter()
+        .collect();
+        let reply = redis::Value::Array(vec![redis::Value::Nil; 2]);
+        let store = FixedReplyStore::new(vec![reply.clone()]);
+        let other = FixedReplyStore::new(vec![reply]);
+        store.get_feature_values(request.clone()).await?;
+        other.get_feature_values(request).await?;
+        assert_eq!(store.feature_keys.len(), 1);
+
+        store.registry_reloaded();
+        assert!(store.feature_keys.is_empty());
+        assert_eq!(other.feature_keys.len(), 1);
+        Ok(())
+    }
 }
@@ -4,18 +4,28 @@ use crate::intern;
 use crate::key_serialization::deserialize_key;
 use crate::key_serialization::serialize_key;
 use crate::model::{Feature, HashEntityKey};
-use crate::onlinestore::{OnlineStore, OnlineStoreRow};
+use crate::onlinestore::{
+    OnlineStore, OnlineStoreRow, OnlineWriteRow, record_corrupt_key, record_decode_failure,
+};
 use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
 use lasso::Spur;
 use prost::Message;
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
-use sqlx::sqlite::{SqlitePoolOptions, SqliteRow};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
 use sqlx::{FromRow, Pool, Row, Sqlite};
-use std::sync::Arc;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use tokio::task::JoinSet;
 
+/// Placeholder of the feature view name in the path of a store sharded by feature view.
+pub const FEATURE_VIEW_PLACEHOLDER: &str = "{feature_view}";
+
+#[derive(Clone)]
 pub struct ConnectionOptions {
     max_connections: u32,
     min_connections: u32,
@@ -56,29 +66,38 @@ impl SqliteStoreRow {
         } = self;
         let rodeo = intern::rodeo_ref();
 
-        let decoded_value = Value::decode(value.as_slice()).with_context(|| {
-            format!(
-                "Failed to decode value for feature {}:{}",
-                rodeo.resolve(&feature_view_name),
-                feature_name
-            )
-        })?;
         let entity_key =
             deserialize_key(entity_key, EntityKeySerializationVersion::V3).map_err(|e| {
+                record_corrupt_key("sqlite", feature_view_name);
                 anyhow!(
                     "Failed to deserialize entity key for feature view {}: {:?}",
                     rodeo.resolve(&feature_view_name),
                     e
                 )
             })?;
+        let (value, undecoded) = match Value::decode(value.as_slice()) {
+            Ok(decoded) => (decoded, None),
+            Err(err) => {
+                record_decode_failure(
+                    "sqlite",
+                    feature_view_name,
+                    &feature_name,
+                    &entity_key,
+                    &err,
+                );
+                (Value::default(), Some(value))
+            }
+        };
         let feature_name = rodeo.get_or_intern(feature_name.as_ref());
         Ok(OnlineStoreRow {
             feature_view_name,
             entity_key: HashEntityKey(Arc::new(entity_key)),
             feature_name,
-            value: decoded_value,
+            value,
             event_ts,
             created_ts: Some(created_ts),
+            invalid: undecoded.is_some(),
+            undecoded,
         })
     }
 }
@@ -100,9 +119,150 @@ impl FromRow<'_, SqliteRow> for SqliteStoreRow {
     }
 }
 
+/// Timestamp column value as Feast writes it: naive UTC with microseconds, which Python's
+/// sqlite3 timestamp converter and sqlx both parse.
+fn sqlite_timestamp(ts: &DateTime<Utc>) -> String {
+    ts.naive_utc().format("%Y-%m-%d %H:%M:%S%.6f").to_string()
+}
+
+/// Failure of the sqlite read of one feature view.
+#[derive(Debug)]
+pub enum SqliteReadError {
+    /// The database cannot be used at all: the pool is closed or timed out, or the database
+    /// file cannot be opened. Every other feature view fails the same way.
+    DatabaseUnavailable {
+        feature_view: Spur,
+        source: sqlx::Error,
+    },
+    /// The query of the feature view failed, or its rows cannot be read.
+    Query {
+        feature_view: Spur,
+        source: anyhow::Error,
+    },
+}
+
+impl SqliteReadError {
+    fn classify(feature_view: Spur, err: sqlx::Error) -> Self {
+        let unavailable = match &err {
+            sqlx::Error::PoolClosed
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::Io(_)
+            | sqlx::Error::Configuration(_) => true,
+            // SQLITE_CANTOPEN and SQLITE_NOTADB
+            sqlx::Error::Database(db_err) => {
+                matches!(db_err.code().as_deref(), Some("14") | Some("26"))
+            }
+            _ => false,
+        };
+        if unavailable {
+            Self::DatabaseUnavailable {
+                feature_view,
+                source: err,
+            }
+        } else {
+            Self::Query {
+                feature_view,
+                source: err.into(),
+            }
+        }
+    }
+
+    pub fn feature_view(&self) -> Spur {
+        match self {
+            Self::DatabaseUnavailable { feature_view, .. } | Self::Query { feature_view, .. } => {
+                *feature_view
+            }
+        }
+    }
+
+    pub fn is_database_unavailable(&self) -> bool {
+        matches!(self, Self::DatabaseUnavailable { .. })
+    }
+}
+
+impl fmt::Display for SqliteReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let feature_view = intern::rodeo_ref().resolve(&self.feature_view());
+        match self {
+            Self::DatabaseUnavailable { source, .. } => write!(
+                f,
+                "Sqlite database unavailable while reading feature view {}: {}",
+                feature_view, source
+            ),
+            Self::Query { source, .. } => write!(
+                f,
+                "Failed to read feature view {} from sqlite: {:#}",
+                feature_view, source
+            ),
+        }
+    }
+}
+
+// The source is part of the message, so that it is kept by `to_string`
+impl std::error::Error for SqliteReadError {}
+
 pub struct SqliteOnlineStore {
     project: String,
-    connection_pool: Pool<Sqlite>,
+    databases: Databases,
+}
+
+/// Databases of the store: a single file, or one file per feature view.
+enum Databases {
+    Single(Pool<Sqlite>),
+    PerView(ShardedPools),
+}
+
+/// Pools of the databases holding one feature view each, opened on first use. Views
+/// without a database file read as views without a table.
+struct ShardedPools {
+    path_template: String,
+    options: ConnectionOptions,
+    pools: RwLock<HashMap<Spur, Pool<Sqlite>>>,
+    closed: AtomicBool,
+}
+
+impl ShardedPools {
+    fn path(&self, feature_view_name: Spur) -> String {
+        self.path_template.replace(
+            FEATURE_VIEW_PLACEHOLDER,
+            intern::rodeo_ref().resolve(&feature_view_name),
+        )
+    }
+
+    /// Pool of the database of `feature_view_name`. Without `create`, `None` when the
+    /// database file does not exist.
+    fn pool(&self, feature_view_name: Spur, create: bool) -> sqlx::Result<Option<Pool<Sqlite>>> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err(sqlx::Error::PoolClosed);
+        }
+        if let Some(pool) = self
+            .pools
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&feature_view_name)
+        {
+            return Ok(Some(pool.clone()));
+        }
+        let path = self.path(feature_view_name);
+        if !create && !Path::new(&path).exists() {
+            return Ok(None);
+        }
+        let connect_options = SqliteConnectOptions::from_str(&path)?.create_if_missing(true);
+        let mut pools = self.pools.write().unwrap_or_else(|e| e.into_inner());
+        let pool = pools
+            .entry(feature_view_name)
+            .or_insert_with(|| pool_options(&self.options).connect_lazy_with(connect_options));
+        Ok(Some(pool.clone()))
+    }
+
+    fn opened(&self) -> Vec<Pool<Sqlite>> {
+        self.pools
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -133,16 +293,20 @@ impl OnlineStore for SqliteOnlineStore {
             }
         }
 
-        let mut join_set: JoinSet<Result<Vec<OnlineStoreRow>>> = JoinSet::new();
+        let mut join_set: JoinSet<Result<Vec<OnlineStoreRow>, SqliteReadError>> = JoinSet::new();
         for (view_name, serialized_keys) in view_to_keys {
             let features = view_features.remove(&view_name).unwrap_or_default();
             if serialized_keys.is_empty() || features.is_empty() {
                 continue;
             }
 
-            let mut connection = self.connection_pool.acquire().await?;
+            let classify = |err: sqlx::Error| SqliteReadError::classify(view_name, err);
+            let Some(pool) = self.pool(view_name, false).map_err(classify)? else {
+                continue;
+            };
+            let mut connection = pool.acquire().await.map_err(classify)?;
             let rodeo = intern::rodeo_ref();
-            let table_name = format!("{}_{}", self.project, rodeo.resolve(&view_name));
+            let table_name = self.table_name(view_name);
 
             join_set.spawn(async move {
                 let entity_keys_parameters =
@@ -164,13 +328,17 @@ impl OnlineStore for SqliteOnlineStore {
                     Ok(rows) => rows
                         .into_iter()
                         .map(|r: SqliteStoreRow| r.try_into_online_store_row(view_name))
-                        .collect::<Result<Vec<_>>>(),
+                        .collect::<Result<Vec<_>>>()
+                        .map_err(|source| SqliteReadError::Query {
+                            feature_view: view_name,
+                            source,
+                        }),
                     Err(sqlx::Error::Database(db_err))
                         if db_err.message().contains("no such table") =>
                     {
                         Ok(Vec::new())
                     }
-                    Err(err) => Err(err.into()),
+                    Err(err) => Err(SqliteReadError::classify(view_name, err)),
                 }
             });
         }
@@ -178,62 +346,256 @@ impl OnlineStore for SqliteOnlineStore {
         let mut feature_rows = Vec::new();
         while let Some(res) = join_set.join_next().await {
             match res {
-                Ok(val) => feature_rows.push(val),
-                Err(e) => return Err(anyhow!("Error joining online feature task: {:?}", e)),
+                Ok(Ok(rows)) => feature_rows.extend(rows),
+                Ok(Err(err)) => {
+                    // The rows of the other views are of no use once one of them failed
+                    join_set.abort_all();
+                    return Err(err.into());
+                }
+                Err(e) => {
+                    join_set.abort_all();
+                    return Err(anyhow!("Error joining online feature task: {:?}", e));
+                }
+            }
+        }
+        Ok(feature_rows)
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        let pools = match &self.databases {
+            Databases::Single(pool) => vec![pool.clone()],
+            Databases::PerView(shards) => shards.opened(),
+        };
+        for pool in pools {
+            sqlx::query("SELECT 1").execute(&pool).await?;
+        }
+        Ok(())
+    }
+
+    async fn missing_tables(&self, feature_view_names: Vec<Spur>) -> Result<Vec<Spur>> {
+        let mut missing = Vec::new();
+        for view in feature_view_names {
+            let tables = match self.pool(view, false)? {
+                Some(pool) => Self::tables(&pool).await?,
+                None => HashSet::default(),
+            };
+            if !tables.contains(&self.table_name(view)) {
+                missing.push(view);
             }
         }
-        let mut errors = vec![];
-        let clean_data: Vec<OnlineStoreRow> = feature_rows
-            .into_iter()
-            .filter_map(|r| r.map_err(|e| errors.push(e)).ok())
-            .flatten()
-            .collect();
-        if !errors.is_empty() {
-            return Err(anyhow!(
-                "error while getting online data, errors: {:?}",
-                errors
-            ));
+        Ok(missing)
+    }
+
+    async fn sample_entity_keys(
+        &self,
+        feature_view_names: Vec<Spur>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>> {
+        let missing = self.missing_tables(feature_view_names.clone()).await?;
+        let mut keys = Vec::new();
+        for view in feature_view_names {
+            if keys.len() >= limit {
+                break;
+            }
+            if missing.contains(&view) {
+                continue;
+            }
+            let Some(pool) = self.pool(view, false)? else {
+                continue;
+            };
+            let view_keys: Vec<Vec<u8>> = sqlx::query_scalar(&format!(
+                "SELECT DISTINCT entity_key FROM {} LIMIT ?",
+                self.table_name(view)
+            ))
+            .bind((limit - keys.len()) as i64)
+            .fetch_all(&pool)
+            .await?;
+            keys.extend(view_keys);
         }
-        Ok(clean_data)
+        Ok(keys)
+    }
+
+    /// Wait for the pooled connections in use to be returned and close them all.
+    async fn shutdown(&self) -> Result<()> {
+        match &self.databases {
+            Databases::Single(pool) => pool.close().await,
+            Databases::PerView(shards) => {
+                shards.closed.store(true, Ordering::Release);
+                for pool in shards.opened() {
+                    pool.close().await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn online_write_batch(
+        &self,
+        feature_view_name: Spur,
+        rows: Vec<OnlineWriteRow>,
+    ) -> Result<()> {
+        let rodeo = intern::rodeo_ref();
+        let table_name = self.table_name(feature_view_name);
+        let pool = self
+            .pool(feature_view_name, true)?
+            .ok_or_else(|| anyhow!("No sqlite database for feature view"))?;
+        let mut transaction = pool.begin().await?;
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (entity_key BLOB, feature_name TEXT, value BLOB, \
+             vector_value BLOB, event_ts timestamp, created_ts timestamp, \
+             PRIMARY KEY(entity_key, feature_name))",
+            table_name
+        ))
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS {0}_ek ON {0} (entity_key)",
+            table_name
+        ))
+        .execute(&mut *transaction)
+        .await?;
+
+        let upsert = format!(
+            "INSERT INTO {} (entity_key, feature_name, value, event_ts, created_ts) \
+             VALUES (?, ?, ?, ?, ?) ON CONFLICT(entity_key, feature_name) DO UPDATE SET \
+             value = excluded.value, event_ts = excluded.event_ts, created_ts = excluded.created_ts",
+            table_name
+        );
+        for row in rows {
+            let entity_key = serialize_key(&row.entity_key, EntityKeySerializationVersion::V3)?;
+            let created_ts = row.created_ts.unwrap_or_else(Utc::now);
+            for (feature_name, value) in row.values {
+                sqlx::query(&upsert)
+                    .bind(&entity_key)
+                    .bind(rodeo.resolve(&feature_name))
+                    .bind(value.encode_to_vec())
+                    .bind(sqlite_timestamp(&row.event_ts))
+                    .bind(sqlite_timestamp(&created_ts))
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_entity_keys(
+        &self,
+        feature_view_name: Spur,
+        feature_names: Vec<Spur>,
+        entity_keys: Vec<EntityKey>,
+    ) -> Result<u64> {
+        if feature_names.is_empty() {
+            return Ok(0);
+        }
+        let rodeo = intern::rodeo_ref();
+        let delete = format!(
+            "DELETE FROM {} WHERE entity_key = ? AND feature_name in (?{})",
+            self.table_name(feature_view_name),
+            ", ?".repeat(feature_names.len() - 1)
+        );
+        let Some(pool) = self.pool(feature_view_name, false)? else {
+            return Ok(0);
+        };
+        let mut transaction = pool.begin().await?;
+        let mut deleted = 0;
+        for entity_key in entity_keys {
+            let entity_key = serialize_key(&entity_key, EntityKeySerializationVersion::V3)?;
+            let mut query = sqlx::query(&delete).bind(entity_key);
+            for feature_name in &feature_names {
+                query = query.bind(rodeo.resolve(feature_name));
+            }
+            match query.execute(&mut *transaction).await {
+                Ok(result) if result.rows_affected() > 0 => deleted += 1,
+                Ok(_) => {}
+                Err(sqlx::Error::Database(db_err))
+                    if db_err.message().contains("no such table") =>
+                {
+                    return Ok(0);
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        transaction.commit().await?;
+        Ok(deleted)
     }
 }
 
 impl SqliteOnlineStore {
+    fn table_name(&self, feature_view_name: Spur) -> String {
+        format!(
+            "{}_{}",
+            self.project,
+            intern::rodeo_ref().resolve(&feature_view_name)
+        )
+    }
+
+    /// Pool of the database of `feature_view_name`, `None` when the store is sharded and
+    /// the view has no database file yet, unless `create` is set.
+    fn pool(&self, feature_view_name: Spur, create: bool) -> sqlx::Result<Option<Pool<Sqlite>>> {
+        match &self.databases {
+            Databases::Single(pool) => Ok(Some(pool.clone())),
+            Databases::PerView(shards) => shards.pool(feature_view_name, create),
+        }
+    }
+
+    async fn tables(pool: &Pool<Sqlite>) -> Result<HashSet<String>> {
+        Ok(
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'")
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .collect(),
+        )
+    }
+
+    /// Open the database at `path`. A path containing `{feature_view}` is a template of
+    /// one database per feature view, such as `data/{feature_view}.db`, whose databases are
+    /// opened when their feature view is first used.
     pub async fn from_options(
         path: &str,
         project: String,
         connection_options: ConnectionOptions,
     ) -> Result<Self> {
-        let pool = SqlitePoolOptions::new()
-            .max_connections(connection_options.max_connections)
-            .min_connections(connection_options.min_connections)
-            .acquire_timeout(
-                connection_options
-                    .acquire_timeout
-                    .to_std()
-                    .unwrap_or_else(|_| std::time::Duration::from_secs(0)),
-            )
-            .idle_timeout(
-                connection_options
-                    .idle_timeout
-                    .to_std()
-                    .unwrap_or_else(|_| std::time::Duration::from_secs(0)),
-            )
-            .test_before_acquire(connection_options.test_before_acquire)
-            .connect(path)
-            .await?;
-        Ok(Self {
-            project,
-            connection_pool: pool,
-        })
+        let databases = if path.contains(FEATURE_VIEW_PLACEHOLDER) {
+            Databases::PerView(ShardedPools {
+                path_template: path.to_string(),
+                options: connection_options,
+                pools: RwLock::default(),
+                closed: AtomicBool::new(false),
+            })
+        } else {
+            Databases::Single(pool_options(&connection_options).connect(path).await?)
+        };
+        Ok(Self { project, databases })
     }
 }
 
+fn pool_options(connection_options: &ConnectionOptions) -> SqlitePoolOptions {
+    SqlitePoolOptions::new()
+        .max_connections(connection_options.max_connections)
+        .min_connections(connection_options.min_connections)
+        .acquire_timeout(
+            connection_options
+                .acquire_timeout
+                .to_std()
+                .unwrap_or_else(|_| std::time::Duration::from_secs(0)),
+        )
+        .idle_timeout(
+            connection_options
+                .idle_timeout
+                .to_std()
+                .unwrap_or_else(|_| std::time::Duration::from_secs(0)),
+        )
+        .test_before_acquire(connection_options.test_before_acquire)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::feast::types::Value;
     use crate::feast::types::value::Val;
+    use crate::test_util::temp_online_store;
 
     #[tokio::test]
     async fn read_sqlite_trait() -> Result<()> {
@@ -259,9 +621,197 @@ mod test {
         )
         .await?;
         let online_store: Box<dyn OnlineStore> = Box::new(sqlite_store);
-        let result = online_store.get_feature_values(arg).await?;
+        let result = online_store.get_feature_values(arg.clone()).await?;
         println!("{:?}", result);
         assert_eq!(result.len(), 1);
+
+        online_store.shutdown().await?;
+        assert!(online_store.get_feature_values(arg).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_batch_creates_table_and_upserts() -> Result<()> {
+        let online_store_db = temp_online_store()?;
+        let sqlite_path = online_store_db.path();
+        let sqlite_store = SqliteOnlineStore::from_options(
+            sqlite_path.to_str().unwrap(),
+            "golden_hornet".to_string(),
+            ConnectionOptions::default(),
+        )
+        .await?;
+
+        let rodeo = intern::rodeo_ref();
+        let entity_key = EntityKey {
+            join_keys: vec!["driver_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(4242)),
+            }],
+        };
+        let event_ts = DateTime::<Utc>::from_timestamp(1_700_000_000, 123_456_000).unwrap();
+        let write_row = |value: f32| OnlineWriteRow {
+            entity_key: entity_key.clone(),
+            values: vec![(
+                rodeo.get_or_intern("conv_rate"),
+                Value {
+                    val: Some(Val::FloatVal(value)),
+                },
+            )],
+            event_ts,
+            created_ts: None,
+        };
+        let view_name = rodeo.get_or_intern("pushed_driver_stats");
+        sqlite_store
+            .online_write_batch(view_name, vec![write_row(0.5)])
+            .await?;
+        sqlite_store
+            .online_write_batch(view_name, vec![write_row(0.75)])
+            .await?;
+
+        let arg: HashMap<HashEntityKey, Vec<Feature>> = HashMap::from_iter([(
+            HashEntityKey(Arc::new(entity_key)),
+            vec![Feature::from_names("pushed_driver_stats", "conv_rate")],
+        )]);
+        let result = sqlite_store.get_feature_values(arg).await?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].value.val, Some(Val::FloatVal(0.75)));
+        assert_eq!(result[0].event_ts, event_ts);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sharded_by_feature_view() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let dir = temp_dir.path();
+        let template = dir.join("{feature_view}.db");
+        let sqlite_store = SqliteOnlineStore::from_options(
+            template.to_str().unwrap(),
+            "golden_hornet".to_string(),
+            ConnectionOptions::default(),
+        )
+        .await?;
+
+        let rodeo = intern::rodeo_ref();
+        let entity_key = EntityKey {
+            join_keys: vec!["driver_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(7)),
+            }],
+        };
+        let written_view = rodeo.get_or_intern("sharded_driver_stats");
+        let absent_view = rodeo.get_or_intern("sharded_absent_stats");
+        sqlite_store
+            .online_write_batch(
+                written_view,
+                vec![OnlineWriteRow {
+                    entity_key: entity_key.clone(),
+                    values: vec![(
+                        rodeo.get_or_intern("conv_rate"),
+                        Value {
+                            val: Some(Val::FloatVal(0.5)),
+                        },
+                    )],
+                    event_ts: Utc::now(),
+                    created_ts: None,
+                }],
+            )
+            .await?;
+        assert!(dir.join("sharded_driver_stats.db").exists());
+
+        let arg: HashMap<HashEntityKey, Vec<Feature>> = HashMap::from_iter([(
+            HashEntityKey(Arc::new(entity_key)),
+            vec![
+                Feature::from_names("sharded_driver_stats", "conv_rate"),
+                Feature::from_names("sharded_absent_stats", "conv_rate"),
+            ],
+        )]);
+        let result = sqlite_store.get_feature_values(arg).await?;
+        let missing = sqlite_store
+            .missing_tables(vec![written_view, absent_view])
+            .await?;
+        sqlite_store.health_check().await?;
+        sqlite_store.shutdown().await?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].feature_view_name, written_view);
+        assert_eq!(result[0].value.val, Some(Val::FloatVal(0.5)));
+        assert_eq!(missing, [absent_view]);
+        // Reading a view does not create its database
+        assert!(!dir.join("sharded_absent_stats.db").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_errors_are_classified() -> Result<()> {
+        let online_store_db = temp_online_store()?;
+        let sqlite_path = online_store_db.path();
+        let sqlite_store = SqliteOnlineStore::from_options(
+            sqlite_path.to_str().unwrap(),
+            "golden_hornet".to_string(),
+            ConnectionOptions::default(),
+        )
+        .await?;
+        let broken_view = intern::rodeo_ref().get_or_intern("broken_view");
+        let pool = sqlite_store.pool(broken_view, false)?.unwrap();
+        sqlx::query("CREATE TABLE golden_hornet_broken_view (entity_key BLOB)")
+            .execute(&pool)
+            .await?;
+
+        let entity_key = Arc::new(EntityKey {
+            join_keys: vec!["driver_id".to_string()],
+            entity_values: vec![Value {
+                val: Some(Val::Int64Val(1005)),
+            }],
+        });
+        let arg: HashMap<HashEntityKey, Vec<Feature>> = HashMap::from_iter([(
+            HashEntityKey(entity_key),
+            vec![
+                Feature::from_names("broken_view", "conv_rate"),
+                Feature::from_names("driver_hourly_stats", "conv_rate"),
+            ],
+        )]);
+        let err = sqlite_store
+            .get_feature_values(arg.clone())
+            .await
+            .unwrap_err();
+        let read_err = err.downcast_ref::<SqliteReadError>().unwrap();
+        assert!(!read_err.is_database_unavailable());
+        assert_eq!(
+            read_err.feature_view(),
+            intern::rodeo_ref().get_or_intern("broken_view")
+        );
+        assert!(
+            err.to_string()
+                .starts_with("Failed to read feature view broken_view from sqlite: ")
+        );
+
+        sqlite_store.shutdown().await?;
+        let err = sqlite_store.get_feature_values(arg).await.unwrap_err();
+        let read_err = err.downcast_ref::<SqliteReadError>().unwrap();
+        assert!(read_err.is_database_unavailable());
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_entity_keys_fail_the_row() -> Result<()> {
+        let entity_key = serialize_key(
+            &EntityKey {
+                join_keys: vec!["driver_id".to_string()],
+                entity_values: vec![Value {
+                    val: Some(Val::Int64Val(1005)),
+                }],
+            },
+            EntityKeySerializationVersion::V3,
+        )?;
+        let row = SqliteStoreRow {
+            entity_key: entity_key[..entity_key.len() - 3].to_vec(),
+            feature_name: Arc::from("conv_rate"),
+            value: Vec::new(),
+            event_ts: Utc::now(),
+            created_ts: Utc::now(),
+        };
+        let view = intern::rodeo_ref().get_or_intern("driver_hourly_stats");
+        let err = row.try_into_online_store_row(view).unwrap_err();
+        assert!(format!("{:?}", err).contains("Entity key of 34 bytes is truncated"));
         Ok(())
     }
 }
@@ -0,0 +1,186 @@
+//! Sizes of the request and response payloads of the servers, recorded per route or RPC
+//! and feature service to size pods and online store bandwidth. The feature services with
+//! the largest responses are also logged periodically, see
+//! [`PayloadSizeReportConfig`].
+
+use crate::config::PayloadSizeReportConfig;
+use rustc_hash::FxHashMap as HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Histogram of request body sizes in bytes, labeled by `route` and `feature_service`.
+pub const REQUEST_SIZE_METRIC: &str = "feast_request_size_bytes";
+/// Histogram of response body sizes in bytes, labeled by `route` and `feature_service`.
+pub const RESPONSE_SIZE_METRIC: &str = "feast_response_size_bytes";
+
+static REPORT: LazyLock<Mutex<FeatureServiceSizes>> = LazyLock::new(|| {
+    Mutex::new(FeatureServiceSizes::new(
+        &Default::default(),
+        Instant::now(),
+    ))
+});
+
+/// Replace the configuration of the largest feature services report. Sizes recorded so far
+/// are dropped.
+pub fn set_report(config: &PayloadSizeReportConfig) {
+    *REPORT.lock().unwrap_or_else(|e| e.into_inner()) =
+        FeatureServiceSizes::new(config, Instant::now());
+}
+
+/// Record the payload sizes of a request served on `route`. `feature_service` is the
+/// feature service it read, if any; requests which failed should not name one, so that
+/// the label only takes names of the registry. `request_bytes` is unknown for streamed
+/// bodies.
+pub fn record(
+    route: &str,
+    feature_service: Option<&str>,
+    request_bytes: Option<u64>,
+    response_bytes: u64,
+) {
+    let labels = [
+        ("route", route.to_string()),
+        (
+            "feature_service",
+            feature_service.unwrap_or_default().to_string(),
+        ),
+    ];
+    if let Some(bytes) = request_bytes {
+        metrics::histogram!(REQUEST_SIZE_METRIC, &labels).record(bytes as f64);
+    }
+    metrics::histogram!(RESPONSE_SIZE_METRIC, &labels).record(response_bytes as f64);
+
+    let Some(feature_service) = feature_service else {
+        return;
+    };
+    let report = {
+        let mut sizes = REPORT.lock().unwrap_or_else(|e| e.into_inner());
+        sizes.add(feature_service, request_bytes.unwrap_or(0), response_bytes);
+        sizes.take_report(Instant::now())
+    };
+    for (rank, (name, sizes)) in report.into_iter().flatten().enumerate() {
+        tracing::info!(
+            "Largest feature services #{}: {} served {} requests, {} response bytes in total, \
+             {} at most, {} request bytes in total",
+            rank + 1,
+            name,
+            sizes.requests,
+            sizes.response_bytes,
+            sizes.max_response_bytes,
+            sizes.request_bytes
+        );
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServiceSizes {
+    pub requests: u64,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    pub max_response_bytes: u64,
+}
+
+impl ServiceSizes {
+    fn add(&mut self, request_bytes: u64, response_bytes: u64) {
+        self.requests += 1;
+        self.request_bytes = self.request_bytes.saturating_add(request_bytes);
+        self.response_bytes = self.response_bytes.saturating_add(response_bytes);
+        self.max_response_bytes = self.max_response_bytes.max(response_bytes);
+    }
+}
+
+/// Payload sizes per feature service since the last report.
+struct FeatureServiceSizes {
+    top: usize,
+    interval: Duration,
+    since: Instant,
+    services: HashMap<String, ServiceSizes>,
+}
+
+impl FeatureServiceSizes {
+    fn new(config: &PayloadSizeReportConfig, now: Instant) -> Self {
+        Self {
+            top: config.top_feature_services,
+            interval: Duration::from_secs(config.interval_secs),
+            since: now,
+            services: HashMap::default(),
+        }
+    }
+
+    fn add(&mut self, feature_service: &str, request_bytes: u64, response_bytes: u64) {
+        if self.top == 0 {
+            return;
+        }
+        match self.services.get_mut(feature_service) {
+            Some(sizes) => sizes.add(request_bytes, response_bytes),
+            None => {
+                let mut sizes = ServiceSizes::default();
+                sizes.add(request_bytes, response_bytes);
+                self.services.insert(feature_service.to_string(), sizes);
+            }
+        }
+    }
+
+    /// The feature services with the most response bytes once the report interval has
+    /// passed, largest first. Starts the next interval.
+    fn take_report(&mut self, now: Instant) -> Option<Vec<(String, ServiceSizes)>> {
+        if now.duration_since(self.since) < self.interval || self.services.is_empty() {
+            return None;
+        }
+        self.since = now;
+        let mut services: Vec<_> = self.services.drain().collect();
+        services.sort_by(|(a_name, a), (b_name, b)| {
+            b.response_bytes
+                .cmp(&a.response_bytes)
+                .then_with(|| a_name.cmp(b_name))
+        });
+        services.truncate(self.top);
+        Some(services)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_feature_services_are_reported_per_interval() {
+        let config = PayloadSizeReportConfig {
+            top_feature_services: 2,
+            interval_secs: 60,
+        };
+        let start = Instant::now();
+        let mut sizes = FeatureServiceSizes::new(&config, start);
+        sizes.add("driver_activity", 100, 4_000);
+        sizes.add("driver_activity", 100, 6_000);
+        sizes.add("customer_profile", 50, 20_000);
+        sizes.add("rider_activity", 10, 500);
+        assert_eq!(sizes.take_report(start + Duration::from_secs(30)), None);
+
+        let report = sizes
+            .take_report(start + Duration::from_secs(60))
+            .expect("the interval has passed");
+        let names: Vec<_> = report.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["customer_profile", "driver_activity"]);
+        assert_eq!(
+            report[1].1,
+            ServiceSizes {
+                requests: 2,
+                request_bytes: 200,
+                response_bytes: 10_000,
+                max_response_bytes: 6_000,
+            }
+        );
+        // The next report only covers the next interval
+        assert_eq!(sizes.take_report(start + Duration::from_secs(180)), None);
+
+        let mut disabled = FeatureServiceSizes::new(
+            &PayloadSizeReportConfig {
+                top_feature_services: 0,
+                ..config
+            },
+            start,
+        );
+        disabled.add("driver_activity", 100, 4_000);
+        assert_eq!(disabled.take_report(start + Duration::from_secs(60)), None);
+    }
+}
@@ -0,0 +1,163 @@
+//! Startup checks run before the servers bind their ports. Every check runs even when an
+//! earlier one fails, so that a misconfigured deployment reports all of its problems at once.
+
+use crate::config::RepoConfig;
+use crate::feature_store::FeatureStore;
+use crate::onlinestore::{OnlineStore, get_online_store};
+use crate::registry::{FeatureRegistryService, get_registry};
+use anyhow::{Context, Result, anyhow};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// File the server reads at startup, e.g. a TLS certificate.
+pub struct RequiredFile {
+    pub description: String,
+    pub path: PathBuf,
+}
+
+impl RequiredFile {
+    pub fn new(description: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            description: description.into(),
+            path: path.into(),
+        }
+    }
+}
+
+/// Check that `files` are readable, load the registry and ping the online store of
+/// `repo_config`, then build the feature store from them. The error lists every failed check.
+pub async fn run_preflight_checks(
+    repo_config: &RepoConfig,
+    cwd: &Path,
+    files: &[RequiredFile],
+) -> Result<FeatureStore> {
+    let mut failures = Vec::new();
+    for file in files {
+        if let Err(err) = check_readable(&file.path) {
+            failures.push((file.description.as_str(), err));
+        }
+    }
+    let (registry, online_store) = match cwd.to_str() {
+        Some(cwd) => (
+            load_registry(repo_config, cwd).await,
+            connect_online_store(repo_config, cwd).await,
+        ),
+        None => {
+            let invalid_cwd = || anyhow!("Feature repository path contains invalid UTF-8");
+            (Err(invalid_cwd()), Err(invalid_cwd()))
+        }
+    };
+    let registry = registry
+        .map_err(|err| failures.push(("registry", err)))
+        .ok();
+    let online_store = online_store
+        .map_err(|err| failures.push(("online store", err)))
+        .ok();
+
+    match (registry, online_store) {
+        (Some(registry), Some(online_store)) if failures.is_empty() => {
+            tracing::info!("Preflight checks passed");
+            FeatureStore::builder()
+                .with_repo_config(repo_config.clone())
+                .with_cwd(cwd)
+                .with_registry(registry)
+                .with_online_store(online_store)
+                .build()
+                .await
+        }
+        _ => {
+            let mut message = String::from("Preflight checks failed:");
+            for (check, err) in failures {
+                let _ = write!(message, "\n  - {}: {:#}", check, err);
+            }
+            Err(anyhow!(message))
+        }
+    }
+}
+
+fn check_readable(path: &Path) -> Result<()> {
+    let contents =
+        std::fs::read(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    if contents.is_empty() {
+        return Err(anyhow!("{} is empty", path.display()));
+    }
+    Ok(())
+}
+
+async fn load_registry(
+    repo_config: &RepoConfig,
+    cwd: &str,
+) -> Result<Arc<dyn FeatureRegistryService>> {
+    get_registry(
+        repo_config.registry.clone(),
+        repo_config.project.clone(),
+        Some(cwd),
+    )
+    .await
+}
+
+async fn connect_online_store(repo_config: &RepoConfig, cwd: &str) -> Result<Arc<dyn OnlineStore>> {
+    let online_store =
+        get_online_store(&repo_config.online_store, &repo_config.project, Some(cwd)).await?;
+    online_store
+        .health_check()
+        .await
+        .context("Online store is not reachable")?;
+    Ok(online_store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_data() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data")
+    }
+
+    fn repo_config(registry: &str) -> Result<RepoConfig> {
+        RepoConfig::from_yaml_str(&format!(
+            r#"
+project: golden_hornet
+registry: {}
+online_store:
+    type: sqlite
+    path: online_store.db
+"#,
+            registry
+        ))
+    }
+
+    #[tokio::test]
+    async fn passing_checks_build_feature_store() -> Result<()> {
+        let files = [RequiredFile::new(
+            "TLS certificate",
+            test_data().join("registry.pb"),
+        )];
+        let store =
+            run_preflight_checks(&repo_config("registry.pb")?, &test_data(), &files).await?;
+        store.health_check().await
+    }
+
+    #[tokio::test]
+    async fn failed_checks_are_all_reported() -> Result<()> {
+        let files = [RequiredFile::new(
+            "TLS private key",
+            test_data().join("missing.key"),
+        )];
+        let err = run_preflight_checks(&repo_config("missing.pb")?, &test_data(), &files)
+            .await
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.starts_with("Preflight checks failed:"), "{}", err);
+        assert!(
+            err.contains("\n  - TLS private key: Cannot read"),
+            "{}",
+            err
+        );
+        assert!(err.contains("\n  - registry: "), "{}", err);
+        assert!(!err.contains("online store"), "{}", err);
+        Ok(())
+    }
+}
@@ -1,7 +1,13 @@
 use crate::feast::types::value::Val;
-use crate::feast::types::value_type;
+use crate::feast::types::{
+    BoolList, BytesList, DoubleList, FloatList, Int32List, Int64List, StringList, Value, value_type,
+};
 use crate::model::EntityIdValue;
+use crate::util::parse_json_timestamp;
 use anyhow::{Error, Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::Value as JsonValue;
 use std::any::Any;
 use std::hash::{Hash, Hasher};
 
@@ -60,3 +66,128 @@ impl TryFrom<Val> for EntityIdValue {
         }
     }
 }
+
+/// Convert a JSON value from a push payload into a proto value of the declared type.
+/// JSON `null` maps to an empty value, which readers report as missing.
+pub fn json_to_proto_value(json: &JsonValue, value_type: value_type::Enum) -> Result<Value> {
+    use value_type::Enum;
+    if json.is_null() {
+        return Ok(Value { val: None });
+    }
+    let val = match value_type {
+        Enum::Bytes => Val::BytesVal(json_to_bytes(json)?),
+        Enum::String => Val::StringVal(json_to_string(json)?),
+        Enum::Int32 => Val::Int32Val(json_to_i32(json)?),
+        Enum::Int64 => Val::Int64Val(json_to_i64(json)?),
+        Enum::Double => Val::DoubleVal(json_to_f64(json)?),
+        Enum::Float => Val::FloatVal(json_to_f64(json)? as f32),
+        Enum::Bool => Val::BoolVal(json_to_bool(json)?),
+        Enum::UnixTimestamp => Val::UnixTimestampVal(parse_json_timestamp(json)?.timestamp()),
+        Enum::BytesList => Val::BytesListVal(BytesList {
+            val: json_to_list(json, json_to_bytes)?,
+        }),
+        Enum::StringList => Val::StringListVal(StringList {
+            val: json_to_list(json, json_to_string)?,
+        }),
+        Enum::Int32List => Val::Int32ListVal(Int32List {
+            val: json_to_list(json, json_to_i32)?,
+        }),
+        Enum::Int64List => Val::Int64ListVal(Int64List {
+            val: json_to_list(json, json_to_i64)?,
+        }),
+        Enum::DoubleList => Val::DoubleListVal(DoubleList {
+            val: json_to_list(json, json_to_f64)?,
+        }),
+        Enum::FloatList => Val::FloatListVal(FloatList {
+            val: json_to_list(json, |v| json_to_f64(v).map(|f| f as f32))?,
+        }),
+        Enum::BoolList => Val::BoolListVal(BoolList {
+            val: json_to_list(json, json_to_bool)?,
+        }),
+        Enum::UnixTimestampList => Val::UnixTimestampListVal(Int64List {
+            val: json_to_list(json, |v| parse_json_timestamp(v).map(|ts| ts.timestamp()))?,
+        }),
+        Enum::Invalid | Enum::Null => {
+            return Err(anyhow!(
+                "Cannot convert value {} to type {}",
+                json,
+                value_type.as_str_name()
+            ));
+        }
+    };
+    Ok(Value { val: Some(val) })
+}
+
+fn json_to_list<T>(json: &JsonValue, convert: impl Fn(&JsonValue) -> Result<T>) -> Result<Vec<T>> {
+    json.as_array()
+        .ok_or_else(|| anyhow!("Expected list value, got {}", json))?
+        .iter()
+        .map(convert)
+        .collect()
+}
+
+fn json_to_bytes(json: &JsonValue) -> Result<Vec<u8>> {
+    let encoded = json
+        .as_str()
+        .ok_or_else(|| anyhow!("Expected base64 encoded string, got {}", json))?;
+    Ok(BASE64.decode(encoded)?)
+}
+
+fn json_to_string(json: &JsonValue) -> Result<String> {
+    match json {
+        JsonValue::String(s) => Ok(s.clone()),
+        JsonValue::Number(n) => Ok(n.to_string()),
+        other => Err(anyhow!("Expected string value, got {}", other)),
+    }
+}
+
+fn json_to_i64(json: &JsonValue) -> Result<i64> {
+    json.as_i64()
+        .ok_or_else(|| anyhow!("Expected integer value, got {}", json))
+}
+
+fn json_to_i32(json: &JsonValue) -> Result<i32> {
+    Ok(i32::try_from(json_to_i64(json)?)?)
+}
+
+fn json_to_f64(json: &JsonValue) -> Result<f64> {
+    json.as_f64()
+        .ok_or_else(|| anyhow!("Expected numeric value, got {}", json))
+}
+
+fn json_to_bool(json: &JsonValue) -> Result<bool> {
+    json.as_bool()
+        .ok_or_else(|| anyhow!("Expected boolean value, got {}", json))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn convert_json_values() -> Result<()> {
+        assert_eq!(
+            json_to_proto_value(&json!(0.5), value_type::Enum::Float)?.val,
+            Some(Val::FloatVal(0.5))
+        );
+        assert_eq!(
+            json_to_proto_value(&json!([1, 2]), value_type::Enum::Int64List)?.val,
+            Some(Val::Int64ListVal(Int64List { val: vec![1, 2] }))
+        );
+        assert_eq!(
+            json_to_proto_value(
+                &json!("2021-04-12T10:59:42Z"),
+                value_type::Enum::UnixTimestamp
+            )?
+            .val,
+            Some(Val::UnixTimestampVal(1618225182))
+        );
+        assert_eq!(
+            json_to_proto_value(&json!(null), value_type::Enum::Int32)?.val,
+            None
+        );
+        assert!(json_to_proto_value(&json!("x"), value_type::Enum::Int64).is_err());
+        Ok(())
+    }
+}
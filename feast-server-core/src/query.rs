@@ -0,0 +1,301 @@
+//! Tiny SQL-like selection of online features, for poking at the online store without
+//! building request bodies:
+//!
+//! ```sql
+//! SELECT conv_rate, acc_rate FROM driver_hourly_stats WHERE driver_id IN (1001, 1002)
+//! ```
+//!
+//! The `FROM` clause names a feature view and the `WHERE` clause holds one `IN` list or `=`
+//! comparison per join key, joined with `AND`. Lists of several keys are zipped into entity
+//! rows. Keywords are case insensitive; strings are single quoted, with `''` escaping a quote.
+
+use crate::error::FeastCoreError;
+use crate::model::{EntityIdValue, GetOnlineFeaturesRequest, OrderedMap};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Str(String),
+    Comma,
+    LParen,
+    RParen,
+    Eq,
+    Star,
+}
+
+/// Parse a query into the request of its features and entity rows.
+pub fn parse_query(query: &str) -> Result<GetOnlineFeaturesRequest, FeastCoreError> {
+    let mut parser = Parser {
+        tokens: tokenize(query)?,
+        position: 0,
+    };
+    parser.keyword("SELECT")?;
+    let mut columns = vec![parser.column()?];
+    while parser.next_is(&Token::Comma) {
+        columns.push(parser.column()?);
+    }
+    parser.keyword("FROM")?;
+    let feature_view = parser.ident("a feature view")?;
+    parser.keyword("WHERE")?;
+    let mut entities: OrderedMap<String, Vec<EntityIdValue>> = OrderedMap::default();
+    loop {
+        let join_key = parser.ident("a join key")?;
+        let values = if parser.next_is(&Token::Eq) {
+            vec![parser.value()?]
+        } else {
+            parser.keyword("IN")?;
+            parser.expect(&Token::LParen, "(")?;
+            let mut values = vec![parser.value()?];
+            while parser.next_is(&Token::Comma) {
+                values.push(parser.value()?);
+            }
+            parser.expect(&Token::RParen, ")")?;
+            values
+        };
+        if entities.contains_key(&join_key) {
+            return Err(FeastCoreError::invalid_query(format!(
+                "join key '{}' is filtered more than once",
+                join_key
+            )));
+        }
+        entities.insert(join_key, values);
+        if !parser.next_keyword("AND") {
+            break;
+        }
+    }
+    if let Some(token) = parser.tokens.get(parser.position) {
+        return Err(FeastCoreError::invalid_query(format!(
+            "unexpected {} after the WHERE clause",
+            describe(token)
+        )));
+    }
+    Ok(GetOnlineFeaturesRequest {
+        entities,
+        features: Some(
+            columns
+                .into_iter()
+                .map(|column| format!("{}:{}", feature_view, column))
+                .collect(),
+        ),
+        full_feature_names: Some(false),
+        ..Default::default()
+    })
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, FeastCoreError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            // A trailing semicolon is accepted, as typed in SQL consoles
+            ';' if chars.clone().all(|(_, c)| c.is_whitespace()) => break,
+            ',' => Token::Comma,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '=' => Token::Eq,
+            '*' => Token::Star,
+            '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) if chars.peek().is_some_and(|(_, c)| *c == '\'') => {
+                            chars.next();
+                            value.push('\'');
+                        }
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => value.push(c),
+                        None => {
+                            return Err(FeastCoreError::invalid_query("unterminated string"));
+                        }
+                    }
+                }
+                Token::Str(value)
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                let mut end = start + c.len_utf8();
+                while let Some((index, c)) = chars.peek().copied() {
+                    if !c.is_ascii_digit() {
+                        break;
+                    }
+                    end = index + c.len_utf8();
+                    chars.next();
+                }
+                let number = &query[start..end];
+                number.parse().map(Token::Int).map_err(|_| {
+                    FeastCoreError::invalid_query(format!("invalid integer '{}'", number))
+                })?
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((index, c)) = chars.peek().copied() {
+                    if !c.is_alphanumeric() && c != '_' {
+                        break;
+                    }
+                    end = index + c.len_utf8();
+                    chars.next();
+                }
+                Token::Ident(query[start..end].to_string())
+            }
+            other => {
+                return Err(FeastCoreError::invalid_query(format!(
+                    "unexpected character '{}'",
+                    other
+                )));
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Ident(ident) => format!("'{}'", ident),
+        Token::Int(value) => format!("'{}'", value),
+        Token::Str(value) => format!("string '{}'", value),
+        Token::Comma => "','".to_string(),
+        Token::LParen => "'('".to_string(),
+        Token::RParen => "')'".to_string(),
+        Token::Eq => "'='".to_string(),
+        Token::Star => "'*'".to_string(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self, expected: &str) -> Result<Token, FeastCoreError> {
+        let token = self.tokens.get(self.position).cloned().ok_or_else(|| {
+            FeastCoreError::invalid_query(format!("expected {}, found the end", expected))
+        })?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn unexpected(&self, token: &Token, expected: &str) -> FeastCoreError {
+        FeastCoreError::invalid_query(format!("expected {}, found {}", expected, describe(token)))
+    }
+
+    fn next_is(&mut self, token: &Token) -> bool {
+        let matches = self.tokens.get(self.position) == Some(token);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn next_keyword(&mut self, keyword: &str) -> bool {
+        let matches = matches!(
+            self.tokens.get(self.position),
+            Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword)
+        );
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn expect(&mut self, expected: &Token, name: &str) -> Result<(), FeastCoreError> {
+        let token = self.next(&format!("'{}'", name))?;
+        if &token != expected {
+            return Err(self.unexpected(&token, &format!("'{}'", name)));
+        }
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), FeastCoreError> {
+        if self.next_keyword(keyword) {
+            return Ok(());
+        }
+        let token = self.next(keyword)?;
+        Err(self.unexpected(&token, keyword))
+    }
+
+    fn ident(&mut self, expected: &str) -> Result<String, FeastCoreError> {
+        match self.next(expected)? {
+            Token::Ident(ident) => Ok(ident),
+            token => Err(self.unexpected(&token, expected)),
+        }
+    }
+
+    fn column(&mut self) -> Result<String, FeastCoreError> {
+        match self.next("a feature")? {
+            Token::Ident(ident) => Ok(ident),
+            // Every feature of the view, as `view:*` in feature lists
+            Token::Star => Ok("*".to_string()),
+            token => Err(self.unexpected(&token, "a feature")),
+        }
+    }
+
+    fn value(&mut self) -> Result<EntityIdValue, FeastCoreError> {
+        match self.next("an entity value")? {
+            Token::Int(value) => Ok(EntityIdValue::Int(value)),
+            Token::Str(value) => Ok(EntityIdValue::String(value)),
+            token => Err(self.unexpected(&token, "an entity value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_features_and_entity_rows() {
+        let request = parse_query(
+            "select conv_rate, acc_rate FROM driver_hourly_stats \
+             WHERE driver_id IN (1001, -2) and city = 'O''Hare';",
+        )
+        .unwrap();
+        assert_eq!(
+            request.features,
+            Some(vec![
+                "driver_hourly_stats:conv_rate".to_string(),
+                "driver_hourly_stats:acc_rate".to_string(),
+            ])
+        );
+        assert_eq!(
+            request.entities.get("driver_id"),
+            Some(&vec![EntityIdValue::Int(1001), EntityIdValue::Int(-2)])
+        );
+        assert_eq!(
+            request.entities.get("city"),
+            Some(&vec![EntityIdValue::String("O'Hare".to_string())])
+        );
+    }
+
+    #[test]
+    fn star_selects_every_feature() {
+        let request = parse_query("SELECT * FROM driver_hourly_stats WHERE driver_id = 1").unwrap();
+        assert_eq!(
+            request.features,
+            Some(vec!["driver_hourly_stats:*".to_string()])
+        );
+    }
+
+    #[test]
+    fn reports_what_was_expected() {
+        let error = |query| parse_query(query).unwrap_err().to_string();
+        assert_eq!(
+            error("SELECT conv_rate FROM driver_hourly_stats"),
+            "Invalid query: expected WHERE, found the end"
+        );
+        assert_eq!(
+            error("SELECT conv_rate FROM driver_hourly_stats WHERE driver_id IN (1001"),
+            "Invalid query: expected ')', found the end"
+        );
+        assert_eq!(
+            error("SELECT conv_rate acc_rate FROM v WHERE driver_id = 1"),
+            "Invalid query: expected FROM, found 'acc_rate'"
+        );
+        assert_eq!(
+            error("SELECT a FROM v WHERE driver_id = 1 LIMIT 1"),
+            "Invalid query: unexpected 'LIMIT' after the WHERE clause"
+        );
+    }
+}
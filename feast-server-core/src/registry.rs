@@ -1,18 +1,31 @@
 //! Registry module for managing feature views and features metadata.
 
-use crate::model::{Feature, FeatureView, GetOnlineFeaturesRequest, RequestedFeatures};
+use crate::error::FeastCoreError;
+use crate::model::{Feature, FeatureView, GetOnlineFeaturesRequest, OrderedMap, RequestedFeatures};
 use anyhow::Result;
 use async_trait::async_trait;
+use lasso::Spur;
 use rustc_hash::FxHashMap as HashMap;
 use std::sync::Arc;
 
 mod cached_registry;
+pub(crate) mod compat;
+pub mod factory;
 mod feature_registry;
 pub mod file_registry;
+pub mod metadata;
+#[cfg(feature = "postgres-registry")]
+mod postgres_registry;
+pub mod schema;
+mod snowflake_registry;
 mod sql_registry;
 
+pub use cached_registry::{REGISTRY_REFRESH_FAILURES_METRIC, RegistryRefreshFailure};
+pub use factory::{RegistryFactory, register_registry};
 pub use feature_registry::get_registry;
+pub(crate) use feature_registry::load_registry_proto;
 pub use file_registry::FileFeatureRegistry;
+pub use metadata::RegistryMetadata;
 
 #[async_trait]
 pub trait FeatureRegistryService: Send + Sync {
@@ -20,5 +33,64 @@ pub trait FeatureRegistryService: Send + Sync {
     async fn request_to_view_keys(
         &self,
         request: RequestedFeatures,
-    ) -> Result<HashMap<Feature, Arc<FeatureView>>>;
+    ) -> Result<OrderedMap<Feature, Arc<FeatureView>>>;
+
+    /// Like [`Self::request_to_view_keys`], also returning the [`Self::generation`] of the
+    /// registry which resolved the features.
+    async fn request_to_view_keys_versioned(
+        &self,
+        request: RequestedFeatures,
+    ) -> Result<(OrderedMap<Feature, Arc<FeatureView>>, u64)> {
+        let generation = self.generation();
+        Ok((self.request_to_view_keys(request).await?, generation))
+    }
+
+    /// Like [`Self::request_to_view_keys`], against the registry of an earlier
+    /// [`Self::generation`]. Registries keeping no earlier generations only resolve the
+    /// current one.
+    async fn request_to_view_keys_at(
+        &self,
+        request: RequestedFeatures,
+        generation: u64,
+    ) -> Result<OrderedMap<Feature, Arc<FeatureView>>> {
+        let current = self.generation();
+        if generation != current {
+            return Err(FeastCoreError::registry_version_not_found(generation, current).into());
+        }
+        self.request_to_view_keys(request).await
+    }
+
+    /// Get Feature View object by its name
+    async fn feature_view(&self, feature_view_name: Spur) -> Result<Arc<FeatureView>>;
+
+    /// Get all Feature View objects of the project
+    async fn feature_views(&self) -> Result<Vec<Arc<FeatureView>>>;
+
+    /// Get Feature View objects fed by the given push source
+    async fn push_source_views(&self, push_source_name: Spur) -> Result<Vec<Arc<FeatureView>>>;
+
+    /// Description of the registry for catalogs. Registries that only expose feature views
+    /// describe those.
+    async fn metadata(&self) -> Result<RegistryMetadata> {
+        let views = self.feature_views().await?;
+        Ok(RegistryMetadata::from_feature_views(
+            views.iter().map(Arc::as_ref),
+        ))
+    }
+
+    /// Failure of the background refreshes since the last successful one, for registries
+    /// refreshed in the background.
+    fn refresh_failure(&self) -> Option<RegistryRefreshFailure> {
+        None
+    }
+
+    /// Number of times the registry was reloaded. Data derived from the registry must be
+    /// discarded when it changes.
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    /// Stop background work of the registry before the server exits. Registries without
+    /// any have nothing to stop.
+    async fn shutdown(&self) {}
 }
@@ -1,55 +1,173 @@
 use crate::config::RegistryConfig;
-use crate::model::{Feature, FeatureView, GetOnlineFeaturesRequest, RequestedFeatures};
-use crate::registry::{FeatureRegistryService, FileFeatureRegistry};
-use anyhow::Result;
+use crate::error::FeastCoreError;
+use crate::feast::core::Registry;
+use crate::model::{Feature, FeatureView, GetOnlineFeaturesRequest, OrderedMap, RequestedFeatures};
+use crate::registry::sql_registry::SqlRegistrySource;
+use crate::registry::{FeatureRegistryService, FileFeatureRegistry, RegistryMetadata, compat};
+use anyhow::{Context, Result, anyhow};
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use chrono::{DateTime, TimeDelta, Utc};
-use google_cloud_storage::client::{Client as GcsClient, ClientConfig};
+use lasso::Spur;
 use prost::Message;
 use rustc_hash::FxHashMap as HashMap;
+use serde::Serialize;
+use std::collections::VecDeque;
 use std::future::Future;
 use std::ops::Add;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+mod azure;
+#[cfg(feature = "gcp")]
+mod gcs;
+#[cfg(feature = "aws")]
+mod s3;
+
+pub(crate) use azure::{BlobUrl, fetch_azure_registry};
+#[cfg(feature = "gcp")]
+pub(crate) use gcs::fetch_gcs_registry;
+#[cfg(feature = "aws")]
+pub(crate) use s3::fetch_s3_registry;
+
+/// Registry with the generation it was loaded as, swapped together so that readers never
+/// pair a registry with the generation of another one.
+struct Loaded {
+    generation: u64,
+    registry: Box<dyn FeatureRegistryService>,
+}
+
+impl Loaded {
+    fn first(registry: FileFeatureRegistry) -> Self {
+        Self {
+            generation: 0,
+            registry: Box::new(registry),
+        }
+    }
+}
 
 pub struct CachedFileRegistry {
-    inner: ArcSwap<Box<dyn FeatureRegistryService>>,
+    inner: ArcSwap<Loaded>,
     created_at: ArcSwap<DateTime<Utc>>,
     ttl: u64,
+    /// Registries replaced by the last refreshes, oldest first.
+    previous: Mutex<VecDeque<Arc<Loaded>>>,
+    retained_generations: usize,
+    /// Failure of the refreshes since the last successful one.
+    refresh_failure: Mutex<Option<RegistryRefreshFailure>>,
+    /// Background task refreshing the registry, which holds the clients of its storage.
+    refresh_task: Mutex<Option<JoinHandle<()>>>,
+    /// Wakes the refresh task when a request finds the registry stale.
+    refresh_requested: Notify,
+    /// A refresh is running or was requested, so further requests need not ask for one.
+    refreshing: AtomicBool,
+}
+
+/// Least time between the end of a refresh and the next one requested by stale reads, so
+/// that a failing storage is not retried by every request.
+const STALE_REFRESH_COOLDOWN: Duration = Duration::from_secs(1);
+
+/// Number of failed registry refreshes, labelled with `kind` `credentials` when the storage
+/// rejected the credentials of the server and `other` otherwise.
+pub const REGISTRY_REFRESH_FAILURES_METRIC: &str = "feast_registry_refresh_failures_total";
+
+/// Number of registry refreshes, labelled with `trigger` `interval` for the periodic ones
+/// and `stale` for the ones requested by reads of a registry older than its TTL.
+pub const REGISTRY_REFRESHES_METRIC: &str = "feast_registry_refreshes_total";
+
+/// Seconds spent decoding registry protos and interning their names, per load.
+pub const REGISTRY_PARSE_SECONDS_METRIC: &str = "feast_registry_parse_seconds";
+
+/// Registry refreshes failing since `since`. The registry loaded last keeps being served.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegistryRefreshFailure {
+    /// The storage rejected the credentials of the server, e.g. because they expired.
+    pub credentials: bool,
+    pub message: String,
+    pub since: DateTime<Utc>,
+}
+
+/// Cloud storage refused the credentials of the server. Clients are rebuilt when this
+/// happens, so that credentials are loaded again.
+#[derive(Debug)]
+pub(crate) struct CredentialError {
+    storage: &'static str,
+    message: String,
+}
+
+impl CredentialError {
+    fn new(storage: &'static str, err: impl std::fmt::Display) -> Self {
+        Self {
+            storage,
+            message: err.to_string(),
+        }
+    }
 }
 
+impl std::fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} rejected the credentials: {}",
+            self.storage, self.message
+        )
+    }
+}
+
+impl std::error::Error for CredentialError {}
+
 impl CachedFileRegistry {
     async fn create_cached_registry_and_start_background_thread<F, Fut>(
         feature_registry_fn: F,
         ttl: u64,
+        retained_generations: usize,
     ) -> Result<Arc<dyn FeatureRegistryService>>
     where
         F: Fn() -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Result<FileFeatureRegistry>> + Send + 'static,
+        Fut: Future<Output = Result<Option<FileFeatureRegistry>>> + Send + 'static,
     {
-        let feature_registry = feature_registry_fn().await;
+        let feature_registry = first_load(feature_registry_fn().await?)?;
         let result = Arc::new(CachedFileRegistry {
-            inner: ArcSwap::from_pointee(Box::new(feature_registry?)),
+            inner: ArcSwap::from_pointee(Loaded::first(feature_registry)),
             created_at: ArcSwap::from_pointee(Utc::now()),
             ttl,
+            previous: Mutex::new(VecDeque::new()),
+            retained_generations,
+            refresh_failure: Mutex::new(None),
+            refresh_task: Mutex::new(None),
+            refresh_requested: Notify::new(),
+            refreshing: AtomicBool::new(false),
         });
-        start_refresh_task(result.clone(), feature_registry_fn, ttl);
+        let refresh_task = start_refresh_task(result.clone(), feature_registry_fn, ttl);
+        *result
+            .refresh_task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(refresh_task);
         Ok(result)
     }
 
     async fn create_registry<F, Fut>(
         producer_fn: F,
         ttl: Option<u64>,
+        retained_generations: usize,
     ) -> Result<Arc<dyn FeatureRegistryService>>
     where
         F: Fn() -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Result<FileFeatureRegistry>> + Send + 'static,
+        Fut: Future<Output = Result<Option<FileFeatureRegistry>>> + Send + 'static,
     {
         if let Some(ttl_val) = ttl {
-            Self::create_cached_registry_and_start_background_thread(producer_fn, ttl_val).await
+            Self::create_cached_registry_and_start_background_thread(
+                producer_fn,
+                ttl_val,
+                retained_generations,
+            )
+            .await
         } else {
-            let registry = producer_fn().await?;
+            let registry = first_load(producer_fn().await?)?;
             Ok(Arc::new(registry))
         }
     }
@@ -57,126 +175,209 @@ impl CachedFileRegistry {
     pub async fn new_local(
         path: PathBuf,
         cache_ttl_seconds: Option<u64>,
+        retained_generations: usize,
     ) -> Result<Arc<dyn FeatureRegistryService>> {
         let path_arc = Arc::new(path);
+        let loaded = Arc::new(LoadedVersion::default());
         let producer_fn = {
             let path = Arc::clone(&path_arc);
             move || {
                 let path = Arc::clone(&path);
-                async move { FileFeatureRegistry::from_path(path.as_ref()) }
+                let loaded = Arc::clone(&loaded);
+                // Reading and decoding the file blocks
+                async move {
+                    tokio::task::spawn_blocking(move || from_local(path.as_ref(), &loaded)).await?
+                }
             }
         };
-        Self::create_registry(producer_fn, cache_ttl_seconds).await
+        Self::create_registry(producer_fn, cache_ttl_seconds, retained_generations).await
     }
 
-    pub async fn new_s3(
-        bucket_url: String,
-        cache_ttl_seconds: Option<u64>,
+    pub async fn new_sql(
+        config: RegistryConfig,
+        project: String,
     ) -> Result<Arc<dyn FeatureRegistryService>> {
-        let (bucket, key) = parse_storage_url(&bucket_url, "s3", "S3")?;
-        let bucket = Arc::new(bucket);
-        let key = Arc::new(key);
-
-        let config = aws_config::load_from_env().await;
-        let client = Arc::new(aws_sdk_s3::Client::new(&config));
+        let ttl = config.cache_ttl_seconds;
+        let retained_generations = config.retained_generations;
+        let source = Arc::new(SqlRegistrySource::connect(config, project).await?);
+        let producer_fn = move || {
+            let source = Arc::clone(&source);
+            async move { source.load().await.map(Some) }
+        };
+        Self::create_registry(producer_fn, ttl, retained_generations).await
+    }
+}
 
-        let producer_fn = {
-            let client = Arc::clone(&client);
-            let bucket = Arc::clone(&bucket);
-            let key = Arc::clone(&key);
-            move || {
-                let client = Arc::clone(&client);
-                let bucket = Arc::clone(&bucket);
-                let key = Arc::clone(&key);
-                async move { from_s3(client, bucket.as_str(), key.as_str()).await }
+impl CachedFileRegistry {
+    /// Serve `registry` as the next generation, keeping the replaced one if generations
+    /// are retained. The replaced registry is retained before the swap, so that requests
+    /// pinning its generation find it at all times.
+    fn replace(&self, registry: Box<dyn FeatureRegistryService>) {
+        let current = self.inner.load_full();
+        let generation = current.generation + 1;
+        if self.retained_generations > 0 {
+            let mut previous = self.previous.lock().unwrap_or_else(|e| e.into_inner());
+            previous.push_back(current);
+            while previous.len() > self.retained_generations {
+                previous.pop_front();
             }
+        }
+        self.inner.store(Arc::new(Loaded {
+            generation,
+            registry,
+        }));
+    }
+
+    /// Record the error of the last refresh, `None` when it succeeded.
+    fn set_refresh_failure(&self, err: Option<&anyhow::Error>) {
+        let mut failure = self
+            .refresh_failure
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let Some(err) = err else {
+            *failure = None;
+            return;
         };
+        let credentials = err.is::<CredentialError>();
+        let kind = if credentials { "credentials" } else { "other" };
+        metrics::counter!(REGISTRY_REFRESH_FAILURES_METRIC, "kind" => kind).increment(1);
+        let since = failure
+            .as_ref()
+            .map_or_else(Utc::now, |failure| failure.since);
+        *failure = Some(RegistryRefreshFailure {
+            credentials,
+            message: format!("{:#}", err),
+            since,
+        });
+    }
 
-        Self::create_registry(producer_fn, cache_ttl_seconds).await
+    /// The last refresh, successful or unchanged, is older than the TTL.
+    fn is_stale(&self) -> bool {
+        self.created_at
+            .load()
+            .add(TimeDelta::seconds(self.ttl as i64))
+            .lt(&Utc::now())
     }
 
-    pub async fn new_gcs(
-        bucket_url: String,
-        cache_ttl_seconds: Option<u64>,
-    ) -> Result<Arc<dyn FeatureRegistryService>> {
-        let (bucket, object) = parse_storage_url(&bucket_url, "gs", "GCS")?;
-        let bucket = Arc::new(bucket);
-        let object = Arc::new(object);
+    /// Ask the refresh task for a refresh unless one is running or already requested, so
+    /// that the requests served while the registry is stale trigger a single refresh.
+    /// Returns whether a refresh was requested.
+    fn request_refresh(&self) -> bool {
+        if self.refreshing.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        self.refresh_requested.notify_one();
+        true
+    }
 
-        let client_config = ClientConfig::default().with_auth().await?;
-        let client = Arc::new(GcsClient::new(client_config));
+    fn retained(&self, generation: u64) -> Option<Arc<Loaded>> {
+        let previous = self.previous.lock().unwrap_or_else(|e| e.into_inner());
+        previous
+            .iter()
+            .find(|retained| retained.generation == generation)
+            .map(Arc::clone)
+    }
+}
 
-        let producer_fn = {
-            let client = Arc::clone(&client);
-            let bucket = Arc::clone(&bucket);
-            let object = Arc::clone(&object);
-            move || {
-                let client = Arc::clone(&client);
-                let bucket = Arc::clone(&bucket);
-                let object = Arc::clone(&object);
-                async move { from_gcs(client, bucket.as_str(), object.as_str()).await }
-            }
-        };
+/// Load the registry with the current client of `client`. When the storage rejects its
+/// credentials, the client is rebuilt from the environment, which picks up rotated
+/// credentials, and the load is tried once more.
+#[cfg(any(feature = "aws", feature = "gcp"))]
+async fn with_fresh_client<C, L, LF, B, BF>(
+    client: &ArcSwap<C>,
+    load: L,
+    build: B,
+) -> Result<Option<FileFeatureRegistry>>
+where
+    L: Fn(Arc<C>) -> LF,
+    LF: Future<Output = Result<Option<FileFeatureRegistry>>>,
+    B: FnOnce() -> BF,
+    BF: Future<Output = Result<C>>,
+{
+    match load(client.load_full()).await {
+        Err(err) if err.is::<CredentialError>() => {
+            tracing::warn!("{:#}, rebuilding the storage client", err);
+            client.store(Arc::new(build().await?));
+            load(client.load_full()).await
+        }
+        result => result,
+    }
+}
+
+/// Version of the registry object loaded last, e.g. its ETag, so that refreshes skip
+/// objects which did not change.
+struct LoadedVersion<T>(std::sync::Mutex<Option<T>>);
 
-        Self::create_registry(producer_fn, cache_ttl_seconds).await
+impl<T> Default for LoadedVersion<T> {
+    fn default() -> Self {
+        Self(std::sync::Mutex::new(None))
     }
+}
 
-    pub async fn new_sql(
-        config: RegistryConfig,
-        project: String,
-    ) -> Result<Arc<dyn FeatureRegistryService>> {
-        let ttl = config.cache_ttl_seconds;
-        let producer_fn = move || {
-            let config = config.clone();
-            let project = project.clone();
-            async move {
-                let sql_registry = crate::registry::sql_registry::new(config, project).await?;
-                let registry = sql_registry.query_registry().await?;
-                Ok(registry)
-            }
-        };
-        Self::create_registry(producer_fn, ttl).await
+impl<T: Clone + PartialEq> LoadedVersion<T> {
+    fn get(&self) -> Option<T> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn is(&self, version: &T) -> bool {
+        self.get().as_ref() == Some(version)
+    }
+
+    fn set(&self, version: T) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(version);
     }
 }
 
-async fn from_s3(
-    s3_client: Arc<aws_sdk_s3::Client>,
-    bucket: &str,
-    key: &str,
-) -> Result<FileFeatureRegistry> {
-    let proto_file = s3_client
-        .get_object()
-        .bucket(bucket)
-        .key(key)
-        .send()
-        .await?;
-    let data = proto_file.body.collect().await?.into_bytes();
-    let registry_proto = crate::feast::core::Registry::decode(&*data)?;
-    FileFeatureRegistry::from_proto(registry_proto)
+/// Run `parse`, recording its duration in [`REGISTRY_PARSE_SECONDS_METRIC`].
+fn timed_parse<T>(parse: impl FnOnce() -> Result<T>) -> Result<T> {
+    let started = Instant::now();
+    let parsed = parse();
+    metrics::histogram!(REGISTRY_PARSE_SECONDS_METRIC).record(started.elapsed().as_secs_f64());
+    parsed
 }
 
-async fn from_gcs(
-    gcs_client: Arc<GcsClient>,
-    bucket: &str,
-    object: &str,
-) -> Result<FileFeatureRegistry> {
-    use google_cloud_storage::http::objects::download::Range;
-    use google_cloud_storage::http::objects::get::GetObjectRequest;
-
-    let request = GetObjectRequest {
-        bucket: bucket.to_string(),
-        object: object.to_string(),
-        ..Default::default()
-    };
-
-    let data = gcs_client
-        .download_object(&request, &Range::default())
-        .await?;
-    let registry_proto = crate::feast::core::Registry::decode(&*data)?;
-    FileFeatureRegistry::from_proto(registry_proto)
+/// Decode and intern the registry object `data` downloaded from `location` on a blocking
+/// thread, as this takes hundreds of milliseconds for large registries.
+pub(crate) async fn parse_registry<D>(data: D, location: String) -> Result<FileFeatureRegistry>
+where
+    D: AsRef<[u8]> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        timed_parse(|| {
+            FileFeatureRegistry::from_proto(compat::decode_registry(data.as_ref(), &location)?)
+        })
+    })
+    .await?
+}
+
+/// Registry of the first load, which cannot be skipped as unchanged.
+fn first_load(registry: Option<FileFeatureRegistry>) -> Result<FileFeatureRegistry> {
+    registry.ok_or_else(|| anyhow!("Registry was reported unchanged before it was loaded"))
+}
+
+/// Read the registry file unless its modification time and size are the ones of the file
+/// loaded last.
+fn from_local(
+    path: &Path,
+    loaded: &LoadedVersion<(SystemTime, u64)>,
+) -> Result<Option<FileFeatureRegistry>> {
+    let metadata = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read registry file at '{}'", path.display()))?;
+    let version = (metadata.modified()?, metadata.len());
+    if loaded.is(&version) {
+        return Ok(None);
+    }
+    let registry = timed_parse(|| FileFeatureRegistry::from_path(path))?;
+    loaded.set(version);
+    Ok(Some(registry))
 }
 
-fn parse_storage_url(url_str: &str, scheme: &str, provider_name: &str) -> Result<(String, String)> {
+#[cfg(any(feature = "aws", feature = "gcp"))]
+pub(crate) fn parse_storage_url(
+    url_str: &str,
+    scheme: &str,
+    provider_name: &str,
+) -> Result<(String, String)> {
     let url = url::Url::parse(url_str)?;
     if url.scheme() != scheme {
         return Err(anyhow::anyhow!(
@@ -201,26 +402,43 @@ fn start_refresh_task<F, Fut>(
     mut registry: Arc<CachedFileRegistry>,
     feature_registry_fn: F,
     ttl: u64,
-) where
+) -> JoinHandle<()>
+where
     F: Fn() -> Fut + Send + Sync + 'static,
-    Fut: Future<Output = Result<FileFeatureRegistry>> + Send + 'static,
+    Fut: Future<Output = Result<Option<FileFeatureRegistry>>> + Send + 'static,
 {
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(ttl));
         loop {
-            interval.tick().await;
+            let trigger = tokio::select! {
+                _ = interval.tick() => "interval",
+                _ = registry.refresh_requested.notified() => "stale",
+            };
+            registry.refreshing.store(true, Ordering::Release);
+            metrics::counter!(REGISTRY_REFRESHES_METRIC, "trigger" => trigger).increment(1);
             let new_registry = feature_registry_fn().await;
             match new_registry {
-                Ok(reg) => {
-                    registry.inner.store(Arc::new(Box::new(reg)));
+                Ok(None) => {
+                    tracing::debug!("Registry unchanged, keeping the loaded one");
+                    registry.created_at.store(Arc::new(Utc::now()));
+                    registry.set_refresh_failure(None);
+                }
+                Ok(Some(reg)) => {
+                    registry.replace(Box::new(reg));
                     registry.created_at.store(Arc::new(Utc::now()));
+                    registry.set_refresh_failure(None);
+                    crate::intern::record_metrics();
                 }
-                Err(msg) => {
-                    tracing::error!("Failed to refresh registry: {:?}", msg);
+                Err(err) => {
+                    tracing::error!("Failed to refresh registry: {:?}", err);
+                    registry.set_refresh_failure(Some(&err));
                 }
             }
+            interval.reset();
+            tokio::time::sleep(STALE_REFRESH_COOLDOWN).await;
+            registry.refreshing.store(false, Ordering::Release);
         }
-    });
+    })
 }
 
 #[async_trait]
@@ -228,49 +446,283 @@ impl FeatureRegistryService for CachedFileRegistry {
     async fn request_to_view_keys(
         &self,
         request: RequestedFeatures,
-    ) -> Result<HashMap<Feature, Arc<FeatureView>>> {
-        if self
-            .created_at
-            .load()
-            .add(TimeDelta::seconds(self.ttl as i64))
-            .lt(&Utc::now())
-        {
-            tracing::warn!("Using stale registry");
+    ) -> Result<OrderedMap<Feature, Arc<FeatureView>>> {
+        // Requests keep being answered from the loaded registry while it is refreshed
+        if self.is_stale() && self.request_refresh() {
+            tracing::warn!("Registry is older than its TTL, refreshing it in the background");
+        }
+        let loaded = self.inner.load_full();
+        loaded.registry.request_to_view_keys(request).await
+    }
+
+    /// Resolved against the registry snapshot the returned generation belongs to.
+    async fn request_to_view_keys_versioned(
+        &self,
+        request: RequestedFeatures,
+    ) -> Result<(OrderedMap<Feature, Arc<FeatureView>>, u64)> {
+        if self.is_stale() && self.request_refresh() {
+            tracing::warn!("Registry is older than its TTL, refreshing it in the background");
+        }
+        let loaded = self.inner.load_full();
+        let views = loaded.registry.request_to_view_keys(request).await?;
+        Ok((views, loaded.generation))
+    }
+
+    async fn request_to_view_keys_at(
+        &self,
+        request: RequestedFeatures,
+        generation: u64,
+    ) -> Result<OrderedMap<Feature, Arc<FeatureView>>> {
+        if let Some(retained) = self.retained(generation) {
+            return retained.registry.request_to_view_keys(request).await;
+        }
+        let loaded = self.inner.load_full();
+        if generation != loaded.generation {
+            return Err(
+                FeastCoreError::registry_version_not_found(generation, loaded.generation).into(),
+            );
+        }
+        if self.is_stale() && self.request_refresh() {
+            tracing::warn!("Registry is older than its TTL, refreshing it in the background");
+        }
+        loaded.registry.request_to_view_keys(request).await
+    }
+
+    async fn feature_view(&self, feature_view_name: Spur) -> Result<Arc<FeatureView>> {
+        let registry = &self.inner.load().registry;
+        registry.feature_view(feature_view_name).await
+    }
+
+    async fn feature_views(&self) -> Result<Vec<Arc<FeatureView>>> {
+        let registry = &self.inner.load().registry;
+        registry.feature_views().await
+    }
+
+    async fn push_source_views(&self, push_source_name: Spur) -> Result<Vec<Arc<FeatureView>>> {
+        let registry = &self.inner.load().registry;
+        registry.push_source_views(push_source_name).await
+    }
+
+    async fn metadata(&self) -> Result<RegistryMetadata> {
+        let registry = &self.inner.load().registry;
+        registry.metadata().await
+    }
+
+    fn generation(&self) -> u64 {
+        self.inner.load().generation
+    }
+
+    fn refresh_failure(&self) -> Option<RegistryRefreshFailure> {
+        self.refresh_failure
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Stop the refreshes, dropping the storage clients and connection pools they hold.
+    /// The registry loaded last keeps being served.
+    async fn shutdown(&self) {
+        let refresh_task = self
+            .refresh_task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(refresh_task) = refresh_task {
+            refresh_task.abort();
         }
-        let registry = self.inner.load();
-        registry.request_to_view_keys(request).await
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::feast::core::{Entity, EntitySpecV2, Registry};
     use crate::model::{GetOnlineFeaturesRequest, RequestedFeatures};
+    use prost::Message;
+
+    #[test]
+    fn unchanged_local_registry_is_not_reloaded() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("registry.pb.zst");
+        let write = |registry: &Registry| {
+            std::fs::write(
+                &path,
+                zstd::encode_all(registry.encode_to_vec().as_slice(), 3)?,
+            )
+        };
+        let mut registry = Registry::default();
+        write(&registry)?;
+        let loaded = LoadedVersion::default();
+
+        let first = from_local(&path, &loaded);
+        let unchanged = from_local(&path, &loaded);
+        registry.entities.push(Entity {
+            spec: Some(EntitySpecV2 {
+                name: "driver".to_string(),
+                join_key: "driver_id".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        write(&registry)?;
+        let changed = from_local(&path, &loaded);
+
+        assert!(first?.is_some());
+        assert!(unchanged?.is_none());
+        assert!(changed?.is_some());
+        Ok(())
+    }
+
+    #[cfg(any(feature = "aws", feature = "gcp"))]
+    #[tokio::test]
+    async fn rejected_credentials_rebuild_the_client() -> anyhow::Result<()> {
+        let client = ArcSwap::from_pointee("expired");
+        let load = |client: Arc<&'static str>| async move {
+            match *client {
+                "expired" => Err(CredentialError::new("S3", "ExpiredToken").into()),
+                _ => Ok(None),
+            }
+        };
+        let result = with_fresh_client(&client, load, || async { Ok("fresh") }).await;
+        assert!(result?.is_none());
+        assert_eq!(**client.load(), "fresh");
+
+        let failing = |_: Arc<&'static str>| async { Err(anyhow!("bucket not found")) };
+        let result = with_fresh_client(&client, failing, || async {
+            unreachable!("only rejected credentials rebuild the client")
+        })
+        .await;
+        assert!(!result.unwrap_err().is::<CredentialError>());
+        Ok(())
+    }
+
+    fn cached_registry(
+        registry: FileFeatureRegistry,
+        retained_generations: usize,
+    ) -> CachedFileRegistry {
+        CachedFileRegistry {
+            inner: ArcSwap::from_pointee(Loaded::first(registry)),
+            created_at: ArcSwap::from_pointee(Utc::now()),
+            ttl: 60,
+            previous: Mutex::new(VecDeque::new()),
+            retained_generations,
+            refresh_failure: Mutex::new(None),
+            refresh_task: Mutex::new(None),
+            refresh_requested: Notify::new(),
+            refreshing: AtomicBool::new(false),
+        }
+    }
 
     #[tokio::test]
-    #[ignore]
-    async fn read_registry_from_s3() -> anyhow::Result<()> {
-        let bucket_url = "s3://feast-rust-feature-registry/registry.db".to_string();
-        let s3_registry = super::CachedFileRegistry::new_s3(bucket_url, None).await?;
-        let mut request_obj = GetOnlineFeaturesRequest::default();
-        request_obj.features = vec!["driver_hourly_stats_fresh:conv_rate".to_string()].into();
-        let requested_features = RequestedFeatures::from(&request_obj);
-        let result = s3_registry.request_to_view_keys(requested_features).await?;
-        println!("{:#?}", result);
+    async fn stale_reads_request_a_single_refresh() -> Result<()> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/registry.pb");
+        let registry = cached_registry(FileFeatureRegistry::from_path(&path)?, 0);
+        let request = || {
+            RequestedFeatures::try_from(&GetOnlineFeaturesRequest {
+                features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+                ..Default::default()
+            })
+        };
+
+        registry.request_to_view_keys(request()?).await?;
+        assert!(!registry.refreshing.load(Ordering::Acquire));
+
+        registry
+            .created_at
+            .store(Arc::new(Utc::now() - TimeDelta::seconds(120)));
+        // Stale reads are answered from the loaded registry and only the first one asks
+        // for a refresh
+        registry.request_to_view_keys(request()?).await?;
+        registry.request_to_view_keys(request()?).await?;
+        assert!(registry.refreshing.load(Ordering::Acquire));
+        assert!(!registry.request_refresh());
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            registry.refresh_requested.notified(),
+        )
+        .await?;
         Ok(())
     }
 
     #[tokio::test]
-    #[ignore]
-    async fn read_registry_from_gcs() -> anyhow::Result<()> {
-        let bucket_url = "gs://feast-rust-feature-registry/registry.db".to_string();
-        let gcs_registry = super::CachedFileRegistry::new_gcs(bucket_url, None).await?;
-        let mut request_obj = GetOnlineFeaturesRequest::default();
-        request_obj.features = vec!["driver_hourly_stats_fresh:conv_rate".to_string()].into();
-        let requested_features = RequestedFeatures::from(&request_obj);
-        let result = gcs_registry
-            .request_to_view_keys(requested_features)
-            .await?;
-        println!("{:#?}", result);
+    async fn pinned_generations_use_retained_registries() -> Result<()> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/registry.pb");
+        let proto = crate::registry::file_registry::read_registry_proto(&path)?;
+        let mut reduced = proto.clone();
+        reduced.feature_views.retain(|view| {
+            view.spec
+                .as_ref()
+                .is_none_or(|spec| spec.name != "driver_hourly_stats")
+        });
+        let registry = cached_registry(FileFeatureRegistry::from_proto(proto)?, 1);
+        let request = || {
+            RequestedFeatures::try_from(&GetOnlineFeaturesRequest {
+                features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+                ..Default::default()
+            })
+        };
+
+        registry.replace(Box::new(FileFeatureRegistry::from_proto(reduced.clone())?));
+        assert_eq!(registry.generation(), 1);
+        assert!(registry.request_to_view_keys(request()?).await.is_err());
+        assert!(
+            registry
+                .request_to_view_keys_at(request()?, 0)
+                .await
+                .is_ok()
+        );
+
+        registry.replace(Box::new(FileFeatureRegistry::from_proto(reduced)?));
+        let err = registry
+            .request_to_view_keys_at(request()?, 0)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<FeastCoreError>(),
+            Some(&FeastCoreError::registry_version_not_found(0, 2))
+        );
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn versioned_reads_pair_views_with_their_generation() -> Result<()> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/registry.pb");
+        let proto = crate::registry::file_registry::read_registry_proto(&path)?;
+        let mut reduced = proto.clone();
+        reduced.feature_views.retain(|view| {
+            view.spec
+                .as_ref()
+                .is_none_or(|spec| spec.name != "driver_hourly_stats")
+        });
+        let registry = Arc::new(cached_registry(
+            FileFeatureRegistry::from_proto(proto.clone())?,
+            0,
+        ));
+        // Even generations resolve the view, odd ones do not
+        let refreshes = tokio::spawn({
+            let registry = Arc::clone(&registry);
+            async move {
+                for generation in 1..=200 {
+                    let proto = if generation % 2 == 0 {
+                        &proto
+                    } else {
+                        &reduced
+                    };
+                    registry.replace(Box::new(FileFeatureRegistry::from_proto(proto.clone())?));
+                    tokio::task::yield_now().await;
+                }
+                anyhow::Ok(())
+            }
+        });
+        while !refreshes.is_finished() {
+            let request = RequestedFeatures::try_from(&GetOnlineFeaturesRequest {
+                features: Some(vec!["driver_hourly_stats:conv_rate".to_string()]),
+                ..Default::default()
+            })?;
+            if let Ok((_, generation)) = registry.request_to_view_keys_versioned(request).await {
+                assert_eq!(generation % 2, 0);
+            }
+        }
+        refreshes.await?
+    }
 }
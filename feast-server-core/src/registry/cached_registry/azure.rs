@@ -0,0 +1,229 @@
+//! Registries stored on Azure Blob Storage, read over HTTPS.
+//!
+//! Blobs are addressed by `https://<account>.blob.core.windows.net/<container>/<blob>` URLs,
+//! `abfss://<container>@<account>.dfs.core.windows.net/<blob>` (or `abfs`, `wasb`, `wasbs`)
+//! URLs, or `az://<container>/<blob>` with the account in `AZURE_STORAGE_ACCOUNT_NAME`.
+//! Requests are authorized with the SAS token of the URL, or the one of
+//! `AZURE_STORAGE_SAS_TOKEN`, and are anonymous otherwise.
+
+use super::{CachedFileRegistry, CredentialError, LoadedVersion, parse_registry};
+use crate::feast::core::Registry;
+use crate::registry::{FeatureRegistryService, FileFeatureRegistry, compat};
+use anyhow::{Result, anyhow};
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use std::sync::Arc;
+use url::Url;
+
+/// Version of the Blob service REST API the requests are written against.
+const AZURE_STORAGE_API_VERSION: &str = "2021-08-06";
+
+impl CachedFileRegistry {
+    pub async fn new_azure(
+        location: String,
+        cache_ttl_seconds: Option<u64>,
+        retained_generations: usize,
+    ) -> Result<Arc<dyn FeatureRegistryService>> {
+        let blob = Arc::new(BlobUrl::from_env(&location)?);
+        let client = reqwest::Client::new();
+        let loaded = Arc::new(LoadedVersion::default());
+        let producer_fn = move || {
+            let blob = Arc::clone(&blob);
+            let client = client.clone();
+            let loaded = Arc::clone(&loaded);
+            async move { from_azure(&client, &blob, &loaded).await }
+        };
+        Self::create_registry(producer_fn, cache_ttl_seconds, retained_generations).await
+    }
+}
+
+/// HTTPS URL of a blob, and the same URL without its SAS token for messages.
+#[derive(Debug, PartialEq)]
+pub(crate) struct BlobUrl {
+    url: Url,
+    display: String,
+}
+
+impl BlobUrl {
+    pub(crate) fn from_env(location: &str) -> Result<Self> {
+        Self::new(
+            location,
+            std::env::var("AZURE_STORAGE_ACCOUNT_NAME").ok().as_deref(),
+            std::env::var("AZURE_STORAGE_SAS_TOKEN").ok().as_deref(),
+        )
+    }
+
+    fn new(location: &str, account: Option<&str>, sas_token: Option<&str>) -> Result<Self> {
+        let parsed = Url::parse(location)
+            .map_err(|err| anyhow!("Invalid Azure Blob Storage URL '{}': {}", location, err))?;
+        let host = parsed.host_str().unwrap_or_default();
+        let mut url = match parsed.scheme() {
+            "https" | "http" => parsed.clone(),
+            "az" => {
+                let account = account.ok_or_else(|| {
+                    anyhow!(
+                        "AZURE_STORAGE_ACCOUNT_NAME must be set to read {}",
+                        location
+                    )
+                })?;
+                blob_service_url(account, host, parsed.path())?
+            }
+            "abfs" | "abfss" | "wasb" | "wasbs" => {
+                let container = parsed.username();
+                let account = host.split('.').next().unwrap_or_default();
+                if container.is_empty() || account.is_empty() {
+                    return Err(anyhow!(
+                        "Azure Blob Storage URL '{}' must name <container>@<account>",
+                        location
+                    ));
+                }
+                blob_service_url(account, container, parsed.path())?
+            }
+            other => {
+                return Err(anyhow!(
+                    "Unsupported Azure Blob Storage URL scheme '{}' in '{}'",
+                    other,
+                    location
+                ));
+            }
+        };
+        if url.query().is_none()
+            && let Some(sas_token) = sas_token
+        {
+            url.set_query(Some(sas_token.trim_start_matches('?')));
+        }
+        let mut display = url.clone();
+        display.set_query(None);
+        Ok(Self {
+            url,
+            display: display.to_string(),
+        })
+    }
+}
+
+fn blob_service_url(account: &str, container: &str, blob: &str) -> Result<Url> {
+    Ok(Url::parse(&format!(
+        "https://{}.blob.core.windows.net/{}/{}",
+        account,
+        container,
+        blob.trim_start_matches('/')
+    ))?)
+}
+
+fn status_error(blob: &BlobUrl, status: StatusCode) -> anyhow::Error {
+    let message = format!("GET {} answered {}", blob.display, status);
+    if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        CredentialError::new("Azure Blob Storage", message).into()
+    } else {
+        anyhow!(message)
+    }
+}
+
+/// Download the registry blob with a conditional GET, answered 304 without a body when the
+/// blob still has the ETag of the last load.
+async fn from_azure(
+    client: &reqwest::Client,
+    blob: &BlobUrl,
+    loaded: &LoadedVersion<String>,
+) -> Result<Option<FileFeatureRegistry>> {
+    let mut request = client
+        .get(blob.url.clone())
+        .header("x-ms-version", AZURE_STORAGE_API_VERSION);
+    if let Some(e_tag) = loaded.get() {
+        request = request.header(IF_NONE_MATCH, e_tag);
+    }
+    let response = request.send().await?;
+    match response.status() {
+        StatusCode::NOT_MODIFIED => return Ok(None),
+        status if !status.is_success() => return Err(status_error(blob, status)),
+        _ => {}
+    }
+    let e_tag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|e_tag| e_tag.to_str().ok())
+        .map(str::to_string);
+    let data = response.bytes().await?;
+    let registry = parse_registry(data, blob.display.clone()).await?;
+    if let Some(e_tag) = e_tag {
+        loaded.set(e_tag);
+    }
+    Ok(Some(registry))
+}
+
+pub(crate) async fn fetch_azure_registry(blob: &BlobUrl) -> Result<Registry> {
+    let response = reqwest::Client::new()
+        .get(blob.url.clone())
+        .header("x-ms-version", AZURE_STORAGE_API_VERSION)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(status_error(blob, response.status()));
+    }
+    compat::decode_registry(&response.bytes().await?, &blob.display)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_http::HttpStub;
+    use prost::Message;
+
+    #[test]
+    fn blob_urls_of_azure_locations() -> Result<()> {
+        let https = BlobUrl::new(
+            "https://acc.blob.core.windows.net/feast/registry.pb?sv=2021&sig=secret",
+            None,
+            Some("sv=ignored"),
+        )?;
+        assert_eq!(https.url.query(), Some("sv=2021&sig=secret"));
+        assert_eq!(
+            https.display,
+            "https://acc.blob.core.windows.net/feast/registry.pb"
+        );
+
+        let az = BlobUrl::new("az://feast/repo/registry.pb", Some("acc"), Some("?sig=s"))?;
+        assert_eq!(
+            az.url.as_str(),
+            "https://acc.blob.core.windows.net/feast/repo/registry.pb?sig=s"
+        );
+        assert!(BlobUrl::new("az://feast/registry.pb", None, None).is_err());
+
+        let abfss = BlobUrl::new(
+            "abfss://feast@acc.dfs.core.windows.net/registry.pb",
+            None,
+            None,
+        )?;
+        assert_eq!(
+            abfss.url.as_str(),
+            "https://acc.blob.core.windows.net/feast/registry.pb"
+        );
+        assert!(BlobUrl::new("abfss://acc.dfs.core.windows.net/registry.pb", None, None).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn conditional_get_skips_unchanged_blobs() -> Result<()> {
+        let body = Registry::default().encode_to_vec();
+        let stub = HttpStub::start(vec![
+            (200, vec![("ETag", "\"v1\"")], body),
+            (304, vec![], vec![]),
+            (403, vec![], vec![]),
+        ])
+        .await?;
+        let blob = BlobUrl::new(&format!("{}/feast/registry.pb", stub.url()), None, None)?;
+        let client = reqwest::Client::new();
+        let loaded = LoadedVersion::default();
+
+        assert!(from_azure(&client, &blob, &loaded).await?.is_some());
+        assert!(from_azure(&client, &blob, &loaded).await?.is_none());
+        let err = from_azure(&client, &blob, &loaded).await.unwrap_err();
+        assert!(err.is::<CredentialError>());
+
+        let requests = stub.requests();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+        assert!(requests[1].contains("x-ms-version: 2021-08-06"));
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+//! Registries stored on Google Cloud Storage, built with the `gcp` feature.
+
+use super::{
+    CachedFileRegistry, CredentialError, LoadedVersion, parse_registry, parse_storage_url,
+    with_fresh_client,
+};
+use crate::feast::core::Registry;
+use crate::registry::{FeatureRegistryService, FileFeatureRegistry, compat};
+use anyhow::Result;
+use arc_swap::ArcSwap;
+use google_cloud_storage::client::{Client as GcsClient, ClientConfig};
+use std::sync::Arc;
+
+impl CachedFileRegistry {
+    pub async fn new_gcs(
+        bucket_url: String,
+        cache_ttl_seconds: Option<u64>,
+        retained_generations: usize,
+    ) -> Result<Arc<dyn FeatureRegistryService>> {
+        let (bucket, object) = parse_storage_url(&bucket_url, "gs", "GCS")?;
+        let bucket = Arc::new(bucket);
+        let object = Arc::new(object);
+
+        let client = Arc::new(ArcSwap::from_pointee(gcs_client().await?));
+
+        let loaded = Arc::new(LoadedVersion::default());
+        let producer_fn = {
+            let client = Arc::clone(&client);
+            let bucket = Arc::clone(&bucket);
+            let object = Arc::clone(&object);
+            move || {
+                let client = Arc::clone(&client);
+                let bucket = Arc::clone(&bucket);
+                let object = Arc::clone(&object);
+                let loaded = Arc::clone(&loaded);
+                async move {
+                    with_fresh_client(
+                        &client,
+                        |client| from_gcs(client, bucket.as_str(), object.as_str(), &loaded),
+                        gcs_client,
+                    )
+                    .await
+                }
+            }
+        };
+
+        Self::create_registry(producer_fn, cache_ttl_seconds, retained_generations).await
+    }
+}
+
+async fn gcs_client() -> Result<GcsClient> {
+    Ok(GcsClient::new(ClientConfig::default().with_auth().await?))
+}
+
+fn gcs_error(err: google_cloud_storage::http::Error) -> anyhow::Error {
+    use google_cloud_storage::http::Error;
+
+    match &err {
+        Error::TokenSource(_) => CredentialError::new("GCS", err).into(),
+        Error::Response(response) if matches!(response.code, 401 | 403) => {
+            CredentialError::new("GCS", err).into()
+        }
+        _ => err.into(),
+    }
+}
+
+/// Download the registry object unless its generation is the one loaded last. Only the
+/// object metadata is fetched for unchanged objects.
+async fn from_gcs(
+    gcs_client: Arc<GcsClient>,
+    bucket: &str,
+    object: &str,
+    loaded: &LoadedVersion<i64>,
+) -> Result<Option<FileFeatureRegistry>> {
+    use google_cloud_storage::http::objects::download::Range;
+    use google_cloud_storage::http::objects::get::GetObjectRequest;
+
+    let mut request = GetObjectRequest {
+        bucket: bucket.to_string(),
+        object: object.to_string(),
+        ..Default::default()
+    };
+    let generation = gcs_client
+        .get_object(&request)
+        .await
+        .map_err(gcs_error)?
+        .generation;
+    if loaded.is(&generation) {
+        return Ok(None);
+    }
+    // Pinned, so that the downloaded content is the one of the checked generation
+    request.generation = Some(generation);
+    let data = gcs_client
+        .download_object(&request, &Range::default())
+        .await
+        .map_err(gcs_error)?;
+    let location = format!("gs://{}/{}", bucket, object);
+    let registry = parse_registry(data, location).await?;
+    loaded.set(generation);
+    Ok(Some(registry))
+}
+
+pub(crate) async fn fetch_gcs_registry(
+    gcs_client: &GcsClient,
+    bucket: &str,
+    object: &str,
+) -> Result<Registry> {
+    use google_cloud_storage::http::objects::download::Range;
+    use google_cloud_storage::http::objects::get::GetObjectRequest;
+
+    let request = GetObjectRequest {
+        bucket: bucket.to_string(),
+        object: object.to_string(),
+        ..Default::default()
+    };
+
+    let data = gcs_client
+        .download_object(&request, &Range::default())
+        .await?;
+    compat::decode_registry(&data, &format!("gs://{}/{}", bucket, object))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{GetOnlineFeaturesRequest, RequestedFeatures};
+
+    #[test]
+    fn gcs_token_errors_are_credential_errors() {
+        use google_cloud_storage::http::Error;
+        use google_cloud_storage::http::error::ErrorResponse;
+
+        let rejected = Error::Response(ErrorResponse {
+            code: 401,
+            errors: vec![],
+            message: "Invalid Credentials".to_string(),
+        });
+        assert!(gcs_error(rejected).is::<CredentialError>());
+        let missing = Error::Response(ErrorResponse {
+            code: 404,
+            errors: vec![],
+            message: "No such object".to_string(),
+        });
+        assert!(!gcs_error(missing).is::<CredentialError>());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn read_registry_from_gcs() -> anyhow::Result<()> {
+        let bucket_url = "gs://feast-rust-feature-registry/registry.db".to_string();
+        let gcs_registry = super::CachedFileRegistry::new_gcs(bucket_url, None, 0).await?;
+        let request_obj = GetOnlineFeaturesRequest {
+            features: Some(vec!["driver_hourly_stats_fresh:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let requested_features = RequestedFeatures::try_from(&request_obj)?;
+        let result = gcs_registry
+            .request_to_view_keys(requested_features)
+            .await?;
+        println!("{:#?}", result);
+        Ok(())
+    }
+}
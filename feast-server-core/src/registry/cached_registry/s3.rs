@@ -0,0 +1,171 @@
+//! Registries stored on S3, built with the `aws` feature.
+
+use super::{
+    CachedFileRegistry, CredentialError, LoadedVersion, parse_registry, parse_storage_url,
+    with_fresh_client,
+};
+use crate::feast::core::Registry;
+use crate::registry::{FeatureRegistryService, FileFeatureRegistry, compat};
+use anyhow::Result;
+use arc_swap::ArcSwap;
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::{DisplayErrorContext, ProvideErrorMetadata, SdkError};
+use std::sync::Arc;
+
+impl CachedFileRegistry {
+    pub async fn new_s3(
+        bucket_url: String,
+        cache_ttl_seconds: Option<u64>,
+        retained_generations: usize,
+    ) -> Result<Arc<dyn FeatureRegistryService>> {
+        let (bucket, key) = parse_storage_url(&bucket_url, "s3", "S3")?;
+        let bucket = Arc::new(bucket);
+        let key = Arc::new(key);
+
+        let client = Arc::new(ArcSwap::from_pointee(s3_client().await));
+
+        let loaded = Arc::new(LoadedVersion::default());
+        let producer_fn = {
+            let client = Arc::clone(&client);
+            let bucket = Arc::clone(&bucket);
+            let key = Arc::clone(&key);
+            move || {
+                let client = Arc::clone(&client);
+                let bucket = Arc::clone(&bucket);
+                let key = Arc::clone(&key);
+                let loaded = Arc::clone(&loaded);
+                async move {
+                    with_fresh_client(
+                        &client,
+                        |client| from_s3(client, bucket.as_str(), key.as_str(), &loaded),
+                        || async { Ok(s3_client().await) },
+                    )
+                    .await
+                }
+            }
+        };
+
+        Self::create_registry(producer_fn, cache_ttl_seconds, retained_generations).await
+    }
+}
+
+async fn s3_client() -> aws_sdk_s3::Client {
+    aws_sdk_s3::Client::new(&aws_config::load_from_env().await)
+}
+
+/// Error codes of S3 requests signed with missing, invalid or expired credentials.
+const S3_CREDENTIAL_ERROR_CODES: &[&str] = &[
+    "ExpiredToken",
+    "ExpiredTokenException",
+    "InvalidAccessKeyId",
+    "InvalidToken",
+    "SignatureDoesNotMatch",
+    "TokenRefreshRequired",
+];
+
+fn s3_error<E>(err: SdkError<E, HttpResponse>) -> anyhow::Error
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    let rejected = err
+        .raw_response()
+        .is_some_and(|response| matches!(response.status().as_u16(), 401 | 403))
+        || err
+            .code()
+            .is_some_and(|code| S3_CREDENTIAL_ERROR_CODES.contains(&code))
+        // Credentials which cannot be loaded fail the request before it is sent
+        || matches!(err, SdkError::ConstructionFailure(_) | SdkError::DispatchFailure(_))
+            && DisplayErrorContext(&err)
+                .to_string()
+                .to_lowercase()
+                .contains("credentials");
+    if rejected {
+        CredentialError::new("S3", DisplayErrorContext(&err)).into()
+    } else {
+        err.into()
+    }
+}
+
+/// Validators of the S3 object loaded last.
+#[derive(Clone, PartialEq)]
+struct S3Version {
+    e_tag: Option<String>,
+    last_modified: Option<aws_sdk_s3::primitives::DateTime>,
+}
+
+/// Download the registry object with a conditional GET: S3 answers 304 without a body when
+/// the object still has the ETag, or was not modified since the time, of the last load.
+async fn from_s3(
+    s3_client: Arc<aws_sdk_s3::Client>,
+    bucket: &str,
+    key: &str,
+    loaded: &LoadedVersion<S3Version>,
+) -> Result<Option<FileFeatureRegistry>> {
+    let mut request = s3_client.get_object().bucket(bucket).key(key);
+    match loaded.get() {
+        Some(S3Version {
+            e_tag: Some(e_tag), ..
+        }) => request = request.if_none_match(e_tag),
+        Some(S3Version {
+            last_modified: Some(last_modified),
+            ..
+        }) => request = request.if_modified_since(last_modified),
+        _ => {}
+    }
+    let object = match request.send().await {
+        Ok(object) => object,
+        Err(err)
+            if err
+                .raw_response()
+                .is_some_and(|response| response.status().as_u16() == 304) =>
+        {
+            return Ok(None);
+        }
+        Err(err) => return Err(s3_error(err)),
+    };
+    let version = S3Version {
+        e_tag: object.e_tag().map(str::to_string),
+        last_modified: object.last_modified().copied(),
+    };
+    let data = object.body.collect().await?.into_bytes();
+    let location = format!("s3://{}/{}", bucket, key);
+    let registry = parse_registry(data, location).await?;
+    loaded.set(version);
+    Ok(Some(registry))
+}
+
+pub(crate) async fn fetch_s3_registry(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    key: &str,
+) -> Result<Registry> {
+    let proto_file = s3_client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await?;
+    let data = proto_file.body.collect().await?.into_bytes();
+    compat::decode_registry(&data, &format!("s3://{}/{}", bucket, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{GetOnlineFeaturesRequest, RequestedFeatures};
+
+    #[tokio::test]
+    #[ignore]
+    async fn read_registry_from_s3() -> anyhow::Result<()> {
+        let bucket_url = "s3://feast-rust-feature-registry/registry.db".to_string();
+        let s3_registry = super::CachedFileRegistry::new_s3(bucket_url, None, 0).await?;
+        let request_obj = GetOnlineFeaturesRequest {
+            features: Some(vec!["driver_hourly_stats_fresh:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let requested_features = RequestedFeatures::try_from(&request_obj)?;
+        let result = s3_registry.request_to_view_keys(requested_features).await?;
+        println!("{:#?}", result);
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+//! Compatibility checks for registry protos written by different Feast releases.
+//! Decoding a registry produced by an unsupported Feast version otherwise fails with
+//! low level protobuf errors or silently drops objects the server does not understand.
+
+use crate::feast::core::Registry;
+use anyhow::{Context, Result, anyhow};
+use prost::Message;
+use std::borrow::Cow;
+use std::io::Read;
+
+/// Registry schema versions written by the Feast releases this server understands.
+/// Registries created before the field was introduced leave it empty.
+pub(crate) const SUPPORTED_SCHEMA_VERSIONS: &[&str] = &["1"];
+
+/// Highest top level field number of the `Registry` message known to this server.
+const LAST_KNOWN_REGISTRY_FIELD: u32 = 17;
+
+/// Subset of the `Registry` message which can be decoded even when the rest
+/// of the registry has an incompatible layout.
+#[derive(Clone, PartialEq, Message)]
+struct RegistryHeader {
+    #[prost(string, tag = "3")]
+    registry_schema_version: String,
+}
+
+/// Decode a serialized registry and validate that it can be served.
+/// `location` identifies the registry in error messages; registries whose location ends
+/// with `.zst` or `.gz` are decompressed first.
+pub(crate) fn decode_registry(buf: &[u8], location: &str) -> Result<Registry> {
+    let buf = decompress(buf, location)?;
+    let buf = buf.as_ref();
+    let registry = match Registry::decode(buf) {
+        Ok(registry) => registry,
+        Err(err) => {
+            let hint = match RegistryHeader::decode(buf) {
+                Ok(header) if !is_supported_version(&header.registry_schema_version) => format!(
+                    "registry schema version '{}' is not supported (supported: {})",
+                    header.registry_schema_version,
+                    SUPPORTED_SCHEMA_VERSIONS.join(", ")
+                ),
+                _ => "the file may be corrupted or written by an incompatible Feast version"
+                    .to_string(),
+            };
+            return Err(anyhow::Error::new(err).context(format!(
+                "Failed to parse registry protobuf at '{}': {}",
+                location, hint
+            )));
+        }
+    };
+
+    let unknown_fields = unknown_top_level_fields(buf);
+    if !unknown_fields.is_empty() {
+        tracing::warn!(
+            "Registry at '{}' contains fields unknown to this server (field numbers {:?}), \
+             it was probably written by a newer Feast version; these objects are ignored",
+            location,
+            unknown_fields
+        );
+    }
+    check_compatibility(&registry).with_context(|| format!("Registry at '{}'", location))?;
+    Ok(registry)
+}
+
+/// Content of a registry object, decompressed according to the extension of its location.
+fn decompress<'a>(buf: &'a [u8], location: &str) -> Result<Cow<'a, [u8]>> {
+    let decompressed = if location.ends_with(".zst") {
+        zstd::decode_all(buf)
+    } else if location.ends_with(".gz") {
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(buf)
+            .read_to_end(&mut decompressed)
+            .map(|_| decompressed)
+    } else {
+        return Ok(Cow::Borrowed(buf));
+    };
+    decompressed
+        .map(Cow::Owned)
+        .with_context(|| format!("Failed to decompress registry at '{}'", location))
+}
+
+/// Validate the schema version and report registry objects which are not served.
+pub(crate) fn check_compatibility(registry: &Registry) -> Result<()> {
+    if !is_supported_version(&registry.registry_schema_version) {
+        return Err(anyhow!(
+            "registry schema version '{}' is not supported (supported: {}). \
+             Upgrade the feature server or apply the repository with a compatible Feast version",
+            registry.registry_schema_version,
+            SUPPORTED_SCHEMA_VERSIONS.join(", ")
+        ));
+    }
+    let unsupported = unsupported_objects(registry);
+    if !unsupported.is_empty() {
+        tracing::warn!(
+            "Registry contains objects not supported by this server, requests referencing them \
+             will fail: {}",
+            unsupported.join("; ")
+        );
+    }
+    Ok(())
+}
+
+fn is_supported_version(version: &str) -> bool {
+    version.is_empty() || SUPPORTED_SCHEMA_VERSIONS.contains(&version)
+}
+
+/// Describe the non empty groups of registry objects this server ignores.
+fn unsupported_objects(registry: &Registry) -> Vec<String> {
+    let mut unsupported = Vec::new();
+    let feature_tables: Vec<&str> = registry
+        .feature_tables
+        .iter()
+        .filter_map(|table| table.spec.as_ref())
+        .map(|spec| spec.name.as_str())
+        .collect();
+    if !feature_tables.is_empty() {
+        unsupported.push(format!(
+            "legacy feature tables: {}",
+            feature_tables.join(", ")
+        ));
+    }
+    unsupported
+}
+
+/// Collect top level field numbers above the last field known to this server by walking
+/// the protobuf wire format. Stops at the first malformed field, which decoding reports.
+fn unknown_top_level_fields(mut buf: &[u8]) -> Vec<u32> {
+    let mut unknown = Vec::new();
+    while !buf.is_empty() {
+        let Some(key) = read_varint(&mut buf) else {
+            break;
+        };
+        let tag = (key >> 3) as u32;
+        let skip = match key & 0x7 {
+            0 => read_varint(&mut buf).map(|_| 0),
+            1 => Some(8),
+            2 => read_varint(&mut buf).map(|len| len as usize),
+            5 => Some(4),
+            _ => None,
+        };
+        let Some(skip) = skip.filter(|skip| *skip <= buf.len()) else {
+            break;
+        };
+        buf = &buf[skip..];
+        if tag > LAST_KNOWN_REGISTRY_FIELD && !unknown.contains(&tag) {
+            unknown.push(tag);
+        }
+    }
+    unknown
+}
+
+fn read_varint(buf: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for (i, byte) in buf.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            *buf = &buf[i + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feast::core::{FeatureTable, FeatureTableSpec};
+
+    #[test]
+    fn reject_unsupported_schema_version() {
+        let registry = Registry {
+            registry_schema_version: "2".to_string(),
+            ..Default::default()
+        };
+        let err = decode_registry(&registry.encode_to_vec(), "registry.pb").unwrap_err();
+        assert!(format!("{:#}", err).contains("registry schema version '2' is not supported"));
+
+        let registry = Registry {
+            registry_schema_version: "1".to_string(),
+            ..Default::default()
+        };
+        assert!(decode_registry(&registry.encode_to_vec(), "registry.pb").is_ok());
+    }
+
+    #[test]
+    fn report_unsupported_objects_and_fields() {
+        let registry = Registry {
+            feature_tables: vec![FeatureTable {
+                spec: Some(FeatureTableSpec {
+                    name: "driver_table".to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            unsupported_objects(&registry),
+            vec!["legacy feature tables: driver_table".to_string()]
+        );
+
+        let mut buf = registry.encode_to_vec();
+        // field 42, length delimited, 2 bytes of payload
+        buf.extend_from_slice(&[0xd2, 0x02, 0x02, 0x08, 0x01]);
+        assert_eq!(unknown_top_level_fields(&buf), vec![42]);
+        assert!(decode_registry(&buf, "registry.pb").is_ok());
+    }
+
+    #[test]
+    fn decode_error_mentions_location() {
+        let err = decode_registry(&[0x0a, 0xff], "gs://bucket/registry.pb").unwrap_err();
+        assert!(format!("{:#}", err).contains("gs://bucket/registry.pb"));
+    }
+
+    #[test]
+    fn decode_compressed_registries() -> Result<()> {
+        use std::io::Write;
+
+        let registry = Registry {
+            registry_schema_version: "1".to_string(),
+            ..Default::default()
+        };
+        let buf = registry.encode_to_vec();
+        let zstd = zstd::encode_all(buf.as_slice(), 3)?;
+        assert_eq!(
+            decode_registry(&zstd, "s3://bucket/registry.pb.zst")?,
+            registry
+        );
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gzip.write_all(&buf)?;
+        assert_eq!(
+            decode_registry(&gzip.finish()?, "registry.pb.gz")?,
+            registry
+        );
+
+        let err = decode_registry(&buf, "registry.pb.zst").unwrap_err();
+        assert!(
+            format!("{:#}", err).starts_with("Failed to decompress registry at 'registry.pb.zst'")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! Registry of feature registry backends living outside this crate. A backend registered
+//! under a module name is selected with `registry_type: custom` and `module: <name>` in the
+//! `registry` section of `feature_store.yaml`.
+
+use crate::config::RegistryConfig;
+use crate::registry::FeatureRegistryService;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Creates a custom registry from the `registry` config. Settings unknown to the server are
+/// available in [`RegistryConfig::options`].
+#[async_trait]
+pub trait RegistryFactory: Send + Sync + 'static {
+    async fn create(
+        &self,
+        config: &RegistryConfig,
+        project: &str,
+        cwd: Option<&str>,
+    ) -> Result<Arc<dyn FeatureRegistryService>>;
+}
+
+type Factories = RwLock<BTreeMap<String, Arc<dyn RegistryFactory>>>;
+
+static FACTORIES: OnceLock<Factories> = OnceLock::new();
+
+fn factories() -> &'static Factories {
+    FACTORIES.get_or_init(Default::default)
+}
+
+/// Make a registry backend available as `module` to `registry_type: custom` configs.
+/// Backends must be registered before the feature store is built; a later registration
+/// under the same name replaces the earlier one.
+pub fn register_registry(module: impl Into<String>, factory: impl RegistryFactory) {
+    factories()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(module.into(), Arc::new(factory));
+}
+
+pub(crate) async fn create_custom_registry(
+    config: &RegistryConfig,
+    project: &str,
+    cwd: Option<&str>,
+) -> Result<Arc<dyn FeatureRegistryService>> {
+    let module = config
+        .module
+        .as_deref()
+        .ok_or_else(|| anyhow!("registry.module is required for custom registries"))?;
+    let factory = {
+        let factories = factories().read().unwrap_or_else(|e| e.into_inner());
+        match factories.get(module) {
+            Some(factory) => Arc::clone(factory),
+            None => {
+                return Err(anyhow!(
+                    "No registry registered for module {}; registered modules: [{}]",
+                    module,
+                    factories.keys().cloned().collect::<Vec<_>>().join(", ")
+                ));
+            }
+        }
+    };
+    factory.create(config, project, cwd).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{RegistryType, RepoConfig};
+    use crate::registry::{FileFeatureRegistry, get_registry};
+    use serde_json::Value as JsonValue;
+    use std::path::PathBuf;
+
+    struct FileFactory;
+
+    #[async_trait]
+    impl RegistryFactory for FileFactory {
+        async fn create(
+            &self,
+            config: &RegistryConfig,
+            _project: &str,
+            cwd: Option<&str>,
+        ) -> Result<Arc<dyn FeatureRegistryService>> {
+            assert_eq!(config.options["endpoint"], JsonValue::from("metadata:8080"));
+            let path = PathBuf::from(cwd.unwrap_or_default()).join(&config.path);
+            Ok(Arc::new(FileFeatureRegistry::from_path(&path)?))
+        }
+    }
+
+    fn repo_config(module: &str) -> Result<RepoConfig> {
+        RepoConfig::from_yaml_str_strict(&format!(
+            r#"
+project: golden_hornet
+registry:
+    registry_type: custom
+    module: {}
+    path: registry.pb
+    endpoint: metadata:8080
+online_store:
+    type: sqlite
+    path: online_store.db
+"#,
+            module
+        ))
+    }
+
+    #[tokio::test]
+    async fn registered_registry_is_created_from_config() -> Result<()> {
+        register_registry("test_file", FileFactory);
+        let config = repo_config("test_file")?;
+        assert_eq!(config.registry.registry_type, RegistryType::Custom);
+
+        let cwd = concat!(env!("CARGO_MANIFEST_DIR"), "/test_data");
+        let registry = get_registry(config.registry, config.project, Some(cwd)).await?;
+        assert!(!registry.feature_views().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unregistered_module_fails() -> Result<()> {
+        let config = repo_config("missing_registry")?;
+        let err = get_registry(config.registry, config.project, None)
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string()
+                .starts_with("No registry registered for module missing_registry"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_keys_of_other_registries_are_rejected() {
+        let err = RepoConfig::from_yaml_str_strict(
+            r#"
+project: golden_hornet
+registry:
+    path: registry.pb
+    endpoint: metadata:8080
+online_store:
+    type: sqlite
+    path: online_store.db
+"#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unknown keys in configuration: registry.endpoint"
+        );
+    }
+}
@@ -1,32 +1,32 @@
 use crate::config::{Provider, RegistryConfig, RegistryType};
-use crate::registry::cached_registry::CachedFileRegistry;
-use crate::registry::{FeatureRegistryService, FileFeatureRegistry};
+use crate::feast::core::Registry;
+#[cfg(feature = "gcp")]
+use crate::registry::cached_registry::fetch_gcs_registry;
+#[cfg(feature = "aws")]
+use crate::registry::cached_registry::fetch_s3_registry;
+#[cfg(any(feature = "aws", feature = "gcp"))]
+use crate::registry::cached_registry::parse_storage_url;
+use crate::registry::cached_registry::{BlobUrl, CachedFileRegistry, fetch_azure_registry};
+use crate::registry::file_registry::read_registry_proto;
+use crate::registry::{FeatureRegistryService, FileFeatureRegistry, factory};
+#[cfg(not(all(feature = "aws", feature = "gcp")))]
+use crate::util::feature_disabled;
 use anyhow::{Result, anyhow};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::log::info;
 
-fn get_provider(provider_opt: Option<Provider>, path: &str) -> Provider {
-    if let Some(provider) = provider_opt {
-        provider
-    } else if path.starts_with("s3://") {
-        Provider::AWS
-    } else if path.starts_with("gs://") {
-        Provider::GCP
-    } else {
-        Provider::Local
-    }
-}
-
+/// Create the registry described by `conf`. File registries are read from the storage of
+/// their configured provider, or the one matching the scheme of the registry path,
+/// independently of the project provider.
 pub async fn get_registry(
     conf: RegistryConfig,
-    provider: Option<Provider>,
     project: String,
     cwd: Option<&str>,
 ) -> Result<Arc<dyn FeatureRegistryService>> {
     let path_prefix = cwd.unwrap_or("");
     match &conf.registry_type {
-        RegistryType::File => match get_provider(provider, conf.path.as_str()) {
+        RegistryType::File => match conf.provider() {
             Provider::Local => {
                 let mut path_buf = PathBuf::new();
                 path_buf.push(path_prefix);
@@ -35,34 +35,152 @@ pub async fn get_registry(
                     "Using local feature registry from path {}",
                     path_buf.display()
                 );
-                let registry =
-                    CachedFileRegistry::new_local(path_buf, conf.cache_ttl_seconds).await?;
+                let registry = CachedFileRegistry::new_local(
+                    path_buf,
+                    conf.cache_ttl_seconds,
+                    conf.retained_generations,
+                )
+                .await?;
                 Ok(registry)
             }
+            #[cfg(feature = "aws")]
             Provider::AWS => {
                 info!(
                     "Using AWS feature registry from path {}",
                     conf.path.as_str()
                 );
-                let registry =
-                    CachedFileRegistry::new_s3(conf.path.clone(), conf.cache_ttl_seconds).await?;
+                let registry = CachedFileRegistry::new_s3(
+                    conf.path.clone(),
+                    conf.cache_ttl_seconds,
+                    conf.retained_generations,
+                )
+                .await?;
                 Ok(registry)
             }
+            #[cfg(not(feature = "aws"))]
+            Provider::AWS => Err(feature_disabled("S3 registries", "aws")),
+            #[cfg(feature = "gcp")]
             Provider::GCP => {
                 info!(
                     "Using GCP feature registry from path {}",
                     conf.path.as_str()
                 );
-                let registry =
-                    CachedFileRegistry::new_gcs(conf.path.clone(), conf.cache_ttl_seconds).await?;
+                let registry = CachedFileRegistry::new_gcs(
+                    conf.path.clone(),
+                    conf.cache_ttl_seconds,
+                    conf.retained_generations,
+                )
+                .await?;
                 Ok(registry)
             }
-            _ => Err(anyhow!("Unsupported provider for file registry")),
+            #[cfg(not(feature = "gcp"))]
+            Provider::GCP => Err(feature_disabled("GCS registries", "gcp")),
+            Provider::Azure => {
+                info!(
+                    "Using Azure feature registry from path {}",
+                    conf.path.as_str()
+                );
+                let registry = CachedFileRegistry::new_azure(
+                    conf.path.clone(),
+                    conf.cache_ttl_seconds,
+                    conf.retained_generations,
+                )
+                .await?;
+                Ok(registry)
+            }
+            other => Err(anyhow!(
+                "Unsupported provider {:?} for file registry",
+                other
+            )),
         },
         RegistryType::Sql => {
             info!("Using SQL feature registry");
             let registry = CachedFileRegistry::new_sql(conf.clone(), project).await?;
             Ok(registry)
         }
+        RegistryType::Custom => {
+            info!(
+                "Using custom feature registry {}",
+                conf.module.as_deref().unwrap_or_default()
+            );
+            factory::create_custom_registry(&conf, &project, cwd).await
+        }
+    }
+}
+
+/// Fetch the registry proto described by `conf` once, without caching. Only file registries
+/// store a registry proto; SQL and custom registries are rejected.
+pub(crate) async fn load_registry_proto(
+    conf: &RegistryConfig,
+    cwd: Option<&str>,
+) -> Result<Registry> {
+    if conf.registry_type != RegistryType::File {
+        return Err(anyhow!(
+            "Registry type {:?} has no registry proto, only file registries are supported",
+            conf.registry_type
+        ));
+    }
+    match conf.provider() {
+        Provider::Local => {
+            let mut path_buf = PathBuf::new();
+            path_buf.push(cwd.unwrap_or(""));
+            path_buf.push(conf.path.as_str());
+            read_registry_proto(&path_buf)
+        }
+        #[cfg(feature = "aws")]
+        Provider::AWS => {
+            let (bucket, key) = parse_storage_url(&conf.path, "s3", "S3")?;
+            let config = aws_config::load_from_env().await;
+            fetch_s3_registry(&aws_sdk_s3::Client::new(&config), &bucket, &key).await
+        }
+        #[cfg(not(feature = "aws"))]
+        Provider::AWS => Err(feature_disabled("S3 registries", "aws")),
+        #[cfg(feature = "gcp")]
+        Provider::GCP => {
+            use google_cloud_storage::client::{Client as GcsClient, ClientConfig};
+
+            let (bucket, object) = parse_storage_url(&conf.path, "gs", "GCS")?;
+            let client_config = ClientConfig::default().with_auth().await?;
+            fetch_gcs_registry(&GcsClient::new(client_config), &bucket, &object).await
+        }
+        #[cfg(not(feature = "gcp"))]
+        Provider::GCP => Err(feature_disabled("GCS registries", "gcp")),
+        Provider::Azure => fetch_azure_registry(&BlobUrl::from_env(&conf.path)?).await,
+        other => Err(anyhow!(
+            "Unsupported provider {:?} for file registry",
+            other
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_http::HttpStub;
+    use prost::Message;
+
+    #[tokio::test]
+    async fn explicit_provider_selects_the_registry_storage() -> Result<()> {
+        let stub =
+            HttpStub::start(vec![(200, vec![], Registry::default().encode_to_vec())]).await?;
+        let path = format!("{}/feast/registry.pb", stub.url());
+        // Inferred as a local path, which does not exist
+        let inferred = RegistryConfig {
+            path: path.clone(),
+            ..Default::default()
+        };
+        assert!(
+            get_registry(inferred, "project".to_string(), None)
+                .await
+                .is_err()
+        );
+        let explicit = RegistryConfig {
+            path,
+            provider: Some(Provider::Azure),
+            ..Default::default()
+        };
+        get_registry(explicit, "project".to_string(), None).await?;
+        assert!(stub.requests()[0].starts_with("GET /feast/registry.pb "));
+        Ok(())
     }
 }
@@ -1,10 +1,10 @@
 use crate::error::FeastCoreError;
 use crate::feast::core::Registry;
 use crate::model::{
-    Feature, FeatureRegistry, FeatureService, FeatureView, GetOnlineFeaturesRequest,
+    Feature, FeatureRegistry, FeatureService, FeatureView, GetOnlineFeaturesRequest, OrderedMap,
     RequestedFeatures,
 };
-use crate::registry::FeatureRegistryService;
+use crate::registry::{FeatureRegistryService, RegistryMetadata, compat};
 use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use lasso::Spur;
@@ -19,6 +19,9 @@ use std::sync::Arc;
 
 use crate::intern;
 
+/// Feature name requesting every feature of a view, as in `driver_hourly_stats:*`.
+const WILDCARD_FEATURE_NAME: &str = "*";
+
 #[derive(Debug)]
 pub struct FileFeatureRegistry {
     registry: FeatureRegistry,
@@ -33,38 +36,14 @@ impl FileFeatureRegistry {
         Ok(Self { registry })
     }
 
-    pub fn from_path(registry_file_path: &PathBuf) -> Result<Self> {
-        let mut file = fs::File::open(registry_file_path).map_err(|err| {
-            if err.kind() == std::io::ErrorKind::NotFound {
-                anyhow!(
-                    "Registry file not found at '{}'. Check your repository configuration (e.g. FEATURE_REPO_DIR or --chdir).",
-                    registry_file_path.display()
-                )
-            } else {
-                anyhow::Error::new(err).context(format!("Failed to open registry file at '{}'", registry_file_path.display()))
-            }
-        })?;
-        let mut buf = Vec::new();
-        file.read_to_end(&mut buf).with_context(|| {
-            format!(
-                "Failed to read registry file at '{}'",
-                registry_file_path.display()
-            )
-        })?;
-        let registry_proto = Registry::decode(&*buf).with_context(|| {
-            format!(
-                "Failed to parse registry protobuf at '{}'",
-                registry_file_path.display()
-            )
-        })?;
-        let registry = FeatureRegistry::try_from(registry_proto)?;
-        Ok(Self { registry })
+    pub fn from_path(registry_file_path: &Path) -> Result<Self> {
+        Self::from_proto(read_registry_proto(registry_file_path)?)
     }
 
     fn feature_views_from_service(
         &self,
         service_name: Spur,
-    ) -> Result<HashMap<Feature, Arc<FeatureView>>> {
+    ) -> Result<OrderedMap<Feature, Arc<FeatureView>>> {
         let rodeo = intern::rodeo_ref();
         let service = self
             .registry
@@ -86,7 +65,7 @@ impl FileFeatureRegistry {
             )
             .into());
         }
-        let mut result: HashMap<Feature, Arc<FeatureView>> = HashMap::default();
+        let mut result: OrderedMap<Feature, Arc<FeatureView>> = OrderedMap::default();
         for resolved in &service.resolved_projections {
             if self
                 .registry
@@ -96,48 +75,69 @@ impl FileFeatureRegistry {
                 return Err(anyhow!("OnDemand feature view for now is not supported"));
             }
 
+            // Features are keyed by projection, so that a view projected twice under
+            // different aliases, e.g. joined on other entity columns, is answered twice.
+            let projection_name = resolved.feature_view.name_to_use();
             for field in resolved.feature_view.features.iter() {
-                let feature = Feature::new(resolved.feature_view.name, field.name);
-                result.insert(feature, resolved.feature_view.clone());
+                let feature = Feature::new(projection_name, field.name);
+                if let Some(previous) = result.insert(feature, resolved.feature_view.clone())
+                    && !Arc::ptr_eq(&previous, &resolved.feature_view)
+                {
+                    return Err(anyhow!(
+                        "Feature service {} projects feature view {} more than once under the name {}; give the projections different aliases",
+                        rodeo.resolve(&service_name),
+                        rodeo.resolve(&resolved.feature_view.name),
+                        rodeo.resolve(&projection_name)
+                    ));
+                }
             }
         }
         Ok(result)
     }
 
+    /// Views of the requested features. A `<view>:*` wildcard stands for every feature of
+    /// the view, in the order of its fields.
     fn feature_views_from_names(
         &self,
         names: &[Feature],
-    ) -> Result<HashMap<Feature, Arc<FeatureView>>> {
+    ) -> Result<OrderedMap<Feature, Arc<FeatureView>>> {
         let rodeo = intern::rodeo_ref();
-        names
-            .iter()
-            .map(|req_feature| -> Result<(Feature, Arc<FeatureView>)> {
-                if self
-                    .registry
-                    .on_demand_feature_views
-                    .contains_key(&req_feature.feature_view_name)
-                {
-                    return Err(anyhow!("OnDemand feature view for now is not supported"));
+        let wildcard = rodeo.get_or_intern(WILDCARD_FEATURE_NAME);
+        let mut result: OrderedMap<Feature, Arc<FeatureView>> = OrderedMap::default();
+        for req_feature in names {
+            if self
+                .registry
+                .on_demand_feature_views
+                .contains_key(&req_feature.feature_view_name)
+            {
+                return Err(anyhow!("OnDemand feature view for now is not supported"));
+            }
+            let view = self
+                .registry
+                .feature_views
+                .get(&req_feature.feature_view_name)
+                .cloned()
+                .ok_or_else(|| {
+                    FeastCoreError::feature_view_not_found(
+                        rodeo.resolve(&req_feature.feature_view_name),
+                    )
+                })?;
+            let view = Arc::new(view);
+            if req_feature.feature_name == wildcard {
+                for field in view.features.iter() {
+                    result.insert(Feature::new(view.name, field.name), view.clone());
                 }
-                let view = self
-                    .registry
-                    .feature_views
-                    .get(&req_feature.feature_view_name)
-                    .cloned()
-                    .ok_or_else(|| {
-                        FeastCoreError::feature_view_not_found(
-                            rodeo.resolve(&req_feature.feature_view_name),
-                        )
-                    })?;
-                Ok((req_feature.clone(), Arc::from(view)))
-            })
-            .collect()
+            } else {
+                result.insert(req_feature.clone(), view);
+            }
+        }
+        Ok(result)
     }
 
     fn get_feature_views(
         &self,
         requested_features: RequestedFeatures,
-    ) -> Result<HashMap<Feature, Arc<FeatureView>>> {
+    ) -> Result<OrderedMap<Feature, Arc<FeatureView>>> {
         match requested_features {
             RequestedFeatures::FeatureService(service_name) => {
                 self.feature_views_from_service(service_name)
@@ -146,7 +146,7 @@ impl FileFeatureRegistry {
                 let mut bad_requests = vec![];
                 let parsed_requested_features: Vec<Feature> = names
                     .iter()
-                    .map(Feature::try_from)
+                    .map(|name| Feature::try_from(name.as_str()))
                     .filter_map(|r| r.map_err(|e| bad_requests.push(e)).ok())
                     .collect();
                 if !bad_requests.is_empty() {
@@ -166,14 +166,81 @@ impl FileFeatureRegistry {
     }
 }
 
+/// Read and decode the registry proto stored in a local file.
+pub(crate) fn read_registry_proto(registry_file_path: &Path) -> Result<Registry> {
+    let mut file = fs::File::open(registry_file_path).map_err(|err| {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            anyhow!(
+                "Registry file not found at '{}'. Check your repository configuration (e.g. FEATURE_REPO_DIR or --chdir).",
+                registry_file_path.display()
+            )
+        } else {
+            anyhow::Error::new(err).context(format!("Failed to open registry file at '{}'", registry_file_path.display()))
+        }
+    })?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).with_context(|| {
+        format!(
+            "Failed to read registry file at '{}'",
+            registry_file_path.display()
+        )
+    })?;
+    compat::decode_registry(&buf, &registry_file_path.display().to_string())
+}
+
 #[async_trait]
 impl FeatureRegistryService for FileFeatureRegistry {
     async fn request_to_view_keys(
         &self,
         request: RequestedFeatures,
-    ) -> Result<HashMap<Feature, Arc<FeatureView>>> {
+    ) -> Result<OrderedMap<Feature, Arc<FeatureView>>> {
         self.get_feature_views(request)
     }
+
+    async fn feature_view(&self, feature_view_name: Spur) -> Result<Arc<FeatureView>> {
+        self.registry
+            .feature_views
+            .get(&feature_view_name)
+            .map(|view| Arc::new(view.clone()))
+            .ok_or_else(|| {
+                let rodeo = intern::rodeo_ref();
+                FeastCoreError::feature_view_not_found(rodeo.resolve(&feature_view_name)).into()
+            })
+    }
+
+    async fn feature_views(&self) -> Result<Vec<Arc<FeatureView>>> {
+        Ok(self
+            .registry
+            .feature_views
+            .values()
+            .map(|view| Arc::new(view.clone()))
+            .collect())
+    }
+
+    async fn push_source_views(&self, push_source_name: Spur) -> Result<Vec<Arc<FeatureView>>> {
+        let views: Vec<Arc<FeatureView>> = self
+            .registry
+            .feature_views
+            .values()
+            .filter(|view| {
+                view.push_source
+                    .as_ref()
+                    .is_some_and(|source| source.name == push_source_name)
+            })
+            .map(|view| Arc::new(view.clone()))
+            .collect();
+        if views.is_empty() {
+            let rodeo = intern::rodeo_ref();
+            return Err(
+                FeastCoreError::push_source_not_found(rodeo.resolve(&push_source_name)).into(),
+            );
+        }
+        Ok(views)
+    }
+
+    async fn metadata(&self) -> Result<RegistryMetadata> {
+        Ok(RegistryMetadata::from(&self.registry))
+    }
 }
 
 #[cfg(test)]
@@ -206,9 +273,11 @@ mod tests {
         let feature_registry_proto = FileFeatureRegistry::from_path(&registry_path)?;
         let feature_registry_service: Box<dyn FeatureRegistryService> =
             Box::new(feature_registry_proto);
-        let mut request_obj = GetOnlineFeaturesRequest::default();
-        request_obj.features = vec!["driver_hourly_stats_fresh:conv_rate".to_string()].into();
-        let requested_features = RequestedFeatures::from(&request_obj);
+        let request_obj = GetOnlineFeaturesRequest {
+            features: Some(vec!["driver_hourly_stats_fresh:conv_rate".to_string()]),
+            ..Default::default()
+        };
+        let requested_features = RequestedFeatures::try_from(&request_obj)?;
         let result = feature_registry_service
             .request_to_view_keys(requested_features)
             .await?;
@@ -223,13 +292,262 @@ mod tests {
         let feature_registry_proto = FileFeatureRegistry::from_path(&registry_path)?;
         let feature_registry_service: Box<dyn FeatureRegistryService> =
             Box::new(feature_registry_proto);
-        let mut request_obj = GetOnlineFeaturesRequest::default();
-        request_obj.feature_service = Some("driver_activity_v4".to_string());
-        let requested_features = RequestedFeatures::from(&request_obj);
+        let request_obj = GetOnlineFeaturesRequest {
+            feature_service: Some("driver_activity_v4".to_string()),
+            ..Default::default()
+        };
+        let requested_features = RequestedFeatures::try_from(&request_obj)?;
         let result = feature_registry_service
             .request_to_view_keys(requested_features)
             .await?;
         println!("{:?}", result);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn expand_wildcard_feature() -> Result<()> {
+        let project_dir = env!("CARGO_MANIFEST_DIR");
+        let registry_file = format!("{}/test_data/registry.pb", project_dir);
+        let feature_registry =
+            FileFeatureRegistry::from_path(std::path::Path::new(&registry_file))?;
+        let request_obj = GetOnlineFeaturesRequest {
+            features: Some(vec![
+                "driver_hourly_stats:acc_rate".to_string(),
+                "driver_hourly_stats:*".to_string(),
+            ]),
+            ..Default::default()
+        };
+        let result = feature_registry
+            .request_to_view_keys(RequestedFeatures::try_from(&request_obj)?)
+            .await?;
+        let view = feature_registry
+            .feature_view(crate::intern::rodeo_ref().get_or_intern("driver_hourly_stats"))
+            .await?;
+        let acc_rate = Feature::from_names("driver_hourly_stats", "acc_rate");
+        let mut expected = vec![acc_rate.clone()];
+        expected.extend(
+            view.features
+                .iter()
+                .map(|field| Feature::new(view.name, field.name))
+                .filter(|feature| feature != &acc_rate),
+        );
+        assert!(expected.len() > 1);
+        assert_eq!(result.keys().cloned().collect::<Vec<_>>(), expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_push_source_views() -> Result<()> {
+        let project_dir = env!("CARGO_MANIFEST_DIR");
+        let registry_file = format!("{}/test_data/registry.pb", project_dir);
+        let registry_path = std::path::PathBuf::from(registry_file);
+        let feature_registry = FileFeatureRegistry::from_path(&registry_path)?;
+        let rodeo = crate::intern::rodeo_ref();
+        let views = feature_registry
+            .push_source_views(rodeo.get_or_intern("driver_stats_push_source"))
+            .await?;
+        assert_eq!(views.len(), 1);
+        assert_eq!(rodeo.resolve(&views[0].name), "driver_hourly_stats_fresh");
+        let missing = feature_registry
+            .push_source_views(rodeo.get_or_intern("unknown_push_source"))
+            .await;
+        assert!(missing.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serve_stream_feature_views() -> Result<()> {
+        use crate::feast::core::{
+            FeatureSpecV2, Registry, StreamFeatureView, StreamFeatureViewSpec,
+        };
+        use crate::feast::types::value_type::Enum as ValueTypeEnum;
+
+        let registry = Registry {
+            stream_feature_views: vec![StreamFeatureView {
+                spec: Some(StreamFeatureViewSpec {
+                    name: "driver_stream_stats".to_string(),
+                    entities: vec!["driver".to_string()],
+                    features: vec![FeatureSpecV2 {
+                        name: "trips_last_10m".to_string(),
+                        value_type: ValueTypeEnum::Int64 as i32,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let feature_registry = FileFeatureRegistry::from_proto(registry)?;
+        let request_obj = GetOnlineFeaturesRequest {
+            features: Some(vec!["driver_stream_stats:trips_last_10m".to_string()]),
+            ..Default::default()
+        };
+        let result = feature_registry
+            .request_to_view_keys(RequestedFeatures::try_from(&request_obj)?)
+            .await?;
+        assert_eq!(result.len(), 1);
+        let view = result.values().next().unwrap();
+        assert_eq!(view.features.len(), 1);
+        assert_eq!(view.entity_names.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn view_projected_twice() -> Result<()> {
+        use crate::feast::core::{
+            FeatureService, FeatureServiceMeta, FeatureServiceSpec, FeatureSpecV2, FeatureView,
+            FeatureViewProjection, FeatureViewSpec, Registry,
+        };
+        use crate::feast::types::value_type::Enum as ValueTypeEnum;
+
+        let trips = FeatureSpecV2 {
+            name: "trips".to_string(),
+            value_type: ValueTypeEnum::Int64 as i32,
+            ..Default::default()
+        };
+        let projection = |alias: &str, join_key: &str| FeatureViewProjection {
+            feature_view_name: "location_stats".to_string(),
+            feature_view_name_alias: alias.to_string(),
+            feature_columns: vec![trips.clone()],
+            join_key_map: [("location_id".to_string(), join_key.to_string())].into(),
+            ..Default::default()
+        };
+        let registry = Registry {
+            feature_views: vec![FeatureView {
+                spec: Some(FeatureViewSpec {
+                    name: "location_stats".to_string(),
+                    entities: vec!["location".to_string()],
+                    features: vec![trips.clone()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            feature_services: vec![FeatureService {
+                spec: Some(FeatureServiceSpec {
+                    name: "trip_endpoints".to_string(),
+                    features: vec![
+                        projection("origin", "origin_id"),
+                        projection("destination", "destination_id"),
+                    ],
+                    ..Default::default()
+                }),
+                meta: Some(FeatureServiceMeta::default()),
+            }],
+            ..Default::default()
+        };
+        let feature_registry = FileFeatureRegistry::from_proto(registry)?;
+        let request_obj = GetOnlineFeaturesRequest {
+            feature_service: Some("trip_endpoints".to_string()),
+            ..Default::default()
+        };
+        let result = feature_registry
+            .request_to_view_keys(RequestedFeatures::try_from(&request_obj)?)
+            .await?;
+        let rodeo = crate::intern::rodeo_ref();
+        let projections: Vec<_> = result
+            .iter()
+            .map(|(feature, view)| {
+                let join_key_map = view.join_key_map.as_ref().unwrap();
+                (
+                    feature.full_name(),
+                    rodeo.resolve(&view.name),
+                    rodeo.resolve(&join_key_map[&rodeo.get_or_intern("location_id")]),
+                )
+            })
+            .collect();
+        assert_eq!(
+            projections,
+            [
+                ("origin__trips".to_string(), "location_stats", "origin_id"),
+                (
+                    "destination__trips".to_string(),
+                    "location_stats",
+                    "destination_id"
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn projection_ttl_from_service_tags() -> Result<()> {
+        use crate::feast::core::{
+            FeatureService, FeatureServiceMeta, FeatureServiceSpec, FeatureSpecV2, FeatureView,
+            FeatureViewProjection, FeatureViewSpec, Registry,
+        };
+        use crate::feast::types::value_type::Enum as ValueTypeEnum;
+
+        let trips = FeatureSpecV2 {
+            name: "trips".to_string(),
+            value_type: ValueTypeEnum::Int64 as i32,
+            ..Default::default()
+        };
+        let service = |name: &str, tag: (&str, &str)| FeatureService {
+            spec: Some(FeatureServiceSpec {
+                name: name.to_string(),
+                features: vec![FeatureViewProjection {
+                    feature_view_name: "location_stats".to_string(),
+                    feature_view_name_alias: "origin".to_string(),
+                    feature_columns: vec![trips.clone()],
+                    ..Default::default()
+                }],
+                tags: [(tag.0.to_string(), tag.1.to_string())].into(),
+                ..Default::default()
+            }),
+            meta: Some(FeatureServiceMeta::default()),
+        };
+        let registry = |service| Registry {
+            feature_views: vec![FeatureView {
+                spec: Some(FeatureViewSpec {
+                    name: "location_stats".to_string(),
+                    entities: vec!["location".to_string()],
+                    features: vec![trips.clone()],
+                    ttl: Some(prost_types::Duration {
+                        seconds: 86_400,
+                        nanos: 0,
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            feature_services: vec![service],
+            ..Default::default()
+        };
+
+        let feature_registry = FileFeatureRegistry::from_proto(registry(service(
+            "recent_trips",
+            ("ttl:origin", "600"),
+        )))?;
+        let request_obj = GetOnlineFeaturesRequest {
+            feature_service: Some("recent_trips".to_string()),
+            ..Default::default()
+        };
+        let result = feature_registry
+            .request_to_view_keys(RequestedFeatures::try_from(&request_obj)?)
+            .await?;
+        let view = result.values().next().unwrap();
+        assert_eq!(view.ttl, chrono::Duration::seconds(600));
+        assert_eq!(
+            feature_registry
+                .feature_view(crate::intern::rodeo_ref().get_or_intern("location_stats"))
+                .await?
+                .ttl,
+            chrono::Duration::days(1)
+        );
+
+        let err = FileFeatureRegistry::from_proto(registry(service(
+            "recent_trips",
+            ("ttl:destination", "600"),
+        )))
+        .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains(
+                "TTL tag ttl:destination of feature service recent_trips names no projected feature view"
+            ),
+            "{:#}",
+            err
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,231 @@
+//! JSON description of the registry for catalogs and UIs, so that they need not parse
+//! `registry.pb` themselves.
+
+use crate::intern;
+use crate::model::{Entity, FeatureRegistry, FeatureService, FeatureView, Field};
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Entities, feature views and feature services of the registry, each sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RegistryMetadata {
+    pub entities: Vec<EntityMetadata>,
+    pub feature_views: Vec<FeatureViewMetadata>,
+    pub feature_services: Vec<FeatureServiceMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntityMetadata {
+    pub name: String,
+    pub join_key: String,
+    pub value_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldMetadata {
+    pub name: String,
+    pub value_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeatureViewMetadata {
+    pub name: String,
+    pub entities: Vec<String>,
+    pub entity_columns: Vec<FieldMetadata>,
+    pub features: Vec<FieldMetadata>,
+    /// Zero when values never expire.
+    pub ttl_seconds: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub push_source: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeatureServiceMetadata {
+    pub name: String,
+    pub projections: Vec<ProjectionMetadata>,
+    /// Projected feature views the registry does not have.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_feature_views: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_timestamp: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated_timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectionMetadata {
+    pub feature_view: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    pub features: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub join_key_map: BTreeMap<String, String>,
+    /// TTL overriding the one of the view, from the tags of the feature service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<i64>,
+}
+
+fn name(key: &Spur) -> String {
+    intern::rodeo_ref().resolve(key).to_string()
+}
+
+impl From<&Entity> for EntityMetadata {
+    fn from(entity: &Entity) -> Self {
+        Self {
+            name: name(&entity.name),
+            join_key: name(&entity.join_key),
+            value_type: entity.value_type.as_str_name().to_string(),
+        }
+    }
+}
+
+impl From<&Field> for FieldMetadata {
+    fn from(field: &Field) -> Self {
+        Self {
+            name: name(&field.name),
+            value_type: field.value_type.as_str_name().to_string(),
+        }
+    }
+}
+
+impl From<&FeatureView> for FeatureViewMetadata {
+    fn from(view: &FeatureView) -> Self {
+        Self {
+            name: name(&view.name),
+            entities: view.entity_names.iter().map(name).collect(),
+            entity_columns: view
+                .entity_columns
+                .iter()
+                .map(FieldMetadata::from)
+                .collect(),
+            features: view.features.iter().map(FieldMetadata::from).collect(),
+            ttl_seconds: view.ttl.num_seconds(),
+            push_source: view.push_source.as_ref().map(|source| name(&source.name)),
+        }
+    }
+}
+
+impl From<&FeatureService> for FeatureServiceMetadata {
+    fn from(service: &FeatureService) -> Self {
+        let projections = service
+            .projections
+            .iter()
+            .map(|projection| ProjectionMetadata {
+                feature_view: name(&projection.feature_view_name),
+                alias: projection.feature_view_name_alias.as_ref().map(name),
+                features: projection
+                    .features
+                    .iter()
+                    .map(|field| name(&field.name))
+                    .collect(),
+                join_key_map: projection
+                    .join_key_map
+                    .iter()
+                    .map(|(from, to)| (name(from), name(to)))
+                    .collect(),
+                ttl_seconds: projection.ttl.map(|ttl| ttl.num_seconds()),
+            })
+            .collect();
+        Self {
+            name: name(&service.name),
+            projections,
+            missing_feature_views: service.missing_feature_views.iter().map(name).collect(),
+            created_timestamp: service.created_timestamp,
+            last_updated_timestamp: service.last_updated_timestamp,
+        }
+    }
+}
+
+impl RegistryMetadata {
+    /// Metadata of registries only exposing their feature views.
+    pub fn from_feature_views<'a>(views: impl IntoIterator<Item = &'a FeatureView>) -> Self {
+        let mut feature_views: Vec<_> = views.into_iter().map(FeatureViewMetadata::from).collect();
+        feature_views.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            feature_views,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&FeatureRegistry> for RegistryMetadata {
+    fn from(registry: &FeatureRegistry) -> Self {
+        let mut entities: Vec<_> = registry
+            .entities
+            .values()
+            .map(EntityMetadata::from)
+            .collect();
+        entities.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut feature_services: Vec<_> = registry
+            .feature_services
+            .values()
+            .map(FeatureServiceMetadata::from)
+            .collect();
+        feature_services.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            entities,
+            feature_services,
+            ..Self::from_feature_views(registry.feature_views.values())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{FeatureRegistryService, FileFeatureRegistry};
+    use anyhow::Result;
+
+    #[tokio::test]
+    async fn metadata_of_test_registry() -> Result<()> {
+        let registry_file = format!("{}/test_data/registry.pb", env!("CARGO_MANIFEST_DIR"));
+        let registry = FileFeatureRegistry::from_path(&std::path::PathBuf::from(registry_file))?;
+        let metadata = registry.metadata().await?;
+
+        let driver = metadata
+            .entities
+            .iter()
+            .find(|entity| entity.name == "driver")
+            .unwrap();
+        assert_eq!(driver.join_key, "driver_id");
+
+        let view = metadata
+            .feature_views
+            .iter()
+            .find(|view| view.name == "driver_hourly_stats")
+            .unwrap();
+        assert!(view.ttl_seconds > 0);
+        assert_eq!(view.entities, ["driver"]);
+        assert_eq!(
+            view.entity_columns,
+            [FieldMetadata {
+                name: "driver_id".to_string(),
+                value_type: "INT64".to_string(),
+            }]
+        );
+        assert!(
+            view.features
+                .iter()
+                .any(|field| field.name == "conv_rate" && field.value_type == "FLOAT")
+        );
+
+        let service = metadata
+            .feature_services
+            .iter()
+            .find(|service| service.name == "driver_activity_alias")
+            .unwrap();
+        assert_eq!(
+            service.projections[0].alias.as_deref(),
+            Some("driver_alias")
+        );
+        let names: Vec<_> = metadata
+            .feature_services
+            .iter()
+            .map(|service| &service.name)
+            .collect();
+        assert!(names.is_sorted());
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+//! Typed description of the response columns of a feature service, exported as JSON Schema
+//! or as a `.proto` message so that client teams can generate typed response structs.
+
+use crate::error::FeastCoreError;
+use crate::feature_store::full_feature_name;
+use crate::registry::RegistryMetadata;
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::fmt::Write;
+
+/// Format a [`ResponseSchema`] is exported in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SchemaFormat {
+    #[default]
+    JsonSchema,
+    Proto,
+}
+
+/// Response columns of a feature service, entity columns first.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseSchema {
+    pub feature_service: String,
+    pub columns: Vec<SchemaColumn>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaColumn {
+    pub name: String,
+    /// Feast value type name, e.g. `INT64` or `FLOAT_LIST`.
+    pub value_type: String,
+    pub entity: bool,
+}
+
+impl ResponseSchema {
+    /// Columns of `feature_service` as answered with the given `full_feature_names`.
+    pub fn new(
+        metadata: &RegistryMetadata,
+        feature_service: &str,
+        full_feature_names: bool,
+    ) -> Result<Self> {
+        let service = metadata
+            .feature_services
+            .iter()
+            .find(|service| service.name == feature_service)
+            .ok_or_else(|| FeastCoreError::feature_service_not_found(feature_service))?;
+        let mut entity_columns: Vec<SchemaColumn> = Vec::new();
+        let mut feature_columns = Vec::new();
+        for projection in &service.projections {
+            let view = metadata
+                .feature_views
+                .iter()
+                .find(|view| view.name == projection.feature_view)
+                .ok_or_else(|| {
+                    FeastCoreError::feature_view_not_found_for_service(
+                        &projection.feature_view,
+                        feature_service,
+                    )
+                })?;
+            for column in &view.entity_columns {
+                let name = projection
+                    .join_key_map
+                    .get(&column.name)
+                    .unwrap_or(&column.name);
+                if !entity_columns.iter().any(|entity| &entity.name == name) {
+                    entity_columns.push(SchemaColumn {
+                        name: name.clone(),
+                        value_type: column.value_type.clone(),
+                        entity: true,
+                    });
+                }
+            }
+            let view_name = projection
+                .alias
+                .as_ref()
+                .unwrap_or(&projection.feature_view);
+            for feature in &projection.features {
+                let value_type = view
+                    .features
+                    .iter()
+                    .find(|field| &field.name == feature)
+                    .map_or("UNKNOWN", |field| field.value_type.as_str());
+                feature_columns.push(SchemaColumn {
+                    name: if full_feature_names {
+                        full_feature_name(view_name, feature)
+                    } else {
+                        feature.clone()
+                    },
+                    value_type: value_type.to_string(),
+                    entity: false,
+                });
+            }
+        }
+        entity_columns.extend(feature_columns);
+        Ok(Self {
+            feature_service: feature_service.to_string(),
+            columns: entity_columns,
+        })
+    }
+
+    /// JSON Schema of the JSON response, whose `results` hold one entry per column in the
+    /// order of `metadata.feature_names`.
+    pub fn to_json_schema(&self) -> Value {
+        let names: Vec<_> = self.columns.iter().map(|column| &column.name).collect();
+        let results: Vec<_> = self
+            .columns
+            .iter()
+            .map(|column| {
+                json!({
+                    "title": column.name,
+                    "type": "object",
+                    "properties": {
+                        "values": {
+                            "type": "array",
+                            "items": {
+                                "anyOf": [json_type(&column.value_type), {"type": "null"}]
+                            }
+                        },
+                        "statuses": {
+                            "type": "array",
+                            "items": {
+                                "enum": [
+                                    "PRESENT", "NULL_VALUE", "NOT_FOUND", "OUTSIDE_MAX_AGE",
+                                    "INVALID"
+                                ]
+                            }
+                        },
+                        "event_timestamps": {
+                            "type": "array",
+                            "items": {"type": "string", "format": "date-time"}
+                        }
+                    },
+                    "required": ["values"]
+                })
+            })
+            .collect();
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": self.feature_service,
+            "type": "object",
+            "properties": {
+                "metadata": {
+                    "type": "object",
+                    "properties": {"feature_names": {"const": names}},
+                    "required": ["feature_names"]
+                },
+                "results": {
+                    "type": "array",
+                    "prefixItems": results,
+                    "items": false
+                }
+            },
+            "required": ["metadata", "results"]
+        })
+    }
+
+    /// `.proto` file with a message holding one response row.
+    pub fn to_proto(&self) -> String {
+        let mut proto = String::from("syntax = \"proto3\";\n\n");
+        let _ = writeln!(
+            proto,
+            "// One entity row of the responses of feature service '{}'.",
+            self.feature_service
+        );
+        let _ = writeln!(
+            proto,
+            "message {}Row {{",
+            pascal_case(&self.feature_service)
+        );
+        for (number, column) in self.columns.iter().enumerate() {
+            let (label, proto_type) = proto_type(&column.value_type);
+            let _ = write!(
+                proto,
+                "  {}{} {} = {};",
+                label,
+                proto_type,
+                column.name,
+                number + 1
+            );
+            if column.value_type == "UNIX_TIMESTAMP" {
+                proto.push_str(" // seconds since the epoch");
+            }
+            proto.push('\n');
+        }
+        proto.push_str("}\n");
+        proto
+    }
+}
+
+/// JSON type of the values of a Feast value type, as the JSON responses encode them.
+fn json_type(value_type: &str) -> Value {
+    if let Some(element) = value_type.strip_suffix("_LIST") {
+        return json!({"type": "array", "items": json_type(element)});
+    }
+    match value_type {
+        "INT32" | "INT64" | "UNIX_TIMESTAMP" => json!({"type": "integer"}),
+        "FLOAT" | "DOUBLE" => json!({"type": "number"}),
+        "STRING" => json!({"type": "string"}),
+        "BOOL" => json!({"type": "boolean"}),
+        "BYTES" => json!({
+            "type": "array",
+            "items": {"type": "integer", "minimum": 0, "maximum": 255}
+        }),
+        _ => json!({}),
+    }
+}
+
+/// Field label and type of a Feast value type. Scalars are `optional`, so that missing
+/// values can be told apart from default ones.
+fn proto_type(value_type: &str) -> (&'static str, &'static str) {
+    if let Some(element) = value_type.strip_suffix("_LIST") {
+        return ("repeated ", proto_type(element).1);
+    }
+    let proto_type = match value_type {
+        "INT32" => "int32",
+        "INT64" | "UNIX_TIMESTAMP" => "int64",
+        "FLOAT" => "float",
+        "DOUBLE" => "double",
+        "STRING" => "string",
+        "BOOL" => "bool",
+        _ => "bytes",
+    };
+    ("optional ", proto_type)
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{FeatureRegistryService, FileFeatureRegistry};
+
+    async fn metadata() -> Result<RegistryMetadata> {
+        let registry_file = format!("{}/test_data/registry.pb", env!("CARGO_MANIFEST_DIR"));
+        let registry = FileFeatureRegistry::from_path(&std::path::PathBuf::from(registry_file))?;
+        registry.metadata().await
+    }
+
+    #[tokio::test]
+    async fn columns_follow_the_feature_service() -> Result<()> {
+        let metadata = metadata().await?;
+        let schema = ResponseSchema::new(&metadata, "driver_activity_alias", true)?;
+        assert!(schema.columns[0].entity);
+        assert!(
+            schema
+                .columns
+                .iter()
+                .all(|column| column.entity || column.name.starts_with("driver_alias__"))
+        );
+
+        let proto = schema.to_proto();
+        assert!(proto.contains("message DriverActivityAliasRow {"));
+        let json_schema = schema.to_json_schema();
+        assert_eq!(
+            json_schema["properties"]["results"]["prefixItems"]
+                .as_array()
+                .map(Vec::len),
+            Some(schema.columns.len())
+        );
+
+        let err = ResponseSchema::new(&metadata, "missing", false).unwrap_err();
+        assert!(err.downcast_ref::<FeastCoreError>().unwrap().is_not_found());
+        Ok(())
+    }
+
+    #[test]
+    fn value_types_map_to_proto_fields() {
+        assert_eq!(proto_type("INT64"), ("optional ", "int64"));
+        assert_eq!(proto_type("FLOAT_LIST"), ("repeated ", "float"));
+        assert_eq!(pascal_case("driver_activity_v4"), "DriverActivityV4");
+    }
+}
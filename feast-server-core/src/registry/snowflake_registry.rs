@@ -0,0 +1,441 @@
+use crate::config::RegistryConfig;
+use crate::intern;
+use crate::model::{
+    Entity, FeatureRegistry, FeatureService, FeatureView, OnDemandFeatureView, StreamFeatureView,
+};
+use crate::registry::FileFeatureRegistry;
+use anyhow::{Context, Result, anyhow};
+use lasso::Spur;
+use reqwest::Client;
+use rustc_hash::FxHashMap as HashMap;
+use serde::Deserialize;
+use serde_json::{Value as JsonValue, json};
+use std::fmt::{self, Display, Formatter};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+const FEAST_SQL_REGISTRY_USERNAME_ENV_VAR: &str = "FEAST_SQL_REGISTRY_USERNAME";
+const FEAST_SQL_REGISTRY_PASSWORD_ENV_VAR: &str = "FEAST_SQL_REGISTRY_PASSWORD";
+const DEFAULT_SCHEMA: &str = "PUBLIC";
+/// Response codes of requests made with an expired session token.
+const SESSION_EXPIRED_CODES: [&str; 2] = ["390112", "390114"];
+
+/// Location of the registry tables parsed from `snowflake://<database>[/<schema>][?warehouse=<name>]`.
+#[derive(Debug, Clone, PartialEq)]
+struct SnowflakeLocation {
+    database: String,
+    schema: String,
+    warehouse: Option<String>,
+}
+
+impl SnowflakeLocation {
+    fn parse(path: &str) -> Result<Self> {
+        let rest = path
+            .strip_prefix("snowflake://")
+            .ok_or_else(|| anyhow!("Snowflake registry path must start with snowflake://"))?;
+        let (location, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut parts = location.split('/').filter(|part| !part.is_empty());
+        let database = parts
+            .next()
+            .ok_or_else(|| anyhow!("Missing database in Snowflake registry path {}", path))?
+            .to_string();
+        let schema = parts.next().unwrap_or(DEFAULT_SCHEMA).to_string();
+        let warehouse = query
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .find(|(key, _)| *key == "warehouse")
+            .map(|(_, value)| value.to_string());
+        Ok(Self {
+            database,
+            schema,
+            warehouse,
+        })
+    }
+
+    fn table(&self, name: &str) -> String {
+        format!("\"{}\".\"{}\".{}", self.database, self.schema, name)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SnowflakeResponse {
+    success: bool,
+    code: Option<String>,
+    message: Option<String>,
+    data: Option<JsonValue>,
+}
+
+/// The session token of a request expired, so the session has to be logged in again.
+#[derive(Debug)]
+struct SessionExpired;
+
+impl Display for SessionExpired {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Snowflake session expired")
+    }
+}
+
+impl std::error::Error for SessionExpired {}
+
+impl SnowflakeResponse {
+    fn into_data(self, action: &str) -> Result<JsonValue> {
+        if !self.success
+            && self
+                .code
+                .as_deref()
+                .is_some_and(|code| SESSION_EXPIRED_CODES.contains(&code))
+        {
+            return Err(SessionExpired.into());
+        }
+        if !self.success {
+            return Err(anyhow!(
+                "Snowflake {} failed: {}",
+                action,
+                self.message.unwrap_or_default()
+            ));
+        }
+        self.data
+            .ok_or_else(|| anyhow!("Snowflake {} returned no data", action))
+    }
+}
+
+/// Registry stored in the Snowflake tables written by the Feast Snowflake registry.
+/// Uses the session login and query endpoints of the Snowflake connectors, which accept
+/// user and password credentials, and reads results in JSON format. A single session is
+/// kept for every read and logged in again once its token expires.
+pub(crate) struct SnowflakeRegistry {
+    client: Client,
+    base_url: String,
+    login_query: Vec<(&'static str, String)>,
+    login_body: JsonValue,
+    token: Mutex<String>,
+    location: SnowflakeLocation,
+    project: String,
+}
+
+impl SnowflakeRegistry {
+    pub(crate) async fn connect(config: &RegistryConfig, project: String) -> Result<Self> {
+        let location = SnowflakeLocation::parse(&config.path)?;
+        let account = config
+            .account
+            .clone()
+            .ok_or_else(|| anyhow!("Snowflake registry requires the account setting"))?;
+        let user = std::env::var(FEAST_SQL_REGISTRY_USERNAME_ENV_VAR)
+            .ok()
+            .or_else(|| config.user.clone())
+            .ok_or_else(|| anyhow!("Snowflake registry requires the user setting"))?;
+        let password = std::env::var(FEAST_SQL_REGISTRY_PASSWORD_ENV_VAR)
+            .ok()
+            .or_else(|| config.password.clone())
+            .ok_or_else(|| anyhow!("Snowflake registry requires the password setting"))?;
+
+        let account_name = account.split('.').next().unwrap_or(&account).to_string();
+        let base_url = if account.ends_with(".snowflakecomputing.com") {
+            format!("https://{}", account)
+        } else {
+            format!("https://{}.snowflakecomputing.com", account)
+        };
+
+        let mut login_query = vec![
+            ("databaseName", location.database.clone()),
+            ("schemaName", location.schema.clone()),
+        ];
+        if let Some(warehouse) = &location.warehouse {
+            login_query.push(("warehouse", warehouse.clone()));
+        }
+        if let Some(role) = &config.role {
+            login_query.push(("roleName", role.clone()));
+        }
+        let login_body = json!({
+            "data": {
+                "CLIENT_APP_ID": "feast-server-rust",
+                "CLIENT_APP_VERSION": env!("CARGO_PKG_VERSION"),
+                "ACCOUNT_NAME": account_name,
+                "LOGIN_NAME": user,
+                "PASSWORD": password,
+            }
+        });
+        let registry = Self {
+            client: Client::new(),
+            base_url,
+            login_query,
+            login_body,
+            token: Mutex::new(String::new()),
+            location,
+            project,
+        };
+        registry.login().await?;
+        Ok(registry)
+    }
+
+    /// Open a new session and use its token for the following queries.
+    async fn login(&self) -> Result<()> {
+        let response: SnowflakeResponse = self
+            .client
+            .post(format!("{}/session/v1/login-request", self.base_url))
+            .query(&self.login_query)
+            .header("Accept", "application/json")
+            .json(&self.login_body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let token = response
+            .into_data("login")?
+            .get("token")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| anyhow!("Snowflake login response has no session token"))?
+            .to_string();
+        *self.token.lock().unwrap_or_else(|e| e.into_inner()) = token;
+        Ok(())
+    }
+
+    /// Read the registry tables with the current session, logging in again first when its
+    /// token expired.
+    pub(crate) async fn query_registry(&self) -> Result<FileFeatureRegistry> {
+        match self.read_registry().await {
+            Err(err) if err.is::<SessionExpired>() => {
+                debug!("Snowflake session expired, logging in again");
+                self.login().await?;
+                self.read_registry().await
+            }
+            result => result,
+        }
+    }
+
+    async fn read_registry(&self) -> Result<FileFeatureRegistry> {
+        let entities = self
+            .query_table::<Entity>("ENTITIES", "entity_name", "entity_proto", "Entity")
+            .await?;
+        let mut feature_views = self
+            .query_table::<FeatureView>(
+                "FEATURE_VIEWS",
+                "feature_view_name",
+                "feature_view_proto",
+                "FeatureView",
+            )
+            .await?;
+        let stream_feature_views = self
+            .query_table::<StreamFeatureView>(
+                "STREAM_FEATURE_VIEWS",
+                "stream_feature_view_name",
+                "stream_feature_view_proto",
+                "StreamFeatureView",
+            )
+            .await?;
+        feature_views.extend(
+            stream_feature_views
+                .into_iter()
+                .map(|(name, view)| (name, view.0)),
+        );
+        let on_demand_feature_views = self
+            .query_table::<OnDemandFeatureView>(
+                "ON_DEMAND_FEATURE_VIEWS",
+                "on_demand_feature_view_name",
+                "on_demand_feature_view_proto",
+                "OnDemandFeatureView",
+            )
+            .await?;
+        let feature_services = self
+            .query_table::<FeatureService>(
+                "FEATURE_SERVICES",
+                "feature_service_name",
+                "feature_service_proto",
+                "FeatureService",
+            )
+            .await?;
+
+        Ok(FileFeatureRegistry::from_registry(FeatureRegistry::new(
+            entities,
+            feature_views,
+            on_demand_feature_views,
+            feature_services,
+        )))
+    }
+
+    async fn query_table<T>(
+        &self,
+        table_name: &str,
+        name_col: &str,
+        proto_col: &str,
+        type_name: &str,
+    ) -> Result<HashMap<Spur, T>>
+    where
+        T: TryFrom<Vec<u8>, Error = anyhow::Error>,
+    {
+        let query = format!(
+            "SELECT {}, {} FROM {} WHERE project_id = ?",
+            name_col,
+            proto_col,
+            self.location.table(table_name)
+        );
+        let rows = self.execute(&query, &self.project).await?;
+        let rodeo = intern::rodeo_ref();
+        rows.into_iter()
+            .map(|row| {
+                let (Some(name), Some(proto_hex)) = (
+                    row.first().and_then(JsonValue::as_str),
+                    row.get(1).and_then(JsonValue::as_str),
+                ) else {
+                    return Err(anyhow!("Unexpected row in {}: {:?}", table_name, row));
+                };
+                // Snowflake returns BINARY columns as hex strings in JSON results
+                let proto = hex::decode(proto_hex)?;
+                T::try_from(proto)
+                    .map_err(|e| {
+                        anyhow!(
+                            "Failed to convert {} proto for '{}': {}",
+                            type_name,
+                            name,
+                            e
+                        )
+                    })
+                    .map(|item| (rodeo.get_or_intern(name), item))
+            })
+            .collect()
+    }
+
+    /// Run a query with a single text binding and collect all result rows, including
+    /// rows of result chunks stored outside of the response.
+    async fn execute(&self, sql: &str, binding: &str) -> Result<Vec<Vec<JsonValue>>> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let token = self.token.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let body = json!({
+            "sqlText": sql,
+            "asyncExec": false,
+            "sequenceId": 1,
+            "querySubmissionTime": now.as_millis() as u64,
+            "bindings": {"1": {"type": "TEXT", "value": binding}},
+        });
+        let response: SnowflakeResponse = self
+            .client
+            .post(format!("{}/queries/v1/query-request", self.base_url))
+            .query(&[("requestId", request_id(now.as_nanos()))])
+            .header("Authorization", format!("Snowflake Token=\"{}\"", token))
+            .header("Accept", "application/snowflake")
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let data = response.into_data("query")?;
+        // Arrow results are only sent to clients announcing support for them
+        if data
+            .get("rowsetBase64")
+            .and_then(JsonValue::as_str)
+            .is_some_and(|rowset| !rowset.is_empty())
+        {
+            return Err(anyhow!("Arrow result format is not supported"));
+        }
+
+        let mut rows: Vec<Vec<JsonValue>> = match data.get("rowset") {
+            Some(rowset) => serde_json::from_value(rowset.clone())?,
+            None => Vec::new(),
+        };
+        let chunks = data
+            .get("chunks")
+            .and_then(JsonValue::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for chunk in chunks {
+            let url = chunk
+                .get("url")
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| anyhow!("Result chunk without url"))?;
+            let mut request = self.client.get(url);
+            if let Some(headers) = data.get("chunkHeaders").and_then(JsonValue::as_object) {
+                for (name, value) in headers {
+                    request = request.header(name, value.as_str().unwrap_or_default());
+                }
+            } else if let Some(qrmk) = data.get("qrmk").and_then(JsonValue::as_str) {
+                request = request
+                    .header("x-amz-server-side-encryption-customer-algorithm", "AES256")
+                    .header("x-amz-server-side-encryption-customer-key", qrmk);
+            }
+            let content = request.send().await?.error_for_status()?.text().await?;
+            // JSON chunks hold comma separated rows without the enclosing array
+            let chunk_rows: Vec<Vec<JsonValue>> =
+                serde_json::from_str(&format!("[{}]", content))
+                    .context("Failed to parse Snowflake result chunk")?;
+            rows.extend(chunk_rows);
+        }
+        Ok(rows)
+    }
+}
+
+/// Build a UUID formatted request id from a timestamp, unique enough for request retries.
+fn request_id(nanos: u128) -> String {
+    let hex = format!("{:032x}", nanos ^ ((std::process::id() as u128) << 96));
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_snowflake_location() -> Result<()> {
+        let location = SnowflakeLocation::parse("snowflake://FEAST/REGISTRY?warehouse=COMPUTE_WH")?;
+        assert_eq!(
+            location,
+            SnowflakeLocation {
+                database: "FEAST".to_string(),
+                schema: "REGISTRY".to_string(),
+                warehouse: Some("COMPUTE_WH".to_string()),
+            }
+        );
+        assert_eq!(
+            location.table("ENTITIES"),
+            "\"FEAST\".\"REGISTRY\".ENTITIES"
+        );
+
+        let location = SnowflakeLocation::parse("snowflake://FEAST")?;
+        assert_eq!(location.schema, DEFAULT_SCHEMA);
+        assert!(SnowflakeLocation::parse("postgres://FEAST").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn expired_session_is_told_apart() {
+        let response = |code: &str| SnowflakeResponse {
+            success: false,
+            code: Some(code.to_string()),
+            message: Some("Your session has expired. Please login again.".to_string()),
+            data: None,
+        };
+        let expired = response("390112").into_data("query").unwrap_err();
+        assert!(expired.is::<SessionExpired>());
+        let failed = response("002003").into_data("query").unwrap_err();
+        assert!(!failed.is::<SessionExpired>());
+        assert_eq!(
+            failed.to_string(),
+            "Snowflake query failed: Your session has expired. Please login again."
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn read_registry_from_snowflake() -> Result<()> {
+        let config = RegistryConfig {
+            path: "snowflake://FEAST/PUBLIC?warehouse=COMPUTE_WH".to_string(),
+            account: std::env::var("SNOWFLAKE_ACCOUNT").ok(),
+            user: std::env::var("SNOWFLAKE_USER").ok(),
+            password: std::env::var("SNOWFLAKE_PASSWORD").ok(),
+            ..Default::default()
+        };
+        let registry = SnowflakeRegistry::connect(&config, "careful_tomcat".to_string()).await?;
+        let registry_data = registry.query_registry().await?;
+        println!("{:#?}", registry_data);
+        Ok(())
+    }
+}
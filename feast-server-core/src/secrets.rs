@@ -0,0 +1,399 @@
+//! Resolution of secret references in the repository configuration.
+//!
+//! Any string value of `feature_store.yaml` may reference a secret instead of holding it:
+//! - `aws-sm://<secret-id>` reads a secret string from AWS Secrets Manager, and
+//!   `aws-sm://<secret-id>#<key>` reads `key` of a secret holding a JSON object. Secret
+//!   ids may contain `/`, but not `#`.
+//! - `gcp-sm://<project>/<secret>[/<version>]` reads a secret version from GCP Secret
+//!   Manager, defaulting to the `latest` version.
+//!
+//! Credentials for both services come from the default provider chains of their SDKs.
+
+use crate::config::RepoConfig;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+#[cfg(feature = "gcp")]
+use base64::Engine;
+#[cfg(feature = "gcp")]
+use base64::engine::general_purpose::STANDARD as BASE64;
+#[cfg(feature = "gcp")]
+use google_cloud_auth::project::Config as GcpAuthConfig;
+#[cfg(feature = "gcp")]
+use google_cloud_auth::token::DefaultTokenSourceProvider;
+#[cfg(feature = "gcp")]
+use google_cloud_token::TokenSourceProvider;
+use rustc_hash::FxHashMap as HashMap;
+use serde_json::Value as JsonValue;
+#[cfg(any(feature = "aws", feature = "gcp"))]
+use tokio::sync::OnceCell;
+
+const AWS_SECRET_PREFIX: &str = "aws-sm://";
+const GCP_SECRET_PREFIX: &str = "gcp-sm://";
+#[cfg(feature = "gcp")]
+const GCP_SECRET_MANAGER_URL: &str = "https://secretmanager.googleapis.com/v1";
+#[cfg(feature = "gcp")]
+const GCP_SCOPES: [&str; 1] = ["https://www.googleapis.com/auth/cloud-platform"];
+
+/// Secret referenced from a config value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SecretRef {
+    Aws {
+        secret_id: String,
+        key: Option<String>,
+    },
+    Gcp {
+        project: String,
+        secret: String,
+        version: String,
+    },
+}
+
+impl SecretRef {
+    /// Parse a secret reference, returning `None` for values which are not references.
+    pub fn parse(value: &str) -> Option<Result<Self>> {
+        if let Some(path) = value.strip_prefix(AWS_SECRET_PREFIX) {
+            let secret = match path.split_once('#') {
+                Some((secret_id, key)) if !secret_id.is_empty() && !key.is_empty() => {
+                    Ok(SecretRef::Aws {
+                        secret_id: secret_id.to_string(),
+                        key: Some(key.to_string()),
+                    })
+                }
+                None if !path.is_empty() => Ok(SecretRef::Aws {
+                    secret_id: path.to_string(),
+                    key: None,
+                }),
+                _ => Err(anyhow!("Invalid AWS secret reference: {}", value)),
+            };
+            return Some(secret);
+        }
+        if let Some(path) = value.strip_prefix(GCP_SECRET_PREFIX) {
+            let parts: Vec<&str> = path.split('/').collect();
+            let secret = match parts.as_slice() {
+                [project, secret] | [project, secret, _]
+                    if !project.is_empty() && !secret.is_empty() =>
+                {
+                    Ok(SecretRef::Gcp {
+                        project: project.to_string(),
+                        secret: secret.to_string(),
+                        version: parts.get(2).unwrap_or(&"latest").to_string(),
+                    })
+                }
+                _ => Err(anyhow!("Invalid GCP secret reference: {}", value)),
+            };
+            return Some(secret);
+        }
+        None
+    }
+}
+
+/// Source of secret values.
+#[async_trait]
+pub trait SecretFetcher: Send + Sync {
+    async fn fetch(&self, secret: &SecretRef) -> Result<String>;
+}
+
+/// Fetches secrets from AWS Secrets Manager and GCP Secret Manager. Clients are created
+/// on first use, so configs referencing a single cloud need credentials for that cloud only.
+/// Each cloud needs its feature, `aws` or `gcp`.
+#[derive(Default)]
+pub struct CloudSecretFetcher {
+    #[cfg(feature = "aws")]
+    aws: OnceCell<aws_sdk_secretsmanager::Client>,
+    #[cfg(feature = "gcp")]
+    gcp: OnceCell<DefaultTokenSourceProvider>,
+    #[cfg(feature = "gcp")]
+    http: reqwest::Client,
+}
+
+impl CloudSecretFetcher {
+    #[cfg(feature = "aws")]
+    async fn fetch_aws(&self, secret_id: &str) -> Result<String> {
+        let client = self
+            .aws
+            .get_or_init(|| async {
+                let config = aws_config::load_from_env().await;
+                aws_sdk_secretsmanager::Client::new(&config)
+            })
+            .await;
+        let output = client
+            .get_secret_value()
+            .secret_id(secret_id)
+            .send()
+            .await?;
+        if let Some(value) = output.secret_string() {
+            return Ok(value.to_string());
+        }
+        let binary = output
+            .secret_binary()
+            .ok_or_else(|| anyhow!("AWS secret {} has no value", secret_id))?;
+        Ok(String::from_utf8(binary.as_ref().to_vec())?)
+    }
+
+    #[cfg(feature = "gcp")]
+    async fn fetch_gcp(&self, project: &str, secret: &str, version: &str) -> Result<String> {
+        let provider = self
+            .gcp
+            .get_or_try_init(|| async {
+                DefaultTokenSourceProvider::new(GcpAuthConfig::default().with_scopes(&GCP_SCOPES))
+                    .await
+            })
+            .await?;
+        let token = provider
+            .token_source()
+            .token()
+            .await
+            .map_err(|err| anyhow!("Failed to get GCP access token: {}", err))?;
+        let url = format!(
+            "{}/projects/{}/secrets/{}/versions/{}:access",
+            GCP_SECRET_MANAGER_URL, project, secret, version
+        );
+        let response: JsonValue = self
+            .http
+            .get(url)
+            .header("Authorization", token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let data = response
+            .pointer("/payload/data")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| anyhow!("GCP secret {} has no payload", secret))?;
+        Ok(String::from_utf8(BASE64.decode(data)?)?)
+    }
+}
+
+#[async_trait]
+impl SecretFetcher for CloudSecretFetcher {
+    async fn fetch(&self, secret: &SecretRef) -> Result<String> {
+        match secret {
+            #[cfg(feature = "aws")]
+            SecretRef::Aws { secret_id, .. } => self.fetch_aws(secret_id).await,
+            #[cfg(not(feature = "aws"))]
+            SecretRef::Aws { .. } => Err(crate::util::feature_disabled(
+                "AWS Secrets Manager references",
+                "aws",
+            )),
+            #[cfg(feature = "gcp")]
+            SecretRef::Gcp {
+                project,
+                secret,
+                version,
+            } => self.fetch_gcp(project, secret, version).await,
+            #[cfg(not(feature = "gcp"))]
+            SecretRef::Gcp { .. } => Err(crate::util::feature_disabled(
+                "GCP Secret Manager references",
+                "gcp",
+            )),
+        }
+    }
+}
+
+/// Replace secret references in `config` with secret values fetched from the cloud providers.
+pub async fn resolve_secrets(config: RepoConfig) -> Result<RepoConfig> {
+    resolve_secrets_with(config, &CloudSecretFetcher::default()).await
+}
+
+/// Replace secret references in `config` with values returned by `fetcher`. Every secret
+/// is fetched once even when several keys of it are referenced.
+pub async fn resolve_secrets_with(
+    config: RepoConfig,
+    fetcher: &dyn SecretFetcher,
+) -> Result<RepoConfig> {
+    let mut value = serde_json::to_value(&config)?;
+    let mut references = Vec::new();
+    collect_references(&value, &mut references)?;
+    if references.is_empty() {
+        return Ok(config);
+    }
+
+    let mut fetched: HashMap<SecretRef, String> = HashMap::default();
+    for (target, secret) in references {
+        let cache_key = match &secret {
+            SecretRef::Aws { secret_id, .. } => SecretRef::Aws {
+                secret_id: secret_id.clone(),
+                key: None,
+            },
+            gcp => gcp.clone(),
+        };
+        if !fetched.contains_key(&cache_key) {
+            let secret_value = fetcher
+                .fetch(&cache_key)
+                .await
+                .with_context(|| format!("Failed to fetch secret {:?}", cache_key))?;
+            fetched.insert(cache_key.clone(), secret_value);
+        }
+        let secret_value = &fetched[&cache_key];
+        let resolved = match &secret {
+            SecretRef::Aws { key: Some(key), .. } => json_secret_key(secret_value, key)?,
+            _ => secret_value.clone(),
+        };
+        *value
+            .pointer_mut(&target)
+            .ok_or_else(|| anyhow!("Config value {} disappeared", target))? =
+            JsonValue::String(resolved);
+    }
+    tracing::info!(
+        "Resolved {} secret references in configuration",
+        fetched.len()
+    );
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Collect JSON pointers of string values holding secret references.
+fn collect_references(value: &JsonValue, references: &mut Vec<(String, SecretRef)>) -> Result<()> {
+    fn walk(
+        value: &JsonValue,
+        pointer: String,
+        references: &mut Vec<(String, SecretRef)>,
+    ) -> Result<()> {
+        match value {
+            JsonValue::Object(map) => {
+                for (key, value) in map {
+                    walk(
+                        value,
+                        format!("{}/{}", pointer, escape_pointer_token(key)),
+                        references,
+                    )?;
+                }
+            }
+            JsonValue::Array(items) => {
+                for (index, value) in items.iter().enumerate() {
+                    walk(value, format!("{}/{}", pointer, index), references)?;
+                }
+            }
+            JsonValue::String(text) => {
+                if let Some(secret) = SecretRef::parse(text) {
+                    references.push((pointer, secret?));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    walk(value, String::new(), references)
+}
+
+/// Escape a key as a JSON pointer reference token, as RFC 6901 requires for `~` and `/`.
+fn escape_pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn json_secret_key(secret_value: &str, key: &str) -> Result<String> {
+    let json: JsonValue = serde_json::from_str(secret_value)
+        .map_err(|_| anyhow!("Secret referenced with key '{}' is not a JSON object", key))?;
+    match json.get(key) {
+        Some(JsonValue::String(value)) => Ok(value.clone()),
+        Some(JsonValue::Null) | None => Err(anyhow!("Secret has no key '{}'", key)),
+        Some(other) => Ok(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OnlineStoreConfig;
+    use std::sync::Mutex;
+
+    struct StaticFetcher {
+        calls: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl SecretFetcher for StaticFetcher {
+        async fn fetch(&self, secret: &SecretRef) -> Result<String> {
+            *self.calls.lock().unwrap() += 1;
+            match secret {
+                SecretRef::Aws { secret_id, .. } if secret_id == "feast/creds" => {
+                    Ok(r#"{"user": "feast", "password": "hunter2"}"#.to_string())
+                }
+                SecretRef::Gcp { secret, .. } if secret == "redis" => {
+                    Ok("redis:6379,password=hunter2".to_string())
+                }
+                _ => Err(anyhow!("unknown secret")),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_secret_references() {
+        assert_eq!(
+            SecretRef::parse("aws-sm://my-secret#key").unwrap().unwrap(),
+            SecretRef::Aws {
+                secret_id: "my-secret".to_string(),
+                key: Some("key".to_string()),
+            }
+        );
+        // Secret names often hold `/`, which never starts a key
+        assert_eq!(
+            SecretRef::parse("aws-sm://prod/feast/db-password")
+                .unwrap()
+                .unwrap(),
+            SecretRef::Aws {
+                secret_id: "prod/feast/db-password".to_string(),
+                key: None,
+            }
+        );
+        assert!(SecretRef::parse("aws-sm://prod/feast#").unwrap().is_err());
+        assert_eq!(
+            SecretRef::parse("gcp-sm://proj/redis").unwrap().unwrap(),
+            SecretRef::Gcp {
+                project: "proj".to_string(),
+                secret: "redis".to_string(),
+                version: "latest".to_string(),
+            }
+        );
+        assert!(SecretRef::parse("gcp-sm://proj").unwrap().is_err());
+        assert!(SecretRef::parse("postgresql://db/feast").is_none());
+    }
+
+    #[tokio::test]
+    async fn resolve_config_secrets() -> Result<()> {
+        let yaml_str = r#"
+project: secrets
+registry:
+    registry_type: sql
+    path: postgresql://db:5432/feast
+    user: aws-sm://feast/creds#user
+    password: aws-sm://feast/creds#password
+online_store:
+    type: redis
+    connection_string: gcp-sm://proj/redis
+"#;
+        let config = RepoConfig::from_yaml_str(yaml_str)?;
+        let fetcher = StaticFetcher {
+            calls: Mutex::new(0),
+        };
+        let resolved = resolve_secrets_with(config, &fetcher).await?;
+        assert_eq!(resolved.registry.user.as_deref(), Some("feast"));
+        assert_eq!(resolved.registry.password.as_deref(), Some("hunter2"));
+        let OnlineStoreConfig::Redis {
+            connection_string, ..
+        } = &resolved.online_store
+        else {
+            panic!("expected redis online store");
+        };
+        assert_eq!(connection_string, "redis:6379,password=hunter2");
+        assert_eq!(*fetcher.calls.lock().unwrap(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn references_under_keys_with_slashes_and_tildes() -> Result<()> {
+        let value = serde_json::json!({
+            "options": {"auth/token~1": "aws-sm://feast/creds"}
+        });
+        let mut references = Vec::new();
+        collect_references(&value, &mut references)?;
+        assert_eq!(references.len(), 1);
+        let (pointer, _) = &references[0];
+        assert_eq!(pointer, "/options/auth~1token~01");
+        assert_eq!(
+            value.pointer(pointer),
+            Some(&JsonValue::String("aws-sm://feast/creds".to_string()))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+//! Helpers shared by the tests of the crate.
+
+use anyhow::Result;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+/// Copy of `test_data/online_store.db` in a temporary file, removed when dropped.
+pub(crate) fn temp_online_store() -> Result<NamedTempFile> {
+    let file = tempfile::Builder::new()
+        .prefix("feast_online_store_")
+        .suffix(".db")
+        .tempfile()?;
+    std::fs::copy(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/online_store.db"),
+        file.path(),
+    )?;
+    Ok(file)
+}
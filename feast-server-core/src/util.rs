@@ -1,7 +1,25 @@
+use crate::feast::types::value_type::Enum as ValueTypeEnum;
 use crate::feast::types::{EntityKey, Value};
-use chrono::{DateTime, Duration, Utc};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use prost_types::Duration as ProstDuration;
 use prost_types::Timestamp as ProstTimestamp;
+use serde_json::Value as JsonValue;
+
+/// Error for configs needing a backend the server was compiled without.
+#[cfg(not(all(
+    feature = "aws",
+    feature = "gcp",
+    feature = "redis",
+    feature = "postgres-registry"
+)))]
+pub(crate) fn feature_disabled(backend: &str, feature: &str) -> anyhow::Error {
+    anyhow!(
+        "{} are not supported by this build, which was compiled without the `{}` feature",
+        backend,
+        feature
+    )
+}
 
 pub fn prost_duration_to_duration(prost_duration: &ProstDuration) -> Duration {
     let seconds = prost_duration.seconds.max(0);
@@ -15,6 +33,75 @@ pub fn prost_timestamp_to_datetime(prost_timestamp: &ProstTimestamp) -> DateTime
     DateTime::<Utc>::from_timestamp(seconds, nanos).unwrap_or(DateTime::<Utc>::UNIX_EPOCH)
 }
 
+/// Parse a timestamp from pushed JSON data. Accepts RFC 3339 strings, naive
+/// `YYYY-MM-DD HH:MM:SS[.f]` strings interpreted as UTC, and epoch seconds.
+pub fn parse_json_timestamp(json: &serde_json::Value) -> Result<DateTime<Utc>> {
+    match json {
+        serde_json::Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .map(|ts| ts.with_timezone(&Utc))
+            .or_else(|_| {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f").map(|ts| ts.and_utc())
+            })
+            .map_err(|_| anyhow!("Invalid timestamp value: {}", s)),
+        serde_json::Value::Number(n) => {
+            let seconds = n
+                .as_f64()
+                .ok_or_else(|| anyhow!("Invalid timestamp value: {}", n))?;
+            let nanos = (seconds.fract() * 1e9) as u32;
+            DateTime::<Utc>::from_timestamp(seconds.trunc() as i64, nanos)
+                .ok_or_else(|| anyhow!("Timestamp out of range: {}", n))
+        }
+        other => Err(anyhow!("Invalid timestamp value: {}", other)),
+    }
+}
+
+/// JSON value of a CSV cell of a column of `value_type`: numbers and booleans are parsed for
+/// columns of these types, list cells as JSON arrays, and strings stay strings, so "007"
+/// keeps its zeros. Cells of columns without a known type are inferred: integers, finite
+/// floats and booleans are parsed and anything else stays a string. Empty cells are null.
+pub fn csv_cell_to_json(cell: &str, value_type: Option<ValueTypeEnum>) -> JsonValue {
+    use ValueTypeEnum as Enum;
+    if cell.is_empty() {
+        return JsonValue::Null;
+    }
+    let parsed = match value_type {
+        None => return infer_csv_cell(cell),
+        Some(Enum::String | Enum::Bytes) => None,
+        Some(Enum::Bool) => match cell {
+            "true" | "True" | "TRUE" | "1" => Some(JsonValue::Bool(true)),
+            "false" | "False" | "FALSE" | "0" => Some(JsonValue::Bool(false)),
+            _ => None,
+        },
+        Some(Enum::Int32 | Enum::Int64 | Enum::UnixTimestamp) => {
+            cell.parse::<i64>().ok().map(JsonValue::from)
+        }
+        Some(Enum::Float | Enum::Double) => cell
+            .parse::<f64>()
+            .ok()
+            .filter(|f| f.is_finite())
+            .map(JsonValue::from),
+        Some(_) => serde_json::from_str(cell).ok().filter(JsonValue::is_array),
+    };
+    // Cells not of the column type stay strings, and fail the conversion to the type
+    parsed.unwrap_or_else(|| JsonValue::String(cell.to_string()))
+}
+
+fn infer_csv_cell(cell: &str) -> JsonValue {
+    if let Ok(i) = cell.parse::<i64>() {
+        return JsonValue::from(i);
+    }
+    if let Ok(f) = cell.parse::<f64>()
+        && f.is_finite()
+    {
+        return JsonValue::from(f);
+    }
+    match cell {
+        "true" | "True" => JsonValue::Bool(true),
+        "false" | "False" => JsonValue::Bool(false),
+        _ => JsonValue::String(cell.to_string()),
+    }
+}
+
 #[derive(Debug)]
 pub struct EntityKeyWrapper(pub EntityKey);
 
@@ -37,3 +124,101 @@ impl PartialEq for EntityKeyWrapper {
         self_values == other_values
     }
 }
+
+/// HTTP server answering requests with canned responses, for clients of HTTP backends.
+#[cfg(test)]
+pub(crate) mod test_http {
+    use anyhow::Result;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Status, headers and body of a response.
+    pub(crate) type StubResponse = (u16, Vec<(&'static str, &'static str)>, Vec<u8>);
+
+    pub(crate) struct HttpStub {
+        addr: std::net::SocketAddr,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl HttpStub {
+        /// Answer the connections made to the stub with `responses` in order, one response
+        /// per connection.
+        pub(crate) async fn start(responses: Vec<StubResponse>) -> Result<Self> {
+            let listener = TcpListener::bind("127.0.0.1:0").await?;
+            let addr = listener.local_addr()?;
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&requests);
+            tokio::spawn(async move {
+                for (status, headers, body) in responses {
+                    let Ok((mut stream, _)) = listener.accept().await else {
+                        return;
+                    };
+                    let Ok(request) = read_request(&mut stream).await else {
+                        return;
+                    };
+                    recorded.lock().unwrap().push(request);
+                    let mut response = format!(
+                        "HTTP/1.1 {} Stub\r\ncontent-length: {}\r\nconnection: close\r\n",
+                        status,
+                        body.len()
+                    );
+                    for (name, value) in headers {
+                        response.push_str(&format!("{}: {}\r\n", name, value));
+                    }
+                    response.push_str("\r\n");
+                    let mut bytes = response.into_bytes();
+                    bytes.extend(body);
+                    let _ = stream.write_all(&bytes).await;
+                    let _ = stream.shutdown().await;
+                }
+            });
+            Ok(Self { addr, requests })
+        }
+
+        pub(crate) fn url(&self) -> String {
+            format!("http://{}", self.addr)
+        }
+
+        /// Requests received so far, with lowercase header names.
+        pub(crate) fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> Result<String> {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let read = stream.read(&mut buf).await?;
+            if read == 0 {
+                break;
+            }
+            data.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&data);
+            let Some(head_end) = text.find("\r\n\r\n") else {
+                continue;
+            };
+            let content_length = text[..head_end]
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if data.len() >= head_end + 4 + content_length {
+                break;
+            }
+        }
+        let text = String::from_utf8_lossy(&data);
+        let (head, body) = text.split_once("\r\n\r\n").unwrap_or((&text, ""));
+        let head = head
+            .lines()
+            .map(|line| match line.split_once(':') {
+                Some((name, value)) => format!("{}:{}", name.to_ascii_lowercase(), value),
+                None => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        Ok(format!("{}\r\n\r\n{}", head, body))
+    }
+}
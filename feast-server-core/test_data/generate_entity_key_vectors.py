@@ -0,0 +1,61 @@
+"""Writes entity_key_vectors.json with entity keys serialized by the Feast Python SDK.
+
+Run with the SDK installed: python generate_entity_key_vectors.py > entity_key_vectors.json
+"""
+
+import json
+
+from feast.infra.key_encoding_utils import serialize_entity_key
+from feast.protos.feast.types.EntityKey_pb2 import EntityKey
+from feast.protos.feast.types.Value_pb2 import Value
+
+CASES = [
+    ("int64 key", ["driver_id"], [("int64_val", 1005)]),
+    ("negative int64 key", ["driver_id"], [("int64_val", -1)]),
+    ("int32 key", ["customer_id"], [("int32_val", 42)]),
+    ("string key", ["user"], [("string_val", "alice")]),
+    ("empty string key", ["user"], [("string_val", "")]),
+    ("unicode string value", ["city"], [("string_val", "Zürich 東京")]),
+    ("bytes key", ["hash"], [("bytes_val", "00ff10")]),
+    (
+        "composite key given unsorted",
+        ["driver_id", "customer_id"],
+        [("int64_val", 1001), ("string_val", "c1")],
+    ),
+    (
+        "composite key of three types",
+        ["b", "a", "c"],
+        [("int32_val", 7), ("int64_val", 9007199254740993), ("bytes_val", "cafe")],
+    ),
+    ("unicode join key", ["größe"], [("int64_val", 3)]),
+    (
+        "unicode and ascii join keys",
+        ["ключ", "id"],
+        [("string_val", "значение"), ("int64_val", 12)],
+    ),
+]
+
+
+def value(kind, raw):
+    if kind == "bytes_val":
+        return Value(bytes_val=bytes.fromhex(raw))
+    return Value(**{kind: raw})
+
+
+vectors = []
+for description, join_keys, values in CASES:
+    key = EntityKey(
+        join_keys=join_keys,
+        entity_values=[value(kind, raw) for kind, raw in values],
+    )
+    vectors.append(
+        {
+            "description": description,
+            "join_keys": join_keys,
+            "values": [{kind: raw} for kind, raw in values],
+            "serialized": serialize_entity_key(
+                key, entity_key_serialization_version=3
+            ).hex(),
+        }
+    )
+print(json.dumps(vectors, indent=2, ensure_ascii=False))
@@ -0,0 +1,18 @@
+//! Encoded `GetOnlineFeaturesRequest` messages of the gRPC server, converted and served
+//! when they decode.
+
+#![no_main]
+
+use grpc_server::proto::feast::serving::GetOnlineFeaturesRequest;
+use grpc_server::server::FeastGrpcService;
+use libfuzzer_sys::fuzz_target;
+use prost::Message;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(request) = GetOnlineFeaturesRequest::decode(data) else {
+        return;
+    };
+    if let Ok(request) = FeastGrpcService::from_request_proto(request) {
+        feast_server_fuzz::serve(request);
+    }
+});
@@ -0,0 +1,154 @@
+//! gRPC requests built from structured input, to reach entity values of every variant and
+//! huge entity lists more often than decoding random bytes does.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use feast_server_fuzz::{ENTITY, FEATURE_SERVICES, FEATURES};
+use grpc_server::proto::feast::serving::{
+    FeatureList, GetOnlineFeaturesRequest, get_online_features_request,
+};
+use grpc_server::proto::feast::types::value::Val;
+use grpc_server::proto::feast::types::{
+    BytesList, Int32List, Int64List, Null, RepeatedValue, StringList, Value,
+};
+use grpc_server::server::FeastGrpcService;
+use libfuzzer_sys::fuzz_target;
+
+/// Values of an entity past which repeated values are dropped, so that nested repetitions
+/// stay within the memory limit of the fuzzer.
+const MAX_ENTITY_VALUES: usize = 100_000;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    entities: Vec<(EntityName, Vec<EntityValue>)>,
+    kind: Kind,
+    full_feature_names: bool,
+}
+
+#[derive(Arbitrary, Debug)]
+enum EntityName {
+    Registered,
+    Other(String),
+}
+
+#[derive(Arbitrary, Debug)]
+enum EntityValue {
+    Missing,
+    Null,
+    Int32(i32),
+    Int64(i64),
+    String(String),
+    Bytes(Vec<u8>),
+    Double(f64),
+    Bool(bool),
+    UnixTimestamp(i64),
+    Int32List(Vec<i32>),
+    Int64List(Vec<i64>),
+    StringList(Vec<String>),
+    BytesList(Vec<Vec<u8>>),
+    /// A value repeated up to 65535 times, for huge entity lists.
+    Repeated(Box<EntityValue>, u16),
+}
+
+#[derive(Arbitrary, Debug)]
+enum Kind {
+    None,
+    Features(Vec<FeatureRef>),
+    FeatureService(FeatureServiceRef),
+}
+
+#[derive(Arbitrary, Debug)]
+enum FeatureRef {
+    Registered(u8),
+    Other(String),
+}
+
+#[derive(Arbitrary, Debug)]
+enum FeatureServiceRef {
+    Registered(u8),
+    Other(String),
+}
+
+impl EntityValue {
+    fn push_to(self, values: &mut Vec<Value>) {
+        let val = match self {
+            EntityValue::Repeated(value, count) => {
+                let mut repeated = Vec::new();
+                value.push_to(&mut repeated);
+                for _ in 0..count {
+                    if values.len() >= MAX_ENTITY_VALUES {
+                        break;
+                    }
+                    values.extend(repeated.iter().cloned());
+                }
+                return;
+            }
+            EntityValue::Missing => None,
+            EntityValue::Null => Some(Val::NullVal(Null::Null as i32)),
+            EntityValue::Int32(v) => Some(Val::Int32Val(v)),
+            EntityValue::Int64(v) => Some(Val::Int64Val(v)),
+            EntityValue::String(v) => Some(Val::StringVal(v)),
+            EntityValue::Bytes(v) => Some(Val::BytesVal(v)),
+            EntityValue::Double(v) => Some(Val::DoubleVal(v)),
+            EntityValue::Bool(v) => Some(Val::BoolVal(v)),
+            EntityValue::UnixTimestamp(v) => Some(Val::UnixTimestampVal(v)),
+            EntityValue::Int32List(val) => Some(Val::Int32ListVal(Int32List { val })),
+            EntityValue::Int64List(val) => Some(Val::Int64ListVal(Int64List { val })),
+            EntityValue::StringList(val) => Some(Val::StringListVal(StringList { val })),
+            EntityValue::BytesList(val) => Some(Val::BytesListVal(BytesList { val })),
+        };
+        values.push(Value { val });
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let entities = input
+        .entities
+        .into_iter()
+        .map(|(name, values)| {
+            let name = match name {
+                EntityName::Registered => ENTITY.to_string(),
+                EntityName::Other(name) => name,
+            };
+            let mut val = Vec::new();
+            for value in values {
+                value.push_to(&mut val);
+            }
+            (name, RepeatedValue { val })
+        })
+        .collect();
+    let kind = match input.kind {
+        Kind::None => None,
+        Kind::Features(features) => {
+            Some(get_online_features_request::Kind::Features(FeatureList {
+                val: features
+                    .into_iter()
+                    .map(|feature| match feature {
+                        FeatureRef::Registered(i) => {
+                            FEATURES[i as usize % FEATURES.len()].to_string()
+                        }
+                        FeatureRef::Other(name) => name,
+                    })
+                    .collect(),
+            }))
+        }
+        Kind::FeatureService(FeatureServiceRef::Registered(i)) => {
+            Some(get_online_features_request::Kind::FeatureService(
+                FEATURE_SERVICES[i as usize % FEATURE_SERVICES.len()].to_string(),
+            ))
+        }
+        Kind::FeatureService(FeatureServiceRef::Other(name)) => {
+            Some(get_online_features_request::Kind::FeatureService(name))
+        }
+    };
+    let request = GetOnlineFeaturesRequest {
+        entities,
+        full_feature_names: input.full_feature_names,
+        request_context: Default::default(),
+        kind,
+    };
+    if let Ok(request) = FeastGrpcService::from_request_proto(request) {
+        feast_server_fuzz::serve(request);
+    }
+});
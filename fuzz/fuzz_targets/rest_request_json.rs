@@ -0,0 +1,13 @@
+//! Bodies of `POST /get-online-features` as parsed by the REST server, served when they
+//! parse.
+
+#![no_main]
+
+use feast_server_core::model::GetOnlineFeaturesRequest;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(request) = serde_json::from_slice::<GetOnlineFeaturesRequest>(data) {
+        feast_server_fuzz::serve(request);
+    }
+});
@@ -0,0 +1,44 @@
+//! Harness shared by the fuzz targets: requests are served by a feature store over the
+//! test registry and an empty in-memory online store, so that entity values go through
+//! coercion and key serialization like on a running server.
+
+use feast_server_core::feature_store::FeatureStore;
+use feast_server_core::model::GetOnlineFeaturesRequest;
+use feast_server_core::onlinestore::in_memory::InMemoryOnlineStore;
+use feast_server_core::registry::FileFeatureRegistry;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
+use tokio::runtime::Runtime;
+
+/// Feature references of the test registry, for the structured targets to request.
+pub const FEATURES: &[&str] = &[
+    "driver_hourly_stats:conv_rate",
+    "driver_hourly_stats:acc_rate",
+    "driver_hourly_stats:avg_daily_trips",
+    "driver_hourly_stats_fresh:conv_rate",
+];
+/// Feature services of the test registry.
+pub const FEATURE_SERVICES: &[&str] = &["driver_activity_v1", "driver_activity_alias"];
+/// Entity name of the test registry.
+pub const ENTITY: &str = "driver_id";
+
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to start runtime")
+});
+
+static FEATURE_STORE: LazyLock<FeatureStore> = LazyLock::new(|| {
+    let registry_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../feast-server-core/test_data/registry.pb");
+    let registry =
+        FileFeatureRegistry::from_path(&registry_path).expect("Failed to read test registry");
+    FeatureStore::new(Arc::new(registry), Arc::new(InMemoryOnlineStore::new()))
+});
+
+/// Serve `request`. Errors are expected answers to malformed requests, only panics are
+/// findings.
+pub fn serve(request: GetOnlineFeaturesRequest) {
+    let _ = RUNTIME.block_on(FEATURE_STORE.get_online_features(request));
+}
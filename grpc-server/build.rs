@@ -1,6 +1,10 @@
+use std::path::PathBuf;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
     tonic_build::configure()
         .build_client(false)
+        .file_descriptor_set_path(out_dir.join("feast_serving_descriptor.bin"))
         .compile_protos(
             &["../feast-server-core/protos/feast/serving/ServingService.proto"],
             &["../feast-server-core/protos"],
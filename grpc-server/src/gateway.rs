@@ -0,0 +1,455 @@
+//! grpc-web and JSON requests on the gRPC port, translated for the gRPC services.
+//!
+//! grpc-web requests carry the framing of gRPC requests, but responses cannot rely on HTTP
+//! trailers, so the trailers are sent as a last frame of the body instead. The text variant
+//! (`application/grpc-web-text`) is the base64 encoding of the same bytes. Only unary calls
+//! are translated, which is all the serving service has. Bodies are read up to the decoding
+//! limit of the gRPC services, so that translated requests cannot grow without bound.
+
+use crate::server::{
+    FEATURE_SERVICE_VERSION_METADATA_KEY, FeastGrpcService, REGISTRY_VERSION_METADATA_KEY,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use feast_server_core::model::GetOnlineFeaturesRequest;
+use http_body_util::{BodyExt, Full, Limited};
+use tonic::body::{BoxBody, boxed};
+use tonic::codegen::http::header::{CONTENT_LENGTH, CONTENT_TYPE, HeaderMap, HeaderValue, TE};
+use tonic::codegen::http::{Method, Request, Response, StatusCode, Version};
+use tonic::codegen::{Body, BoxFuture, Bytes, Context, Poll, Service, StdError};
+use tonic::{Code, Status};
+use tower_layer::Layer;
+
+const GRPC_WEB: &str = "application/grpc-web";
+const GRPC_WEB_TEXT: &str = "application/grpc-web-text";
+const JSON: &str = "application/json";
+/// Path of the method answered for JSON requests.
+const GET_ONLINE_FEATURES_PATH: &str = "/feast.serving.ServingService/GetOnlineFeatures";
+/// Flag of the grpc-web frame holding the trailers.
+const TRAILERS_FRAME: u8 = 0x80;
+/// Length of the header of a gRPC frame: a flag byte and the message length.
+const FRAME_HEADER_BYTES: usize = 5;
+/// Default message size limit of tonic, used when none is configured.
+pub(crate) const DEFAULT_MAX_MESSAGE_BYTES: usize = 4 << 20;
+
+#[derive(Clone)]
+pub(crate) struct GatewayLayer {
+    pub(crate) grpc_web: bool,
+    /// Service answering JSON requests, `None` when they are not translated.
+    pub(crate) json: Option<FeastGrpcService>,
+    /// Largest request message read for translation.
+    pub(crate) max_message_bytes: usize,
+}
+
+impl<S> Layer<S> for GatewayLayer {
+    type Service = Gateway<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Gateway {
+            inner,
+            grpc_web: self.grpc_web,
+            json: self.json.clone(),
+            max_message_bytes: self.max_message_bytes,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Gateway<S> {
+    inner: S,
+    grpc_web: bool,
+    json: Option<FeastGrpcService>,
+    max_message_bytes: usize,
+}
+
+/// Kind of a request by its content type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Protocol {
+    Grpc,
+    GrpcWeb { text: bool },
+    Json,
+}
+
+impl Protocol {
+    fn of<B>(request: &Request<B>) -> Self {
+        let content_type = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if content_type.starts_with(GRPC_WEB_TEXT) {
+            Protocol::GrpcWeb { text: true }
+        } else if content_type.starts_with(GRPC_WEB) {
+            Protocol::GrpcWeb { text: false }
+        } else if content_type.starts_with(JSON) {
+            Protocol::Json
+        } else {
+            Protocol::Grpc
+        }
+    }
+}
+
+impl<S, B> Service<Request<BoxBody>> for Gateway<S>
+where
+    S: Service<Request<BoxBody>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send,
+    B: Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<StdError>,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<BoxBody>) -> Self::Future {
+        // The ready service is the one to call, a fresh clone takes its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let max_message_bytes = self.max_message_bytes;
+        match Protocol::of(&request) {
+            Protocol::GrpcWeb { text } if self.grpc_web => Box::pin(async move {
+                let request = match grpc_web_request(request, text, max_message_bytes).await {
+                    Ok(request) => request,
+                    Err(status) => return Ok(grpc_web_error(status, text)),
+                };
+                let response = inner.call(request).await?;
+                Ok(grpc_web_response(response, text).await)
+            }),
+            Protocol::Json
+                if request.method() == Method::POST
+                    && request.uri().path() == GET_ONLINE_FEATURES_PATH =>
+            {
+                match self.json.clone() {
+                    Some(service) => Box::pin(async move {
+                        Ok(json_response(service, request, max_message_bytes).await)
+                    }),
+                    None => Box::pin(async move { Ok(inner.call(request).await?.map(boxed)) }),
+                }
+            }
+            _ => Box::pin(async move { Ok(inner.call(request).await?.map(boxed)) }),
+        }
+    }
+}
+
+/// Body of `body` when it holds at most `limit` bytes.
+async fn read_body(body: BoxBody, limit: usize) -> Result<Bytes, Status> {
+    match Limited::new(body, limit).collect().await {
+        Ok(collected) => Ok(collected.to_bytes()),
+        Err(err) => match err.downcast::<http_body_util::LengthLimitError>() {
+            Ok(_) => Err(Status::resource_exhausted(format!(
+                "Request body is larger than {} bytes",
+                limit
+            ))),
+            Err(err) => Err(match err.downcast::<Status>() {
+                Ok(status) => *status,
+                Err(err) => Status::internal(format!("Failed to read request: {}", err)),
+            }),
+        },
+    }
+}
+
+/// Native gRPC request of a grpc-web request.
+async fn grpc_web_request(
+    request: Request<BoxBody>,
+    text: bool,
+    max_message_bytes: usize,
+) -> Result<Request<BoxBody>, Status> {
+    let (mut parts, body) = request.into_parts();
+    let mut limit = max_message_bytes.saturating_add(FRAME_HEADER_BYTES);
+    if text {
+        // Base64 takes 4 bytes for every 3
+        limit = limit.div_ceil(3).saturating_mul(4);
+    }
+    let mut data = read_body(body, limit).await?;
+    if text {
+        data = BASE64
+            .decode(&data)
+            .map(Bytes::from)
+            .map_err(|err| Status::invalid_argument(format!("Invalid base64 body: {}", err)))?;
+    }
+    parts.version = Version::HTTP_2;
+    parts.headers.remove(CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+    parts
+        .headers
+        .insert(TE, HeaderValue::from_static("trailers"));
+    Ok(Request::from_parts(parts, boxed(Full::new(data))))
+}
+
+/// grpc-web response of a native gRPC response, with its trailers as the last frame.
+async fn grpc_web_response<B>(response: Response<B>, text: bool) -> Response<BoxBody>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<StdError>,
+{
+    let (mut parts, body) = response.into_parts();
+    let collected = match body.collect().await {
+        Ok(collected) => collected,
+        Err(err) => {
+            let err: StdError = err.into();
+            tracing::error!("Failed to read gRPC response: {}", err);
+            return grpc_web_error(Status::internal("failed to read response"), text);
+        }
+    };
+    let trailers = collected.trailers().cloned().unwrap_or_default();
+    let mut data = collected.to_bytes().to_vec();
+    let encoded_trailers = encode_trailers(&trailers);
+    data.push(TRAILERS_FRAME);
+    data.extend_from_slice(&(encoded_trailers.len() as u32).to_be_bytes());
+    data.extend_from_slice(&encoded_trailers);
+    set_grpc_web_headers(&mut parts.headers, text);
+    Response::from_parts(parts, boxed(Full::new(web_body(data, text))))
+}
+
+/// Trailers-only grpc-web response of `status`.
+fn grpc_web_error(status: Status, text: bool) -> Response<BoxBody> {
+    let mut response = status.into_http();
+    set_grpc_web_headers(response.headers_mut(), text);
+    response
+}
+
+fn set_grpc_web_headers(headers: &mut HeaderMap, text: bool) {
+    let content_type = if text {
+        "application/grpc-web-text+proto"
+    } else {
+        "application/grpc-web+proto"
+    };
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    headers.remove(CONTENT_LENGTH);
+}
+
+fn web_body(data: Vec<u8>, text: bool) -> Bytes {
+    if text {
+        Bytes::from(BASE64.encode(data))
+    } else {
+        Bytes::from(data)
+    }
+}
+
+/// Trailers as the HTTP/1 header lines of a grpc-web trailers frame.
+fn encode_trailers(trailers: &HeaderMap) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for (name, value) in trailers {
+        encoded.extend_from_slice(name.as_str().as_bytes());
+        encoded.extend_from_slice(b":");
+        encoded.extend_from_slice(value.as_bytes());
+        encoded.extend_from_slice(b"\r\n");
+    }
+    encoded
+}
+
+/// Answer a JSON `GetOnlineFeatures` request with the bodies of the REST server.
+async fn json_response(
+    service: FeastGrpcService,
+    request: Request<BoxBody>,
+    max_message_bytes: usize,
+) -> Response<BoxBody> {
+    let body = match read_body(request.into_body(), max_message_bytes).await {
+        Ok(body) => body,
+        Err(status) => return json_error(&status),
+    };
+    let request: GetOnlineFeaturesRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(err) => return json_error(&Status::invalid_argument(err.to_string())),
+    };
+    let response = match service.online_features(request).await {
+        Ok(response) => response,
+        Err(status) => return json_error(&status),
+    };
+    let mut headers = HeaderMap::new();
+    if let Some(version) = response
+        .feature_service_version
+        .as_deref()
+        .and_then(|version| HeaderValue::from_str(version).ok())
+    {
+        headers.insert(FEATURE_SERVICE_VERSION_METADATA_KEY, version);
+    }
+    if let Some(version) = response.registry_version {
+        headers.insert(REGISTRY_VERSION_METADATA_KEY, HeaderValue::from(version));
+    }
+    match serde_json::to_vec(&response) {
+        Ok(body) => {
+            let mut response = json(StatusCode::OK, body);
+            response.headers_mut().extend(headers);
+            response
+        }
+        Err(err) => {
+            tracing::error!("Failed to encode JSON response: {}", err);
+            json_error(&Status::internal("failed to encode response"))
+        }
+    }
+}
+
+fn json_error(status: &Status) -> Response<BoxBody> {
+    let code = match status.code() {
+        Code::InvalidArgument => StatusCode::BAD_REQUEST,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::ResourceExhausted => StatusCode::PAYLOAD_TOO_LARGE,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = serde_json::json!({ "message": status.message() }).to_string();
+    json(code, body.into_bytes())
+}
+
+fn json(code: StatusCode, body: Vec<u8>) -> Response<BoxBody> {
+    let mut response = Response::new(boxed(Full::new(Bytes::from(body))));
+    *response.status_mut() = code;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(JSON));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::feast::serving::serving_service_server::ServingServiceServer;
+    use feast_server_core::feature_store::FeatureStore;
+    use feast_server_core::onlinestore::in_memory::InMemoryOnlineStore;
+    use feast_server_core::registry::FileFeatureRegistry;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn gateway() -> Gateway<ServingServiceServer<FeastGrpcService>> {
+        let registry_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../feast-server-core/test_data/registry.pb");
+        let registry = Arc::new(FileFeatureRegistry::from_path(&registry_path).unwrap());
+        let feature_store = FeatureStore::new(registry, Arc::new(InMemoryOnlineStore::new()));
+        let service = FeastGrpcService::new(feature_store);
+        GatewayLayer {
+            grpc_web: true,
+            json: Some(service.clone()),
+            max_message_bytes: 256,
+        }
+        .layer(ServingServiceServer::new(service))
+    }
+
+    async fn call(
+        content_type: &str,
+        path: &str,
+        body: impl Into<Bytes>,
+    ) -> (StatusCode, HeaderMap, Bytes) {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(path)
+            .version(Version::HTTP_11)
+            .header(CONTENT_TYPE, content_type)
+            .body(boxed(Full::new(body.into())))
+            .unwrap();
+        let response = gateway().call(request).await.unwrap();
+        let (parts, body) = response.into_parts();
+        let body = body.collect().await.unwrap().to_bytes();
+        (parts.status, parts.headers, body)
+    }
+
+    #[tokio::test]
+    async fn grpc_web_responses_end_with_trailers() {
+        // Empty GetFeastServingInfoRequest message
+        let message = vec![0u8, 0, 0, 0, 0];
+        let path = "/feast.serving.ServingService/GetFeastServingInfo";
+        let (status, headers, body) =
+            call("application/grpc-web+proto", path, message.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[CONTENT_TYPE], "application/grpc-web+proto");
+        let trailers_at = 5 + u32::from_be_bytes(body[1..5].try_into().unwrap()) as usize;
+        assert_eq!(body[0], 0);
+        assert_eq!(body[trailers_at], TRAILERS_FRAME);
+        let trailers = String::from_utf8_lossy(&body[trailers_at + 5..]);
+        assert!(trailers.contains("grpc-status:0\r\n"), "{}", trailers);
+
+        let (_, headers, text_body) =
+            call("application/grpc-web-text", path, BASE64.encode(message)).await;
+        assert_eq!(headers[CONTENT_TYPE], "application/grpc-web-text+proto");
+        assert_eq!(BASE64.decode(&text_body).unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn json_requests_use_rest_bodies() {
+        let request = serde_json::json!({
+            "entities": {"driver_id": [1005]},
+            "features": ["driver_hourly_stats:acc_rate"],
+        });
+        let (status, _, body) = call(
+            "application/json",
+            GET_ONLINE_FEATURES_PATH,
+            request.to_string(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["metadata"]["feature_names"],
+            serde_json::json!(["driver_id", "acc_rate"])
+        );
+
+        let request = serde_json::json!({"entities": "driver_id"});
+        let (status, _, _) = call(
+            "application/json",
+            GET_ONLINE_FEATURES_PATH,
+            request.to_string(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn oversized_bodies_are_rejected() {
+        let path = "/feast.serving.ServingService/GetFeastServingInfo";
+        let (status, headers, body) =
+            call("application/grpc-web+proto", path, vec![0u8; 300]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_empty());
+        assert_eq!(headers["grpc-status"], "8", "RESOURCE_EXHAUSTED");
+
+        let (status, _, _) = call(
+            "application/json",
+            GET_ONLINE_FEATURES_PATH,
+            format!(
+                "{{\"entities\": {{\"driver_id\": [{}]}}}}",
+                "1,".repeat(150) + "1"
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn trailers_are_encoded_as_header_lines() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", HeaderValue::from_static("0"));
+        trailers.insert("grpc-message", HeaderValue::from_static("ok"));
+        let encoded = String::from_utf8(encode_trailers(&trailers)).unwrap();
+        assert!(encoded.contains("grpc-status:0\r\n"));
+        assert!(encoded.contains("grpc-message:ok\r\n"));
+    }
+
+    #[test]
+    fn protocol_follows_content_type() {
+        let request = |content_type: &str| {
+            Request::builder()
+                .header(CONTENT_TYPE, content_type)
+                .body(())
+                .unwrap()
+        };
+        assert_eq!(Protocol::of(&request("application/grpc")), Protocol::Grpc);
+        assert_eq!(
+            Protocol::of(&request("application/grpc-web+proto")),
+            Protocol::GrpcWeb { text: false }
+        );
+        assert_eq!(
+            Protocol::of(&request("application/grpc-web-text")),
+            Protocol::GrpcWeb { text: true }
+        );
+        assert_eq!(
+            Protocol::of(&request("application/json; charset=utf-8")),
+            Protocol::Json
+        );
+    }
+}
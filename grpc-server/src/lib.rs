@@ -1,6 +1,11 @@
+mod gateway;
 pub mod server;
 
 pub mod proto {
+    /// Encoded descriptors of the serving protos, served by the reflection service.
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("feast_serving_descriptor");
+
     pub mod feast {
         pub mod serving {
             tonic::include_proto!("feast.serving");
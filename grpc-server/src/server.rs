@@ -1,3 +1,4 @@
+use crate::gateway::{DEFAULT_MAX_MESSAGE_BYTES, GatewayLayer};
 use crate::proto::feast::serving::serving_service_server::{ServingService, ServingServiceServer};
 use crate::proto::feast::serving::{
     FeatureList, GetFeastServingInfoRequest, GetFeastServingInfoResponse,
@@ -11,44 +12,160 @@ use crate::proto::feast::types::{
 };
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
+use feast_server_core::config::{GrpcServerTuning, LoadSheddingConfig};
+use feast_server_core::error::FeastCoreError;
 use feast_server_core::feast::types::{
     BoolList as CoreBoolList, BytesList as CoreBytesList, DoubleList as CoreDoubleList,
     FloatList as CoreFloatList, Int32List as CoreInt32List, Int64List as CoreInt64List,
     StringList as CoreStringList, Value as CoreValue, value::Val as CoreVal,
 };
 use feast_server_core::feature_store::FeatureStore;
+use feast_server_core::load_shedding::LoadShedder;
 use feast_server_core::model::{
     EntityIdValue, FeatureResults, FeatureStatus, GetOnlineFeatureResponse,
-    GetOnlineFeaturesRequest, ValueWrapper,
+    GetOnlineFeaturesRequest, OrderedMap, ValueWrapper,
 };
+use feast_server_core::payload_sizes;
+use prost::Message;
 use prost_types::Timestamp;
-use rustc_hash::FxHashMap as HashMap;
-use std::net::{SocketAddr, ToSocketAddrs};
+use server_listener::{ListenConfig, ServerListener, open_listeners};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
+use tokio::task::JoinHandle;
+#[cfg(unix)]
+use tokio_stream::wrappers::UnixListenerStream;
+use tokio_stream::{StreamExt, StreamMap};
 use tonic::Status as TonicStatus;
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::server::NamedService;
+use tonic::transport::server::TcpIncoming;
 use tonic::transport::{Identity, Server, ServerTlsConfig};
 use tonic::{Request, Response};
+use tonic_health::ServingStatus;
+use tonic_health::pb::health_server::{Health, HealthServer};
+use tonic_health::server::HealthReporter;
 
 type GrpcStatus = Box<TonicStatus>;
 
+/// Interval between the readiness checks reported by the health service.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Time after which a readiness check counts as failed.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Response metadata key carrying each warning of a partial response.
+const WARNING_METADATA_KEY: &str = "x-feast-warning";
+/// Response metadata key carrying the version of the requested feature service.
+pub(crate) const FEATURE_SERVICE_VERSION_METADATA_KEY: &str = "x-feast-feature-service-version";
+/// Request and response metadata key of the registry version, which requests can pin.
+pub(crate) const REGISTRY_VERSION_METADATA_KEY: &str = "x-feast-registry-version";
+/// Request metadata keys of the response field flags, which the serving proto has no
+/// fields for.
+const INCLUDE_ENTITY_VALUES_METADATA_KEY: &str = "x-feast-include-entity-values";
+const INCLUDE_STATUSES_METADATA_KEY: &str = "x-feast-include-statuses";
+const INCLUDE_TIMESTAMPS_METADATA_KEY: &str = "x-feast-include-timestamps";
+const INCLUDE_CREATED_TIMESTAMPS_METADATA_KEY: &str = "x-feast-include-created-timestamps";
+/// Response metadata key carrying the created timestamps of requests asking for them, as a
+/// JSON list per column.
+const CREATED_TIMESTAMPS_METADATA_KEY: &str = "x-feast-created-timestamps";
+const DEBUG_METADATA_KEY: &str = "x-feast-debug";
+/// Request metadata key selecting the project to read instead of the configured one.
+const PROJECT_METADATA_KEY: &str = "x-feast-project";
+/// Response metadata key carrying the timings of requests asking for them, as JSON.
+const DEBUG_INFO_METADATA_KEY: &str = "x-feast-debug-info";
+
 #[derive(Clone)]
 pub struct FeastGrpcService {
     feature_store: Arc<FeatureStore>,
+    load_shedder: Option<Arc<LoadShedder>>,
+    collapse_requests: bool,
+    max_entity_rows: Option<usize>,
 }
 
 impl FeastGrpcService {
-    pub fn new(feature_store: FeatureStore) -> Self {
+    pub fn new(feature_store: impl Into<Arc<FeatureStore>>) -> Self {
         Self {
-            feature_store: Arc::new(feature_store),
+            feature_store: feature_store.into(),
+            load_shedder: None,
+            collapse_requests: false,
+            max_entity_rows: None,
         }
     }
 
-    fn from_request_proto(
+    /// Answer `UNAVAILABLE` to feature requests arriving while the server is overloaded.
+    pub fn with_load_shedding(mut self, config: LoadSheddingConfig) -> Self {
+        self.load_shedder = Some(Arc::new(LoadShedder::new(config)));
+        self
+    }
+
+    /// Answer requests identical to one in flight with its response.
+    pub fn with_request_collapsing(mut self, collapse_requests: bool) -> Self {
+        self.collapse_requests = collapse_requests;
+        self
+    }
+
+    /// Answer `INVALID_ARGUMENT` to feature requests with more entity rows than
+    /// `max_entity_rows`, before their response grows too large to be encoded.
+    pub fn with_max_entity_rows(mut self, max_entity_rows: Option<usize>) -> Self {
+        self.max_entity_rows = max_entity_rows;
+        self
+    }
+
+    /// Feature values of a translated request, shedding load first when configured.
+    pub(crate) async fn online_features(
+        &self,
+        request: GetOnlineFeaturesRequest,
+    ) -> Result<GetOnlineFeatureResponse, TonicStatus> {
+        if let Some(max_entity_rows) = self.max_entity_rows {
+            let rows = request.entities.values().map(Vec::len).max().unwrap_or(0);
+            if rows > max_entity_rows {
+                return Err(TonicStatus::invalid_argument(format!(
+                    "Request has {} entity rows, more than the {} allowed",
+                    rows, max_entity_rows
+                )));
+            }
+        }
+        let _in_flight = match &self.load_shedder {
+            Some(shedder) => Some(
+                shedder
+                    .try_acquire("GetOnlineFeatures")
+                    .ok_or_else(|| TonicStatus::unavailable("server is overloaded"))?,
+            ),
+            None => None,
+        };
+        let response = if self.collapse_requests {
+            self.feature_store
+                .get_online_features_collapsed(request)
+                .await
+        } else {
+            self.feature_store.get_online_features(request).await
+        };
+        response.map_err(|err| {
+            if let Some(feast_error) = err.downcast_ref::<FeastCoreError>() {
+                if feast_error.is_invalid_request() {
+                    return TonicStatus::invalid_argument(feast_error.to_string());
+                }
+                if feast_error.is_not_found() {
+                    return TonicStatus::not_found(feast_error.to_string());
+                }
+                if feast_error.is_unavailable() {
+                    return TonicStatus::unavailable(feast_error.to_string());
+                }
+            }
+            tracing::error!(error = ?err, "Failed to retrieve online features");
+            TonicStatus::internal("failed to retrieve online features")
+        })
+    }
+
+    /// Core request of a serving proto request, failing with `INVALID_ARGUMENT` for entity
+    /// values which cannot identify entities.
+    pub fn from_request_proto(
         request: GrpcGetOnlineFeaturesRequest,
     ) -> Result<GetOnlineFeaturesRequest, GrpcStatus> {
-        let mut entities: HashMap<String, Vec<EntityIdValue>> = HashMap::default();
-        for (entity_name, values) in request.entities {
+        // Protobuf maps carry no order, so entity columns are returned sorted by name.
+        let mut request_entities: Vec<_> = request.entities.into_iter().collect();
+        request_entities.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        let mut entities: OrderedMap<String, Vec<EntityIdValue>> = OrderedMap::default();
+        for (entity_name, values) in request_entities {
             entities.insert(
                 entity_name.clone(),
                 repeated_value_to_entity_ids(entity_name.as_str(), values)?,
@@ -70,6 +187,7 @@ impl FeastGrpcService {
             feature_service,
             features,
             full_feature_names: Some(request.full_feature_names),
+            ..Default::default()
         })
     }
 
@@ -111,49 +229,252 @@ impl ServingService for FeastGrpcService {
         &self,
         request: Request<GrpcGetOnlineFeaturesRequest>,
     ) -> Result<Response<GetOnlineFeaturesResponse>, TonicStatus> {
+        let metadata = request.metadata();
+        let include_entity_values = metadata_flag(metadata, INCLUDE_ENTITY_VALUES_METADATA_KEY)
+            .map_err(|status| *status)?;
+        let include_statuses =
+            metadata_flag(metadata, INCLUDE_STATUSES_METADATA_KEY).map_err(|status| *status)?;
+        let include_timestamps =
+            metadata_flag(metadata, INCLUDE_TIMESTAMPS_METADATA_KEY).map_err(|status| *status)?;
+        let include_created_timestamps =
+            metadata_flag(metadata, INCLUDE_CREATED_TIMESTAMPS_METADATA_KEY)
+                .map_err(|status| *status)?;
+        let registry_version =
+            metadata_version(metadata, REGISTRY_VERSION_METADATA_KEY).map_err(|status| *status)?;
+        let debug = metadata_flag(metadata, DEBUG_METADATA_KEY).map_err(|status| *status)?;
+        let project = metadata_string(metadata, PROJECT_METADATA_KEY).map_err(|status| *status)?;
         let inner = request.into_inner();
-        let translated_request = Self::from_request_proto(inner).map_err(|status| *status)?;
-        let response = self
-            .feature_store
-            .get_online_features(translated_request)
-            .await
-            .map_err(|err| {
-                tracing::error!(error = ?err, "Failed to retrieve online features");
-                TonicStatus::internal("failed to retrieve online features")
-            })?;
+        let request_bytes = inner.encoded_len() as u64;
+        let translated_request = GetOnlineFeaturesRequest {
+            include_entity_values,
+            include_statuses,
+            include_timestamps,
+            include_created_timestamps,
+            registry_version,
+            debug,
+            project,
+            ..Self::from_request_proto(inner).map_err(|status| *status)?
+        };
+        let feature_service = translated_request.feature_service.clone();
+        let response = self.online_features(translated_request).await?;
+        let warnings = response.warnings.clone();
+        let version = response.feature_service_version.clone();
+        let registry_version = response.registry_version;
+        let debug_info = response
+            .debug
+            .as_ref()
+            .and_then(|debug| serde_json::to_string(debug).ok());
+        let created_timestamps = created_timestamps_metadata(&response);
         let response = Self::to_response_proto(response).map_err(|status| *status)?;
-        Ok(Response::new(response))
+        payload_sizes::record(
+            "GetOnlineFeatures",
+            feature_service.as_deref(),
+            Some(request_bytes),
+            response.encoded_len() as u64,
+        );
+        let mut response = Response::new(response);
+        if let Some(version) = version.and_then(|version| MetadataValue::try_from(version).ok()) {
+            response
+                .metadata_mut()
+                .insert(FEATURE_SERVICE_VERSION_METADATA_KEY, version);
+        }
+        if let Some(version) = registry_version {
+            response
+                .metadata_mut()
+                .insert(REGISTRY_VERSION_METADATA_KEY, MetadataValue::from(version));
+        }
+        if let Some(value) = created_timestamps.and_then(|json| MetadataValue::try_from(json).ok())
+        {
+            response
+                .metadata_mut()
+                .insert(CREATED_TIMESTAMPS_METADATA_KEY, value);
+        }
+        if let Some(value) = debug_info.and_then(|info| MetadataValue::try_from(info).ok()) {
+            response
+                .metadata_mut()
+                .insert(DEBUG_INFO_METADATA_KEY, value);
+        }
+        // The serving proto has no warnings field, so they are sent as response metadata
+        for warning in warnings {
+            if let Ok(value) = MetadataValue::try_from(warning.as_str()) {
+                response.metadata_mut().append(WARNING_METADATA_KEY, value);
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// Created timestamps of every column as JSON, `None` unless the request asked for them.
+/// The serving proto has no field for them.
+fn created_timestamps_metadata(response: &GetOnlineFeatureResponse) -> Option<String> {
+    if response
+        .results
+        .iter()
+        .all(|results| results.created_timestamps.is_empty())
+    {
+        return None;
+    }
+    let columns: Vec<_> = response
+        .results
+        .iter()
+        .map(|results| &results.created_timestamps)
+        .collect();
+    serde_json::to_string(&columns).ok()
+}
+
+/// Boolean flag sent as request metadata, `None` when absent.
+fn metadata_flag(metadata: &MetadataMap, key: &str) -> Result<Option<bool>, GrpcStatus> {
+    let Some(value) = metadata.get(key) else {
+        return Ok(None);
+    };
+    match value.to_str().map(str::to_ascii_lowercase).as_deref() {
+        Ok("true") => Ok(Some(true)),
+        Ok("false") => Ok(Some(false)),
+        _ => Err(Box::new(TonicStatus::invalid_argument(format!(
+            "{} must be true or false",
+            key
+        )))),
+    }
+}
+
+/// Unsigned integer sent as request metadata, `None` when absent.
+fn metadata_version(metadata: &MetadataMap, key: &str) -> Result<Option<u64>, GrpcStatus> {
+    let Some(value) = metadata.get(key) else {
+        return Ok(None);
+    };
+    match value.to_str().ok().and_then(|value| value.parse().ok()) {
+        Some(version) => Ok(Some(version)),
+        None => Err(Box::new(TonicStatus::invalid_argument(format!(
+            "{} must be an unsigned integer",
+            key
+        )))),
+    }
+}
+
+/// String sent as request metadata, `None` when absent.
+fn metadata_string(metadata: &MetadataMap, key: &str) -> Result<Option<String>, GrpcStatus> {
+    let Some(value) = metadata.get(key) else {
+        return Ok(None);
+    };
+    match value.to_str() {
+        Ok(value) => Ok(Some(value.to_string())),
+        Err(_) => Err(Box::new(TonicStatus::invalid_argument(format!(
+            "{} must be a valid string",
+            key
+        )))),
     }
 }
 
 pub struct ServerConfig {
     pub host: String,
+    /// Hosts listened on besides `host`, on the same port.
+    pub additional_hosts: Vec<String>,
+    /// Also listen on the IPv6 counterpart of IPv4 wildcard and loopback hosts, and the
+    /// other way around.
+    pub dual_stack: bool,
     pub port: u16,
     pub tls_enabled: bool,
     pub tls_cert_path: Option<String>,
     pub tls_key_path: Option<String>,
+    /// Maximum number of pending connections in the listen queue.
+    pub backlog: u32,
+    /// Serve on this Unix domain socket instead of `host` and `port`.
+    pub unix_socket_path: Option<String>,
+    /// HTTP/2 flow control and keep-alive settings.
+    pub tuning: GrpcServerTuning,
+    /// Reject feature requests early when the server is overloaded.
+    pub load_shedding: Option<LoadSheddingConfig>,
+    /// Also serve grpc-web requests, over HTTP/1.1 as well, for browser clients.
+    pub grpc_web: bool,
+    /// Also answer JSON `GetOnlineFeatures` requests, with the bodies of the REST server.
+    pub json_transcoding: bool,
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
             host: "127.0.0.1".to_string(),
+            additional_hosts: Vec::new(),
+            dual_stack: false,
             port: 6567,
             tls_enabled: false,
             tls_cert_path: None,
             tls_key_path: None,
+            backlog: 1024,
+            unix_socket_path: None,
+            tuning: GrpcServerTuning::default(),
+            load_shedding: None,
+            grpc_web: false,
+            json_transcoding: false,
         }
     }
 }
 
-pub async fn start_server(server_config: ServerConfig, feature_store: FeatureStore) -> Result<()> {
-    let addr: SocketAddr = format!("{}:{}", server_config.host, server_config.port)
-        .to_socket_addrs()?
-        .next()
-        .ok_or_else(|| anyhow!("Cannot resolve host"))?;
+/// Serve `feature_store` until the future is dropped. Callers keeping a handle on the
+/// store shut it down afterwards.
+pub async fn start_server(
+    server_config: ServerConfig,
+    feature_store: impl Into<Arc<FeatureStore>>,
+) -> Result<()> {
+    start_server_with_shutdown(server_config, feature_store, std::future::pending()).await
+}
+
+/// Serve `feature_store` until `shutdown` completes, then stop accepting connections and
+/// return once the requests in flight are answered.
+pub async fn start_server_with_shutdown(
+    server_config: ServerConfig,
+    feature_store: impl Into<Arc<FeatureStore>>,
+    shutdown: impl Future<Output = ()> + Send,
+) -> Result<()> {
+    let listeners = open_listeners(&ListenConfig {
+        host: &server_config.host,
+        additional_hosts: &server_config.additional_hosts,
+        dual_stack: server_config.dual_stack,
+        port: server_config.port,
+        backlog: server_config.backlog,
+        unix_socket_path: server_config.unix_socket_path.as_deref(),
+    })?;
 
-    let service = FeastGrpcService::new(feature_store);
-    let mut builder = Server::builder();
+    let mut service = FeastGrpcService::new(feature_store)
+        .with_request_collapsing(server_config.tuning.collapse_identical_requests)
+        .with_max_entity_rows(server_config.tuning.max_entity_rows);
+    if let Some(config) = server_config.load_shedding.clone() {
+        service = service.with_load_shedding(config);
+    }
+    let (health_service, health_task) = health_service(service.feature_store.clone()).await;
+    let reflection_v1 = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(crate::proto::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build_v1()?;
+    // Older grpcurl and grpcui releases only speak the v1alpha reflection protocol.
+    let reflection_v1alpha = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(crate::proto::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build_v1alpha()?;
+    let tuning = &server_config.tuning;
+    let gateway = GatewayLayer {
+        grpc_web: server_config.grpc_web,
+        json: server_config.json_transcoding.then(|| service.clone()),
+        max_message_bytes: tuning
+            .max_decoding_message_size
+            .unwrap_or(DEFAULT_MAX_MESSAGE_BYTES),
+    };
+    let mut builder = Server::builder()
+        .accept_http1(server_config.grpc_web || server_config.json_transcoding)
+        .max_concurrent_streams(tuning.max_concurrent_streams)
+        .initial_stream_window_size(tuning.initial_stream_window_size)
+        .initial_connection_window_size(tuning.initial_connection_window_size)
+        .max_frame_size(tuning.max_frame_size)
+        .http2_keepalive_interval(
+            tuning
+                .http2_keep_alive_interval_secs
+                .map(Duration::from_secs),
+        )
+        .http2_keepalive_timeout(
+            tuning
+                .http2_keep_alive_timeout_secs
+                .map(Duration::from_secs),
+        );
 
     if server_config.tls_enabled {
         let cert_path = server_config
@@ -170,17 +491,108 @@ pub async fn start_server(server_config: ServerConfig, feature_store: FeatureSto
             .map_err(|err| anyhow!("Failed to configure TLS: {}", err))?;
     }
 
-    tracing::info!(
-        "gRPC server listening on {}:{}",
-        server_config.host,
-        server_config.port
-    );
+    let mut serving_service = ServingServiceServer::new(service);
+    if let Some(limit) = tuning.max_decoding_message_size {
+        serving_service = serving_service.max_decoding_message_size(limit);
+    }
+    if let Some(limit) = tuning.max_encoding_message_size {
+        serving_service = serving_service.max_encoding_message_size(limit);
+    }
+    let router = builder
+        .layer(gateway)
+        .add_service(serving_service)
+        .add_service(health_service)
+        .add_service(reflection_v1)
+        .add_service(reflection_v1alpha);
+    let mut listeners = listeners;
+    let served = match listeners.pop() {
+        #[cfg(unix)]
+        Some(ServerListener::Unix(listener)) => {
+            tracing::info!("gRPC server listening on {:?}", listener.local_addr()?);
+            let incoming = UnixListenerStream::new(tokio::net::UnixListener::from_std(listener)?);
+            router
+                .serve_with_incoming_shutdown(incoming, shutdown)
+                .await
+        }
+        last => {
+            // Connections of all addresses are served by the same router
+            let mut incoming = StreamMap::new();
+            for (index, listener) in listeners.into_iter().chain(last).enumerate() {
+                let listener = match listener {
+                    ServerListener::Tcp(listener) => listener,
+                    #[cfg(unix)]
+                    ServerListener::Unix(_) => {
+                        return Err(anyhow!("Unix domain sockets cannot be mixed with TCP"));
+                    }
+                };
+                tracing::info!("gRPC server listening on {}", listener.local_addr()?);
+                let listener_incoming = TcpIncoming::from_listener(
+                    tokio::net::TcpListener::from_std(listener)?,
+                    server_config.tuning.tcp_nodelay,
+                    server_config
+                        .tuning
+                        .tcp_keepalive_secs
+                        .map(Duration::from_secs),
+                )
+                .map_err(|err| anyhow!("Failed to accept connections: {}", err))?;
+                incoming.insert(index, listener_incoming);
+            }
+            router
+                .serve_with_incoming_shutdown(incoming.map(|(_, connection)| connection), shutdown)
+                .await
+        }
+    };
+    health_task.abort();
+    served.map_err(|err| anyhow!("Failed to start gRPC server: {}", err))
+}
+
+/// Standard gRPC health service reporting the readiness of `feature_store` for the serving
+/// service and for the server as a whole. The returned task refreshes the status until it
+/// is aborted; both are reported as not serving until the first check passes.
+async fn health_service(
+    feature_store: Arc<FeatureStore>,
+) -> (HealthServer<impl Health>, JoinHandle<()>) {
+    let (mut reporter, service) = tonic_health::server::health_reporter();
+    set_health_status(&mut reporter, ServingStatus::NotServing).await;
+    let task = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+        let mut reported = ServingStatus::NotServing;
+        loop {
+            interval.tick().await;
+            let status = match tokio::time::timeout(
+                HEALTH_CHECK_TIMEOUT,
+                feature_store.health_check(),
+            )
+            .await
+            {
+                Ok(Ok(())) => ServingStatus::Serving,
+                Ok(Err(err)) => {
+                    tracing::warn!("Health check failed: {:#}", err);
+                    ServingStatus::NotServing
+                }
+                Err(_) => {
+                    tracing::warn!("Health check timed out after {:?}", HEALTH_CHECK_TIMEOUT);
+                    ServingStatus::NotServing
+                }
+            };
+            if status != reported {
+                tracing::info!("gRPC health status changed to {:?}", status);
+                set_health_status(&mut reporter, status).await;
+                reported = status;
+            }
+        }
+    });
+    (service, task)
+}
 
-    builder
-        .add_service(ServingServiceServer::new(service))
-        .serve(addr)
-        .await
-        .map_err(|err| anyhow!("Failed to start gRPC server: {}", err))
+async fn set_health_status(reporter: &mut HealthReporter, status: ServingStatus) {
+    reporter.set_service_status("", status).await;
+    reporter
+        .set_service_status(
+            <ServingServiceServer<FeastGrpcService> as NamedService>::NAME,
+            status,
+        )
+        .await;
 }
 
 fn repeated_value_to_entity_ids(
@@ -241,6 +653,7 @@ fn map_status_to_proto(status: FeatureStatus) -> i32 {
         FeatureStatus::NullValue => 2,
         FeatureStatus::NotFound => 3,
         FeatureStatus::OutsideMaxAge => 4,
+        FeatureStatus::DefaultValue => 5,
     }
 }
 
@@ -352,6 +765,246 @@ fn core_value_to_grpc(value: CoreValue) -> Result<grpc_types::Value, GrpcStatus>
 mod tests {
     use super::*;
     use chrono::TimeZone;
+    use feast_server_core::model::{Feature, HashEntityKey};
+    use feast_server_core::onlinestore::in_memory::InMemoryOnlineStore;
+    use feast_server_core::onlinestore::{OnlineStore, OnlineStoreRow};
+    use feast_server_core::registry::FileFeatureRegistry;
+    use rustc_hash::FxHashMap as HashMap;
+    use std::path::PathBuf;
+    use tonic_health::pb::HealthCheckRequest;
+    use tonic_health::pb::health_client::HealthClient;
+
+    struct UnreachableStore;
+
+    #[tonic::async_trait]
+    impl OnlineStore for UnreachableStore {
+        async fn get_feature_values(
+            &self,
+            _features: HashMap<HashEntityKey, Vec<Feature>>,
+        ) -> Result<Vec<OnlineStoreRow>> {
+            Err(anyhow!("Connection refused"))
+        }
+
+        async fn health_check(&self) -> Result<()> {
+            Err(anyhow!("Connection refused"))
+        }
+    }
+
+    /// Serve the health service of a feature store over `online_store` and return the status
+    /// of the serving service once it is reported as serving, or after a few checks.
+    async fn reported_status(online_store: Arc<dyn OnlineStore>) -> Result<i32> {
+        let registry_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../feast-server-core/test_data/registry.pb");
+        let registry = Arc::new(FileFeatureRegistry::from_path(&registry_path)?);
+        let feature_store = Arc::new(FeatureStore::new(registry, online_store));
+        let (service, health_task) = health_service(feature_store).await;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let incoming = TcpIncoming::from_listener(listener, true, None)
+            .map_err(|err| anyhow!("Failed to accept connections: {}", err))?;
+        let server = tokio::spawn(
+            Server::builder()
+                .add_service(service)
+                .serve_with_incoming(incoming),
+        );
+
+        let channel = tonic::transport::Channel::from_shared(format!("http://{}", addr))?
+            .connect()
+            .await?;
+        let mut client = HealthClient::new(channel);
+        let mut status = ServingStatus::Unknown as i32;
+        for _ in 0..20 {
+            status = client
+                .check(HealthCheckRequest {
+                    service: "feast.serving.ServingService".to_string(),
+                })
+                .await?
+                .into_inner()
+                .status;
+            if status == ServingStatus::Serving as i32 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        health_task.abort();
+        server.abort();
+        Ok(status)
+    }
+
+    #[tokio::test]
+    async fn health_follows_online_store() -> Result<()> {
+        assert_eq!(
+            reported_status(Arc::new(InMemoryOnlineStore::new())).await?,
+            ServingStatus::Serving as i32
+        );
+        assert_eq!(
+            reported_status(Arc::new(UnreachableStore)).await?,
+            ServingStatus::NotServing as i32
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn overloaded_service_is_unavailable() -> Result<()> {
+        let registry_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../feast-server-core/test_data/registry.pb");
+        let registry = Arc::new(FileFeatureRegistry::from_path(&registry_path)?);
+        let feature_store = FeatureStore::new(registry, Arc::new(InMemoryOnlineStore::new()));
+        let service = FeastGrpcService::new(feature_store).with_load_shedding(LoadSheddingConfig {
+            max_in_flight: Some(1),
+            ..Default::default()
+        });
+        let shedder = service.load_shedder.clone().unwrap();
+        let busy = shedder.try_acquire("test");
+        assert!(busy.is_some());
+
+        let status = service
+            .get_online_features(Request::new(GrpcGetOnlineFeaturesRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_too_many_entity_rows() -> Result<()> {
+        let registry_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../feast-server-core/test_data/registry.pb");
+        let registry = Arc::new(FileFeatureRegistry::from_path(&registry_path)?);
+        let feature_store = FeatureStore::new(registry, Arc::new(InMemoryOnlineStore::new()));
+        let service = FeastGrpcService::new(feature_store).with_max_entity_rows(Some(2));
+        let request = GrpcGetOnlineFeaturesRequest {
+            entities: std::collections::HashMap::from([(
+                "driver_id".to_string(),
+                GrpcRepeatedValue {
+                    val: (1001..1004)
+                        .map(|id| grpc_types::Value {
+                            val: Some(grpc_types::value::Val::Int64Val(id)),
+                        })
+                        .collect(),
+                },
+            )]),
+            ..Default::default()
+        };
+
+        let status = service
+            .get_online_features(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(
+            status.message(),
+            "Request has 3 entity rows, more than the 2 allowed"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unknown_feature_service_is_not_found() -> Result<()> {
+        let registry_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../feast-server-core/test_data/registry.pb");
+        let registry = Arc::new(FileFeatureRegistry::from_path(&registry_path)?);
+        let feature_store = FeatureStore::new(registry, Arc::new(InMemoryOnlineStore::new()));
+        let service = FeastGrpcService::new(feature_store);
+        let request = GrpcGetOnlineFeaturesRequest {
+            kind: Some(get_online_features_request::Kind::FeatureService(
+                "missing_service".to_string(),
+            )),
+            entities: std::collections::HashMap::from([(
+                "driver_id".to_string(),
+                GrpcRepeatedValue {
+                    val: vec![grpc_types::Value {
+                        val: Some(grpc_types::value::Val::Int64Val(1001)),
+                    }],
+                },
+            )]),
+            ..Default::default()
+        };
+
+        let status = service
+            .get_online_features(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn created_timestamps_are_sent_as_metadata() -> Result<()> {
+        let registry_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../feast-server-core/test_data/registry.pb");
+        let registry = Arc::new(FileFeatureRegistry::from_path(&registry_path)?);
+        let online_store = InMemoryOnlineStore::new();
+        let fixture = serde_json::json!([{
+            "feature_view": "driver_hourly_stats",
+            "entities": {"driver_id": 1001},
+            "features": {"acc_rate": 0.5},
+            "event_timestamp": "2025-09-12T14:00:00Z",
+            "created_timestamp": "2025-09-12T14:05:00Z",
+        }]);
+        online_store.load_json(fixture.to_string().as_bytes())?;
+        let service = FeastGrpcService::new(FeatureStore::new(registry, Arc::new(online_store)));
+        let request = || {
+            Request::new(GrpcGetOnlineFeaturesRequest {
+                kind: Some(get_online_features_request::Kind::Features(FeatureList {
+                    val: vec!["driver_hourly_stats:acc_rate".to_string()],
+                })),
+                entities: std::collections::HashMap::from([(
+                    "driver_id".to_string(),
+                    GrpcRepeatedValue {
+                        val: vec![grpc_types::Value {
+                            val: Some(grpc_types::value::Val::Int64Val(1001)),
+                        }],
+                    },
+                )]),
+                ..Default::default()
+            })
+        };
+
+        let response = service.get_online_features(request()).await?;
+        assert!(
+            response
+                .metadata()
+                .get(CREATED_TIMESTAMPS_METADATA_KEY)
+                .is_none()
+        );
+
+        let mut with_created = request();
+        with_created.metadata_mut().insert(
+            INCLUDE_CREATED_TIMESTAMPS_METADATA_KEY,
+            "true".parse().unwrap(),
+        );
+        let response = service.get_online_features(with_created).await?;
+        let created = response
+            .metadata()
+            .get(CREATED_TIMESTAMPS_METADATA_KEY)
+            .unwrap()
+            .to_str()?;
+        let created: serde_json::Value = serde_json::from_str(created)?;
+        assert_eq!(
+            created,
+            serde_json::json!([[null], ["2025-09-12T14:05:00Z"]])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parses_metadata_flags() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert(INCLUDE_STATUSES_METADATA_KEY, "False".parse().unwrap());
+        metadata.insert(INCLUDE_TIMESTAMPS_METADATA_KEY, "no".parse().unwrap());
+        assert_eq!(
+            metadata_flag(&metadata, INCLUDE_STATUSES_METADATA_KEY).unwrap(),
+            Some(false)
+        );
+        assert_eq!(
+            metadata_flag(&metadata, INCLUDE_ENTITY_VALUES_METADATA_KEY).unwrap(),
+            None
+        );
+        let status = metadata_flag(&metadata, INCLUDE_TIMESTAMPS_METADATA_KEY).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
 
     #[test]
     fn converts_repeated_value_to_entities() {
@@ -375,6 +1028,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rejects_values_which_cannot_identify_entities() {
+        let request = |val| GrpcGetOnlineFeaturesRequest {
+            entities: [(
+                "driver_id".to_string(),
+                GrpcRepeatedValue {
+                    val: vec![grpc_types::Value { val }],
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        for val in [
+            None,
+            Some(grpc_types::value::Val::NullVal(0)),
+            Some(grpc_types::value::Val::DoubleVal(f64::NAN)),
+            Some(grpc_types::value::Val::Int64ListVal(GrpcInt64List {
+                val: vec![1001; 100_000],
+            })),
+        ] {
+            let status = FeastGrpcService::from_request_proto(request(val)).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+    }
+
     #[test]
     fn converts_feature_result() {
         let mut result = FeatureResults::default();
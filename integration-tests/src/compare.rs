@@ -0,0 +1,165 @@
+//! Comparison of server responses with the responses of the test repository served from
+//! its SQLite online store.
+
+use crate::fixtures::test_data;
+use crate::servers::{self, RunningServer};
+use anyhow::{Context, Result, anyhow, ensure};
+use feast_client::proto::feast::types::Value as ClientValue;
+use feast_client::proto::feast::types::value::Val as ClientVal;
+use feast_client::{Entities, FeastClient};
+use feast_server_core::config::RepoConfig;
+use feast_server_core::feature_store::FeatureStore;
+use feast_server_core::model::{EntityIdValue, GetOnlineFeaturesRequest};
+use serde_json::Value as JsonValue;
+
+/// Feature store of the test repository configured by `yaml`, with paths relative to
+/// `feast-server-core/test_data`.
+pub async fn feature_store(yaml: &str) -> Result<FeatureStore> {
+    FeatureStore::builder()
+        .with_repo_config(RepoConfig::from_yaml_str(yaml)?)
+        .with_cwd(test_data(""))
+        .build()
+        .await
+}
+
+pub async fn reference_store() -> Result<FeatureStore> {
+    feature_store(
+        r#"
+project: golden_hornet
+registry: registry.pb
+online_store:
+    type: sqlite
+    path: online_store.db
+"#,
+    )
+    .await
+}
+
+/// Requests of the golden compatibility suite. Cases of the entity-less view are left out,
+/// as the suite adds that view to the test repository itself.
+pub fn golden_requests() -> Result<Vec<(String, GetOnlineFeaturesRequest)>> {
+    let mut requests = Vec::new();
+    for entry in std::fs::read_dir(test_data("golden"))? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        if !name.ends_with(".json") || name.starts_with("entity_less") {
+            continue;
+        }
+        let case: JsonValue = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        let request = serde_json::from_value(case["request"].clone())
+            .with_context(|| format!("Invalid golden request {}", name))?;
+        requests.push((name, request));
+    }
+    requests.sort_by(|a, b| a.0.cmp(&b.0));
+    ensure!(!requests.is_empty(), "no golden requests found");
+    Ok(requests)
+}
+
+async fn reference_response(
+    reference: &FeatureStore,
+    request: &GetOnlineFeaturesRequest,
+) -> Result<JsonValue> {
+    let response = reference.get_online_features(request.clone()).await?;
+    // Parsed from text, as `to_value` keeps all digits of float32 values converted to f64
+    Ok(serde_json::from_str(&serde_json::to_string(&response)?)?)
+}
+
+/// Check that the REST server answers the golden requests as the reference store does.
+pub async fn assert_rest_matches_reference(server: &RunningServer) -> Result<()> {
+    let reference = reference_store().await?;
+    for (name, request) in golden_requests()? {
+        let expected = reference_response(&reference, &request).await?;
+        let actual = servers::get_online_features(server, &request)
+            .await
+            .with_context(|| format!("Request of {} failed", name))?;
+        ensure!(
+            actual == expected,
+            "{}: response {} differs from {}",
+            name,
+            actual,
+            expected
+        );
+    }
+    Ok(())
+}
+
+/// Check that the gRPC server answers the golden requests with the values and statuses of
+/// the reference store.
+pub async fn assert_grpc_matches_reference(server: &RunningServer) -> Result<()> {
+    let reference = reference_store().await?;
+    let client = FeastClient::connect(server.url()).await?;
+    for (name, request) in golden_requests()? {
+        let expected = reference_response(&reference, &request).await?;
+        let mut entities = Entities::new();
+        for (entity, values) in &request.entities {
+            entities = entities.with(entity, values.iter().map(client_entity_value));
+        }
+        let client = client
+            .clone()
+            .with_full_feature_names(request.full_feature_names.unwrap_or(false));
+        let features = match (&request.feature_service, &request.features) {
+            (Some(feature_service), _) => {
+                client.get_online_features(feature_service, entities).await
+            }
+            (None, Some(features)) => {
+                client
+                    .get_online_features_by_refs(features.clone(), entities)
+                    .await
+            }
+            (None, None) => return Err(anyhow!("{}: request without features", name)),
+        }
+        .with_context(|| format!("Request of {} failed", name))?;
+
+        let expected_names = expected["metadata"]["feature_names"]
+            .as_array()
+            .ok_or_else(|| anyhow!("{}: response without feature names", name))?;
+        ensure!(
+            features.feature_names() == expected_names.as_slice(),
+            "{}: columns {:?} differ from {:?}",
+            name,
+            features.feature_names(),
+            expected_names
+        );
+        for (column, feature) in features.feature_names().iter().enumerate() {
+            let expected_column = &expected["results"][column];
+            for row in 0..features.len() {
+                let value = client_value_json(features.value(feature, row))?;
+                let status = features.status(feature, row).map(|s| s.as_str_name());
+                ensure!(
+                    value == expected_column["values"][row]
+                        && status == expected_column["statuses"][row].as_str(),
+                    "{}: {} of row {} is {} ({:?}) instead of {} ({})",
+                    name,
+                    feature,
+                    row,
+                    value,
+                    status,
+                    expected_column["values"][row],
+                    expected_column["statuses"][row]
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn client_entity_value(value: &EntityIdValue) -> ClientValue {
+    match value {
+        EntityIdValue::Int(v) => ClientValue::from(*v),
+        EntityIdValue::String(v) => ClientValue::from(v.as_str()),
+    }
+}
+
+/// Value as written in the JSON responses of the REST server.
+fn client_value_json(value: Option<&ClientValue>) -> Result<JsonValue> {
+    Ok(match value.and_then(|value| value.val.as_ref()) {
+        None | Some(ClientVal::NullVal(_)) => JsonValue::Null,
+        Some(ClientVal::Int32Val(v)) => JsonValue::from(*v),
+        Some(ClientVal::Int64Val(v) | ClientVal::UnixTimestampVal(v)) => JsonValue::from(*v),
+        Some(ClientVal::FloatVal(v)) => serde_json::from_str(&serde_json::to_string(v)?)?,
+        Some(ClientVal::DoubleVal(v)) => serde_json::to_value(v)?,
+        Some(ClientVal::StringVal(v)) => JsonValue::from(v.as_str()),
+        Some(ClientVal::BoolVal(v)) => JsonValue::from(*v),
+        Some(other) => return Err(anyhow!("Unexpected value {:?}", other)),
+    })
+}
@@ -0,0 +1,81 @@
+//! Backend containers. Containers are removed when their handle is dropped.
+
+use anyhow::{Result, anyhow};
+use std::time::Duration;
+use testcontainers::core::{IntoContainerPort, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, GenericImage, ImageExt};
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::redis::{REDIS_PORT, Redis};
+
+/// First port of the Redis cluster nodes. Nodes announce their container ports, so they
+/// are published on the same host ports.
+const CLUSTER_INITIAL_PORT: u16 = 7000;
+const CLUSTER_NODES: u16 = 6;
+
+pub struct Backend<I: testcontainers::Image> {
+    pub container: ContainerAsync<I>,
+    /// `host:port` of the service, or comma separated addresses of cluster nodes.
+    pub address: String,
+}
+
+pub async fn start_redis() -> Result<Backend<Redis>> {
+    let container = Redis::default().start().await?;
+    let port = container.get_host_port_ipv4(REDIS_PORT).await?;
+    Ok(Backend {
+        container,
+        address: format!("127.0.0.1:{}", port),
+    })
+}
+
+/// Redis cluster of three primaries and three replicas.
+pub async fn start_redis_cluster() -> Result<Backend<GenericImage>> {
+    let mut image = GenericImage::new("grokzen/redis-cluster", "7.0.10")
+        .with_wait_for(WaitFor::message_on_stdout("Ready to accept connections"))
+        .with_env_var("IP", "0.0.0.0")
+        .with_env_var("INITIAL_PORT", CLUSTER_INITIAL_PORT.to_string());
+    for port in CLUSTER_INITIAL_PORT..CLUSTER_INITIAL_PORT + CLUSTER_NODES {
+        image = image.with_mapped_port(port, port.tcp());
+    }
+    let container = image.start().await?;
+    let address = (CLUSTER_INITIAL_PORT..CLUSTER_INITIAL_PORT + CLUSTER_NODES)
+        .map(|port| format!("127.0.0.1:{}", port))
+        .collect::<Vec<_>>()
+        .join(",");
+    wait_for_cluster(&format!("redis://127.0.0.1:{}", CLUSTER_INITIAL_PORT)).await?;
+    Ok(Backend { container, address })
+}
+
+/// Wait until all slots of the cluster are assigned, which happens after the nodes start.
+async fn wait_for_cluster(url: &str) -> Result<()> {
+    let client = redis::Client::open(url)?;
+    for _ in 0..60 {
+        if let Ok(mut connection) = client.get_multiplexed_async_connection().await
+            && let Ok(info) = redis::cmd("CLUSTER")
+                .arg("INFO")
+                .query_async::<String>(&mut connection)
+                .await
+            && info.contains("cluster_state:ok")
+        {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    Err(anyhow!("Redis cluster at {} did not become ready", url))
+}
+
+/// Postgres with the default `postgres` user, password and database.
+pub async fn start_postgres() -> Result<Backend<Postgres>> {
+    let container = Postgres::default().start().await?;
+    let port = container.get_host_port_ipv4(5432).await?;
+    Ok(Backend {
+        container,
+        address: format!("127.0.0.1:{}", port),
+    })
+}
+
+impl Backend<Postgres> {
+    pub fn connection_string(&self) -> String {
+        format!("postgresql://postgres:postgres@{}/postgres", self.address)
+    }
+}
@@ -0,0 +1,230 @@
+//! Test repository data written in the formats of the Feast Python SDK, independently of the
+//! server code reading them.
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use feast_server_core::feast::core::Registry;
+use prost::Message;
+use sqlx::{Connection, Executor, PgPool, Row, SqliteConnection};
+use std::path::{Path, PathBuf};
+
+/// Project of the test repository.
+pub const PROJECT: &str = "golden_hornet";
+
+pub fn test_data(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../feast-server-core/test_data")
+        .join(relative)
+}
+
+/// Feature value of the test repository as stored by the SQLite online store.
+#[derive(Debug, Clone)]
+pub struct FeatureRow {
+    pub feature_view: String,
+    /// Entity key serialized by Feast, without the project.
+    pub entity_key: Vec<u8>,
+    pub feature_name: String,
+    /// Encoded `feast.types.Value`.
+    pub value: Vec<u8>,
+    pub event_ts: DateTime<Utc>,
+}
+
+/// All feature values of the test repository's SQLite online store.
+pub async fn feature_rows() -> Result<Vec<FeatureRow>> {
+    let path = test_data("online_store.db");
+    let mut connection =
+        SqliteConnection::connect(&format!("sqlite://{}?mode=ro", path.display())).await?;
+    let prefix = format!("{}_", PROJECT);
+    let tables: Vec<String> =
+        sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE ?")
+            .bind(format!("{}%", prefix))
+            .fetch_all(&mut connection)
+            .await?;
+    let mut rows = Vec::new();
+    for table in tables {
+        let feature_view = table[prefix.len()..].to_string();
+        let query = format!(
+            "SELECT entity_key, feature_name, value, event_ts FROM \"{}\"",
+            table
+        );
+        for row in sqlx::query(&query).fetch_all(&mut connection).await? {
+            rows.push(FeatureRow {
+                feature_view: feature_view.clone(),
+                entity_key: row.try_get("entity_key")?,
+                feature_name: row.try_get("feature_name")?,
+                value: row.try_get("value")?,
+                event_ts: row.try_get("event_ts")?,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+/// Write `rows` as the Feast Redis online store does: one hash per entity key and project,
+/// holding each feature under the murmur3 hash of `feature_view:feature` and the event time
+/// of every view under `_ts:<feature_view>`.
+pub async fn load_redis(
+    connection: &mut impl redis::aio::ConnectionLike,
+    rows: &[FeatureRow],
+) -> Result<()> {
+    for row in rows {
+        let key = [row.entity_key.as_slice(), PROJECT.as_bytes()].concat();
+        let feature_key = format!("{}:{}", row.feature_view, row.feature_name);
+        let hashed_key = murmur3::murmur3_32(&mut feature_key.as_bytes(), 0)?;
+        let event_ts = prost_types::Timestamp {
+            seconds: row.event_ts.timestamp(),
+            nanos: row.event_ts.timestamp_subsec_nanos() as i32,
+        };
+        redis::cmd("HSET")
+            .arg(&key)
+            .arg(hashed_key.to_le_bytes().as_slice())
+            .arg(&row.value)
+            .arg(format!("_ts:{}", row.feature_view).as_bytes())
+            .arg(event_ts.encode_to_vec())
+            .query_async::<()>(connection)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Tables of the Feast SQL registry read by the server.
+const SQL_REGISTRY_SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS entities (
+    entity_name VARCHAR(255) NOT NULL,
+    project_id VARCHAR(255) NOT NULL,
+    last_updated_timestamp BIGINT NOT NULL,
+    entity_proto BYTEA NOT NULL,
+    PRIMARY KEY (entity_name, project_id)
+);
+CREATE TABLE IF NOT EXISTS feature_views (
+    feature_view_name VARCHAR(255) NOT NULL,
+    project_id VARCHAR(255) NOT NULL,
+    last_updated_timestamp BIGINT NOT NULL,
+    materialized_intervals BYTEA,
+    feature_view_proto BYTEA NOT NULL,
+    user_metadata BYTEA,
+    PRIMARY KEY (feature_view_name, project_id)
+);
+CREATE TABLE IF NOT EXISTS stream_feature_views (
+    feature_view_name VARCHAR(255) NOT NULL,
+    project_id VARCHAR(255) NOT NULL,
+    last_updated_timestamp BIGINT NOT NULL,
+    feature_view_proto BYTEA NOT NULL,
+    user_metadata BYTEA,
+    PRIMARY KEY (feature_view_name, project_id)
+);
+CREATE TABLE IF NOT EXISTS on_demand_feature_views (
+    feature_view_name VARCHAR(255) NOT NULL,
+    project_id VARCHAR(255) NOT NULL,
+    last_updated_timestamp BIGINT NOT NULL,
+    feature_view_proto BYTEA NOT NULL,
+    user_metadata BYTEA,
+    PRIMARY KEY (feature_view_name, project_id)
+);
+CREATE TABLE IF NOT EXISTS feature_services (
+    feature_service_name VARCHAR(255) NOT NULL,
+    project_id VARCHAR(255) NOT NULL,
+    last_updated_timestamp BIGINT NOT NULL,
+    feature_service_proto BYTEA NOT NULL,
+    PRIMARY KEY (feature_service_name, project_id)
+);
+CREATE TABLE IF NOT EXISTS feast_metadata (
+    project_id VARCHAR(255) NOT NULL,
+    metadata_key VARCHAR(50) NOT NULL,
+    metadata_value VARCHAR(50) NOT NULL,
+    last_updated_timestamp BIGINT NOT NULL,
+    PRIMARY KEY (metadata_key, project_id)
+);
+"#;
+
+/// The test repository's registry.
+pub fn registry() -> Result<Registry> {
+    let bytes = std::fs::read(test_data("registry.pb"))?;
+    Ok(Registry::decode(bytes.as_slice())?)
+}
+
+/// Write `registry` to the tables of the Feast SQL registry, one row per object holding its
+/// encoded proto.
+pub async fn load_sql_registry(pool: &PgPool, registry: &Registry) -> Result<()> {
+    pool.execute(SQL_REGISTRY_SCHEMA).await?;
+    let updated = Utc::now().timestamp();
+    let name = |spec_name: Option<&String>| {
+        spec_name
+            .cloned()
+            .ok_or_else(|| anyhow!("Registry object without spec"))
+    };
+    let mut objects: Vec<(&str, &str, &str, String, Vec<u8>)> = Vec::new();
+    for entity in &registry.entities {
+        let spec_name = name(entity.spec.as_ref().map(|spec| &spec.name))?;
+        objects.push((
+            "entities",
+            "entity_name",
+            "entity_proto",
+            spec_name,
+            entity.encode_to_vec(),
+        ));
+    }
+    for view in &registry.feature_views {
+        let spec_name = name(view.spec.as_ref().map(|spec| &spec.name))?;
+        objects.push((
+            "feature_views",
+            "feature_view_name",
+            "feature_view_proto",
+            spec_name,
+            view.encode_to_vec(),
+        ));
+    }
+    for view in &registry.stream_feature_views {
+        let spec_name = name(view.spec.as_ref().map(|spec| &spec.name))?;
+        objects.push((
+            "stream_feature_views",
+            "feature_view_name",
+            "feature_view_proto",
+            spec_name,
+            view.encode_to_vec(),
+        ));
+    }
+    for view in &registry.on_demand_feature_views {
+        let spec_name = name(view.spec.as_ref().map(|spec| &spec.name))?;
+        objects.push((
+            "on_demand_feature_views",
+            "feature_view_name",
+            "feature_view_proto",
+            spec_name,
+            view.encode_to_vec(),
+        ));
+    }
+    for service in &registry.feature_services {
+        let spec_name = name(service.spec.as_ref().map(|spec| &spec.name))?;
+        objects.push((
+            "feature_services",
+            "feature_service_name",
+            "feature_service_proto",
+            spec_name,
+            service.encode_to_vec(),
+        ));
+    }
+    for (table, name_col, proto_col, object_name, proto) in objects {
+        let query = format!(
+            "INSERT INTO {} ({}, project_id, last_updated_timestamp, {}) VALUES ($1, $2, $3, $4)",
+            table, name_col, proto_col
+        );
+        sqlx::query(&query)
+            .bind(object_name)
+            .bind(PROJECT)
+            .bind(updated)
+            .bind(proto)
+            .execute(pool)
+            .await?;
+    }
+    sqlx::query(
+        "INSERT INTO feast_metadata (project_id, metadata_key, metadata_value, \
+         last_updated_timestamp) VALUES ($1, 'last_updated_timestamp', $2, $3)",
+    )
+    .bind(PROJECT)
+    .bind(updated.to_string())
+    .bind(updated)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,16 @@
+//! Integration tests running the REST and gRPC servers against Redis and Postgres started in
+//! Docker containers with `testcontainers`.
+//!
+//! Backends are filled with the test repository of `feast-server-core/test_data` written in
+//! the formats the Feast Python SDK uses, and responses are compared with those of the same
+//! repository served from its SQLite online store. The tests need a Docker daemon and are
+//! ignored by default:
+//!
+//! ```bash
+//! cargo test -p integration-tests -- --ignored --test-threads 1
+//! ```
+
+pub mod compare;
+pub mod containers;
+pub mod fixtures;
+pub mod servers;
@@ -0,0 +1,96 @@
+//! REST and gRPC servers started on free local ports.
+
+use anyhow::{Result, anyhow};
+use feast_server_core::feature_store::FeatureStore;
+use feast_server_core::model::GetOnlineFeaturesRequest;
+use serde_json::Value as JsonValue;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Server running on a background task, stopped when dropped.
+pub struct RunningServer {
+    pub port: u16,
+    task: JoinHandle<Result<()>>,
+}
+
+impl Drop for RunningServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl RunningServer {
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+}
+
+fn free_port() -> Result<u16> {
+    Ok(std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port())
+}
+
+/// Wait until the server accepts connections, failing if it stopped before.
+async fn wait_until_listening(server: &RunningServer) -> Result<()> {
+    for _ in 0..100 {
+        if server.task.is_finished() {
+            return Err(anyhow!("Server on port {} stopped", server.port));
+        }
+        if tokio::net::TcpStream::connect(("127.0.0.1", server.port))
+            .await
+            .is_ok()
+        {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    Err(anyhow!("Server on port {} is not listening", server.port))
+}
+
+pub async fn start_rest(feature_store: FeatureStore) -> Result<RunningServer> {
+    let port = free_port()?;
+    let config = rest_server::server::ServerConfig {
+        port,
+        ..Default::default()
+    };
+    let task = tokio::spawn(rest_server::server::start_server(
+        config,
+        feature_store,
+        false,
+        rest_server::server::ShutdownHandle::new(),
+    ));
+    let server = RunningServer { port, task };
+    wait_until_listening(&server).await?;
+    Ok(server)
+}
+
+pub async fn start_grpc(feature_store: FeatureStore) -> Result<RunningServer> {
+    let port = free_port()?;
+    let config = grpc_server::server::ServerConfig {
+        port,
+        ..Default::default()
+    };
+    let task = tokio::spawn(grpc_server::server::start_server(config, feature_store));
+    let server = RunningServer { port, task };
+    wait_until_listening(&server).await?;
+    Ok(server)
+}
+
+/// JSON response of `POST /get-online-features`.
+pub async fn get_online_features(
+    server: &RunningServer,
+    request: &GetOnlineFeaturesRequest,
+) -> Result<JsonValue> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/get-online-features", server.url()))
+        .json(request)
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.json::<JsonValue>().await?;
+    if !status.is_success() {
+        return Err(anyhow!("Request failed with {}: {}", status, body));
+    }
+    Ok(body)
+}
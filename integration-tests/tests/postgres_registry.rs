@@ -0,0 +1,31 @@
+use anyhow::Result;
+use integration_tests::compare::{
+    assert_grpc_matches_reference, assert_rest_matches_reference, feature_store,
+};
+use integration_tests::{containers, fixtures, servers};
+
+#[tokio::test]
+#[ignore]
+async fn serve_sql_registry() -> Result<()> {
+    let postgres = containers::start_postgres().await?;
+    let pool = sqlx::PgPool::connect(&postgres.connection_string()).await?;
+    fixtures::load_sql_registry(&pool, &fixtures::registry()?).await?;
+
+    let config = format!(
+        r#"
+project: golden_hornet
+registry:
+    registry_type: sql
+    path: {}
+    cache_ttl_seconds: 60
+online_store:
+    type: sqlite
+    path: online_store.db
+"#,
+        postgres.connection_string()
+    );
+    let rest = servers::start_rest(feature_store(&config).await?).await?;
+    assert_rest_matches_reference(&rest).await?;
+    let grpc = servers::start_grpc(feature_store(&config).await?).await?;
+    assert_grpc_matches_reference(&grpc).await
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use integration_tests::compare::{
+    assert_grpc_matches_reference, assert_rest_matches_reference, feature_store,
+};
+use integration_tests::{containers, fixtures, servers};
+
+fn redis_config(redis_type: &str, connection_string: &str) -> String {
+    format!(
+        r#"
+project: golden_hornet
+registry: registry.pb
+online_store:
+    type: redis
+    redis_type: {}
+    connection_string: "{}"
+entity_key_serialization_version: 3
+"#,
+        redis_type, connection_string
+    )
+}
+
+#[tokio::test]
+#[ignore]
+async fn serve_redis_single_node() -> Result<()> {
+    let redis = containers::start_redis().await?;
+    let client = redis::Client::open(format!("redis://{}", redis.address))?;
+    let mut connection = client.get_multiplexed_async_connection().await?;
+    fixtures::load_redis(&mut connection, &fixtures::feature_rows().await?).await?;
+
+    let config = redis_config("single_node", &redis.address);
+    let rest = servers::start_rest(feature_store(&config).await?).await?;
+    assert_rest_matches_reference(&rest).await?;
+    let grpc = servers::start_grpc(feature_store(&config).await?).await?;
+    assert_grpc_matches_reference(&grpc).await
+}
+
+#[tokio::test]
+#[ignore]
+async fn serve_redis_cluster() -> Result<()> {
+    let cluster = containers::start_redis_cluster().await?;
+    let nodes = cluster
+        .address
+        .split(',')
+        .map(|node| format!("redis://{}", node))
+        .collect::<Vec<_>>();
+    let client = redis::cluster::ClusterClient::new(nodes)?;
+    let mut connection = client.get_async_connection().await?;
+    fixtures::load_redis(&mut connection, &fixtures::feature_rows().await?).await?;
+
+    let config = redis_config("redis_cluster", &cluster.address);
+    let rest = servers::start_rest(feature_store(&config).await?).await?;
+    assert_rest_matches_reference(&rest).await?;
+    let grpc = servers::start_grpc(feature_store(&config).await?).await?;
+    assert_grpc_matches_reference(&grpc).await
+}
@@ -0,0 +1,141 @@
+//! Access control of the admin listener, which serves `/metrics`, `/admin/*` and CPU
+//! profiles apart from the serving port when `runtime.admin_listener` is set.
+
+use crate::server::AppError;
+use anyhow::{Context, Result};
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum_server::tls_rustls::RustlsConfig;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use feast_server_core::config::{AdminTlsConfig, BasicAuthConfig};
+use rustls::RootCertStore;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use std::sync::Arc;
+
+/// `Authorization` header expected from admin clients.
+pub(crate) struct BasicAuth {
+    expected: Vec<u8>,
+}
+
+impl BasicAuth {
+    pub(crate) fn new(config: &BasicAuthConfig) -> Self {
+        let credentials = BASE64.encode(format!("{}:{}", config.username, config.password));
+        Self {
+            expected: format!("Basic {}", credentials).into_bytes(),
+        }
+    }
+
+    fn allows(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get(header::AUTHORIZATION)
+            .is_some_and(|value| constant_time_eq(value.as_bytes(), &self.expected))
+    }
+}
+
+/// Compare without stopping at the first difference, so that response times do not tell
+/// how much of the credentials a guess got right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Answer 401 to requests without the configured credentials.
+pub(crate) async fn require_basic_auth(
+    State(auth): State<Arc<BasicAuth>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if auth.allows(request.headers()) {
+        return next.run(request).await;
+    }
+    let mut response =
+        AppError::new(StatusCode::UNAUTHORIZED, "Missing or invalid credentials").into_response();
+    response.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        HeaderValue::from_static("Basic realm=\"feast-admin\""),
+    );
+    response
+}
+
+/// TLS config of the admin listener. Clients must present a certificate signed by one of
+/// the CAs of `client_ca_path` when it is set.
+pub(crate) fn rustls_config(config: &AdminTlsConfig) -> Result<RustlsConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let certs = read_certs(&config.cert_path)
+        .with_context(|| format!("Failed to read admin TLS certificate {}", config.cert_path))?;
+    let key = PrivateKeyDer::from_pem_file(&config.key_path)
+        .with_context(|| format!("Failed to read admin TLS private key {}", config.key_path))?;
+    let builder = rustls::ServerConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()?;
+    let builder = match &config.client_ca_path {
+        Some(path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in read_certs(path)
+                .with_context(|| format!("Failed to read admin client CA {}", path))?
+            {
+                roots.add(cert)?;
+            }
+            let verifier =
+                WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider).build()?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut server_config = builder.with_single_cert(certs, key)?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(RustlsConfig::from_config(Arc::new(server_config)))
+}
+
+fn read_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    Ok(CertificateDer::pem_file_iter(path)?.collect::<Result<Vec<_>, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_auth_checks_credentials() {
+        let auth = BasicAuth::new(&BasicAuthConfig {
+            username: "prometheus".to_string(),
+            password: "scrape".to_string(),
+        });
+        let headers = |value: &'static str| {
+            HeaderMap::from_iter([(header::AUTHORIZATION, HeaderValue::from_static(value))])
+        };
+        // base64("prometheus:scrape")
+        assert!(auth.allows(&headers("Basic cHJvbWV0aGV1czpzY3JhcGU=")));
+        // base64("prometheus:scrap")
+        assert!(!auth.allows(&headers("Basic cHJvbWV0aGV1czpzY3JhcA==")));
+        assert!(!auth.allows(&headers("Bearer cHJvbWV0aGV1czpzY3JhcGU=")));
+        assert!(!auth.allows(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn tls_config_requires_readable_files() {
+        let certs = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../feast-server-core/test_data/redis_singlenode_tls/certs"
+        );
+        let config = AdminTlsConfig {
+            cert_path: format!("{}/server.crt", certs),
+            key_path: format!("{}/server.key", certs),
+            client_ca_path: Some(format!("{}/root_ca.crt", certs)),
+        };
+        assert!(rustls_config(&config).is_ok());
+
+        let missing_ca = AdminTlsConfig {
+            client_ca_path: Some(format!("{}/missing_ca.crt", certs)),
+            ..config
+        };
+        let err = rustls_config(&missing_ca).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Failed to read admin client CA")
+        );
+    }
+}
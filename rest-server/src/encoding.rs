@@ -0,0 +1,108 @@
+//! Response body encodings negotiated from the `Accept` request header.
+
+use axum::Json;
+use axum::http::header::{ACCEPT, CONTENT_TYPE};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use feast_server_core::feast::serving::GetOnlineFeaturesResponse as GetOnlineFeaturesResponseProto;
+use feast_server_core::model::GetOnlineFeatureResponse;
+use prost::Message;
+
+const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseEncoding {
+    Json,
+    /// Feast serving `GetOnlineFeaturesResponse` proto.
+    Protobuf,
+    /// MessagePack with the same structure as the JSON response.
+    MsgPack,
+}
+
+impl ResponseEncoding {
+    /// Pick the encoding with the highest quality in the `Accept` header. Unknown media
+    /// types and missing headers fall back to JSON.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut best = (ResponseEncoding::Json, 0.0f32);
+        for value in headers.get_all(ACCEPT) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            for media_range in value.split(',') {
+                let mut params = media_range.split(';');
+                let media_type = params.next().unwrap_or_default().trim();
+                let quality = params
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                let encoding = match media_type.to_ascii_lowercase().as_str() {
+                    "application/x-protobuf" | "application/protobuf" => Self::Protobuf,
+                    "application/msgpack" | "application/x-msgpack" => Self::MsgPack,
+                    "application/json" => Self::Json,
+                    _ => continue,
+                };
+                if quality > best.1 {
+                    best = (encoding, quality);
+                }
+            }
+        }
+        best.0
+    }
+
+    pub fn encode(self, response: GetOnlineFeatureResponse) -> Response {
+        match self {
+            Self::Json => Json(response).into_response(),
+            Self::Protobuf => {
+                let body = GetOnlineFeaturesResponseProto::from(response).encode_to_vec();
+                binary_response(PROTOBUF_CONTENT_TYPE, body)
+            }
+            Self::MsgPack => match rmp_serde::to_vec_named(&response) {
+                Ok(body) => binary_response(MSGPACK_CONTENT_TYPE, body),
+                Err(err) => {
+                    tracing::error!("Failed to encode msgpack response: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            },
+        }
+    }
+}
+
+fn binary_response(content_type: &'static str, body: Vec<u8>) -> Response {
+    (
+        [(CONTENT_TYPE, HeaderValue::from_static(content_type))],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(accept: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_str(accept).unwrap());
+        headers
+    }
+
+    #[test]
+    fn negotiate_encoding() {
+        assert_eq!(
+            ResponseEncoding::from_headers(&HeaderMap::new()),
+            ResponseEncoding::Json
+        );
+        assert_eq!(
+            ResponseEncoding::from_headers(&headers("application/x-protobuf")),
+            ResponseEncoding::Protobuf
+        );
+        assert_eq!(
+            ResponseEncoding::from_headers(&headers("application/json;q=0.5, application/msgpack")),
+            ResponseEncoding::MsgPack
+        );
+        assert_eq!(
+            ResponseEncoding::from_headers(&headers("text/html, */*")),
+            ResponseEncoding::Json
+        );
+    }
+}
@@ -1 +1,5 @@
+mod admin;
+mod encoding;
+#[cfg(feature = "pprof")]
+mod pprof;
 pub mod server;
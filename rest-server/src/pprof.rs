@@ -0,0 +1,171 @@
+//! CPU profiles of the running server sampled with pprof-rs, served at
+//! `/debug/pprof/profile` when `runtime.http.pprof` is set.
+
+use crate::server::AppError;
+use axum::extract::Query;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use pprof::protos::Message;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const DEFAULT_SECONDS: u64 = 30;
+const MAX_SECONDS: u64 = 300;
+/// Sampling frequency in Hz, off the 100 Hz of periodic work so that it is not sampled in
+/// lockstep.
+const DEFAULT_FREQUENCY: i32 = 99;
+const MAX_FREQUENCY: i32 = 1000;
+/// Libraries whose frames can't be unwound safely from a signal handler.
+const BLOCKLIST: [&str; 4] = ["libc", "libgcc", "pthread", "vdso"];
+
+/// pprof-rs keeps a single profiler per process.
+static PROFILING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileFormat {
+    /// Flame graph SVG, viewable in a browser.
+    #[default]
+    Flamegraph,
+    /// Uncompressed pprof protobuf, read by `go tool pprof`.
+    Protobuf,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProfileParams {
+    pub seconds: Option<u64>,
+    pub frequency: Option<i32>,
+    pub format: ProfileFormat,
+}
+
+impl ProfileParams {
+    fn duration(&self) -> Result<Duration, AppError> {
+        match self.seconds.unwrap_or(DEFAULT_SECONDS) {
+            seconds @ 1..=MAX_SECONDS => Ok(Duration::from_secs(seconds)),
+            seconds => Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                format!("seconds must be between 1 and {MAX_SECONDS}, got {seconds}"),
+            )),
+        }
+    }
+
+    fn frequency(&self) -> Result<i32, AppError> {
+        match self.frequency.unwrap_or(DEFAULT_FREQUENCY) {
+            frequency @ 1..=MAX_FREQUENCY => Ok(frequency),
+            frequency => Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                format!("frequency must be between 1 and {MAX_FREQUENCY}, got {frequency}"),
+            )),
+        }
+    }
+}
+
+/// Marks the profiler as taken until dropped.
+struct ProfilingSlot;
+
+impl ProfilingSlot {
+    fn acquire() -> Option<Self> {
+        PROFILING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| Self)
+    }
+}
+
+impl Drop for ProfilingSlot {
+    fn drop(&mut self) {
+        PROFILING.store(false, Ordering::Release);
+    }
+}
+
+fn internal_error(err: impl std::fmt::Display) -> AppError {
+    AppError::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+}
+
+/// Sample the CPU for `seconds` and answer with the profile, like Go's
+/// `/debug/pprof/profile`. Requests made while a profile is taken get 409.
+pub async fn handle_profile(Query(params): Query<ProfileParams>) -> Result<Response, AppError> {
+    let duration = params.duration()?;
+    let frequency = params.frequency()?;
+    let _slot = ProfilingSlot::acquire().ok_or_else(|| {
+        AppError::new(StatusCode::CONFLICT, "A CPU profile is already being taken")
+    })?;
+    tracing::info!("Taking a {:?} CPU profile at {} Hz", duration, frequency);
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(frequency)
+        .blocklist(&BLOCKLIST)
+        .build()
+        .map_err(internal_error)?;
+    tokio::time::sleep(duration).await;
+    // Symbolizing the samples reads debug info, so it is kept off the async workers
+    let format = params.format;
+    tokio::task::spawn_blocking(move || {
+        let report = guard.report().build().map_err(internal_error)?;
+        drop(guard);
+        match format {
+            ProfileFormat::Flamegraph => {
+                let mut svg = Vec::new();
+                report.flamegraph(&mut svg).map_err(internal_error)?;
+                Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response())
+            }
+            ProfileFormat::Protobuf => {
+                let profile = report.pprof().map_err(internal_error)?;
+                Ok((
+                    [(header::CONTENT_TYPE, "application/octet-stream")],
+                    profile.encode_to_vec(),
+                )
+                    .into_response())
+            }
+        }
+    })
+    .await
+    .map_err(internal_error)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_params_are_bounded() {
+        let params = ProfileParams::default();
+        assert_eq!(params.duration().ok(), Some(Duration::from_secs(30)));
+        assert_eq!(params.frequency().ok(), Some(99));
+        assert_eq!(params.format, ProfileFormat::Flamegraph);
+
+        let params = ProfileParams {
+            seconds: Some(0),
+            frequency: Some(5000),
+            format: ProfileFormat::Protobuf,
+        };
+        assert!(params.duration().is_err());
+        assert!(params.frequency().is_err());
+    }
+
+    #[tokio::test]
+    async fn profile_is_served_in_both_formats() {
+        // Both checks share the process-wide profiler, so they run in one test
+        let slot = ProfilingSlot::acquire();
+        let conflict = handle_profile(Query(ProfileParams::default())).await;
+        assert!(matches!(conflict, Err(e) if e.status() == StatusCode::CONFLICT));
+        drop(slot);
+
+        for (format, content_type) in [
+            (ProfileFormat::Flamegraph, "image/svg+xml"),
+            (ProfileFormat::Protobuf, "application/octet-stream"),
+        ] {
+            let params = ProfileParams {
+                seconds: Some(1),
+                format,
+                ..Default::default()
+            };
+            let response = handle_profile(Query(params))
+                .await
+                .unwrap_or_else(|_| panic!("{format:?} profile failed"));
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], content_type);
+        }
+    }
+}
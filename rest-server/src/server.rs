@@ -1,46 +1,150 @@
+use crate::admin::{self, BasicAuth, require_basic_auth};
+use crate::encoding::ResponseEncoding;
 use anyhow::{Result, anyhow};
 use axum::{
     Json, Router,
-    extract::{State, rejection::JsonRejection},
-    http::StatusCode,
+    body::HttpBody,
+    extract::{
+        DefaultBodyLimit, MatchedPath, Path, Query, Request, State,
+        rejection::{JsonRejection, QueryRejection},
+    },
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{MethodRouter, get, post},
 };
 use axum_prometheus::PrometheusMetricLayer;
-use axum_server::tls_rustls::RustlsConfig;
+use axum_server::accept::Accept;
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
+use feast_server_core::config::{
+    AdminListenerConfig, HttpServerTuning, LoadSheddingConfig, NanPolicy,
+};
 use feast_server_core::error::FeastCoreError;
 use feast_server_core::feature_store::FeatureStore;
-use feast_server_core::model::GetOnlineFeaturesRequest;
-use serde::Serialize;
-use std::net::SocketAddr;
-use std::net::ToSocketAddrs;
+use feast_server_core::load_shedding::LoadShedder;
+use feast_server_core::model::{
+    DeleteEntityKeysRequest, EntityIdValue, FeatureFreshnessRequest, GetOnlineFeaturesRequest,
+    PushRequest,
+};
+use feast_server_core::payload_sizes;
+use feast_server_core::registry::schema::SchemaFormat;
+use hyper_util::rt::TokioTimer;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use server_listener::{ListenConfig, ServerListener, open_listeners, open_tcp_listeners};
+use std::borrow::Cow;
+use std::future::Ready;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+
+/// Response header carrying the version of the requested feature service.
+pub const FEATURE_SERVICE_VERSION_HEADER: &str = "x-feast-feature-service-version";
+/// Response header carrying the registry version the features were resolved against,
+/// which requests can pin with their `registry_version` field.
+pub const REGISTRY_VERSION_HEADER: &str = "x-feast-registry-version";
+/// Request header selecting the project to read, like the `project` field of the body.
+pub const PROJECT_HEADER: &str = "x-feast-project";
+
+/// Routes whose handlers read a request body, which `max_request_body_bytes` may limit.
+const BODY_ROUTES: [&str; 4] = [
+    "/get-online-features",
+    "/query",
+    "/push",
+    "/admin/delete-entity-keys",
+];
+
+/// Response extension naming the feature service a feature request was answered from.
+#[derive(Clone)]
+struct ServedFeatureService(String);
 
 #[derive(Clone)]
 pub struct FeastServer {
     feature_store: Arc<FeatureStore>,
+    nan_policy: NanPolicy,
+    collapse_requests: bool,
 }
 
 pub struct ServerConfig {
     pub host: String,
+    /// Hosts listened on besides `host`, on the same port.
+    pub additional_hosts: Vec<String>,
+    /// Also listen on the IPv6 counterpart of IPv4 wildcard and loopback hosts, and the
+    /// other way around.
+    pub dual_stack: bool,
     pub port: u16,
     pub tls_enabled: bool,
     pub tls_cert_path: Option<String>,
     pub tls_key_path: Option<String>,
+    /// Maximum number of pending connections in the listen queue.
+    pub backlog: u32,
+    /// Serve on this Unix domain socket instead of `host` and `port`.
+    pub unix_socket_path: Option<String>,
+    /// Keep-alive and HTTP/2 settings of TCP connections.
+    pub tuning: HttpServerTuning,
+    /// Reject feature requests early when the server is overloaded.
+    pub load_shedding: Option<LoadSheddingConfig>,
+    /// Handling of NaN and infinite feature values in JSON and MessagePack responses.
+    pub nan_policy: NanPolicy,
+    /// Serve `/metrics`, `/admin/*` and CPU profiles on this listener instead.
+    pub admin_listener: Option<AdminListenerConfig>,
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
             host: "127.0.0.1".to_string(),
+            additional_hosts: Vec::new(),
+            dual_stack: false,
             port: 6566,
             tls_enabled: false,
             tls_cert_path: None,
             tls_key_path: None,
+            backlog: 1024,
+            unix_socket_path: None,
+            tuning: HttpServerTuning::default(),
+            load_shedding: None,
+            nan_policy: NanPolicy::default(),
+            admin_listener: None,
         }
     }
 }
 
+/// Acceptor applying socket options to accepted TCP connections.
+#[derive(Clone, Copy, Debug)]
+struct TcpAcceptor {
+    nodelay: bool,
+}
+
+impl<S> Accept<TcpStream, S> for TcpAcceptor {
+    type Stream = TcpStream;
+    type Service = S;
+    type Future = Ready<std::io::Result<(TcpStream, S)>>;
+
+    fn accept(&self, stream: TcpStream, service: S) -> Self::Future {
+        std::future::ready(stream.set_nodelay(self.nodelay).map(|_| (stream, service)))
+    }
+}
+
+/// Apply connection tuning to the hyper connection builder of `server`.
+fn configure_http<A>(server: &mut axum_server::Server<A>, tuning: &HttpServerTuning) {
+    let builder = server.http_builder();
+    builder.http1().keep_alive(tuning.http1_keep_alive);
+    let mut http2 = builder.http2();
+    http2
+        .timer(TokioTimer::new())
+        .max_concurrent_streams(tuning.http2_max_concurrent_streams)
+        .keep_alive_interval(
+            tuning
+                .http2_keep_alive_interval_secs
+                .map(Duration::from_secs),
+        );
+    if let Some(timeout) = tuning.http2_keep_alive_timeout_secs {
+        http2.keep_alive_timeout(Duration::from_secs(timeout));
+    }
+}
+
 #[derive(Serialize)]
 struct ErrorResponse {
     message: String,
@@ -52,12 +156,16 @@ pub struct AppError {
 }
 
 impl AppError {
-    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+    pub(crate) fn new(status: StatusCode, message: impl Into<String>) -> Self {
         Self {
             status,
             message: message.into(),
         }
     }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
 }
 
 impl From<JsonRejection> for AppError {
@@ -66,6 +174,30 @@ impl From<JsonRejection> for AppError {
     }
 }
 
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(err: anyhow::Error) -> Self {
+        tracing::error!("{}", err);
+        if let Some(feast_error) = err.downcast_ref::<FeastCoreError>() {
+            if feast_error.is_not_found() {
+                return Self::new(StatusCode::NOT_FOUND, feast_error.to_string());
+            }
+            if feast_error.is_invalid_request() {
+                return Self::new(StatusCode::BAD_REQUEST, feast_error.to_string());
+            }
+            if feast_error.is_unavailable() {
+                return Self::new(StatusCode::SERVICE_UNAVAILABLE, feast_error.to_string());
+            }
+        }
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let status = self.status;
@@ -76,40 +208,204 @@ impl IntoResponse for AppError {
     }
 }
 
+/// Stops the servers of [`start_server`], on TCP and Unix domain sockets alike.
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownHandle {
+    tcp: axum_server::Handle,
+    /// Grace period of a requested shutdown, `Some(None)` for an indefinite one.
+    requested: Arc<tokio::sync::watch::Sender<Option<Option<Duration>>>>,
+}
+
+impl ShutdownHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop accepting connections and close the open ones once their requests are
+    /// answered, or once `duration` elapsed. `None` waits for them indefinitely.
+    pub fn graceful_shutdown(&self, duration: Option<Duration>) {
+        self.tcp.graceful_shutdown(duration);
+        self.requested.send_replace(Some(duration));
+    }
+
+    /// Stop the servers without waiting for open connections.
+    pub fn shutdown(&self) {
+        self.tcp.shutdown();
+        self.requested.send_replace(Some(Some(Duration::ZERO)));
+    }
+
+    /// Wait until a shutdown is requested, returning its grace period.
+    async fn requested(&self) -> Option<Duration> {
+        let mut requested = self.requested.subscribe();
+        let grace_period = requested
+            .wait_for(Option::is_some)
+            .await
+            .map(|grace_period| grace_period.flatten());
+        match grace_period {
+            Ok(grace_period) => grace_period,
+            // The sender lives as long as `self`
+            Err(_) => std::future::pending().await,
+        }
+    }
+
+    /// Wait until the grace period of a requested shutdown elapsed.
+    async fn grace_period_elapsed(&self) {
+        match self.requested().await {
+            Some(grace_period) => tokio::time::sleep(grace_period).await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
 pub async fn start_server(
     server_config: ServerConfig,
-    feature_store: FeatureStore,
+    feature_store: impl Into<Arc<FeatureStore>>,
     metrics_enabled: bool,
-    shutdown_handler: axum_server::Handle,
+    shutdown_handler: ShutdownHandle,
 ) -> Result<()> {
     let server = FeastServer {
-        feature_store: Arc::new(feature_store),
+        feature_store: feature_store.into(),
+        nan_policy: server_config.nan_policy,
+        collapse_requests: server_config.tuning.collapse_identical_requests,
     };
 
-    let mut app = Router::new()
-        .route("/get-online-features", post(handle_feature_request))
-        .route("/health", get(|| async { StatusCode::OK }))
+    let limits = &server_config.tuning.max_request_body_bytes;
+    for route in limits.keys() {
+        if !BODY_ROUTES.contains(&route.as_str()) {
+            tracing::warn!(
+                "Ignoring the request body limit of {}, which is not a route taking a body",
+                route
+            );
+        }
+    }
+    let limit_body = |route: &str, handlers: MethodRouter<FeastServer>| match limits.get(route) {
+        Some(limit) => handlers.layer(DefaultBodyLimit::max(*limit)),
+        None => handlers,
+    };
+    let mut feature_routes = Router::new()
+        .route(
+            "/get-online-features",
+            limit_body("/get-online-features", post(handle_feature_request)),
+        )
+        .route("/query", limit_body("/query", post(handle_query_request)))
+        .route("/push", limit_body("/push", post(handle_push_request)))
+        .route("/feature-freshness", get(handle_freshness_request));
+    if let Some(config) = server_config.load_shedding.clone() {
+        feature_routes = feature_routes.route_layer(middleware::from_fn_with_state(
+            Arc::new(LoadShedder::new(config)),
+            shed_load,
+        ));
+    }
+    let app = feature_routes
+        .route("/metadata", get(handle_metadata))
+        .route("/schema/{feature_service}", get(handle_schema_request))
+        .route("/health", get(handle_health))
+        .with_state(server.clone());
+    let mut admin_app = Router::new()
+        .route(
+            "/admin/stats",
+            get(handle_read_stats).delete(handle_reset_read_stats),
+        )
+        .route("/admin/slow-requests", get(handle_slow_requests))
+        .route("/admin/config", get(handle_effective_config))
+        .route(
+            "/admin/delete-entity-keys",
+            limit_body(
+                "/admin/delete-entity-keys",
+                post(handle_delete_entity_keys_request),
+            ),
+        )
         .with_state(server);
-    let trace = tower_http::trace::TraceLayer::new_for_http();
-    app = app.layer(trace);
+    if server_config.tuning.pprof {
+        admin_app = with_pprof(admin_app);
+    }
+    let with_layers = |app: Router| {
+        app.layer(middleware::from_fn(record_payload_sizes))
+            .layer(tower_http::trace::TraceLayer::new_for_http())
+    };
+    let admin_listener = server_config.admin_listener.as_ref();
+    let (mut app, mut admin_app) = match admin_listener {
+        Some(_) => (with_layers(app), with_layers(admin_app)),
+        None => (with_layers(app.merge(admin_app)), Router::new()),
+    };
     if metrics_enabled {
         let (prometheus_layer, metric_handle) = PrometheusMetricLayer::pair();
-        app = app
-            .route("/metrics", get(|| async move { metric_handle.render() }))
-            .layer(prometheus_layer)
-    }
-
-    let addr: SocketAddr = format!("{}:{}", server_config.host, server_config.port)
-        .to_socket_addrs()?
-        .next()
-        .ok_or(anyhow!("Cannot resolve host"))?;
-
-    tracing::info!(
-        "Server listening on {}:{}",
-        server_config.host,
-        server_config.port
-    );
-    if server_config.tls_enabled {
+        let metrics_route = get(|| async move { metric_handle.render() });
+        match admin_listener {
+            Some(_) => admin_app = admin_app.route("/metrics", metrics_route),
+            None => app = app.route("/metrics", metrics_route),
+        }
+        app = app.layer(prometheus_layer);
+    }
+
+    let mut servers = JoinSet::new();
+    if let Some(admin) = admin_listener {
+        if let Some(auth) = &admin.basic_auth {
+            admin_app = admin_app.layer(middleware::from_fn_with_state(
+                Arc::new(BasicAuth::new(auth)),
+                require_basic_auth,
+            ));
+        }
+        let rustls_config = admin.tls.as_ref().map(admin::rustls_config).transpose()?;
+        let listeners = open_tcp_listeners(&ListenConfig {
+            host: &admin.host,
+            additional_hosts: &[],
+            dual_stack: false,
+            port: admin.port,
+            backlog: server_config.backlog,
+            unix_socket_path: None,
+        })?;
+        for listener in listeners {
+            tracing::info!("Admin endpoints listening on {}", listener.local_addr()?);
+            spawn_server(
+                &mut servers,
+                listener,
+                admin_app.clone(),
+                rustls_config.as_ref(),
+                &server_config.tuning,
+                &shutdown_handler,
+            );
+        }
+    }
+
+    let listeners = open_listeners(&ListenConfig {
+        host: &server_config.host,
+        additional_hosts: &server_config.additional_hosts,
+        dual_stack: server_config.dual_stack,
+        port: server_config.port,
+        backlog: server_config.backlog,
+        unix_socket_path: server_config.unix_socket_path.as_deref(),
+    })?;
+    let mut tcp_listeners = Vec::with_capacity(listeners.len());
+    for listener in listeners {
+        match listener {
+            ServerListener::Tcp(listener) => tcp_listeners.push(listener),
+            #[cfg(unix)]
+            ServerListener::Unix(listener) => {
+                if server_config.tls_enabled {
+                    return Err(anyhow!("TLS is not supported on Unix domain sockets"));
+                }
+                tracing::info!("Server listening on {:?}", listener.local_addr()?);
+                let listener = tokio::net::UnixListener::from_std(listener)?;
+                let shutdown = shutdown_handler.clone();
+                servers.spawn(async move {
+                    let serve = axum::serve(listener, app).with_graceful_shutdown({
+                        let shutdown = shutdown.clone();
+                        async move {
+                            shutdown.requested().await;
+                        }
+                    });
+                    // Connections still open after the grace period are dropped
+                    tokio::select! {
+                        served = serve.into_future() => served,
+                        _ = shutdown.grace_period_elapsed() => Ok(()),
+                    }
+                });
+                return join_servers(servers, &shutdown_handler).await;
+            }
+        }
+    }
+    let rustls_config = if server_config.tls_enabled {
         let cert_path = server_config
             .tls_cert_path
             .ok_or(anyhow!("TLS is enabled but cert path is not provided"))?;
@@ -119,38 +415,436 @@ pub async fn start_server(
         let rustls_config = RustlsConfig::from_pem_file(cert_path, key_path)
             .await
             .map_err(|e| anyhow!("Failed to load TLS config: {}", e))?;
-        axum_server::bind_rustls(addr, rustls_config)
-            .handle(shutdown_handler)
-            .serve(app.into_make_service())
-            .await?;
-        Ok(())
+        Some(rustls_config)
     } else {
-        axum_server::bind(addr)
-            .handle(shutdown_handler)
-            .serve(app.into_make_service())
-            .await?;
-        Ok(())
+        None
+    };
+    // One server per address, all stopped through the same handle
+    for listener in tcp_listeners {
+        tracing::info!("Server listening on {}", listener.local_addr()?);
+        spawn_server(
+            &mut servers,
+            listener,
+            app.clone(),
+            rustls_config.as_ref(),
+            &server_config.tuning,
+            &shutdown_handler,
+        );
+    }
+    join_servers(servers, &shutdown_handler).await
+}
+
+/// Serve `app` on `listener` until `handle` shuts it down.
+fn spawn_server(
+    servers: &mut JoinSet<std::io::Result<()>>,
+    listener: std::net::TcpListener,
+    app: Router,
+    rustls_config: Option<&RustlsConfig>,
+    tuning: &HttpServerTuning,
+    handle: &ShutdownHandle,
+) {
+    let acceptor = TcpAcceptor {
+        nodelay: tuning.tcp_nodelay,
+    };
+    let service = app.into_make_service();
+    let handle = handle.tcp.clone();
+    match rustls_config {
+        Some(rustls_config) => {
+            let mut server = axum_server::from_tcp(listener)
+                .acceptor(RustlsAcceptor::new(rustls_config.clone()).acceptor(acceptor));
+            configure_http(&mut server, tuning);
+            servers.spawn(async move { server.handle(handle).serve(service).await });
+        }
+        None => {
+            let mut server = axum_server::from_tcp(listener).acceptor(acceptor);
+            configure_http(&mut server, tuning);
+            servers.spawn(async move { server.handle(handle).serve(service).await });
+        }
+    }
+}
+
+/// Wait for every server, failing with the first error once the other servers are stopped.
+async fn join_servers(
+    mut servers: JoinSet<std::io::Result<()>>,
+    shutdown: &ShutdownHandle,
+) -> Result<()> {
+    while let Some(served) = servers.join_next().await {
+        if let Err(err) = served
+            .map_err(anyhow::Error::from)
+            .and_then(|served| Ok(served?))
+        {
+            shutdown.shutdown();
+            while servers.join_next().await.is_some() {}
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "pprof")]
+fn with_pprof(app: Router) -> Router {
+    tracing::warn!("CPU profiles are served at /debug/pprof/profile");
+    app.route("/debug/pprof/profile", get(crate::pprof::handle_profile))
+}
+
+#[cfg(not(feature = "pprof"))]
+fn with_pprof(app: Router) -> Router {
+    tracing::warn!("runtime.http.pprof is set but the server was built without the pprof feature");
+    app
+}
+
+/// Answer 503 without running the handler when the load shedder rejects the request.
+async fn shed_load(
+    State(shedder): State<Arc<LoadShedder>>,
+    matched_path: MatchedPath,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(_in_flight) = shedder.try_acquire(matched_path.as_str()) else {
+        return AppError::new(StatusCode::SERVICE_UNAVAILABLE, "Server is overloaded")
+            .into_response();
+    };
+    next.run(request).await
+}
+
+/// Record the body sizes of requests and responses per route, and per feature service for
+/// feature requests, see [`payload_sizes`]. Streamed bodies of unknown size are skipped.
+async fn record_payload_sizes(request: Request, next: Next) -> Response {
+    let route = request.extensions().get::<MatchedPath>().cloned();
+    let request_bytes = request.body().size_hint().exact();
+    let response = next.run(request).await;
+    if let (Some(route), Some(response_bytes)) = (route, response.body().size_hint().exact()) {
+        let feature_service = response.extensions().get::<ServedFeatureService>();
+        payload_sizes::record(
+            route.as_str(),
+            feature_service.map(|service| service.0.as_str()),
+            request_bytes,
+            response_bytes,
+        );
     }
+    response
 }
 
 async fn handle_feature_request(
     State(server): State<FeastServer>,
+    headers: HeaderMap,
     payload: Result<Json<GetOnlineFeaturesRequest>, JsonRejection>,
 ) -> Result<impl IntoResponse, AppError> {
-    let Json(get_online_feature_request) = payload?;
+    let Json(mut get_online_feature_request) = payload?;
+    let encoding = ResponseEncoding::from_headers(&headers);
+    if let Some(project) = headers.get(PROJECT_HEADER) {
+        let project = project.to_str().map_err(|_| {
+            AppError::new(
+                StatusCode::BAD_REQUEST,
+                format!("{} must be a valid string", PROJECT_HEADER),
+            )
+        })?;
+        match &get_online_feature_request.project {
+            Some(requested) if requested != project => {
+                return Err(AppError::new(
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Project {} of the {} header differs from project {} of the body",
+                        project, PROJECT_HEADER, requested
+                    ),
+                ));
+            }
+            _ => get_online_feature_request.project = Some(project.to_string()),
+        }
+    }
+
+    let feature_service = get_online_feature_request.feature_service.clone();
+    let response = if server.collapse_requests {
+        server
+            .feature_store
+            .get_online_features_collapsed(get_online_feature_request)
+            .await?
+    } else {
+        server
+            .feature_store
+            .get_online_features(get_online_feature_request)
+            .await?
+    };
+    if server.nan_policy == NanPolicy::Error && encoding != ResponseEncoding::Protobuf {
+        response.check_finite()?;
+    }
+    let version = response.feature_service_version.clone();
+    let registry_version = response.registry_version;
+    let mut response = encoding.encode(response).into_response();
+    if let Some(version) = version.and_then(|version| HeaderValue::from_str(&version).ok()) {
+        response
+            .headers_mut()
+            .insert(FEATURE_SERVICE_VERSION_HEADER, version);
+    }
+    if let Some(version) = registry_version {
+        response
+            .headers_mut()
+            .insert(REGISTRY_VERSION_HEADER, HeaderValue::from(version));
+    }
+    if let Some(feature_service) = feature_service {
+        response
+            .extensions_mut()
+            .insert(ServedFeatureService(feature_service));
+    }
+    Ok(response)
+}
+
+/// Experimental: features selected by a SQL-like query sent as the plain text body.
+async fn handle_query_request(
+    State(server): State<FeastServer>,
+    headers: HeaderMap,
+    query: String,
+) -> Result<impl IntoResponse, AppError> {
+    let request = feast_server_core::query::parse_query(&query)
+        .map_err(|err| AppError::new(StatusCode::BAD_REQUEST, err.to_string()))?;
+    handle_feature_request(State(server), headers, Ok(Json(request))).await
+}
+
+async fn handle_push_request(
+    State(server): State<FeastServer>,
+    payload: Result<Json<PushRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(push_request) = payload?;
+
+    server.feature_store.push(push_request).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn handle_delete_entity_keys_request(
+    State(server): State<FeastServer>,
+    payload: Result<Json<DeleteEntityKeysRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(delete_request) = payload?;
+    if delete_request.entities.is_empty() {
+        return Err(AppError::new(
+            StatusCode::BAD_REQUEST,
+            "Missing entity keys to delete",
+        ));
+    }
+
+    server
+        .feature_store
+        .delete_entity_keys(delete_request)
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SchemaParams {
+    format: SchemaFormat,
+    full_feature_names: bool,
+}
+
+/// Handle `GET /schema/<feature service>?format=json-schema|proto&full_feature_names=true`.
+async fn handle_schema_request(
+    State(server): State<FeastServer>,
+    Path(feature_service): Path<String>,
+    query: Result<Query<SchemaParams>, QueryRejection>,
+) -> Result<Response, AppError> {
+    let Query(params) = query?;
+    let schema = server
+        .feature_store
+        .response_schema(&feature_service, params.full_feature_names)
+        .await?;
+    Ok(match params.format {
+        SchemaFormat::JsonSchema => (
+            [(header::CONTENT_TYPE, "application/schema+json")],
+            Json(schema.to_json_schema()),
+        )
+            .into_response(),
+        SchemaFormat::Proto => schema.to_proto().into_response(),
+    })
+}
+
+/// Handle `GET /feature-freshness?feature_view=<name>&entity=<key>:<value>[,<key>:<value>]...`.
+/// Every `entity` parameter describes one entity row; composite keys list all join keys.
+/// Keys and values are percent-decoded once more after splitting, so a value containing
+/// `,` is sent as `%252C`.
+async fn handle_freshness_request(
+    State(server): State<FeastServer>,
+    query: Result<Query<Vec<(String, String)>>, QueryRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Query(params) = query?;
+    let freshness_request = parse_freshness_query(params)
+        .map_err(|err| AppError::new(StatusCode::BAD_REQUEST, err.to_string()))?;
 
     server
         .feature_store
-        .get_online_features(get_online_feature_request)
+        .get_feature_freshness(freshness_request)
         .await
         .map(Json)
-        .map_err(|err| {
-            tracing::error!("{}", err);
-            if let Some(feast_error) = err.downcast_ref::<FeastCoreError>()
-                && feast_error.is_not_found()
-            {
-                return AppError::new(StatusCode::NOT_FOUND, feast_error.to_string());
+        .map_err(AppError::from)
+}
+
+fn parse_freshness_query(params: Vec<(String, String)>) -> Result<FeatureFreshnessRequest> {
+    let mut request = FeatureFreshnessRequest::default();
+    for (name, value) in params {
+        match name.as_str() {
+            "feature_view" => request.feature_view = value,
+            "entity" => {
+                for pair in value.split(',') {
+                    let (join_key, id) = pair
+                        .split_once(':')
+                        .ok_or_else(|| anyhow!("Entity must be in key:value format: {}", pair))?;
+                    let join_key = decode_entity_part(join_key)?;
+                    let id = decode_entity_part(id)?;
+                    let id = id
+                        .parse::<i64>()
+                        .map(EntityIdValue::Int)
+                        .unwrap_or(EntityIdValue::String(id));
+                    request.entities.entry(join_key).or_default().push(id);
+                }
             }
-            AppError::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+            other => return Err(anyhow!("Unknown query parameter: {}", other)),
+        }
+    }
+    if request.feature_view.is_empty() {
+        return Err(anyhow!("Missing feature_view query parameter"));
+    }
+    Ok(request)
+}
+
+fn decode_entity_part(part: &str) -> Result<String> {
+    percent_decode_str(part)
+        .decode_utf8()
+        .map(Cow::into_owned)
+        .map_err(|_| anyhow!("Entity is not valid UTF-8 once decoded: {}", part))
+}
+
+async fn handle_health(State(server): State<FeastServer>) -> Response {
+    match server.feature_store.check_required_tables().await {
+        // The registry loaded last is still served, so a failing refresh keeps the server ready
+        Ok(()) => match server.feature_store.registry_refresh_failure() {
+            Some(failure) if failure.credentials => (
+                StatusCode::OK,
+                format!(
+                    "Serving a stale registry, credentials rejected since {}: {}",
+                    failure.since, failure.message
+                ),
+            )
+                .into_response(),
+            Some(failure) => (
+                StatusCode::OK,
+                format!(
+                    "Serving a stale registry, refresh failing since {}: {}",
+                    failure.since, failure.message
+                ),
+            )
+                .into_response(),
+            None => StatusCode::OK.into_response(),
+        },
+        Err(err) => {
+            tracing::warn!("Health check failed: {:#}", err);
+            (StatusCode::SERVICE_UNAVAILABLE, format!("{:#}", err)).into_response()
+        }
+    }
+}
+
+async fn handle_metadata(State(server): State<FeastServer>) -> Result<impl IntoResponse, AppError> {
+    server
+        .feature_store
+        .metadata()
+        .await
+        .map(Json)
+        .map_err(AppError::from)
+}
+
+async fn handle_read_stats(State(server): State<FeastServer>) -> impl IntoResponse {
+    Json(server.feature_store.read_stats())
+}
+
+async fn handle_slow_requests(State(server): State<FeastServer>) -> impl IntoResponse {
+    Json(server.feature_store.slow_requests())
+}
+
+async fn handle_effective_config(
+    State(server): State<FeastServer>,
+) -> Result<impl IntoResponse, AppError> {
+    server
+        .feature_store
+        .effective_config()
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| {
+            AppError::new(
+                StatusCode::NOT_FOUND,
+                "The feature store was built without a config",
+            )
         })
 }
+
+async fn handle_reset_read_stats(State(server): State<FeastServer>) -> impl IntoResponse {
+    server.feature_store.reset_read_stats();
+    StatusCode::NO_CONTENT
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use feast_server_core::onlinestore::in_memory::InMemoryOnlineStore;
+    use feast_server_core::registry::FileFeatureRegistry;
+
+    #[test]
+    fn freshness_entities_keep_escaped_separators() -> Result<()> {
+        let params = vec![
+            (
+                "feature_view".to_string(),
+                "driver_hourly_stats".to_string(),
+            ),
+            ("entity".to_string(), "driver_id:1001".to_string()),
+            // `a,b:c` escaped once for the query and once for the entity list
+            (
+                "entity".to_string(),
+                "driver_id:a%2Cb:c,city:NYC".to_string(),
+            ),
+        ];
+        let request = parse_freshness_query(params)?;
+        assert_eq!(request.feature_view, "driver_hourly_stats");
+        assert_eq!(
+            request.entities["driver_id"],
+            [
+                EntityIdValue::Int(1001),
+                EntityIdValue::String("a,b:c".to_string())
+            ]
+        );
+        assert_eq!(
+            request.entities["city"],
+            [EntityIdValue::String("NYC".to_string())]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unix_socket_server_stops_on_shutdown() -> Result<()> {
+        let registry = FileFeatureRegistry::from_path(std::path::Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../feast-server-core/test_data/registry.pb"
+        )))?;
+        let feature_store =
+            FeatureStore::new(Arc::new(registry), Arc::new(InMemoryOnlineStore::new()));
+        let dir = tempfile::tempdir()?;
+        let socket = dir.path().join("feast-rest.sock");
+        let server_config = ServerConfig {
+            unix_socket_path: Some(socket.display().to_string()),
+            ..Default::default()
+        };
+        let shutdown = ShutdownHandle::new();
+        let server = tokio::spawn(start_server(
+            server_config,
+            feature_store,
+            false,
+            shutdown.clone(),
+        ));
+        while !socket.exists() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // An idle connection does not hold the server up past the grace period
+        let _connection = tokio::net::UnixStream::connect(&socket).await?;
+
+        shutdown.graceful_shutdown(Some(Duration::from_millis(100)));
+        tokio::time::timeout(Duration::from_secs(5), server).await???;
+        Ok(())
+    }
+}
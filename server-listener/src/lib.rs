@@ -0,0 +1,286 @@
+//! Listening socket setup shared by the REST and gRPC servers.
+//! Supports TCP on one or more addresses with a configurable backlog, Unix domain sockets
+//! and sockets passed by systemd socket activation.
+
+use anyhow::{Context, Result, anyhow};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+#[cfg(unix)]
+use std::sync::OnceLock;
+
+/// First file descriptor passed by systemd, see `sd_listen_fds(3)`.
+#[cfg(unix)]
+const SD_LISTEN_FDS_START: i32 = 3;
+
+#[derive(Debug)]
+pub enum ServerListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+/// Where a server should accept connections. Sockets inherited from systemd take
+/// precedence over `unix_socket_path`, which takes precedence over the hosts and `port`.
+#[derive(Debug, Clone)]
+pub struct ListenConfig<'a> {
+    pub host: &'a str,
+    /// Hosts listened on besides `host`, on the same port.
+    pub additional_hosts: &'a [String],
+    /// Also listen on the IPv6 counterpart of IPv4 wildcard and loopback hosts, and the
+    /// other way around.
+    pub dual_stack: bool,
+    pub port: u16,
+    pub backlog: u32,
+    pub unix_socket_path: Option<&'a str>,
+}
+
+/// Open the listeners described by `config`: one per TCP address, or a single Unix or
+/// systemd socket. Returned listeners are in non-blocking mode.
+pub fn open_listeners(config: &ListenConfig) -> Result<Vec<ServerListener>> {
+    #[cfg(unix)]
+    {
+        if let Some(listener) = systemd_listener()? {
+            tracing::info!("Using socket passed by systemd");
+            return Ok(vec![listener]);
+        }
+        if let Some(path) = config.unix_socket_path {
+            return Ok(vec![ServerListener::Unix(bind_unix(path)?)]);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        if config.unix_socket_path.is_some() {
+            return Err(anyhow!(
+                "Unix domain sockets are not supported on this platform"
+            ));
+        }
+    }
+
+    Ok(open_tcp_listeners(config)?
+        .into_iter()
+        .map(ServerListener::Tcp)
+        .collect())
+}
+
+/// Open a TCP listener per address of `config`, ignoring its Unix socket and sockets passed
+/// by systemd, e.g. for a listener besides the main one. Returned listeners are in
+/// non-blocking mode.
+pub fn open_tcp_listeners(config: &ListenConfig) -> Result<Vec<TcpListener>> {
+    let addrs = listen_addrs(config)?;
+    // IPv6 wildcard sockets accept IPv4 connections too on most systems, which would
+    // conflict with a separate IPv4 listener on the same port.
+    let only_v6 = addrs.len() > 1;
+    let mut port = config.port;
+    let mut listeners = Vec::with_capacity(addrs.len());
+    for mut addr in addrs {
+        // An ephemeral port is picked once and shared by all addresses.
+        addr.set_port(port);
+        let listener = bind_tcp(addr, config.backlog, only_v6)?;
+        port = listener.local_addr()?.port();
+        listeners.push(listener);
+    }
+    Ok(listeners)
+}
+
+/// Addresses of the hosts of `config`, without duplicates.
+fn listen_addrs(config: &ListenConfig) -> Result<Vec<SocketAddr>> {
+    let hosts =
+        std::iter::once(config.host).chain(config.additional_hosts.iter().map(String::as_str));
+    let mut addrs: Vec<SocketAddr> = Vec::new();
+    for host in hosts {
+        let addr: SocketAddr = format!("{}:{}", host, config.port)
+            .to_socket_addrs()
+            .with_context(|| format!("Cannot resolve host {}", host))?
+            .next()
+            .ok_or_else(|| anyhow!("Cannot resolve host {}", host))?;
+        let counterpart = config
+            .dual_stack
+            .then(|| other_family(addr.ip()))
+            .flatten()
+            .map(|ip| SocketAddr::new(ip, config.port));
+        for addr in std::iter::once(addr).chain(counterpart) {
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
+    }
+    Ok(addrs)
+}
+
+/// Wildcard or loopback address of the other IP family.
+fn other_family(ip: IpAddr) -> Option<IpAddr> {
+    match ip {
+        IpAddr::V4(ip) if ip.is_unspecified() => Some(Ipv6Addr::UNSPECIFIED.into()),
+        IpAddr::V4(ip) if ip.is_loopback() => Some(Ipv6Addr::LOCALHOST.into()),
+        IpAddr::V6(ip) if ip.is_unspecified() => Some(Ipv4Addr::UNSPECIFIED.into()),
+        IpAddr::V6(ip) if ip.is_loopback() => Some(Ipv4Addr::LOCALHOST.into()),
+        _ => None,
+    }
+}
+
+fn bind_tcp(addr: SocketAddr, backlog: u32, only_v6: bool) -> Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_reuse_address(true)?;
+    if addr.is_ipv6() && only_v6 {
+        socket.set_only_v6(true)?;
+    }
+    socket
+        .bind(&addr.into())
+        .with_context(|| format!("Failed to bind {}", addr))?;
+    socket.listen(backlog.try_into().unwrap_or(i32::MAX))?;
+    socket.set_nonblocking(true)?;
+    Ok(socket.into())
+}
+
+/// Bind a Unix domain socket, replacing a stale socket file left by a previous run.
+#[cfg(unix)]
+fn bind_unix(path: &str) -> Result<UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(anyhow!("{} exists and is not a socket", path));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener =
+        UnixListener::bind(path).with_context(|| format!("Failed to bind socket {}", path))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+/// `LISTEN_FDS` when `LISTEN_PID` matches this process, read once.
+#[cfg(unix)]
+static LISTEN_FDS: OnceLock<Option<String>> = OnceLock::new();
+
+/// Read the sockets passed by systemd socket activation and unset `LISTEN_PID`, `LISTEN_FDS`
+/// and `LISTEN_FDNAMES`, like `sd_listen_fds(1)` does, so that child processes do not take
+/// the sockets for theirs.
+///
+/// # Safety
+///
+/// Changes the environment, so no other thread may run yet.
+pub unsafe fn take_systemd_env() {
+    #[cfg(unix)]
+    {
+        LISTEN_FDS.get_or_init(listen_fds_of_process);
+        // SAFETY: the caller guarantees that no other thread runs.
+        unsafe {
+            std::env::remove_var("LISTEN_PID");
+            std::env::remove_var("LISTEN_FDS");
+            std::env::remove_var("LISTEN_FDNAMES");
+        }
+    }
+}
+
+#[cfg(unix)]
+fn listen_fds_of_process() -> Option<String> {
+    let pid = std::env::var("LISTEN_PID").ok()?;
+    let fds = std::env::var("LISTEN_FDS").ok()?;
+    (pid.parse::<u32>().ok() == Some(std::process::id())).then_some(fds)
+}
+
+/// Take the first socket passed through `LISTEN_FDS` when `LISTEN_PID` matches this process.
+#[cfg(unix)]
+fn systemd_listener() -> Result<Option<ServerListener>> {
+    use std::os::fd::{FromRawFd, IntoRawFd};
+
+    let Some(fds) = LISTEN_FDS.get_or_init(listen_fds_of_process) else {
+        return Ok(None);
+    };
+    let fds: u32 = fds
+        .parse()
+        .map_err(|_| anyhow!("Invalid LISTEN_FDS value: {}", fds))?;
+    if fds == 0 {
+        return Ok(None);
+    }
+    if fds > 1 {
+        tracing::warn!("systemd passed {} sockets, only the first one is used", fds);
+    }
+
+    // SAFETY: systemd hands over ownership of descriptors starting at SD_LISTEN_FDS_START
+    // and nothing else in the process uses them.
+    let unix = unsafe { UnixListener::from_raw_fd(SD_LISTEN_FDS_START) };
+    let listener = if unix.local_addr().is_ok() {
+        unix.set_nonblocking(true)?;
+        ServerListener::Unix(unix)
+    } else {
+        let fd = unix.into_raw_fd();
+        // SAFETY: the descriptor was released by the Unix listener above.
+        let tcp = unsafe { TcpListener::from_raw_fd(fd) };
+        tcp.local_addr()
+            .context("Socket passed by systemd is neither a TCP nor a Unix socket")?;
+        tcp.set_nonblocking(true)?;
+        ServerListener::Tcp(tcp)
+    };
+    Ok(Some(listener))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn bind_unix_socket_replaces_stale_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("feast_listener.sock");
+        let path_str = path.to_str().unwrap();
+        let config = ListenConfig {
+            host: "127.0.0.1",
+            additional_hosts: &[],
+            dual_stack: false,
+            port: 0,
+            backlog: 16,
+            unix_socket_path: Some(path_str),
+        };
+        drop(open_listeners(&config)?);
+        let listeners = open_listeners(&config)?;
+        assert!(matches!(listeners[..], [ServerListener::Unix(_)]));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn all_hosts_share_the_picked_port() -> Result<()> {
+        let additional_hosts = ["127.0.0.2".to_string(), "127.0.0.1".to_string()];
+        let config = ListenConfig {
+            host: "127.0.0.1",
+            additional_hosts: &additional_hosts,
+            dual_stack: false,
+            port: 0,
+            backlog: 16,
+            unix_socket_path: None,
+        };
+        let addrs: Vec<_> = open_listeners(&config)?
+            .iter()
+            .map(|listener| match listener {
+                ServerListener::Tcp(listener) => listener.local_addr().unwrap(),
+                ServerListener::Unix(_) => panic!("Unix listener for TCP hosts"),
+            })
+            .collect();
+        assert_eq!(addrs.len(), 2);
+        assert_eq!(addrs[0].ip().to_string(), "127.0.0.1");
+        assert_eq!(addrs[1].ip().to_string(), "127.0.0.2");
+        assert_eq!(addrs[0].port(), addrs[1].port());
+        Ok(())
+    }
+
+    #[test]
+    fn dual_stack_adds_other_family() -> Result<()> {
+        let config = ListenConfig {
+            host: "0.0.0.0",
+            additional_hosts: &["::".to_string(), "10.0.0.1".to_string()],
+            dual_stack: true,
+            port: 6566,
+            backlog: 16,
+            unix_socket_path: None,
+        };
+        let addrs: Vec<_> = listen_addrs(&config)?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(addrs, ["0.0.0.0:6566", "[::]:6566", "10.0.0.1:6566"]);
+        Ok(())
+    }
+}